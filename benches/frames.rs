@@ -0,0 +1,49 @@
+//! Benchmarks of the layout and drawing hot paths, on the host with criterion.
+//!
+//! Run with `cargo bench`. The frames are drawn into a framebuffer in memory, see [scenes].
+
+mod scenes;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use kolibri_embedded_gui::text::{measure_lines, Font, TextRenderer};
+use scenes::{style, Framebuffer, LabelScene, PARAGRAPH};
+
+fn labels(c: &mut Criterion) {
+    let mut display = Framebuffer::new();
+    let mut group = c.benchmark_group("labels");
+
+    // everything, on a fresh screen
+    group.bench_function("first frame", |b| {
+        b.iter(|| LabelScene::new().frame(&mut display, true))
+    });
+
+    // nothing changed, so nothing is drawn
+    let mut scene = LabelScene::new();
+    scene.frame(&mut display, true);
+    group.bench_function("unchanged", |b| b.iter(|| scene.frame(&mut display, false)));
+
+    // five of the labels change every frame
+    group.bench_function("10% changed", |b| {
+        b.iter(|| {
+            scene.change_tenth();
+            scene.frame(&mut display, false)
+        })
+    });
+    group.finish();
+}
+
+fn text(c: &mut Criterion) {
+    let style = style();
+    let font = Font::resolve(None, &style);
+    let mut group = c.benchmark_group("text");
+    group.bench_function("measure label", |b| {
+        b.iter(|| font.measure(black_box("Value 12:  345")))
+    });
+    group.bench_function("measure wrapped paragraph", |b| {
+        b.iter(|| measure_lines(&font, black_box(PARAGRAPH), Some(200)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, labels, text);
+criterion_main!(benches);
@@ -0,0 +1,119 @@
+//! # Benchmark scenes
+//!
+//! Representative frames for the benchmarks in `benches/frames.rs`, rendered into an in-memory
+//! [Framebuffer], so that the numbers don't include a simulator window or a display bus.
+//!
+//! The scenes are shared with `tests/perf_counters.rs`, which checks their performance counters
+//! (e.g. that an unchanged frame draws no pixels) in plain `cargo test`.
+
+#![allow(dead_code)]
+
+use core::convert::Infallible;
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use kolibri_embedded_gui::format::Buf;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::smartstate::SmartstateProvider;
+use kolibri_embedded_gui::style::{medsize_rgb565_style, Style};
+use kolibri_embedded_gui::ui::{PerfStats, Ui};
+
+/// Number of labels in the label scenes
+pub const LABELS: usize = 50;
+
+/// Number of labels per row
+const COLUMNS: usize = 5;
+
+/// A framebuffer in memory, as a draw target for the scenes
+pub struct Framebuffer {
+    size: Size,
+    pixels: Vec<Rgb565>,
+}
+
+impl Framebuffer {
+    /// Creates a black framebuffer of 480x320 pixels.
+    pub fn new() -> Self {
+        let size = Size::new(480, 320);
+        Self {
+            size,
+            pixels: vec![Rgb565::BLACK; (size.width * size.height) as usize],
+        }
+    }
+}
+
+impl OriginDimensions for Framebuffer {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl DrawTarget for Framebuffer {
+    type Color = Rgb565;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (width, height) = (self.size.width as i32, self.size.height as i32);
+        for Pixel(point, color) in pixels {
+            if (0..width).contains(&point.x) && (0..height).contains(&point.y) {
+                self.pixels[(point.y * width + point.x) as usize] = color;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The style of the scenes: the default one, with a smaller font to fit the labels
+pub fn style() -> Style<Rgb565> {
+    let mut style = medsize_rgb565_style();
+    style.default_font = ascii::FONT_6X10;
+    style
+}
+
+/// A grid of [LABELS] labels showing values, each with a smartstate
+pub struct LabelScene {
+    pub smartstates: SmartstateProvider<LABELS>,
+    pub values: [u32; LABELS],
+}
+
+impl LabelScene {
+    /// Creates the scene, which wasn't drawn yet.
+    pub fn new() -> Self {
+        Self {
+            smartstates: SmartstateProvider::new(),
+            values: core::array::from_fn(|i| i as u32 * 37),
+        }
+    }
+
+    /// Changes every tenth value, so 10% of the labels redraw in the next frame.
+    pub fn change_tenth(&mut self) {
+        for value in self.values.iter_mut().step_by(10) {
+            *value += 1;
+        }
+    }
+
+    /// Draws a frame, clearing the background first if `first` is set, and returns the
+    /// performance counters of the frame.
+    pub fn frame(&mut self, display: &mut Framebuffer, first: bool) -> PerfStats {
+        let mut ui = Ui::new_fullscreen(display, style());
+        if first {
+            ui.clear_background().unwrap();
+        }
+        self.smartstates.restart_counter();
+        for (i, value) in self.values.iter().enumerate() {
+            let text = Buf::<16>::from_fmt(format_args!("Value {i:02}: {value:4}"));
+            ui.add_horizontal(Label::owned(text).smartstate(self.smartstates.nxt()));
+            if i % COLUMNS == COLUMNS - 1 {
+                ui.new_row();
+            }
+        }
+        ui.perf()
+    }
+}
+
+/// A paragraph for measuring text, wrapped to a width
+pub const PARAGRAPH: &str = "Kolibri measures text to lay out widgets before drawing them, \
+    e.g. to size a button around its label, to wrap a text box, or to truncate a label that is \
+    too long. This paragraph is long enough to wrap into several lines.";
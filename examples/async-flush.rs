@@ -0,0 +1,153 @@
+//! Decoupling drawing from flushing, for displays with an async (e.g. DMA-driven) flush.
+//!
+//! The UI draws into a frame buffer in RAM. After the frame is built, [Ui::finish_frame]
+//! reports the dirty area, and only that area is transferred to the panel, asynchronously.
+//! No widget ever touches the bus.
+//!
+//! The "panel" here is a mock async target: its `flush` yields to the executor a few times,
+//! like a real driver awaiting its DMA-complete interrupt, and then copies the area into the
+//! simulator window's display. On hardware, the loop would run in an embassy task.
+//!
+//! The example runs on embassy-futures' executor: `cargo run --example async-flush --features
+//! embassy-futures`.
+
+use embassy_futures::{block_on, yield_now};
+use embedded_graphics::geometry::Size;
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics_simulator::sdl2::MouseButton;
+use embedded_graphics_simulator::{
+    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
+};
+use kolibri_embedded_gui::button::Button;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::smartstate::SmartstateProvider;
+use kolibri_embedded_gui::style::medsize_rgb565_style;
+use kolibri_embedded_gui::ui::{Interaction, Ui};
+
+/// A mock display driver with an async flush
+struct DmaPanel {
+    panel: SimulatorDisplay<Rgb565>,
+    transfers: u32,
+}
+
+impl DmaPanel {
+    /// Transfers `area` of the frame buffer to the panel.
+    async fn flush(&mut self, framebuffer: &SimulatorDisplay<Rgb565>, area: Rectangle) {
+        // a real driver would start the DMA transfer here, and await its completion
+        for _ in 0..4 {
+            yield_now().await;
+        }
+        self.panel
+            .draw_iter(area.points().map(|p| Pixel(p, framebuffer.get_pixel(p))))
+            .unwrap();
+        self.transfers += 1;
+        println!(
+            "transfer #{}: {} px at {:?}",
+            self.transfers,
+            area.size.width * area.size.height,
+            area.top_left
+        );
+    }
+}
+
+fn main() {
+    block_on(run());
+}
+
+async fn run() {
+    // Simulator Setup (ILI9341-like Display)
+    let size = Size::new(320, 240);
+    let mut framebuffer = SimulatorDisplay::<Rgb565>::new(size);
+    let mut panel = DmaPanel {
+        panel: SimulatorDisplay::new(size),
+        transfers: 0,
+    };
+
+    let output_settings = OutputSettingsBuilder::new().build();
+    let mut window = Window::new("Async Flush", &output_settings);
+
+    // input handling variables
+    let mut mouse_down = false;
+    let mut last_down = false;
+    let mut location = Point::new(0, 0);
+
+    let mut i = 0u8;
+    let mut smartstates = SmartstateProvider::<20>::new();
+
+    // clear bg once
+    let mut ui = Ui::new_fullscreen(&mut framebuffer, medsize_rgb565_style());
+    ui.clear_background().unwrap();
+    let frame = ui.finish_frame();
+    if let Some(area) = frame.dirty_area {
+        panel.flush(&framebuffer, area).await;
+    }
+
+    'outer: loop {
+        // build the frame in RAM
+        let mut ui = Ui::new_fullscreen(&mut framebuffer, medsize_rgb565_style());
+
+        match (last_down, mouse_down, location) {
+            (false, true, loc) => ui.interact(Interaction::Click(loc)),
+            (true, true, loc) => ui.interact(Interaction::Drag(loc)),
+            (true, false, loc) => ui.interact(Interaction::Release(loc)),
+            (false, false, loc) => ui.interact(Interaction::Hover(loc)),
+        }
+        last_down = mouse_down;
+        smartstates.restart_counter();
+
+        ui.add(
+            Label::new("Async Flush")
+                .with_font(ascii::FONT_10X20)
+                .smartstate(smartstates.nxt()),
+        );
+        if ui
+            .add_horizontal(Button::new("-").smartstate(smartstates.nxt()))
+            .clicked()
+        {
+            i = i.saturating_sub(1);
+            smartstates.nxt().force_redraw();
+        }
+        ui.add_horizontal(Label::new(&format!("Count: {i:3}")).smartstate(smartstates.nxt()));
+        if ui
+            .add_horizontal(Button::new("+").smartstate(smartstates.nxt()))
+            .clicked()
+        {
+            i = i.saturating_add(1);
+            smartstates.prev().force_redraw();
+        }
+
+        // flush only what changed
+        let frame = ui.finish_frame();
+        if let Some(area) = frame.dirty_area {
+            panel.flush(&framebuffer, area).await;
+        }
+
+        // simulator window update
+        window.update(&panel.panel);
+
+        for evt in window.events() {
+            match evt {
+                SimulatorEvent::MouseButtonUp { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = false;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseButtonDown { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = true;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseMove { point } => {
+                    location = point;
+                }
+                SimulatorEvent::Quit => break 'outer,
+                _ => {}
+            }
+        }
+    }
+}
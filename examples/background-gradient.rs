@@ -0,0 +1,128 @@
+//! A vertical gradient as the background of the whole [Ui], with a background painter.
+//!
+//! The painter is called for every area the [Ui] clears: the whole screen in the first frame,
+//! behind labels whose text changed, and the rows freed by removing list entries (with
+//! [Ui::clear_unused]). It draws the part of the gradient belonging to each area, so the
+//! gradient stays seamless without the application tracking what needs to be repainted.
+use embedded_graphics::draw_target::Clipped;
+use embedded_graphics::geometry::Size;
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+use embedded_graphics_simulator::sdl2::MouseButton;
+use embedded_graphics_simulator::{
+    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
+};
+use kolibri_embedded_gui::button::Button;
+use kolibri_embedded_gui::format::Buf;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::smartstate::SmartstateProvider;
+use kolibri_embedded_gui::style::medsize_rgb565_style;
+use kolibri_embedded_gui::ui::{Interaction, Ui, UsedArea};
+
+const HEIGHT: u32 = 240;
+const ENTRIES: [&str; 6] = ["Alpha", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot"];
+
+/// Paints the rows of the gradient within `area`, from dark blue at the top to black
+fn gradient(
+    target: &mut Clipped<'_, SimulatorDisplay<Rgb565>>,
+    area: Rectangle,
+) -> Result<(), core::convert::Infallible> {
+    let right = area.top_left.x + area.size.width as i32 - 1;
+    for y in area.rows() {
+        let shade = (HEIGHT as i32 - y).clamp(0, HEIGHT as i32) as u32;
+        let color = Rgb565::new(0, (shade * 16 / HEIGHT) as u8, (shade * 20 / HEIGHT) as u8);
+        Line::new(Point::new(area.top_left.x, y), Point::new(right, y))
+            .into_styled(PrimitiveStyle::with_stroke(color, 1))
+            .draw(target)?;
+    }
+    Ok(())
+}
+
+fn main() -> Result<(), core::convert::Infallible> {
+    let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, HEIGHT));
+    let output_settings = OutputSettingsBuilder::new().scale(2).build();
+    let mut window = Window::new("Background Gradient", &output_settings);
+
+    let mut mouse_down = false;
+    let mut last_down = false;
+    let mut location = Point::new(0, 0);
+
+    let mut smartstates = SmartstateProvider::<20>::new();
+    let mut used = UsedArea::new();
+    let mut count_buf = Buf::<24>::new();
+    let mut entries = 3usize;
+    let mut first_frame = true;
+
+    'outer: loop {
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_background_painter(gradient);
+        match (last_down, mouse_down) {
+            (false, true) => ui.interact(Interaction::Click(location)),
+            (true, true) => ui.interact(Interaction::Drag(location)),
+            (true, false) => ui.interact(Interaction::Release(location)),
+            (false, false) => ui.interact(Interaction::Hover(location)),
+        }
+        last_down = mouse_down;
+        smartstates.restart_counter();
+        if first_frame {
+            ui.clear_background().ok();
+            first_frame = false;
+        }
+
+        ui.add(
+            Label::new("Background Gradient")
+                .with_font(ascii::FONT_10X20)
+                .smartstate(smartstates.nxt()),
+        );
+        if ui
+            .add_horizontal(Button::new("-").smartstate(smartstates.nxt()))
+            .clicked()
+        {
+            entries = entries.saturating_sub(1);
+        }
+        ui.add_horizontal(
+            Label::new_fmt(&mut count_buf, format_args!("{entries} entries"))
+                .smartstate(smartstates.nxt()),
+        );
+        if ui
+            .add_horizontal(Button::new("+").smartstate(smartstates.nxt()))
+            .clicked()
+        {
+            entries = (entries + 1).min(ENTRIES.len());
+        }
+        ui.new_row();
+
+        for entry in &ENTRIES[..entries] {
+            ui.add(Label::new(entry).smartstate(smartstates.nxt()));
+        }
+        // repaint the gradient where removed entries were
+        ui.clear_unused(&mut used).ok();
+
+        window.update(&display);
+
+        for evt in window.events() {
+            match evt {
+                SimulatorEvent::MouseButtonUp { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = false;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseButtonDown { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = true;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseMove { point } => {
+                    location = point;
+                }
+                SimulatorEvent::Quit => break 'outer,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+//! Draws app-side decorations around widgets, using the areas of their [Response]s:
+//! an underline beneath whichever of three labels was clicked last, and a line connecting
+//! it to a button below.
+use embedded_graphics::geometry::Size;
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+use embedded_graphics_simulator::sdl2::MouseButton;
+use embedded_graphics_simulator::{
+    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
+};
+use kolibri_embedded_gui::button::Button;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::style::medsize_rgb565_style;
+use kolibri_embedded_gui::ui::{Interaction, ResponseCtx, Ui};
+
+const TABS: [&str; 3] = ["Overview", "Details", "Log"];
+
+fn main() -> Result<(), core::convert::Infallible> {
+    let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    let output_settings = OutputSettingsBuilder::new().scale(2).build();
+    let mut window = Window::new("Custom Decorations", &output_settings);
+
+    let mut mouse_down = false;
+    let mut last_down = false;
+    let mut location = Point::new(0, 0);
+
+    // the tab that was clicked last
+    let mut selected = 0;
+
+    'outer: loop {
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        match (last_down, mouse_down) {
+            (false, true) => ui.interact(Interaction::Click(location)),
+            (true, true) => ui.interact(Interaction::Drag(location)),
+            (true, false) => ui.interact(Interaction::Release(location)),
+            (false, false) => ui.interact(Interaction::Hover(location)),
+        }
+        last_down = mouse_down;
+        ui.clear_background().ok();
+
+        ui.add(Label::new("Custom Decorations").with_font(ascii::FONT_10X20));
+
+        // labels don't report clicks, but their response has the interaction
+        let mut tab_areas = [Rectangle::zero(); TABS.len()];
+        for (i, tab) in TABS.iter().enumerate() {
+            let response = ui.add_horizontal(Label::new(tab));
+            if response.interaction().is_release() {
+                selected = i;
+            }
+            tab_areas[i] = response.area();
+        }
+        ui.new_row();
+        ui.new_row();
+
+        let ResponseCtx {
+            area: button_area,
+            clicked,
+            ..
+        } = ui.add(Button::new("Next tab")).ctx();
+        if clicked {
+            selected = (selected + 1) % TABS.len();
+        }
+
+        // the underline, 2px below the selected label
+        let tab = tab_areas[selected];
+        let underline = Rectangle::new(
+            Point::new(tab.top_left.x, tab.top_left.y + tab.size.height as i32 + 1),
+            Size::new(tab.size.width, 2),
+        );
+        let primary = ui.style().primary_color;
+        ui.draw(&underline.into_styled(PrimitiveStyle::with_fill(primary)))
+            .ok();
+
+        // and a line from it to the button
+        let connection = Line::new(
+            underline.center() + Point::new(0, 2),
+            Point::new(button_area.center().x, button_area.top_left.y),
+        );
+        let secondary = ui.style().secondary_color;
+        ui.draw(&connection.into_styled(PrimitiveStyle::with_stroke(secondary, 1)))
+            .ok();
+
+        window.update(&display);
+
+        for evt in window.events() {
+            match evt {
+                SimulatorEvent::MouseButtonUp { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = false;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseButtonDown { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = true;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseMove { point } => {
+                    location = point;
+                }
+                SimulatorEvent::Quit => break 'outer,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! Event-driven rendering: a frame is only built when there is new input or a widget
+//! animates, and only shown when it had changes. On a device, the idle branch would sleep
+//! (e.g. on `WFI`) until the touch interrupt instead of polling.
+//!
+//! The label at the bottom counts the frames that were built and the idle polls in between,
+//! so you can see that nothing is rendered while the mouse rests and the spinner is off.
+use embedded_graphics::geometry::Size;
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::Point;
+use embedded_graphics_simulator::sdl2::MouseButton;
+use embedded_graphics_simulator::{
+    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
+};
+use kolibri_embedded_gui::button::Button;
+use kolibri_embedded_gui::checkbox::Checkbox;
+use kolibri_embedded_gui::format::Buf;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::smartstate::SmartstateProvider;
+use kolibri_embedded_gui::spinner::Spinner;
+use kolibri_embedded_gui::style::medsize_rgb565_style;
+use kolibri_embedded_gui::ui::{Interaction, Ui};
+use std::time::{Duration, Instant};
+
+fn main() -> Result<(), core::convert::Infallible> {
+    let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    let output_settings = OutputSettingsBuilder::new().scale(2).build();
+    let mut window = Window::new("Event-Driven Rendering", &output_settings);
+
+    let mut mouse_down = false;
+    let mut last_down = false;
+    let mut location = Point::new(0, 0);
+
+    let mut smartstates = SmartstateProvider::<10>::new();
+    let mut counter_buf = Buf::<24>::new();
+    let mut stats_buf = Buf::<48>::new();
+    let start = Instant::now();
+
+    let mut i = 0u8;
+    let mut busy = false;
+    let mut frames_built = 0u32;
+    let mut idle_polls = 0u32;
+
+    // build the first frame
+    let mut build_frame = true;
+    let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ui.clear_background().ok();
+
+    'outer: loop {
+        if build_frame {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            // only animate the spinner while busy, it is static otherwise
+            if busy {
+                ui.begin_frame(start.elapsed().as_millis() as u64);
+            }
+            match (last_down, mouse_down) {
+                (false, true) => ui.interact(Interaction::Click(location)),
+                (true, true) => ui.interact(Interaction::Drag(location)),
+                (true, false) => ui.interact(Interaction::Release(location)),
+                (false, false) => ui.interact(Interaction::Hover(location)),
+            }
+            last_down = mouse_down;
+            smartstates.restart_counter();
+            frames_built += 1;
+
+            ui.add(
+                Label::new("Event-Driven Rendering")
+                    .with_font(ascii::FONT_10X20)
+                    .smartstate(smartstates.nxt()),
+            );
+            if ui
+                .add_horizontal(Button::new("-").smartstate(smartstates.nxt()))
+                .clicked()
+            {
+                i = i.saturating_sub(1);
+            }
+            ui.add_horizontal(
+                Label::new_fmt(&mut counter_buf, format_args!("Clicked {i} times"))
+                    .smartstate(smartstates.nxt()),
+            );
+            if ui
+                .add_horizontal(Button::new("+").smartstate(smartstates.nxt()))
+                .clicked()
+            {
+                i = i.saturating_add(1);
+            }
+            ui.new_row();
+
+            ui.add_horizontal(Checkbox::new(&mut busy).smartstate(smartstates.nxt()));
+            ui.add_horizontal(Label::new("Busy").smartstate(smartstates.nxt()));
+            ui.add(Spinner::new().smartstate(smartstates.nxt()));
+
+            ui.add(
+                Label::new_fmt(
+                    &mut stats_buf,
+                    format_args!("{frames_built} frames built, {idle_polls} idle"),
+                )
+                .smartstate(smartstates.nxt()),
+            );
+
+            // keep building frames while something animates
+            build_frame = ui.needs_repaint();
+            if ui.frame_had_changes() {
+                window.update(&display);
+            }
+        } else {
+            // on a device: sleep until the next interrupt
+            idle_polls += 1;
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        for evt in window.events() {
+            match evt {
+                SimulatorEvent::MouseButtonUp { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = false;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseButtonDown { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = true;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseMove { point } => {
+                    location = point;
+                }
+                SimulatorEvent::Quit => break 'outer,
+                _ => continue,
+            }
+            // new input: build a frame for it
+            build_frame = true;
+        }
+    }
+    Ok(())
+}
@@ -119,7 +119,7 @@ fn main() -> Result<(), core::convert::Infallible> {
             ui.sub_ui(|ui| {
                 let style = ui.style_mut();
 
-                style.item_background_color = Rgb565::CSS_ORANGE_RED;
+                style.item_background_color = Rgb565::CSS_ORANGE_RED.into();
                 style.highlight_item_background_color = Rgb565::CSS_ORANGE_RED;
                 style.primary_color = Rgb565::CSS_RED;
                 style.text_color = Rgb565::CSS_BLACK;
@@ -139,7 +139,7 @@ fn main() -> Result<(), core::convert::Infallible> {
             ui.sub_ui(|ui| {
                 let style = ui.style_mut();
 
-                style.item_background_color = Rgb565::CSS_LIME_GREEN;
+                style.item_background_color = Rgb565::CSS_LIME_GREEN.into();
                 style.highlight_item_background_color = Rgb565::CSS_LIME_GREEN;
                 style.primary_color = Rgb565::CSS_GREEN;
                 style.text_color = Rgb565::CSS_BLACK;
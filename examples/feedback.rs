@@ -0,0 +1,127 @@
+//! A feedback handler that prints the interactions of all widgets, in place of a buzzer or a
+//! vibration motor.
+//!
+//! Tap the buttons for `Press` and `Release`, hold the "Hold" button for `LongPress` (it
+//! repeats while held) and change the checkbox or the slider for `ValueChanged`.
+use embedded_graphics::geometry::Size;
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::Point;
+use embedded_graphics_simulator::sdl2::MouseButton;
+use embedded_graphics_simulator::{
+    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
+};
+use kolibri_embedded_gui::button::{Button, RepeatState};
+use kolibri_embedded_gui::checkbox::Checkbox;
+use kolibri_embedded_gui::format::Buf;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::slider::Slider;
+use kolibri_embedded_gui::smartstate::SmartstateProvider;
+use kolibri_embedded_gui::style::medsize_rgb565_style;
+use kolibri_embedded_gui::ui::{FeedbackEvent, Interaction, Ui};
+use std::time::Instant;
+
+/// On a device, this would start a short buzz or a click sound
+fn feedback(event: FeedbackEvent) {
+    println!("feedback: {event:?}");
+}
+
+fn main() -> Result<(), core::convert::Infallible> {
+    let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    let output_settings = OutputSettingsBuilder::new().scale(2).build();
+    let mut window = Window::new("Feedback", &output_settings);
+
+    let mut mouse_down = false;
+    let mut last_down = false;
+    let mut location = Point::new(0, 0);
+
+    let mut smartstates = SmartstateProvider::<10>::new();
+    let mut counter_buf = Buf::<24>::new();
+    let start = Instant::now();
+
+    let mut i = 0u8;
+    let mut hold = RepeatState::new();
+    let mut enabled = false;
+    let mut level = 50i16;
+
+    let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ui.clear_background().ok();
+
+    'outer: loop {
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_feedback_handler(feedback);
+        // repeating buttons need the time
+        ui.begin_frame(start.elapsed().as_millis() as u64);
+        match (last_down, mouse_down) {
+            (false, true) => ui.interact(Interaction::Click(location)),
+            (true, true) => ui.interact(Interaction::Drag(location)),
+            (true, false) => ui.interact(Interaction::Release(location)),
+            (false, false) => ui.interact(Interaction::Hover(location)),
+        }
+        last_down = mouse_down;
+        smartstates.restart_counter();
+
+        ui.add(
+            Label::new("Feedback")
+                .with_font(ascii::FONT_10X20)
+                .smartstate(smartstates.nxt()),
+        );
+        if ui
+            .add_horizontal(Button::new("-").smartstate(smartstates.nxt()))
+            .clicked()
+        {
+            i = i.saturating_sub(1);
+        }
+        ui.add_horizontal(
+            Label::new_fmt(&mut counter_buf, format_args!("Clicked {i} times"))
+                .smartstate(smartstates.nxt()),
+        );
+        if ui
+            .add_horizontal(Button::new("+").smartstate(smartstates.nxt()))
+            .clicked()
+        {
+            i = i.saturating_add(1);
+        }
+        if ui
+            .add_horizontal(
+                Button::new("Hold")
+                    .repeat(&mut hold, 500, 100)
+                    .smartstate(smartstates.nxt()),
+            )
+            .clicked()
+        {
+            i = i.saturating_add(1);
+        }
+        ui.new_row();
+
+        ui.add_horizontal(Checkbox::new(&mut enabled).smartstate(smartstates.nxt()));
+        ui.add_horizontal(Label::new("Enabled").smartstate(smartstates.nxt()));
+        ui.new_row();
+        ui.add(Slider::new(&mut level, 0..=100).smartstate(smartstates.nxt()));
+
+        window.update(&display);
+
+        for evt in window.events() {
+            match evt {
+                SimulatorEvent::MouseButtonUp { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = false;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseButtonDown { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = true;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseMove { point } => {
+                    location = point;
+                }
+                SimulatorEvent::Quit => break 'outer,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
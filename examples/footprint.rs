@@ -0,0 +1,96 @@
+//! Prints the sizes of Kolibri's public types as a markdown table, to budget RAM on small MCUs.
+//!
+//! The sizes are those of the target the example is compiled for: on the host, pointers and
+//! `usize` take 8 bytes, on 32-bit MCUs 4. Build it for your target (or look at the constants
+//! like [Ui::STATIC_SIZE] and [SmartstateProvider::SIZE] there) for exact numbers.
+//!
+//! - The [Ui] lives for a frame, on the stack of the task drawing the UI. On a 64-bit host it
+//!   has grown from 288 to about 1640 bytes, mostly for the state it keeps during the frame:
+//!   the [Ui::push_style] stack (616 bytes), the pointer and its press (88), the areas covered
+//!   by overlays (72), the key queue (56) and the [PerfStats] (36). The [Style] grew by 32.
+//! - Smartstates and retained widget state (like a [ScrollState]) live as long as the UI, so
+//!   they cost RAM even between frames. A provider only holds as many (keyed) smartstates as its
+//!   const generics ask for.
+//! - Widgets are builders, which only live on the stack while they are added.
+use core::mem::size_of;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics_simulator::SimulatorDisplay;
+use kolibri_embedded_gui::anim::Animated;
+use kolibri_embedded_gui::button::{Button, RepeatState};
+use kolibri_embedded_gui::checkbox::Checkbox;
+use kolibri_embedded_gui::compass::Compass;
+use kolibri_embedded_gui::dropdown::{Dropdown, DropdownState};
+use kolibri_embedded_gui::format::Buf;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::pan_canvas::PanState;
+use kolibri_embedded_gui::reorderable_list::ReorderState;
+use kolibri_embedded_gui::scope::ScopeState;
+use kolibri_embedded_gui::slider::Slider;
+use kolibri_embedded_gui::smartstate::{Slot, Smartstate, SmartstateProvider, StateStore};
+use kolibri_embedded_gui::spinner::Spinner;
+use kolibri_embedded_gui::style::Style;
+use kolibri_embedded_gui::toggle_button::ToggleButton;
+use kolibri_embedded_gui::toggle_switch::ToggleSwitch;
+use kolibri_embedded_gui::ui::{
+    FrameInfo, GuiError, Interaction, InternalResponse, PerfStats, Response, ScrollState, Ui,
+    UiState, UsedArea,
+};
+
+type Display = SimulatorDisplay<Rgb565>;
+
+/// Prints a table row with the size of each type
+macro_rules! sizes {
+    ($($ty:ty),* $(,)?) => {
+        $(println!("| `{}` | {} |", stringify!($ty), size_of::<$ty>());)*
+    };
+}
+
+fn main() {
+    println!("| Type | Bytes |");
+    println!("|------|------:|");
+
+    // per frame
+    sizes!(
+        Ui<Display, Rgb565>,
+        Style<Rgb565>,
+        Interaction,
+        InternalResponse,
+        Response,
+        PerfStats,
+        FrameInfo,
+        GuiError,
+    );
+
+    // retained
+    sizes!(
+        Smartstate,
+        SmartstateProvider<16>,
+        SmartstateProvider<16, 4>,
+        SmartstateProvider<64>,
+        Slot,
+        StateStore<16>,
+        UiState<Rgb565>,
+        UsedArea,
+        ScrollState,
+        PanState,
+        ReorderState,
+        DropdownState,
+        RepeatState,
+        ScopeState<64>,
+        Animated<i32>,
+        Buf<16>,
+    );
+
+    // widgets, while they are added
+    sizes!(
+        Label,
+        Button,
+        Checkbox,
+        ToggleButton,
+        ToggleSwitch,
+        Slider,
+        Dropdown,
+        Spinner,
+        Compass,
+    );
+}
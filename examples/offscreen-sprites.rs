@@ -0,0 +1,81 @@
+//! Twelve identical gauges, drawn from primitives or blitted from a pre-rendered sprite.
+//!
+//! A [Compass] is drawn from circles, lines, triangles and text. When the same one appears many
+//! times, it can be rendered once with [Ui::render_offscreen] and then drawn as an image with
+//! the [Img] widget. Both variants are timed over a number of full redraws, and the blitted
+//! gauges are shown afterwards.
+use embedded_graphics::geometry::Size;
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics_simulator::{OutputSettingsBuilder, SimulatorDisplay, Window};
+use kolibri_embedded_gui::compass::Compass;
+use kolibri_embedded_gui::img::Img;
+use kolibri_embedded_gui::style::{medsize_rgb565_style, Style};
+use kolibri_embedded_gui::ui::Ui;
+use std::time::{Duration, Instant};
+
+const GAUGES: usize = 12;
+const DIAMETER: u32 = 48;
+const FRAMES: u32 = 200;
+
+/// The default style, with a font small enough for the gauges' labels
+fn style() -> Style<Rgb565> {
+    let mut style = medsize_rgb565_style();
+    style.default_font = ascii::FONT_5X8;
+    style
+}
+
+/// Redraws the gauges `FRAMES` times, `add`ing each one, and returns the time taken
+fn time(
+    display: &mut SimulatorDisplay<Rgb565>,
+    mut add: impl FnMut(&mut Ui<SimulatorDisplay<Rgb565>, Rgb565>),
+) -> Duration {
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        let mut ui = Ui::new_fullscreen(display, style());
+        for gauge in 0..GAUGES {
+            add(&mut ui);
+            // six gauges per row
+            if gauge % 6 == 5 {
+                ui.new_row();
+            }
+        }
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+
+    // drawn from primitives, every time
+    let primitives = time(&mut display, |ui| {
+        ui.add_horizontal(Compass::new(72.0).diameter(DIAMETER));
+    });
+
+    // rendered once, then blitted
+    let mut buf = [Rgb565::BLACK; (DIAMETER * DIAMETER) as usize];
+    let ui = Ui::new_fullscreen(&mut display, style());
+    let sprite = ui
+        .render_offscreen(
+            Compass::new(72.0).diameter(DIAMETER),
+            &mut buf,
+            Size::new_equal(DIAMETER),
+        )
+        .unwrap();
+    drop(ui);
+    let blitted = time(&mut display, |ui| {
+        ui.add_horizontal(Img::new(&sprite));
+    });
+
+    println!("{GAUGES} gauges, {FRAMES} frames:");
+    println!("  from primitives: {primitives:?}");
+    println!("  blitted sprites: {blitted:?}");
+    println!(
+        "  speedup: {:.1}x",
+        primitives.as_secs_f64() / blitted.as_secs_f64()
+    );
+
+    let output_settings = OutputSettingsBuilder::new().scale(2).build();
+    Window::new("Offscreen Sprites", &output_settings).show_static(&display);
+}
@@ -0,0 +1,107 @@
+use embedded_graphics::geometry::Size;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::Point;
+use embedded_graphics_simulator::sdl2::{Keycode, MouseButton};
+use embedded_graphics_simulator::{
+    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
+};
+use kolibri_embedded_gui::button::Button;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::smartstate::SmartstateProvider;
+use kolibri_embedded_gui::style::medsize_rgb565_style;
+use kolibri_embedded_gui::ui::{Interaction, ScrollState, Ui, UiState};
+
+fn main() -> Result<(), core::convert::Infallible> {
+    // ILI9341-clone like display
+    let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+
+    let output_settings = OutputSettingsBuilder::new().build();
+    let mut window = Window::new("Scrolling", &output_settings);
+
+    let mut mouse_down = false;
+    let mut last_down = false;
+    let mut location = Point::new(0, 0);
+    // mouse wheel and arrow key detents since the last frame, positive scrolling down
+    let mut detents = 0;
+    let mut smartstates = SmartstateProvider::<30>::new();
+    let mut scroll = ScrollState::new();
+    let mut clicked = "none";
+    // the Ui of every frame is created from it
+    let mut state = UiState::new(medsize_rgb565_style());
+    let start = std::time::Instant::now();
+
+    // clear bg once
+    let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ui.clear_background().unwrap();
+
+    'outer: loop {
+        let interaction = match (last_down, mouse_down) {
+            (false, true) => Interaction::Click(location),
+            (true, true) => Interaction::Drag(location),
+            (true, false) => Interaction::Release(location),
+            (false, false) => Interaction::Hover(location),
+        };
+        last_down = mouse_down;
+        let now = start.elapsed().as_millis() as u64;
+        let mut ui = state.begin_frame(&mut display, interaction, now);
+        smartstates.restart_counter();
+
+        // the scroll replaces the hover, the scroll area remembers where the pointer was
+        if detents != 0 {
+            ui.interact(Interaction::Scroll(detents));
+            detents = 0;
+        }
+
+        ui.add(Label::new("Scroll with the wheel or the arrow keys").smartstate(smartstates.nxt()));
+        ui.scroll_area(160, &mut scroll, |ui| {
+            for name in [
+                "Alpha", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India",
+                "Juliett", "Kilo", "Lima",
+            ] {
+                if ui
+                    .add(Button::new(name).smartstate(smartstates.nxt()))
+                    .clicked()
+                {
+                    clicked = name;
+                }
+            }
+        });
+        // padded, so a shorter name covers a longer one
+        ui.add(Label::new(format!("Clicked: {:<8}", clicked).as_ref()));
+        state.end_frame(ui);
+
+        window.update(&display);
+
+        for evt in window.events() {
+            match evt {
+                SimulatorEvent::KeyDown { keycode, .. } => match keycode {
+                    Keycode::Up => detents -= 1,
+                    Keycode::Down => detents += 1,
+                    _ => {}
+                },
+                SimulatorEvent::MouseWheel { scroll_delta, .. } => {
+                    // the wheel's y is positive when scrolling up
+                    detents -= scroll_delta.y;
+                }
+                SimulatorEvent::MouseButtonUp { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = false;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseButtonDown { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = true;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseMove { point } => {
+                    location = point;
+                }
+                SimulatorEvent::Quit => break 'outer,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,105 @@
+//! Shows how to calibrate a touch controller with [CalibrationScreen], and connect it with
+//! [TouchAdapter].
+//!
+//! The simulator has no touch screen, so the mouse pretends to be a resistive XPT2046-style
+//! controller: 12-bit raw readings with swapped and mirrored axes, plus a bit of noise and the
+//! occasional dropout while pressed.
+use embedded_graphics::geometry::Size;
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::Point;
+use embedded_graphics_simulator::sdl2::MouseButton;
+use embedded_graphics_simulator::{
+    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
+};
+use kolibri_embedded_gui::button::Button;
+use kolibri_embedded_gui::input::calibration::{CalibrationPoints, CalibrationScreen};
+use kolibri_embedded_gui::input::touch::TouchAdapter;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::style::medsize_rgb565_style;
+use kolibri_embedded_gui::ui::Ui;
+
+/// What the fake controller reports for a touch at `screen`
+fn raw_reading(screen: Point, frame: u32) -> Point {
+    // cheap deterministic noise of a few raw units
+    let noise = ((frame * 7919) % 17) as i32 - 8;
+    Point::new(
+        3900 - screen.y * 3600 / 240 + noise,
+        200 + screen.x * 3600 / 320 - noise,
+    )
+}
+
+fn counter_ui(ui: &mut Ui<SimulatorDisplay<Rgb565>, Rgb565>, touch: &TouchAdapter, i: &mut u8) {
+    ui.add(Label::new("Touch Adapter").with_font(ascii::FONT_10X20));
+    ui.add(Label::new(
+        format!("Matrix: {:?}", touch.calibration().matrix()).as_ref(),
+    ));
+    if ui.add_horizontal(Button::new("-")).clicked() {
+        *i = i.saturating_sub(1);
+    }
+    ui.add_horizontal(Label::new(format!("Clicked {} times", i).as_ref()));
+    if ui.add_horizontal(Button::new("+")).clicked() {
+        *i = i.saturating_add(1);
+    }
+}
+
+fn main() -> Result<(), core::convert::Infallible> {
+    let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    let output_settings = OutputSettingsBuilder::new().scale(2).build();
+    let mut window = Window::new("Touch Calibration", &output_settings);
+
+    // on a real device, you'd store the calibration and only run this on first boot
+    let mut calibration_screen = CalibrationScreen::new(CalibrationPoints::Five);
+    let mut calibrated = false;
+    let mut touch = TouchAdapter::new().debounce(2);
+
+    let mut mouse_down = false;
+    let mut location = Point::new(0, 0);
+    let mut frame = 0u32;
+    let mut i = 0u8;
+
+    'outer: loop {
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+
+        // every 13th sample drops out while pressed, which the debouncing hides
+        let sample =
+            (mouse_down && !frame.is_multiple_of(13)).then(|| raw_reading(location, frame));
+        frame = frame.wrapping_add(1);
+
+        if !calibrated {
+            if let Some(calibration) = calibration_screen.step(&mut ui, sample) {
+                touch.set_calibration(calibration);
+                calibrated = true;
+            }
+        } else {
+            ui.interact(touch.update(sample));
+            ui.clear_background().ok();
+            counter_ui(&mut ui, &touch, &mut i);
+        }
+
+        window.update(&display);
+
+        for evt in window.events() {
+            match evt {
+                SimulatorEvent::MouseButtonUp { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = false;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseButtonDown { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = true;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseMove { point } => {
+                    location = point;
+                }
+                SimulatorEvent::Quit => break 'outer,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,151 @@
+//! Widgets drawn over a background image, with `transparent_background()`.
+//!
+//! Transparent widgets can't erase their previous content. When one needs to redraw, it
+//! reports its area as stale instead, and the application restores the background there at the
+//! start of the next frame, before the widgets are added again.
+use embedded_graphics::geometry::Size;
+use embedded_graphics::image::Image;
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics_simulator::sdl2::MouseButton;
+use embedded_graphics_simulator::{
+    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
+};
+use embedded_iconoir::size24px;
+use kolibri_embedded_gui::button::Button;
+use kolibri_embedded_gui::format::Buf;
+use kolibri_embedded_gui::iconbutton::IconButton;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::smartstate::SmartstateProvider;
+use kolibri_embedded_gui::style::medsize_rgb565_style;
+use kolibri_embedded_gui::ui::{Interaction, Ui};
+use tinybmp::Bmp;
+
+/// A tile of the background image, repeated over the screen
+const TILE: &[u8] = include_bytes!("../assets/wallpaper-tile.bmp");
+
+/// The background image within `area`
+struct Wallpaper<'a> {
+    tile: &'a Bmp<'a, Rgb565>,
+    area: Rectangle,
+}
+
+impl Drawable for Wallpaper<'_> {
+    type Color = Rgb565;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = Rgb565>>(&self, target: &mut D) -> Result<(), D::Error> {
+        let tile = self.tile.bounding_box().size;
+        let (w, h) = (tile.width as i32, tile.height as i32);
+        let mut target = target.clipped(&self.area);
+        // the tiles touching the area
+        let start = Point::new(
+            self.area.top_left.x.div_euclid(w) * w,
+            self.area.top_left.y.div_euclid(h) * h,
+        );
+        let end = self.area.top_left + self.area.size;
+        for y in (start.y..end.y).step_by(h as usize) {
+            for x in (start.x..end.x).step_by(w as usize) {
+                Image::new(self.tile, Point::new(x, y)).draw(&mut target)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), core::convert::Infallible> {
+    let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    let output_settings = OutputSettingsBuilder::new().scale(2).build();
+    let mut window = Window::new("Transparent Widgets", &output_settings);
+    let tile = Bmp::<Rgb565>::from_slice(TILE).expect("valid bitmap");
+
+    let mut mouse_down = false;
+    let mut last_down = false;
+    let mut location = Point::new(0, 0);
+
+    let mut smartstates = SmartstateProvider::<10>::new();
+    let mut buf = Buf::<24>::new();
+    let mut i = 0u8;
+
+    // the whole screen needs the background in the first frame
+    let mut stale = Some(display.bounding_box());
+
+    'outer: loop {
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        match (last_down, mouse_down) {
+            (false, true) => ui.interact(Interaction::Click(location)),
+            (true, true) => ui.interact(Interaction::Drag(location)),
+            (true, false) => ui.interact(Interaction::Release(location)),
+            (false, false) => ui.interact(Interaction::Hover(location)),
+        }
+        last_down = mouse_down;
+        smartstates.restart_counter();
+
+        // restore the background where transparent widgets couldn't redraw last frame
+        if let Some(area) = stale {
+            ui.draw(&Wallpaper { tile: &tile, area }).ok();
+            ui.invalidate(area);
+        }
+
+        ui.add(
+            Label::new("Transparent Widgets")
+                .with_font(ascii::FONT_10X20)
+                .transparent_background()
+                .smartstate(smartstates.nxt()),
+        );
+        if ui
+            .add_horizontal(
+                Button::new("-")
+                    .transparent_background()
+                    .smartstate(smartstates.nxt()),
+            )
+            .clicked()
+        {
+            i = i.saturating_sub(1);
+        }
+        ui.add_horizontal(
+            Label::new_fmt(&mut buf, format_args!("Clicked {i} times"))
+                .transparent_background()
+                .smartstate(smartstates.nxt()),
+        );
+        if ui
+            .add_horizontal(
+                IconButton::new(size24px::actions::AddCircle)
+                    .transparent_background()
+                    .smartstate(smartstates.nxt()),
+            )
+            .clicked()
+        {
+            i = i.saturating_add(1);
+        }
+
+        stale = ui.stale_area();
+
+        window.update(&display);
+
+        for evt in window.events() {
+            match evt {
+                SimulatorEvent::MouseButtonUp { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = false;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseButtonDown { mouse_btn, point } => {
+                    if let MouseButton::Left = mouse_btn {
+                        mouse_down = true;
+                    }
+                    location = point;
+                }
+                SimulatorEvent::MouseMove { point } => {
+                    location = point;
+                }
+                SimulatorEvent::Quit => break 'outer,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,382 @@
+//! # Animations
+//!
+//! Easing functions and [Animated], a value that moves smoothly toward a target over time,
+//! for transitions like collapsing sections or page swipes. Apart from opt-in transitions of a
+//! few widgets (like [Checkbox::animate](crate::checkbox::Checkbox::animate)), widgets don't
+//! animate themselves: the app drives positions and sizes with an [Animated] value.
+//!
+//! Everything is integer math: progress is fixed-point, with [ONE] being the end of the
+//! animation.
+//!
+//! [Animated] advances by the frame time [Ui::dt]. Without a frame time (see
+//! [FrameClock](crate::ui::FrameClock)), it jumps to its target right away.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # use kolibri_embedded_gui::ui::FrameClock;
+//! # use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::anim::Animated;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! let start = std::time::Instant::now();
+//! let mut clock = FrameClock::new();
+//! // the height of a collapsible section, animated over 200ms
+//! let mut height = Animated::new(0u32, 200);
+//!
+//! loop {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     clock.begin_frame(&mut ui, start.elapsed().as_millis() as u64);
+//!
+//!     if ui.add(Button::new("Details")).clicked() {
+//!         height.set_target(if height.target() == 0 { 80 } else { 0 });
+//!     }
+//!     let height = height.update(&ui);
+//!     ui.add(Spacer::new(Size::new(100, height)));
+//! }
+//! ```
+
+#[cfg(any(feature = "checkbox", feature = "controls"))]
+use crate::smartstate::Smartstate;
+use crate::ui::Ui;
+#[cfg(any(feature = "checkbox", feature = "controls"))]
+use core::cmp::max;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+
+/// The fixed-point representation of `1.0` for animation progress
+pub const ONE: i32 = 1 << 16;
+
+/// Linear easing: `t` unchanged, clamped to `0..=ONE`.
+pub fn linear(t: i32) -> i32 {
+    t.clamp(0, ONE)
+}
+
+/// Cubic ease-in-out easing: slow start, fast middle, slow end.
+///
+/// `t` is clamped to `0..=ONE`, and `0` and [ONE] map to themselves exactly.
+pub fn ease_in_out_cubic(t: i32) -> i32 {
+    let t = t.clamp(0, ONE) as i64;
+    let one = ONE as i64;
+    let eased = if t < one / 2 {
+        // 4t³
+        4 * t * t * t / (one * one)
+    } else {
+        // 1 - (2 - 2t)³ / 2
+        let u = 2 * (one - t);
+        one - u * u * u / (2 * one * one)
+    };
+    eased as i32
+}
+
+/// An easing curve of an [Animated] value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// See [linear]
+    Linear,
+    /// See [ease_in_out_cubic]
+    #[default]
+    EaseInOutCubic,
+}
+
+impl Easing {
+    /// Applies the easing to the progress `t` (`0..=ONE`).
+    pub fn apply(self, t: i32) -> i32 {
+        match self {
+            Easing::Linear => linear(t),
+            Easing::EaseInOutCubic => ease_in_out_cubic(t),
+        }
+    }
+}
+
+/// A value that can be [Animated]
+pub trait Interpolate: Copy + PartialEq {
+    /// Returns the value at progress `t` (`0..=ONE`) from `from` to `to`. Has to return exactly
+    /// `from` at `0` and `to` at [ONE].
+    fn interpolate(from: Self, to: Self, t: i32) -> Self;
+}
+
+macro_rules! impl_interpolate_int {
+    ($($t:ty),*) => {
+        $(
+            impl Interpolate for $t {
+                fn interpolate(from: Self, to: Self, t: i32) -> Self {
+                    let (from, to) = (from as i64, to as i64);
+                    (from + (to - from) * t as i64 / ONE as i64) as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_interpolate_int!(i8, i16, i32, u8, u16, u32);
+
+impl Interpolate for Point {
+    fn interpolate(from: Self, to: Self, t: i32) -> Self {
+        Point::new(
+            i32::interpolate(from.x, to.x, t),
+            i32::interpolate(from.y, to.y, t),
+        )
+    }
+}
+
+impl Interpolate for Size {
+    fn interpolate(from: Self, to: Self, t: i32) -> Self {
+        Size::new(
+            u32::interpolate(from.width, to.width, t),
+            u32::interpolate(from.height, to.height, t),
+        )
+    }
+}
+
+/// A value moving toward a target over a fixed duration, see the [module documentation](self).
+///
+/// See [retained state](crate::smartstate::Smartstate#retained-state).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Animated<T: Interpolate> {
+    from: T,
+    to: T,
+    elapsed_ms: u32,
+    duration_ms: u32,
+    easing: Easing,
+}
+
+impl<T: Interpolate> Animated<T> {
+    /// Creates a value resting at `value`. Changes of the target take `duration_ms`.
+    pub fn new(value: T, duration_ms: u32) -> Self {
+        Self {
+            from: value,
+            to: value,
+            elapsed_ms: duration_ms,
+            duration_ms,
+            easing: Easing::default(),
+        }
+    }
+
+    /// Sets the easing curve ([Easing::EaseInOutCubic] by default).
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Starts moving toward `target`, from the current value. Setting the current target again
+    /// doesn't restart the animation.
+    pub fn set_target(&mut self, target: T) {
+        if target == self.to {
+            return;
+        }
+        self.from = self.value();
+        self.to = target;
+        self.elapsed_ms = 0;
+    }
+
+    /// Jumps to `value` without animating.
+    pub fn set(&mut self, value: T) {
+        self.from = value;
+        self.to = value;
+        self.elapsed_ms = self.duration_ms;
+    }
+
+    /// Returns the target the value is moving toward (or resting at).
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    /// Returns the current value.
+    pub fn value(&self) -> T {
+        if !self.animating() {
+            return self.to;
+        }
+        let t = (self.elapsed_ms as i64 * ONE as i64 / self.duration_ms as i64) as i32;
+        T::interpolate(self.from, self.to, self.easing.apply(t))
+    }
+
+    /// Returns whether the value is still moving toward its target.
+    pub fn animating(&self) -> bool {
+        self.elapsed_ms < self.duration_ms
+    }
+
+    /// Advances the animation by `dt_ms` milliseconds.
+    pub fn advance(&mut self, dt_ms: u32) {
+        self.elapsed_ms = self.elapsed_ms.saturating_add(dt_ms).min(self.duration_ms);
+    }
+
+    /// Advances the animation by the frame time ([Ui::dt]) and returns the current value.
+    ///
+    /// Without a frame time, the animation finishes right away. While [Animated::animating],
+    /// call [Ui::request_repaint] so that the next frame follows without new input.
+    pub fn update<DRAW, COL>(&mut self, ui: &Ui<DRAW, COL>) -> T
+    where
+        DRAW: DrawTarget<Color = COL>,
+        COL: PixelColor,
+    {
+        self.advance(ui.dt().unwrap_or(u32::MAX));
+        self.value()
+    }
+}
+
+#[cfg(any(feature = "checkbox", feature = "controls"))]
+/// Number of steps of a widget [Transition]
+pub(crate) const TRANSITION_STEPS: u32 = 16;
+
+#[cfg(any(feature = "checkbox", feature = "controls"))]
+/// Marks the smartstates of widgets in a running [Transition]
+const TRANSITION_TAG: u32 = 0x7e4a_5100;
+
+#[cfg(any(feature = "checkbox", feature = "controls"))]
+/// A widget's transition between two looks (e.g. a checkbox being checked).
+///
+/// Widgets are recreated every frame, so the step of a running transition is kept in the
+/// widget's smartstate instead of the usual state. Without a smartstate, or without a frame
+/// time ([Ui::dt]), there's no transition: the widget jumps to its new look.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Transition {
+    step: u32,
+}
+
+#[cfg(any(feature = "checkbox", feature = "controls"))]
+impl Transition {
+    /// Continues the transition of the last frame, whose smartstate was `previous`, by the frame
+    /// time `dt`. If `restart`, a new transition starts instead, in the opposite direction of a
+    /// running one (it is reversed from where it was).
+    pub(crate) fn update(
+        previous: Option<Smartstate>,
+        restart: bool,
+        duration_ms: u32,
+        dt: Option<u32>,
+    ) -> Self {
+        let done = Transition {
+            step: TRANSITION_STEPS,
+        };
+        let Some(dt) = dt.filter(|_| duration_ms > 0) else {
+            return done;
+        };
+        let running = previous.and_then(|previous| {
+            (0..TRANSITION_STEPS).find(|&step| previous == Transition { step }.smartstate())
+        });
+        let step = match (restart, running) {
+            (true, Some(step)) => TRANSITION_STEPS - step,
+            (true, None) => 0,
+            // at least a step per frame, so that it always ends
+            (false, Some(step)) => {
+                step + max(1, dt.saturating_mul(TRANSITION_STEPS).div_ceil(duration_ms))
+            }
+            (false, None) => TRANSITION_STEPS,
+        };
+        Transition {
+            step: step.min(TRANSITION_STEPS),
+        }
+    }
+
+    /// Returns whether the transition is still running. Widgets then use
+    /// [Transition::smartstate] and request a repaint.
+    pub(crate) fn running(&self) -> bool {
+        self.step < TRANSITION_STEPS
+    }
+
+    /// Returns the eased progress, from `0` to [ONE] once it is done.
+    pub(crate) fn progress(&self) -> i32 {
+        Easing::default().apply((self.step * ONE as u32 / TRANSITION_STEPS) as i32)
+    }
+
+    /// Returns the smartstate of the widget while the transition runs.
+    pub(crate) fn smartstate(&self) -> Smartstate {
+        Smartstate::state(TRANSITION_TAG | self.step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    #[test]
+    fn easings_hit_their_ends() {
+        for easing in [Easing::Linear, Easing::EaseInOutCubic] {
+            assert_eq!(easing.apply(-5), 0);
+            assert_eq!(easing.apply(0), 0);
+            assert_eq!(easing.apply(ONE / 2), ONE / 2);
+            assert_eq!(easing.apply(ONE), ONE);
+            assert_eq!(easing.apply(ONE + 5), ONE);
+        }
+        // slow start, monotonic
+        assert!(ease_in_out_cubic(ONE / 4) < ONE / 4);
+        let mut last = 0;
+        for t in (0..=ONE).step_by(97) {
+            assert!(ease_in_out_cubic(t) >= last);
+            last = ease_in_out_cubic(t);
+        }
+    }
+
+    #[test]
+    fn converges_exactly_to_target() {
+        let mut value = Animated::new(Point::new(0, 100), 100);
+        assert!(!value.animating());
+
+        value.set_target(Point::new(-7, 3));
+        let mut last = value.value();
+        for _ in 0..6 {
+            assert!(value.animating());
+            value.advance(17);
+            assert!(value.value().x <= last.x && value.value().y <= last.y);
+            last = value.value();
+        }
+        value.advance(17);
+        assert!(!value.animating());
+        assert_eq!(value.value(), Point::new(-7, 3));
+
+        // retargeting midway continues from the current value
+        let mut value = Animated::new(0u8, 100).with_easing(Easing::Linear);
+        value.set_target(200);
+        value.advance(50);
+        assert_eq!(value.value(), 100);
+        value.set_target(0);
+        assert_eq!(value.value(), 100);
+        value.advance(100);
+        assert_eq!((value.value(), value.animating()), (0, false));
+    }
+
+    #[test]
+    fn update_uses_frame_time() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut value = Animated::new(0i32, 100).with_easing(Easing::Linear);
+
+        value.set_target(1000);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.tick(25);
+        assert_eq!(value.update(&ui), 250);
+
+        // without a frame time, the animation finishes
+        let ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        assert_eq!(value.update(&ui), 1000);
+        assert!(!value.animating());
+    }
+
+    #[cfg(any(feature = "checkbox", feature = "controls"))]
+    #[test]
+    fn transitions_run_in_the_smartstate() {
+        let frame = |previous: Transition, restart: bool| {
+            Transition::update(Some(previous.smartstate()), restart, 160, Some(20))
+        };
+        let started = Transition::update(None, true, 160, Some(20));
+        assert_eq!((started.step, started.progress()), (0, 0));
+
+        // 160ms in 16 steps: 2 steps per 20ms frame
+        let halfway = frame(frame(frame(frame(started, false), false), false), false);
+        assert_eq!(halfway.step, 8);
+        // reversed from where it was
+        assert_eq!(frame(halfway, true).step, 8);
+        let done = (0..8).fold(halfway, |t, _| frame(t, false));
+        assert!(!done.running());
+        assert_eq!(done.progress(), ONE);
+
+        // without a frame time, there's nothing to animate
+        assert!(!Transition::update(None, true, 160, None).running());
+        assert!(!Transition::update(None, true, 0, Some(20)).running());
+    }
+}
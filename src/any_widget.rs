@@ -0,0 +1,174 @@
+//! # Runtime-Chosen Widgets
+//!
+//! [Widget::draw](crate::ui::Widget::draw) is generic over the draw target, so widgets can't be
+//! used as trait objects.
+//! An [AnyWidget] is one of the built-in widgets, chosen at runtime, e.g. for a menu built from
+//! a configuration. A slice of them can be added one by one:
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::any_widget::AnyWidget;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let show_wifi = true;
+//! let mut wifi = false;
+//! let mut widgets: [AnyWidget; 3] = [
+//!     Label::new("Settings").into(),
+//!     match show_wifi {
+//!         true => Checkbox::new(&mut wifi).into(),
+//!         false => AnyWidget::Spacer(Spacer::new(Size::zero())),
+//!     },
+//!     Button::new("Save").into(),
+//! ];
+//! for widget in widgets.iter_mut() {
+//!     if ui.add(widget).clicked() {
+//!         // ...
+//!     }
+//! }
+//! ```
+//!
+//! The widgets keep their static types (and speed) everywhere else; only adding an
+//! [AnyWidget] matches on the variant. Widgets of other crates can be wrapped in an enum of
+//! their own in the same way. This module needs the `any-widget` feature.
+
+use crate::button::Button;
+use crate::checkbox::Checkbox;
+use crate::dropdown::Dropdown;
+use crate::label::{HashLabel, Label};
+use crate::segmented_control::SegmentedControl;
+use crate::segmented_progress::SegmentedProgress;
+use crate::skeleton::Skeleton;
+use crate::slider::Slider;
+use crate::smartstate::Smartstate;
+use crate::spacer::Spacer;
+use crate::spinner::Spinner;
+use crate::toggle_button::ToggleButton;
+use crate::toggle_switch::ToggleSwitch;
+use crate::ui::{GuiResult, Response, TypedWidget, Ui};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888};
+
+/// Defines [AnyWidget] with a variant and a [From] implementation for each widget
+macro_rules! any_widget {
+    ($($variant:ident($widget:ty),)*) => {
+        /// One of the built-in widgets, see the [module documentation](self).
+        pub enum AnyWidget<'a> {
+            $(
+                #[doc = concat!("A [", stringify!($variant), "]")]
+                $variant($widget),
+            )*
+        }
+
+        $(
+            impl<'a> From<$widget> for AnyWidget<'a> {
+                fn from(widget: $widget) -> Self {
+                    AnyWidget::$variant(widget)
+                }
+            }
+        )*
+
+        impl<COL> TypedWidget<COL> for AnyWidget<'_>
+        where
+            COL: PixelColor + Into<Rgb888> + From<Rgb888>,
+        {
+            fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+                &mut self,
+                ui: &mut Ui<DRAW, COL>,
+            ) -> GuiResult<Response> {
+                match self {
+                    $(AnyWidget::$variant(widget) => widget.draw_typed(ui),)*
+                }
+            }
+
+            fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+                match self {
+                    $(AnyWidget::$variant(widget) => TypedWidget::<COL>::smartstate_mut(widget),)*
+                }
+            }
+        }
+    };
+}
+
+any_widget! {
+    Button(Button<'a>),
+    Checkbox(Checkbox<'a>),
+    Dropdown(Dropdown<'a>),
+    HashLabel(HashLabel<'a>),
+    Label(Label<'a>),
+    SegmentedControl(SegmentedControl<'a>),
+    SegmentedProgress(SegmentedProgress<'a>),
+    Skeleton(Skeleton<'a>),
+    Slider(Slider<'a>),
+    Spacer(Spacer),
+    Spinner(Spinner<'a>),
+    ToggleButton(ToggleButton<'a>),
+    ToggleSwitch(ToggleSwitch<'a>),
+}
+
+/// Adding a borrowed [AnyWidget] draws it, e.g. for adding the widgets of a slice one by one.
+impl<COL> TypedWidget<COL> for &mut AnyWidget<'_>
+where
+    COL: PixelColor + Into<Rgb888> + From<Rgb888>,
+{
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        (**self).draw_typed(ui)
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        TypedWidget::<COL>::smartstate_mut(&mut **self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use crate::ui::Interaction;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::Size;
+
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        checked: &mut bool,
+        smartstate: &mut Smartstate,
+        interaction: Interaction,
+    ) -> [Response; 3] {
+        let mut widgets: [AnyWidget; 3] = [
+            Label::new("x").smartstate(smartstate).into(),
+            Spacer::new(Size::new(0, 5)).into(),
+            Checkbox::new(checked).into(),
+        ];
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        ui.interact(interaction);
+        widgets.each_mut().map(|widget| ui.add(widget))
+    }
+
+    #[test]
+    fn any_widgets_draw_like_the_widgets_they_are() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut checked = false;
+        let mut smartstate = Smartstate::empty();
+
+        let [label, spacer, checkbox] = frame(
+            &mut display,
+            &mut checked,
+            &mut smartstate,
+            Interaction::None,
+        );
+        assert!(spacer.area().top_left.y > label.area().bottom_right().unwrap().y);
+        assert_eq!(smartstate.drawn_area(), Some(label.area()));
+
+        // the checkbox is toggled through the enum
+        let release = Interaction::Release(checkbox.area().center());
+        let [_, _, checkbox] = frame(&mut display, &mut checked, &mut smartstate, release);
+        assert!(checkbox.changed && checked);
+    }
+}
@@ -0,0 +1,296 @@
+//! # Attitude Indicator Widget
+//!
+//! An [AttitudeIndicator] (or artificial horizon) shows the pitch and roll of e.g. a drone: a
+//! disc split into sky and ground at the horizon, which tilts with the roll and moves down as
+//! the nose pitches up, under a fixed aircraft symbol. Ladder lines mark every 10° of pitch.
+//!
+//! This module is only available with the `instruments` feature (on by default).
+//!
+//! The disc is drawn row by row (see [circle_span]), with one rectangle of sky and one of
+//! ground per row, using the integer tables of the [trig](crate::trig) module. The disc shows
+//! ±30° of pitch from the center to the rim.
+//!
+//! As sky and ground have colors of their own (see [AttitudeIndicator::colors]), an attitude
+//! indicator works in [Ui]s of color types that can be converted from [Rgb888]
+//! (see [TypedWidget]).
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! # let (pitch, roll) = (4.2, -12.5);
+//! ui.add(
+//!     AttitudeIndicator::new(pitch, roll)
+//!         .diameter(101)
+//!         .smartstate(smartstates.nxt()),
+//! );
+//! ```
+//!
+//! With a smartstate, the indicator is only redrawn when the pitch or the roll change by a
+//! [step](AttitudeIndicator::step), a degree by default.
+
+use crate::smartstate::{Container, Smartstate};
+use crate::trig::{circle_span, cos, sin, ONE};
+use crate::ui::{GuiError, GuiResult, Response, TypedWidget, Ui};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888, RgbColor};
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+
+/// Degrees of pitch from the center of the disc to its rim
+const PITCH_RANGE: i32 = 30;
+/// The default sky color
+const SKY: Rgb888 = Rgb888::new(40, 120, 220);
+/// The default ground color
+const GROUND: Rgb888 = Rgb888::new(140, 85, 40);
+/// The color of the aircraft symbol
+const AIRCRAFT: Rgb888 = Rgb888::new(255, 200, 0);
+
+/// An artificial horizon, see the [module documentation](self).
+pub struct AttitudeIndicator<'a> {
+    pitch: f32,
+    roll: f32,
+    diameter: Option<u32>,
+    step: u32,
+    colors: (Rgb888, Rgb888),
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<'a> AttitudeIndicator<'a> {
+    /// Creates an attitude indicator for `pitch` (positive with the nose up) and `roll`
+    /// (positive with the right wing down), in degrees.
+    pub fn new(pitch: f32, roll: f32) -> Self {
+        Self {
+            pitch,
+            roll,
+            diameter: None,
+            step: 1,
+            colors: (SKY, GROUND),
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Sets the diameter of the disc, four times the style's default widget height by default.
+    /// Odd diameters center the disc on a pixel.
+    pub fn diameter(mut self, diameter: u32) -> Self {
+        self.diameter = Some(diameter);
+        self
+    }
+
+    /// Sets the step the pitch and the roll are rounded to, in whole degrees (1 by default).
+    /// Larger steps redraw less often, e.g. for noisy sensors.
+    pub fn step(mut self, degrees: u32) -> Self {
+        self.step = degrees.max(1);
+        self
+    }
+
+    /// Sets the colors of the sky and the ground, blue and brown by default.
+    pub fn colors(mut self, sky: Rgb888, ground: Rgb888) -> Self {
+        self.colors = (sky, ground);
+        self
+    }
+
+    /// Adds a smartstate, so that the indicator is only redrawn when the attitude changes.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+/// Returns `degrees` rounded to the nearest multiple of `step`
+fn round_to(degrees: f32, step: u32) -> i32 {
+    let steps = degrees / step as f32;
+    let rounded = match steps < 0.0 {
+        true => steps - 0.5,
+        false => steps + 0.5,
+    };
+    rounded as i32 * step as i32
+}
+
+impl<COL: PixelColor + From<Rgb888>> TypedWidget<COL> for AttitudeIndicator<'_> {
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        // get size
+        let diameter = self
+            .diameter
+            .unwrap_or(4 * ui.style().default_widget_height)
+            .max(9);
+
+        // allocate space
+        let iresponse = ui.allocate_space(Size::new_equal(diameter))?;
+        let area = iresponse.area;
+
+        // check smartstate
+        let pitch = round_to(self.pitch, self.step).clamp(-90, 90);
+        let roll = round_to(self.roll, self.step).rem_euclid(360);
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(&(pitch, roll, diameter, self.colors));
+        state.mix(ui.style_key());
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(area);
+        self.smartstate.modify(|st| *st = state);
+        if !redraw {
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+
+        // the ground is where (dx, dy) · (sin roll, cos roll) > offset, the distance the
+        // horizon moved down from the center
+        let radius = (diameter as i32 - 1) / 2;
+        let center = area.top_left + Point::new_equal(radius);
+        let (s, c) = (sin(roll), cos(roll));
+        let offset = pitch * radius / PITCH_RANGE;
+        let (sky, ground) = (COL::from(self.colors.0), COL::from(self.colors.1));
+        let background = ui.style().background_color;
+
+        ui.start_drawing(&area);
+        let cleared = ui.cleared();
+        let fill = |ui: &mut Ui<DRAW, COL>, y: i32, from: i32, to: i32, color: COL| {
+            if from > to {
+                return Ok(());
+            }
+            let row = Rectangle::with_corners(Point::new(from, y), Point::new(to, y));
+            ui.draw(&row.into_styled(PrimitiveStyle::with_fill(color)))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw AttitudeIndicator")))
+        };
+        let (left, right) = (area.top_left.x, area.top_left.x + diameter as i32 - 1);
+        for y in area.top_left.y..area.top_left.y + diameter as i32 {
+            let Some((first, last)) = circle_span(center, radius, y) else {
+                if !cleared {
+                    fill(ui, y, left, right, background)?;
+                }
+                continue;
+            };
+            if !cleared {
+                fill(ui, y, left, first - 1, background)?;
+                fill(ui, y, last + 1, right, background)?;
+            }
+            // dx * s > rest on the ground
+            let rest = offset * ONE - (y - center.y) * c;
+            let (sky_from, sky_to, ground_from, ground_to) = match s {
+                0 if rest < 0 => (1, 0, first, last),
+                0 => (first, last, 1, 0),
+                s if s > 0 => {
+                    let split = center.x + rest.div_euclid(s);
+                    (first, split.min(last), (split + 1).max(first), last)
+                }
+                s => {
+                    let split = center.x - rest.div_euclid(-s) - 1;
+                    ((split + 1).max(first), last, first, split.min(last))
+                }
+            };
+            fill(ui, y, sky_from, sky_to, sky)?;
+            fill(ui, y, ground_from, ground_to, ground)?;
+        }
+
+        // the pitch ladder, parallel to the horizon, where it fits into the disc
+        let ladder = PrimitiveStyle::with_stroke(COL::from(Rgb888::WHITE), 1);
+        for degrees in (-PITCH_RANGE..=PITCH_RANGE).step_by(10).filter(|&d| d != 0) {
+            let distance = offset - degrees * radius / PITCH_RANGE;
+            let half = match degrees % 20 {
+                0 => radius / 6,
+                _ => radius / 4,
+            };
+            if distance.abs() + half >= radius {
+                continue;
+            }
+            let at = |along: i32| {
+                center
+                    + Point::new(
+                        (distance * s + along * c) / ONE,
+                        (distance * c - along * s) / ONE,
+                    )
+            };
+            ui.draw(&Line::new(at(-half), at(half)).into_styled(ladder))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw AttitudeIndicator ladder")))?;
+        }
+
+        // the aircraft symbol: wings on both sides of a dot in the center
+        let aircraft = PrimitiveStyle::with_fill(COL::from(AIRCRAFT));
+        let (inner, outer) = (radius / 6, radius / 2);
+        let symbol = [
+            Rectangle::with_corners(
+                center + Point::new(-outer, 0),
+                center + Point::new(-inner, 1),
+            ),
+            Rectangle::with_corners(center + Point::new(inner, 0), center + Point::new(outer, 1)),
+            Rectangle::with_center(center, Size::new_equal(3)),
+        ];
+        for part in symbol {
+            ui.draw(&part.into_styled(aircraft))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw AttitudeIndicator symbol")))?;
+        }
+        ui.finalize()?;
+
+        Ok(Response::new(iresponse).set_redraw(true))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// Draws an indicator of 55 pixels (a radius of 27), returning its area and whether it was
+    /// redrawn
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        smartstate: &mut Smartstate,
+        pitch: f32,
+        roll: f32,
+    ) -> (Rectangle, bool) {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        let indicator = AttitudeIndicator::new(pitch, roll).diameter(55);
+        let response = ui.add(indicator.smartstate(smartstate));
+        (response.area(), response.redraw)
+    }
+
+    #[test]
+    fn the_horizon_tilts_and_moves_with_the_attitude() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+        let (sky, ground) = (Rgb565::from(SKY), Rgb565::from(GROUND));
+        let background = medsize_rgb565_style().background_color;
+
+        let (area, redrawn) = frame(&mut display, &mut smartstate, 0.0, 0.0);
+        assert!(redrawn);
+        let center = area.top_left + Point::new_equal(27);
+        let at = |display: &MockDisplay<Rgb565>, x: i32, y: i32| {
+            display.get_pixel(center + Point::new(x, y))
+        };
+        // level, with ladder lines 9 pixels per 10°
+        assert_eq!(at(&display, -20, -1), Some(sky));
+        assert_eq!(at(&display, -20, 1), Some(ground));
+        assert_eq!(at(&display, 0, -9), Some(Rgb565::WHITE));
+        assert_eq!(at(&display, 0, 9), Some(Rgb565::WHITE));
+        assert_eq!(at(&display, -27, -27), Some(background));
+
+        // less than half a degree doesn't redraw
+        assert!(!frame(&mut display, &mut smartstate, 0.3, -0.4).1);
+
+        // nose up: the horizon is 9 pixels lower
+        frame(&mut display, &mut smartstate, 10.0, 0.0);
+        assert_eq!(at(&display, -20, 8), Some(sky));
+        assert_eq!(at(&display, -20, 10), Some(ground));
+
+        // rolled right, the ground is on the right
+        frame(&mut display, &mut smartstate, 0.0, 90.0);
+        assert_eq!(at(&display, -5, -20), Some(sky));
+        assert_eq!(at(&display, 5, -20), Some(ground));
+        frame(&mut display, &mut smartstate, 0.0, -45.0);
+        assert_eq!(at(&display, 10, -12), Some(sky));
+        assert_eq!(at(&display, -12, 10), Some(ground));
+    }
+}
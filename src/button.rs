@@ -2,17 +2,23 @@
 //!
 //! See [Button] for more info.
 
-use crate::smartstate::{Container, Smartstate};
-use crate::ui::{GuiResult, Interaction, Response, Ui, Widget};
+use crate::format::Buf;
+use crate::hidden::Visibility;
+use crate::smartstate::{Container, Slot, Smartstate};
+use crate::style::WidgetContext;
+use crate::text::{
+    ellipsize, lines, measure_lines, text_layout, Font, RenderedText, TextRenderer, ELLIPSIZED_LEN,
+};
+use crate::ui::{
+    resolve_widget_style, rounded_rect_clamped, GuiError, GuiResult, HorizontalAlign, Interaction,
+    PointerState, RelativeWidth, Response, TypedWidget, Ui,
+};
 use core::cmp::max;
-use core::ops::Add;
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::{Point, Size};
-use embedded_graphics::mono_font::MonoTextStyle;
-use embedded_graphics::pixelcolor::PixelColor;
-use embedded_graphics::prelude::*;
-use embedded_graphics::primitives::{PrimitiveStyleBuilder, Rectangle, RoundedRectangle};
-use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888};
+use embedded_graphics::primitives::Primitive;
+use embedded_graphics::transform::Transform;
 
 /// # Button Widget
 ///
@@ -32,20 +38,13 @@ use embedded_graphics::text::{Baseline, Text};
 /// ```no_run
 /// # use embedded_graphics::pixelcolor::Rgb565;
 /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-/// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-/// # use kolibri_embedded_gui::ui::Ui;
+/// # use kolibri_embedded_gui::prelude::*;
 /// # use embedded_graphics::prelude::*;
 /// # use embedded_graphics::primitives::Rectangle;
-/// # use embedded_iconoir::prelude::*;
-/// # use embedded_iconoir::size12px;
-/// # use kolibri_embedded_gui::ui::*;
-/// # use kolibri_embedded_gui::label::*;
-/// # use kolibri_embedded_gui::smartstate::*;
 /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 /// # let output_settings = OutputSettingsBuilder::new().build();
 /// # let mut window = Window::new("Kolibri Example", &output_settings);
 /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-/// # use kolibri_embedded_gui::button::Button;
 ///
 /// // Create a basic button
 /// if ui.add(Button::new("Click me!")).clicked() {
@@ -64,6 +63,15 @@ use embedded_graphics::text::{Baseline, Text};
 /// }
 /// ```
 ///
+/// # Multi-line Labels
+/// Labels can span multiple lines, either split explicitly with `\n` or wrapped at spaces
+/// with [Button::wrap]. Each line is centered, and the button grows to fit all lines (but is
+/// at least [default_widget_height](crate::style::Style::default_widget_height) high).
+///
+/// # Repeat on Hold
+/// With [Button::repeat], a held button keeps firing `clicked()` at a fixed interval
+/// (e.g. for jog buttons), instead of firing once on release.
+///
 /// # Visual States
 /// Buttons have three visual states that provide user feedback:
 /// 1. Normal - Default appearance with standard border and background
@@ -76,10 +84,164 @@ use embedded_graphics::text::{Baseline, Text};
 /// - Background colors (normal, highlighted, and pressed)
 /// - Text color and font
 /// - Padding and spacing
-pub struct Button<'a> {
-    label: &'a str,
+pub struct Button<'a, T = &'a str> {
+    label: T,
     smartstate: Container<'a, Smartstate>,
     corner_radius: Option<u32>,
+    repeat: Option<Repeat<'a>>,
+    wrap: Option<u32>,
+    transparent: bool,
+    truncate: bool,
+    max_width: Option<u32>,
+    width: Option<RelativeWidth>,
+    context: WidgetContext,
+}
+
+/// Persistent state for the repeat-on-hold behavior of [Button::repeat] and
+/// [IconButton::repeat](crate::iconbutton::IconButton::repeat).
+///
+/// One per repeating button, see [retained state](Smartstate#retained-state).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepeatState {
+    held: bool,
+    pressed_at: Option<u64>,
+    fired: u32,
+}
+
+impl RepeatState {
+    /// Creates a new [RepeatState] for a button that isn't held.
+    pub const fn new() -> Self {
+        Self {
+            held: false,
+            pressed_at: None,
+            fired: 0,
+        }
+    }
+}
+
+impl RepeatState {
+    /// Reads the state from a [Slot]: the clicks fired in its count, the press in its timer.
+    fn load(slot: &Slot, now: Option<u64>) -> Self {
+        Self {
+            held: slot.count() > 0,
+            pressed_at: now.and_then(|now| Some(now.saturating_sub(slot.timer(now)?.into()))),
+            fired: slot.count(),
+        }
+    }
+
+    /// Writes the state to a [Slot], see [RepeatState::load].
+    fn save(&self, slot: &mut Slot) {
+        slot.set_count(self.fired);
+        match self.pressed_at {
+            Some(pressed_at) => slot.start_timer(pressed_at),
+            None => slot.stop_timer(),
+        }
+    }
+}
+
+/// Where a [Repeat] keeps its state across frames
+enum RepeatStore<'a> {
+    State(&'a mut RepeatState),
+    Slot(&'a mut Slot),
+}
+
+/// Repeat-on-hold configuration of a single widget, shared by [Button] and
+/// [IconButton](crate::iconbutton::IconButton).
+pub(crate) struct Repeat<'a> {
+    store: RepeatStore<'a>,
+    initial_delay_ms: u32,
+    interval_ms: u32,
+}
+
+impl<'a> Repeat<'a> {
+    pub(crate) fn new(state: &'a mut RepeatState, initial_delay_ms: u32, interval_ms: u32) -> Self {
+        Self {
+            store: RepeatStore::State(state),
+            initial_delay_ms,
+            interval_ms,
+        }
+    }
+
+    /// Creates the configuration keeping its state in a [Slot] of a
+    /// [StateStore](crate::smartstate::StateStore).
+    pub(crate) fn in_slot(slot: &'a mut Slot, initial_delay_ms: u32, interval_ms: u32) -> Self {
+        Self {
+            store: RepeatStore::Slot(slot),
+            initial_delay_ms,
+            interval_ms,
+        }
+    }
+
+    /// Returns whether the button is held with a frame time, so that further clicks will
+    /// fire without any new input.
+    pub(crate) fn waiting(&self) -> bool {
+        match &self.store {
+            RepeatStore::State(state) => state.held && state.pressed_at.is_some(),
+            RepeatStore::Slot(slot) => slot.count() > 0 && slot.timer_running(),
+        }
+    }
+
+    /// Advances the repeat state with this frame's interaction and returns whether
+    /// the widget should report a click.
+    ///
+    /// A press fires immediately. While held, another click fires after the initial delay
+    /// and then once per interval (at most once per frame). Without a frame time
+    /// (see [Ui::begin_frame]), only the initial click fires.
+    pub(crate) fn update(&mut self, interaction: Interaction, now: Option<u64>) -> bool {
+        let mut state = match &self.store {
+            RepeatStore::State(state) => **state,
+            RepeatStore::Slot(slot) => RepeatState::load(slot, now),
+        };
+        let click = self.step(&mut state, interaction, now);
+        match &mut self.store {
+            RepeatStore::State(stored) => **stored = state,
+            RepeatStore::Slot(slot) => state.save(slot),
+        }
+        click
+    }
+
+    /// Advances `state`, see [Repeat::update].
+    fn step(&self, state: &mut RepeatState, interaction: Interaction, now: Option<u64>) -> bool {
+        match interaction {
+            Interaction::Click(_) if !state.held => {
+                *state = RepeatState {
+                    held: true,
+                    pressed_at: now,
+                    fired: 1,
+                };
+                true
+            }
+            Interaction::Click(_) | Interaction::Drag(_) if state.held => {
+                let (Some(pressed_at), Some(now)) = (state.pressed_at, now) else {
+                    return false;
+                };
+                let elapsed = now.saturating_sub(pressed_at);
+                let due = if elapsed < self.initial_delay_ms as u64 {
+                    1
+                } else {
+                    let repeats =
+                        (elapsed - self.initial_delay_ms as u64) / max(self.interval_ms, 1) as u64;
+                    2u32.saturating_add(repeats.try_into().unwrap_or(u32::MAX))
+                };
+                if due > state.fired {
+                    state.fired = due;
+                    true
+                } else {
+                    false
+                }
+            }
+            // activated without a press
+            Interaction::ActivatedByFocus(_) => {
+                *state = RepeatState::new();
+                true
+            }
+            // released, or dragged off the widget
+            _ => {
+                *state = RepeatState::new();
+                false
+            }
+        }
+    }
 }
 
 impl<'a> Button<'a> {
@@ -91,13 +253,109 @@ impl<'a> Button<'a> {
     /// # Returns
     /// A new Button instance with the specified label and no smartstate
     pub fn new(label: &'a str) -> Button<'a> {
+        Button::owned(label)
+    }
+}
+
+impl<'a, T: AsRef<str>> Button<'a, T> {
+    /// Creates a button owning its label, e.g. a [Buf] formatted within the frame, which
+    /// doesn't have to outlive the button like with [Button::new].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::format::Buf;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let unread = 3;
+    /// ui.add(Button::owned(Buf::<16>::from_fmt(format_args!("Inbox ({})", unread))));
+    /// ```
+    pub fn owned(label: T) -> Self {
         Button {
             label,
             smartstate: Container::empty(),
             corner_radius: None,
+            repeat: None,
+            wrap: None,
+            transparent: false,
+            truncate: false,
+            max_width: None,
+            width: None,
+            context: WidgetContext::Normal,
         }
     }
 
+    /// Wraps the label at spaces so that no line is wider than `max_width`.
+    ///
+    /// Explicit line breaks (`\n`) in the label are kept, with or without wrapping.
+    ///
+    /// # Arguments
+    /// * `max_width` - The maximum width of a line of text (without padding), in pixels
+    ///
+    /// # Returns
+    /// Self with wrapping enabled
+    pub fn wrap(mut self, max_width: u32) -> Self {
+        self.wrap = Some(max_width);
+        self
+    }
+
+    /// Cuts off a label that makes the button wider than the rest of the row, ending it with an
+    /// ellipsis ("Temperature se…"), instead of failing to add the button.
+    ///
+    /// The visible text is part of the smartstate. This has no effect on wrapped labels
+    /// (see [Button::wrap]), use [Button::max_width] to limit their width.
+    ///
+    /// # Returns
+    /// Self with truncation enabled
+    pub fn truncate(mut self) -> Self {
+        self.truncate = true;
+        self
+    }
+
+    /// Truncates the label like [Button::truncate], so that the whole button is at most
+    /// `max_width` wide (or as wide as the rest of the row, if that is narrower).
+    ///
+    /// # Arguments
+    /// * `max_width` - The maximum width of the button, including padding and border
+    ///
+    /// # Returns
+    /// Self with truncation enabled
+    pub fn max_width(mut self, max_width: u32) -> Self {
+        self.truncate = true;
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Makes the button (at least) `per_mille` of the row wide, e.g. `500` for half of it.
+    ///
+    /// The width is resolved with [Ui::resolve_width] when the button is drawn, so buttons
+    /// added with [Ui::add_horizontal] whose shares add up to 1000 fit into the row, spacing
+    /// included. A truncated label (see [Button::truncate]) is cut off at the button's share.
+    ///
+    /// # Arguments
+    /// * `per_mille` - The share of the row, in per mille
+    ///
+    /// # Returns
+    /// Self with the relative width
+    pub fn width_fraction(mut self, per_mille: u16) -> Self {
+        self.width = Some(RelativeWidth::PerMille(per_mille));
+        self
+    }
+
+    /// Makes the button (at least) as wide as the rest of the row, e.g. for the last button
+    /// of a row.
+    ///
+    /// # Returns
+    /// Self filling the row
+    pub fn fill_width(mut self) -> Self {
+        self.width = Some(RelativeWidth::Fill);
+        self
+    }
+
     /// Adds smartstate support to the button for incremental redrawing.
     ///
     /// When a smartstate is provided, the button will only redraw when its visual state changes,
@@ -116,6 +374,8 @@ impl<'a> Button<'a> {
     /// Sets a custom corner radius for the button.
     ///
     /// If not specified, the button will use the corner radius from the UI style.
+    /// Radii larger than half of the button's smaller dimension are clamped, so
+    /// `u32::MAX` gives a pill-shaped button.
     ///
     /// # Arguments
     /// * `radius` - The corner radius in pixels
@@ -126,43 +386,162 @@ impl<'a> Button<'a> {
         self.corner_radius = Some(radius);
         self
     }
+
+    /// Sets the role of the button in its screen, e.g. [WidgetContext::Primary] for the main
+    /// action, which is filled with the primary color. The default is [WidgetContext::Normal].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.add_horizontal(Button::new("Cancel"));
+    /// ui.add_horizontal(Button::new("Save").context(WidgetContext::Primary));
+    /// ```
+    pub fn context(mut self, context: WidgetContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Draws the button without filling its background (and without a shadow), so that e.g.
+    /// a background image shows through. The border is still drawn.
+    ///
+    /// Like with [Label::transparent_background](crate::label::Label::transparent_background),
+    /// a button with a smartstate waits for the application to restore the background
+    /// before redrawing (see [Ui::stale_area]).
+    pub fn transparent_background(mut self) -> Self {
+        self.transparent = true;
+        self
+    }
+
+    /// Makes the button repeat while it is held, e.g. for jog buttons.
+    ///
+    /// Instead of firing on release, `clicked()` fires once when the button is pressed,
+    /// again after `initial_delay_ms`, and then every `interval_ms` for as long as the
+    /// button stays pressed. Releasing the button or dragging off of it stops the repetition.
+    ///
+    /// The timing uses the frame time set with [Ui::begin_frame]. If no frame time is set,
+    /// the button only fires once per press.
+    ///
+    /// # Arguments
+    /// * `state` - The [RepeatState] tracking the hold across frames
+    /// * `initial_delay_ms` - The delay before the first repetition
+    /// * `interval_ms` - The interval between subsequent repetitions
+    ///
+    /// # Returns
+    /// Self with repeat-on-hold enabled
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::button::RepeatState;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// let start = std::time::Instant::now();
+    /// let mut jog_left = RepeatState::new();
+    /// let mut position = 0i32;
+    ///
+    /// loop {
+    ///     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ///     ui.begin_frame(start.elapsed().as_millis() as u64);
+    ///     // [...]
+    ///     if ui.add(Button::new("<").repeat(&mut jog_left, 500, 100)).clicked() {
+    ///         position -= 1;
+    ///     }
+    /// }
+    /// ```
+    pub fn repeat(
+        mut self,
+        state: &'a mut RepeatState,
+        initial_delay_ms: u32,
+        interval_ms: u32,
+    ) -> Self {
+        self.repeat = Some(Repeat::new(state, initial_delay_ms, interval_ms));
+        self
+    }
+
+    /// Makes the button repeat while it is held, like [Button::repeat], keeping the hold in a
+    /// [Slot] of a [StateStore](crate::smartstate::StateStore) instead of a [RepeatState].
+    ///
+    /// The slot's count and timer are used.
+    pub fn repeat_in(
+        mut self,
+        slot: &'a mut Slot,
+        initial_delay_ms: u32,
+        interval_ms: u32,
+    ) -> Self {
+        self.repeat = Some(Repeat::in_slot(slot, initial_delay_ms, interval_ms));
+        self
+    }
 }
 
-impl Widget for Button<'_> {
-    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+impl<T: AsRef<str>> Visibility for Button<'_, T> {}
+
+impl<T: AsRef<str>, COL> TypedWidget<COL> for Button<'_, T>
+where
+    COL: PixelColor + Into<Rgb888> + From<Rgb888>,
+{
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
         &mut self,
         ui: &mut Ui<DRAW, COL>,
     ) -> GuiResult<Response> {
         // get size
-        let font = ui.style().default_font;
-
-        let mut text = Text::new(
-            self.label,
-            Point::new(0, 0),
-            MonoTextStyle::new(&font, ui.style().text_color),
-        );
+        let font = Font::resolve(None, ui.style());
 
         let height = ui.style().default_widget_height;
-        let size = text.bounding_box();
-        let padding = ui.style().spacing.button_padding;
+        let padding = ui.style().widget_padding(ui.style().spacing.button_padding);
         let border = ui.style().border_width;
+        let shadow = ui.style().shadow_size();
+        let min_width = self.width.map(|width| ui.resolve_width(width));
 
-        // allocate space
-        let iresponse = ui.allocate_space(Size::new(
-            size.size.width + 2 * padding.width + 2 * border,
-            max(size.size.height + 2 * padding.height + 2 * border, height),
-        ))?;
+        // the part of the label that fits
+        let mut ellipsized = Buf::<ELLIPSIZED_LEN>::new();
+        let label = match self.truncate && self.wrap.is_none() {
+            true => {
+                let chrome = 2 * padding.width + 2 * border + shadow.width;
+                let max_width = self.max_width.or(min_width).unwrap_or(u32::MAX);
+                let max_width = max_width.min(ui.remaining_width()).saturating_sub(chrome);
+                ellipsize(&font, self.label.as_ref(), max_width, &mut ellipsized)
+            }
+            false => self.label.as_ref(),
+        };
+        let size = measure_lines(&font, label, self.wrap);
 
-        // move text
-        text.translate_mut(iresponse.area.top_left.add(Point::new(
-            (padding.width + border) as i32,
-            (padding.height + border) as i32,
-        )));
+        // allocate space
+        let width = max(
+            size.width + 2 * padding.width + 2 * border,
+            min_width.unwrap_or(0).saturating_sub(shadow.width),
+        );
+        let mut iresponse = ui.allocate_space(
+            Size::new(
+                width,
+                max(size.height + 2 * padding.height + 2 * border, height),
+            ) + shadow,
+        )?;
+        if iresponse.interaction == Interaction::None {
+            // a press that started on the button survives jitter around its edge
+            iresponse.interaction = ui.check_held_press(iresponse.area);
+        }
+        let body = ui.style().without_shadow(iresponse.area);
 
-        text.text_style.baseline = Baseline::Top;
+        // the box the text block is centered in (in case more space was allocated)
+        let pressed = iresponse.interaction.is_pressed();
+        let text_box = body.translate(ui.style().pressed_offset_for(pressed));
 
         // check for click
-        let click = matches!(iresponse.interaction, Interaction::Release(_));
+        let click = match self.repeat.as_mut() {
+            Some(repeat) => repeat.update(iresponse.interaction, ui.now()),
+            None => iresponse.interaction.is_release(),
+        };
+        if self.repeat.as_ref().is_some_and(|repeat| repeat.waiting()) {
+            // the next repeat is due without a new interaction
+            ui.request_repaint();
+        }
         let down = matches!(
             iresponse.interaction,
             Interaction::Click(_) | Interaction::Drag(_)
@@ -171,52 +550,487 @@ impl Widget for Button<'_> {
         // styles and smartstate
         let prevstate = self.smartstate.clone_inner();
 
-        let rect_style = match iresponse.interaction {
-            Interaction::None => {
-                self.smartstate.modify(|st| *st = Smartstate::state(1));
+        let (mut rect_style, state) = resolve_widget_style(ui.style(), iresponse.interaction);
+        self.context.apply(&mut rect_style, ui.style());
+        // the visible text is part of the state, so changing the label redraws the button
+        let look = |bucket: u8| (bucket, label, self.wrap, self.context);
+        let style_key = ui.style_key();
+        let previous_pointer = PointerState::previous(prevstate, look, style_key);
+        let pointer = PointerState::of(iresponse.interaction);
+        self.smartstate
+            .modify(|st| *st = pointer.smartstate(&look(state), style_key));
 
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().border_color)
-                    .stroke_width(ui.style().border_width)
-                    .fill_color(ui.style().item_background_color)
-                    .build()
-            }
-            Interaction::Hover(_) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(2));
-
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-                    .fill_color(ui.style().highlight_item_background_color)
-                    .build()
+        let mut redraw = !PointerState::same_look(prevstate, &look(state), style_key)
+            || ui.invalidated(iresponse.area);
+        if redraw && self.transparent {
+            rect_style.fill_color = None;
+            if let Some(prevstate) = prevstate {
+                if !ui.redraw_transparent(iresponse.area) {
+                    // keep the previous state until the background is restored
+                    self.smartstate.modify(|st| *st = prevstate);
+                    redraw = false;
+                }
             }
+        }
 
-            _ => {
-                self.smartstate.modify(|st| *st = Smartstate::state(3));
+        if redraw {
+            trace!(
+                "Button smartstate: {} -> {}",
+                prevstate,
+                self.smartstate.clone_inner()
+            );
+            ui.start_drawing(&iresponse.area);
+
+            let corner_radius = ui.style().corner_radius_for(self.corner_radius, body.size);
+            let rounded_rect = rounded_rect_clamped(body, corner_radius);
 
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-                    .fill_color(ui.style().primary_color)
-                    .build()
+            if self.transparent {
+                ui.draw(&rounded_rect.into_styled(rect_style))
+            } else if state == 0 && !self.context.is_primary() {
+                // only the normal look has the item background, it may be a gradient
+                let fill = ui.style().item_background_color;
+                ui.draw_background_fill(rounded_rect, fill, rect_style)
+            } else {
+                ui.draw_background(rounded_rect, rect_style)
+            }
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw Button")))?;
+            let line_height = font.line_height();
+            for (i, line) in lines(&font, label, self.wrap).enumerate() {
+                // each line is centered horizontally, the block vertically
+                let line_size = Size::new(font.measure(line).width, size.height);
+                let text = RenderedText::new(
+                    &font,
+                    line,
+                    text_layout(line_size, text_box, HorizontalAlign::Center)
+                        + Point::new(0, (i as u32 * line_height) as i32),
+                    ui.style().text_color,
+                );
+                ui.draw(&text)
+                    .map_err(|_| GuiError::DrawError(Some("Couldn't draw Button label")))?;
             }
+
+            ui.finalize()?;
+        }
+
+        Ok(Response::new(iresponse)
+            .set_clicked(click)
+            .set_down(down)
+            .set_previous_pointer(previous_pointer))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_script::InputScript;
+    use crate::style::medsize_rgb565_style;
+    use crate::ui::UiState;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
+
+    const INSIDE: Point = Point::new(10, 10);
+    const OUTSIDE: Point = Point::new(60, 60);
+
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        state: &mut RepeatState,
+        now: Option<u64>,
+        interaction: Interaction,
+    ) -> Response {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        if let Some(now) = now {
+            ui.begin_frame(now);
+        }
+        ui.interact(interaction);
+        ui.add(Button::new("+").repeat(state, 500, 200))
+    }
+
+    fn display() -> MockDisplay<Rgb565> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        display
+    }
+
+    /// Plays `script` on a button (repeating if `repeat` is given), returning the timestamp,
+    /// `clicked()` and `down()` of every frame
+    fn play<const N: usize>(
+        script: InputScript<N>,
+        mut repeat: Option<&mut RepeatState>,
+    ) -> heapless::Vec<(u64, bool, bool), 32> {
+        let mut display = display();
+        let mut frames = heapless::Vec::new();
+        for frame in script {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            frame.apply(&mut ui);
+            let response = match repeat.as_deref_mut() {
+                Some(state) => ui.add(Button::new("+").repeat(state, 500, 200)),
+                None => ui.add(Button::new("+")),
+            };
+            frames
+                .push((frame.now_ms, response.clicked(), response.down()))
+                .unwrap();
+        }
+        frames
+    }
+
+    fn clicks(frames: &[(u64, bool, bool)]) -> heapless::Vec<u64, 32> {
+        frames
+            .iter()
+            .filter(|(_, clicked, _)| *clicked)
+            .map(|(now, _, _)| *now)
+            .collect()
+    }
+
+    #[test]
+    fn padding_changes_the_size() {
+        let size = |padding| {
+            let mut display = display();
+            let mut style = medsize_rgb565_style();
+            style.spacing.button_padding = padding;
+            Ui::new_fullscreen(&mut display, style).measure_widget(&mut Button::new("+"))
         };
+        let (small, large) = (size(Size::new(2, 2)), size(Size::new(12, 24)));
+        assert_eq!(large.width, small.width + 20);
+        assert!(large.height > small.height);
+    }
 
-        if !self.smartstate.eq_option(&prevstate) {
-            ui.start_drawing(&iresponse.area);
+    #[test]
+    fn press_release_clicks_once() {
+        let frames = play(InputScript::<4>::new().click(10, 10).wait_ms(32), None);
+        assert_eq!(clicks(&frames).as_slice(), &[16]);
+    }
 
-            let corner_radius = self.corner_radius.unwrap_or(ui.style().corner_radius);
-            let rounded_rect = RoundedRectangle::with_equal_corners(
-                Rectangle::new(iresponse.area.top_left, iresponse.area.size),
-                Size::new(corner_radius, corner_radius),
-            );
+    #[test]
+    fn dragging_off_cancels_the_click() {
+        let script = InputScript::<4>::new()
+            .press(10, 10)
+            .wait_ms(100)
+            .move_to(OUTSIDE.x, OUTSIDE.y)
+            .release();
+        let frames = play(script, None);
+        assert!(clicks(&frames).is_empty());
+        // no longer down once dragged off
+        assert!(!frames[frames.len() - 2].2);
+    }
 
-            ui.draw(&rounded_rect.into_styled(rect_style)).ok();
-            ui.draw(&text).ok();
+    #[test]
+    fn double_click_clicks_twice() {
+        let script = InputScript::<8>::new()
+            .click(10, 10)
+            .wait_ms(50)
+            .click(10, 10);
+        let frames = play(script, None);
+        assert_eq!(clicks(&frames).as_slice(), &[16, 112]);
+    }
 
-            ui.finalize()?;
+    /// Plays `script` on a button with a smartstate, building the frames with a [UiState],
+    /// returning `clicked()`, `down()` and whether the button was redrawn in every frame
+    fn play_retained<const N: usize>(
+        script: InputScript<N>,
+    ) -> heapless::Vec<(bool, bool, bool), 32> {
+        let mut display = display();
+        let mut state = UiState::new(medsize_rgb565_style());
+        let mut smartstate = Smartstate::empty();
+        let mut frames = heapless::Vec::new();
+        for frame in script {
+            let mut ui = state.begin_frame(&mut display, frame.interaction, frame.now_ms);
+            let response = ui.add(Button::new("+").smartstate(&mut smartstate));
+            let redrawn = ui.perf().widgets_redrawn > 0;
+            state.end_frame(ui);
+            frames
+                .push((response.clicked(), response.down(), redrawn))
+                .unwrap();
+        }
+        frames
+    }
+
+    #[test]
+    fn jitter_around_the_edge_keeps_the_press() {
+        // the button covers x = 3..=23, the press starts at its right edge
+        let script = InputScript::<8>::new()
+            .press(22, 15)
+            .move_to(25, 16)
+            .move_to(21, 14)
+            .move_to(26, 15)
+            .move_to(24, 12)
+            .release();
+        let frames = play_retained(script);
+        // pressed throughout without flickering, and clicked once on release
+        let (released, held) = frames.split_last().unwrap();
+        assert!(held.iter().all(|(_, down, _)| *down));
+        assert!(frames[1..].iter().all(|(_, _, redrawn)| !redrawn));
+        let clicks = frames.iter().filter(|(clicked, _, _)| *clicked).count();
+        assert_eq!(clicks, 1);
+        assert!(released.0);
+
+        // moving further away still cancels the press
+        let script = InputScript::<4>::new()
+            .press(22, 15)
+            .move_to(35, 15)
+            .release();
+        let frames = play_retained(script);
+        assert!(!frames[1].1);
+        assert!(frames.iter().all(|(clicked, _, _)| !clicked));
+    }
+
+    #[test]
+    fn repeat_fires_on_press_after_delay_and_at_interval() {
+        let mut state = RepeatState::new();
+
+        // held from t=0 to t=1000, released at t=1100
+        let script = InputScript::<4>::new()
+            .frame_ms(100)
+            .press(INSIDE.x, INSIDE.y)
+            .wait_ms(1000)
+            .release();
+        let frames = play(script, Some(&mut state));
+
+        assert!(frames[..=10].iter().all(|(_, _, down)| *down));
+        // t=0 (press), t=500 (initial delay), t=700, t=900 (interval); releasing doesn't fire again
+        assert_eq!(clicks(&frames).as_slice(), &[0, 500, 700, 900]);
+        assert_eq!(state, RepeatState::new());
+    }
+
+    #[test]
+    fn repeating_in_a_slot_fires_like_with_a_repeat_state() {
+        let mut display = display();
+        let mut slot = Slot::EMPTY;
+        let script = InputScript::<4>::new()
+            .frame_ms(100)
+            .press(INSIDE.x, INSIDE.y)
+            .wait_ms(1000)
+            .release();
+        let mut clicked = heapless::Vec::<u64, 8>::new();
+        for frame in script {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            frame.apply(&mut ui);
+            if ui
+                .add(Button::new("+").repeat_in(&mut slot, 500, 200))
+                .clicked()
+            {
+                clicked.push(frame.now_ms).unwrap();
+            }
+            if frame.now_ms == 600 {
+                assert_eq!(slot.count(), 2);
+                assert_eq!(slot.timer(600), Some(600));
+            }
+        }
+        assert_eq!(clicked.as_slice(), &[0, 500, 700, 900]);
+        assert_eq!(slot, Slot::EMPTY);
+    }
+
+    #[test]
+    fn repeat_stops_when_dragged_off() {
+        let mut state = RepeatState::new();
+
+        // dragging back on doesn't restart the repetition
+        let script = InputScript::<4>::new()
+            .frame_ms(100)
+            .press(INSIDE.x, INSIDE.y)
+            .move_to(OUTSIDE.x, OUTSIDE.y)
+            .move_to(INSIDE.x, INSIDE.y)
+            .wait_ms(800);
+        let frames = play(script, Some(&mut state));
+        assert_eq!(clicks(&frames).as_slice(), &[0]);
+    }
+
+    #[test]
+    fn repeat_without_frame_time_fires_once() {
+        let mut display = display();
+        let mut state = RepeatState::new();
+
+        assert!(frame(&mut display, &mut state, None, Interaction::Click(INSIDE)).clicked());
+        for _ in 0..20 {
+            assert!(!frame(&mut display, &mut state, None, Interaction::Drag(INSIDE)).clicked());
         }
+        assert!(!frame(&mut display, &mut state, None, Interaction::Release(INSIDE)).clicked());
+    }
+
+    fn pressed(offset: Size) -> MockDisplay<Rgb565> {
+        let mut display = display();
+        let mut style = medsize_rgb565_style();
+        style.pressed_offset = offset;
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        ui.interact(Interaction::Click(INSIDE));
+        ui.add(Button::new("+"));
+        display
+    }
+
+    #[test]
+    fn pressed_offset_shifts_only_the_content() {
+        let flat = pressed(Size::zero());
+        let shifted = pressed(Size::new(1, 1));
 
-        Ok(Response::new(iresponse).set_clicked(click).set_down(down))
+        // the background stays in place, the label moves
+        assert_eq!(flat.affected_area(), shifted.affected_area());
+        assert_ne!(flat, shifted);
+    }
+
+    #[test]
+    fn shadow_is_drawn_inside_the_allocated_area() {
+        let mut display = display();
+        let mut style = medsize_rgb565_style();
+        style.shadow_color = Some(Rgb565::BLACK);
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        let response = ui.add(Button::new("+"));
+        let area = response.internal.area;
+
+        // "+" is 9px wide + 2 * 6px padding, 15px high + 2 * 5px padding, plus the 2px shadow
+        assert_eq!(area.size, Size::new(21 + 2, 25 + 2));
+        assert_eq!(display.affected_area(), area);
+        // the shadow peeks out below the button
+        let below = area.top_left + Point::new(area.size.width as i32 / 2, 26);
+        assert_eq!(display.get_pixel(below), Some(Rgb565::BLACK));
+    }
+
+    #[test]
+    fn truncated_label_fits_the_max_width() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let label = "Temperature sensor";
+        assert!(ui.try_add(Button::new(label)).is_err());
+        // as wide as the rest of the row
+        let width = ui.add(Button::new(label).truncate()).area().size.width;
+        assert!((50..=58).contains(&width), "{width}");
+        let width = ui.add(Button::new(label).max_width(40)).area().size.width;
+        assert!((30..=40).contains(&width), "{width}");
+    }
+
+    #[test]
+    fn gradient_follows_the_rounded_corners() {
+        use crate::style::Fill;
+
+        let mut display = display();
+        let mut style = medsize_rgb565_style();
+        style.item_background_color = Fill::VGradient(Rgb565::RED, Rgb565::BLUE);
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        let area = ui.add(Button::new("+")).internal.area;
+
+        let center = area.top_left.x + area.size.width as i32 / 2;
+        let bottom = area.top_left.y + area.size.height as i32 - 1;
+        assert_eq!(
+            display.get_pixel(Point::new(center, area.top_left.y)),
+            Some(Rgb565::RED)
+        );
+        assert_eq!(
+            display.get_pixel(Point::new(center, bottom)),
+            Some(Rgb565::BLUE)
+        );
+        // outside of the rounded corner
+        assert_eq!(display.get_pixel(area.top_left), None);
+    }
+
+    #[test]
+    fn multi_line_buttons_share_a_row() {
+        use crate::ui::VerticalAlign;
+        use embedded_graphics::mono_font::ascii;
+
+        let mut display = display();
+        let mut style = medsize_rgb565_style();
+        style.default_font = ascii::FONT_4X6;
+        style.spacing.item_spacing = Size::new(2, 2);
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        ui.set_row_alignment(VerticalAlign::Center);
+
+        // the tallest first: widgets are aligned to the row height when they are added
+        let three = ui.add_horizontal(Button::new("a\nb\nc")).internal.area;
+        let two = ui
+            .add_horizontal(Button::new("ab cd").wrap(8))
+            .internal
+            .area;
+        let one = ui.add_horizontal(Button::new("a")).internal.area;
+
+        // 4px + 2 * 6px padding wide, 6px per line + 2 * 5px padding (at least 16px) high
+        assert_eq!(three.size, Size::new(16, 28));
+        assert_eq!(two.size, Size::new(20, 22));
+        assert_eq!(one.size, Size::new(16, 16));
+        assert_eq!(two.center().y, three.center().y);
+        assert_eq!(one.center().y, three.center().y);
+    }
+
+    #[test]
+    fn edge_events_fire_once() {
+        let mut display = display();
+        let mut smartstate = Smartstate::empty();
+
+        // (entered, pressed, exited, redrawn) per frame
+        let mut frames = heapless::Vec::<_, 3>::new();
+        for interaction in [
+            Interaction::Hover(INSIDE),
+            Interaction::Click(INSIDE),
+            Interaction::Release(INSIDE),
+        ] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            ui.interact(interaction);
+            let response = ui.add(Button::new("+").smartstate(&mut smartstate));
+            frames
+                .push((
+                    response.hover_entered(),
+                    response.pressed_started(),
+                    response.hover_exited(),
+                    ui.perf().widgets_redrawn,
+                ))
+                .unwrap();
+        }
+        assert_eq!(
+            frames.as_slice(),
+            &[
+                (true, false, false, 1),
+                (false, true, false, 1),
+                // releasing looks like pressing, so it isn't redrawn
+                (false, false, true, 0)
+            ]
+        );
+
+        // without a smartstate, there's no last frame to compare with
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.interact(Interaction::Click(INSIDE));
+        assert!(!ui.add(Button::new("+")).pressed_started());
+    }
+
+    #[test]
+    fn label_change_redraws() {
+        let mut display = display();
+        let mut smartstate = Smartstate::empty();
+
+        let mut redraws = heapless::Vec::<u32, 3>::new();
+        for label in ["one", "one", "one\ntwo"] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            ui.add(Button::new(label).smartstate(&mut smartstate));
+            redraws.push(ui.perf().widgets_redrawn).unwrap();
+        }
+        assert_eq!(redraws.as_slice(), &[1, 0, 1]);
+    }
+
+    #[test]
+    fn primary_buttons_are_filled_with_the_primary_color() {
+        let mut display = display();
+        let mut smartstate = Smartstate::empty();
+        let style = medsize_rgb565_style();
+
+        let mut redraws = heapless::Vec::<u32, 3>::new();
+        for primary in [false, true, true] {
+            let mut ui = Ui::new_fullscreen(&mut display, style);
+            let context = WidgetContext::from_primary(primary);
+            ui.add(
+                Button::new("OK")
+                    .context(context)
+                    .smartstate(&mut smartstate),
+            );
+            redraws.push(ui.perf().widgets_redrawn).unwrap();
+            let fill = if primary {
+                style.primary_color
+            } else {
+                style.item_background_color.color()
+            };
+            assert_eq!(display.get_pixel(Point::new(5, 5)), Some(fill));
+        }
+        // switching the context redraws
+        assert_eq!(redraws.as_slice(), &[1, 1, 0]);
     }
 }
@@ -2,15 +2,30 @@
 //!
 //! A customizable checkbox widget that provides a simple boolean state control.
 //!
+//! This module is only available with the `checkbox` feature (on by default).
+//!
 //! The checkbox widget provides a traditional square control that can be toggled between checked
 //! and unchecked states. It features an automatic icon that scales based on the available space
 //! and integrates with the framework's theming system for consistent appearance.
 //!
+//! The checkbox is as tall as its row (at least the default widget height). Its padding
+//! ([Spacing::default_padding](crate::style::Spacing::default_padding), kept clear of the
+//! highlight border like in buttons, see
+//! [Style::widget_padding](crate::style::Style::widget_padding)) shrinks the check mark, and
+//! grows the checkbox if even the smallest one wouldn't fit.
+//!
+//! With [Checkbox::tri_state], the checkbox has a third, indeterminate state (drawn as a dash),
+//! e.g. for "select all" checkboxes of partially selected lists.
+//!
 //! This widget is part of the Kolibri embedded GUI framework's core widget set and integrates
 //! with the framework's [Smartstate] system for efficient rendering.
 //!
+use crate::anim::{Transition, ONE};
 use crate::smartstate::{Container, Smartstate};
-use crate::ui::{GuiError, GuiResult, Interaction, Response, Ui, Widget};
+use crate::style::WidgetContext;
+use crate::ui::{
+    resolve_widget_style, rounded_rect_clamped, GuiError, GuiResult, Response, Ui, Widget,
+};
 use core::cmp::max;
 use core::ops::{Add, Sub};
 use embedded_graphics::draw_target::DrawTarget;
@@ -18,7 +33,7 @@ use embedded_graphics::geometry::{Point, Size};
 use embedded_graphics::image::Image;
 use embedded_graphics::pixelcolor::PixelColor;
 use embedded_graphics::prelude::*;
-use embedded_graphics::primitives::{PrimitiveStyleBuilder, Rectangle, RoundedRectangle};
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
 use embedded_iconoir::prelude::*;
 use embedded_iconoir::{size12px, size18px, size24px, size32px};
 
@@ -32,19 +47,12 @@ use embedded_iconoir::{size12px, size18px, size24px, size32px};
 /// ```no_run
 /// # use embedded_graphics::pixelcolor::Rgb565;
 /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-/// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-/// # use kolibri_embedded_gui::ui::Ui;
+/// # use kolibri_embedded_gui::prelude::*;
 /// # use embedded_graphics::prelude::*;
 /// # use embedded_graphics::primitives::Rectangle;
-/// # use embedded_iconoir::prelude::*;
-/// # use embedded_iconoir::size12px;
-/// # use kolibri_embedded_gui::ui::*;
-/// # use kolibri_embedded_gui::label::*;
-/// # use kolibri_embedded_gui::smartstate::*;
 /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 /// # let output_settings = OutputSettingsBuilder::new().build();
 /// # let mut window = Window::new("Kolibri Example", &output_settings);
-/// # use kolibri_embedded_gui::checkbox::Checkbox;
 ///
 /// let mut checked = false;
 /// let mut smartstates = SmartstateProvider::<10>::new();
@@ -72,20 +80,105 @@ use embedded_iconoir::{size12px, size18px, size24px, size32px};
 ///
 /// ```
 pub struct Checkbox<'a> {
-    checked: &'a mut bool,
+    value: Value<'a>,
     smartstate: Container<'a, Smartstate>,
     corner_radius: Option<u32>,
+    animate_ms: u32,
+    context: WidgetContext,
+}
+
+/// The value a [Checkbox] is bound to
+enum Value<'a> {
+    Bool(&'a mut bool),
+    TriState(&'a mut Option<bool>, TriStateCycle),
+}
+
+/// The order in which a [tri-state](Checkbox::tri_state) checkbox cycles through its states
+/// when clicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TriStateCycle {
+    /// Indeterminate → checked → unchecked → checked → ... This is the default.
+    ///
+    /// Clicking never makes the checkbox indeterminate, only the application does (e.g. when
+    /// some, but not all items of a list are selected).
+    #[default]
+    SkipIndeterminate,
+    /// Indeterminate → checked → unchecked → indeterminate → ...
+    IncludeIndeterminate,
+}
+
+impl TriStateCycle {
+    /// Returns the state after `state` when clicked.
+    fn next(self, state: Option<bool>) -> Option<bool> {
+        match (state, self) {
+            (None, _) => Some(true),
+            (Some(true), _) => Some(false),
+            (Some(false), TriStateCycle::SkipIndeterminate) => Some(true),
+            (Some(false), TriStateCycle::IncludeIndeterminate) => None,
+        }
+    }
 }
 
 impl<'a> Checkbox<'a> {
-    pub fn new(checked: &mut bool) -> Checkbox {
+    /// Creates a checkbox bound to `checked`.
+    pub fn new(checked: &'a mut bool) -> Checkbox<'a> {
+        Checkbox {
+            value: Value::Bool(checked),
+            smartstate: Container::empty(),
+            corner_radius: None,
+            animate_ms: 0,
+            context: WidgetContext::Normal,
+        }
+    }
+
+    /// Creates a tri-state checkbox bound to `state`, with `None` being the indeterminate state
+    /// (drawn as a dash).
+    ///
+    /// Clicking cycles through the states as set with [Checkbox::cycle], and sets
+    /// [Response::changed].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let mut items = [true, false, true];
+    ///
+    /// // "select all": indeterminate if only some items are selected
+    /// let mut all = match items.iter().filter(|&&item| item).count() {
+    ///     0 => Some(false),
+    ///     n if n == items.len() => Some(true),
+    ///     _ => None,
+    /// };
+    /// if ui.add(Checkbox::tri_state(&mut all)).changed() {
+    ///     if let Some(all) = all {
+    ///         items = [all; 3];
+    ///     }
+    /// }
+    /// ```
+    pub fn tri_state(state: &'a mut Option<bool>) -> Checkbox<'a> {
         Checkbox {
-            checked,
+            value: Value::TriState(state, TriStateCycle::default()),
             smartstate: Container::empty(),
             corner_radius: None,
+            animate_ms: 0,
+            context: WidgetContext::Normal,
         }
     }
 
+    /// Sets the order in which a [tri-state](Checkbox::tri_state) checkbox cycles through its
+    /// states. Has no effect on a plain checkbox.
+    pub fn cycle(mut self, cycle: TriStateCycle) -> Self {
+        if let Value::TriState(_, current) = &mut self.value {
+            *current = cycle;
+        }
+        self
+    }
+
     /// Attaches a [Smartstate] to the checkbox for incremental redrawing.
     ///
     /// When a smartstate is attached, the checkbox will only redraw when its state
@@ -101,6 +194,7 @@ impl<'a> Checkbox<'a> {
     /// Sets a custom corner radius for the checkbox.
     ///
     /// If not specified, the checkbox will use the corner radius from the UI style.
+    /// Radii larger than half of the checkbox size are clamped.
     ///
     /// # Arguments
     /// * `radius` - The corner radius in pixels
@@ -111,6 +205,24 @@ impl<'a> Checkbox<'a> {
         self.corner_radius = Some(radius);
         self
     }
+
+    /// Sets the role of the checkbox in its screen, like [Button::context](crate::button::Button::context).
+    pub fn context(mut self, context: WidgetContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Animates the check mark when the checkbox is clicked: it grows in from the left over
+    /// `duration_ms` when checked, and shrinks out when unchecked. `0` (the default) doesn't
+    /// animate.
+    ///
+    /// Only the look is animated, the value changes right away. The animation needs a
+    /// [smartstate](Checkbox::smartstate) and a frame time ([Ui::dt]), otherwise the check mark
+    /// appears instantly.
+    pub fn animate(mut self, duration_ms: u32) -> Self {
+        self.animate_ms = duration_ms;
+        self
+    }
 }
 
 impl Checkbox<'_> {
@@ -127,6 +239,7 @@ impl Checkbox<'_> {
         icon: impl ImageDrawable<Color = COL>,
         area: &Rectangle,
         center_offset: Point,
+        shown: Rectangle,
     ) -> GuiResult<()> {
         let img = Image::new(
             &icon,
@@ -135,8 +248,11 @@ impl Checkbox<'_> {
                     .sub(center_offset),
             ),
         );
-        ui.draw(&img)
-            .map_err(|_| GuiError::DrawError(Some("Couldn't draw Checkbox")))
+        ui.draw(&Revealed {
+            item: img,
+            area: shown,
+        })
+        .map_err(|_| GuiError::DrawError(Some("Couldn't draw Checkbox")))
     }
 }
 
@@ -147,55 +263,80 @@ impl Widget for Checkbox<'_> {
     ) -> GuiResult<Response> {
         // allocate space
 
-        let size = ui.style().default_widget_height.max(ui.get_row_height());
         let padding = {
-            // make square padding
-            let pad = ui.style().spacing.default_padding;
+            // make square padding, clear of the highlight border like in buttons
+            let pad = ui
+                .style()
+                .widget_padding(ui.style().spacing.default_padding);
             let biggest_pad = max(pad.width, pad.height);
             Size::new(biggest_pad, biggest_pad)
         };
+        // at least large enough for the smallest check mark within the padding
+        let size = (ui.style().default_widget_height)
+            .max(ui.get_row_height())
+            .max(12 + 2 * padding.width);
         let iresponse = ui.allocate_space(Size::new(size, size))?;
 
         // check interaction
 
+        let was_checked = matches!(
+            self.value,
+            Value::Bool(true) | Value::TriState(Some(true), _)
+        );
         let mut changed = false;
-        if let Interaction::Release(_) = iresponse.interaction {
-            *self.checked = !*self.checked;
+        if iresponse.interaction.is_release() {
+            match &mut self.value {
+                Value::Bool(checked) => **checked = !**checked,
+                Value::TriState(state, cycle) => **state = cycle.next(**state),
+            }
             changed = true;
         }
+        let state = match &self.value {
+            Value::Bool(checked) => Some(**checked),
+            Value::TriState(state, _) => **state,
+        };
+        // distinguishes unchecked, checked and indeterminate in the smartstate
+        let value_state = match state {
+            Some(false) => 0,
+            Some(true) => 1,
+            None => 2,
+        };
 
         // styles
 
         // smartstate
         let prevstate = self.smartstate.clone_inner();
 
-        let style = match iresponse.interaction {
-            Interaction::Click(_) | Interaction::Drag(_) | Interaction::Release(_) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(1));
-                PrimitiveStyleBuilder::new()
-                    .fill_color(ui.style().primary_color)
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-            }
-            Interaction::Hover(_) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(2));
-                PrimitiveStyleBuilder::new()
-                    .fill_color(ui.style().highlight_item_background_color)
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-            }
-            _ => {
-                self.smartstate.modify(|st| *st = Smartstate::state(3));
-                PrimitiveStyleBuilder::new()
-                    .fill_color(ui.style().item_background_color)
-                    .stroke_color(ui.style().border_color)
-                    .stroke_width(ui.style().border_width)
+        let (mut style, bucket) = resolve_widget_style(ui.style(), iresponse.interaction);
+        self.context.apply(&mut style, ui.style());
+        // the check mark grows in or shrinks out (the dash of the indeterminate state doesn't)
+        let restart = changed && was_checked != (state == Some(true));
+        let transition = Transition::update(prevstate, restart, self.animate_ms, ui.dt());
+        let animating = transition.running() && state.is_some();
+        self.smartstate.modify(|st| {
+            if animating {
+                *st = transition.smartstate();
+            } else {
+                *st = Smartstate::state(3 * bucket as u32 + value_state);
+                st.mix(self.context as u32);
+                st.mix(ui.style_key());
             }
-        };
+        });
+        if animating {
+            ui.request_repaint();
+        }
 
-        let redraw = !self.smartstate.eq_option(&prevstate) || changed;
+        let redraw = !self.smartstate.eq_option(&prevstate)
+            || changed
+            || animating
+            || ui.invalidated(iresponse.area);
 
         if redraw {
+            trace!(
+                "Checkbox smartstate: {} -> {}",
+                prevstate,
+                self.smartstate.clone_inner()
+            );
             ui.start_drawing(&iresponse.area);
 
             // clear background if needed
@@ -206,42 +347,73 @@ impl Widget for Checkbox<'_> {
 
             // draw
 
-            let corner_radius = self.corner_radius.unwrap_or(ui.style().corner_radius);
-            let rounded_rect = RoundedRectangle::with_equal_corners(
-                Rectangle::new(iresponse.area.top_left, iresponse.area.size),
-                Size::new(corner_radius, corner_radius),
-            );
+            let corner_radius = ui
+                .style()
+                .corner_radius_for(self.corner_radius, iresponse.area.size);
+            let rounded_rect = rounded_rect_clamped(iresponse.area, corner_radius);
 
-            ui.draw(&rounded_rect.into_styled(style.build()))
+            ui.draw(&rounded_rect.into_styled(style))
                 .map_err(|_| GuiError::DrawError(Some("Couldn't draw Checkbox")))?;
 
-            if *self.checked {
+            // the part of the check mark shown, from the left
+            let shown = match state {
+                _ if !animating => ONE,
+                Some(true) => transition.progress(),
+                _ => ONE - transition.progress(),
+            };
+            let shown = Rectangle::new(
+                iresponse.area.top_left,
+                Size::new(
+                    (iresponse.area.size.width as i64 * shown as i64 / ONE as i64) as u32,
+                    iresponse.area.size.height,
+                ),
+            );
+
+            if state == Some(true) || animating && state == Some(false) {
                 match size - padding.width {
                     0..=18 => self.draw_icon(
                         ui,
                         size12px::actions::Check::new(ui.style().text_color),
                         &iresponse.area,
                         Point::new(6, 6),
+                        shown,
                     ),
                     19..=23 => self.draw_icon(
                         ui,
                         size18px::actions::Check::new(ui.style().text_color),
                         &iresponse.area,
                         Point::new(9, 9),
+                        shown,
                     ),
                     24..=32 => self.draw_icon(
                         ui,
                         size24px::actions::Check::new(ui.style().text_color),
                         &iresponse.area,
                         Point::new(12, 12),
+                        shown,
                     ),
                     _ => self.draw_icon(
                         ui,
                         size32px::actions::Check::new(ui.style().text_color),
                         &iresponse.area,
                         Point::new(16, 16),
+                        shown,
                     ),
                 }?;
+            } else if state.is_none() {
+                // indeterminate: a horizontal dash
+                let area = iresponse.area;
+                let dash = Size::new(area.size.width / 2, max(2, area.size.height / 8));
+                let dash = Rectangle::new(
+                    area.top_left
+                        + Point::new(
+                            ((area.size.width - dash.width) / 2) as i32,
+                            ((area.size.height - dash.height) / 2) as i32,
+                        ),
+                    dash,
+                );
+                ui.draw(&dash.into_styled(PrimitiveStyle::with_fill(ui.style().text_color)))
+                    .map_err(|_| GuiError::DrawError(Some("Couldn't draw Checkbox")))?;
             }
 
             ui.finalize()?;
@@ -249,4 +421,184 @@ impl Widget for Checkbox<'_> {
 
         Ok(Response::new(iresponse).set_changed(changed))
     }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+/// Draws `item` only within `area`
+struct Revealed<T> {
+    item: T,
+    area: Rectangle,
+}
+
+impl<T: Drawable> Drawable for Revealed<T> {
+    type Color = T::Color;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = Self::Color>>(&self, target: &mut D) -> Result<(), D::Error> {
+        self.item.draw(&mut target.clipped(&self.area)).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smartstate::SmartstateProvider;
+    use crate::style::medsize_rgb565_style;
+    use crate::ui::Interaction;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    #[test]
+    fn padding_changes_the_size() {
+        let size = |padding| {
+            let mut display = MockDisplay::<Rgb565>::new();
+            let mut style = medsize_rgb565_style();
+            style.spacing.default_padding = padding;
+            let mut checked = false;
+            Ui::new_fullscreen(&mut display, style).measure_widget(&mut Checkbox::new(&mut checked))
+        };
+        let default = size(Size::new(1, 1));
+        assert_eq!(
+            default,
+            Size::new_equal(medsize_rgb565_style().default_widget_height)
+        );
+        assert_eq!(size(Size::new(8, 20)), Size::new_equal(12 + 2 * 20));
+    }
+
+    #[test]
+    fn tri_state_cycles() {
+        use TriStateCycle::*;
+
+        let mut state = None;
+        let mut states = heapless::Vec::<Option<bool>, 4>::new();
+        for _ in 0..4 {
+            state = SkipIndeterminate.next(state);
+            states.push(state).unwrap();
+        }
+        assert_eq!(
+            states.as_slice(),
+            &[Some(true), Some(false), Some(true), Some(false)]
+        );
+
+        assert_eq!(IncludeIndeterminate.next(Some(false)), None);
+    }
+
+    #[test]
+    fn click_changes_tri_state() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut state = None;
+
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.interact(Interaction::Release(Point::new(5, 5)));
+        let response = ui.add(Checkbox::tri_state(&mut state));
+        assert!(response.changed());
+        assert_eq!(state, Some(true));
+    }
+
+    #[test]
+    fn smartstate_distinguishes_all_states() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<1>::new();
+
+        let mut redraws = heapless::Vec::<u32, 4>::new();
+        for state in [None, None, Some(false), Some(true)] {
+            let mut state = state;
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            ui.add(Checkbox::tri_state(&mut state).smartstate(smartstates.nxt()));
+            redraws.push(ui.perf().widgets_redrawn).unwrap();
+        }
+        // changed by the application, without interaction
+        assert_eq!(redraws.as_slice(), &[1, 0, 1, 1]);
+    }
+
+    #[test]
+    fn focus_activation_acts_like_a_click() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<2>::new();
+        let mut checked = [false; 2];
+
+        // (redrawn widgets, changed) per frame, focusing the second checkbox
+        let mut frames = heapless::Vec::<(u32, bool), 4>::new();
+        for activate in [false, true, false, false] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            ui.set_focus(Some(1));
+            if activate {
+                ui.activate_focus();
+            }
+            smartstates.restart_counter();
+            let mut changed = false;
+            for checked in checked.iter_mut() {
+                changed |= ui
+                    .add(Checkbox::new(checked).smartstate(smartstates.nxt()))
+                    .changed();
+            }
+            assert_eq!(ui.allocations(), 2);
+            frames.push((ui.perf().widgets_redrawn, changed)).unwrap();
+        }
+        assert_eq!(checked, [false, true]);
+        // the activation is drawn pressed for one frame, then focused (hovered) again
+        assert_eq!(
+            frames.as_slice(),
+            &[(2, false), (1, true), (1, false), (0, false)]
+        );
+    }
+
+    #[test]
+    fn animation_only_delays_the_look() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+        let mut checked = false;
+
+        // (repaint requested, redrawn) per 50ms frame, clicking in the first one
+        let mut frames = heapless::Vec::<(bool, u32), 8>::new();
+        for i in 0..7 {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            ui.tick(50);
+            if i == 0 {
+                ui.interact(Interaction::Release(Point::new(5, 5)));
+            }
+            ui.add(
+                Checkbox::new(&mut checked)
+                    .animate(200)
+                    .smartstate(&mut smartstate),
+            );
+            // the value changes right away
+            assert!(checked);
+            frames
+                .push((ui.needs_repaint(), ui.perf().widgets_redrawn))
+                .unwrap();
+        }
+        // 4 steps per frame: 0, 4, 8, 12, then done (redrawn without the transition)
+        assert_eq!(
+            frames.as_slice(),
+            &[
+                (true, 1),
+                (true, 1),
+                (true, 1),
+                (true, 1),
+                (false, 1),
+                (false, 0),
+                (false, 0)
+            ]
+        );
+
+        // without a frame time, it's checked instantly
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.interact(Interaction::Release(Point::new(5, 5)));
+        ui.add(
+            Checkbox::new(&mut checked)
+                .animate(200)
+                .smartstate(&mut smartstate),
+        );
+        assert!(!checked);
+        assert!(!ui.needs_repaint());
+    }
 }
@@ -0,0 +1,256 @@
+//! # Compass Widget
+//!
+//! A [Compass] shows a heading (e.g. of a drone or a boat) on a compass rose: a circle with
+//! ticks every 30° and the cardinal directions N, E, S and W. By default, the rose rotates so
+//! that the heading is at the top, under a fixed needle pointing up, like a ship's compass.
+//! With [Compass::rotate_needle], the rose stays with north at the top and the needle points to
+//! the heading instead. The cardinal labels are always upright.
+//!
+//! This module is only available with the `instruments` feature (on by default).
+//!
+//! The angles are computed with the integer tables of the [trig](crate::trig) module.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! # let heading = 123.4;
+//! ui.add(
+//!     Compass::new(heading)
+//!         .diameter(80)
+//!         .smartstate(smartstates.nxt()),
+//! );
+//! ```
+//!
+//! With a smartstate, the compass is only redrawn when the heading changes by a step it can
+//! show: a degree, or more for small compasses, whose rim moves less than a pixel per degree.
+
+use crate::smartstate::{Container, Smartstate};
+use crate::text::{Font, RenderedText, TextRenderer};
+use crate::trig::polar;
+use crate::ui::{GuiError, GuiResult, Response, Ui, Widget};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{
+    Circle, Line, PrimitiveStyle, PrimitiveStyleBuilder, StrokeAlignment, Triangle,
+};
+
+/// The cardinal directions and their bearings
+const CARDINALS: [(&str, i32); 4] = [("N", 0), ("E", 90), ("S", 180), ("W", 270)];
+
+/// A heading on a compass rose, see the [module documentation](self).
+pub struct Compass<'a> {
+    heading: f32,
+    diameter: Option<u32>,
+    rotate_needle: bool,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<'a> Compass<'a> {
+    /// Creates a compass showing `heading`, in degrees clockwise from north.
+    pub fn new(heading: f32) -> Self {
+        Self {
+            heading,
+            diameter: None,
+            rotate_needle: false,
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Sets the diameter of the compass, four times the style's default widget height by
+    /// default. It is at least large enough for the cardinal labels.
+    pub fn diameter(mut self, diameter: u32) -> Self {
+        self.diameter = Some(diameter);
+        self
+    }
+
+    /// Keeps north at the top and rotates the needle to the heading, instead of rotating the
+    /// rose under a needle pointing up.
+    pub fn rotate_needle(mut self) -> Self {
+        self.rotate_needle = true;
+        self
+    }
+
+    /// Adds a smartstate, so that the compass is only redrawn when the shown heading changes.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+/// Returns `heading` rounded to a multiple of `step` degrees, from 0 to 359
+fn round_heading(heading: f32, step: i32) -> i32 {
+    let heading = match heading % 360.0 {
+        heading if heading < 0.0 => heading + 360.0,
+        heading => heading,
+    };
+    ((heading / step as f32 + 0.5) as i32 * step) % 360
+}
+
+impl Widget for Compass<'_> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        // get size
+        let font = Font::resolve(None, ui.style());
+        let label_size = font.measure("W").width.max(font.line_height());
+        let diameter = self
+            .diameter
+            .unwrap_or(4 * ui.style().default_widget_height)
+            .max(3 * label_size);
+
+        // allocate space
+        let iresponse = ui.allocate_space(Size::new_equal(diameter))?;
+        let area = iresponse.area;
+
+        // the rim moves 360 / (π * diameter) pixels per degree
+        let step = (115 / diameter as i32).max(1);
+        let heading = round_heading(self.heading, step);
+
+        // check smartstate
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(&(heading, diameter, self.rotate_needle));
+        state.mix(ui.style_key());
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(area);
+        self.smartstate.modify(|st| *st = state);
+        if !redraw {
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+
+        // the screen angles of north and of the needle
+        let (north, needle) = match self.rotate_needle {
+            true => (0, heading),
+            false => (-heading, 0),
+        };
+        let radius = diameter as i32 / 2;
+        let center = area.top_left + Point::new_equal(radius);
+        let tick = (radius / 8).max(2);
+        let label_radius = radius - tick - 1 - label_size as i32 / 2;
+
+        // draw, within the allocated area (the circle's bounding box)
+        ui.start_drawing(&area);
+        if !ui.cleared() {
+            ui.clear_area(area)?;
+        }
+        let style = ui.style();
+        let (text_color, needle_color) = (style.text_color, style.primary_color);
+        let rim = PrimitiveStyleBuilder::new()
+            .stroke_color(style.border_color)
+            .stroke_width(style.border_width.max(1))
+            .stroke_alignment(StrokeAlignment::Inside)
+            .build();
+        let tick_style = PrimitiveStyle::with_stroke(style.border_color, 1);
+        ui.draw(&Circle::new(area.top_left, diameter).into_styled(rim))
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw Compass")))?;
+
+        // the ticks between the cardinal directions
+        for bearing in (30..360).step_by(30).filter(|bearing| bearing % 90 != 0) {
+            let angle = north + bearing;
+            let outer = polar(center, radius - 1, angle);
+            let inner = polar(center, radius - 1 - tick, angle);
+            ui.draw(&Line::new(outer, inner).into_styled(tick_style))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw Compass ticks")))?;
+        }
+
+        // the labels, upright at their rotated position
+        for (label, bearing) in CARDINALS {
+            let position = polar(center, label_radius, north + bearing);
+            let size = Size::new(font.measure(label).width, font.line_height());
+            let top_left = position - Point::new(size.width as i32 / 2, size.height as i32 / 2);
+            ui.draw(&RenderedText::new(&font, label, top_left, text_color))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw Compass labels")))?;
+        }
+
+        // the needle, reaching up to the labels
+        let length = label_radius - label_size as i32 / 2 - 1;
+        let width = (radius / 8).max(2);
+        let needle = Triangle::new(
+            polar(center, length, needle),
+            polar(center, width, needle + 90),
+            polar(center, width, needle - 90),
+        );
+        ui.draw(&needle.into_styled(PrimitiveStyle::with_fill(needle_color)))
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw Compass needle")))?;
+        ui.finalize()?;
+
+        Ok(Response::new(iresponse).set_redraw(true))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use crate::ui::PerfStats;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::primitives::Rectangle;
+
+    /// Draws a compass of 56 pixels, returning its area and the performance counters
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        smartstate: &mut Smartstate,
+        compass: Compass,
+    ) -> (Rectangle, PerfStats) {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        let response = ui.add(compass.diameter(56).smartstate(smartstate));
+        (response.area(), ui.perf())
+    }
+
+    #[test]
+    fn the_rose_or_the_needle_turns_with_the_heading() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+        let style = medsize_rgb565_style();
+        let font = Font::resolve(None, &style);
+        let label_radius = 28 - 3 - 1 - font.line_height().max(9) as i32 / 2;
+        let needle_at = |display: &MockDisplay<Rgb565>, point: Point| {
+            display.get_pixel(point) == Some(style.primary_color)
+        };
+        // whether there is text within 3 pixels of a point
+        let text_at = |display: &MockDisplay<Rgb565>, point: Point| {
+            Rectangle::with_center(point, Size::new_equal(7))
+                .points()
+                .any(|point| display.get_pixel(point) == Some(style.text_color))
+        };
+
+        // north at the upper left, the needle pointing up
+        let (area, perf) = frame(&mut display, &mut smartstate, Compass::new(45.0));
+        let center = area.top_left + Point::new_equal(28);
+        assert_eq!(area.size, Size::new_equal(56));
+        assert_eq!(perf.dirty_area, Some(area));
+        assert!(needle_at(&display, polar(center, 8, 0)));
+        assert!(!needle_at(&display, polar(center, 8, 45)));
+        assert!(text_at(&display, polar(center, label_radius, -45)));
+        assert!(!text_at(&display, polar(center, label_radius, 0)));
+
+        // a fraction of a degree isn't shown, two degrees are
+        let (_, perf) = frame(&mut display, &mut smartstate, Compass::new(45.4));
+        assert_eq!(perf.widgets_redrawn, 0);
+        let (_, perf) = frame(&mut display, &mut smartstate, Compass::new(47.0));
+        assert_eq!(perf.widgets_redrawn, 1);
+
+        // north at the top, the needle pointing north-east
+        frame(
+            &mut display,
+            &mut smartstate,
+            Compass::new(45.0).rotate_needle(),
+        );
+        assert!(needle_at(&display, polar(center, 8, 45)));
+        assert!(!needle_at(&display, polar(center, 8, 0)));
+        assert!(text_at(&display, polar(center, label_radius, 0)));
+        assert!(!text_at(&display, polar(center, label_radius, -45)));
+    }
+}
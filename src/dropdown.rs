@@ -0,0 +1,412 @@
+//! # Dropdown
+//!
+//! A [Dropdown] picks one of a list of options, showing only the selected one until it is
+//! tapped. The list of options then opens below it (or above it, if there's no room below)
+//! as an overlay on top of the widgets around it (see [Ui::draw_overlay]). Tapping an option
+//! selects it and closes the list, tapping anywhere else closes it without a change.
+//!
+//! This module is only available with the `controls` feature (on by default).
+//!
+//! Whether the list is open is kept in a [DropdownState] between frames.
+//! The [Ui] has to track its overlays with [Ui::begin_overlays] and [Ui::end_overlays], so
+//! that the widgets below the list are repainted when it closes.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use kolibri_embedded_gui::ui::Overlays;
+//! # use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::dropdown::DropdownState;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! let mut overlays = Overlays::new();
+//! let mut baud_rate = 2;
+//! let mut dropdown = DropdownState::new();
+//!
+//! loop {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     ui.begin_overlays(&mut overlays).unwrap();
+//!
+//!     let response = ui.add(Dropdown::new(
+//!         &["9600", "19200", "57600", "115200"],
+//!         &mut baud_rate,
+//!         &mut dropdown,
+//!     ));
+//!     if response.changed() {
+//!         println!("baud rate {baud_rate} selected");
+//!     }
+//!     // covered by the open list
+//!     ui.add(Label::new("Parity: none"));
+//!
+//!     ui.end_overlays(&mut overlays);
+//! }
+//! ```
+
+use crate::smartstate::{Container, Smartstate};
+use crate::text::{truncate, Font, RenderedText, TextRenderer};
+use crate::ui::{
+    resolve_widget_style, rounded_rect_clamped, GuiError, GuiResult, Interaction, Response, Ui,
+    Widget,
+};
+use core::cmp::max;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, Triangle};
+
+/// Whether the list of a [Dropdown] is open, kept between frames
+///
+/// One per dropdown, see [retained state](Smartstate#retained-state).
+#[derive(Debug, Clone, Copy)]
+pub struct DropdownState {
+    open: bool,
+    /// The look of the open list, to redraw it only when it changes
+    list: Smartstate,
+}
+
+impl Default for DropdownState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DropdownState {
+    /// Creates the state of a closed dropdown.
+    pub fn new() -> Self {
+        Self {
+            open: false,
+            list: Smartstate::empty(),
+        }
+    }
+
+    /// Returns whether the list is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Closes the list, e.g. when leaving the screen with the dropdown.
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+}
+
+/// A closed list of options to pick one from, see the [module documentation](self).
+pub struct Dropdown<'a> {
+    options: &'a [&'a str],
+    selected: &'a mut usize,
+    state: &'a mut DropdownState,
+    width: Option<u32>,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<'a> Dropdown<'a> {
+    /// Creates a dropdown for `options`, with the index of the selected one in `selected`.
+    /// If `selected` is out of range, the dropdown is empty until an option is picked.
+    pub fn new(
+        options: &'a [&'a str],
+        selected: &'a mut usize,
+        state: &'a mut DropdownState,
+    ) -> Self {
+        Self {
+            options,
+            selected,
+            state,
+            width: None,
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Sets the width of the dropdown (and of its list), instead of fitting the widest option.
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Adds a [Smartstate] for incremental redrawing of the closed dropdown. The open list keeps
+    /// its own in the [DropdownState].
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+impl Widget for Dropdown<'_> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let font = Font::resolve(None, ui.style());
+        let padding = ui.style().spacing.button_padding;
+        let border = ui.style().border_width;
+        let inset = padding.width + border;
+        let arrow = font.line_height() / 2;
+
+        let width = self.width.unwrap_or_else(|| {
+            let widest = self
+                .options
+                .iter()
+                .map(|option| font.measure(option).width)
+                .max()
+                .unwrap_or(0);
+            widest + 2 * inset + padding.width + arrow
+        });
+        let row_height = font.line_height() + 2 * padding.height;
+        let height = max(row_height + 2 * border, ui.style().default_widget_height);
+
+        let iresponse = ui.allocate_space(Size::new(width, height))?;
+        let area = iresponse.area;
+        let previous = *self.selected;
+
+        // where the list opens
+        let list_height = self.options.len() as u32 * row_height + 2 * border;
+        let screen = ui.overlay_bounds();
+        let below = area.top_left.y + area.size.height as i32;
+        let top = match below + list_height as i32 <= screen.top_left.y + screen.size.height as i32
+        {
+            true => below,
+            false => area.top_left.y - list_height as i32,
+        };
+        let list = Rectangle::new(
+            Point::new(area.top_left.x, top),
+            Size::new(area.size.width, list_height),
+        );
+
+        // the option under the pointer, in the open list
+        let pointed = match self.state.open {
+            true => ui.check_overlay_interact(list),
+            false => Interaction::None,
+        };
+        let hovered = pointed.get_point().and_then(|point| {
+            (0..self.options.len()).find(|&i| list_row(list, border, row_height, i).contains(point))
+        });
+
+        // open or close the list
+        let click = matches!(
+            iresponse.interaction,
+            Interaction::Release(_) | Interaction::ActivatedByFocus(_)
+        );
+        if click {
+            self.state.open = !self.state.open;
+        } else if self.state.open {
+            if let Interaction::Release(point) = ui.check_overlay_interact(screen) {
+                // picking an option or tapping outside of the list closes it
+                if let Some(i) = hovered {
+                    *self.selected = i;
+                }
+                self.state.open = list.contains(point) && hovered.is_none();
+            }
+        }
+
+        if self.state.open {
+            let (selected, list_state) = (*self.selected, &mut self.state.list);
+            ui.draw_overlay(list, |ui| {
+                let rows = (self.options, selected, hovered);
+                draw_list(ui, list, rows, list_state, row_height)
+            })?;
+        }
+        let changed = *self.selected != previous;
+
+        // the closed dropdown
+        let label = self.options.get(*self.selected).copied().unwrap_or("");
+        let (rect_style, bucket) = resolve_widget_style(ui.style(), iresponse.interaction);
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(&(bucket, label, self.state.open, area.size));
+        state.mix(ui.style_key());
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(area);
+        self.smartstate.modify(|st| *st = state);
+
+        if redraw {
+            ui.start_drawing(&area);
+            let radius = ui.style().corner_radius_for(None, area.size);
+            ui.draw_background(rounded_rect_clamped(area, radius), rect_style)
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw Dropdown")))?;
+
+            let text_width = area
+                .size
+                .width
+                .saturating_sub(2 * inset + padding.width + arrow);
+            let text = truncate(&font, label, text_width);
+            let text_top_left = area.top_left
+                + ui.style()
+                    .pressed_offset_for(iresponse.interaction.is_pressed())
+                + Point::new(
+                    inset as i32,
+                    (area.size.height.saturating_sub(font.line_height()) / 2) as i32,
+                );
+            ui.draw(&RenderedText::new(
+                &font,
+                text,
+                text_top_left,
+                ui.style().text_color,
+            ))
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw Dropdown label")))?;
+
+            // an arrow pointing to where the list opens
+            let center = Point::new(
+                area.top_left.x + (area.size.width - inset - arrow / 2) as i32,
+                area.center().y,
+            );
+            let (x, y) = ((arrow / 2) as i32, (arrow / 4) as i32);
+            let y = if self.state.open { -y } else { y };
+            let triangle = Triangle::new(
+                center + Point::new(-x, -y),
+                center + Point::new(x, -y),
+                center + Point::new(0, y),
+            );
+            ui.draw(&triangle.into_styled(PrimitiveStyle::with_fill(ui.style().text_color)))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw Dropdown arrow")))?;
+            ui.finalize()?;
+        }
+
+        let down = matches!(
+            iresponse.interaction,
+            Interaction::Click(_) | Interaction::Drag(_)
+        );
+        Ok(Response::new(iresponse)
+            .set_clicked(click)
+            .set_down(down)
+            .set_changed(changed)
+            .set_redraw(redraw))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+/// Returns the area of option `i` in the open `list`
+fn list_row(list: Rectangle, border: u32, row_height: u32, i: usize) -> Rectangle {
+    Rectangle::new(
+        list.top_left + Point::new(border as i32, (border + i as u32 * row_height) as i32),
+        Size::new(list.size.width.saturating_sub(2 * border), row_height),
+    )
+}
+
+/// Draws the open list into `list` (in an overlay): the options, the index of the selected one
+/// and the index of the hovered one
+fn draw_list<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+    ui: &mut Ui<DRAW, COL>,
+    list: Rectangle,
+    (options, selected, hovered): (&[&str], usize, Option<usize>),
+    smartstate: &mut Smartstate,
+    row_height: u32,
+) -> GuiResult<()> {
+    let font = Font::resolve(None, ui.style());
+    let border = ui.style().border_width;
+    let inset = ui.style().spacing.button_padding.width + border;
+
+    let mut look = Smartstate::empty();
+    look.set_state_hashed(&(selected, hovered, options, list));
+    look.mix(ui.style_key());
+    let redraw = look != *smartstate || ui.invalidated(list);
+    *smartstate = look;
+    if !redraw {
+        return Ok(());
+    }
+
+    ui.start_drawing(&list);
+    let frame = PrimitiveStyleBuilder::new()
+        .stroke_color(ui.style().border_color)
+        .stroke_width(border)
+        .fill_color(ui.style().item_background_color.color())
+        .build();
+    ui.draw(&list.into_styled(frame))
+        .map_err(|_| GuiError::DrawError(Some("Couldn't draw Dropdown list")))?;
+    for (i, option) in options.iter().enumerate() {
+        let rect = list_row(list, border, row_height, i);
+        let fill = match (i == selected, hovered == Some(i)) {
+            (true, _) => Some(ui.style().primary_color),
+            (false, true) => Some(ui.style().highlight_item_background_color),
+            (false, false) => None,
+        };
+        if let Some(fill) = fill {
+            ui.draw(&rect.into_styled(PrimitiveStyle::with_fill(fill)))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw Dropdown list")))?;
+        }
+        let text = truncate(&font, option, rect.size.width.saturating_sub(2 * inset));
+        let text_top_left =
+            rect.top_left + Point::new(inset as i32, (row_height - font.line_height()) as i32 / 2);
+        ui.draw(&RenderedText::new(
+            &font,
+            text,
+            text_top_left,
+            ui.style().text_color,
+        ))
+        .map_err(|_| GuiError::DrawError(Some("Couldn't draw Dropdown option")))?;
+    }
+    ui.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::label::Label;
+    use crate::style::medsize_rgb565_style;
+    use crate::ui::Overlays;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// Draws a dropdown with a label below it, returning the dropdown's response
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        overlays: &mut Overlays,
+        selected: &mut usize,
+        state: &mut DropdownState,
+        interaction: Interaction,
+    ) -> Response {
+        // small enough for the list to fit below the dropdown
+        let mut style = medsize_rgb565_style();
+        style.spacing.button_padding = Size::new(1, 1);
+        let mut ui = Ui::new_fullscreen(display, style);
+        ui.interact(interaction);
+        ui.begin_overlays(overlays).unwrap();
+        let response = ui.add(Dropdown::new(&["a", "b"], selected, state));
+        ui.add(Label::new("xyz"));
+        ui.end_overlays(overlays);
+        response
+    }
+
+    #[test]
+    fn the_list_opens_over_the_following_widgets() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut overlays = Overlays::new();
+        let (mut selected, mut state) = (0, DropdownState::new());
+        let style = medsize_rgb565_style();
+        let mut frame = |display: &mut _, selected: &mut _, interaction| {
+            frame(display, &mut overlays, selected, &mut state, interaction)
+        };
+
+        let area = frame(&mut display, &mut selected, Interaction::None).area();
+        let response = frame(
+            &mut display,
+            &mut selected,
+            Interaction::Release(area.center()),
+        );
+        assert!(response.clicked() && !response.changed());
+
+        // the selected option is highlighted, the label below doesn't draw over it
+        let list_top = area.top_left.y + area.size.height as i32;
+        let in_first_row = Point::new(area.top_left.x + 2, list_top + 8);
+        assert_eq!(display.get_pixel(in_first_row), Some(style.primary_color));
+
+        // picking the second option closes the list
+        let second_row = in_first_row + Point::new(0, 17);
+        let response = frame(
+            &mut display,
+            &mut selected,
+            Interaction::Release(second_row),
+        );
+        assert!(response.changed() && !response.clicked());
+        assert_eq!(selected, 1);
+
+        // and the list is erased in the next frame
+        frame(&mut display, &mut selected, Interaction::None);
+        assert_eq!(display.get_pixel(second_row), Some(style.background_color));
+        assert_eq!(
+            display.get_pixel(in_first_row),
+            Some(style.background_color)
+        );
+    }
+}
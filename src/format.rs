@@ -0,0 +1,384 @@
+//! # Formatting without allocation
+//!
+//! Dynamic label text (sensor values, counters, ...) usually needs `format!`, which requires
+//! an allocator. [Buf] is a fixed-size string buffer implementing [core::fmt::Write] instead,
+//! so text can be formatted every frame without touching the heap:
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::format::Buf;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! # let temperature = 21.5;
+//! let mut buf = Buf::<32>::new();
+//! // redraws only if the text changes
+//! ui.add(
+//!     Label::new_fmt(&mut buf, format_args!("{:.1} °C", temperature))
+//!         .smartstate(smartstates.nxt()),
+//! );
+//! ```
+//!
+//! Text that doesn't fit is cut off (at a character boundary), which can be checked with
+//! [Buf::truncated].
+//!
+//! Widgets can also own a [Buf] (see [Label::owned](crate::label::Label::owned),
+//! [Button::owned](crate::button::Button::owned) and
+//! [IconButton::label_owned](crate::iconbutton::IconButton::label_owned)), e.g. for text
+//! formatted in a closure, which the borrowed text of
+//! [Label::new_fmt](crate::label::Label::new_fmt) couldn't outlive.
+//!
+//! ## Units
+//!
+//! [duration], [bytes] and [si] format common quantities into a [Buf], using integer math
+//! (and at most one decimal place):
+//!
+//! ```
+//! use kolibri_embedded_gui::format::{bytes, duration, si, Buf};
+//!
+//! let mut buf = Buf::<16>::new();
+//! assert_eq!(duration(&mut buf, 90), "1:30");
+//! assert_eq!(bytes(&mut buf, 1536), "1.5 KB");
+//! assert_eq!(si(&mut buf, 0.0032, "A"), "3.2 mA");
+//! ```
+
+use core::fmt;
+use core::fmt::Write;
+
+/// A fixed-capacity string buffer of `N` bytes, see the [module documentation](self).
+#[derive(Clone, PartialEq, Eq)]
+pub struct Buf<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+    truncated: bool,
+}
+
+impl<const N: usize> Buf<N> {
+    /// Creates an empty buffer.
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Returns the text in the buffer.
+    pub fn as_str(&self) -> &str {
+        // only whole `str`s (or parts cut at char boundaries) are written
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+
+    /// Returns the length of the text in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the buffer holds no text.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the capacity in bytes, i.e. `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns true if text was cut off because the buffer was full (since the last
+    /// [Buf::clear]).
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Empties the buffer and resets the [Buf::truncated] flag.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+
+    /// Appends `s`, cutting it off if it doesn't fit.
+    pub fn push_str(&mut self, s: &str) {
+        // writing to the buffer never fails, it truncates instead
+        fmt::Write::write_str(self, s).ok();
+    }
+
+    /// Creates a buffer holding the formatted `args`, e.g. as the text of
+    /// [Label::owned](crate::label::Label::owned).
+    pub fn from_fmt(args: fmt::Arguments) -> Self {
+        let mut buf = Self::new();
+        buf.format(args);
+        buf
+    }
+
+    /// Replaces the content of the buffer with the formatted `args`.
+    ///
+    /// # Returns
+    ///
+    /// The formatted text.
+    pub fn format(&mut self, args: fmt::Arguments) -> &str {
+        self.clear();
+        // writing to the buffer never fails, it truncates instead
+        fmt::Write::write_fmt(self, args).ok();
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Default for Buf<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for Buf<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let free = N - self.len;
+        let mut end = s.len().min(free);
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end < s.len() {
+            self.truncated = true;
+        }
+        self.bytes[self.len..self.len + end].copy_from_slice(&s.as_bytes()[..end]);
+        self.len += end;
+        Ok(())
+    }
+}
+
+impl<const N: usize> AsRef<str> for Buf<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Display for Buf<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Debug for Buf<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// Formats a fixed-point number (`value` scaled by `10^precision`) with `precision` decimal
+/// places, e.g. `fixed(&mut buf, -325, 2)` gives `-3.25`.
+///
+/// Any precision is valid: from 20 decimal places on, the integer part of every `i64` is `0`.
+///
+/// # Returns
+///
+/// The formatted text.
+pub fn fixed<const N: usize>(buf: &mut Buf<N>, value: i64, precision: u8) -> &str {
+    buf.clear();
+    write_fixed(buf, value, precision);
+    buf.as_str()
+}
+
+/// Appends the fixed-point `value` to `buf`, see [fixed].
+pub(crate) fn write_fixed<const N: usize>(buf: &mut Buf<N>, value: i64, precision: u8) {
+    let abs = value.unsigned_abs();
+    let (int, frac) = match 10u64.checked_pow(precision as u32) {
+        Some(scale) => (abs / scale, abs % scale),
+        // the scale is larger than any value
+        None => (0, abs),
+    };
+    let sign = if value < 0 { "-" } else { "" };
+    // writing to a Buf never fails, it truncates instead
+    write!(buf, "{sign}{int}").ok();
+    if precision > 0 {
+        write!(buf, ".{:0width$}", frac, width = precision as usize).ok();
+    }
+}
+
+/// Formats a duration in seconds as `m:ss`, or `h:mm:ss` from one hour on (e.g. `90` gives
+/// `1:30`, `3725` gives `1:02:05`).
+///
+/// # Returns
+///
+/// The formatted text.
+pub fn duration<const N: usize>(buf: &mut Buf<N>, seconds: u32) -> &str {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    buf.clear();
+    if hours > 0 {
+        write!(buf, "{hours}:{minutes:02}:{seconds:02}").ok();
+    } else {
+        write!(buf, "{minutes}:{seconds:02}").ok();
+    }
+    buf.as_str()
+}
+
+/// Formats a size in bytes with binary prefixes (1 KB = 1024 B), with one decimal place from
+/// 1 KB on (e.g. `512` gives `512 B`, `1536` gives `1.5 KB`).
+///
+/// # Returns
+///
+/// The formatted text.
+pub fn bytes<const N: usize>(buf: &mut Buf<N>, bytes: u32) -> &str {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    buf.clear();
+    if bytes < 1024 {
+        write!(buf, "{bytes} B").ok();
+        return buf.as_str();
+    }
+
+    let mut unit = 0;
+    let mut divisor = 1024u64;
+    // in tenths, rounded; moves up a unit if rounding reaches 1024.0
+    let tenths = loop {
+        let tenths = (bytes as u64 * 10 + divisor / 2) / divisor;
+        if tenths < 10240 || unit == UNITS.len() - 1 {
+            break tenths;
+        }
+        unit += 1;
+        divisor *= 1024;
+    };
+    write_fixed(buf, tenths as i64, 1);
+    write!(buf, " {}", UNITS[unit]).ok();
+    buf.as_str()
+}
+
+/// Formats `value` with an SI prefix and one decimal place, followed by `unit` (e.g.
+/// `si(&mut buf, 0.0032, "A")` gives `3.2 mA`, `si(&mut buf, 1500.0, "")` gives `1.5 k`).
+///
+/// The prefixes range from `p` to `T`, with `u` for micro. Non-finite values are formatted
+/// as `--`.
+///
+/// # Returns
+///
+/// The formatted text.
+pub fn si<'a, const N: usize>(buf: &'a mut Buf<N>, value: f32, unit: &str) -> &'a str {
+    const PREFIXES: [&str; 9] = ["p", "n", "u", "m", "", "k", "M", "G", "T"];
+    const NONE: usize = 4;
+
+    buf.clear();
+    if !value.is_finite() {
+        buf.push_str("--");
+        return buf.as_str();
+    }
+
+    // find the prefix for which the value is in [1, 1000)
+    let mut prefix = NONE;
+    let mut scaled = value;
+    if value != 0.0 {
+        while scaled.abs() >= 1000.0 && prefix < PREFIXES.len() - 1 {
+            scaled /= 1000.0;
+            prefix += 1;
+        }
+        while scaled.abs() < 1.0 && prefix > 0 {
+            scaled *= 1000.0;
+            prefix -= 1;
+        }
+    }
+    let round = |x: f32| {
+        let x = x * 10.0;
+        if x < 0.0 {
+            (x - 0.5) as i64
+        } else {
+            (x + 0.5) as i64
+        }
+    };
+    let mut tenths = round(scaled);
+    // e.g. 999.96 rounds to 1000.0, which is 1.0 of the next prefix
+    if tenths.abs() >= 10000 && prefix < PREFIXES.len() - 1 {
+        prefix += 1;
+        tenths = round(scaled / 1000.0);
+    }
+
+    write_fixed(buf, tenths, 1);
+    if !PREFIXES[prefix].is_empty() || !unit.is_empty() {
+        write!(buf, " {}{}", PREFIXES[prefix], unit).ok();
+    }
+    buf.as_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_into_buffer() {
+        let mut buf = Buf::<16>::new();
+        assert_eq!(buf.format(format_args!("{} + {}", 1, 2)), "1 + 2");
+        assert!(!buf.truncated());
+
+        // formatting again replaces the text
+        assert_eq!(buf.format(format_args!("{:>4}", 7)), "   7");
+        assert_eq!(buf.len(), 4);
+    }
+
+    #[test]
+    fn truncates_at_char_boundaries() {
+        let mut buf = Buf::<4>::new();
+        assert_eq!(buf.format(format_args!("abcdef")), "abcd");
+        assert!(buf.truncated());
+
+        // "°" is two bytes and doesn't fit after "12."
+        assert_eq!(buf.format(format_args!("12.°")), "12.");
+        assert!(buf.truncated());
+
+        buf.clear();
+        assert!(buf.is_empty());
+        assert!(!buf.truncated());
+    }
+
+    #[test]
+    fn durations() {
+        let mut buf = Buf::<16>::new();
+        assert_eq!(duration(&mut buf, 0), "0:00");
+        assert_eq!(duration(&mut buf, 59), "0:59");
+        assert_eq!(duration(&mut buf, 90), "1:30");
+        assert_eq!(duration(&mut buf, 3600), "1:00:00");
+        assert_eq!(duration(&mut buf, u32::MAX), "1193046:28:15");
+    }
+
+    #[test]
+    fn byte_sizes() {
+        let mut buf = Buf::<16>::new();
+        assert_eq!(bytes(&mut buf, 0), "0 B");
+        assert_eq!(bytes(&mut buf, 1023), "1023 B");
+        assert_eq!(bytes(&mut buf, 1024), "1.0 KB");
+        assert_eq!(bytes(&mut buf, 1536), "1.5 KB");
+        // rounds up to the next unit instead of "1024.0 KB"
+        assert_eq!(bytes(&mut buf, 1024 * 1024 - 1), "1.0 MB");
+        assert_eq!(bytes(&mut buf, u32::MAX), "4.0 GB");
+    }
+
+    #[test]
+    fn si_prefixes() {
+        let mut buf = Buf::<16>::new();
+        assert_eq!(si(&mut buf, 0.0, "V"), "0.0 V");
+        assert_eq!(si(&mut buf, 0.0032, ""), "3.2 m");
+        assert_eq!(si(&mut buf, -0.0032, "A"), "-3.2 mA");
+        assert_eq!(si(&mut buf, 1.0, ""), "1.0");
+        assert_eq!(si(&mut buf, 999.96, "Hz"), "1.0 kHz");
+        assert_eq!(si(&mut buf, 4.7e-9, "F"), "4.7 nF");
+        assert_eq!(si(&mut buf, u32::MAX as f32, "B"), "4.3 GB");
+        assert_eq!(si(&mut buf, f32::INFINITY, "V"), "--");
+    }
+
+    #[test]
+    fn fixed_point() {
+        let mut buf = Buf::<16>::new();
+        assert_eq!(fixed(&mut buf, -325, 2), "-3.25");
+        assert_eq!(fixed(&mut buf, 5, 3), "0.005");
+        assert_eq!(fixed(&mut buf, 0, 0), "0");
+
+        // 10^19 is the largest scale in a u64
+        let mut buf = Buf::<32>::new();
+        assert_eq!(fixed(&mut buf, i64::MIN, 19), "-0.9223372036854775808");
+        assert_eq!(fixed(&mut buf, 25, 20), "0.00000000000000000025");
+        assert_eq!(fixed(&mut buf, -1, 28), "-0.0000000000000000000000000001");
+        // truncated, but doesn't overflow
+        assert_eq!(
+            fixed(&mut buf, 1, u8::MAX),
+            "0.000000000000000000000000000000"
+        );
+    }
+}
@@ -44,6 +44,11 @@ impl<'a, C: PixelColor> WidgetFramebuf<'a, C> {
     pub fn get_size(&self) -> Size {
         self.size
     }
+
+    /// Returns the buffer, e.g. to keep what was drawn into it.
+    pub fn into_buf(self) -> &'a mut [C] {
+        self.buf
+    }
 }
 
 impl<C: PixelColor> Dimensions for WidgetFramebuf<'_, C> {
@@ -62,15 +67,19 @@ impl<C: PixelColor> DrawTarget for WidgetFramebuf<'_, C> {
     {
         for pixel in pixels {
             let pt = pixel.0.sub(self.position);
-            let pos = pt.y * self.size.width as i32 + pt.x;
-            if pos < 0 || pos >= self.len as i32
-            /* check for trunc maybe? */
+            // skip pixels outside of the framebuffer (instead of wrapping them to another row)
+            if pt.x < 0
+                || pt.y < 0
+                || pt.x >= self.size.width as i32
+                || pt.y >= self.size.height as i32
             {
-                // !! Make sure that len is correct in new() !!
-                // skip pixels outside of the framebuffer
                 continue;
             }
-            self.buf[pos as usize] = pixel.1;
+            // !! Make sure that len is correct in new() !!
+            let pos = (pt.y * self.size.width as i32 + pt.x) as usize;
+            if pos < self.len {
+                self.buf[pos] = pixel.1;
+            }
         }
 
         Ok(())
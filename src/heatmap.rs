@@ -0,0 +1,355 @@
+//! # Heatmap Widget
+//!
+//! A [Heatmap] shows a grid of intensities as colored cells, e.g. the frames of a thermal
+//! camera. The cells are row-major `u8`s, mapped to colors with a [Colormap], and scaled to the
+//! widget's size (nearest neighbor). Neighboring cells of the same color are drawn as one
+//! rectangle, so smooth images take few draw calls.
+//!
+//! This module is only available with the `charts` feature (on by default).
+//!
+//! As the colormap has colors of its own, a heatmap works in [Ui]s of color types that can be
+//! converted from [Rgb888] (see [TypedWidget]).
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::heatmap::{Colormap, HeatmapCache};
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # fn read_frame(frame: &mut [u8]) -> u32 { 0 }
+//! let mut frame = [0u8; 32 * 24];
+//! let mut smartstate = Smartstate::empty();
+//! let mut cache = HeatmapCache::<{ 32 * 24 }>::new();
+//!
+//! loop {
+//!     let frame_number = read_frame(&mut frame);
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     ui.add(
+//!         Heatmap::new(&frame, 32, 24)
+//!             .size(Size::new(192, 144))
+//!             .colormap(Colormap::Thermal)
+//!             .generation(frame_number)
+//!             .smartstate(&mut smartstate)
+//!             .cache(&mut cache),
+//!     );
+//! }
+//! ```
+//!
+//! ## Redrawing
+//!
+//! With a [smartstate](Heatmap::smartstate), the heatmap is only redrawn when the cells change:
+//! when the [generation](Heatmap::generation) changes, or, without one, the hash of the cells.
+//! With a [HeatmapCache], which remembers the drawn cells, only the cells whose color changed
+//! are redrawn.
+
+use crate::smartstate::{Container, Smartstate};
+use crate::ui::{GuiError, GuiResult, Response, TypedWidget, Ui};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888};
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+/// The "thermal" colormap, from black through blue, red and yellow to white
+const THERMAL: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (20, 0, 60),
+    (40, 0, 110),
+    (70, 0, 140),
+    (100, 0, 150),
+    (130, 0, 145),
+    (160, 0, 130),
+    (190, 20, 100),
+    (215, 45, 60),
+    (235, 75, 20),
+    (245, 110, 0),
+    (250, 145, 0),
+    (255, 180, 0),
+    (255, 210, 40),
+    (255, 235, 120),
+    (255, 255, 220),
+];
+
+/// How the intensities of a [Heatmap] are colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Colormap {
+    /// From black (0) to white (255)
+    #[default]
+    Grayscale,
+    /// From black through blue, red and yellow to white, in 16 steps
+    Thermal,
+}
+
+impl Colormap {
+    /// Returns the color of an intensity.
+    pub fn color(self, value: u8) -> Rgb888 {
+        match self {
+            Colormap::Grayscale => Rgb888::new(value, value, value),
+            Colormap::Thermal => {
+                let (r, g, b) = THERMAL[value as usize >> 4];
+                Rgb888::new(r, g, b)
+            }
+        }
+    }
+}
+
+/// The cells a [Heatmap] drew, so that it only redraws the changed ones
+///
+/// Holds up to `CELLS` cells; a heatmap with more cells is drawn as if there was no cache. See
+/// [retained state](Smartstate#retained-state).
+#[derive(Clone, Debug)]
+pub struct HeatmapCache<const CELLS: usize> {
+    cells: [u8; CELLS],
+    /// The area, grid and colormap the cells were drawn with
+    key: Smartstate,
+}
+
+impl<const CELLS: usize> HeatmapCache<CELLS> {
+    /// Creates an empty cache, so the heatmap is drawn fully the first time.
+    pub fn new() -> Self {
+        Self {
+            cells: [0; CELLS],
+            key: Smartstate::empty(),
+        }
+    }
+}
+
+impl<const CELLS: usize> Default for HeatmapCache<CELLS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A grid of colored intensities, see the [module documentation](self).
+pub struct Heatmap<'a> {
+    cells: &'a [u8],
+    columns: u32,
+    rows: u32,
+    size: Option<Size>,
+    colormap: Colormap,
+    generation: Option<u32>,
+    smartstate: Container<'a, Smartstate>,
+    cache: Option<(&'a mut [u8], &'a mut Smartstate)>,
+}
+
+impl<'a> Heatmap<'a> {
+    /// Creates a heatmap of a grid of `columns` × `rows` cells, row by row in `cells`. Missing
+    /// cells (if `cells` is too short) are 0.
+    pub fn new(cells: &'a [u8], columns: u32, rows: u32) -> Self {
+        Self {
+            cells,
+            columns: columns.max(1),
+            rows: rows.max(1),
+            size: None,
+            colormap: Colormap::default(),
+            generation: None,
+            smartstate: Container::empty(),
+            cache: None,
+        }
+    }
+
+    /// Sets the size of the heatmap, which is at least a pixel per cell. By default, it takes
+    /// the remaining width of the row, with square cells.
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Sets the colormap, [Colormap::Grayscale] by default.
+    pub fn colormap(mut self, colormap: Colormap) -> Self {
+        self.colormap = colormap;
+        self
+    }
+
+    /// Sets a number that changes whenever the cells do, e.g. a frame counter, so that the
+    /// [smartstate](Self::smartstate) doesn't have to hash the cells.
+    pub fn generation(mut self, generation: u32) -> Self {
+        self.generation = Some(generation);
+        self
+    }
+
+    /// Adds a smartstate, so that the heatmap is only redrawn when the cells change.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+
+    /// Adds a cache of the drawn cells, so that only the changed cells are redrawn.
+    pub fn cache<const CELLS: usize>(mut self, cache: &'a mut HeatmapCache<CELLS>) -> Self {
+        self.cache = Some((&mut cache.cells, &mut cache.key));
+        self
+    }
+
+    /// Returns the cell in `column` of `row`
+    fn cell(&self, column: u32, row: u32) -> u8 {
+        let index = (row * self.columns + column) as usize;
+        self.cells.get(index).copied().unwrap_or(0)
+    }
+}
+
+impl<COL: PixelColor + From<Rgb888>> TypedWidget<COL> for Heatmap<'_> {
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        // get size
+        let (columns, rows) = (self.columns, self.rows);
+        let size = self.size.unwrap_or_else(|| {
+            let width = ui.remaining_width();
+            Size::new(width, width * rows / columns)
+        });
+        let size = Size::new(size.width.max(columns), size.height.max(rows));
+
+        // allocate space
+        let iresponse = ui.allocate_space(size)?;
+        let area = iresponse.area;
+
+        // check smartstate
+        let mut layout = Smartstate::empty();
+        layout.set_state_hashed(&(area, columns, rows, self.colormap));
+        layout.mix(ui.style_key());
+        // (the cells are only hashed without a generation)
+        let cells = self.generation.is_none().then_some(self.cells);
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(&(self.generation, cells, area, columns, rows, self.colormap));
+        state.mix(ui.style_key());
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(area);
+        self.smartstate.modify(|st| *st = state);
+        if !redraw {
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+
+        // the cells cover the whole area, so it is never cleared
+        ui.start_drawing(&area);
+        // (a buffer is flushed over the whole area, so all cells have to be drawn to it)
+        let buffered = ui.clear_buffer_raw(ui.style().background_color);
+        let n = (columns * rows) as usize;
+        let mut cache = self.cache.take().filter(|(cells, _)| cells.len() >= n);
+        let partial = match &cache {
+            Some((_, key)) => **key == layout && !buffered && !ui.invalidated(area),
+            None => false,
+        };
+
+        let color = |value: u8| COL::from(self.colormap.color(value));
+        let x = |column: u32| area.top_left.x + (column * area.size.width / columns) as i32;
+        let y = |row: u32| area.top_left.y + (row * area.size.height / rows) as i32;
+        for row in 0..rows {
+            let mut column = 0;
+            while column < columns {
+                let value = self.cell(column, row);
+                let changed = |column: u32, value: u8| match (&cache, partial) {
+                    (Some((cells, _)), true) => {
+                        color(cells[(row * columns + column) as usize]) != color(value)
+                    }
+                    _ => true,
+                };
+                if !changed(column, value) {
+                    column += 1;
+                    continue;
+                }
+                // a run of cells of the same color (which may include unchanged ones)
+                let start = column;
+                let run_color = color(value);
+                column += 1;
+                while column < columns && color(self.cell(column, row)) == run_color {
+                    column += 1;
+                }
+                let run = Rectangle::with_corners(
+                    Point::new(x(start), y(row)),
+                    Point::new(x(column) - 1, y(row + 1) - 1),
+                );
+                ui.draw(&run.into_styled(PrimitiveStyle::with_fill(run_color)))
+                    .map_err(|_| GuiError::DrawError(Some("Couldn't draw Heatmap cells")))?;
+            }
+        }
+        ui.finalize()?;
+
+        if let Some((cells, key)) = &mut cache {
+            for (index, cell) in cells.iter_mut().take(n).enumerate() {
+                *cell = self.cells.get(index).copied().unwrap_or(0);
+            }
+            **key = layout;
+        }
+        self.cache = cache;
+
+        Ok(Response::new(iresponse).set_redraw(true))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// Draws a 4x2 grayscale heatmap of 40x20 pixels, returning its area and the pixels drawn
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        cells: &[u8; 8],
+        smartstate: &mut Smartstate,
+        cache: &mut HeatmapCache<8>,
+    ) -> (Rectangle, u32) {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        let response = ui.add(
+            Heatmap::new(cells, 4, 2)
+                .size(Size::new(40, 20))
+                .smartstate(smartstate)
+                .cache(cache),
+        );
+        (response.area(), ui.perf().pixels_drawn)
+    }
+
+    #[test]
+    fn cells_are_scaled_and_only_changed_ones_redrawn() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+        let mut cache = HeatmapCache::new();
+        let mut cells = [0, 0, 0, 255, 255, 255, 0, 0];
+
+        let (area, drawn) = frame(&mut display, &cells, &mut smartstate, &mut cache);
+        assert_eq!(drawn, 40 * 20);
+        let pixel = |display: &MockDisplay<Rgb565>, x: i32, y: i32| {
+            display.get_pixel(area.top_left + Point::new(x, y))
+        };
+        // cells of 10x10 pixels
+        assert_eq!(pixel(&display, 29, 9), Some(Rgb565::BLACK));
+        assert_eq!(pixel(&display, 30, 0), Some(Rgb565::WHITE));
+        assert_eq!(pixel(&display, 19, 10), Some(Rgb565::WHITE));
+        assert_eq!(pixel(&display, 20, 19), Some(Rgb565::BLACK));
+
+        // unchanged, nothing is drawn
+        let (_, drawn) = frame(&mut display, &cells, &mut smartstate, &mut cache);
+        assert_eq!(drawn, 0);
+        // a single cell changed, only it is redrawn
+        cells[1] = 128;
+        let (_, drawn) = frame(&mut display, &cells, &mut smartstate, &mut cache);
+        assert_eq!(drawn, 10 * 10);
+        assert_eq!(
+            pixel(&display, 15, 5),
+            Some(Rgb565::from(Rgb888::new(128, 128, 128)))
+        );
+        assert_eq!(pixel(&display, 5, 5), Some(Rgb565::BLACK));
+    }
+
+    #[test]
+    fn cells_of_the_same_color_are_drawn_together() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        // the first six cells share a color in the thermal colormap
+        let cells = [0, 1, 2, 3, 4, 15, 16, 255];
+        ui.add(
+            Heatmap::new(&cells, 8, 1)
+                .size(Size::new(16, 2))
+                .colormap(Colormap::Thermal),
+        );
+        assert_eq!(ui.perf().draw_calls, 3);
+    }
+}
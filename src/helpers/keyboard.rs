@@ -1,11 +1,15 @@
 //! Helper for drawing entire on-screen keyboard to the GUI.
 //!
+//! This module is only available with the `text-input` feature (on by default).
+//!
 //! This module contains keyboard layouts for different languages and regions (QWERTY, QWERTZ, AZERTY),
 //! along with functionality to draw an interactive on-screen keyboard.
 use crate::button::Button;
 use crate::iconbutton::IconButton;
 use crate::smartstate::SmartstateProvider;
-use crate::ui::{InternalResponse, Response, Ui};
+use crate::style::Fill;
+use crate::ui::{InternalResponse, KeyEvent, Response, Ui};
+use embedded_graphics::pixelcolor::Rgb888;
 use embedded_graphics::prelude::*;
 use embedded_iconoir::size16px;
 
@@ -489,6 +493,12 @@ impl<'a> Layout<'a> {
 /// The keyboard will automatically use smartstates to animate the buttons,
 /// if the given [SmartstateProvider] is not `None`.
 ///
+/// # Hardware Keys
+///
+/// While the keyboard is open, it also consumes the characters, backspaces and enters queued
+/// with [Ui::push_key], so that a hardware keyboard can type into the same `text`. Other keys
+/// (e.g. arrows) are left in the queue for the app.
+///
 /// # Caveats and Considerations
 ///
 /// Drawing the keyboard will take a lot of space. Therefore, if your screen size is limited,
@@ -517,10 +527,11 @@ impl<'a> Layout<'a> {
 /// * A `Response` made from an `InternalResponse::empty()`.
 ///   If a key was pressed, shift was clicked, or a key was erased, `response.changed()` will be `true`.
 ///   If a key was pressed (irrelevant of changes), `response.clicked()` will be `true`.
+///   If enter was pressed on a hardware keyboard, `response.confirmed()` will be `true`.
 #[allow(clippy::too_many_arguments)]
 pub fn draw_keyboard<
     DRAW: DrawTarget<Color = COL>,
-    COL: PixelColor,
+    COL: PixelColor + Into<Rgb888> + From<Rgb888>,
     const M: usize,
     const N: usize,
 >(
@@ -573,6 +584,24 @@ pub fn draw_keyboard<
 
     let mut clicked = false;
     let mut changed = false;
+    let mut confirmed = false;
+
+    // hardware keys
+    ui.consume_keys(|key| match key {
+        KeyEvent::Char(c) => {
+            changed |= text.push(c).is_ok();
+            true
+        }
+        KeyEvent::Backspace => {
+            changed |= text.pop().is_some();
+            true
+        }
+        KeyEvent::Enter => {
+            confirmed = true;
+            true
+        }
+        _ => false,
+    });
 
     if draw_num_row {
         if pad {
@@ -711,7 +740,7 @@ pub fn draw_keyboard<
 
     ui.sub_ui(|ui| {
         if *shift {
-            ui.style_mut().item_background_color = ui.style().primary_color;
+            ui.style_mut().item_background_color = Fill::Solid(ui.style().primary_color);
         }
 
         if ui
@@ -790,5 +819,6 @@ pub fn draw_keyboard<
     Response::new(InternalResponse::empty())
         .set_clicked(clicked)
         .set_changed(changed)
+        .set_confirmed(confirmed)
         .set_down(redraw)
 }
@@ -1 +1,3 @@
+#[cfg(feature = "text-input")]
 pub mod keyboard;
+pub mod router;
@@ -0,0 +1,245 @@
+//! Helper for apps with multiple screens.
+//!
+//! Apps with more than one screen usually keep an enum of screens, and have to clear the
+//! background and invalidate all smartstates whenever the screen changes. A [Router] does this
+//! bookkeeping: it owns the current screen and a [SmartstateProvider] shared by all screens,
+//! and after every [navigation](Router::navigate), the next frame starts with a cleared
+//! background and forced redraws.
+//!
+//! The router doesn't take over the app structure: every frame, [Router::run] calls a closure
+//! with the current screen, and the closure draws it (and returns whatever the app needs,
+//! e.g. the next screen).
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::helpers::router::Router;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//!
+//! #[derive(Clone, Copy, PartialEq, Eq)]
+//! enum Screen {
+//!     Home,
+//!     Settings,
+//! }
+//!
+//! let mut router = Router::<Screen, 20>::new(Screen::Home);
+//!
+//! loop {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     // [...] handle input
+//!
+//!     let next = router
+//!         .run(&mut ui, |screen, ui, smartstates| match screen {
+//!             Screen::Home => {
+//!                 ui.add(Label::new("Home").smartstate(smartstates.nxt()));
+//!                 let settings = ui.add(Button::new("Settings").smartstate(smartstates.nxt()));
+//!                 settings.clicked().then_some(Screen::Settings)
+//!             }
+//!             Screen::Settings => {
+//!                 ui.add(Label::new("Settings").smartstate(smartstates.nxt()));
+//!                 // [...]
+//!                 None
+//!             }
+//!         })
+//!         .unwrap();
+//!     if let Some(next) = next {
+//!         router.navigate(next);
+//!     }
+//! }
+//! ```
+use crate::smartstate::SmartstateProvider;
+use crate::ui::{GuiResult, Ui};
+use embedded_graphics::prelude::*;
+
+/// Maximum number of screens remembered for [Router::back]
+pub const HISTORY_DEPTH: usize = 8;
+
+/// Tracks the current screen of an app, see the [module documentation](self).
+///
+/// `S` identifies a screen (usually a fieldless enum), `N` is the number of smartstates
+/// shared by all screens, and `K` the number of keyed smartstates (see
+/// [SmartstateProvider::get_keyed]).
+pub struct Router<S: Copy + Eq, const N: usize, const K: usize = 0> {
+    current: S,
+    history: heapless::Vec<S, HISTORY_DEPTH>,
+    smartstates: SmartstateProvider<N, K>,
+    /// Whether the next frame has to clear the background and redraw everything
+    invalidated: bool,
+    went_back: bool,
+}
+
+impl<S: Copy + Eq, const N: usize, const K: usize> Router<S, N, K> {
+    /// Creates a router showing `initial`. The first frame clears the background.
+    pub fn new(initial: S) -> Self {
+        Self {
+            current: initial,
+            history: heapless::Vec::new(),
+            smartstates: SmartstateProvider::new(),
+            invalidated: true,
+            went_back: false,
+        }
+    }
+
+    /// Returns the current screen.
+    pub fn current(&self) -> S {
+        self.current
+    }
+
+    /// Switches to `screen`, remembering the current screen for [Router::back].
+    ///
+    /// The next frame clears the background and redraws all widgets. Navigating to the current
+    /// screen does nothing. If the history is full ([HISTORY_DEPTH]), the oldest screen is
+    /// forgotten.
+    pub fn navigate(&mut self, screen: S) {
+        if screen == self.current {
+            return;
+        }
+        if self.history.is_full() {
+            self.history.remove(0);
+        }
+        self.history.push(self.current).ok();
+        self.current = screen;
+        self.went_back = false;
+        self.invalidate();
+    }
+
+    /// Returns to the previous screen, like [Router::navigate].
+    ///
+    /// ## Returns
+    ///
+    /// `false` if there is no previous screen.
+    pub fn back(&mut self) -> bool {
+        let Some(previous) = self.history.pop() else {
+            return false;
+        };
+        self.current = previous;
+        self.went_back = true;
+        self.invalidate();
+        true
+    }
+
+    /// Returns whether there is a previous screen to go [back](Router::back) to.
+    pub fn can_go_back(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    /// Returns whether the current screen was reached by going [back](Router::back)
+    /// (e.g. to keep a screen's scroll position instead of resetting it).
+    pub fn went_back(&self) -> bool {
+        self.went_back
+    }
+
+    /// Makes the next frame clear the background and redraw all widgets, e.g. after a popup
+    /// drawn on top of the screen was closed.
+    pub fn invalidate(&mut self) {
+        self.invalidated = true;
+    }
+
+    /// Returns the smartstates shared by all screens.
+    pub fn smartstates(&mut self) -> &mut SmartstateProvider<N, K> {
+        &mut self.smartstates
+    }
+
+    /// Draws a frame: calls `f` with the current screen, the [Ui] and the smartstates (with the
+    /// counter restarted).
+    ///
+    /// If the screen changed (or [Router::invalidate] was called) since the last frame, the
+    /// background is cleared and all smartstates are forced to redraw before calling `f`.
+    ///
+    /// ## Returns
+    ///
+    /// What `f` returned, or the error of clearing the background.
+    pub fn run<DRAW, COL, R>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+        f: impl FnOnce(S, &mut Ui<DRAW, COL>, &mut SmartstateProvider<N, K>) -> R,
+    ) -> GuiResult<R>
+    where
+        DRAW: DrawTarget<Color = COL>,
+        COL: PixelColor,
+    {
+        self.smartstates.restart_counter();
+        if self.invalidated {
+            ui.clear_background()?;
+            self.smartstates.force_redraw_all();
+            self.invalidated = false;
+        }
+        Ok(f(self.current, ui, &mut self.smartstates))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::button::Button;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Screen {
+        Home,
+        Settings,
+        About,
+    }
+
+    /// Runs a frame, returning the number of redrawn widgets
+    fn frame(router: &mut Router<Screen, 4>) -> u32 {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        router
+            .run(&mut ui, |_, ui, smartstates| {
+                ui.add(Button::new("A").smartstate(smartstates.nxt()));
+                ui.add(Button::new("B").smartstate(smartstates.nxt()));
+            })
+            .unwrap();
+        ui.perf().widgets_redrawn
+    }
+
+    #[test]
+    fn navigation_redraws_everything_once() {
+        let mut router = Router::new(Screen::Home);
+        assert_eq!(frame(&mut router), 2);
+        assert_eq!(frame(&mut router), 0);
+
+        router.navigate(Screen::Settings);
+        assert_eq!(frame(&mut router), 2);
+        assert_eq!(frame(&mut router), 0);
+
+        // same screen: nothing to do
+        router.navigate(Screen::Settings);
+        assert_eq!(frame(&mut router), 0);
+    }
+
+    #[test]
+    fn back_returns_through_history() {
+        let mut router = Router::<Screen, 4>::new(Screen::Home);
+        assert!(!router.back());
+
+        router.navigate(Screen::Settings);
+        router.navigate(Screen::About);
+        assert!(!router.went_back());
+        assert!(router.back());
+        assert_eq!(router.current(), Screen::Settings);
+        assert!(router.went_back());
+        assert!(router.back());
+        assert_eq!(router.current(), Screen::Home);
+        assert!(!router.can_go_back());
+
+        // the oldest screens are forgotten
+        for _ in 0..HISTORY_DEPTH {
+            router.navigate(Screen::Settings);
+            router.navigate(Screen::About);
+        }
+        let mut depth = 0;
+        while router.back() {
+            depth += 1;
+        }
+        assert_eq!(depth, HISTORY_DEPTH);
+    }
+}
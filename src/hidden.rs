@@ -0,0 +1,194 @@
+//! # Hidden Widgets
+//!
+//! A [Hidden] widget wraps another widget that may be invisible, e.g. a "Cancel" button that
+//! is only shown while a transfer is running. An invisible widget keeps its normal size in the
+//! layout, so the widgets after it don't move, but nothing is drawn there except the
+//! background, and it reports no interactions.
+//!
+//! Every [Widget] can be wrapped with [Visibility::visible]. The wrapped widget is created in
+//! both cases, so it consumes its smartstate (e.g. from
+//! [SmartstateProvider::nxt](crate::smartstate::SmartstateProvider::nxt)) whether it is shown
+//! or not, and the following widgets keep theirs.
+//!
+//! The [Hidden] widget has a [Smartstate] of its own, which it uses to detect when the widget is
+//! hidden or shown: the area is erased once when it is hidden, and the widget is redrawn when it
+//! is shown again, even if its own smartstate is unchanged. Without a smartstate, the area is
+//! erased (or the widget redrawn) every frame.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::hidden::Visibility;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! # let transferring = true;
+//! # let mut cancel_visibility = Smartstate::empty();
+//! ui.add_horizontal(Label::new("Transfer").smartstate(smartstates.nxt()));
+//! if ui
+//!     .add_horizontal(
+//!         Button::new("Cancel")
+//!             .smartstate(smartstates.nxt())
+//!             .visible(transferring)
+//!             .smartstate(&mut cancel_visibility),
+//!     )
+//!     .clicked()
+//! {
+//!     // never clicked while hidden
+//! }
+//! // always in the same place
+//! ui.add_horizontal(Button::new("Close").smartstate(smartstates.nxt()));
+//! ```
+
+use crate::smartstate::{Container, Smartstate};
+use crate::ui::{GuiResult, Interaction, InternalResponse, Response, TypedWidget, Ui, Widget};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::pixelcolor::PixelColor;
+
+/// A widget that may be invisible, see the [module documentation](self).
+pub struct Hidden<'a, W> {
+    widget: W,
+    visible: bool,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<'a, W> Hidden<'a, W> {
+    /// Wraps `widget`, which is only drawn if `visible` is set.
+    pub fn new(widget: W, visible: bool) -> Self {
+        Self {
+            widget,
+            visible,
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Adds a [Smartstate] for incremental redrawing. It tracks whether the widget was visible,
+    /// and is separate from the smartstate of the wrapped widget.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+/// Adds [Visibility::visible] to all [Widget]s, and the buttons (which are [TypedWidget]s for
+/// the color types that can draw [gradients](crate::style::Fill::VGradient))
+pub trait Visibility: Sized {
+    /// Wraps the widget in a [Hidden] widget, which draws it only if `visible` is set.
+    fn visible<'a>(self, visible: bool) -> Hidden<'a, Self> {
+        Hidden::new(self, visible)
+    }
+}
+
+impl<W: Widget> Visibility for W {}
+
+impl<COL: PixelColor, W: TypedWidget<COL>> TypedWidget<COL> for Hidden<'_, W> {
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let mut state = Smartstate::state(self.visible as u32);
+        state.mix(ui.style_key());
+        let changed = !self.smartstate.eq_option(&Some(state));
+
+        if self.visible {
+            if changed {
+                // the area was erased while hidden
+                ui.invalidate_next_allocation();
+            }
+            let response = self.widget.draw_typed(ui);
+            if response.is_ok() {
+                self.smartstate.modify(|st| *st = state);
+            }
+            return response;
+        }
+
+        let size = ui.measure_widget(&mut self.widget);
+        let area = ui.allocate_space(size)?.area;
+        self.smartstate.modify(|st| *st = state);
+
+        let redraw = changed || ui.invalidated(area);
+        if redraw {
+            ui.start_drawing(&area);
+            if !ui.cleared() {
+                ui.clear_area(area)?;
+            }
+            ui.finalize()?;
+        }
+
+        Ok(Response::new(InternalResponse::new(area, Interaction::None)).set_redraw(redraw))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::button::Button;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::{Point, PointsIter};
+    use embedded_graphics::primitives::Rectangle;
+
+    /// Draws a button that may be hidden and a label after it, returning the button's response,
+    /// the label's area and the number of redrawn widgets
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        smartstates: &mut [Smartstate; 3],
+        visible: bool,
+        interaction: Interaction,
+    ) -> (Response, Rectangle, u32) {
+        let [button, hidden, label] = smartstates;
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        ui.interact(interaction);
+        let response = ui.add(
+            Button::new("Go")
+                .smartstate(button)
+                .visible(visible)
+                .smartstate(hidden),
+        );
+        let after = ui.add(crate::label::Label::new("x").smartstate(label));
+        (response, after.area(), ui.perf().widgets_redrawn)
+    }
+
+    #[test]
+    fn hidden_widgets_keep_their_space_and_erase_themselves() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = [Smartstate::empty(); 3];
+        let style = medsize_rgb565_style();
+        let none = Interaction::None;
+
+        let (shown, label, redrawn) = frame(&mut display, &mut smartstates, true, none);
+        assert_eq!(redrawn, 2);
+        let area = shown.area();
+        let drawn = |display: &MockDisplay<Rgb565>| {
+            area.points()
+                .any(|p| display.get_pixel(p) != Some(style.background_color))
+        };
+        assert!(drawn(&display));
+
+        // hiding erases the button, without moving the label
+        let click = Interaction::Click(area.center());
+        let (hidden, hidden_label, redrawn) = frame(&mut display, &mut smartstates, false, click);
+        assert_eq!((hidden.area(), hidden_label), (area, label));
+        assert!(hidden.redraw && !hidden.clicked() && !hidden.down());
+        assert_eq!(redrawn, 1);
+        assert!(!drawn(&display));
+
+        // nothing to do while it stays hidden
+        let (hidden, _, _) = frame(&mut display, &mut smartstates, false, none);
+        assert!(!hidden.redraw);
+
+        // shown again, the button is redrawn although its own smartstate is unchanged
+        let (_, _, redrawn) = frame(&mut display, &mut smartstates, true, none);
+        assert_eq!(redrawn, 1);
+        assert!(drawn(&display));
+        assert_eq!(area.top_left, Point::new(3, 3));
+    }
+}
@@ -0,0 +1,287 @@
+//! # Histogram Widget
+//!
+//! A [Histogram] draws bin counts as vertical bars, e.g. of measured inter-arrival times. The
+//! bars are scaled to the largest bin, and can be scaled logarithmically
+//! (see [Histogram::log_scale]) to keep small bins visible next to a dominant one. The left
+//! and bottom edges are drawn as axes in the border color.
+//!
+//! This module is only available with the `charts` feature (on by default).
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! let mut bins = [0u32; 16];
+//! let mut smartstate = Smartstate::empty();
+//! let mut bars = [Smartstate::empty(); 16];
+//!
+//! loop {
+//!     # let interval_ms = 12u32;
+//!     bins[(interval_ms as usize / 4).min(15)] += 1;
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     ui.add(
+//!         Histogram::new(&bins)
+//!             .size(Size::new(160, 60))
+//!             .log_scale()
+//!             .smartstate(&mut smartstate)
+//!             .bar_smartstates(&mut bars),
+//!     );
+//! }
+//! ```
+//!
+//! ## Smartstates
+//!
+//! The [smartstate](Histogram::smartstate) tracks a hash of the bins, so an unchanged
+//! histogram isn't drawn at all. With [bar smartstates](Histogram::bar_smartstates) (one per
+//! bin), a change only repaints the columns of the bars whose height changed, e.g. a single
+//! column when one bin grows without changing the scale.
+
+use crate::smartstate::{Container, Smartstate};
+use crate::ui::{GuiError, GuiResult, Response, Ui, Widget};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+/// Bars of bin counts, see the [module documentation](self).
+pub struct Histogram<'a, const N: usize> {
+    bins: &'a [u32; N],
+    size: Option<Size>,
+    log_scale: bool,
+    smartstate: Container<'a, Smartstate>,
+    bar_smartstates: &'a mut [Smartstate],
+}
+
+impl<'a, const N: usize> Histogram<'a, N> {
+    /// Creates a histogram of the counts in `bins`.
+    pub fn new(bins: &'a [u32; N]) -> Self {
+        Self {
+            bins,
+            size: None,
+            log_scale: false,
+            smartstate: Container::empty(),
+            bar_smartstates: &mut [],
+        }
+    }
+
+    /// Sets the size of the histogram, including the axes. By default, it takes the remaining
+    /// width of the row, and is three times as high as the style's default widget height.
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Scales the bars with the (integer) base 2 logarithm of the counts, so that a bar is one
+    /// step higher for every doubling.
+    pub fn log_scale(mut self) -> Self {
+        self.log_scale = true;
+        self
+    }
+
+    /// Adds a smartstate, so that the histogram is only redrawn when the bins change.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+
+    /// Adds a smartstate per bar, in the order of the bins, so that only the bars whose height
+    /// changed are repainted.
+    ///
+    /// Bars without a smartstate (if there are fewer smartstates than bins) are repainted
+    /// whenever the histogram is.
+    pub fn bar_smartstates(mut self, smartstates: &'a mut [Smartstate]) -> Self {
+        self.bar_smartstates = smartstates;
+        self
+    }
+}
+
+/// Returns the value a bar's height is proportional to
+fn scaled(count: u32, log_scale: bool) -> u32 {
+    match log_scale {
+        // 0 for 0, 1 for 1, 2 for 2 and 3, 3 for 4 to 7, ...
+        true => u32::BITS - count.leading_zeros(),
+        false => count,
+    }
+}
+
+impl<const N: usize> Widget for Histogram<'_, N> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        // get size
+        let size = self.size.unwrap_or_else(|| {
+            Size::new(ui.remaining_width(), 3 * ui.style().default_widget_height)
+        });
+        // (at least the axes and a pixel per bar)
+        let size = Size::new(size.width.max(2 * N as u32 + 1), size.height.max(2));
+
+        // allocate space
+        let iresponse = ui.allocate_space(size)?;
+        let area = iresponse.area;
+
+        // check smartstate
+        let style_key = ui.style_key();
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(&(self.bins, self.log_scale, area.size));
+        state.mix(style_key);
+        let changed = !self.smartstate.eq_option(&Some(state));
+        self.smartstate.modify(|st| *st = state);
+        if !changed && !ui.invalidated(area) {
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+
+        // the bars are right of the left axis and above the baseline, with 1px gaps
+        let plot = Rectangle::new(
+            area.top_left + Point::new(1, 0),
+            Size::new(area.size.width - 1, area.size.height - 1),
+        );
+        let column = |i: u32| {
+            let x = |i: u32| (i * (plot.size.width + 1) / N as u32) as i32;
+            Rectangle::new(
+                plot.top_left + Point::new(x(i), 0),
+                Size::new((x(i + 1) - x(i) - 1) as u32, plot.size.height),
+            )
+        };
+        let max = self
+            .bins
+            .iter()
+            .map(|&count| scaled(count, self.log_scale))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        // check the bars' smartstates
+        let mut heights = [0; N];
+        let mut repaint = [true; N];
+        for (i, &count) in self.bins.iter().enumerate() {
+            let column = column(i as u32);
+            let value = scaled(count, self.log_scale) as u64;
+            // (non-empty bins are at least a pixel high)
+            heights[i] = match value {
+                0 => 0,
+                value => ((value * column.size.height as u64 / max as u64) as u32).max(1),
+            };
+            let mut state = Smartstate::empty();
+            state.set_state_hashed(&(heights[i], column));
+            state.mix(style_key);
+            if let Some(smartstate) = self.bar_smartstates.get_mut(i) {
+                repaint[i] = *smartstate != state || ui.invalidated(column);
+                *smartstate = state;
+            }
+        }
+
+        // the axes (and the gaps) are only drawn with all bars, or if they were drawn over
+        let left_axis = Rectangle::new(area.top_left, Size::new(1, area.size.height));
+        let baseline = Rectangle::new(
+            area.top_left + Point::new(0, area.size.height as i32 - 1),
+            Size::new(area.size.width, 1),
+        );
+        let all = repaint.iter().all(|&repaint| repaint);
+
+        ui.start_drawing(&area);
+        let cleared = ui.cleared();
+        if all && !cleared {
+            ui.clear_area(area)?;
+        }
+        if all || ui.invalidated(left_axis) || ui.invalidated(baseline) {
+            let axis_style = PrimitiveStyle::with_fill(ui.style().border_color);
+            ui.draw(&left_axis.into_styled(axis_style))
+                .and_then(|_| ui.draw(&baseline.into_styled(axis_style)))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw Histogram axes")))?;
+        }
+        let bar_style = PrimitiveStyle::with_fill(ui.style().primary_color);
+        let empty_style = PrimitiveStyle::with_fill(ui.style().background_color);
+        for i in (0..N).filter(|&i| repaint[i]) {
+            let column = column(i as u32);
+            let empty = column.size.height - heights[i];
+            let bar = Rectangle::new(
+                column.top_left + Point::new(0, empty as i32),
+                Size::new(column.size.width, heights[i]),
+            );
+            if !all && !cleared {
+                // only the part above the bar, which may have been higher
+                let above = Rectangle::new(column.top_left, Size::new(column.size.width, empty));
+                ui.draw(&above.into_styled(empty_style))
+                    .map_err(|_| GuiError::DrawError(Some("Couldn't clear Histogram bar")))?;
+            }
+            ui.draw(&bar.into_styled(bar_style))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw Histogram bar")))?;
+        }
+        ui.finalize()?;
+
+        Ok(Response::new(iresponse).set_redraw(true))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// Draws a 4-bin histogram of 41x21 pixels, returning its area and the pixels drawn
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        bins: &[u32; 4],
+        log_scale: bool,
+        smartstates: &mut [Smartstate; 5],
+    ) -> (Rectangle, u32) {
+        let [smartstate, bars @ ..] = smartstates;
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        let histogram = Histogram::new(bins)
+            .size(Size::new(41, 21))
+            .smartstate(smartstate)
+            .bar_smartstates(bars);
+        let response = match log_scale {
+            true => ui.add(histogram.log_scale()),
+            false => ui.add(histogram),
+        };
+        (response.area(), ui.perf().pixels_drawn)
+    }
+
+    #[test]
+    fn bars_scale_to_the_largest_bin_and_repaint_alone() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = [Smartstate::empty(); 5];
+        let style = medsize_rgb565_style();
+
+        let (area, drawn) = frame(&mut display, &[0, 10, 5, 20], false, &mut smartstates);
+        assert!(drawn >= 41 * 21);
+        // columns of 9 pixels at 1, 11, 21 and 31, 20 pixels high
+        let height = |display: &MockDisplay<Rgb565>, x: i32| {
+            (0..20)
+                .filter(|&y| {
+                    display.get_pixel(area.top_left + Point::new(x, y)) == Some(style.primary_color)
+                })
+                .count()
+        };
+        assert_eq!([1, 11, 21, 31].map(|x| height(&display, x)), [0, 10, 5, 20]);
+        assert_eq!(height(&display, 10), 0);
+        assert_eq!(
+            display.get_pixel(area.top_left + Point::new(20, 20)),
+            Some(style.border_color)
+        );
+
+        // unchanged, nothing is drawn
+        let (_, drawn) = frame(&mut display, &[0, 10, 5, 20], false, &mut smartstates);
+        assert_eq!(drawn, 0);
+        // a single bin changed, only its column is repainted
+        let (_, drawn) = frame(&mut display, &[0, 10, 8, 20], false, &mut smartstates);
+        assert_eq!(drawn, 9 * 20);
+        assert_eq!(height(&display, 21), 8);
+
+        // one step per doubling
+        frame(&mut display, &[0, 1, 7, 1000], true, &mut smartstates);
+        assert_eq!([1, 11, 21, 31].map(|x| height(&display, x)), [0, 2, 6, 20]);
+    }
+}
@@ -4,9 +4,12 @@
 //! It leverages the [embedded_iconoir] crate for icon data and supports Kolibri's smartstate system
 //! for efficient redrawing.
 //!
+//! This module is only available with the `icon-widgets` feature (on by default).
+//!
 //! ## Core Features
 //!
-//! - Simple display of icons from the Iconoir icon set
+//! - Simple display of icons from the Iconoir icon set, or any other icon set implementing
+//!   [GuiIcon] (e.g. 1bpp bitmaps via [RawIcon])
 //! - Automatic integration with Kolibri's theming system (uses colors from the current style)
 //! - Vertical centering of icons within the allocated space
 //! - Support for the smartstate system to minimize unnecessary redraws
@@ -16,22 +19,16 @@
 //! ```no_run
 //! # use embedded_graphics::pixelcolor::Rgb565;
 //! # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-//! # use kolibri_embedded_gui::style::medsize_rgb565_style;
-//! # use kolibri_embedded_gui::ui::Ui;
+//! # use kolibri_embedded_gui::prelude::*;
 //! # use embedded_graphics::prelude::*;
 //! # use embedded_graphics::primitives::Rectangle;
-//! # use embedded_iconoir::prelude::*;
 //! # use embedded_iconoir::size12px;
-//! # use kolibri_embedded_gui::ui::*;
 //! # use embedded_graphics::mono_font::ascii;
-//! # use kolibri_embedded_gui::label::*;
-//! # use kolibri_embedded_gui::smartstate::*;
 //! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 //! # let output_settings = OutputSettingsBuilder::new().build();
 //! # let mut window = Window::new("Kolibri Example", &output_settings);
 //! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
 //! # let mut smartstateProvider = SmartstateProvider::<20>::new();
-//! # use kolibri_embedded_gui::icon::*;
 //! // Basic icon usage
 //! ui.add(IconWidget::new(size12px::actions::AddCircle));
 //!
@@ -43,6 +40,35 @@
 //! ui.add(IconWidget::new(size12px::actions::AddCircle).smartstate(&mut my_smartstate));
 //! ```
 //!
+//! ## Custom Icon Sets
+//!
+//! Widgets take their icon as a type implementing [GuiIcon], which creates the icon image in
+//! the style's color at draw time. All Iconoir icons implement it. For other icon sets exported
+//! as raw 1bpp bitmaps (in the [ImageRaw] format, one bit per pixel, rows padded to full bytes),
+//! describe each icon with [RawIconData] and use it through [RawIcon]:
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # use kolibri_embedded_gui::icon::{RawIcon, RawIconData};
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! struct Logo;
+//!
+//! impl RawIconData for Logo {
+//!     // 8x8 pixels, a hollow square
+//!     const DATA: &'static [u8] = &[
+//!         0xFF, 0x81, 0x81, 0x81, 0x81, 0x81, 0x81, 0xFF,
+//!     ];
+//!     const WIDTH: u32 = 8;
+//! }
+//!
+//! ui.add(IconWidget::new(RawIcon::<Logo>::new()));
+//! ui.add(IconButton::<RawIcon<Logo>>::new_from_type().label("Logo"));
+//! ```
+//!
 //! ## Implementation Details
 //!
 //! The `Icon` widget uses the default icon color from the current style and allocates
@@ -56,25 +82,163 @@ use core::marker::PhantomData;
 use core::ops::Add;
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::Point;
-use embedded_graphics::image::Image;
-use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::image::{Image, ImageDrawable, ImageRaw};
+use embedded_graphics::pixelcolor::{BinaryColor, PixelColor};
 use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
 use embedded_iconoir::prelude::*;
 
-/// A widget for displaying an Iconoir icon.
+/// An icon that widgets like [IconWidget] and [IconButton](crate::iconbutton::IconButton) can
+/// draw.
+///
+/// Icons are identified by their type and drawn in a single color, which the widgets take from
+/// the current style. Implemented for all [IconoirIcon]s and for [RawIcon].
+pub trait GuiIcon {
+    /// The icon image in a color
+    type Image<C: PixelColor>: ImageDrawable<Color = C>;
+
+    /// Creates the icon image, drawn in `color`.
+    fn with_color<C: PixelColor>(color: C) -> Self::Image<C>;
+
+    /// Returns the bounding box of the icon image, with its top left corner at the origin.
+    fn bounding_box() -> Rectangle {
+        Rectangle::new(Point::zero(), Self::with_color(BinaryColor::On).size())
+    }
+}
+
+impl<Ico: IconoirIcon> GuiIcon for Ico {
+    type Image<C: PixelColor> = embedded_iconoir::Icon<C, Ico>;
+
+    fn with_color<C: PixelColor>(color: C) -> Self::Image<C> {
+        Ico::new(color)
+    }
+}
+
+/// The bitmap of a [RawIcon], in the [ImageRaw] format for [BinaryColor]: one bit per pixel,
+/// most significant bit first, with each row padded to full bytes. Set bits are drawn in the
+/// icon color, unset bits are transparent.
+pub trait RawIconData {
+    /// The bitmap. The height of the icon is derived from its length.
+    const DATA: &'static [u8];
+    /// The width of the icon in pixels
+    const WIDTH: u32;
+}
+
+/// Adapts a 1bpp bitmap (described by a [RawIconData] type) to [GuiIcon], see the
+/// [module documentation](self).
+pub struct RawIcon<D: RawIconData>(PhantomData<D>);
+
+impl<D: RawIconData> RawIcon<D> {
+    /// Creates the icon, e.g. to pass it to [IconWidget::new].
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<D: RawIconData> Default for RawIcon<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<D: RawIconData> GuiIcon for RawIcon<D> {
+    type Image<C: PixelColor> = TintedImage<'static, C>;
+
+    fn with_color<C: PixelColor>(color: C) -> Self::Image<C> {
+        TintedImage::new(ImageRaw::new(D::DATA, D::WIDTH), color)
+    }
+}
+
+/// A [BinaryColor] image drawn in a single color: set pixels are drawn in the color, unset
+/// pixels are transparent. This is the image type of [RawIcon].
+#[derive(Debug, Clone, Copy)]
+pub struct TintedImage<'a, C> {
+    raw: ImageRaw<'a, BinaryColor>,
+    color: C,
+}
+
+impl<'a, C: PixelColor> TintedImage<'a, C> {
+    /// Creates an image drawing `raw` in `color`.
+    pub const fn new(raw: ImageRaw<'a, BinaryColor>, color: C) -> Self {
+        Self { raw, color }
+    }
+}
+
+impl<C: PixelColor> OriginDimensions for TintedImage<'_, C> {
+    fn size(&self) -> Size {
+        self.raw.size()
+    }
+}
+
+impl<C: PixelColor> ImageDrawable for TintedImage<'_, C> {
+    type Color = C;
+
+    fn draw<D: DrawTarget<Color = C>>(&self, target: &mut D) -> Result<(), D::Error> {
+        self.raw.draw(&mut Tint {
+            target,
+            color: self.color,
+        })
+    }
+
+    fn draw_sub_image<D: DrawTarget<Color = C>>(
+        &self,
+        target: &mut D,
+        area: &Rectangle,
+    ) -> Result<(), D::Error> {
+        self.raw.draw_sub_image(
+            &mut Tint {
+                target,
+                color: self.color,
+            },
+            area,
+        )
+    }
+}
+
+/// Draws the set pixels of a [BinaryColor] image in `color`, skipping the unset ones
+struct Tint<'a, D: DrawTarget> {
+    target: &'a mut D,
+    color: D::Color,
+}
+
+impl<D: DrawTarget> Dimensions for Tint<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Tint<'_, D> {
+    type Color = BinaryColor;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<BinaryColor>>,
+    {
+        let color = self.color;
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .filter(|Pixel(_, c)| c.is_on())
+                .map(|Pixel(p, _)| Pixel(p, color)),
+        )
+    }
+}
+
+/// A widget for displaying an icon.
 ///
-/// This widget renders icons from the Iconoir library using the [embedded_iconoir], applying
-/// colors from the current style system.
+/// This widget renders icons from the Iconoir library using the [embedded_iconoir], or any
+/// other [GuiIcon], applying colors from the current style system.
 ///
 /// You can choose an icon from all resolutions of [embedded_iconoir], such as [embedded_iconoir::size12px] up to [embedded_iconoir::size144px].
 /// For all icons, see [embedded_iconoir::size12px]
-pub struct IconWidget<'a, Ico: IconoirIcon> {
+pub struct IconWidget<'a, Ico: GuiIcon> {
     marker: PhantomData<Ico>,
     smartstate: Container<'a, Smartstate>,
 }
 
-impl<'a, Ico: IconoirIcon> IconWidget<'a, Ico> {
-    /// Creates a new [IconWidget] from an [IconoirIcon] instance.
+impl<'a, Ico: GuiIcon> IconWidget<'a, Ico> {
+    /// Creates a new [IconWidget] from an icon instance, e.g. an [IconoirIcon].
     ///
     /// The icon color from the icon instance will be ignored, as the widget
     /// will use the icon color from the current UI style.
@@ -111,7 +275,7 @@ impl<'a, Ico: IconoirIcon> IconWidget<'a, Ico> {
     }
 }
 
-impl<Ico: IconoirIcon> Widget for IconWidget<'_, Ico> {
+impl<Ico: GuiIcon> Widget for IconWidget<'_, Ico> {
     /// Draws the icon within the UI.
     ///
     /// This method:
@@ -125,15 +289,21 @@ impl<Ico: IconoirIcon> Widget for IconWidget<'_, Ico> {
         ui: &mut Ui<DRAW, COL>,
     ) -> GuiResult<Response> {
         // find size && allocate space
-        let icon = Ico::new(ui.style().icon_color);
+        let icon = Ico::with_color(ui.style().icon_color);
         let iresponse = ui.allocate_space(icon.size())?;
 
         let prevstate = self.smartstate.clone_inner();
         self.smartstate.modify(|sm| *sm = Smartstate::state(1));
+        self.smartstate.modify(|st| st.mix(ui.style_key()));
 
         // draw icon
 
-        if !self.smartstate.eq_option(&prevstate) {
+        if !self.smartstate.eq_option(&prevstate) || ui.invalidated(iresponse.area) {
+            trace!(
+                "Icon smartstate: {} -> {}",
+                prevstate,
+                self.smartstate.clone_inner()
+            );
             ui.start_drawing(&iresponse.area);
 
             if !ui.cleared() {
@@ -155,4 +325,52 @@ impl<Ico: IconoirIcon> Widget for IconWidget<'_, Ico> {
 
         Ok(Response::new(iresponse))
     }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::iconbutton::IconButton;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
+
+    /// A 3x2 arrow, with the rows padded to a byte
+    struct Arrow;
+
+    impl RawIconData for Arrow {
+        const DATA: &'static [u8] = &[0b0100_0000, 0b1110_0000];
+        const WIDTH: u32 = 3;
+    }
+
+    #[test]
+    fn raw_icon_draws_set_bits_in_color() {
+        let icon = RawIcon::<Arrow>::with_color(Rgb565::RED);
+        assert_eq!(RawIcon::<Arrow>::bounding_box().size, Size::new(3, 2));
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        Image::new(&icon, Point::new(1, 1))
+            .draw(&mut display)
+            .unwrap();
+        display.assert_pattern(&[
+            "    ", //
+            "  R ", //
+            " RRR", //
+        ]);
+    }
+
+    #[test]
+    fn raw_icon_works_in_widgets() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+
+        let response = ui.add(IconWidget::new(RawIcon::<Arrow>::new()));
+        assert_eq!(response.internal.area.size, Size::new(3, 2));
+        ui.add(IconButton::<RawIcon<Arrow>>::new_from_type().label("Up"));
+    }
 }
@@ -4,10 +4,12 @@
 //! It provides a compact way to create clickable icons with optional subtitles,
 //! supporting all the interaction states of standard buttons.
 //!
+//! This module is only available with the `icon-widgets` feature (on by default).
+//!
 //! ## Core Features
 //!
 //! - Combines icon display with button interaction (click, hover, press states)
-//! - Optional subtitle/label text below the icon
+//! - Optional subtitle/label text below or to the right of the icon
 //! - Visual feedback via color changes for different interaction states
 //! - Integration with Kolibri's theming system
 //! - Support for the smartstate system for efficient redrawing
@@ -17,20 +19,15 @@
 //! ```no_run
 //! # use embedded_graphics::pixelcolor::Rgb565;
 //! # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-//! # use kolibri_embedded_gui::style::medsize_rgb565_style;
-//! # use kolibri_embedded_gui::ui::Ui;
+//! # use kolibri_embedded_gui::prelude::*;
 //! # use embedded_graphics::prelude::*;
 //! # use embedded_graphics::primitives::Rectangle;
-//! # use embedded_iconoir::prelude::*;
 //! # use embedded_iconoir::size12px;
-//! # use kolibri_embedded_gui::ui::*;
-//! # use kolibri_embedded_gui::label::*;
-//! # use kolibri_embedded_gui::smartstate::*;
 //! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 //! # let output_settings = OutputSettingsBuilder::new().build();
 //! # let mut window = Window::new("Kolibri Example", &output_settings);
 //! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-//! # use kolibri_embedded_gui::iconbutton::IconButton;
+//! # use kolibri_embedded_gui::iconbutton::LabelPosition;
 //! # use embedded_iconoir::size12px::actions::AddCircle;
 //! // Basic icon button
 //! ui.add(IconButton::new(size12px::actions::AddCircle));
@@ -38,6 +35,13 @@
 //! // Icon button with subtitle
 //! ui.add(IconButton::new(size12px::actions::AddCircle).label("Settings"));
 //!
+//! // Icon button with the label next to the icon (e.g. for toolbars)
+//! ui.add(
+//!     IconButton::new(size12px::actions::AddCircle)
+//!         .label("Settings")
+//!         .label_position(LabelPosition::Right),
+//! );
+//!
 //! // Using with the type system instead of passing an icon instance
 //! ui.add(IconButton::<size12px::actions::AddCircle>::new_from_type());
 //!
@@ -57,35 +61,62 @@
 //! - Normal: Standard background and border colors
 //! - Hover: Highlighted background and border for visual feedback
 //! - Pressed/Active: Primary color background with highlighted border
+//! - Disabled (see [IconButton::enabled]): the normal look, with a stippled icon and label
 //!
-use crate::smartstate::{Container, Smartstate};
-use crate::ui::{GuiResult, Interaction, Response, Ui, Widget};
+use crate::button::{Repeat, RepeatState};
+use crate::hidden::Visibility;
+use crate::icon::GuiIcon;
+use crate::smartstate::{Container, Slot, Smartstate};
+use crate::style::WidgetContext;
+use crate::text::{text_layout, Font, RenderedText, TextRenderer};
+use crate::ui::{
+    resolve_widget_style, rounded_rect_clamped, GuiError, GuiResult, HorizontalAlign, Interaction,
+    PointerState, RelativeWidth, Response, TypedWidget, Ui, WIDGET_STYLE_BUCKETS,
+};
 use core::cmp::max;
 use core::marker::PhantomData;
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::{Point, Size};
 use embedded_graphics::image::Image;
-use embedded_graphics::mono_font::MonoTextStyle;
-use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888};
 use embedded_graphics::prelude::*;
-use embedded_graphics::primitives::{PrimitiveStyleBuilder, Rectangle, RoundedRectangle};
-use embedded_graphics::text::{Alignment, Baseline, Text};
-use embedded_iconoir::prelude::{IconoirIcon, IconoirNewIcon};
+use embedded_graphics::primitives::Rectangle;
+
+/// Placement of an [IconButton]'s label relative to its icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LabelPosition {
+    /// The label is centered below the icon. This is the default.
+    #[default]
+    Below,
+    /// The label is placed to the right of the icon, both vertically centered.
+    ///
+    /// Useful for wide toolbars, as the button keeps roughly the default widget height.
+    Right,
+}
 
 /// A button widget that displays an icon with optional text label.
 ///
 /// [IconButton] combines the visual display of an icon with interactive button
 /// behavior. It changes appearance based on user interaction (normal, hover, pressed)
-/// and can optionally display a text label underneath the icon.
-pub struct IconButton<'a, ICON: IconoirIcon> {
+/// and can optionally display a text label underneath or next to the icon
+/// (see [IconButton::label_position]).
+pub struct IconButton<'a, ICON: GuiIcon, L = &'a str> {
     icon: PhantomData<ICON>,
-    label: Option<&'a str>,
+    label: Option<L>,
+    label_position: LabelPosition,
     smartstate: Container<'a, Smartstate>,
     corner_radius: Option<u32>,
+    repeat: Option<Repeat<'a>>,
+    transparent: bool,
+    width: Option<RelativeWidth>,
+    context: WidgetContext,
+    enabled: bool,
 }
 
-impl<'a, ICON: IconoirIcon> IconButton<'a, ICON> {
-    /// Creates a new [IconButton] from an [IconoirIcon] instance.
+impl<'a, ICON: GuiIcon> IconButton<'a, ICON> {
+    /// Creates a new [IconButton] from an icon instance, e.g. an
+    /// [IconoirIcon](embedded_iconoir::prelude::IconoirIcon). Any [GuiIcon] works, see
+    /// [the icon module](crate::icon) for custom icon sets.
     ///
     /// The icon color from the icon instance will be ignored, as the widget
     /// will use the icon color from the current UI style.
@@ -98,20 +129,13 @@ impl<'a, ICON: IconoirIcon> IconButton<'a, ICON> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use embedded_iconoir::size12px;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # use kolibri_embedded_gui::iconbutton::IconButton;
     /// use embedded_iconoir::size24px;
     /// ui.add(IconButton::new(size24px::actions::AddCircle));
     /// ```
@@ -120,34 +144,34 @@ impl<'a, ICON: IconoirIcon> IconButton<'a, ICON> {
             icon: PhantomData,
             smartstate: Container::empty(),
             label: None,
+            label_position: LabelPosition::Below,
             corner_radius: None,
+            repeat: None,
+            transparent: false,
+            width: None,
+            context: WidgetContext::Normal,
+            enabled: true,
         }
     }
 
     /// Adds a text label/subtitle below the icon.
     ///
     /// The label text will be centered below the icon and sized according
-    /// to the current UI style font settings.
+    /// to the current UI style font settings. Use [IconButton::label_position]
+    /// to place it next to the icon instead.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use embedded_iconoir::size12px;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # use kolibri_embedded_gui::iconbutton::IconButton;
     /// use embedded_iconoir::size24px;
     /// ui.add(IconButton::new(size24px::actions::AddCircle).label("Add"));
     /// ```
@@ -155,6 +179,72 @@ impl<'a, ICON: IconoirIcon> IconButton<'a, ICON> {
         self.label = Some(label);
         self
     }
+}
+
+impl<'a, ICON: GuiIcon, L: AsRef<str>> IconButton<'a, ICON, L> {
+    /// Adds a text label like [IconButton::label], which the button owns, e.g. a
+    /// [Buf](crate::format::Buf) formatted within the frame.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::format::Buf;
+    /// # use embedded_iconoir::size24px;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let progress = 80;
+    /// let label = Buf::<8>::from_fmt(format_args!("{}%", progress));
+    /// ui.add(IconButton::new(size24px::actions::Download).label_owned(label));
+    /// ```
+    pub fn label_owned<M: AsRef<str>>(self, label: M) -> IconButton<'a, ICON, M> {
+        IconButton {
+            icon: PhantomData,
+            label: Some(label),
+            label_position: self.label_position,
+            smartstate: self.smartstate,
+            corner_radius: self.corner_radius,
+            repeat: self.repeat,
+            transparent: self.transparent,
+            width: self.width,
+            context: self.context,
+            enabled: self.enabled,
+        }
+    }
+
+    /// Sets where the label is placed relative to the icon.
+    ///
+    /// With [LabelPosition::Below] (the default), the label is centered below the icon.
+    /// With [LabelPosition::Right], the label is drawn next to the icon, which makes the
+    /// button wider instead of taller. Has no effect if no label is set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # use kolibri_embedded_gui::iconbutton::LabelPosition;
+    /// use embedded_iconoir::size24px;
+    /// ui.add(
+    ///     IconButton::new(size24px::actions::AddCircle)
+    ///         .label("Add")
+    ///         .label_position(LabelPosition::Right),
+    /// );
+    /// ```
+    pub fn label_position(mut self, position: LabelPosition) -> Self {
+        self.label_position = position;
+        self
+    }
 
     /// Creates a new [IconButton] using just the icon's type.
     ///
@@ -165,20 +255,13 @@ impl<'a, ICON: IconoirIcon> IconButton<'a, ICON> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use embedded_iconoir::size12px;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # use kolibri_embedded_gui::iconbutton::IconButton;
     /// use embedded_iconoir::size24px;
     /// ui.add(IconButton::<size24px::actions::AddCircle>::new_from_type());
     /// ```
@@ -187,7 +270,13 @@ impl<'a, ICON: IconoirIcon> IconButton<'a, ICON> {
             icon: PhantomData,
             smartstate: Container::empty(),
             label: None,
+            label_position: LabelPosition::Below,
             corner_radius: None,
+            repeat: None,
+            transparent: false,
+            width: None,
+            context: WidgetContext::Normal,
+            enabled: true,
         }
     }
 
@@ -201,20 +290,14 @@ impl<'a, ICON: IconoirIcon> IconButton<'a, ICON> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
     /// # use embedded_iconoir::size12px;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # use kolibri_embedded_gui::iconbutton::IconButton;
     /// let mut my_smartstate = Smartstate::empty();
     /// ui.add(IconButton::new(size12px::actions::AddCircle).smartstate(&mut my_smartstate));
     /// ```
@@ -228,6 +311,8 @@ impl<'a, ICON: IconoirIcon> IconButton<'a, ICON> {
     /// Sets a custom corner radius for the icon button.
     ///
     /// If not specified, the button will use the corner radius from the UI style.
+    /// Radii larger than half of the button's smaller dimension are clamped, so
+    /// `u32::MAX` gives a pill-shaped button.
     ///
     /// # Arguments
     /// * `radius` - The corner radius in pixels
@@ -238,9 +323,86 @@ impl<'a, ICON: IconoirIcon> IconButton<'a, ICON> {
         self.corner_radius = Some(radius);
         self
     }
+
+    /// Sets the role of the icon button in its screen, like [Button::context](crate::button::Button::context).
+    pub fn context(mut self, context: WidgetContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Enables or disables the icon button. It is enabled by default.
+    ///
+    /// A disabled icon button ignores the pointer, so it never reports clicks, and draws its
+    /// icon and label stippled (every other pixel), which greys them out in any color type.
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Draws the icon button without filling its background (and without a shadow), like
+    /// [Button::transparent_background](crate::button::Button::transparent_background).
+    pub fn transparent_background(mut self) -> Self {
+        self.transparent = true;
+        self
+    }
+
+    /// Makes the icon button (at least) `per_mille` of the row wide, like
+    /// [Button::width_fraction](crate::button::Button::width_fraction).
+    pub fn width_fraction(mut self, per_mille: u16) -> Self {
+        self.width = Some(RelativeWidth::PerMille(per_mille));
+        self
+    }
+
+    /// Makes the icon button (at least) as wide as the rest of the row, like
+    /// [Button::fill_width](crate::button::Button::fill_width).
+    pub fn fill_width(mut self) -> Self {
+        self.width = Some(RelativeWidth::Fill);
+        self
+    }
+
+    /// Makes the icon button repeat while it is held, e.g. for jog buttons.
+    ///
+    /// Works like [Button::repeat](crate::button::Button::repeat): `clicked()` fires on press,
+    /// after `initial_delay_ms`, and then every `interval_ms` until the button is released
+    /// or the pointer is dragged off of it. Requires a frame time (see [Ui::begin_frame]) for
+    /// the repetition, otherwise the button fires once per press.
+    ///
+    /// # Arguments
+    /// * `state` - The [RepeatState] tracking the hold across frames
+    /// * `initial_delay_ms` - The delay before the first repetition
+    /// * `interval_ms` - The interval between subsequent repetitions
+    ///
+    /// # Returns
+    /// Self with repeat-on-hold enabled
+    pub fn repeat(
+        mut self,
+        state: &'a mut RepeatState,
+        initial_delay_ms: u32,
+        interval_ms: u32,
+    ) -> Self {
+        self.repeat = Some(Repeat::new(state, initial_delay_ms, interval_ms));
+        self
+    }
+
+    /// Makes the icon button repeat while it is held, keeping the hold in a [Slot] (see
+    /// [Button::repeat_in](crate::button::Button::repeat_in)).
+    pub fn repeat_in(
+        mut self,
+        slot: &'a mut Slot,
+        initial_delay_ms: u32,
+        interval_ms: u32,
+    ) -> Self {
+        self.repeat = Some(Repeat::in_slot(slot, initial_delay_ms, interval_ms));
+        self
+    }
 }
 
-impl<ICON: IconoirIcon> Widget for IconButton<'_, ICON> {
+impl<ICON: GuiIcon, L: AsRef<str>> Visibility for IconButton<'_, ICON, L> {}
+
+impl<ICON: GuiIcon, L: AsRef<str>, COL> TypedWidget<COL> for IconButton<'_, ICON, L>
+where
+    COL: PixelColor + Into<Rgb888> + From<Rgb888>,
+{
     /// Draws the icon button within the UI.
     ///
     /// This method:
@@ -251,43 +413,63 @@ impl<ICON: IconoirIcon> Widget for IconButton<'_, ICON> {
     /// 5. Manages visual appearance based on interaction state
     /// 6. Updates the smartstate and draws when necessary
     /// 7. Returns a response that includes click information
-    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
         &mut self,
         ui: &mut Ui<DRAW, COL>,
     ) -> GuiResult<Response> {
         // get size
-        let icon = ICON::new(ui.style().icon_color);
+        let icon = ICON::with_color(ui.style().icon_color);
 
-        let padding = ui.style().spacing.button_padding;
+        let padding = ui.style().widget_padding(ui.style().spacing.button_padding);
         let border = ui.style().border_width;
 
-        let mut min_height = icon.bounding_box().size.height + 2 * padding.height + 2 * border;
+        let icon_size = icon.bounding_box().size;
+        let font = Font::resolve(None, ui.style());
+        let label = self.label.as_ref().map(|label| label.as_ref());
+        let text_size = label.map(|label| font.measure(label));
 
-        let mut width = min_height;
-
-        let font = ui.style().default_font;
+        // content size (icon + label, without padding and border)
+        let content = match (text_size, self.label_position) {
+            (None, _) => icon_size,
+            (Some(text_size), LabelPosition::Below) => Size::new(
+                max(icon_size.width, text_size.width),
+                icon_size.height + padding.height + text_size.height,
+            ),
+            (Some(text_size), LabelPosition::Right) => Size::new(
+                icon_size.width + padding.width + text_size.width,
+                max(icon_size.height, text_size.height),
+            ),
+        };
 
-        let mut text = if let Some(label) = self.label {
-            let mut text = Text::new(
-                label,
-                Point::new(0, 0),
-                MonoTextStyle::new(&font, ui.style().text_color),
-            );
-            text.text_style.alignment = Alignment::Center;
-            text.text_style.baseline = Baseline::Top;
-            min_height += padding.height + text.bounding_box().size.height;
-            width = width.max(text.bounding_box().size.width + 2 * padding.width + 2 * border);
-            Some(text)
-        } else {
-            None
+        let min_height = content.height + 2 * padding.height + 2 * border;
+        // without a label to the right, the button is (at least) square, and wide enough for
+        // icons wider than tall
+        let width = match (text_size, self.label_position) {
+            (Some(_), LabelPosition::Right) => content.width + 2 * padding.width + 2 * border,
+            _ => max(
+                max(icon_size.width, icon_size.height) + 2 * padding.height + 2 * border,
+                text_size
+                    .map(|t| t.width + 2 * padding.width + 2 * border)
+                    .unwrap_or(0),
+            ),
+        };
+        // match the row height, including the shadow of the widgets before
+        let shadow = ui.style().shadow_size();
+        let width = match self.width {
+            Some(relative) => max(
+                width,
+                ui.resolve_width(relative).saturating_sub(shadow.width),
+            ),
+            None => width,
         };
         let height = max(
-            max(ui.style().default_widget_height, ui.get_row_height()),
+            max(
+                ui.style().default_widget_height,
+                ui.get_row_height().saturating_sub(shadow.height),
+            ),
             min_height,
         );
 
-        let size = Size::new(width, height);
-
         /*
         let icon = match size.width - 2 * padding.width {
             0..=17 => 12,
@@ -298,40 +480,75 @@ impl<ICON: IconoirIcon> Widget for IconButton<'_, ICON> {
          */
 
         // allocate space
-        let iresponse = ui.allocate_space(Size::new(size.width, max(size.height, height)))?;
-
-        // translate icon
-        let size = icon.bounding_box();
-
-        // center icon
-        let center_offset = iresponse.area.top_left
-            + Point::new(
-                ((iresponse.area.size.width - size.size.width) / 2) as i32,
-                ((iresponse.area.size.height
-                    - size.size.height
-                    - text
-                        .map(|t| t.bounding_box().size.height + padding.height)
-                        .unwrap_or(0))
-                    / 2) as i32,
-            );
+        let mut iresponse = ui.allocate_space(Size::new(width, height) + shadow)?;
+        if !self.enabled {
+            iresponse.interaction = Interaction::None;
+        } else if iresponse.interaction == Interaction::None {
+            // a press that started on the button survives jitter around its edge
+            iresponse.interaction = ui.check_held_press(iresponse.area);
+        }
+        let body = ui.style().without_shadow(iresponse.area);
+        // area of the content; shifted while pressed, while the background stays in place
+        let pressed = iresponse.interaction.is_pressed();
+        let area = body.translate(ui.style().pressed_offset_for(pressed));
 
-        let icon_img = Image::new(&icon, center_offset);
-
-        // center text (if it exists)
-        if let Some(text) = text.as_mut() {
-            let center_offset = iresponse.area.top_left
-                + Point::new(
-                    (iresponse.area.size.width / 2) as i32,
-                    (iresponse.area.size.height
-                        - text.bounding_box().size.height
-                        - padding.height
-                        - border) as i32,
+        // position icon and text (if it exists). The area fits the content, unless it is
+        // clipped (with Overflow::Clip), so the content starts at the top left then.
+        let centered = |outer: u32, inner: u32| (outer.saturating_sub(inner) / 2) as i32;
+        let mut text_pos = Point::zero();
+        let icon_pos = match (text_size, self.label_position) {
+            (Some(text_size), LabelPosition::Below) => {
+                // center icon in the space above the label
+                let below = text_size.height + padding.height;
+                let icon_pos = area.top_left
+                    + Point::new(
+                        centered(area.size.width, icon_size.width),
+                        centered(area.size.height.saturating_sub(below), icon_size.height),
+                    );
+                let line = Rectangle::new(
+                    area.top_left
+                        + Point::new(0, area.size.height.saturating_sub(below + border) as i32),
+                    Size::new(area.size.width, text_size.height),
                 );
-            text.translate_mut(center_offset);
-        }
+                text_pos = text_layout(text_size, line, HorizontalAlign::Center);
+                icon_pos
+            }
+            (Some(text_size), LabelPosition::Right) => {
+                // center the icon + label group horizontally, center both vertically
+                let left = area.top_left.x + centered(area.size.width, content.width);
+                let beside = Rectangle::new(
+                    Point::new(
+                        left + (icon_size.width + padding.width) as i32,
+                        area.top_left.y,
+                    ),
+                    Size::new(text_size.width, area.size.height),
+                );
+                text_pos = text_layout(text_size, beside, HorizontalAlign::Left);
+                Point::new(
+                    left,
+                    area.top_left.y + centered(area.size.height, icon_size.height),
+                )
+            }
+            (None, _) => {
+                area.top_left
+                    + Point::new(
+                        centered(area.size.width, icon_size.width),
+                        centered(area.size.height, icon_size.height),
+                    )
+            }
+        };
+
+        let icon_img = Image::new(&icon, icon_pos);
 
         // check for click
-        let click = matches!(iresponse.interaction, Interaction::Release(_));
+        let click = match self.repeat.as_mut() {
+            Some(repeat) => repeat.update(iresponse.interaction, ui.now()),
+            None => iresponse.interaction.is_release(),
+        };
+        if self.repeat.as_ref().is_some_and(|repeat| repeat.waiting()) {
+            // the next repeat is due without a new interaction
+            ui.request_repaint();
+        }
         let down = matches!(
             iresponse.interaction,
             Interaction::Click(_) | Interaction::Drag(_)
@@ -340,65 +557,376 @@ impl<ICON: IconoirIcon> Widget for IconButton<'_, ICON> {
         // styles and smartstate
         let prevstate = self.smartstate.clone_inner();
 
-        let rect_style = match iresponse.interaction {
-            Interaction::None => {
-                self.smartstate.modify(|st| *st = Smartstate::state(1));
-
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().border_color)
-                    .stroke_width(ui.style().border_width)
-                    .fill_color(ui.style().item_background_color)
-                    .build()
-            }
-            Interaction::Hover(_) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(2));
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-                    .fill_color(ui.style().highlight_item_background_color)
-                    .build()
-            }
+        // the layout and the label are part of the state, so switching label positions or
+        // changing the label repaints
+        let layout = match self.label_position {
+            LabelPosition::Below => 0,
+            LabelPosition::Right => WIDGET_STYLE_BUCKETS,
+        };
 
-            _ => {
-                self.smartstate.modify(|st| *st = Smartstate::state(3));
+        let (mut rect_style, bucket) = resolve_widget_style(ui.style(), iresponse.interaction);
+        self.context.apply(&mut rect_style, ui.style());
+        let look = |bucket: u8| (bucket as u32 + layout, label, self.context, self.enabled);
+        let style_key = ui.style_key();
+        let previous_pointer = PointerState::previous(prevstate, look, style_key);
+        let pointer = PointerState::of(iresponse.interaction);
+        self.smartstate
+            .modify(|st| *st = pointer.smartstate(&look(bucket), style_key));
 
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-                    .fill_color(ui.style().primary_color)
-                    .build()
+        let mut redraw = !PointerState::same_look(prevstate, &look(bucket), style_key)
+            || ui.invalidated(iresponse.area);
+        if redraw && self.transparent {
+            rect_style.fill_color = None;
+            if let Some(prevstate) = prevstate {
+                if !ui.redraw_transparent(iresponse.area) {
+                    // keep the previous state until the background is restored
+                    self.smartstate.modify(|st| *st = prevstate);
+                    redraw = false;
+                }
             }
-        };
+        }
 
-        if !self.smartstate.eq_option(&prevstate) {
+        if redraw {
+            trace!(
+                "IconButton smartstate: {} -> {}",
+                prevstate,
+                self.smartstate.clone_inner()
+            );
             ui.start_drawing(&iresponse.area);
 
-            let corner_radius = self.corner_radius.unwrap_or(ui.style().corner_radius);
-            let rounded_rect = RoundedRectangle::with_equal_corners(
-                Rectangle::new(iresponse.area.top_left, iresponse.area.size),
-                Size::new(corner_radius, corner_radius),
-            );
+            let corner_radius = ui.style().corner_radius_for(self.corner_radius, body.size);
+            let rounded_rect = rounded_rect_clamped(body, corner_radius);
 
-            ui.draw(&rounded_rect.into_styled(rect_style)).ok();
-            ui.draw(&icon_img).ok();
-            if let Some(text) = text.as_mut() {
-                ui.draw(text).unwrap();
+            if self.transparent {
+                ui.draw(&rounded_rect.into_styled(rect_style))
+            } else if bucket == 0 && !self.context.is_primary() {
+                // only the normal look has the item background, it may be a gradient
+                let fill = ui.style().item_background_color;
+                ui.draw_background_fill(rounded_rect, fill, rect_style)
+            } else {
+                ui.draw_background(rounded_rect, rect_style)
+            }
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw IconButton")))?;
+            ui.draw(&Stippled::new(&icon_img, !self.enabled))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw IconButton icon")))?;
+            if let Some(label) = label {
+                let text = RenderedText::new(&font, label, text_pos, ui.style().text_color);
+                ui.draw(&Stippled::new(&text, !self.enabled))
+                    .map_err(|_| GuiError::DrawError(Some("Couldn't draw IconButton label")))?;
             }
 
             ui.finalize()?;
         }
 
-        Ok(Response::new(iresponse).set_clicked(click).set_down(down))
+        Ok(Response::new(iresponse)
+            .set_clicked(click)
+            .set_down(down)
+            .set_previous_pointer(previous_pointer))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+/// Draws `item`, only every other pixel (in a checkerboard pattern) if `stipple` is set
+struct Stippled<'a, T> {
+    item: &'a T,
+    stipple: bool,
+}
+
+impl<'a, T> Stippled<'a, T> {
+    fn new(item: &'a T, stipple: bool) -> Self {
+        Self { item, stipple }
+    }
+}
+
+impl<T: Drawable> Drawable for Stippled<'_, T> {
+    type Color = T::Color;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = T::Color>>(&self, target: &mut D) -> Result<(), D::Error> {
+        match self.stipple {
+            true => self.item.draw(&mut Stipple { target }),
+            false => self.item.draw(target),
+        }
+        .map(|_| ())
+    }
+}
+
+/// Drops every other pixel drawn to `target`
+struct Stipple<'a, D> {
+    target: &'a mut D,
+}
+
+impl<D: DrawTarget> Dimensions for Stipple<'_, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D: DrawTarget> DrawTarget for Stipple<'_, D> {
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<D::Color>>,
+    {
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .filter(|Pixel(p, _)| (p.x + p.y).rem_euclid(2) == 0),
+        )
     }
 }
 
 // Implement common traits for IconButton
-impl<ICON: IconoirIcon> core::fmt::Debug for IconButton<'_, ICON> {
+impl<ICON: GuiIcon, L: AsRef<str>> core::fmt::Debug for IconButton<'_, ICON, L> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("IconButton")
             .field("type", &core::any::type_name::<ICON>())
-            .field("label", &self.label)
+            .field("label", &self.label.as_ref().map(|label| label.as_ref()))
+            .field("label_position", &self.label_position)
+            .field("enabled", &self.enabled)
             .field("smartstate", &"<smartstate>")
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icon::{RawIcon, RawIconData};
+    use crate::style::medsize_rgb565_style;
+    use crate::ui::{GuiError, Overflow};
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// A filled 32x32 icon
+    struct Square;
+
+    impl RawIconData for Square {
+        const DATA: &'static [u8] = &[0xFF; 32 * 4];
+        const WIDTH: u32 = 32;
+    }
+
+    /// A filled icon of 40x8 pixels, wider than tall
+    struct Bar;
+
+    impl RawIconData for Bar {
+        const DATA: &'static [u8] = &[0xFF; 5 * 8];
+        const WIDTH: u32 = 40;
+    }
+
+    /// A filled 8x8 icon, small enough for a label next to or below it
+    struct Dot;
+
+    impl RawIconData for Dot {
+        const DATA: &'static [u8] = &[0xFF; 8];
+        const WIDTH: u32 = 8;
+    }
+
+    /// Adds `button` to a Ui of 64x64 pixels with `overflow`
+    fn add<L: AsRef<str>>(
+        button: IconButton<'_, impl GuiIcon, L>,
+        overflow: Overflow,
+    ) -> GuiResult<Response> {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        display.set_allow_out_of_bounds_drawing(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_overflow_policy(overflow);
+        ui.try_add(button)
+    }
+
+    #[test]
+    fn content_wider_than_the_button_does_not_underflow() {
+        let label = "long label ".repeat(20);
+        for position in [LabelPosition::Below, LabelPosition::Right] {
+            // a label much wider than the Ui doesn't fit
+            let button = IconButton::<RawIcon<Square>>::new_from_type()
+                .label(&label)
+                .label_position(position);
+            let result = add(button, Overflow::Wrap);
+            assert!(matches!(result, Err(GuiError::NoHorizontalSpace { .. })));
+            // or is clipped
+            let button = IconButton::<RawIcon<Square>>::new_from_type()
+                .label(&label)
+                .label_position(position);
+            assert!(add(button, Overflow::Clip).is_ok());
+        }
+
+        // an icon wider than tall gets a wide enough button
+        let area = add(IconButton::<RawIcon<Bar>>::new_from_type(), Overflow::Wrap)
+            .unwrap()
+            .area();
+        assert!(area.size.width >= 40);
+        let area = add(
+            IconButton::<RawIcon<Bar>>::new_from_type().label("a"),
+            Overflow::Wrap,
+        )
+        .unwrap()
+        .area();
+        assert!(area.size.width >= 40);
+
+        // filling the row
+        let area = add(
+            IconButton::<RawIcon<Square>>::new_from_type().fill_width(),
+            Overflow::Wrap,
+        )
+        .unwrap()
+        .area();
+        assert_eq!(area.size.width, 58);
+    }
+
+    /// Draws a button with the label "ab" at `position`, returning the button's body and the
+    /// bounding boxes of the icon and the label pixels
+    fn label_layout(position: LabelPosition) -> (Rectangle, Rectangle, Rectangle) {
+        let mut style = medsize_rgb565_style();
+        style.icon_color = Rgb565::RED;
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        let button = IconButton::<RawIcon<Dot>>::new_from_type()
+            .label("ab")
+            .label_position(position);
+        let body = style.without_shadow(ui.add(button).area());
+        drop(ui);
+
+        let pixels = |color| {
+            let points = display.bounding_box().points();
+            let mut drawn = points.filter(|&p| display.get_pixel(p) == Some(color));
+            let first = drawn.next().unwrap();
+            let (min, max) = drawn.fold((first, first), |(min, max), p| {
+                (min.component_min(p), max.component_max(p))
+            });
+            Rectangle::with_corners(min, max)
+        };
+        (body, pixels(style.icon_color), pixels(style.text_color))
+    }
+
+    #[test]
+    fn the_label_is_centered_below_the_icon() {
+        let (body, icon, label) = label_layout(LabelPosition::Below);
+        assert_eq!(icon.size, Size::new(8, 8));
+        assert!(body.contains(icon.top_left) && body.contains(label.bottom_right().unwrap()));
+        assert!(label.top_left.y > icon.bottom_right().unwrap().y);
+        // icon and label share the center column of the button
+        for content in [icon, label] {
+            assert!(
+                (content.center().x - body.center().x).abs() <= 1,
+                "{content:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn the_label_is_centered_right_of_the_icon() {
+        let (body, icon, label) = label_layout(LabelPosition::Right);
+        assert_eq!(icon.size, Size::new(8, 8));
+        assert!(body.contains(icon.top_left) && body.contains(label.bottom_right().unwrap()));
+        assert!(label.top_left.x > icon.bottom_right().unwrap().x);
+        // icon and label are centered vertically, the group horizontally
+        for content in [icon, label] {
+            assert!(
+                (content.center().y - body.center().y).abs() <= 2,
+                "{content:?}"
+            );
+        }
+        let group = Rectangle::with_corners(icon.top_left, label.bottom_right().unwrap());
+        assert!((group.center().x - body.center().x).abs() <= 1, "{group:?}");
+    }
+
+    #[test]
+    fn changing_the_label_position_redraws() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        display.set_allow_out_of_bounds_drawing(true);
+        let mut smartstate = Smartstate::empty();
+
+        for (position, redraw) in [
+            (LabelPosition::Below, true),
+            (LabelPosition::Below, false),
+            (LabelPosition::Right, true),
+            (LabelPosition::Right, false),
+        ] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            // the same area for both positions, so only the look changes
+            ui.set_row_height(40);
+            let button = IconButton::<RawIcon<Dot>>::new_from_type()
+                .label("ab")
+                .label_position(position)
+                .fill_width()
+                .smartstate(&mut smartstate);
+            let response = ui.add(button);
+            assert_eq!(response.error(), None);
+            assert_eq!(response.area().size, Size::new(58, 40));
+            assert_eq!(ui.perf().widgets_redrawn, redraw as u32, "{position:?}");
+        }
+    }
+
+    #[test]
+    fn padding_changes_the_size() {
+        let size = |padding| {
+            let mut display = MockDisplay::<Rgb565>::new();
+            let mut style = medsize_rgb565_style();
+            style.spacing.button_padding = padding;
+            let mut ui = Ui::new_fullscreen(&mut display, style);
+            ui.measure_widget(&mut IconButton::<RawIcon<Square>>::new_from_type())
+        };
+        let (small, large) = (size(Size::new(2, 2)), size(Size::new(12, 24)));
+        // the button is square, so both sides grow with the larger padding
+        assert_eq!(large, small + Size::new_equal(2 * (24 - 2)));
+    }
+
+    #[test]
+    fn a_thicker_highlight_border_does_not_cover_the_icon() {
+        let mut style = medsize_rgb565_style();
+        style.border_width = 1;
+        style.highlight_border_width = 3;
+        style.highlight_border_color = Rgb565::RED;
+        style.spacing.button_padding = Size::new(1, 1);
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        ui.interact(Interaction::Hover(Point::new(10, 10)));
+        let area = ui
+            .add(IconButton::<RawIcon<Square>>::new_from_type())
+            .area();
+        let inside = style.without_shadow(area).offset(-3);
+        drop(ui);
+
+        // all of the icon is drawn, inside the hovered border
+        let icon = display
+            .bounding_box()
+            .points()
+            .filter(|&p| display.get_pixel(p) == Some(style.icon_color))
+            .inspect(|&p| assert!(inside.contains(p), "icon pixel {p} under the border"))
+            .count();
+        assert_eq!(icon, 32 * 32);
+    }
+
+    #[test]
+    fn a_disabled_button_ignores_the_pointer() {
+        let style = medsize_rgb565_style();
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        ui.interact(Interaction::Release(Point::new(10, 10)));
+        let response = ui.add(IconButton::<RawIcon<Square>>::new_from_type().enabled(false));
+        assert!(!response.clicked());
+        assert!(!response.down());
+        drop(ui);
+
+        // half of the icon is drawn, in a checkerboard pattern
+        let icon = display
+            .bounding_box()
+            .points()
+            .filter(|&p| display.get_pixel(p) == Some(style.icon_color))
+            .inspect(|&p| assert_eq!((p.x + p.y) % 2, 0))
+            .count();
+        assert_eq!(icon, 32 * 32 / 2);
+    }
+}
@@ -0,0 +1,214 @@
+//! # Image Widget
+//!
+//! The [Img] widget draws any [ImageDrawable] (e.g. an [ImageRaw](embedded_graphics::image::ImageRaw)
+//! or a BMP) as a widget of the image's size. It doesn't clear its area, so images with
+//! transparent pixels (like icons) are drawn over the background.
+//!
+//! A [Sprite] is an image from a buffer of colors, like the ones [Ui::render_offscreen] draws
+//! widgets into. Rendering a complex widget (e.g. a gauge) once and blitting the sprite wherever
+//! it appears is a lot faster than drawing it from primitives each time:
+//!
+//! ```no_run
+//! # #[cfg(feature = "instruments")] {
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! // with the `instruments` feature
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! let mut buf = [Rgb565::BLACK; 48 * 48];
+//! let sprite = ui
+//!     .render_offscreen(Compass::new(90.0).diameter(48), &mut buf, Size::new_equal(48))
+//!     .unwrap();
+//! for _ in 0..3 {
+//!     ui.add_horizontal(Img::new(&sprite));
+//! }
+//! # }
+//! ```
+
+use crate::smartstate::{Container, Smartstate};
+use crate::ui::{GuiError, GuiResult, Response, TypedWidget, Ui};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::image::{Image, ImageDrawable};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+
+/// An image from a buffer of `COL` colors, row by row from the top left, like an
+/// [ImageRaw](embedded_graphics::image::ImageRaw) of the display's color type.
+///
+/// Returned by [Ui::render_offscreen], and drawn with the [Img] widget or as an [Image].
+#[derive(Debug, Clone, Copy)]
+pub struct Sprite<'a, COL: PixelColor> {
+    pixels: &'a [COL],
+    size: Size,
+}
+
+impl<'a, COL: PixelColor> Sprite<'a, COL> {
+    /// Creates a sprite of `size` from `pixels`.
+    ///
+    /// # Panics
+    ///
+    /// If there are fewer pixels than `size` has.
+    pub fn new(pixels: &'a [COL], size: Size) -> Self {
+        let len = size.width as usize * size.height as usize;
+        assert!(len <= pixels.len(), "pixels too few for sprite");
+        Self {
+            pixels: &pixels[..len],
+            size,
+        }
+    }
+
+    /// Returns the pixels of the sprite, row by row.
+    pub fn pixels(&self) -> &'a [COL] {
+        self.pixels
+    }
+}
+
+impl<COL: PixelColor> OriginDimensions for Sprite<'_, COL> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<COL: PixelColor> ImageDrawable for Sprite<'_, COL> {
+    type Color = COL;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        target.fill_contiguous(&self.bounding_box(), self.pixels.iter().copied())
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        let area = area.intersection(&self.bounding_box());
+        let width = self.size.width as usize;
+        let (x, len) = (area.top_left.x as usize, area.size.width as usize);
+        for (row, y) in (area.top_left.y..)
+            .take(area.size.height as usize)
+            .enumerate()
+        {
+            let start = y as usize * width + x;
+            target.fill_contiguous(
+                &Rectangle::new(Point::new(0, row as i32), Size::new(area.size.width, 1)),
+                self.pixels[start..start + len].iter().copied(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A widget drawing an image, see the [module documentation](self).
+pub struct Img<'a, I> {
+    image: &'a I,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<'a, I> Img<'a, I> {
+    /// Creates a widget drawing `image`.
+    pub fn new(image: &'a I) -> Self {
+        Self {
+            image,
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Adds a smartstate, so that the image is only drawn once (and when invalidated).
+    ///
+    /// The image is assumed not to change; draw a changed image without the smartstate (or
+    /// [invalidate](Ui::invalidate) its area) to show it.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+impl<COL, I> TypedWidget<COL> for Img<'_, I>
+where
+    COL: PixelColor,
+    I: ImageDrawable<Color = COL>,
+{
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let iresponse = ui.allocate_space(self.image.size())?;
+        let area = iresponse.area;
+
+        // check smartstate
+        let prevstate = self.smartstate.clone_inner();
+        self.smartstate.modify(|sm| *sm = Smartstate::state(1));
+        self.smartstate.modify(|st| st.mix(ui.style_key()));
+        if self.smartstate.eq_option(&prevstate) && !ui.invalidated(area) {
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+
+        ui.start_drawing(&area);
+        ui.draw(&Image::new(self.image, area.top_left))
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw Img")))?;
+        ui.finalize()?;
+
+        Ok(Response::new(iresponse).set_redraw(true))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+// the tests render a compass into a sprite
+#[cfg(all(test, feature = "instruments"))]
+mod tests {
+    use super::*;
+    use crate::compass::Compass;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::mono_font::ascii;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    #[test]
+    fn sprites_draw_like_the_widget_they_were_rendered_from() {
+        // a small font, for a small compass
+        let mut style = medsize_rgb565_style();
+        style.default_font = ascii::FONT_4X6;
+        let size = Size::new_equal(24);
+
+        // the compass, drawn directly at the top left
+        let mut direct = MockDisplay::<Rgb565>::new();
+        direct.set_allow_overdraw(true);
+        let mut flat = style;
+        flat.spacing.window_border_padding = Size::zero();
+        let mut ui = Ui::new(&mut direct, Rectangle::new(Point::zero(), size), flat);
+        ui.clear_background().unwrap();
+        ui.add(Compass::new(30.0).diameter(24));
+        drop(ui);
+
+        // rendered offscreen, then blitted twice
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        let mut buf = [Rgb565::BLACK; 24 * 24];
+        let sprite = ui
+            .render_offscreen(Compass::new(30.0).diameter(24), &mut buf, size)
+            .unwrap();
+        assert_eq!(sprite.size(), size);
+        let first = ui.add_horizontal(Img::new(&sprite)).area();
+        let second = ui.add_horizontal(Img::new(&sprite)).area();
+        drop(ui);
+
+        for point in Rectangle::new(Point::zero(), size).points() {
+            let pixel = direct.get_pixel(point);
+            assert_eq!(display.get_pixel(first.top_left + point), pixel);
+            assert_eq!(display.get_pixel(second.top_left + point), pixel);
+        }
+
+        // a buffer that's too small is an error
+        let ui = Ui::new_fullscreen(&mut display, style);
+        let result = ui.render_offscreen(Compass::new(30.0), &mut buf[..10], size);
+        assert!(matches!(result, Err(GuiError::BoundsTooSmall { .. })));
+    }
+}
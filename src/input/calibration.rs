@@ -0,0 +1,356 @@
+//! # Touch calibration screen
+//!
+//! [CalibrationScreen] is a ready-made calibration flow for [TouchAdapter](super::touch::TouchAdapter):
+//! it shows a crosshair at three or five known points, one after the other, collects the raw
+//! touch readings for each, and computes the [Calibration] from them.
+//!
+//! The screen is a small state machine that the app drives once per frame, passing the raw
+//! (uncalibrated) touch sample. It only redraws when the target changes. If the readings turn
+//! out to be inconsistent, it starts over and tells the user to try again.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use embedded_graphics::prelude::*;
+//! # use kolibri_embedded_gui::input::calibration::{CalibrationPoints, CalibrationScreen};
+//! # use kolibri_embedded_gui::input::touch::TouchAdapter;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # fn read_touch_controller() -> Option<Point> { None }
+//! let mut touch = TouchAdapter::new();
+//! let mut calibration_screen = CalibrationScreen::new(CalibrationPoints::Five);
+//!
+//! loop {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     if let Some(calibration) = calibration_screen.step(&mut ui, read_touch_controller()) {
+//!         touch.set_calibration(calibration);
+//!         break;
+//!     }
+//! }
+//! ```
+
+use crate::input::touch::Calibration;
+use crate::ui::{GuiResult, Ui};
+use core::fmt::Write;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Circle, Line, PrimitiveStyle, Rectangle};
+use embedded_graphics::text::{Alignment, Baseline, Text, TextStyleBuilder};
+
+/// Which points a [CalibrationScreen] asks the user to touch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CalibrationPoints {
+    /// Three points: enough for the affine transform, but inconsistent readings can only be
+    /// detected if they're degenerate (e.g. all on a line)
+    Three,
+    /// Five points (the corners and the center): noisy readings are averaged out, and
+    /// inconsistent ones are detected. This is the default.
+    #[default]
+    Five,
+}
+
+impl CalibrationPoints {
+    /// Returns the target points within `area`, inset from the edges so they're easy to touch.
+    pub fn targets(&self, area: Rectangle) -> heapless::Vec<Point, 5> {
+        let size = area.size;
+        let at = |x_percent: u32, y_percent: u32| {
+            area.top_left
+                + Point::new(
+                    (size.width * x_percent / 100) as i32,
+                    (size.height * y_percent / 100) as i32,
+                )
+        };
+        let targets: &[Point] = match self {
+            CalibrationPoints::Three => &[at(10, 10), at(90, 50), at(50, 90)],
+            CalibrationPoints::Five => {
+                &[at(10, 10), at(90, 10), at(90, 90), at(10, 90), at(50, 50)]
+            }
+        };
+        heapless::Vec::from_slice(targets).unwrap()
+    }
+}
+
+/// Interactive touch calibration, see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalibrationScreen {
+    points: CalibrationPoints,
+    min_samples: u32,
+    tolerance: u32,
+    /// Averaged raw readings of the targets done so far
+    raw: heapless::Vec<Point, 5>,
+    /// Sum and count of the raw samples of the current touch
+    sum: (i64, i64),
+    count: u32,
+    retries: u32,
+    /// Target index and retry count of what's on screen
+    drawn: Option<(usize, u32)>,
+    result: Option<Calibration>,
+}
+
+impl CalibrationScreen {
+    /// Creates a calibration screen for the given points.
+    ///
+    /// By default, a touch needs at least 3 samples to count, and five-point readings may be
+    /// off by 6 pixels.
+    pub fn new(points: CalibrationPoints) -> Self {
+        Self {
+            points,
+            min_samples: 3,
+            tolerance: 6,
+            raw: heapless::Vec::new(),
+            sum: (0, 0),
+            count: 0,
+            retries: 0,
+            drawn: None,
+            result: None,
+        }
+    }
+
+    /// Sets how many samples a touch needs to count. Shorter touches are ignored as noise.
+    pub fn min_samples(mut self, samples: u32) -> Self {
+        self.min_samples = samples.max(1);
+        self
+    }
+
+    /// Sets by how many pixels (on either axis) a target may be missed after calibration before
+    /// the readings count as inconsistent. Only applies to [CalibrationPoints::Five].
+    pub fn tolerance(mut self, pixels: u32) -> Self {
+        self.tolerance = pixels;
+        self
+    }
+
+    /// Returns how often the calibration was restarted because of inconsistent readings.
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Starts over, e.g. to recalibrate later on. The screen is redrawn on the next step.
+    pub fn restart(&mut self) {
+        self.raw.clear();
+        self.sum = (0, 0);
+        self.count = 0;
+        self.drawn = None;
+        self.result = None;
+    }
+
+    /// Advances the calibration by one frame.
+    ///
+    /// Draws the current target (if it changed), and collects `raw_sample`. A touch counts once
+    /// it's released, as the average of its samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `ui` - The [Ui] to draw the calibration screen on. Targets are placed within its bounds.
+    /// * `raw_sample` - The raw (uncalibrated) reading of the touch controller, or `None` if not
+    ///   touched
+    ///
+    /// # Returns
+    ///
+    /// The [Calibration] once all targets were touched, `None` before. The screen isn't cleared
+    /// afterwards, so clear the background before drawing the next screen.
+    pub fn step<DRAW, COL>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+        raw_sample: Option<Point>,
+    ) -> Option<Calibration>
+    where
+        DRAW: DrawTarget<Color = COL>,
+        COL: PixelColor,
+    {
+        if self.result.is_some() {
+            return self.result;
+        }
+        let targets = self.points.targets(ui.bounds());
+
+        if let Some(raw) = raw_sample {
+            self.sum.0 += raw.x as i64;
+            self.sum.1 += raw.y as i64;
+            self.count += 1;
+        } else if self.count > 0 {
+            if self.count >= self.min_samples {
+                let count = self.count as i64;
+                let average = Point::new((self.sum.0 / count) as i32, (self.sum.1 / count) as i32);
+                // can't overflow, as there are as many readings as targets
+                self.raw.push(average).ok();
+            }
+            self.sum = (0, 0);
+            self.count = 0;
+
+            if self.raw.len() == targets.len() {
+                match self.calibrate(&targets) {
+                    Some(calibration) => {
+                        self.result = Some(calibration);
+                        return self.result;
+                    }
+                    None => {
+                        self.retries += 1;
+                        self.restart();
+                    }
+                }
+            }
+        }
+
+        let index = self.raw.len();
+        if self.drawn != Some((index, self.retries)) {
+            // a failed draw is retried in the next frame
+            if self.draw(ui, &targets, index).is_ok() {
+                self.drawn = Some((index, self.retries));
+            }
+        }
+        None
+    }
+
+    /// Computes the calibration, or `None` if the readings are inconsistent.
+    fn calibrate(&self, targets: &[Point]) -> Option<Calibration> {
+        let calibration = Calibration::from_points(&self.raw, targets)?;
+        let tolerance = self.tolerance as i32;
+        let consistent = self.raw.iter().zip(targets).all(|(raw, target)| {
+            let error = calibration.apply(*raw) - *target;
+            error.x.abs() <= tolerance && error.y.abs() <= tolerance
+        });
+        consistent.then_some(calibration)
+    }
+
+    fn draw<DRAW, COL>(
+        &self,
+        ui: &mut Ui<DRAW, COL>,
+        targets: &[Point],
+        index: usize,
+    ) -> GuiResult<()>
+    where
+        DRAW: DrawTarget<Color = COL>,
+        COL: PixelColor,
+    {
+        ui.clear_background()?;
+        let style = *ui.style();
+
+        // crosshair
+        let target = targets[index];
+        let line = PrimitiveStyle::with_stroke(style.text_color, 1);
+        let arm = 10;
+        ui.draw(
+            &Line::new(target - Point::new(arm, 0), target + Point::new(arm, 0)).into_styled(line),
+        )?;
+        ui.draw(
+            &Line::new(target - Point::new(0, arm), target + Point::new(0, arm)).into_styled(line),
+        )?;
+        ui.draw(
+            &Circle::with_center(target, 11)
+                .into_styled(PrimitiveStyle::with_stroke(style.primary_color, 1)),
+        )?;
+
+        // instructions, between the top targets and the center
+        let bounds = ui.bounds();
+        let mut text = heapless::String::<64>::new();
+        write!(
+            text,
+            "Touch the crosshair ({}/{})",
+            index + 1,
+            targets.len()
+        )
+        .ok();
+        if self.retries > 0 {
+            text.push_str("\nInconsistent, try again").ok();
+        }
+        let position = bounds.top_left
+            + Point::new(
+                bounds.size.width as i32 / 2,
+                bounds.size.height as i32 * 3 / 10,
+            );
+        ui.draw(&Text::with_text_style(
+            &text,
+            position,
+            MonoTextStyle::new(&style.default_font, style.text_color),
+            TextStyleBuilder::new()
+                .alignment(Alignment::Center)
+                .baseline(Baseline::Middle)
+                .build(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// A fake touch controller with swapped and mirrored axes
+    fn raw(screen: Point) -> Point {
+        Point::new(4000 - screen.y * 50, 100 + screen.x * 60)
+    }
+
+    fn display() -> MockDisplay<Rgb565> {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        display.set_allow_out_of_bounds_drawing(true);
+        display
+    }
+
+    /// Touches each point for 3 frames and releases, returning the result of the last step
+    fn touch_all(
+        screen: &mut CalibrationScreen,
+        display: &mut MockDisplay<Rgb565>,
+        points: &[Point],
+    ) -> Option<Calibration> {
+        let mut result = None;
+        for p in points {
+            for sample in [Some(raw(*p)), Some(raw(*p)), Some(raw(*p)), None] {
+                let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+                result = screen.step(&mut ui, sample);
+            }
+        }
+        result
+    }
+
+    fn targets() -> heapless::Vec<Point, 5> {
+        let mut display = display();
+        let ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        CalibrationPoints::Five.targets(ui.bounds())
+    }
+
+    #[test]
+    fn five_points_calibrate() {
+        let mut display = display();
+        let mut screen = CalibrationScreen::new(CalibrationPoints::Five);
+
+        let calibration = touch_all(&mut screen, &mut display, &targets()).unwrap();
+        for p in [Point::new(3, 3), Point::new(30, 40), Point::new(60, 60)] {
+            assert_eq!(calibration.apply(raw(p)), p);
+        }
+        assert_eq!(screen.retries(), 0);
+    }
+
+    #[test]
+    fn inconsistent_readings_restart() {
+        let mut display = display();
+        let mut screen = CalibrationScreen::new(CalibrationPoints::Five);
+
+        // the user hits the wrong spot for the last target
+        let mut points = targets();
+        points[4] += Point::new(20, -15);
+        assert_eq!(touch_all(&mut screen, &mut display, &points), None);
+        assert_eq!(screen.retries(), 1);
+
+        assert!(touch_all(&mut screen, &mut display, &targets()).is_some());
+    }
+
+    #[test]
+    fn short_touches_are_ignored() {
+        let mut display = display();
+        let mut screen = CalibrationScreen::new(CalibrationPoints::Three);
+        let targets =
+            CalibrationPoints::Three.targets(Rectangle::new(Point::new(3, 3), Size::new(58, 58)));
+
+        for sample in [Some(raw(targets[0])), None] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            assert_eq!(screen.step(&mut ui, sample), None);
+        }
+        assert!(touch_all(&mut screen, &mut display, &targets).is_some());
+    }
+}
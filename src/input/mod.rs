@@ -0,0 +1,3 @@
+//! Helpers for turning raw input device readings into [Interaction](crate::ui::Interaction)s.
+pub mod calibration;
+pub mod touch;
@@ -0,0 +1,427 @@
+//! # Touch controller adapter
+//!
+//! Touch controllers (e.g. the resistive XPT2046 or the capacitive FT6x36) report raw samples:
+//! either "not touched", or a position in the controller's own coordinate system. [TouchAdapter]
+//! turns one raw sample per frame into the [Interaction] for [Ui::interact](crate::ui::Ui::interact):
+//!
+//! - raw coordinates are mapped to screen coordinates with a [Calibration] (an affine transform,
+//!   so scaling, offsets, swapped and mirrored axes and slight rotation are all covered)
+//! - touches and releases are debounced, i.e. only accepted after a number of consecutive
+//!   samples agree
+//! - edges are detected, producing [Interaction::Click] on press, [Interaction::Drag] while held,
+//!   [Interaction::Release] on release, and [Interaction::None] otherwise (touch screens
+//!   don't hover)
+//!
+//! The adapter is driver-agnostic: read the controller with whatever driver you use, and pass
+//! the samples as `Option<Point>`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use embedded_graphics::prelude::*;
+//! # use kolibri_embedded_gui::input::touch::{Calibration, TouchAdapter};
+//! # use kolibri_embedded_gui::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # fn read_touch_controller() -> Option<Point> { None }
+//! // raw XPT2046 readings at two known screen points (e.g. from a calibration run)
+//! let calibration = Calibration::from_two_points(
+//!     [Point::new(3800, 300), Point::new(300, 3800)],
+//!     [Point::new(0, 0), Point::new(319, 239)],
+//! )
+//! .unwrap();
+//!
+//! let mut touch = TouchAdapter::new().debounce(2);
+//! touch.set_calibration(calibration);
+//!
+//! loop {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     ui.interact(touch.update(read_touch_controller()));
+//!     // [...]
+//! #   break;
+//! }
+//! ```
+
+use crate::ui::Interaction;
+use embedded_graphics::prelude::Point;
+
+/// Number of fractional bits of the [Calibration] matrix entries
+pub const CALIBRATION_FRAC_BITS: u32 = 16;
+
+const ONE: i128 = 1 << CALIBRATION_FRAC_BITS;
+
+/// Affine transform from raw touch controller coordinates to screen coordinates.
+///
+/// A raw point `(x, y)` maps to
+///
+/// ```text
+/// screen_x = a * x + b * y + c
+/// screen_y = d * x + e * y + f
+/// ```
+///
+/// The matrix `[a, b, c, d, e, f]` is stored in fixed point, with [CALIBRATION_FRAC_BITS]
+/// fractional bits (i.e. `1 << 16` is `1.0`), so no floating point math is needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Calibration {
+    matrix: [i32; 6],
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl Calibration {
+    /// The identity transform, for controllers that already report screen coordinates
+    pub const IDENTITY: Calibration = Calibration {
+        matrix: [
+            1 << CALIBRATION_FRAC_BITS,
+            0,
+            0,
+            0,
+            1 << CALIBRATION_FRAC_BITS,
+            0,
+        ],
+    };
+
+    /// Creates a calibration from the fixed-point matrix `[a, b, c, d, e, f]` (see [Calibration]).
+    pub const fn from_matrix(matrix: [i32; 6]) -> Self {
+        Self { matrix }
+    }
+
+    /// Returns the fixed-point matrix `[a, b, c, d, e, f]` (see [Calibration]), e.g. to store it.
+    pub const fn matrix(&self) -> [i32; 6] {
+        self.matrix
+    }
+
+    /// Creates a calibration from two raw readings and the screen points they were taken at.
+    ///
+    /// This covers scaling, offsets and mirrored axes, but not swapped axes or rotation. Use
+    /// points far apart on both axes (e.g. opposite corners) for the best accuracy.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The raw readings
+    /// * `screen` - The screen points where `raw` was read
+    ///
+    /// # Returns
+    ///
+    /// `None` if the raw readings have the same x or y coordinate.
+    pub fn from_two_points(raw: [Point; 2], screen: [Point; 2]) -> Option<Self> {
+        let axis = |r0: i32, r1: i32, s0: i32, s1: i32| -> Option<(i32, i32)> {
+            let (r0, r1, s0, s1) = (r0 as i128, r1 as i128, s0 as i128, s1 as i128);
+            if r0 == r1 {
+                return None;
+            }
+            let scale = div_round((s1 - s0) * ONE, r1 - r0);
+            let offset = s0 * ONE - scale * r0;
+            Some((fixed(scale)?, fixed(offset)?))
+        };
+        let (a, c) = axis(raw[0].x, raw[1].x, screen[0].x, screen[1].x)?;
+        let (e, f) = axis(raw[0].y, raw[1].y, screen[0].y, screen[1].y)?;
+        Some(Self::from_matrix([a, 0, c, 0, e, f]))
+    }
+
+    /// Creates a calibration from three or more raw readings and the screen points they were
+    /// taken at, as the least-squares fit of an affine transform.
+    ///
+    /// With three points, the transform maps them exactly. More points (e.g. five: the corners
+    /// and the center) average out noisy readings.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The raw readings
+    /// * `screen` - The screen points where `raw` was read, in the same order
+    ///
+    /// # Returns
+    ///
+    /// `None` if there are fewer than three points, `raw` and `screen` differ in length,
+    /// or the raw readings are on a line.
+    pub fn from_points(raw: &[Point], screen: &[Point]) -> Option<Self> {
+        if raw.len() < 3 || raw.len() != screen.len() {
+            return None;
+        }
+        let n = raw.len() as i128;
+        let sum = |f: fn(&Point) -> i32, points: &[Point]| -> i128 {
+            points.iter().map(|p| f(p) as i128).sum()
+        };
+        let (sum_x, sum_y) = (sum(|p| p.x, raw), sum(|p| p.y, raw));
+        let (sum_sx, sum_sy) = (sum(|p| p.x, screen), sum(|p| p.y, screen));
+
+        // centered (and scaled by n, to stay in integers) coordinates, so there's no intercept
+        // to solve for
+        let (mut suu, mut svv, mut suv) = (0i128, 0i128, 0i128);
+        let (mut su_sx, mut sv_sx, mut su_sy, mut sv_sy) = (0i128, 0i128, 0i128, 0i128);
+        for (r, s) in raw.iter().zip(screen) {
+            let u = n * r.x as i128 - sum_x;
+            let v = n * r.y as i128 - sum_y;
+            let sx = n * s.x as i128 - sum_sx;
+            let sy = n * s.y as i128 - sum_sy;
+            suu += u * u;
+            svv += v * v;
+            suv += u * v;
+            su_sx += u * sx;
+            sv_sx += v * sx;
+            su_sy += u * sy;
+            sv_sy += v * sy;
+        }
+        let det = suu * svv - suv * suv;
+        if det == 0 {
+            return None;
+        }
+
+        // solves the 2x2 normal equations, then moves the origin back
+        let row = |su_s: i128, sv_s: i128, sum_s: i128| -> Option<[i32; 3]> {
+            let alpha = su_s * svv - sv_s * suv;
+            let beta = sv_s * suu - su_s * suv;
+            let a = div_round(alpha * ONE, det);
+            let b = div_round(beta * ONE, det);
+            let c = div_round((sum_s * det - alpha * sum_x - beta * sum_y) * ONE, n * det);
+            Some([fixed(a)?, fixed(b)?, fixed(c)?])
+        };
+        let [a, b, c] = row(su_sx, sv_sx, sum_sx)?;
+        let [d, e, f] = row(su_sy, sv_sy, sum_sy)?;
+        Some(Self::from_matrix([a, b, c, d, e, f]))
+    }
+
+    /// Maps a raw reading to screen coordinates, rounding to the nearest pixel.
+    pub fn apply(&self, raw: Point) -> Point {
+        let [a, b, c, d, e, f] = self.matrix.map(|v| v as i64);
+        let (x, y) = (raw.x as i64, raw.y as i64);
+        let half = 1i64 << (CALIBRATION_FRAC_BITS - 1);
+        Point::new(
+            ((a * x + b * y + c + half) >> CALIBRATION_FRAC_BITS) as i32,
+            ((d * x + e * y + f + half) >> CALIBRATION_FRAC_BITS) as i32,
+        )
+    }
+}
+
+/// Divides, rounding to the nearest integer (away from zero on ties)
+fn div_round(n: i128, d: i128) -> i128 {
+    if (n < 0) != (d < 0) {
+        (n - d / 2) / d
+    } else {
+        (n + d / 2) / d
+    }
+}
+
+fn fixed(v: i128) -> Option<i32> {
+    i32::try_from(v).ok()
+}
+
+/// Converts raw touch samples to [Interaction]s, see the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TouchAdapter {
+    calibration: Calibration,
+    debounce: u8,
+    pressed: bool,
+    /// Consecutive samples that disagree with `pressed`
+    streak: u8,
+    /// Last (calibrated) touch position
+    last: Point,
+}
+
+impl Default for TouchAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TouchAdapter {
+    /// Creates an adapter with the identity calibration and no debouncing.
+    pub fn new() -> Self {
+        Self {
+            calibration: Calibration::IDENTITY,
+            debounce: 1,
+            pressed: false,
+            streak: 0,
+            last: Point::zero(),
+        }
+    }
+
+    /// Sets how many consecutive samples have to agree before a touch or release is accepted.
+    ///
+    /// `1` (the default) accepts every change immediately. Higher values filter out the
+    /// spurious touches and dropouts of resistive panels, at the cost of `samples - 1` frames
+    /// of latency. While a release isn't accepted yet, the adapter keeps dragging at the last
+    /// position.
+    pub fn debounce(mut self, samples: u8) -> Self {
+        self.debounce = samples.max(1);
+        self
+    }
+
+    /// Sets the calibration used to map raw samples to screen coordinates.
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+    }
+
+    /// Returns the current calibration.
+    pub fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+
+    /// Returns whether a touch is currently accepted.
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Forgets the current touch, e.g. after the screen was switched off.
+    pub fn reset(&mut self) {
+        self.pressed = false;
+        self.streak = 0;
+    }
+
+    /// Processes the raw sample of this frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw` - The raw touch position, or `None` if not touched
+    ///
+    /// # Returns
+    ///
+    /// The [Interaction] to pass to [Ui::interact](crate::ui::Ui::interact).
+    pub fn update(&mut self, raw: Option<Point>) -> Interaction {
+        let sample = raw.map(|p| self.calibration.apply(p));
+        if sample.is_some() == self.pressed {
+            self.streak = 0;
+        } else {
+            self.streak = self.streak.saturating_add(1);
+        }
+        let change = self.streak >= self.debounce;
+
+        match (self.pressed, sample) {
+            (false, Some(p)) if change => {
+                self.pressed = true;
+                self.streak = 0;
+                self.last = p;
+                Interaction::Click(p)
+            }
+            (false, _) => Interaction::None,
+            (true, Some(p)) => {
+                self.last = p;
+                Interaction::Drag(p)
+            }
+            (true, None) if change => {
+                self.pressed = false;
+                self.streak = 0;
+                Interaction::Release(self.last)
+            }
+            (true, None) => Interaction::Drag(self.last),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fake XPT2046 mounted with swapped and mirrored axes on a 320x240 screen
+    fn raw(screen: Point) -> Point {
+        Point::new(3900 - screen.y * 3600 / 240, 200 + screen.x * 3600 / 320)
+    }
+
+    #[test]
+    fn three_points_map_exactly() {
+        let screen = [
+            Point::new(20, 20),
+            Point::new(300, 120),
+            Point::new(160, 220),
+        ];
+        let calibration = Calibration::from_points(&screen.map(raw), &screen).unwrap();
+        for p in screen {
+            assert_eq!(calibration.apply(raw(p)), p);
+        }
+        assert_eq!(
+            calibration.apply(raw(Point::new(100, 50))),
+            Point::new(100, 50)
+        );
+    }
+
+    #[test]
+    fn five_noisy_points_average_out() {
+        let screen = [
+            Point::new(20, 20),
+            Point::new(300, 20),
+            Point::new(300, 220),
+            Point::new(20, 220),
+            Point::new(160, 120),
+        ];
+        let noise = [(9, -7), (-8, 6), (7, 8), (-6, -9), (5, 0)];
+        let readings: heapless::Vec<Point, 5> = screen
+            .iter()
+            .zip(noise)
+            .map(|(p, (dx, dy))| raw(*p) + Point::new(dx, dy))
+            .collect();
+        let calibration = Calibration::from_points(&readings, &screen).unwrap();
+        for p in screen {
+            let error = calibration.apply(raw(p)) - p;
+            assert!(error.x.abs() <= 1 && error.y.abs() <= 1, "{p:?}: {error:?}");
+        }
+    }
+
+    #[test]
+    fn degenerate_points_fail() {
+        let line = [Point::new(0, 0), Point::new(10, 10), Point::new(20, 20)];
+        assert_eq!(Calibration::from_points(&line, &line), None);
+        assert_eq!(Calibration::from_points(&line[..2], &line[..2]), None);
+        assert_eq!(
+            Calibration::from_two_points([Point::new(5, 0), Point::new(5, 9)], [Point::zero(); 2]),
+            None
+        );
+    }
+
+    #[test]
+    fn two_points_scale_and_mirror() {
+        let calibration = Calibration::from_two_points(
+            [Point::new(3800, 300), Point::new(300, 3800)],
+            [Point::new(0, 0), Point::new(350, 350)],
+        )
+        .unwrap();
+        assert_eq!(calibration.apply(Point::new(3800, 300)), Point::new(0, 0));
+        assert_eq!(
+            calibration.apply(Point::new(2050, 2050)),
+            Point::new(175, 175)
+        );
+        assert_eq!(
+            calibration.apply(Point::new(300, 3800)),
+            Point::new(350, 350)
+        );
+    }
+
+    #[test]
+    fn adapter_emits_edges() {
+        let p = Point::new(10, 20);
+        let q = Point::new(30, 40);
+        let mut touch = TouchAdapter::new();
+        assert_eq!(touch.update(None), Interaction::None);
+        assert_eq!(touch.update(Some(p)), Interaction::Click(p));
+        assert_eq!(touch.update(Some(q)), Interaction::Drag(q));
+        assert_eq!(touch.update(None), Interaction::Release(q));
+        assert_eq!(touch.update(None), Interaction::None);
+    }
+
+    #[test]
+    fn adapter_debounces_touches_and_dropouts() {
+        let p = Point::new(10, 20);
+        let mut touch = TouchAdapter::new().debounce(3);
+
+        // a single spurious sample is ignored
+        assert_eq!(touch.update(Some(p)), Interaction::None);
+        assert_eq!(touch.update(None), Interaction::None);
+
+        assert_eq!(touch.update(Some(p)), Interaction::None);
+        assert_eq!(touch.update(Some(p)), Interaction::None);
+        assert_eq!(touch.update(Some(p)), Interaction::Click(p));
+        // a dropout keeps dragging at the last position
+        assert_eq!(touch.update(None), Interaction::Drag(p));
+        assert_eq!(touch.update(Some(p)), Interaction::Drag(p));
+        assert_eq!(touch.update(None), Interaction::Drag(p));
+        assert_eq!(touch.update(None), Interaction::Drag(p));
+        assert_eq!(touch.update(None), Interaction::Release(p));
+        assert!(!touch.is_pressed());
+    }
+}
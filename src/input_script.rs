@@ -0,0 +1,312 @@
+//! # Scripted input for tests
+//!
+//! [InputScript] describes a timed sequence of touch/mouse input, e.g. "press, hold for 500ms,
+//! drag off, release". Played back, it yields one [Frame] per frame, carrying the frame's
+//! timestamp and [Interaction]. Applying a [Frame] to a [Ui] sets both, so interaction logic
+//! (clicks, long presses, repeats, drag cancelling) can be tested deterministically without
+//! a simulator window.
+//!
+//! Frames are `frame_ms` apart (16ms by default, see [InputScript::frame_ms]), starting at 0ms.
+//! [InputScript::press], [InputScript::move_to] and [InputScript::release] take one frame each,
+//! [InputScript::wait_ms] repeats the current state for the given time.
+//!
+//! This module is only available with the `test-utils` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use embedded_graphics::prelude::*;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # use kolibri_embedded_gui::input_script::InputScript;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! let script = InputScript::<8>::new()
+//!     .press(10, 10)
+//!     .wait_ms(100)
+//!     .move_to(200, 200)
+//!     .release();
+//!
+//! let mut clicks = 0;
+//! for frame in script {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     frame.apply(&mut ui);
+//!     if ui.add(Button::new("OK")).clicked() {
+//!         clicks += 1;
+//!     }
+//! }
+//! // dragged off before releasing
+//! assert_eq!(clicks, 0);
+//! ```
+
+use crate::ui::{Interaction, Ui};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::Point;
+
+/// A single step of an [InputScript]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Press down at the point
+    Press(Point),
+    /// Move to the point, dragging if pressed and hovering otherwise
+    MoveTo(Point),
+    /// Release at the current point
+    Release,
+    /// Keep the current state for the given number of milliseconds
+    Wait(u64),
+}
+
+/// A timed sequence of input for tests, holding up to `N` [Step]s.
+///
+/// See the [module documentation](self) for an example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputScript<const N: usize = 32> {
+    steps: heapless::Vec<Step, N>,
+    frame_ms: u64,
+}
+
+impl<const N: usize> Default for InputScript<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> InputScript<N> {
+    /// Creates an empty script with a frame time of 16ms.
+    pub fn new() -> Self {
+        Self {
+            steps: heapless::Vec::new(),
+            frame_ms: 16,
+        }
+    }
+
+    /// Sets the time between two frames.
+    ///
+    /// # Panics
+    ///
+    /// If `frame_ms` is zero.
+    pub fn frame_ms(mut self, frame_ms: u64) -> Self {
+        assert!(frame_ms > 0, "frame time must not be zero");
+        self.frame_ms = frame_ms;
+        self
+    }
+
+    /// Appends a step.
+    ///
+    /// # Panics
+    ///
+    /// If the script already holds `N` steps.
+    pub fn step(mut self, step: Step) -> Self {
+        self.steps
+            .push(step)
+            .expect("InputScript is full, increase its capacity");
+        self
+    }
+
+    /// Presses down at (`x`, `y`).
+    pub fn press(self, x: i32, y: i32) -> Self {
+        self.step(Step::Press(Point::new(x, y)))
+    }
+
+    /// Moves to (`x`, `y`). Drags if pressed, hovers otherwise.
+    pub fn move_to(self, x: i32, y: i32) -> Self {
+        self.step(Step::MoveTo(Point::new(x, y)))
+    }
+
+    /// Releases at the current point.
+    pub fn release(self) -> Self {
+        self.step(Step::Release)
+    }
+
+    /// Presses and releases at (`x`, `y`) in two consecutive frames.
+    pub fn click(self, x: i32, y: i32) -> Self {
+        self.press(x, y).release()
+    }
+
+    /// Keeps the current state (held down, hovering or idle) for `ms` milliseconds.
+    ///
+    /// This yields `ms / frame_ms` frames, rounded up.
+    pub fn wait_ms(self, ms: u64) -> Self {
+        self.step(Step::Wait(ms))
+    }
+
+    /// Returns the steps of the script.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+impl<const N: usize> IntoIterator for InputScript<N> {
+    type Item = Frame;
+    type IntoIter = Frames<N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Frames {
+            script: self,
+            index: 0,
+            wait_frames: 0,
+            now_ms: 0,
+            pos: None,
+            pressed: false,
+        }
+    }
+}
+
+/// The input of a single frame, see [InputScript]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// Timestamp of the frame, in milliseconds since the start of the script
+    pub now_ms: u64,
+    /// Time since the previous frame, in milliseconds (`0` for the first frame)
+    pub dt_ms: u32,
+    /// The interaction in this frame
+    pub interaction: Interaction,
+}
+
+impl Frame {
+    /// Starts the frame on `ui`, setting its clock (see [Ui::begin_frame] and [Ui::tick]) and
+    /// interaction.
+    pub fn apply<DRAW, COL>(&self, ui: &mut Ui<DRAW, COL>)
+    where
+        DRAW: DrawTarget<Color = COL>,
+        COL: PixelColor,
+    {
+        ui.begin_frame(self.now_ms);
+        ui.tick(self.dt_ms);
+        ui.interact(self.interaction);
+    }
+}
+
+/// Iterator over the [Frame]s of an [InputScript]
+#[derive(Debug, Clone)]
+pub struct Frames<const N: usize> {
+    script: InputScript<N>,
+    index: usize,
+    /// Frames left in the current [Step::Wait]
+    wait_frames: u64,
+    now_ms: u64,
+    pos: Option<Point>,
+    pressed: bool,
+}
+
+impl<const N: usize> Frames<N> {
+    /// The interaction of a frame in which the state doesn't change
+    fn held(&self) -> Interaction {
+        match (self.pos, self.pressed) {
+            (Some(pos), true) => Interaction::Drag(pos),
+            (Some(pos), false) => Interaction::Hover(pos),
+            (None, _) => Interaction::None,
+        }
+    }
+
+    fn frame(&mut self, interaction: Interaction) -> Frame {
+        let frame = Frame {
+            now_ms: self.now_ms,
+            dt_ms: if self.now_ms == 0 {
+                0
+            } else {
+                self.script.frame_ms.try_into().unwrap_or(u32::MAX)
+            },
+            interaction,
+        };
+        self.now_ms += self.script.frame_ms;
+        frame
+    }
+}
+
+impl<const N: usize> Iterator for Frames<N> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        loop {
+            if self.wait_frames > 0 {
+                self.wait_frames -= 1;
+                return Some(self.frame(self.held()));
+            }
+
+            let step = *self.script.steps.get(self.index)?;
+            self.index += 1;
+            let interaction = match step {
+                Step::Press(pos) => {
+                    self.pos = Some(pos);
+                    self.pressed = true;
+                    Interaction::Click(pos)
+                }
+                Step::MoveTo(pos) => {
+                    self.pos = Some(pos);
+                    self.held()
+                }
+                Step::Release => {
+                    let interaction = match (self.pos, self.pressed) {
+                        (Some(pos), true) => Interaction::Release(pos),
+                        _ => Interaction::None,
+                    };
+                    // a touch screen doesn't hover after releasing
+                    self.pos = None;
+                    self.pressed = false;
+                    interaction
+                }
+                Step::Wait(ms) => {
+                    self.wait_frames = ms.div_ceil(self.script.frame_ms);
+                    continue;
+                }
+            };
+            return Some(self.frame(interaction));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interactions<const N: usize>(
+        script: InputScript<N>,
+    ) -> heapless::Vec<(u64, Interaction), 32> {
+        script
+            .into_iter()
+            .map(|f| (f.now_ms, f.interaction))
+            .collect()
+    }
+
+    #[test]
+    fn steps_become_timed_frames() {
+        let p = Point::new(1, 2);
+        let q = Point::new(3, 4);
+        let script = InputScript::<8>::new()
+            .frame_ms(10)
+            .press(1, 2)
+            .wait_ms(25)
+            .move_to(3, 4)
+            .release()
+            .wait_ms(10);
+
+        assert_eq!(
+            interactions(script).as_slice(),
+            &[
+                (0, Interaction::Click(p)),
+                (10, Interaction::Drag(p)),
+                (20, Interaction::Drag(p)),
+                (30, Interaction::Drag(p)),
+                (40, Interaction::Drag(q)),
+                (50, Interaction::Release(q)),
+                (60, Interaction::None),
+            ]
+        );
+    }
+
+    #[test]
+    fn moving_without_press_hovers() {
+        let p = Point::new(5, 5);
+        let script = InputScript::<4>::new().move_to(5, 5).wait_ms(16).release();
+        assert_eq!(
+            interactions(script).as_slice(),
+            &[
+                (0, Interaction::Hover(p)),
+                (16, Interaction::Hover(p)),
+                (32, Interaction::None),
+            ]
+        );
+    }
+}
@@ -7,27 +7,22 @@
 //! - Automatic vertical centering
 //! - Integration with the smartstate system for efficient redraws
 //! - HashLabel variant for auto-refreshing on content changes
+//! - Allocation-free formatted text with [Label::new_fmt]
 //!
 //! # Examples
 //!
 //! ```no_run
 //! # use embedded_graphics::pixelcolor::Rgb565;
 //! # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-//! # use kolibri_embedded_gui::style::medsize_rgb565_style;
-//! # use kolibri_embedded_gui::ui::Ui;
+//! # use kolibri_embedded_gui::prelude::*;
 //! # use embedded_graphics::prelude::*;
 //! # use embedded_graphics::primitives::Rectangle;
-//! # use embedded_iconoir::prelude::*;
-//! # use embedded_iconoir::size12px;
-//! # use kolibri_embedded_gui::ui::*;
 //! # use embedded_graphics::mono_font::ascii;
-//! # use kolibri_embedded_gui::label::*;
-//! # use kolibri_embedded_gui::smartstate::*;
+//! # use kolibri_embedded_gui::label::Hasher;
 //! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 //! # let output_settings = OutputSettingsBuilder::new().build();
 //! # let mut window = Window::new("Kolibri Example", &output_settings);
 //! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-//! # use kolibri_embedded_gui::label::*;
 //!
 //! // Create a basic label
 //! ui.add(Label::new("Hello World"));
@@ -39,18 +34,20 @@
 //! ui.add(HashLabel::new("Dynamic content", smartstate.nxt(), &hasher));
 //! ```
 
+use crate::format::Buf;
 use crate::smartstate::{Container, Smartstate};
-use crate::ui::{GuiError, GuiResult, Response, Ui, Widget};
+use crate::text::{
+    ellipsize, lines, measure_lines, text_layout, Font, RenderedText, TextRenderer, ELLIPSIZED_LEN,
+};
+use crate::ui::{GuiError, GuiResult, HorizontalAlign, Response, Ui, Widget};
+use core::fmt;
 use core::hash::BuildHasher;
 use core::hash::Hash;
-use core::ops::Add;
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::{Point, Size};
 use embedded_graphics::mono_font::MonoFont;
-use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::pixelcolor::PixelColor;
-use embedded_graphics::prelude::*;
-use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::primitives::Rectangle;
 use foldhash::fast::RandomState;
 
 /// A widget for displaying text in the UI.
@@ -63,28 +60,22 @@ use foldhash::fast::RandomState;
 /// - Basic text display with customizable fonts
 /// - Smartstate integration for incremental redrawing
 /// - Automatic vertical centering in allocated space
+/// - Borrowed (`&str`) or owned text (see [Label::owned])
 ///
 /// # Examples
 ///
 /// ```no_run
 /// # use embedded_graphics::pixelcolor::Rgb565;
 /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-/// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-/// # use kolibri_embedded_gui::ui::Ui;
+/// # use kolibri_embedded_gui::prelude::*;
 /// # use embedded_graphics::prelude::*;
 /// # use embedded_graphics::primitives::Rectangle;
-/// # use embedded_iconoir::prelude::*;
-/// # use embedded_iconoir::size12px;
-/// # use kolibri_embedded_gui::ui::*;
 /// # use embedded_graphics::mono_font::ascii;
-/// # use kolibri_embedded_gui::label::*;
-/// # use kolibri_embedded_gui::smartstate::*;
 /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 /// # let output_settings = OutputSettingsBuilder::new().build();
 /// # let mut window = Window::new("Kolibri Example", &output_settings);
 /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
 /// # let mut smartstateProvider = SmartstateProvider::<20>::new();
-/// # use kolibri_embedded_gui::label::*;
 ///
 /// // Basic label
 /// ui.add(Label::new("Basic text"));
@@ -92,10 +83,18 @@ use foldhash::fast::RandomState;
 /// // Label with custom font and smartstate
 /// ui.add(Label::new("Custom font").with_font(ascii::FONT_10X20).smartstate(smartstateProvider.nxt()));
 /// ```
-pub struct Label<'a> {
-    text: &'a str,
+pub struct Label<'a, T = &'a str> {
+    text: T,
     font: Option<MonoFont<'a>>,
     smartstate: Container<'a, Smartstate>,
+    /// Whether the smartstate tracks the text (see [Label::new_fmt])
+    hashed: bool,
+    /// Whether the area is left as is behind the text (see [Label::transparent_background])
+    transparent: bool,
+    /// Whether text that doesn't fit ends in an ellipsis (see [Label::truncate])
+    truncate: bool,
+    /// Width the text is truncated to (see [Label::max_width])
+    max_width: Option<u32>,
 }
 
 impl<'a> Label<'a> {
@@ -106,20 +105,13 @@ impl<'a> Label<'a> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use embedded_iconoir::size12px;
-    /// # use kolibri_embedded_gui::ui::*;
     /// # use embedded_graphics::mono_font::ascii;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # use kolibri_embedded_gui::label::*;
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
     /// ui.add(Label::new("Hello World"));
     /// ```
@@ -128,6 +120,73 @@ impl<'a> Label<'a> {
             text,
             font: None,
             smartstate: Container::empty(),
+            hashed: false,
+            transparent: false,
+            truncate: false,
+            max_width: None,
+        }
+    }
+
+    /// Creates a label with formatted text, without allocating.
+    ///
+    /// The text is formatted into `buf` (see [Buf]), and cut off if it doesn't fit. Unlike
+    /// [Label::new], the label's smartstate tracks a hash of the text, so the label redraws
+    /// by itself whenever the text changes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::format::Buf;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut smartstateProvider = SmartstateProvider::<20>::new();
+    /// # let rpm = 1200;
+    /// let mut buf = Buf::<24>::new();
+    /// ui.add(Label::new_fmt(&mut buf, format_args!("{} rpm", rpm)).smartstate(smartstateProvider.nxt()));
+    /// ```
+    pub fn new_fmt<const N: usize>(buf: &'a mut Buf<N>, args: fmt::Arguments) -> Label<'a> {
+        Label {
+            hashed: true,
+            ..Label::new(buf.format(args))
+        }
+    }
+}
+
+impl<'a, T: AsRef<str>> Label<'a, T> {
+    /// Creates a label owning its text, e.g. a [Buf] formatted within the frame (or a `String`
+    /// with an allocator), which doesn't have to outlive the label like with [Label::new].
+    ///
+    /// Like with [Label::new_fmt], the label's smartstate tracks a hash of the text, so the
+    /// label redraws by itself whenever the text changes.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::format::Buf;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut smartstateProvider = SmartstateProvider::<20>::new();
+    /// # let rpm = 1200;
+    /// let text = Buf::<24>::from_fmt(format_args!("{} rpm", rpm));
+    /// ui.add(Label::owned(text).smartstate(smartstateProvider.nxt()));
+    /// ```
+    pub fn owned(text: T) -> Self {
+        Label {
+            text,
+            font: None,
+            smartstate: Container::empty(),
+            hashed: true,
+            transparent: false,
+            truncate: false,
+            max_width: None,
         }
     }
 
@@ -138,20 +197,14 @@ impl<'a> Label<'a> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use embedded_iconoir::size12px;
-    /// # use kolibri_embedded_gui::ui::*;
     /// # use embedded_graphics::mono_font::ascii;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
+    /// # use kolibri_embedded_gui::label::Hasher;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # use kolibri_embedded_gui::label::*;
     /// # let hasher = Hasher::new();
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
     /// ui.add(Label::new("Custom Font").with_font(ascii::FONT_10X20));
@@ -161,6 +214,47 @@ impl<'a> Label<'a> {
         self
     }
 
+    /// Cuts off text that is wider than the rest of the row, ending it with an ellipsis
+    /// ("Temperature se…"), instead of failing to add the label.
+    ///
+    /// The visible text is tracked by the smartstate, so the label redraws when it changes.
+    /// See [ellipsize](crate::text::ellipsize) for the details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let sensor_name = "Temperature sensor in the basement";
+    /// ui.add(Label::new(sensor_name).truncate());
+    /// ```
+    pub fn truncate(mut self) -> Self {
+        self.truncate = true;
+        self
+    }
+
+    /// Truncates the text like [Label::truncate], but to at most `max_width` pixels (or the rest
+    /// of the row, if that is narrower).
+    pub fn max_width(mut self, max_width: u32) -> Self {
+        self.truncate = true;
+        self.max_width = Some(max_width);
+        self
+    }
+
+    /// Draws the text without clearing the area behind it, e.g. over a background image.
+    ///
+    /// The label can't erase its previous text then. With a smartstate, it waits for the
+    /// application to restore the background before redrawing (see [Ui::stale_area]).
+    /// Without one, it draws over its previous text every frame.
+    pub fn transparent_background(mut self) -> Self {
+        self.transparent = true;
+        self
+    }
+
     /// Adds a [Smartstate] to the label for incremental redrawing.
     ///
     /// When using smartstate, the label will only redraw when the smartstate is
@@ -171,20 +265,14 @@ impl<'a> Label<'a> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use embedded_iconoir::size12px;
-    /// # use kolibri_embedded_gui::ui::*;
     /// # use embedded_graphics::mono_font::ascii;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
+    /// # use kolibri_embedded_gui::label::Hasher;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # use kolibri_embedded_gui::label::*;
     /// # let hasher = Hasher::new();
     /// # let mut smartstateProvider = SmartstateProvider::<20>::new();
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
@@ -197,60 +285,100 @@ impl<'a> Label<'a> {
     }
 }
 
-impl Widget for Label<'_> {
+impl<T: AsRef<str>> Widget for Label<'_, T> {
     fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
         &mut self,
         ui: &mut Ui<DRAW, COL>,
     ) -> GuiResult<Response> {
         // get size
 
-        let font = if let Some(font) = self.font {
-            font
-        } else {
-            ui.style().default_font
+        let font = Font::resolve(self.font, ui.style());
+        let mut ellipsized = Buf::<ELLIPSIZED_LEN>::new();
+        let visible = match self.truncate {
+            true => {
+                let max_width = self.max_width.unwrap_or(u32::MAX);
+                ellipsize(
+                    &font,
+                    self.text.as_ref(),
+                    max_width.min(ui.remaining_width()),
+                    &mut ellipsized,
+                )
+            }
+            false => self.text.as_ref(),
         };
+        let size = measure_lines(&font, visible, None);
 
-        let mut text = Text::new(
-            self.text,
-            Point::new(0, 0),
-            MonoTextStyle::new(&font, ui.style().text_color),
-        );
-
-        let size = text.bounding_box();
-
-        // allocate space
+        // allocate space (with room for a shadow below, like buttons have, so that the text
+        // lines up with theirs in a row)
 
-        let iresponse = ui.allocate_space(Size::new(size.size.width, size.size.height))?;
-
-        // move text (center vertically)
-
-        text.translate_mut(iresponse.area.top_left.add(Point::new(
-            0,
-            (iresponse.area.size.height - size.size.height) as i32 / 2,
-        )));
-        text.text_style.baseline = Baseline::Top;
+        let shadow = Size::new(0, ui.style().shadow_size().height);
+        let iresponse = ui.allocate_space(size + shadow)?;
 
         // check smartstate (a bool would work, but this is consistent with other widgets)
-        let redraw = !self.smartstate.eq_option(&Some(Smartstate::state(0)));
-        self.smartstate.modify(|st| *st = Smartstate::state(0));
+        let mut state = Smartstate::state(0);
+        if self.hashed || self.truncate {
+            // (truncated text depends on the available width)
+            state.set_state_hashed(visible);
+        }
+        state.mix(ui.style_key());
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(iresponse.area);
+        let tracked = self.smartstate.clone_inner().is_some();
+        if redraw && self.transparent && tracked && !ui.redraw_transparent(iresponse.area) {
+            // keep the previous state until the background is restored
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+        if redraw {
+            trace!(
+                "Label smartstate: {} -> {}",
+                self.smartstate.clone_inner(),
+                state
+            );
+        }
+        self.smartstate.modify(|st| *st = state);
 
         // draw
 
         if redraw {
             ui.start_drawing(&iresponse.area);
             // clear background if necessary
-            if !ui.cleared() {
+            if !ui.cleared() && !self.transparent {
                 ui.clear_area(iresponse.area)?;
             }
 
-            ui.draw(&text)
-                .map_err(|_| GuiError::DrawError(Some("Couldn't draw text")))?;
+            let text_box = Rectangle::new(
+                iresponse.area.top_left,
+                iresponse.area.size.saturating_sub(shadow),
+            );
+            draw_lines(ui, &font, visible, size, text_box)?;
 
             ui.finalize()?;
         }
 
         Ok(Response::new(iresponse))
     }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+/// Draws `text` split into [lines], left aligned, with the block of the measured `size` centered
+/// vertically in `text_box`.
+fn draw_lines<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+    ui: &mut Ui<DRAW, COL>,
+    font: &Font<'_>,
+    text: &str,
+    size: Size,
+    text_box: Rectangle,
+) -> GuiResult<()> {
+    let top_left = text_layout(size, text_box, HorizontalAlign::Left);
+    for (i, line) in lines(font, text, None).enumerate() {
+        let offset = Point::new(0, (i as u32 * font.line_height()) as i32);
+        let text = RenderedText::new(font, line, top_left + offset, ui.style().text_color);
+        ui.draw(&text)
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw text")))?;
+    }
+    Ok(())
 }
 
 /// A hasher for widgets that require hashing of data.
@@ -295,19 +423,13 @@ impl Default for Hasher {
 /// ```no_run
 /// # use embedded_graphics::pixelcolor::Rgb565;
 /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-/// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-/// # use kolibri_embedded_gui::ui::Ui;
+/// # use kolibri_embedded_gui::prelude::*;
 /// # use embedded_graphics::prelude::*;
 /// # use embedded_graphics::primitives::Rectangle;
-/// # use embedded_iconoir::prelude::*;
-/// # use embedded_iconoir::size12px;
-/// # use kolibri_embedded_gui::ui::*;
-/// # use kolibri_embedded_gui::label::*;
-/// # use kolibri_embedded_gui::smartstate::*;
+/// # use kolibri_embedded_gui::label::Hasher;
 /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 /// # let output_settings = OutputSettingsBuilder::new().build();
 /// # let mut window = Window::new("Kolibri Example", &output_settings);
-/// # use kolibri_embedded_gui::label::*;
 /// # let hasher = Hasher::new();
 /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
 /// # let mut smartstateProvider = SmartstateProvider::<20>::new();
@@ -338,19 +460,13 @@ impl<'a> HashLabel<'a> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use embedded_iconoir::size12px;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
+    /// # use kolibri_embedded_gui::label::Hasher;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # use kolibri_embedded_gui::label::*;
     /// # let hasher = Hasher::new();
     /// # let mut smartstateProvider = SmartstateProvider::<20>::new();
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
@@ -380,20 +496,14 @@ impl<'a> HashLabel<'a> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
     /// # use embedded_graphics::mono_font::ascii;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use embedded_iconoir::size12px;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
+    /// # use kolibri_embedded_gui::label::Hasher;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # use kolibri_embedded_gui::label::*;
     /// # let hasher = Hasher::new();
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
     /// # let mut smartstateProvider = SmartstateProvider::<20>::new();
@@ -414,40 +524,28 @@ impl Widget for HashLabel<'_> {
     ) -> GuiResult<Response> {
         // get size
 
-        let font = if let Some(font) = self.font {
-            font
-        } else {
-            ui.style().default_font
-        };
+        let font = Font::resolve(self.font, ui.style());
+        let size = measure_lines(&font, self.text, None);
 
-        let mut text = Text::new(
-            self.text,
-            Point::new(0, 0),
-            MonoTextStyle::new(&font, ui.style().text_color),
-        );
-
-        let size = text.bounding_box();
-
-        // allocate space
+        // allocate space (with room for a shadow below, see Label)
 
-        let iresponse = ui.allocate_space(Size::new(size.size.width, size.size.height))?;
+        let shadow = Size::new(0, ui.style().shadow_size().height);
+        let iresponse = ui.allocate_space(size + shadow)?;
 
-        let hash = self.hasher.hash(self.text) as u32;
-
-        let redraw = !self.smartstate.eq_option(&Some(Smartstate::state(hash)));
-        self.smartstate.modify(|st| *st = Smartstate::state(hash));
+        let mut state = Smartstate::state(self.hasher.hash(self.text) as u32);
+        state.mix(ui.style_key());
 
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(iresponse.area);
         if redraw {
-            // move text (center vertically)
-
-            text.translate_mut(iresponse.area.top_left.add(Point::new(
-                0,
-                (iresponse.area.size.height - size.size.height) as i32 / 2,
-            )));
-            text.text_style.baseline = Baseline::Top;
-
-            // check smartstate (a bool would work, but this is consistent with other widgets)
+            trace!(
+                "HashLabel smartstate: {} -> {}",
+                self.smartstate.clone_inner(),
+                state
+            );
+        }
+        self.smartstate.modify(|st| *st = state);
 
+        if redraw {
             // draw
 
             ui.start_drawing(&iresponse.area);
@@ -456,12 +554,175 @@ impl Widget for HashLabel<'_> {
                 ui.clear_area(iresponse.area)?;
             }
 
-            ui.draw(&text)
-                .map_err(|_| GuiError::DrawError(Some("Couldn't draw text")))?;
+            let text_box = Rectangle::new(
+                iresponse.area.top_left,
+                iresponse.area.size.saturating_sub(shadow),
+            );
+            draw_lines(ui, &font, self.text, size, text_box)?;
 
             ui.finalize()?;
         }
 
         Ok(Response::new(iresponse))
     }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smartstate::SmartstateProvider;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
+    use embedded_graphics::primitives::{PointsIter, Primitive, PrimitiveStyle};
+    use embedded_graphics::Drawable;
+
+    #[test]
+    fn truncated_label_fits_the_row() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let text = "Temperature sensor";
+        assert!(ui.try_add(Label::new(text)).is_err());
+
+        let response = ui.add(Label::new(text).truncate());
+        // 9px per character, 58px available
+        assert_eq!(response.area().size.width, 6 * 9);
+        let response = ui.add(Label::new(text).max_width(30));
+        assert_eq!(response.area().size.width, 3 * 9);
+
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let response = ui.add(Label::new("Temp").truncate());
+        assert_eq!(response.area().size.width, 4 * 9);
+    }
+
+    #[test]
+    fn multi_line_labels_are_drawn_per_line() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.clear_background().unwrap();
+        // 9x15px per character
+        let area = ui.add(Label::new("On\nIdle")).area();
+        assert_eq!(area.size.width, 4 * 9);
+        assert_eq!(area.size.height, 2 * 15);
+
+        // the second line starts one line height below the first
+        let mut expected = MockDisplay::<Rgb565>::new();
+        let font = medsize_rgb565_style().default_font;
+        for (line, y) in [("On", 0), ("Idle", 15)] {
+            RenderedText::new(&font, line, area.top_left + Point::new(0, y), Rgb565::WHITE)
+                .draw(&mut expected)
+                .unwrap();
+        }
+        for p in Rectangle::new(Point::zero(), Size::new(64, 64)).points() {
+            let text = |display: &MockDisplay<Rgb565>| display.get_pixel(p) == Some(Rgb565::WHITE);
+            assert_eq!(text(&display), text(&expected), "{p}");
+        }
+    }
+
+    #[test]
+    fn non_ascii_labels_are_measured_per_character() {
+        use embedded_graphics::mono_font::iso_8859_5;
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        // 9px per character, also for the replacement glyphs of the Latin font
+        let response = ui.add(Label::new("2°C ок"));
+        assert_eq!(response.area().size.width, 6 * 9);
+
+        let response = ui.add(
+            Label::new("Температура")
+                .with_font(iso_8859_5::FONT_9X15)
+                .truncate(),
+        );
+        // "Тем..."
+        assert_eq!(response.area().size.width, 6 * 9);
+        let response = ui.add(Label::new("Café 21°C").max_width(40));
+        // "C..."
+        assert_eq!(response.area().size.width, 4 * 9);
+    }
+
+    #[test]
+    fn formatted_label_redraws_when_text_changes() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<1>::new();
+        let mut buf = Buf::<8>::new();
+
+        for (value, redraw) in [(1, true), (1, false), (2, true)] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            ui.add(
+                Label::new_fmt(&mut buf, format_args!("v={value}")).smartstate(smartstates.nxt()),
+            );
+            assert_eq!(ui.perf().widgets_redrawn, redraw as u32, "{value}");
+        }
+    }
+
+    #[test]
+    fn owned_text_is_created_within_the_frame() {
+        use crate::button::Button;
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<2>::new();
+
+        for (value, redrawn) in [(1, 2), (1, 0), (2, 2)] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            // the texts are dropped with the widgets
+            let text = Buf::<8>::from_fmt(format_args!("v={value}"));
+            ui.add(Label::owned(text).smartstate(smartstates.nxt()));
+            ui.add(Button::owned(format!("b{value}")).smartstate(smartstates.nxt()));
+            assert_eq!(ui.perf().widgets_redrawn, redrawn, "{value}");
+        }
+
+        // borrowed text still infers the default type parameters, also through `as_ref`
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let text = format!("v={}", 3);
+        let label: Label = Label::new(text.as_ref());
+        let button: Button<'_> = Button::new(&text);
+        assert_eq!(ui.add(label).area().size.width, 3 * 9);
+        ui.add(button);
+    }
+
+    #[test]
+    fn transparent_label_waits_for_the_background() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<1>::new();
+        let mut buf = Buf::<8>::new();
+        let wallpaper = Rgb565::new(3, 5, 7);
+
+        let mut stale: Option<Rectangle> = None;
+        let mut redrawn = heapless::Vec::<u32, 4>::new();
+        for value in [1, 1, 1, 2] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            if let Some(area) = stale {
+                ui.draw(&area.into_styled(PrimitiveStyle::with_fill(wallpaper)))
+                    .unwrap();
+                ui.invalidate(area);
+            }
+            ui.add(
+                Label::new_fmt(&mut buf, format_args!("v={value}"))
+                    .transparent_background()
+                    .smartstate(smartstates.nxt()),
+            );
+            stale = ui.stale_area();
+            assert_eq!(ui.needs_repaint(), stale.is_some());
+            redrawn.push(ui.perf().widgets_redrawn).unwrap();
+        }
+        // drawn once the background was restored, and again after the text changed
+        assert_eq!(redrawn.as_slice(), &[0, 1, 0, 0]);
+        assert!(stale.is_some());
+        // the wallpaper shows through around the text
+        assert_eq!(display.get_pixel(Point::new(3, 3)), Some(wallpaper));
+    }
 }
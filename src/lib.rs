@@ -1,30 +1,88 @@
 #![cfg_attr(not(test), no_std)]
 #![allow(clippy::needless_doctest_main)]
 #![allow(clippy::doc_nested_refdefs)]
-#![cfg_attr(not(doctest), doc = include_str!("../README.md"))]
+#![doc = include_str!("../README.md")]
 
+/// Logs at trace level with [defmt] if the `defmt` feature is enabled, and compiles to nothing otherwise.
+macro_rules! trace {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "defmt")]
+        ::defmt::trace!($($arg)*);
+    }};
+}
+
+pub mod anim;
+#[cfg(feature = "any-widget")]
+pub mod any_widget;
+#[cfg(feature = "instruments")]
+pub mod attitude;
 pub mod button;
+#[cfg(feature = "checkbox")]
 pub mod checkbox;
+#[cfg(feature = "instruments")]
+pub mod compass;
+#[cfg(feature = "controls")]
+pub mod dropdown;
 // mod icon;
 // pub mod icon;
+#[cfg(feature = "icon-widgets")]
 pub mod icon;
 pub mod label;
+pub mod pan_canvas;
+#[cfg(feature = "icon-widgets")]
+pub mod reorderable_list;
+#[cfg(feature = "indicators")]
+pub mod rich_label;
 pub mod smartstate;
 pub mod spacer;
 pub mod style;
+pub mod style_check;
+#[cfg(feature = "serde")]
+pub mod style_de;
 // mod temp;
+pub mod format;
 pub mod framebuf;
+#[cfg(feature = "charts")]
+pub mod heatmap;
 pub mod helpers;
+pub mod hidden;
+#[cfg(feature = "charts")]
+pub mod histogram;
+#[cfg(feature = "icon-widgets")]
 pub mod iconbutton;
+pub mod img;
+pub mod input;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod input_script;
+#[cfg(feature = "charts")]
+pub mod scope;
+#[cfg(feature = "controls")]
+pub mod segmented_control;
+#[cfg(feature = "indicators")]
+pub mod segmented_progress;
+#[cfg(feature = "indicators")]
+pub mod skeleton;
+#[cfg(feature = "controls")]
 pub mod slider;
+#[cfg(feature = "indicators")]
+pub mod spinner;
+pub mod text;
+#[cfg(feature = "embedded-text")]
+pub mod text_box;
+#[cfg(feature = "controls")]
 pub mod toggle_button;
+#[cfg(feature = "controls")]
 pub mod toggle_switch;
+pub mod trig;
 pub mod ui;
+#[cfg(feature = "indicators")]
+pub mod value_label;
+pub mod widget_builder;
+pub mod widget_prelude;
 
-pub mod prelude {
-    pub use embedded_iconoir::prelude::*;
-}
+pub mod prelude;
 
+#[cfg(feature = "icon-widgets")]
 pub use embedded_iconoir::icons;
 
 pub enum RefOption<'a, T> {
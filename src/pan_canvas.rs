@@ -0,0 +1,484 @@
+//! # Pan Canvas
+//!
+//! A [PanCanvas] is a viewport onto content larger than the screen, e.g. a map made of tiles
+//! in external flash, which is panned by dragging it. The content is painted by a closure,
+//! which gets a target in content coordinates (clipped to the viewport) and the rectangle of
+//! the content to paint.
+//!
+//! The viewport's position is kept in a caller-owned [PanState], which also remembers what
+//! was painted. When the view pans and the [Ui] has an [area mover](Ui::set_area_mover), the
+//! painted pixels are moved along, and only the newly exposed parts (at most two thin
+//! rectangles along the edges) are painted. Without one, the whole viewport is painted again.
+//!
+//! A [zoomable](PanCanvas::zoomable) canvas also zooms with a pinch of two touches (see
+//! [Ui::interact_second]) around the pinch's center. The content coordinates are then scaled by
+//! the zoom ([PanState::zoom]), which the closure reads from the state before the canvas is
+//! created. A zoom takes effect in the next frame, which paints the whole viewport.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use kolibri_embedded_gui::ui::CustomTarget;
+//! # use embedded_graphics::prelude::*;
+//! # use embedded_graphics::primitives::Rectangle;
+//! use kolibri_embedded_gui::pan_canvas::PanState;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # fn draw_tile<D: DrawTarget>(target: &mut D, x: i32, y: i32) -> Result<(), D::Error> { Ok(()) }
+//! // a map of 16x16 tiles of 64x64 pixels
+//! let mut view = PanState::new();
+//!
+//! loop {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     ui.add(
+//!         PanCanvas::new(&mut view, |target: &mut CustomTarget<Rgb565>, visible: Rectangle| {
+//!             // the tiles overlapping the visible part
+//!             let first = visible.top_left / 64;
+//!             let last = visible.bottom_right().unwrap_or(visible.top_left) / 64;
+//!             for y in first.y..=last.y {
+//!                 for x in first.x..=last.x {
+//!                     draw_tile(target, x, y)?;
+//!                 }
+//!             }
+//!             Ok::<(), GuiError>(())
+//!         })
+//!         .size(Size::new(200, 150))
+//!         .content_size(Size::new(16 * 64, 16 * 64))
+//!         .momentum(true),
+//!     );
+//! }
+//! ```
+
+use crate::smartstate::Smartstate;
+use crate::ui::{CustomTarget, Gesture, GuiResult, Interaction, Response, TypedWidget, Ui};
+use core::ops::RangeInclusive;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::Transform;
+use embedded_graphics::primitives::Rectangle;
+
+/// The position of a [PanCanvas], and what of it was painted
+///
+/// See [retained state](Smartstate#retained-state).
+#[derive(Clone, Debug)]
+pub struct PanState {
+    /// The content point at the viewport's top left corner
+    offset: Point,
+    /// The scale of the content coordinates
+    zoom: f32,
+    /// The offset the viewport was painted at, `None` if it has to be painted fully
+    painted: Option<Point>,
+    /// The area and style the viewport was painted with
+    key: Smartstate,
+    /// Where the pointer was last frame, while dragging
+    pointer: Option<Point>,
+    /// How far the view moved in the last frame, for the momentum
+    velocity: Point,
+}
+
+impl PanState {
+    /// Creates a state showing the top left corner of the content.
+    pub fn new() -> Self {
+        Self {
+            offset: Point::zero(),
+            zoom: 1.0,
+            painted: None,
+            key: Smartstate::empty(),
+            pointer: None,
+            velocity: Point::zero(),
+        }
+    }
+
+    /// Returns the content point at the viewport's top left corner.
+    pub fn offset(&self) -> Point {
+        self.offset
+    }
+
+    /// Pans the view so that `offset` is at the viewport's top left corner, e.g. to center the
+    /// current position. It stops the momentum.
+    pub fn set_offset(&mut self, offset: Point) {
+        self.offset = offset;
+        self.velocity = Point::zero();
+    }
+
+    /// Returns the zoom of the content, `1.0` unless the canvas is
+    /// [zoomable](PanCanvas::zoomable). The content coordinates (including the offset) are
+    /// scaled by it.
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    /// Sets the zoom of the content, keeping the offset. The whole viewport is painted in the
+    /// next frame.
+    pub fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom;
+        self.painted = None;
+    }
+
+    /// Makes the canvas paint the whole viewport in the next frame, e.g. after the content
+    /// changed.
+    pub fn invalidate(&mut self) {
+        self.painted = None;
+    }
+}
+
+impl Default for PanState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A draggable viewport onto large content, see the [module documentation](self).
+pub struct PanCanvas<'a, F> {
+    state: &'a mut PanState,
+    paint: F,
+    size: Option<Size>,
+    content_size: Option<Size>,
+    momentum: bool,
+    zoom_range: Option<RangeInclusive<f32>>,
+}
+
+impl<'a, F> PanCanvas<'a, F> {
+    /// Creates a viewport at the position of `state`, with `paint` painting the content.
+    ///
+    /// `paint` is called with a target in content coordinates, clipped to the part of the
+    /// viewport to paint, and that part's rectangle in content coordinates.
+    pub fn new(state: &'a mut PanState, paint: F) -> Self {
+        Self {
+            state,
+            paint,
+            size: None,
+            content_size: None,
+            momentum: false,
+            zoom_range: None,
+        }
+    }
+
+    /// Sets the size of the viewport. By default, it takes the remaining width of the row, and
+    /// is six times as high as the style's default widget height.
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Limits panning to content of `size` (at a zoom of `1.0`), starting at the origin. By
+    /// default, the content is unbounded.
+    pub fn content_size(mut self, size: Size) -> Self {
+        self.content_size = Some(size);
+        self
+    }
+
+    /// Sets whether the view keeps gliding (and slowing down) after a drag is released, off by
+    /// default. While it glides, the canvas asks for the next frame with
+    /// [Ui::request_repaint].
+    pub fn momentum(mut self, momentum: bool) -> Self {
+        self.momentum = momentum;
+        self
+    }
+
+    /// Lets a pinch zoom the content, within `range` (e.g. `0.5..=4.0`). By default, the
+    /// canvas doesn't zoom.
+    pub fn zoomable(mut self, range: RangeInclusive<f32>) -> Self {
+        self.zoom_range = Some(range);
+        self
+    }
+}
+
+impl<COL, F> TypedWidget<COL> for PanCanvas<'_, F>
+where
+    COL: PixelColor,
+    F: FnMut(&mut CustomTarget<'_, COL>, Rectangle) -> GuiResult<()>,
+{
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        // get size
+        let size = self.size.unwrap_or_else(|| {
+            Size::new(ui.remaining_width(), 6 * ui.style().default_widget_height)
+        });
+        let size = Size::new(size.width.max(1), size.height.max(1));
+
+        // allocate space
+        let iresponse = ui.allocate_space(size)?;
+        let area = iresponse.area;
+
+        // zoom around the center of a pinch
+        let state = &mut *self.state;
+        let previous = state.offset;
+        let pinch = self.zoom_range.as_ref().zip(ui.check_gesture(area));
+        let zoomed = if let Some((
+            range,
+            Gesture::Pinch {
+                center,
+                scale_delta,
+            },
+        )) = pinch
+        {
+            let zoom = (state.zoom * scale_delta).clamp(*range.start(), *range.end());
+            let anchor = center - area.top_left;
+            let scale = |value: i32| match value as f32 * zoom / state.zoom {
+                scaled if scaled < 0.0 => (scaled - 0.5) as i32,
+                scaled => (scaled + 0.5) as i32,
+            };
+            let content = state.offset + anchor;
+            state.offset = Point::new(scale(content.x), scale(content.y)) - anchor;
+            state.pointer = None;
+            state.velocity = Point::zero();
+            let zoomed = zoom != state.zoom;
+            state.zoom = zoom;
+            zoomed
+        } else {
+            false
+        };
+
+        // pan with the pointer, or glide on
+        match iresponse.interaction {
+            Interaction::Click(point) => {
+                state.pointer = Some(point);
+                state.velocity = Point::zero();
+            }
+            Interaction::Drag(point) | Interaction::Release(point) => {
+                if let Some(last) = state.pointer {
+                    state.velocity = point - last;
+                    state.offset -= state.velocity;
+                }
+                state.pointer = match iresponse.interaction {
+                    Interaction::Drag(_) => Some(point),
+                    _ => None,
+                };
+            }
+            _ => {
+                state.pointer = None;
+                if self.momentum && state.velocity != Point::zero() {
+                    state.offset -= state.velocity;
+                    // (rounded towards zero, so it stops)
+                    state.velocity = state.velocity * 3 / 4;
+                    ui.request_repaint();
+                }
+            }
+        }
+        if !self.momentum && state.pointer.is_none() {
+            state.velocity = Point::zero();
+        }
+        if let Some(content) = self.content_size {
+            let zoomed = |content: u32| (content as f32 * state.zoom) as u32;
+            let max = |content: u32, view: u32| zoomed(content).saturating_sub(view) as i32;
+            state.offset = Point::new(
+                state.offset.x.clamp(0, max(content.width, size.width)),
+                state.offset.y.clamp(0, max(content.height, size.height)),
+            );
+        }
+        let offset = state.offset;
+        if zoomed {
+            // painted in the next frame, when the closure knows the zoom
+            state.painted = None;
+            ui.request_repaint();
+            return Ok(Response::new(iresponse).set_changed(true).set_redraw(false));
+        }
+
+        // check what is painted
+        let mut key = Smartstate::empty();
+        key.set_state_hashed(&area);
+        key.mix(ui.style_key());
+        let painted = state
+            .painted
+            .filter(|_| state.key == key && !ui.invalidated(area));
+        state.key = key;
+        state.painted = Some(offset);
+        let response = Response::new(iresponse).set_changed(offset != previous);
+        if painted == Some(offset) {
+            return Ok(response.set_redraw(false));
+        }
+
+        // the parts of the viewport to paint: what the pixels didn't move to, or everything
+        let mut parts = [Some(area), None];
+        if let Some(painted) = painted {
+            let by = painted - offset;
+            let (width, height) = (size.width as i32, size.height as i32);
+            if by.x.abs() < width && by.y.abs() < height && ui.move_area(area, by) {
+                let part = |x: i32, y: i32, width: i32, height: i32| {
+                    (width > 0 && height > 0).then(|| {
+                        let size = Size::new(width as u32, height as u32);
+                        Rectangle::new(area.top_left + Point::new(x, y), size)
+                    })
+                };
+                // a column on the side moved away from, and the rest of the rows on the top or
+                // the bottom
+                let column_x = if by.x > 0 { 0 } else { width + by.x };
+                let rows_x = if by.x > 0 { by.x } else { 0 };
+                let rows_y = if by.y > 0 { 0 } else { height + by.y };
+                parts = [
+                    part(column_x, 0, by.x.abs(), height),
+                    part(rows_x, rows_y, width - by.x.abs(), by.y.abs()),
+                ];
+            }
+        }
+
+        // paint them, with the content's origin where it shows
+        let origin = area.top_left - offset;
+        for part in parts.into_iter().flatten() {
+            ui.start_drawing(&part);
+            if !ui.cleared() {
+                ui.clear_area(part)?;
+            }
+            let (mut target, _) = ui.custom_target(part);
+            (self.paint)(&mut target.with_origin(origin), part.translate(-origin))?;
+            ui.finalize()?;
+        }
+
+        Ok(response.set_redraw(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::*;
+    use embedded_graphics::Pixel;
+
+    /// The content: a color for every point
+    fn color(point: Point) -> Rgb565 {
+        Rgb565::new((point.x & 31) as u8, (point.y & 63) as u8, 0)
+    }
+
+    /// Moves pixels of the mock display
+    fn move_pixels(display: &mut MockDisplay<Rgb565>, area: Rectangle, by: Point) -> bool {
+        let before = display.clone();
+        for point in area.points().filter(|point| area.contains(*point - by)) {
+            display.set_pixel(point, before.get_pixel(point - by));
+        }
+        true
+    }
+
+    /// Draws a 40x30 canvas over 100x100 content, returning its area and the parts painted
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        state: &mut PanState,
+        interaction: Interaction,
+        mover: bool,
+    ) -> (Rectangle, heapless::Vec<Rectangle, 4>) {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        if mover {
+            ui.set_area_mover(move_pixels);
+        }
+        ui.interact(interaction);
+        let mut parts = heapless::Vec::new();
+        let canvas = PanCanvas::new(
+            state,
+            |target: &mut CustomTarget<Rgb565>, part: Rectangle| {
+                parts.push(part).unwrap();
+                target.draw_iter(part.points().map(|point| Pixel(point, color(point))))
+            },
+        );
+        let response = ui.add(
+            canvas
+                .size(Size::new(40, 30))
+                .content_size(Size::new_equal(100)),
+        );
+        (response.area(), parts)
+    }
+
+    #[test]
+    fn panning_paints_only_the_exposed_parts() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut state = PanState::new();
+        let shows = |display: &MockDisplay<Rgb565>, area: Rectangle, offset: Point| {
+            area.points().all(|point| {
+                display.get_pixel(point) == Some(color(point - area.top_left + offset))
+            })
+        };
+
+        let (area, parts) = frame(&mut display, &mut state, Interaction::None, true);
+        assert_eq!(parts, [Rectangle::new(Point::zero(), Size::new(40, 30))]);
+        assert!(shows(&display, area, Point::zero()));
+        let (_, parts) = frame(&mut display, &mut state, Interaction::None, true);
+        assert!(parts.is_empty());
+
+        // drag the content up and left, showing more on the right and bottom
+        let start = area.center();
+        frame(&mut display, &mut state, Interaction::Click(start), true);
+        let to = start - Point::new(3, 2);
+        let (_, parts) = frame(&mut display, &mut state, Interaction::Drag(to), true);
+        assert_eq!(state.offset(), Point::new(3, 2));
+        assert_eq!(
+            parts,
+            [
+                Rectangle::new(Point::new(40, 2), Size::new(3, 30)),
+                Rectangle::new(Point::new(3, 30), Size::new(37, 2)),
+            ]
+        );
+        assert!(shows(&display, area, Point::new(3, 2)));
+
+        // back down, beyond the content's top, without moving pixels
+        let (_, parts) = frame(
+            &mut display,
+            &mut state,
+            Interaction::Drag(to + Point::new(0, 9)),
+            false,
+        );
+        assert_eq!(state.offset(), Point::new(3, 0));
+        assert_eq!(parts, [Rectangle::new(Point::new(3, 0), Size::new(40, 30))]);
+        assert!(shows(&display, area, Point::new(3, 0)));
+    }
+
+    #[test]
+    fn a_pinch_zooms_around_its_center() {
+        use crate::ui::UiState;
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui_state = UiState::new(medsize_rgb565_style());
+        let mut view = PanState::new();
+
+        // returns the response and the parts painted
+        let mut frame = |view: &mut PanState, interaction, second: Option<Point>| {
+            let mut ui = ui_state.begin_frame(&mut display, interaction, 0);
+            ui.interact_second(second);
+            let mut parts = heapless::Vec::<Rectangle, 4>::new();
+            let response = ui.add(
+                PanCanvas::new(view, |_: &mut CustomTarget<Rgb565>, part: Rectangle| {
+                    parts.push(part).unwrap();
+                    Ok(())
+                })
+                .size(Size::new(40, 30))
+                .content_size(Size::new_equal(100))
+                .zoomable(0.5..=4.0),
+            );
+            ui_state.end_frame(ui);
+            (response, parts)
+        };
+
+        let (response, _) = frame(&mut view, Interaction::None, None);
+        let area = response.area();
+        let first = area.top_left + Point::new(10, 10);
+        frame(&mut view, Interaction::Click(first), None);
+
+        // the first pinch frame only starts it, and doesn't drag
+        let (response, parts) = frame(
+            &mut view,
+            Interaction::Drag(first),
+            Some(first + Point::new(6, 8)),
+        );
+        assert!(!response.changed() && parts.is_empty());
+
+        // twice the distance: the content under the center (16, 18) stays there
+        let (response, parts) = frame(
+            &mut view,
+            Interaction::Drag(first),
+            Some(first + Point::new(12, 16)),
+        );
+        assert!(response.changed() && parts.is_empty());
+        assert_eq!((view.zoom(), view.offset()), (2.0, Point::new(16, 18)));
+        let (_, parts) = frame(&mut view, Interaction::Drag(first), None);
+        assert_eq!(
+            parts,
+            [Rectangle::new(Point::new(16, 18), Size::new(40, 30))]
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! # Prelude
+//!
+//! The types used by most UIs, imported with `use kolibri_embedded_gui::prelude::*;`: the [Ui]
+//! and its results, the built-in widgets of the enabled features, smartstates, styles with the
+//! bundled themes, and with the `icon-widgets` feature, the embedded-iconoir icons (the `icons`
+//! module and the traits to create them).
+//!
+//! Less common types, e.g. [HorizontalAlign](crate::ui::HorizontalAlign) or retained state like
+//! a [ScrollState](crate::ui::ScrollState), are imported from their modules.
+//! Writing a widget needs more, see the [widget_prelude](crate::widget_prelude).
+//!
+//! The prelude doesn't export any names of the [embedded_graphics] prelude, so both can be
+//! imported with globs, also in `no_std` crates:
+//!
+//! ```
+//! #![no_std]
+//! # extern crate std;
+//! use embedded_graphics::mock_display::MockDisplay;
+//! use embedded_graphics::pixelcolor::Rgb565;
+//! use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::prelude::*;
+//!
+//! fn frame(display: &mut MockDisplay<Rgb565>, smartstates: &mut SmartstateProvider<4>) {
+//!     let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+//!     ui.add(Label::new("Kolibri").smartstate(smartstates.nxt()));
+//!     let _: GuiResult<Response> = Ok(ui.add(Button::new("Ok")));
+//!
+//!     // with the `icon-widgets` feature
+//!     # #[cfg(feature = "icon-widgets")]
+//!     ui.add(IconButton::new(icons::size24px::actions::Check).smartstate(smartstates.nxt()));
+//! }
+//!
+//! # fn main() {
+//! let mut display = MockDisplay::new();
+//! display.set_allow_overdraw(true);
+//! display.set_allow_out_of_bounds_drawing(true);
+//! frame(&mut display, &mut SmartstateProvider::new());
+//! # let _: Size = Size::zero();
+//! # }
+//! ```
+
+#[cfg(feature = "instruments")]
+pub use crate::attitude::AttitudeIndicator;
+pub use crate::button::Button;
+#[cfg(feature = "checkbox")]
+pub use crate::checkbox::Checkbox;
+#[cfg(feature = "instruments")]
+pub use crate::compass::Compass;
+#[cfg(feature = "controls")]
+pub use crate::dropdown::Dropdown;
+#[cfg(feature = "charts")]
+pub use crate::heatmap::Heatmap;
+pub use crate::hidden::Hidden;
+#[cfg(feature = "charts")]
+pub use crate::histogram::Histogram;
+#[cfg(feature = "icon-widgets")]
+pub use crate::icon::IconWidget;
+#[cfg(feature = "icon-widgets")]
+pub use crate::iconbutton::IconButton;
+pub use crate::img::Img;
+pub use crate::label::{HashLabel, Label};
+pub use crate::pan_canvas::PanCanvas;
+#[cfg(feature = "icon-widgets")]
+pub use crate::reorderable_list::ReorderableList;
+#[cfg(feature = "indicators")]
+pub use crate::rich_label::RichLabel;
+#[cfg(feature = "charts")]
+pub use crate::scope::Scope;
+#[cfg(feature = "controls")]
+pub use crate::segmented_control::SegmentedControl;
+#[cfg(feature = "indicators")]
+pub use crate::segmented_progress::SegmentedProgress;
+#[cfg(feature = "indicators")]
+pub use crate::skeleton::Skeleton;
+#[cfg(feature = "controls")]
+pub use crate::slider::Slider;
+pub use crate::smartstate::{Smartstate, SmartstateProvider};
+pub use crate::spacer::Spacer;
+#[cfg(feature = "indicators")]
+pub use crate::spinner::Spinner;
+pub use crate::style::{
+    medsize_blue_rgb565_style, medsize_crt_rgb565_style, medsize_light_rgb565_style,
+    medsize_retro_rgb565_style, medsize_rgb565_debug_style, medsize_rgb565_style,
+    medsize_rgb888_base_style, medsize_sakura_rgb565_style, Fill, Spacing, Style, WidgetContext,
+};
+#[cfg(feature = "embedded-text")]
+pub use crate::text_box::TextBox;
+#[cfg(feature = "controls")]
+pub use crate::toggle_button::ToggleButton;
+#[cfg(feature = "controls")]
+pub use crate::toggle_switch::ToggleSwitch;
+pub use crate::ui::{GuiError, GuiResult, Interaction, Response, Ui, Widget};
+#[cfg(feature = "indicators")]
+pub use crate::value_label::ValueLabel;
+#[cfg(feature = "icon-widgets")]
+pub use embedded_iconoir::icons;
+#[cfg(feature = "icon-widgets")]
+pub use embedded_iconoir::prelude::*;
@@ -0,0 +1,737 @@
+//! # Reorderable List
+//!
+//! A [ReorderableList] shows a column of rows (e.g. the steps of a program, or the stations of
+//! a playlist) that can be reordered by drag and drop: a long press on a row lifts it, the
+//! lifted row follows the pointer vertically, and the other rows shift to show the gap it will
+//! be dropped into. Releasing the pointer drops it there, dragging it out of the list cancels.
+//!
+//! This module is only available with the `icon-widgets` feature (on by default).
+//!
+//! The list doesn't reorder the items itself: on release, the move is available from
+//! [ReorderState::take_move] as `(from, to)`, the indices before and after the move, and the
+//! [Response] is marked as changed.
+//!
+//! The long press needs the frame time (see [Ui::begin_frame]). Without it, rows can't be
+//! lifted.
+//!
+//! ## Swipe to Dismiss
+//!
+//! With [ReorderableList::swipe_to_dismiss], rows can also be swiped sideways: a row dragged
+//! mostly horizontally (past [Spacing::press_slop](crate::style::Spacing::press_slop)) follows
+//! the pointer, uncovering an action area with a trash icon. Releasing it past half of its
+//! width dismisses the row, available from [ReorderState::take_dismissed]. Releasing it
+//! earlier slides it back. Mostly vertical drags are left alone, e.g. for scrolling.
+//!
+//! The horizontal offset of each row is an [Animated] value owned by the caller, which also
+//! sets how long sliding back takes. Remove the dismissed item (and keep its offset at the
+//! same index as the item) before the next frame.
+//!
+//! ## Smartstates
+//!
+//! Each row slot has its own smartstate (see [ReorderableList::smartstates]), so while a row is
+//! dragged, only the slots it passes over and the ones around the gap are redrawn.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::reorderable_list::ReorderState;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let now_ms = || 0;
+//! let mut steps = ["Heat", "Soak", "Reflow", "Cool"];
+//! let mut state = ReorderState::new();
+//! let mut smartstates = [Smartstate::empty(); 4];
+//!
+//! loop {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     ui.begin_frame(now_ms());
+//!     ui.add(ReorderableList::new(&steps, &mut state).smartstates(&mut smartstates));
+//!     if let Some((from, to)) = state.take_move() {
+//!         let step = steps[from];
+//!         if from < to {
+//!             steps.copy_within(from + 1..=to, from);
+//!         } else {
+//!             steps.copy_within(to..from, to + 1);
+//!         }
+//!         steps[to] = step;
+//!     }
+//! }
+//! ```
+
+use crate::anim::Animated;
+use crate::icon::GuiIcon;
+use crate::smartstate::Smartstate;
+use crate::style::Style;
+use crate::text::{truncate, Font, RenderedText, TextRenderer};
+use crate::ui::{
+    resolve_widget_style, CustomTarget, GuiResult, Interaction, Response, TypedWidget, Ui,
+};
+use core::cmp::max;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::image::Image;
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888};
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_iconoir::size16px::actions::Trash;
+
+/// The default color of the dismiss action area, a warning red
+const DISMISS_COLOR: Rgb888 = Rgb888::new(0xd3, 0x2f, 0x2f);
+
+/// The drag and drop state of a [ReorderableList]
+///
+/// See [retained state](Smartstate#retained-state).
+#[derive(Clone, Debug, Default)]
+pub struct ReorderState {
+    /// The pressed row, until it is lifted
+    press: Option<Press>,
+    /// The lifted row, while it is dragged
+    lifted: Option<Lifted>,
+    /// The swiped row, while it is dragged sideways
+    swipe: Option<Swipe>,
+    /// The last move, until it is taken
+    moved: Option<(usize, usize)>,
+    /// The last dismissed row, until it is taken
+    dismissed: Option<usize>,
+}
+
+/// A press on a row, which lifts it when held long enough
+#[derive(Clone, Copy, Debug)]
+struct Press {
+    row: usize,
+    /// The frame time of the click, if known
+    since: Option<u64>,
+    origin: Point,
+}
+
+/// A lifted row following the pointer
+#[derive(Clone, Copy, Debug)]
+struct Lifted {
+    /// The row's index before the move
+    from: usize,
+    /// The pointer's offset from the row's top edge
+    grab: i32,
+    /// The pointer's vertical position
+    y: i32,
+}
+
+/// A row dragged sideways
+#[derive(Clone, Copy, Debug)]
+struct Swipe {
+    row: usize,
+    /// Where the press started
+    origin: Point,
+}
+
+impl ReorderState {
+    /// Creates the state of a list without a lifted row.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the index of the lifted row, if a row is being dragged.
+    pub fn lifted(&self) -> Option<usize> {
+        self.lifted.map(|lifted| lifted.from)
+    }
+
+    /// Takes the last move of a row, from the index `from` to the index `to` (both within the
+    /// items before the move). Moves to the same index aren't reported.
+    ///
+    /// ## Returns
+    ///
+    /// `Some((from, to))` once after a row was dropped at a new index, `None` otherwise.
+    pub fn take_move(&mut self) -> Option<(usize, usize)> {
+        self.moved.take()
+    }
+
+    /// Takes the index of the last row swiped away (see
+    /// [ReorderableList::swipe_to_dismiss]).
+    ///
+    /// ## Returns
+    ///
+    /// `Some(index)` once after a row was dismissed, `None` otherwise.
+    pub fn take_dismissed(&mut self) -> Option<usize> {
+        self.dismissed.take()
+    }
+
+    /// Drops the lifted row back at its place, e.g. when the items change while dragging. A
+    /// swiped row slides back in the next frame.
+    pub fn cancel(&mut self) {
+        self.press = None;
+        self.lifted = None;
+        self.swipe = None;
+    }
+}
+
+/// A column of rows to reorder by drag and drop, see the [module documentation](self).
+pub struct ReorderableList<'a> {
+    items: &'a [&'a str],
+    state: &'a mut ReorderState,
+    width: Option<u32>,
+    long_press_ms: u64,
+    offsets: &'a mut [Animated<i32>],
+    dismiss_color: Rgb888,
+    smartstates: &'a mut [Smartstate],
+}
+
+impl<'a> ReorderableList<'a> {
+    /// Creates a list of `items`, with its drag and drop state in `state`.
+    pub fn new(items: &'a [&'a str], state: &'a mut ReorderState) -> Self {
+        Self {
+            items,
+            state,
+            width: None,
+            long_press_ms: 500,
+            offsets: &mut [],
+            dismiss_color: DISMISS_COLOR,
+            smartstates: &mut [],
+        }
+    }
+
+    /// Sets the width of the list, by default as wide as the widest item.
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets how long a row has to be pressed to lift it, 500 ms by default.
+    pub fn long_press_ms(mut self, long_press_ms: u64) -> Self {
+        self.long_press_ms = long_press_ms;
+        self
+    }
+
+    /// Lets rows be swiped away sideways, with their horizontal offsets in `offsets`, one per
+    /// item (see the [module documentation](self#swipe-to-dismiss)).
+    ///
+    /// Rows without an offset (if there are fewer offsets than items) can't be swiped.
+    pub fn swipe_to_dismiss(mut self, offsets: &'a mut [Animated<i32>]) -> Self {
+        self.offsets = offsets;
+        self
+    }
+
+    /// Sets the color of the action area uncovered by swiping a row, red by default.
+    pub fn dismiss_color(mut self, color: Rgb888) -> Self {
+        self.dismiss_color = color;
+        self
+    }
+
+    /// Adds a smartstate per row slot, from top to bottom.
+    ///
+    /// Slots without a smartstate (if there are fewer smartstates than items) are redrawn every
+    /// frame.
+    pub fn smartstates(mut self, smartstates: &'a mut [Smartstate]) -> Self {
+        self.smartstates = smartstates;
+        self
+    }
+}
+
+/// What a row slot shows
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Slot {
+    Item(usize),
+    Gap,
+}
+
+/// What changed with the interaction of a frame
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Change {
+    None,
+    Moved,
+    Dismissed(usize),
+}
+
+impl ReorderableList<'_> {
+    /// Updates the press, the lifted row and the swiped row with the interaction anywhere on
+    /// the screen
+    fn update<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+        area: Rectangle,
+        row_height: u32,
+    ) -> Change {
+        let row_at = |y: i32| ((y - area.top_left.y) / row_height as i32) as usize;
+        let slop = ui.style().spacing.press_slop.min(i32::MAX as u32) as i32;
+        let width = area.size.width as i32;
+        let interaction = ui.check_interact(ui.overlay_bounds());
+        let point = interaction.get_point().unwrap_or_default();
+        let state = &mut *self.state;
+
+        if let Some(swipe) = state.swipe {
+            let Some(offset) = self.offsets.get_mut(swipe.row) else {
+                state.swipe = None;
+                return Change::None;
+            };
+            return match interaction {
+                Interaction::Drag(_) => {
+                    offset.set((point.x - swipe.origin.x).clamp(-width, width));
+                    Change::None
+                }
+                Interaction::Release(_) if (point.x - swipe.origin.x).abs() >= width / 2 => {
+                    state.swipe = None;
+                    state.dismissed = Some(swipe.row);
+                    offset.set(0);
+                    Change::Dismissed(swipe.row)
+                }
+                // released early, or lost track of the pointer
+                _ => {
+                    state.swipe = None;
+                    offset.set_target(0);
+                    Change::None
+                }
+            };
+        }
+
+        let Some(mut lifted) = state.lifted else {
+            state.press = match (interaction, state.press) {
+                (Interaction::Click(_), _) if area.contains(point) => Some(Press {
+                    row: row_at(point.y),
+                    since: ui.now(),
+                    origin: point,
+                }),
+                // a mostly sideways drag swipes, a mostly vertical one is left alone
+                (Interaction::Drag(_), Some(press))
+                    if (point - press.origin).x.abs() > slop
+                        || (point - press.origin).y.abs() > slop =>
+                {
+                    let moved = point - press.origin;
+                    let offset = self.offsets.get_mut(press.row);
+                    if let Some(offset) = offset.filter(|_| moved.x.abs() > moved.y.abs()) {
+                        offset.set(moved.x.clamp(-width, width));
+                        state.swipe = Some(Swipe {
+                            row: press.row,
+                            origin: press.origin,
+                        });
+                    }
+                    None
+                }
+                // held in place, until long enough to lift the row
+                (Interaction::Drag(_), Some(press)) => match (press.since, ui.now()) {
+                    (Some(since), Some(now)) if now.saturating_sub(since) >= self.long_press_ms => {
+                        let top = area.top_left.y + press.row as i32 * row_height as i32;
+                        state.lifted = Some(Lifted {
+                            from: press.row,
+                            grab: press.origin.y - top,
+                            y: point.y,
+                        });
+                        None
+                    }
+                    _ => Some(press),
+                },
+                _ => None,
+            };
+            if state.press.is_some_and(|press| press.since.is_some()) {
+                // the press is timed without new input
+                ui.request_repaint();
+            }
+            return Change::None;
+        };
+
+        match interaction {
+            Interaction::Drag(_) if area.contains(point) => {
+                lifted.y = point.y;
+                state.lifted = Some(lifted);
+                Change::None
+            }
+            Interaction::Release(_) if area.contains(point) => {
+                let to = drop_index(area, row_height, self.items.len(), lifted);
+                state.lifted = None;
+                state.moved = (to != lifted.from).then_some((lifted.from, to));
+                match state.moved {
+                    Some(_) => Change::Moved,
+                    None => Change::None,
+                }
+            }
+            // dragged out of the list, or lost track of the pointer
+            _ => {
+                state.lifted = None;
+                Change::None
+            }
+        }
+    }
+}
+
+/// Returns the top edge of the lifted row, following the pointer within the list
+fn lifted_top(area: Rectangle, row_height: u32, lifted: Lifted) -> i32 {
+    let lowest = area.top_left.y + area.size.height as i32 - row_height as i32;
+    (lifted.y - lifted.grab).clamp(area.top_left.y, lowest.max(area.top_left.y))
+}
+
+/// Returns the index the lifted row would be dropped at: the slot its center is over
+fn drop_index(area: Rectangle, row_height: u32, count: usize, lifted: Lifted) -> usize {
+    let center = lifted_top(area, row_height, lifted) - area.top_left.y + row_height as i32 / 2;
+    ((center / row_height as i32) as usize).min(count.saturating_sub(1))
+}
+
+impl<COL: PixelColor + From<Rgb888>> TypedWidget<COL> for ReorderableList<'_> {
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let font = Font::resolve(None, ui.style());
+        let padding = ui.style().spacing.button_padding;
+        let border = ui.style().border_width;
+        let count = self.items.len();
+
+        // all rows as high as a button, the list as wide as the widest item
+        let inset = padding.width + border;
+        let width = self.width.unwrap_or_else(|| {
+            let widest = self.items.iter().map(|item| font.measure(item).width);
+            widest.max().unwrap_or(0) + 2 * inset
+        });
+        let row_height = max(
+            font.line_height() + 2 * (padding.height + border),
+            ui.style().default_widget_height,
+        );
+
+        let iresponse = ui.allocate_space(Size::new(width, count as u32 * row_height))?;
+        let area = iresponse.area;
+        let row = |i: usize| {
+            let top = area.top_left.y + (i as u32 * row_height) as i32;
+            Rectangle::new(
+                Point::new(area.top_left.x, top),
+                Size::new(width, row_height),
+            )
+        };
+
+        if self.state.lifted.is_some_and(|lifted| lifted.from >= count) {
+            self.state.cancel();
+        }
+        let change = self.update(ui, area, row_height);
+        let lifted = self.state.lifted;
+        let swiping = self.state.swipe.is_some();
+
+        // rows released early slide back
+        for offset in self.offsets.iter_mut() {
+            offset.update(ui);
+            if offset.animating() {
+                ui.request_repaint();
+            }
+        }
+        let pressed = self.state.press.map(|press| press.row);
+
+        // the slots, with the gap at the drop index
+        let gap = lifted.map(|lifted| (lifted.from, drop_index(area, row_height, count, lifted)));
+        let slot = |i: usize| match gap {
+            Some((_, to)) if i == to => Slot::Gap,
+            // the other items, without the lifted one
+            Some((from, to)) => {
+                let shown = if i < to { i } else { i - 1 };
+                Slot::Item(if shown < from { shown } else { shown + 1 })
+            }
+            None => Slot::Item(i),
+        };
+        let lifted_rect = lifted.map(|lifted| {
+            let top = lifted_top(area, row_height, lifted);
+            Rectangle::new(Point::new(area.top_left.x, top), row(0).size)
+        });
+
+        let style_key = ui.style_key();
+        let mut lifted_covered = false;
+        for i in 0..count {
+            let rect = row(i);
+            let slot = slot(i);
+            // the part of the slot under the lifted row, which is drawn over it
+            let covered = lifted_rect
+                .map(|lifted| lifted.intersection(&rect))
+                .filter(|covered| !covered.is_zero_sized());
+            let interaction = match pressed == Some(i) {
+                true => Interaction::Click(rect.center()),
+                false => Interaction::None,
+            };
+            let (rect_style, bucket) = resolve_widget_style(ui.style(), interaction);
+            let (item, offset) = match slot {
+                // the dismissed row stays swiped away until it is removed
+                Slot::Item(item) if change == Change::Dismissed(item) => continue,
+                Slot::Item(item) => (
+                    self.items.get(item).copied(),
+                    self.offsets.get(item).map_or(0, |offset| offset.value()),
+                ),
+                Slot::Gap => (None, 0),
+            };
+
+            // check the slot's smartstate
+            let smartstate = self.smartstates.get_mut(i);
+            let prevstate = smartstate.as_ref().map(|st| **st);
+            let mut state = Smartstate::empty();
+            state.set_state_hashed(&(slot, item, offset, bucket, covered, rect.size));
+            state.mix(style_key);
+            if let Some(smartstate) = smartstate {
+                *smartstate = state;
+            }
+            if prevstate == Some(state) && !ui.invalidated(rect) {
+                continue;
+            }
+
+            lifted_covered |= covered.is_some();
+            ui.start_drawing(&rect);
+            match item {
+                Some(item) => {
+                    let row = Row {
+                        rect,
+                        offset,
+                        style: rect_style,
+                        inset,
+                        dismiss_color: self.dismiss_color.into(),
+                    };
+                    let (mut target, style) = ui.custom_target(rect);
+                    row.draw(&mut target, style, &font, item)?;
+                }
+                None if !ui.cleared() => ui.clear_area(rect)?,
+                None => {}
+            }
+            ui.finalize()?;
+        }
+
+        // the lifted row, over the slots below it
+        if let Some(rect) = lifted_rect.filter(|_| lifted_covered) {
+            let item = lifted.and_then(|lifted| self.items.get(lifted.from));
+            let (rect_style, _) =
+                resolve_widget_style(ui.style(), Interaction::Drag(rect.center()));
+            let row = Row {
+                rect,
+                offset: 0,
+                style: rect_style,
+                inset,
+                dismiss_color: self.dismiss_color.into(),
+            };
+            ui.start_drawing(&rect);
+            let (mut target, style) = ui.custom_target(rect);
+            row.draw(&mut target, style, &font, item.unwrap_or(&""))?;
+            ui.finalize()?;
+        }
+
+        Ok(Response::new(iresponse)
+            .set_changed(change != Change::None)
+            .set_down(lifted.is_some() || pressed.is_some() || swiping))
+    }
+}
+
+/// How a row is drawn
+struct Row<COL: PixelColor> {
+    rect: Rectangle,
+    /// How far the row is swiped sideways
+    offset: i32,
+    style: PrimitiveStyle<COL>,
+    inset: u32,
+    dismiss_color: COL,
+}
+
+impl<COL: PixelColor + From<Rgb888>> Row<COL> {
+    /// Draws the row with its item, left-aligned and cut off at the row's end, and the dismiss
+    /// action area it uncovers when swiped
+    fn draw(
+        &self,
+        target: &mut CustomTarget<COL>,
+        style: &Style<COL>,
+        font: &Font,
+        item: &str,
+    ) -> GuiResult<()> {
+        let rect = self.rect;
+        if self.offset != 0 {
+            let width = self.offset.unsigned_abs().min(rect.size.width);
+            let left = match self.offset > 0 {
+                true => 0,
+                false => (rect.size.width - width) as i32,
+            };
+            let action = Rectangle::new(
+                rect.top_left + Point::new(left, 0),
+                Size::new(width, rect.size.height),
+            );
+            action
+                .into_styled(PrimitiveStyle::with_fill(self.dismiss_color))
+                .draw(target)?;
+
+            // the icon, once there's room for it
+            let icon = Trash::bounding_box().size;
+            if width >= icon.width + 2 * self.inset {
+                let icon_top_left =
+                    action.center() - Point::new(icon.width as i32 / 2, icon.height as i32 / 2);
+                let icon = Trash::with_color(COL::from(Rgb888::WHITE));
+                Image::new(&icon, icon_top_left).draw(target)?;
+            }
+        }
+
+        // the row, slid sideways and cut off by the target
+        let body = rect.translate(Point::new(self.offset, 0));
+        body.into_styled(self.style).draw(target)?;
+        let text = truncate(font, item, rect.size.width.saturating_sub(2 * self.inset));
+        let text_height = font.measure(text).height;
+        let text_top_left = body.top_left
+            + Point::new(
+                self.inset as i32,
+                (rect.size.height.saturating_sub(text_height) / 2) as i32,
+            );
+        RenderedText::new(font, text, text_top_left, style.text_color).draw(target)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    const ITEMS: [&str; 3] = ["A", "B", "C"];
+
+    /// Draws a frame at `now`, returning the list's area and the number of rows redrawn
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        state: &mut ReorderState,
+        smartstates: &mut [Smartstate],
+        offsets: &mut [Animated<i32>],
+        now: u64,
+        interaction: Interaction,
+    ) -> (Rectangle, u32) {
+        // with rows of 17 pixels, to fit the 64x64 display
+        let mut style = medsize_rgb565_style();
+        style.spacing.button_padding = Size::new(2, 1);
+        let mut ui = Ui::new_fullscreen(display, style);
+        ui.begin_frame(now);
+        ui.interact(interaction);
+        let response = ui.add(
+            ReorderableList::new(&ITEMS, state)
+                .width(50)
+                .swipe_to_dismiss(offsets)
+                .smartstates(smartstates),
+        );
+        (response.area(), ui.perf().widgets_redrawn)
+    }
+
+    #[test]
+    fn a_long_pressed_row_is_dragged_to_the_gap() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut state = ReorderState::new();
+        let mut smartstates = [Smartstate::empty(); 3];
+        let mut frame = |state: &mut ReorderState, now, interaction| {
+            frame(
+                &mut display,
+                state,
+                &mut smartstates,
+                &mut [],
+                now,
+                interaction,
+            )
+        };
+
+        let (area, redrawn) = frame(&mut state, 0, Interaction::None);
+        assert_eq!(redrawn, 3);
+        let height = area.size.height as i32 / 3;
+        let first = area.top_left + Point::new(10, height / 2);
+
+        // pressing shows the pressed row, but only lifts it after a while
+        frame(&mut state, 0, Interaction::Click(first));
+        frame(&mut state, 300, Interaction::Drag(first));
+        assert_eq!(state.lifted(), None);
+        let (_, redrawn) = frame(&mut state, 600, Interaction::Drag(first));
+        assert_eq!(state.lifted(), Some(0));
+        // the gap in the first slot, and the lifted row over it
+        assert_eq!(redrawn, 2);
+
+        // halfway down to the second slot: the gap moves, the third slot stays
+        let down = first + Point::new(0, height * 3 / 4);
+        let (_, redrawn) = frame(&mut state, 620, Interaction::Drag(down));
+        assert_eq!(redrawn, 3);
+        let (_, redrawn) = frame(&mut state, 640, Interaction::Drag(down));
+        assert_eq!(redrawn, 0);
+
+        // dropped into the last slot
+        let last = first + Point::new(0, height * 2);
+        frame(&mut state, 660, Interaction::Drag(last));
+        frame(&mut state, 680, Interaction::Release(last));
+        assert_eq!(state.lifted(), None);
+        assert_eq!(state.take_move(), Some((0, 2)));
+        assert_eq!(state.take_move(), None);
+
+        // dragging out of the list cancels
+        frame(&mut state, 700, Interaction::Click(last));
+        frame(&mut state, 1300, Interaction::Drag(last));
+        assert_eq!(state.lifted(), Some(2));
+        let outside = Point::new(last.x, area.top_left.y + area.size.height as i32 + 2);
+        frame(&mut state, 1320, Interaction::Drag(outside));
+        frame(&mut state, 1340, Interaction::Release(outside));
+        assert_eq!(state.lifted(), None);
+        assert_eq!(state.take_move(), None);
+    }
+
+    #[test]
+    fn a_row_swiped_past_half_its_width_is_dismissed() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut state = ReorderState::new();
+        let mut smartstates = [Smartstate::empty(); 3];
+        let mut offsets = [Animated::new(0, 100); 3];
+        let red = Rgb565::from(DISMISS_COLOR);
+
+        let (area, _) = frame(
+            &mut display,
+            &mut state,
+            &mut smartstates,
+            &mut offsets,
+            0,
+            Interaction::None,
+        );
+        let second = area.top_left + Point::new(10, area.size.height as i32 / 2);
+
+        // a mostly vertical drag doesn't swipe
+        let mut frame = |state: &mut ReorderState, offsets: &mut [Animated<i32>], interaction| {
+            frame(
+                &mut display,
+                state,
+                &mut smartstates,
+                offsets,
+                0,
+                interaction,
+            );
+            display.get_pixel(area.top_left + Point::new(1, area.size.height as i32 / 2))
+        };
+        frame(&mut state, &mut offsets, Interaction::Click(second));
+        frame(
+            &mut state,
+            &mut offsets,
+            Interaction::Drag(second + Point::new(4, 8)),
+        );
+        frame(
+            &mut state,
+            &mut offsets,
+            Interaction::Drag(second + Point::new(20, 8)),
+        );
+        assert_eq!(offsets[1].value(), 0);
+
+        // a sideways one uncovers the action area, and slides back when released early
+        frame(&mut state, &mut offsets, Interaction::Click(second));
+        let pixel = frame(
+            &mut state,
+            &mut offsets,
+            Interaction::Drag(second + Point::new(10, 1)),
+        );
+        assert_eq!((offsets[1].value(), pixel), (10, Some(red)));
+        frame(
+            &mut state,
+            &mut offsets,
+            Interaction::Release(second + Point::new(10, 1)),
+        );
+        let pixel = frame(&mut state, &mut offsets, Interaction::None);
+        assert_eq!(offsets[1].value(), 0);
+        assert_ne!(pixel, Some(red));
+        assert_eq!(state.take_dismissed(), None);
+
+        // past half the width, it is dismissed
+        frame(&mut state, &mut offsets, Interaction::Click(second));
+        frame(
+            &mut state,
+            &mut offsets,
+            Interaction::Drag(second + Point::new(30, 0)),
+        );
+        frame(
+            &mut state,
+            &mut offsets,
+            Interaction::Release(second + Point::new(30, 0)),
+        );
+        assert_eq!(state.take_dismissed(), Some(1));
+        assert_eq!(state.take_dismissed(), None);
+    }
+}
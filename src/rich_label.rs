@@ -0,0 +1,250 @@
+//! # Rich Label
+//!
+//! A [RichLabel] shows a line of text made of spans with their own colors, e.g. a status
+//! line like "CPU: OK  NET: DOWN" with "OK" in green and "DOWN" in red. A span can also be
+//! [inverted](Emphasis::Inverted), i.e. drawn on a background of its color.
+//!
+//! This module is only available with the `indicators` feature (on by default).
+//!
+//! The spans are laid out one after the other with the style's font. Spans without a color use
+//! the style's text color. The spans are kept in a fixed-size array of 8 spans, further spans are
+//! ignored. For more (or fewer) spans, create the label with e.g.
+//! `RichLabel::<_, 12>::default()`.
+//!
+//! As the spans have colors of their own, a rich label only works in a [Ui] of the same color
+//! type (see [TypedWidget]).
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! # let network_up = false;
+//! let (net, net_color) = match network_up {
+//!     true => ("UP", Rgb565::GREEN),
+//!     false => ("DOWN", Rgb565::RED),
+//! };
+//! ui.add(
+//!     RichLabel::new()
+//!         .span("CPU: ")
+//!         .colored("OK", Rgb565::GREEN)
+//!         .span("  NET: ")
+//!         .colored(net, net_color)
+//!         .smartstate(smartstates.nxt()),
+//! );
+//! ```
+
+use crate::smartstate::{Container, Smartstate};
+use crate::text::{Font, RenderedText, TextRenderer};
+use crate::ui::{GuiError, GuiResult, Response, TypedWidget, Ui};
+use core::hash::Hash;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, Rectangle};
+
+/// How a [Span] is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Emphasis {
+    /// The text in the span's color
+    #[default]
+    Normal,
+    /// The text in the background color, on a box of the span's color
+    Inverted,
+}
+
+/// A part of a [RichLabel]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span<'a, COL> {
+    /// The text of the span
+    pub text: &'a str,
+    /// The color of the span, the style's text color if `None`
+    pub color: Option<COL>,
+    /// How the span is drawn
+    pub emphasis: Emphasis,
+}
+
+/// A line of text with differently colored spans, see the [module documentation](self).
+pub struct RichLabel<'a, COL, const N: usize = 8> {
+    spans: heapless::Vec<Span<'a, COL>, N>,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<COL, const N: usize> Default for RichLabel<'_, COL, N> {
+    fn default() -> Self {
+        Self {
+            spans: heapless::Vec::new(),
+            smartstate: Container::empty(),
+        }
+    }
+}
+
+impl<COL> RichLabel<'_, COL> {
+    /// Creates a rich label without any spans, with room for 8 spans.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a, COL, const N: usize> RichLabel<'a, COL, N> {
+    /// Adds `span`. If the label already has `N` spans, it is ignored.
+    pub fn with_span(mut self, span: Span<'a, COL>) -> Self {
+        self.spans.push(span).ok();
+        self
+    }
+
+    /// Adds a span in the style's text color.
+    pub fn span(self, text: &'a str) -> Self {
+        self.with_span(Span {
+            text,
+            color: None,
+            emphasis: Emphasis::Normal,
+        })
+    }
+
+    /// Adds a span in `color`.
+    pub fn colored(self, text: &'a str, color: COL) -> Self {
+        self.with_span(Span {
+            text,
+            color: Some(color),
+            emphasis: Emphasis::Normal,
+        })
+    }
+
+    /// Adds an [inverted](Emphasis::Inverted) span, on a box of `color` (or of the style's
+    /// text color, if `None`).
+    pub fn inverted(self, text: &'a str, color: Option<COL>) -> Self {
+        self.with_span(Span {
+            text,
+            color,
+            emphasis: Emphasis::Inverted,
+        })
+    }
+
+    /// Adds a [Smartstate] for incremental redrawing. The label redraws whenever a span
+    /// changes (its text, color or emphasis).
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+impl<COL: PixelColor + Hash, const N: usize> TypedWidget<COL> for RichLabel<'_, COL, N> {
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let font = Font::resolve(None, ui.style());
+        let height = font.line_height();
+        let width = self
+            .spans
+            .iter()
+            .map(|span| font.measure(span.text).width)
+            .sum();
+
+        let iresponse = ui.allocate_space(Size::new(width, height))?;
+        let area = iresponse.area;
+
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(self.spans.as_slice());
+        state.mix(ui.style_key());
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(area);
+        self.smartstate.modify(|st| *st = state);
+
+        if redraw {
+            ui.start_drawing(&area);
+            if !ui.cleared() {
+                ui.clear_area(area)?;
+            }
+
+            // centered vertically, in case more space was allocated
+            let top = area.top_left.y + (area.size.height.saturating_sub(height) / 2) as i32;
+            let mut x = area.top_left.x;
+            for span in &self.spans {
+                let width = font.measure(span.text).width;
+                let color = span.color.unwrap_or(ui.style().text_color);
+                let text_color = match span.emphasis {
+                    Emphasis::Normal => color,
+                    Emphasis::Inverted => {
+                        let highlight =
+                            Rectangle::new(Point::new(x, top), Size::new(width, height));
+                        ui.draw(&highlight.into_styled(PrimitiveStyle::with_fill(color)))
+                            .map_err(|_| GuiError::DrawError(Some("Couldn't draw RichLabel")))?;
+                        ui.style().background_color
+                    }
+                };
+                ui.draw(&RenderedText::new(
+                    &font,
+                    span.text,
+                    Point::new(x, top),
+                    text_color,
+                ))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw RichLabel text")))?;
+                x += width as i32;
+            }
+            ui.finalize()?;
+        }
+
+        Ok(Response::new(iresponse))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
+    use embedded_graphics::prelude::PointsIter;
+
+    /// Draws a status line, returning its area and whether it was redrawn
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        smartstate: &mut Smartstate,
+        status: Rgb565,
+    ) -> (Rectangle, bool) {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        let response = ui.add(
+            RichLabel::<_, 4>::default()
+                .span("A:")
+                .colored("ok", status)
+                .inverted("!", None)
+                .smartstate(smartstate),
+        );
+        (response.area(), ui.perf().widgets_redrawn == 1)
+    }
+
+    #[test]
+    fn spans_are_drawn_in_their_colors() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+
+        let (area, redrawn) = frame(&mut display, &mut smartstate, Rgb565::GREEN);
+        assert!(redrawn);
+        // 9px per character
+        assert_eq!(area.size.width, 5 * 9);
+        let style = medsize_rgb565_style();
+        let colors_in = |from: u32, to: u32, color: Rgb565| {
+            let span = Rectangle::new(
+                area.top_left + Point::new(from as i32 * 9, 0),
+                Size::new((to - from) * 9, area.size.height),
+            );
+            span.points().any(|p| display.get_pixel(p) == Some(color))
+        };
+        assert!(colors_in(0, 2, style.text_color) && !colors_in(0, 2, Rgb565::GREEN));
+        assert!(colors_in(2, 4, Rgb565::GREEN) && !colors_in(2, 4, style.text_color));
+        // the inverted span has a box of the text color
+        assert!(colors_in(4, 5, style.text_color) && colors_in(4, 5, style.background_color));
+
+        assert!(!frame(&mut display, &mut smartstate, Rgb565::GREEN).1);
+        assert!(frame(&mut display, &mut smartstate, Rgb565::RED).1);
+    }
+}
@@ -0,0 +1,316 @@
+//! # Scope Widget
+//!
+//! A [Scope] plots a stream of samples in "roll mode", like an oscilloscope without a
+//! trigger: every sample is a column of pixels, written at a cursor that moves to the right
+//! and wraps around, overwriting the oldest samples. A cursor line and an empty column ahead of
+//! it separate the newest samples from the oldest ones.
+//!
+//! This module is only available with the `charts` feature (on by default).
+//!
+//! The samples are kept in a caller-owned [ScopeState], a ring buffer that also remembers what
+//! was drawn. Each frame, only the columns of the samples pushed since the last frame are
+//! drawn, along with the cursor, so the cost is proportional to the number of new samples, not
+//! to the size of the plot. The whole plot is only drawn in the first frame, or if its size,
+//! range or style changed, or its area was invalidated.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! use kolibri_embedded_gui::scope::ScopeState;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # fn read_adc(batch: &mut [i32]) {}
+//! // up to 200 columns
+//! let mut scope = ScopeState::<200>::new();
+//! let mut batch = [0; 8];
+//!
+//! loop {
+//!     read_adc(&mut batch);
+//!     scope.extend(&batch);
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     ui.add(Scope::new(&mut scope).size(Size::new(200, 60)).range(0..=4095));
+//! }
+//! ```
+
+use crate::smartstate::Smartstate;
+use crate::ui::{GuiError, GuiResult, Response, Ui, Widget};
+use core::ops::RangeInclusive;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+/// The samples of a [Scope], and what of them was drawn
+///
+/// Holds the last `N` samples, so a [Scope] is at most `N` pixels wide. See
+/// [retained state](Smartstate#retained-state).
+#[derive(Clone, Debug)]
+pub struct ScopeState<const N: usize> {
+    samples: [i32; N],
+    /// Number of samples pushed since the creation (or [ScopeState::clear])
+    written: u64,
+    /// Number of samples pushed when the scope was last drawn, `None` if it needs a full redraw
+    drawn: Option<u64>,
+    /// The area, range and style the scope was drawn with
+    key: Smartstate,
+}
+
+impl<const N: usize> ScopeState<N> {
+    /// Creates an empty state.
+    pub fn new() -> Self {
+        Self {
+            samples: [0; N],
+            written: 0,
+            drawn: None,
+            key: Smartstate::empty(),
+        }
+    }
+
+    /// Adds a sample at the cursor.
+    pub fn push(&mut self, sample: i32) {
+        if N > 0 {
+            self.samples[(self.written % N as u64) as usize] = sample;
+            self.written += 1;
+        }
+    }
+
+    /// Adds the samples of a batch at the cursor, oldest first.
+    pub fn extend(&mut self, samples: &[i32]) {
+        for &sample in samples {
+            self.push(sample);
+        }
+    }
+
+    /// Returns the last sample, if there is one.
+    pub fn latest(&self) -> Option<i32> {
+        self.sample(self.written.checked_sub(1)?)
+    }
+
+    /// Returns the number of samples pushed so far.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Removes all samples. The scope is drawn empty in the next frame.
+    pub fn clear(&mut self) {
+        self.written = 0;
+        self.drawn = None;
+    }
+
+    /// Returns the `index`th sample pushed, if it is still held
+    fn sample(&self, index: u64) -> Option<i32> {
+        (index < self.written && self.written - index <= N as u64)
+            .then(|| self.samples[(index % N as u64) as usize])
+    }
+}
+
+impl<const N: usize> Default for ScopeState<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A plot of a sample stream in roll mode, see the [module documentation](self).
+pub struct Scope<'a, const N: usize> {
+    state: &'a mut ScopeState<N>,
+    size: Option<Size>,
+    range: RangeInclusive<i32>,
+}
+
+impl<'a, const N: usize> Scope<'a, N> {
+    /// Creates a scope plotting the samples in `state`.
+    pub fn new(state: &'a mut ScopeState<N>) -> Self {
+        Self {
+            state,
+            size: None,
+            range: i16::MIN as i32..=i16::MAX as i32,
+        }
+    }
+
+    /// Sets the size of the plot. Its width is limited to the `N` samples of the
+    /// [ScopeState]. By default, it is `N` pixels wide, and three times as high as the style's
+    /// default widget height.
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    /// Sets the range of sample values from the bottom to the top of the plot, the range of
+    /// an `i16` by default. Samples outside of it are drawn at the edges.
+    pub fn range(mut self, range: RangeInclusive<i32>) -> Self {
+        self.range = range;
+        self
+    }
+}
+
+impl<const N: usize> Widget for Scope<'_, N> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        // get size
+        let size = self
+            .size
+            .unwrap_or_else(|| Size::new(N as u32, 3 * ui.style().default_widget_height));
+        // (at least a sample, the cursor and the column ahead of it)
+        let size = Size::new(size.width.min(N as u32).max(3), size.height.max(1));
+
+        // allocate space
+        let iresponse = ui.allocate_space(size)?;
+        let area = iresponse.area;
+
+        // check whether the plot can be continued
+        let state = &mut *self.state;
+        let (low, high) = (*self.range.start(), *self.range.end());
+        let mut key = Smartstate::empty();
+        key.set_state_hashed(&(area, low, high));
+        key.mix(ui.style_key());
+        let width = area.size.width as u64;
+        let written = state.written;
+        // the oldest visible sample, as the cursor and the column ahead take two columns
+        let oldest = written.saturating_sub(width - 2);
+        let drawn = state
+            .drawn
+            .filter(|&drawn| state.key == key && drawn >= oldest && !ui.invalidated(area));
+        state.key = key;
+        state.drawn = Some(written);
+        if drawn == Some(written) {
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+
+        // the columns to draw by sample index: the new samples, the cursor at `written`, and
+        // the empty column ahead of it, in one or (when wrapping) two spans of columns
+        let from = drawn.unwrap_or(oldest);
+        let to = written + 1;
+        let wrap = (from / width + 1) * width;
+        let spans = match drawn {
+            None => [Some((from, to)), None],
+            Some(_) if wrap <= to => [Some((from, wrap - 1)), Some((wrap, to))],
+            Some(_) => [Some((from, to)), None],
+        };
+
+        let y = |sample: i32| {
+            let clamped = sample.clamp(low.min(high), high.max(low)) as i64;
+            let span = (high as i64 - low as i64).max(1);
+            let from_top = (high as i64 - clamped) * (area.size.height as i64 - 1) / span;
+            area.top_left.y + from_top as i32
+        };
+        let column = |index: u64, top: i32, bottom: i32| {
+            Rectangle::with_corners(
+                Point::new(area.top_left.x + (index % width) as i32, top),
+                Point::new(area.top_left.x + (index % width) as i32, bottom),
+            )
+        };
+        let bottom = area.top_left.y + area.size.height as i32 - 1;
+        let trace = PrimitiveStyle::with_fill(ui.style().primary_color);
+        let cursor = PrimitiveStyle::with_fill(ui.style().border_color);
+        let empty = PrimitiveStyle::with_fill(ui.style().background_color);
+
+        for (first, last) in spans.into_iter().flatten() {
+            let span = match drawn {
+                None => area,
+                Some(_) => Rectangle::with_corners(
+                    column(first, area.top_left.y, bottom).top_left,
+                    column(last, area.top_left.y, bottom)
+                        .bottom_right()
+                        .unwrap(),
+                ),
+            };
+            ui.start_drawing(&span);
+            if drawn.is_none() && !ui.cleared() {
+                ui.clear_area(area)?;
+            }
+            for index in first..=last {
+                let full = column(index, area.top_left.y, bottom);
+                let drawn_column = if index == written {
+                    ui.draw(&full.into_styled(cursor))
+                } else {
+                    let erase = match drawn {
+                        Some(_) => ui.draw(&full.into_styled(empty)).map(|_| ()),
+                        None => Ok(()),
+                    };
+                    // a line from the previous sample, for a connected trace
+                    match (erase, state.sample(index)) {
+                        (Ok(()), Some(sample)) => {
+                            let previous = state
+                                .sample(index.wrapping_sub(1))
+                                .filter(|_| index > oldest)
+                                .unwrap_or(sample);
+                            let (a, b) = (y(previous), y(sample));
+                            ui.draw(&column(index, a.min(b), a.max(b)).into_styled(trace))
+                        }
+                        (erase, _) => erase,
+                    }
+                };
+                drawn_column.map_err(|_| GuiError::DrawError(Some("Couldn't draw Scope")))?;
+            }
+            ui.finalize()?;
+        }
+
+        Ok(Response::new(iresponse).set_redraw(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// Draws a 40x20 scope for values from 0 to 19, returning its response and the pixels drawn
+    fn frame(display: &mut MockDisplay<Rgb565>, state: &mut ScopeState<40>) -> (Response, u32) {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        let response = ui.add(Scope::new(state).size(Size::new(40, 20)).range(0..=19));
+        (response, ui.perf().pixels_drawn)
+    }
+
+    #[test]
+    fn only_new_samples_are_drawn() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let style = medsize_rgb565_style();
+        let mut state = ScopeState::<40>::new();
+        state.extend(&[0, 5, 19]);
+
+        let (response, _) = frame(&mut display, &mut state);
+        let area = response.area();
+        let pixel = |display: &MockDisplay<Rgb565>, x: i32, y: i32| {
+            display.get_pixel(area.top_left + Point::new(x, y))
+        };
+        // a line from the previous sample in each column, y = 19 - value
+        assert_eq!(pixel(&display, 0, 19), Some(style.primary_color));
+        assert_eq!(pixel(&display, 1, 14), Some(style.primary_color));
+        assert_eq!(pixel(&display, 1, 19), Some(style.primary_color));
+        assert_eq!(pixel(&display, 2, 0), Some(style.primary_color));
+        assert_eq!(pixel(&display, 3, 10), Some(style.border_color));
+        assert_eq!(pixel(&display, 4, 10), Some(style.background_color));
+
+        // nothing new, nothing drawn
+        let (response, drawn) = frame(&mut display, &mut state);
+        assert!(!response.redraw);
+        assert_eq!(drawn, 0);
+
+        // two samples: their columns, the cursor and the empty column ahead of it
+        state.extend(&[19, 19]);
+        let (response, drawn) = frame(&mut display, &mut state);
+        assert!(response.redraw);
+        assert!(drawn <= 4 * 20 + 2, "{drawn}");
+        assert_eq!(pixel(&display, 3, 10), Some(style.background_color));
+        assert_eq!(pixel(&display, 4, 0), Some(style.primary_color));
+        assert_eq!(pixel(&display, 5, 10), Some(style.border_color));
+
+        // wrapping around costs the same
+        state.extend(&[10; 34]);
+        frame(&mut display, &mut state);
+        state.extend(&[0; 3]);
+        let (_, drawn) = frame(&mut display, &mut state);
+        assert!(drawn <= 5 * 20 + 3 + 11, "{drawn}");
+        assert_eq!(pixel(&display, 1, 19), Some(style.primary_color));
+        assert_eq!(pixel(&display, 2, 10), Some(style.border_color));
+        assert_eq!(pixel(&display, 3, 10), Some(style.background_color));
+        assert_eq!(pixel(&display, 4, 0), Some(style.primary_color));
+    }
+}
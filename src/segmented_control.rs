@@ -0,0 +1,337 @@
+//! # Segmented Control
+//!
+//! A [SegmentedControl] picks one of a few (2–4) mutually exclusive options, shown as a row of
+//! connected segments. The selected segment is filled with the primary color.
+//!
+//! This module is only available with the `controls` feature (on by default).
+//!
+//! All segments are as wide as the widest option, unless the control's width is set with
+//! [SegmentedControl::width]. Options that don't fit their segment are cut off.
+//!
+//! ## Smartstates
+//!
+//! Each segment has its own smartstate (see [SegmentedControl::smartstates]), so changing the
+//! selection only redraws the two segments involved, and hovering only the hovered one.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! let mut unit = 0;
+//! let mut smartstates = [Smartstate::empty(); 3];
+//!
+//! loop {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     let response = ui.add(
+//!         SegmentedControl::new(&["°C", "°F", "K"], &mut unit).smartstates(&mut smartstates),
+//!     );
+//!     if response.changed() {
+//!         println!("unit {unit} selected");
+//!     }
+//! }
+//! ```
+
+use crate::smartstate::Smartstate;
+use crate::text::{truncate, Font, RenderedText, TextRenderer};
+use crate::ui::{
+    resolve_widget_style, rounded_rect_clamped, GuiError, GuiResult, Interaction, Response, Ui,
+    Widget,
+};
+use core::cmp::max;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle, RoundedRectangle};
+
+/// A row of connected segments to pick one option from, see the [module documentation](self).
+pub struct SegmentedControl<'a> {
+    options: &'a [&'a str],
+    selected: &'a mut usize,
+    width: Option<u32>,
+    smartstates: &'a mut [Smartstate],
+}
+
+impl<'a> SegmentedControl<'a> {
+    /// Creates a segmented control for `options`, with the index of the selected one in
+    /// `selected`. If `selected` is out of range, no segment is selected.
+    pub fn new(options: &'a [&'a str], selected: &'a mut usize) -> Self {
+        Self {
+            options,
+            selected,
+            width: None,
+            smartstates: &mut [],
+        }
+    }
+
+    /// Sets the width of the whole control, which is split evenly between the segments.
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Adds a smartstate per segment, in the order of the options.
+    ///
+    /// Segments without a smartstate (if there are fewer smartstates than options) are
+    /// redrawn every frame.
+    pub fn smartstates(mut self, smartstates: &'a mut [Smartstate]) -> Self {
+        self.smartstates = smartstates;
+        self
+    }
+}
+
+impl Widget for SegmentedControl<'_> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let font = Font::resolve(None, ui.style());
+        let padding = ui.style().spacing.button_padding;
+        let border = ui.style().border_width;
+        let count = self.options.len() as u32;
+
+        // all segments as wide as the widest option
+        let inset = padding.width + border;
+        let width = self.width.unwrap_or_else(|| {
+            let widest = self
+                .options
+                .iter()
+                .map(|option| font.measure(option).width)
+                .max()
+                .unwrap_or(0);
+            count * (widest + 2 * inset)
+        });
+        let height = max(
+            font.line_height() + 2 * (padding.height + border),
+            ui.style().default_widget_height,
+        );
+
+        let iresponse = ui.allocate_space(Size::new(width, height))?;
+        let area = iresponse.area;
+        let segment = |i: u32| {
+            let x = |i: u32| area.top_left.x + (area.size.width * i / count.max(1)) as i32;
+            Rectangle::with_corners(
+                Point::new(x(i), area.top_left.y),
+                Point::new(x(i + 1) - 1, area.top_left.y + area.size.height as i32 - 1),
+            )
+        };
+
+        // the segment under the pointer
+        let interacted = (0..count).find(|&i| {
+            iresponse
+                .interaction
+                .get_point()
+                .is_some_and(|p| segment(i).contains(p))
+        });
+        let previous = *self.selected;
+        match iresponse.interaction {
+            // without a pointer, step through the options
+            Interaction::ActivatedByFocus(_) if count > 0 => {
+                *self.selected = (previous + 1) % count as usize;
+            }
+            Interaction::Release(_) => {
+                if let Some(i) = interacted {
+                    *self.selected = i as usize;
+                }
+            }
+            _ => {}
+        }
+        let changed = *self.selected != previous;
+
+        let style_key = ui.style_key();
+        for (i, option) in (0..count).zip(self.options) {
+            let rect = segment(i);
+            let selected = *self.selected == i as usize;
+            let interaction = match interacted {
+                Some(interacted) if interacted == i => iresponse.interaction,
+                _ => Interaction::None,
+            };
+            let (mut rect_style, bucket) = resolve_widget_style(ui.style(), interaction);
+            if selected {
+                rect_style.fill_color = Some(ui.style().primary_color);
+            }
+
+            // check the segment's smartstate
+            let smartstate = self.smartstates.get_mut(i as usize);
+            let prevstate = smartstate.as_ref().map(|st| **st);
+            let mut state = Smartstate::empty();
+            state.set_state_hashed(&(bucket, selected, *option, rect.size));
+            state.mix(style_key);
+            if let Some(smartstate) = smartstate {
+                *smartstate = state;
+            }
+            if prevstate == Some(state) && !ui.invalidated(rect) {
+                continue;
+            }
+
+            ui.start_drawing(&rect);
+            let shape = segment_shape(rect, i, count, ui.style().corner_radius);
+            draw_segment(ui, shape, i, rect_style)?;
+            let text = truncate(&font, option, rect.size.width.saturating_sub(2 * inset));
+            let text_size = font.measure(text);
+            let text_top_left = rect.top_left
+                + ui.style().pressed_offset_for(interaction.is_pressed())
+                + Point::new(
+                    (rect.size.width.saturating_sub(text_size.width) / 2) as i32,
+                    (rect.size.height.saturating_sub(text_size.height) / 2) as i32,
+                );
+            ui.draw(&RenderedText::new(
+                &font,
+                text,
+                text_top_left,
+                ui.style().text_color,
+            ))
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw SegmentedControl label")))?;
+            ui.finalize()?;
+        }
+
+        let down = matches!(
+            iresponse.interaction,
+            Interaction::Click(_) | Interaction::Drag(_)
+        );
+        // (releasing on the selected segment is a click, too)
+        let click = iresponse.interaction.is_release() && (interacted.is_some() || changed);
+        Ok(Response::new(iresponse)
+            .set_clicked(click)
+            .set_down(down)
+            .set_changed(changed))
+    }
+}
+
+/// Returns the shape of segment `i` of `count` in `rect`: only the outer ends are rounded, with
+/// `radius` clamped to the segment's size
+fn segment_shape(rect: Rectangle, i: u32, count: u32, radius: u32) -> RoundedRectangle {
+    let mut shape = rounded_rect_clamped(rect, radius);
+    if i > 0 {
+        shape.corners.top_left = Size::zero();
+        shape.corners.bottom_left = Size::zero();
+    }
+    if i + 1 < count {
+        shape.corners.top_right = Size::zero();
+        shape.corners.bottom_right = Size::zero();
+    }
+    shape
+}
+
+/// Draws the background of segment `i`, with a separator on its left if it has no border
+fn draw_segment<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+    ui: &mut Ui<DRAW, COL>,
+    shape: RoundedRectangle,
+    i: u32,
+    style: PrimitiveStyle<COL>,
+) -> GuiResult<()> {
+    let rect = shape.rectangle;
+    ui.draw(&shape.into_styled(style))
+        .map_err(|_| GuiError::DrawError(Some("Couldn't draw SegmentedControl")))?;
+
+    // a thin separator, unless the borders of the segments separate them already
+    if style.stroke_width == 0 && i > 0 {
+        let inset = ui.style().spacing.button_padding.height;
+        let separator = Rectangle::new(
+            rect.top_left + Point::new(0, inset as i32),
+            Size::new(1, rect.size.height.saturating_sub(2 * inset)),
+        );
+        ui.draw(&separator.into_styled(PrimitiveStyle::with_fill(ui.style().border_color)))
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw SegmentedControl")))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    const OPTIONS: [&str; 3] = ["A", "B", "C"];
+
+    /// Draws a frame, returning the response and the number of segments redrawn
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        selected: &mut usize,
+        smartstates: &mut [Smartstate],
+        interaction: Interaction,
+    ) -> (Response, u32) {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        ui.interact(interaction);
+        let response = ui.add(
+            SegmentedControl::new(&OPTIONS, selected)
+                .width(57)
+                .smartstates(smartstates),
+        );
+        (response, ui.perf().widgets_redrawn)
+    }
+
+    #[test]
+    fn tapping_a_segment_selects_it() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut selected = 0;
+        let mut smartstates = [Smartstate::empty(); 3];
+
+        let (response, redrawn) = frame(
+            &mut display,
+            &mut selected,
+            &mut smartstates,
+            Interaction::None,
+        );
+        assert_eq!(redrawn, 3);
+        assert_eq!(response.area().size.width, 57);
+
+        // the third segment starts at 3 + 2 * 57 / 3
+        let third = Point::new(45, 10);
+        let (response, redrawn) = frame(
+            &mut display,
+            &mut selected,
+            &mut smartstates,
+            Interaction::Release(third),
+        );
+        assert!(response.changed() && response.clicked());
+        assert_eq!(selected, 2);
+        // the previously and the newly selected segment
+        assert_eq!(redrawn, 2);
+
+        let (response, redrawn) = frame(
+            &mut display,
+            &mut selected,
+            &mut smartstates,
+            Interaction::Release(third),
+        );
+        assert!(!response.changed() && response.clicked());
+        assert_eq!(redrawn, 0);
+    }
+
+    #[test]
+    fn long_options_are_cut_off() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let mut selected = 0;
+        ui.add(SegmentedControl::new(&["Celsius", "Fahrenheit"], &mut selected).width(40));
+        // nothing drawn outside of the control
+        let area = display.affected_area();
+        assert!(area.size.width <= 40, "{area:?}");
+    }
+
+    #[test]
+    fn narrow_segments_clamp_their_radius() {
+        let rect = Rectangle::new(Point::zero(), Size::new(8, 20));
+        let first = segment_shape(rect, 0, 3, 10);
+        assert_eq!(first.corners.top_left, Size::new(4, 4));
+        assert_eq!(first.corners.bottom_left, Size::new(4, 4));
+        assert_eq!(first.corners.top_right, Size::zero());
+
+        let middle = segment_shape(rect, 1, 3, 10);
+        assert_eq!(middle.corners.top_left, Size::zero());
+        assert_eq!(middle.corners.bottom_right, Size::zero());
+
+        // a single segment is rounded on both ends
+        let wide = Rectangle::new(Point::zero(), Size::new(60, 20));
+        let single = segment_shape(wide, 0, 1, 10);
+        assert_eq!(single.corners.top_right, Size::new(10, 10));
+        assert_eq!(single.corners.bottom_left, Size::new(10, 10));
+    }
+}
@@ -0,0 +1,271 @@
+//! # Segmented Progress
+//!
+//! A [SegmentedProgress] shows the progress through a fixed sequence of stages, e.g. the
+//! download, verification, flashing and reboot of a firmware update. Each stage is a block of
+//! its own, separated by small gaps: completed stages are filled with the primary color, the
+//! current one is filled as far as its own progress, and the stages to come show the item
+//! background.
+//!
+//! This module is only available with the `indicators` feature (on by default).
+//!
+//! Unlike a plain progress bar, the segments tell where the process is, so each can have a
+//! label below it (see [SegmentedProgress::labels]).
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! # let (received, total) = (300, 1000);
+//! // downloading, 30% done
+//! ui.add(
+//!     SegmentedProgress::new(4, 0, received as f32 / total as f32)
+//!         .labels(&["Load", "Check", "Flash", "Boot"])
+//!         .smartstate(smartstates.nxt()),
+//! );
+//! ```
+//!
+//! With a smartstate, the widget is only redrawn when the stage changes, or the current
+//! stage's fill grows by a pixel.
+
+use crate::format::Buf;
+use crate::smartstate::{Container, Smartstate};
+use crate::text::{ellipsize, Font, RenderedText, TextRenderer, ELLIPSIZED_LEN};
+use crate::ui::{GuiError, GuiResult, Response, Ui, Widget};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+/// Gap between the blocks, in pixels
+const GAP: u32 = 2;
+
+/// Progress through a sequence of stages, see the [module documentation](self).
+pub struct SegmentedProgress<'a> {
+    segments: u32,
+    current: u32,
+    fraction: f32,
+    labels: &'a [&'a str],
+    width: Option<u32>,
+    height: Option<u32>,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<'a> SegmentedProgress<'a> {
+    /// Creates a progress of `segments` stages, at stage `current` (counting from 0), which is
+    /// done by `fraction` (from 0.0 to 1.0).
+    ///
+    /// A `current` stage of `segments` (or more) shows all stages completed.
+    pub fn new(segments: u32, current: u32, fraction: f32) -> Self {
+        Self {
+            segments: segments.max(1),
+            current,
+            fraction,
+            labels: &[],
+            width: None,
+            height: None,
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Adds a label below each block, cut off with an ellipsis if it is wider than the block.
+    /// Blocks without a label (if there are fewer labels than segments) get none.
+    pub fn labels(mut self, labels: &'a [&'a str]) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Sets the width of the widget, which is the remaining width of the row by default.
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets the height of the blocks, which is a quarter of the style's default widget height
+    /// by default.
+    pub fn height(mut self, height: u32) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    /// Adds a smartstate, so that the widget is only redrawn when the shown progress changes.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+impl Widget for SegmentedProgress<'_> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        // get size
+        let font = Font::resolve(None, ui.style());
+        let block_height = self
+            .height
+            .unwrap_or(ui.style().default_widget_height / 4)
+            .max(1);
+        let label_height = match self.labels.is_empty() {
+            true => 0,
+            false => font.line_height(),
+        };
+        let width = self
+            .width
+            .unwrap_or_else(|| ui.remaining_width())
+            .max(self.segments * (GAP + 1));
+        let size = Size::new(width, block_height + label_height);
+
+        // allocate space
+        let iresponse = ui.allocate_space(size)?;
+        let area = iresponse.area;
+
+        // the columns of block `i`, relative to the left edge
+        let segments = self.segments;
+        let block = |i: u32| {
+            let left = i * (width + GAP) / segments;
+            let right = (i + 1) * (width + GAP) / segments - GAP;
+            (left, right)
+        };
+
+        // the filled width of the current block, which is all the fraction changes
+        let current = self.current.min(segments);
+        let filled = match current < segments {
+            true => {
+                let (left, right) = block(current);
+                ((right - left) as f32 * self.fraction.clamp(0.0, 1.0)) as u32
+            }
+            false => 0,
+        };
+
+        // check smartstate
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(&(current, filled, area.size, self.labels, ui.style_key()));
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(area);
+        self.smartstate.modify(|st| *st = state);
+        if !redraw {
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+
+        // draw
+        ui.start_drawing(&area);
+        if !ui.cleared() {
+            ui.clear_area(area)?;
+        }
+        let done = PrimitiveStyle::with_fill(ui.style().primary_color);
+        let todo = PrimitiveStyle::with_fill(ui.style().item_background_color.color());
+        let text_color = ui.style().text_color;
+        let mut ellipsized = Buf::<ELLIPSIZED_LEN>::new();
+        for i in 0..segments {
+            let (left, right) = block(i);
+            let rect = |from: u32, to: u32| {
+                Rectangle::new(
+                    area.top_left + Point::new(from as i32, 0),
+                    Size::new(to - from, block_height),
+                )
+            };
+            let drawn = match i.cmp(&current) {
+                core::cmp::Ordering::Less => ui.draw(&rect(left, right).into_styled(done)),
+                core::cmp::Ordering::Equal => ui
+                    .draw(&rect(left, left + filled).into_styled(done))
+                    .and_then(|_| ui.draw(&rect(left + filled, right).into_styled(todo))),
+                core::cmp::Ordering::Greater => ui.draw(&rect(left, right).into_styled(todo)),
+            };
+            drawn.map_err(|_| GuiError::DrawError(Some("Couldn't draw SegmentedProgress")))?;
+
+            if let Some(label) = self.labels.get(i as usize) {
+                let label = ellipsize(&font, label, right - left, &mut ellipsized);
+                let offset = (right - left).saturating_sub(font.measure(label).width) / 2;
+                let position = Point::new((left + offset) as i32, block_height as i32);
+                ui.draw(&RenderedText::new(
+                    &font,
+                    label,
+                    area.top_left + position,
+                    text_color,
+                ))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw SegmentedProgress label")))?;
+            }
+        }
+        ui.finalize()?;
+
+        Ok(Response::new(iresponse).set_redraw(true))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// Draws a 4-stage progress, 58 pixels wide, returning its response
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        smartstate: &mut Smartstate,
+        current: u32,
+        fraction: f32,
+    ) -> Response {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        ui.add(
+            SegmentedProgress::new(4, current, fraction)
+                .height(6)
+                .smartstate(smartstate),
+        )
+    }
+
+    #[test]
+    fn blocks_show_completed_current_and_future_stages() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+        let style = medsize_rgb565_style();
+
+        let response = frame(&mut display, &mut smartstate, 1, 0.5);
+        let area = response.area();
+        assert!(response.redraw);
+        assert_eq!(area.size, Size::new(58, 6));
+        // blocks of 13 pixels at 0, 15, 30 and 45
+        let color = |display: &MockDisplay<Rgb565>, x: i32| {
+            display.get_pixel(area.top_left + Point::new(x, 3))
+        };
+        assert_eq!(color(&display, 0), Some(style.primary_color));
+        assert_eq!(color(&display, 12), Some(style.primary_color));
+        assert_eq!(color(&display, 13), Some(style.background_color));
+        assert_eq!(color(&display, 15), Some(style.primary_color));
+        assert_eq!(color(&display, 15 + 5), Some(style.primary_color));
+        assert_eq!(
+            color(&display, 15 + 6),
+            Some(style.item_background_color.color())
+        );
+        assert_eq!(
+            color(&display, 57),
+            Some(style.item_background_color.color())
+        );
+
+        // less than a pixel of progress doesn't redraw
+        assert!(!frame(&mut display, &mut smartstate, 1, 0.52).redraw);
+        assert!(frame(&mut display, &mut smartstate, 1, 0.6).redraw);
+        assert!(frame(&mut display, &mut smartstate, 4, 0.0).redraw);
+        assert_eq!(color(&display, 57), Some(style.primary_color));
+    }
+
+    #[test]
+    fn labels_are_cut_to_their_block() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let response = ui.add(SegmentedProgress::new(2, 0, 0.0).labels(&["Download", "OK"]));
+        let line_height = Font::resolve(None, ui.style()).line_height();
+        let block_height = ui.style().default_widget_height / 4;
+        assert_eq!(response.area().size.height, block_height + line_height);
+    }
+}
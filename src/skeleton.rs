@@ -0,0 +1,315 @@
+//! # Skeleton Widget
+//!
+//! A [Skeleton] is a placeholder block shown where content (a label, an image, ...) will be
+//! once its data has arrived. It is filled with the style's item background color.
+//!
+//! This module is only available with the `indicators` feature (on by default).
+//!
+//! [Skeleton::text_lines] sizes the placeholder like a [Label](crate::label::Label) with that
+//! many lines, so the layout doesn't jump when the real content replaces it.
+//!
+//! With [Skeleton::shimmer], a lighter band (the highlight item background color) moves over
+//! the placeholder to show activity. The band is driven by the frame time
+//! (see [Ui::begin_frame]) and moves in steps; with a smartstate, a step only redraws the
+//! columns the band left and entered. Without shimmer (or without a frame time), a skeleton
+//! with a smartstate is drawn once and costs nothing afterwards.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! # let name: Option<&str> = None;
+//! match name {
+//!     Some(name) => ui.add(Label::new(name).smartstate(smartstates.nxt())),
+//!     None => ui.add(
+//!         Skeleton::text_lines(1)
+//!             .width(120)
+//!             .shimmer(1200)
+//!             .smartstate(smartstates.nxt()),
+//!     ),
+//! };
+//! ```
+
+use crate::smartstate::{Container, Smartstate};
+use crate::text::{Font, TextRenderer};
+use crate::ui::{GuiResult, Response, Ui, Widget};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::mono_font::MonoFont;
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+/// Number of positions of the shimmer band per sweep
+const STEPS: u32 = 16;
+
+enum Shape {
+    Block(Size),
+    Lines(u32),
+}
+
+/// A placeholder for content that isn't available yet, see the [module documentation](self).
+pub struct Skeleton<'a> {
+    shape: Shape,
+    width: Option<u32>,
+    font: Option<MonoFont<'a>>,
+    shimmer_ms: Option<u32>,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<'a> Skeleton<'a> {
+    /// Creates a placeholder block of the given size.
+    pub fn new(size: Size) -> Self {
+        Self {
+            shape: Shape::Block(size),
+            width: None,
+            font: None,
+            shimmer_ms: None,
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Creates a placeholder for `lines` lines of text, as high as a
+    /// [Label](crate::label::Label) with that many lines in the style's default font.
+    ///
+    /// Each line is drawn as a bar, with the last one shorter. The placeholder takes the
+    /// remaining width of the row, unless set with [Skeleton::width].
+    pub fn text_lines(lines: u32) -> Self {
+        Self {
+            shape: Shape::Lines(lines.max(1)),
+            ..Self::new(Size::zero())
+        }
+    }
+
+    /// Sets the width of the placeholder.
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Sets the font the lines of [Skeleton::text_lines] are measured with, if the content
+    /// uses a custom font.
+    pub fn with_font(mut self, font: MonoFont<'a>) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Moves a lighter band over the placeholder, sweeping across it every `period_ms`.
+    ///
+    /// Needs a frame time (see [Ui::begin_frame]), the placeholder is static otherwise.
+    pub fn shimmer(mut self, period_ms: u32) -> Self {
+        self.shimmer_ms = Some(period_ms.max(1));
+        self
+    }
+
+    /// Adds a smartstate, so that the placeholder is only redrawn when the shimmer band moves.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+/// Returns the columns covered by the shimmer band at `step`, relative to the left edge
+fn band(step: u32, width: u32) -> (i32, i32) {
+    let band_width = (width / 4).max(1);
+    // the band sweeps in from the left and out to the right
+    let left = (step * (width + band_width) / STEPS) as i32 - band_width as i32;
+    (left, left + band_width as i32)
+}
+
+impl Widget for Skeleton<'_> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        // get size
+        let (size, lines, line_height) = match self.shape {
+            Shape::Block(size) => (
+                Size::new(self.width.unwrap_or(size.width), size.height),
+                1,
+                size.height,
+            ),
+            Shape::Lines(lines) => {
+                let line_height = Font::resolve(self.font, ui.style()).line_height();
+                let width = self.width.unwrap_or_else(|| ui.remaining_width());
+                (Size::new(width, lines * line_height), lines, line_height)
+            }
+        };
+
+        // allocate space
+        let iresponse = ui.allocate_space(size)?;
+        let area = iresponse.area;
+
+        // the position of the band, STEPS if there is none
+        let step = match (self.shimmer_ms, ui.now()) {
+            (Some(period), Some(now)) => {
+                ((now % period as u64) * STEPS as u64 / period as u64) as u32
+            }
+            _ => STEPS,
+        };
+        if step < STEPS {
+            ui.request_repaint();
+        }
+        let style_key = ui.style_key();
+        let state = |step: u32| (step, area.size, lines, style_key);
+
+        // check smartstate
+        let prevstate = self.smartstate.clone_inner();
+        self.smartstate
+            .modify(|st| st.set_state_hashed(&state(step)));
+        let invalidated = ui.invalidated(area);
+        if self.smartstate.eq_option(&prevstate) && !invalidated {
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+
+        // the bars of the lines: the glyph height without the spacing, the last one shorter
+        let text = matches!(self.shape, Shape::Lines(_));
+        let bar_height = if text {
+            (line_height * 3 / 4).max(1)
+        } else {
+            line_height
+        };
+        let bar = |line: u32| {
+            let width = if lines > 1 && line == lines - 1 {
+                area.size.width * 2 / 3
+            } else {
+                area.size.width
+            };
+            Rectangle::new(
+                area.top_left
+                    + Point::new(
+                        0,
+                        (line * line_height + (line_height - bar_height) / 2) as i32,
+                    ),
+                Size::new(width, bar_height),
+            )
+        };
+        let columns = |(left, right): (i32, i32)| {
+            Rectangle::with_corners(
+                area.top_left + Point::new(left, 0),
+                area.top_left + Point::new(right - 1, area.size.height as i32 - 1),
+            )
+            .intersection(&area)
+        };
+
+        // only redraw the columns the band left and entered, if it was drawn and moved since
+        let moved = prevstate
+            .filter(|_| !invalidated && step < STEPS)
+            .and_then(|prev| (0..STEPS).find(|&step| prev.is_state_hashed(&state(step))));
+
+        ui.start_drawing(&area);
+        let buffered = ui.clear_buffer_raw(ui.style().background_color);
+        let redraw = match moved {
+            Some(previous) if !buffered => {
+                let (prev_left, prev_right) = band(previous, area.size.width);
+                let (left, right) = band(step, area.size.width);
+                columns((prev_left.min(left), prev_right.max(right)))
+            }
+            _ => {
+                if !buffered && !ui.cleared() && text {
+                    // the gaps around the bars
+                    ui.clear_area(area)?;
+                }
+                area
+            }
+        };
+        let base = PrimitiveStyle::with_fill(ui.style().item_background_color.color());
+        let highlight = PrimitiveStyle::with_fill(ui.style().highlight_item_background_color);
+        let band_area = (step < STEPS).then(|| columns(band(step, area.size.width)));
+        for line in 0..lines {
+            let bar = bar(line).intersection(&redraw);
+            ui.draw(&bar.into_styled(base))?;
+            if let Some(band_area) = band_area {
+                ui.draw(&bar.intersection(&band_area).into_styled(highlight))?;
+            }
+        }
+        ui.finalize()?;
+
+        Ok(Response::new(iresponse).set_redraw(true))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::label::Label;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// Draws a frame at `now`, returning the number of pixels drawn
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        smartstate: &mut Smartstate,
+        now: Option<u64>,
+    ) -> u32 {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        if let Some(now) = now {
+            ui.begin_frame(now);
+        }
+        ui.add(
+            Skeleton::text_lines(2)
+                .width(48)
+                .shimmer(1600)
+                .smartstate(smartstate),
+        );
+        ui.perf().pixels_drawn
+    }
+
+    #[test]
+    fn text_lines_match_label_height() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+
+        let label = ui.add(Label::new("Hi")).internal.area.size.height;
+        let skeleton = ui.add(Skeleton::text_lines(1).width(20));
+        assert_eq!(skeleton.internal.area.size, Size::new(20, label));
+
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let skeleton = ui.add(Skeleton::text_lines(2).width(20));
+        assert_eq!(skeleton.internal.area.size.height, 2 * label);
+    }
+
+    #[test]
+    fn static_without_shimmer() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+
+        assert!(frame(&mut display, &mut smartstate, None) > 0);
+        assert_eq!(frame(&mut display, &mut smartstate, None), 0);
+    }
+
+    #[test]
+    fn shimmer_redraws_only_the_band() {
+        let mut smartstate = Smartstate::empty();
+        let mut stepped = MockDisplay::new();
+        stepped.set_allow_overdraw(true);
+
+        let full = frame(&mut stepped, &mut smartstate, Some(0));
+        assert_eq!(frame(&mut stepped, &mut smartstate, Some(50)), 0);
+        for now in [100, 200, 700] {
+            let step = frame(&mut stepped, &mut smartstate, Some(now));
+            assert!(step > 0 && step < full / 2, "{step} of {full}");
+        }
+
+        // the same pixels as drawing it at once
+        let mut once = MockDisplay::new();
+        once.set_allow_overdraw(true);
+        frame(&mut once, &mut Smartstate::empty(), Some(700));
+        let background = medsize_rgb565_style().background_color;
+        for p in once.affected_area().points() {
+            let color = |d: &MockDisplay<Rgb565>| d.get_pixel(p).unwrap_or(background);
+            assert_eq!(color(&stepped), color(&once), "{p:?}");
+        }
+    }
+}
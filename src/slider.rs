@@ -2,6 +2,8 @@
 //!
 //! A widget for selecting numeric values within an inclusive range.
 //!
+//! This module is only available with the `controls` feature (on by default).
+//!
 //! # Core Features
 //!
 //! - Interactive value selection within inclusive ranges using fixed-point arithmetic
@@ -15,22 +17,15 @@
 //! ```no_run
 //! # use embedded_graphics::pixelcolor::Rgb565;
 //! # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-//! # use kolibri_embedded_gui::style::medsize_rgb565_style;
-//! # use kolibri_embedded_gui::ui::Ui;
+//! # use kolibri_embedded_gui::prelude::*;
 //! # use embedded_graphics::prelude::*;
 //! # use embedded_graphics::primitives::Rectangle;
-//! # use embedded_iconoir::prelude::*;
-//! # use embedded_iconoir::size12px;
-//! # use kolibri_embedded_gui::ui::*;
 //! # use embedded_graphics::mono_font::ascii;
-//! # use kolibri_embedded_gui::label::*;
-//! # use kolibri_embedded_gui::smartstate::*;
 //! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 //! # let output_settings = OutputSettingsBuilder::new().build();
 //! # let mut window = Window::new("Kolibri Example", &output_settings);
 //! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
 //! # let mut smartstateProvider = SmartstateProvider::<20>::new();
-//! # use kolibri_embedded_gui::slider::*;
 //! // Basic slider with range
 //! let mut value = 0i16;
 //! ui.add(Slider::new(&mut value, -100..=100));
@@ -63,7 +58,7 @@
 //! - Efficient fixed-point arithmetic for smooth value interpolation
 //!
 use crate::smartstate::{Container, Smartstate};
-use crate::ui::{GuiResult, Interaction, Response, Ui, Widget};
+use crate::ui::{GuiError, GuiResult, Interaction, RelativeWidth, Response, Ui, Widget};
 use core::cmp::max;
 use core::ops::RangeInclusive;
 use embedded_graphics::draw_target::DrawTarget;
@@ -126,6 +121,7 @@ pub struct Slider<'a> {
     step_size: u16,
     label: Option<&'a str>,
     width: u32,
+    relative_width: Option<RelativeWidth>,
     smartstate: Container<'a, Smartstate>,
 }
 
@@ -147,6 +143,7 @@ impl<'a> Slider<'a> {
             smartstate: Container::empty(),
             label: None,
             width: 200,
+            relative_width: None,
         }
     }
 
@@ -176,6 +173,24 @@ impl<'a> Slider<'a> {
         self
     }
 
+    /// Makes the slider `per_mille` of the row wide, e.g. `500` for half of it, instead of
+    /// a fixed [width](Slider::width).
+    ///
+    /// Unlike [Slider::width], this is the width of the whole slider, including padding. It is
+    /// resolved with [Ui::resolve_width] when the slider is drawn, so that widgets added with
+    /// [Ui::add_horizontal] whose shares add up to 1000 fit into the row.
+    pub fn width_fraction(mut self, per_mille: u16) -> Self {
+        self.relative_width = Some(RelativeWidth::PerMille(per_mille));
+        self
+    }
+
+    /// Makes the slider as wide as the rest of the row, instead of a fixed
+    /// [width](Slider::width).
+    pub fn fill_width(mut self) -> Self {
+        self.relative_width = Some(RelativeWidth::Fill);
+        self
+    }
+
     /// Sets the step size for value adjustments.
     ///
     /// The step size determines how the value snaps during interaction:
@@ -209,7 +224,14 @@ impl Widget for Slider<'_> {
             slider_knob_diameter + padding.height * 2,
         );
 
-        let mut width = self.width + 2 * padding.width;
+        let mut width = match self.relative_width {
+            // (wide enough for the knob)
+            Some(relative) => max(
+                ui.resolve_width(relative),
+                slider_knob_diameter + 2 * padding.width,
+            ),
+            None => self.width + 2 * padding.width,
+        };
 
         let font = ui.style().default_font;
         let mut text = if let Some(label) = self.label {
@@ -368,34 +390,47 @@ impl Widget for Slider<'_> {
                 1
             }
             _ => {
-                slider_knob_style.fill_color = Some(style.item_background_color);
+                slider_knob_style.fill_color = Some(style.item_background_color.color());
                 0
             }
         };
-        let state_val = (*self.value as u16) as u32 | ((interact_val as u32) << 16);
-
-        if !self.smartstate.eq_inner(&Smartstate::state(state_val)) {
+        let mut state =
+            Smartstate::state((*self.value as u16) as u32 | ((interact_val as u32) << 16));
+        state.mix(ui.style_key());
+
+        if !self.smartstate.eq_inner(&state) || ui.invalidated(iresponse.area) {
+            trace!(
+                "Slider smartstate: {} -> {}",
+                self.smartstate.clone_inner(),
+                state
+            );
             ui.start_drawing(&iresponse.area);
 
             if old_slider_knob_pos != slider_knob_pos {
                 ui.draw(&old_slider_knob.into_styled(old_slider_knob_style))
-                    .ok();
+                    .map_err(|_| GuiError::DrawError(Some("Couldn't clear Slider knob")))?;
             }
-            ui.draw(&slider_line.into_styled(line_style)).ok();
-            ui.draw(&slider_knob.into_styled(slider_knob_style)).ok();
+            ui.draw(&slider_line.into_styled(line_style))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw Slider line")))?;
+            ui.draw(&slider_knob.into_styled(slider_knob_style))
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw Slider knob")))?;
             // ui.draw(&icon_img).ok();
             if let Some(text) = text.as_mut() {
-                ui.draw(text).unwrap();
+                ui.draw(text)
+                    .map_err(|_| GuiError::DrawError(Some("Couldn't draw Slider label")))?;
             }
 
             ui.finalize()?;
         }
 
-        self.smartstate
-            .modify(|s| *s = Smartstate::state(state_val));
+        self.smartstate.modify(|s| *s = state);
 
         Ok(Response::new(iresponse).set_changed(old_val != *self.value)) //.set_clicked(click).set_down(down))
     }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
 }
 
 #[cfg(test)]
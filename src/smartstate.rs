@@ -1,6 +1,9 @@
+use core::cmp::Reverse;
 use core::hash::BuildHasher;
 use core::hash::Hash;
 
+use embedded_graphics::prelude::{Point, Size};
+use embedded_graphics::primitives::Rectangle;
 use foldhash::fast::FixedState;
 
 /// A container for an optional mutable reference to a value.
@@ -42,6 +45,11 @@ impl<'a, T> Container<'a, T> {
         self.optional_something = Some(val);
     }
 
+    /// Returns the contained value, if it exists.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.optional_something.as_deref_mut()
+    }
+
     /// Applies a modification function to the contained value if it exists.
     ///
     /// # Example
@@ -102,7 +110,7 @@ impl<T: PartialEq> Container<'_, T> {
 /// Hasher for hashed smartstates.
 const HASH_STATE: FixedState = FixedState::with_seed(0x3094572067945102 /* random number */);
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy)]
 /// Smartstates are used to dynamically redraw widgets. By doing so, there's no need to redraw
 /// widgets that haven't changed.
 /// Widgets can optionally use smartstates to redraw themselves, or they can just redraw themselves
@@ -114,6 +122,14 @@ const HASH_STATE: FixedState = FixedState::with_seed(0x3094572067945102 /* rando
 /// If you want to clear the background anyways, you can use the `Smartstate::force_redraw()` function to
 /// guarantee that all smartstate widgets are redrawn.
 ///
+/// ## Retained State
+///
+/// The [Ui](crate::ui::Ui) and the widgets are created anew every frame, so what has to be
+/// remembered between frames lives outside of the UI loop: smartstates (e.g. in a
+/// [SmartstateProvider]) as well as the other state types of this crate, e.g. a
+/// [ScrollState](crate::ui::ScrollState) or an [Animated](crate::anim::Animated) value. Create
+/// them once before the loop and pass them to the widgets in every frame.
+///
 /// ## Usage
 ///
 /// The concept behind smartstates is to give specific "states" to different draw calls. For
@@ -129,8 +145,9 @@ const HASH_STATE: FixedState = FixedState::with_seed(0x3094572067945102 /* rando
 /// ### Example
 ///
 /// ```rust
-/// use kolibri_embedded_gui::smartstate::{Container, Smartstate};
-/// # use kolibri_embedded_gui::ui::{GuiResult, InternalResponse, Response, Ui, Widget};
+/// use kolibri_embedded_gui::prelude::*;
+/// use kolibri_embedded_gui::smartstate::Container;
+/// # use kolibri_embedded_gui::ui::InternalResponse;
 /// # use embedded_graphics::draw_target::DrawTarget;
 /// # use embedded_graphics::pixelcolor::PixelColor;
 /// # use embedded_graphics::primitives::PrimitiveStyleBuilder;
@@ -186,7 +203,7 @@ const HASH_STATE: FixedState = FixedState::with_seed(0x3094572067945102 /* rando
 ///             self.smartstate.modify(|st| *st = Smartstate::state(2));
 ///             PrimitiveStyleBuilder::new()
 ///                 // ...
-///                 # .fill_color(ui.style().item_background_color)
+///                 # .fill_color(ui.style().item_background_color.color())
 ///                 # .stroke_color(ui.style().border_color)
 ///                 # .stroke_width(ui.style().border_width)
 ///                 .build()
@@ -202,55 +219,204 @@ const HASH_STATE: FixedState = FixedState::with_seed(0x3094572067945102 /* rando
 /// }
 /// ```
 ///
-pub struct Smartstate(u32, bool);
+/// ## Drawn Areas
+///
+/// Widgets added with a [Ui](crate::ui::Ui) record the area they were drawn at in their
+/// smartstate (see [Widget::smartstate_mut](crate::ui::Widget::smartstate_mut)). With it,
+/// [invalidate_area](Smartstate::invalidate_area) (or
+/// [SmartstateProvider::invalidate_area]) redraws exactly the widgets under something that
+/// covered them, e.g. a closed popup or a chart the app drew over the UI, without knowing which
+/// smartstate belongs to which widget.
+///
+/// The [Ui](crate::ui::Ui) also redraws a widget allocated elsewhere than where it was drawn,
+/// e.g. after a [wrap point](crate::ui::Ui::set_overflow_policy) moved, even if its state is
+/// the same, and one [erased](crate::ui::Ui::erased_area) by clearing after it was added.
+///
+/// To keep a smartstate at 8 bytes, the corners of the area are stored with 10 bits per
+/// coordinate, clamped to -1 and 1022. On draw targets larger than 1023 pixels, widgets
+/// beyond that are recorded at the edge, so that moving them there isn't detected.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Smartstate(u32, u32);
+
+// A smartstate packs 64 bits into two words (keeping the alignment of a `u32`): the state ID
+// folded to 23 bits, the valid flag, and the corners of the drawn area in 10 bits per coordinate.
+const STATE_MASK: u64 = (1 << 23) - 1;
+const VALID: u64 = 1 << 23;
+const AREA_SHIFT: u32 = 24;
+/// Bits per coordinate of a drawn area
+const COORD_BITS: u32 = 10;
+/// Largest coordinate of a drawn area, larger ones are clamped. Smaller ones are clamped to -1,
+/// so that a stored coordinate (offset by one) of `0` is left of or above the draw target.
+const MAX_COORD: i32 = (1 << COORD_BITS) - 2;
+
+/// Folds a state ID into the bits stored in a smartstate. IDs below 2^23 are kept apart.
+fn fold(state: u32) -> u64 {
+    (state ^ (state >> 23)) as u64 & STATE_MASK
+}
+
+/// Packs `area` into the bits of a drawn area, `0` for an empty one
+fn pack_area(area: &Rectangle) -> u64 {
+    let Some(bottom_right) = area.bottom_right() else {
+        return 0;
+    };
+    [
+        area.top_left.x,
+        bottom_right.x,
+        area.top_left.y,
+        bottom_right.y,
+    ]
+    .into_iter()
+    .fold(0, |packed, v| {
+        (packed << COORD_BITS) | (v.clamp(-1, MAX_COORD) + 1) as u64
+    })
+}
+
+/// Unpacks a drawn area packed by [pack_area]: `None` if it is unknown (or was entirely left of
+/// and above the draw target, where nothing is visible).
+fn unpack_area(packed: u64) -> Option<Rectangle> {
+    let coord = |i: u32| {
+        let shift = COORD_BITS * (3 - i);
+        ((packed >> shift) & ((1 << COORD_BITS) - 1)) as i32 - 1
+    };
+    (packed != 0).then(|| {
+        Rectangle::with_corners(
+            Point::new(coord(0), coord(2)),
+            Point::new(coord(1), coord(3)),
+        )
+    })
+}
 
 impl Smartstate {
+    /// The size of a smartstate in bytes, e.g. to budget the RAM of a [SmartstateProvider]
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// Returns the packed bits of the smartstate.
+    fn bits(&self) -> u64 {
+        ((self.0 as u64) << 32) | self.1 as u64
+    }
+
+    fn set_bits(&mut self, bits: u64) {
+        *self = Self((bits >> 32) as u32, bits as u32);
+    }
+
+    /// Returns the folded state ID.
+    fn id(&self) -> u64 {
+        self.bits() & STATE_MASK
+    }
+
+    /// Sets the folded state ID `id` and marks the state as valid, keeping the drawn area.
+    fn set_id(&mut self, id: u64) {
+        self.set_bits((self.bits() & !STATE_MASK) | VALID | id);
+    }
+
+    fn valid(&self) -> bool {
+        self.bits() & VALID != 0
+    }
+
     /// Creates an empty state that will trigger a redraw.
     pub fn empty() -> Self {
-        Self(0, false)
+        Self(0, 0)
     }
 
     /// Creates a new state with the given state ID.
+    ///
+    /// State IDs are stored in 23 bits: IDs below 2^23 (e.g. small counters or enum values)
+    /// are always told apart, larger ones are folded into these bits.
     pub fn state(state: u32) -> Self {
-        Self(state, true)
+        let mut smartstate = Self::empty();
+        smartstate.set_state(state);
+        smartstate
     }
 
     /// Sets the current state ID and marks it as valid.
     pub fn set_state(&mut self, state: u32) {
-        self.0 = state;
-        self.1 = true;
+        self.set_id(fold(state));
     }
 
     /// Sets the current state ID based on a hash of the provided value.
     pub fn set_state_hashed<T: Hash + ?Sized>(&mut self, to_hash: &T) {
-        self.0 = HASH_STATE.hash_one(to_hash) as u32;
-        self.1 = true;
+        self.set_id(HASH_STATE.hash_one(to_hash) & STATE_MASK);
     }
 
     /// Returns true if this is an empty/invalid state.
     pub fn is_empty(&self) -> bool {
-        !self.1
+        !self.valid()
     }
 
     /// Returns true if this matches the given state ID and is valid.
     pub fn is_state(&self, state: u32) -> bool {
-        self.1 && self.0 == state
+        self.valid() && self.id() == fold(state)
     }
 
     /// Returns true if this matches the given state ID and is valid, using a hash.
     pub fn is_state_hashed<T: Hash + ?Sized>(&self, to_hash: &T) -> bool {
-        self.1 && self.0 == HASH_STATE.hash_one(to_hash) as u32
+        self.valid() && self.id() == HASH_STATE.hash_one(to_hash) & STATE_MASK
+    }
+
+    /// Mixes `salt` into the state ID, so that the state differs whenever the salt does.
+    ///
+    /// Widgets mix in the [Ui::style_key](crate::ui::Ui::style_key), so that they are redrawn
+    /// when they are drawn with a different [StylePatch](crate::style::StylePatch).
+    /// A salt of `0` leaves the state unchanged.
+    pub fn mix(&mut self, salt: u32) {
+        if salt != 0 {
+            let id = HASH_STATE.hash_one((self.id(), salt)) & STATE_MASK;
+            self.set_bits((self.bits() & !STATE_MASK) | id);
+        }
     }
 
     /// Forces a redraw by invalidating the current state.
     pub fn force_redraw(&mut self) {
-        self.1 = false;
+        self.set_bits(self.bits() & !VALID);
+    }
+
+    /// Returns the area the widget was drawn at in the last frame, if it was added with a
+    /// [Ui](crate::ui::Ui) (see [Drawn Areas](Smartstate#drawn-areas)).
+    pub fn drawn_area(&self) -> Option<Rectangle> {
+        unpack_area(self.bits() >> AREA_SHIFT)
+    }
+
+    /// Records the area the widget was drawn at.
+    pub(crate) fn set_drawn_area(&mut self, area: &Rectangle) {
+        let state = self.bits() & (STATE_MASK | VALID);
+        self.set_bits(state | (pack_area(area) << AREA_SHIFT));
+    }
+
+    /// Returns `area` as a smartstate records it (see [Smartstate::drawn_area]).
+    pub(crate) fn recorded_area(area: &Rectangle) -> Option<Rectangle> {
+        unpack_area(pack_area(area))
+    }
+
+    /// Forces a redraw if the widget was drawn within `area` in the last frame (see
+    /// [Drawn Areas](Smartstate#drawn-areas)).
+    ///
+    /// ## Returns
+    ///
+    /// Whether the widget was within `area`.
+    pub fn invalidate_area(&mut self, area: &Rectangle) -> bool {
+        let hit = self
+            .drawn_area()
+            .is_some_and(|drawn| drawn.intersection(area).size != Size::zero());
+        if hit {
+            self.force_redraw();
+        }
+        hit
     }
 }
 
 impl PartialEq for Smartstate {
     fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0 && self.1 && other.1
+        self.id() == other.id() && self.valid() && other.valid()
+    }
+}
+
+impl core::fmt::Debug for Smartstate {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Smartstate")
+            .field("id", &self.id())
+            .field("valid", &self.valid())
+            .field("drawn_area", &self.drawn_area())
+            .finish()
     }
 }
 
@@ -272,18 +438,76 @@ impl PartialEq for Smartstate {
 /// // Reset counter for next frame
 /// provider.restart_counter();
 ///
-pub struct SmartstateProvider<const N: usize = 16> {
+/// # Conditional Widgets
+///
+/// Sequential smartstates are assigned by call order, so a widget that is only added sometimes
+/// shifts the smartstates of all widgets after it, and they may compare against the state of
+/// another widget (and not redraw when they should). Give the widgets of conditional parts of
+/// the UI keyed smartstates instead: [get_keyed](SmartstateProvider::get_keyed) returns the same
+/// smartstate for the same key, independent of the call order.
+///
+/// Keyed smartstates live in `K` extra slots. When all slots are taken, the slot unused for the
+/// longest time is reused (and its widget redraws once it's back). If more than `K` keys are
+/// used in a single frame, the extra widgets redraw every frame and
+/// [keyed_overflows](SmartstateProvider::keyed_overflows) counts them, so increase `K` then.
+///
+/// ```
+/// # use embedded_graphics::pixelcolor::Rgb565;
+/// # use embedded_graphics::mock_display::MockDisplay;
+/// use kolibri_embedded_gui::prelude::*;
+/// # let mut display = MockDisplay::<Rgb565>::new();
+/// # display.set_allow_overdraw(true);
+/// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+/// # let error: Option<&str> = None;
+/// // 10 sequential and 4 keyed smartstates
+/// let mut smartstates = SmartstateProvider::<10, 4>::new();
+///
+/// smartstates.restart_counter();
+/// if let Some(error) = error {
+///     ui.add(Label::new(error).smartstate(smartstates.get_keyed(1)));
+/// }
+/// ui.add(Button::new("Retry").smartstate(smartstates.get_keyed(2)));
+/// ```
+pub struct SmartstateProvider<const N: usize = 16, const K: usize = 0> {
     states: [Smartstate; N],
     pos: usize,
+    keyed: [KeyedSlot; K],
+    /// Number of [restart_counter](SmartstateProvider::restart_counter) calls, to find the
+    /// least recently used keyed slot
+    frame: u32,
+    /// Returned by [get_keyed](SmartstateProvider::get_keyed) when all keyed slots are in use
+    overflow: Smartstate,
+    overflows: u32,
 }
 
-impl<const N: usize> SmartstateProvider<N> {
-    /// Creates a new provider with N empty smartstates.
+/// A keyed smartstate of a [SmartstateProvider]
+#[derive(Clone, Copy, Debug)]
+struct KeyedSlot {
+    key: Option<u32>,
+    state: Smartstate,
+    /// Frame in which the slot was last used
+    frame: u32,
+}
+
+impl<const N: usize, const K: usize> SmartstateProvider<N, K> {
+    /// The size of the provider in bytes, mostly its N + K smartstates of [Smartstate::SIZE]
+    /// bytes each
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// Creates a new provider with N empty smartstates (and K empty keyed smartstates).
     #[inline(always)]
     pub fn new() -> Self {
         Self {
             states: [Smartstate::empty(); N],
             pos: 0,
+            keyed: [KeyedSlot {
+                key: None,
+                state: Smartstate::empty(),
+                frame: 0,
+            }; K],
+            frame: 0,
+            overflow: Smartstate::empty(),
+            overflows: 0,
         }
     }
 
@@ -292,6 +516,7 @@ impl<const N: usize> SmartstateProvider<N> {
     #[inline(always)]
     pub fn restart_counter(&mut self) {
         self.pos = 0;
+        self.frame = self.frame.wrapping_add(1);
     }
 
     /// Returns the total number of smartstates (N).
@@ -332,6 +557,19 @@ impl<const N: usize> SmartstateProvider<N> {
         state
     }
 
+    /// Gets the next smartstate together with its retained state in `store`, and advances the
+    /// position counter (see [StateStore]).
+    ///
+    /// # Panics
+    /// Panics if no more smartstates are available (pos >= N).
+    pub fn nxt_with<'s>(
+        &'s mut self,
+        store: &'s mut StateStore<N>,
+    ) -> (&'s mut Smartstate, &'s mut Slot) {
+        let slot = store.slot(self.pos);
+        (self.nxt(), slot)
+    }
+
     /// Gets the current smartstate (at pos-1).
     ///
     /// # Panics
@@ -388,12 +626,84 @@ impl<const N: usize> SmartstateProvider<N> {
             .expect("ERROR: Invalid index in SmartstateProvider!")
     }
 
-    /// Forces a redraw of all smartstates.
+    /// Gets the keyed smartstate for `key`, independent of the position counter.
+    ///
+    /// The same key returns the same smartstate in every frame, so widgets that are only added
+    /// sometimes don't affect the smartstates of other widgets (see
+    /// [Conditional Widgets](SmartstateProvider#conditional-widgets)). Use every key only once
+    /// per frame.
+    ///
+    /// If the key is new and all keyed slots are taken, the least recently used slot is reused
+    /// for it. If all slots were used in this frame already, an empty smartstate is returned,
+    /// so the widget redraws (see [keyed_overflows](SmartstateProvider::keyed_overflows)).
+    pub fn get_keyed(&mut self, key: u32) -> &mut Smartstate {
+        let frame = self.frame;
+        let slot = match self.keyed.iter().position(|slot| slot.key == Some(key)) {
+            Some(slot) => slot,
+            None => {
+                // a free slot, or the one unused for the longest time
+                let Some((slot, _)) = self
+                    .keyed
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, slot)| slot.key.is_none() || slot.frame != frame)
+                    .min_by_key(|(_, slot)| {
+                        (slot.key.is_some(), Reverse(frame.wrapping_sub(slot.frame)))
+                    })
+                else {
+                    self.overflows = self.overflows.saturating_add(1);
+                    trace!("Keyed smartstates full, increase K in SmartstateProvider<N, K>");
+                    self.overflow = Smartstate::empty();
+                    return &mut self.overflow;
+                };
+                self.keyed[slot] = KeyedSlot {
+                    key: Some(key),
+                    state: Smartstate::empty(),
+                    frame,
+                };
+                slot
+            }
+        };
+        self.keyed[slot].frame = frame;
+        &mut self.keyed[slot].state
+    }
+
+    /// Gets the keyed smartstate for a hash of `key`, like [get_keyed](SmartstateProvider::get_keyed).
+    pub fn get_keyed_hashed<T: Hash + ?Sized>(&mut self, key: &T) -> &mut Smartstate {
+        self.get_keyed(HASH_STATE.hash_one(key) as u32)
+    }
+
+    /// Returns how often [get_keyed](SmartstateProvider::get_keyed) found no free slot, because
+    /// more than `K` keys were used in one frame.
+    pub fn keyed_overflows(&self) -> u32 {
+        self.overflows
+    }
+
+    /// Forces a redraw of all smartstates (including the keyed ones).
     #[inline(always)]
     pub fn force_redraw_all(&mut self) {
         for state in self.states.iter_mut() {
             state.force_redraw();
         }
+        for slot in self.keyed.iter_mut() {
+            slot.state.force_redraw();
+        }
+    }
+
+    /// Forces a redraw of all smartstates (including the keyed ones) whose widgets were drawn
+    /// within `area` in the last frame, e.g. after a popup covering it was closed, or the app
+    /// drew over the UI (see [Drawn Areas](Smartstate#drawn-areas)).
+    ///
+    /// ## Returns
+    ///
+    /// The number of invalidated smartstates.
+    pub fn invalidate_area(&mut self, area: Rectangle) -> usize {
+        let keyed = self.keyed.iter_mut().map(|slot| &mut slot.state);
+        self.states
+            .iter_mut()
+            .chain(keyed)
+            .filter_map(|state| state.invalidate_area(&area).then_some(()))
+            .count()
     }
 
     /// Force redraw in all smartstates after (and including) the current position.
@@ -441,7 +751,161 @@ impl<const N: usize> SmartstateProvider<N> {
     }
 }
 
-impl<const N: usize> Default for SmartstateProvider<N> {
+impl<const N: usize, const K: usize> Default for SmartstateProvider<N, K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Retained state of a single widget: two 32-bit words, with typed accessors for what widgets
+/// keep between frames.
+///
+/// The first word holds either a [press origin](Slot::press_origin) or a
+/// [count](Slot::count), the second a [timer](Slot::timer). A widget decides which ones it
+/// uses, so one slot must only be used by one widget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Slot {
+    value: u32,
+    /// The start of the timer in milliseconds (wrapping), or [Slot::STOPPED]
+    timer: u32,
+}
+
+impl Slot {
+    /// The value of a stopped timer
+    const STOPPED: u32 = u32::MAX;
+
+    /// An empty slot: no press origin, a count of 0 and a stopped timer
+    pub const EMPTY: Self = Self {
+        value: 0,
+        timer: Self::STOPPED,
+    };
+
+    /// Returns where the widget was pressed, if it was.
+    pub fn press_origin(&self) -> Option<Point> {
+        // stored with the sign bits flipped, so that 0 is "none"
+        (self.value != 0).then(|| {
+            let value = self.value ^ 0x8000_8000;
+            Point::new((value as u16 as i16).into(), ((value >> 16) as i16).into())
+        })
+    }
+
+    /// Sets (or clears) where the widget was pressed. The coordinates are clamped to ±32767.
+    pub fn set_press_origin(&mut self, origin: Option<Point>) {
+        let clamp = |v: i32| v.clamp(-i16::MAX as i32, i16::MAX as i32) as i16 as u16 as u32;
+        self.value = origin.map_or(0, |p| (clamp(p.x) | clamp(p.y) << 16) ^ 0x8000_8000);
+    }
+
+    /// Returns the count, e.g. of clicks fired while a button is held.
+    pub fn count(&self) -> u32 {
+        self.value
+    }
+
+    /// Sets the count.
+    pub fn set_count(&mut self, count: u32) {
+        self.value = count;
+    }
+
+    /// Returns the milliseconds since the timer was started at a frame time
+    /// ([Ui::now](crate::ui::Ui::now)), or `None` if it is stopped.
+    ///
+    /// The timer wraps after about 49 days.
+    pub fn timer(&self, now: u64) -> Option<u32> {
+        self.timer_running()
+            .then(|| (now as u32).wrapping_sub(self.timer))
+    }
+
+    /// Returns whether the timer is running.
+    pub fn timer_running(&self) -> bool {
+        self.timer != Self::STOPPED
+    }
+
+    /// Starts the timer at the frame time `now`.
+    pub fn start_timer(&mut self, now: u64) {
+        // off by a millisecond at the one time that looks like a stopped timer
+        self.timer = (now as u32).min(Self::STOPPED - 1);
+    }
+
+    /// Stops the timer.
+    pub fn stop_timer(&mut self) {
+        self.timer = Self::STOPPED;
+    }
+
+    /// Empties the slot, e.g. when the widget is released.
+    pub fn clear(&mut self) {
+        *self = Self::EMPTY;
+    }
+}
+
+impl Default for Slot {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+/// Retained state for the widgets of a [SmartstateProvider], one [Slot] per smartstate.
+///
+/// Some widgets keep state between frames, like the press time of a
+/// [repeating button](crate::button::Button::repeat_in). Instead of creating that state for
+/// each widget, own a store next to the provider, with the same `N`, and get the slot of each
+/// smartstate with [SmartstateProvider::nxt_with]. Widgets only use a slot if they are given
+/// one, and behave as without the feature otherwise.
+///
+/// A slot takes 8 bytes, so a store costs `8 * N` bytes ([StateStore::SIZE]), on top of the
+/// provider's `12 * N` for the smartstates.
+///
+/// ```
+/// use kolibri_embedded_gui::prelude::*;
+/// use kolibri_embedded_gui::smartstate::StateStore;
+/// # use embedded_graphics::pixelcolor::Rgb565;
+/// # use embedded_graphics::mock_display::MockDisplay;
+/// # let mut display = MockDisplay::<Rgb565>::new();
+/// # display.set_allow_overdraw(true);
+/// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+/// let mut smartstates = SmartstateProvider::<16>::new();
+/// let mut store = StateStore::<16>::new();
+/// # let mut volume = 0;
+///
+/// // in the UI loop
+/// smartstates.restart_counter();
+/// let (smartstate, slot) = smartstates.nxt_with(&mut store);
+/// if ui.add(Button::new("+").smartstate(smartstate).repeat_in(slot, 500, 100)).clicked() {
+///     volume += 1;
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StateStore<const N: usize> {
+    slots: [Slot; N],
+}
+
+impl<const N: usize> StateStore<N> {
+    /// The size of the store in bytes
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// Creates a store of N empty slots.
+    pub const fn new() -> Self {
+        Self {
+            slots: [Slot::EMPTY; N],
+        }
+    }
+
+    /// Returns the slot at `index`, the position of its smartstate in the provider.
+    ///
+    /// # Panics
+    /// Panics if `index` >= N.
+    pub fn slot(&mut self, index: usize) -> &mut Slot {
+        self.slots
+            .get_mut(index)
+            .expect("ERROR: StateStore too small! Use the same N as the SmartstateProvider.")
+    }
+
+    /// Empties all slots, e.g. when switching to another screen.
+    pub fn clear(&mut self) {
+        self.slots.fill(Slot::EMPTY);
+    }
+}
+
+impl<const N: usize> Default for StateStore<N> {
     fn default() -> Self {
         Self::new()
     }
@@ -523,7 +987,7 @@ mod tests {
     fn test_smartstate_empty() {
         let state = Smartstate::empty();
         assert!(state.is_empty());
-        assert!(!state.1);
+        assert!(!state.valid());
     }
 
     #[test]
@@ -575,6 +1039,85 @@ mod tests {
         assert_ne!(state1, state4);
     }
 
+    #[test]
+    fn the_valid_flag_and_the_drawn_area_are_independent() {
+        assert_eq!(Smartstate::SIZE, 8);
+        let area = Rectangle::new(Point::new(-1, 10), Size::new(40, 1013));
+        let mut state = Smartstate::empty();
+        state.set_drawn_area(&area);
+        assert!(state.is_empty());
+        assert_eq!(state.drawn_area(), Some(area));
+
+        state.set_state(7);
+        assert!(state.is_state(7));
+        assert_eq!(state.drawn_area(), Some(area));
+        state.set_drawn_area(&Rectangle::new(Point::new(-5, 3), Size::new(70000, 70000)));
+        assert!(state.is_state(7));
+        let clamped = Rectangle::with_corners(Point::new(-1, 3), Point::new_equal(1022));
+        assert_eq!(state.drawn_area(), Some(clamped));
+        state.force_redraw();
+        assert!(state.is_empty());
+        assert_eq!(state.drawn_area(), Some(clamped));
+        state.mix(3);
+        assert_eq!(state.drawn_area(), Some(clamped));
+
+        // nothing visible is known about an area entirely above and left of the target
+        state.set_drawn_area(&Rectangle::new(Point::new(-20, -20), Size::new(10, 10)));
+        assert_eq!(state.drawn_area(), None);
+        state.set_drawn_area(&Rectangle::zero());
+        assert_eq!(state.drawn_area(), None);
+    }
+
+    #[test]
+    fn state_ids_keep_their_bits_apart() {
+        let mut state = Smartstate::state(0);
+        for id in [
+            1,
+            2,
+            1 << 22,
+            (1 << 23) - 1,
+            1 << 23,
+            u32::MAX,
+            1.0f32.to_bits(),
+        ] {
+            assert!(!state.is_state(id), "{id}");
+            state.set_state(id);
+            assert!(state.is_state(id));
+        }
+        assert!(!state.is_state(2.0f32.to_bits()));
+        assert!(!state.is_state_hashed(&2.0f32.to_bits()));
+    }
+
+    #[test]
+    fn slots_keep_press_origins_counts_and_timers() {
+        let mut slot = Slot::default();
+        assert_eq!(slot.press_origin(), None);
+        assert_eq!(slot.timer(100), None);
+        for origin in [Point::zero(), Point::new(-3, 70), Point::new(40000, -40000)] {
+            slot.set_press_origin(Some(origin));
+            let clamped = Point::new(origin.x.clamp(-32767, 32767), origin.y.clamp(-32767, 32767));
+            assert_eq!(slot.press_origin(), Some(clamped));
+        }
+        slot.set_count(3);
+        assert_eq!(slot.count(), 3);
+
+        // the timer wraps with the frame time
+        slot.start_timer(u32::MAX as u64 - 10);
+        assert_eq!(slot.timer(u32::MAX as u64 + 20), Some(30));
+        slot.stop_timer();
+        assert!(!slot.timer_running());
+
+        // the slots follow the smartstates
+        let mut smartstates = SmartstateProvider::<2>::new();
+        let mut store = StateStore::<2>::new();
+        smartstates.nxt_with(&mut store).1.set_count(1);
+        smartstates.nxt_with(&mut store).1.set_count(2);
+        smartstates.restart_counter();
+        assert_eq!(smartstates.nxt_with(&mut store).1.count(), 1);
+        assert_eq!(store.slot(1).count(), 2);
+        assert_eq!(StateStore::<2>::SIZE, 16);
+    }
+
     // SmartstateProvider tests
     #[test]
     fn test_provider_new_and_default() {
@@ -783,4 +1326,112 @@ mod tests {
         assert!(provider.states[3].is_empty());
         assert!(!provider.states[4].is_empty());
     }
+
+    #[test]
+    fn keyed_slots_are_reused_least_recently_used_first() {
+        let mut provider = SmartstateProvider::<0, 2>::new();
+        provider.restart_counter();
+        provider.get_keyed(1).set_state(10);
+        provider.get_keyed(2).set_state(20);
+        // a third key in the same frame doesn't evict anything
+        assert!(provider.get_keyed(3).is_empty());
+        assert_eq!(provider.keyed_overflows(), 1);
+
+        provider.restart_counter();
+        assert!(provider.get_keyed(2).is_state(20));
+        provider.restart_counter();
+        // key 1 was unused for the longest time
+        assert!(provider.get_keyed(3).is_empty());
+        assert!(provider.get_keyed(2).is_state(20));
+        assert!(provider.get_keyed_hashed("one").is_empty());
+        assert_eq!(provider.keyed_overflows(), 2);
+    }
+
+    #[test]
+    fn invalidating_an_area_redraws_the_widgets_in_it() {
+        use crate::label::Label;
+        use crate::style::medsize_rgb565_style;
+        use crate::ui::Ui;
+        use embedded_graphics::mock_display::MockDisplay;
+        use embedded_graphics::pixelcolor::Rgb565;
+
+        // returns the labels' areas and the number of redrawn widgets
+        fn frame(
+            display: &mut MockDisplay<Rgb565>,
+            smartstates: &mut SmartstateProvider<2, 1>,
+        ) -> ([Rectangle; 3], u32) {
+            let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            let a = ui.add(Label::new("A").smartstate(smartstates.nxt()));
+            let b = ui.add(Label::new("B").smartstate(smartstates.nxt()));
+            let c = ui.add(Label::new("C").smartstate(smartstates.get_keyed(7)));
+            let areas = [a, b, c].map(|response| response.area());
+            (areas, ui.perf().widgets_redrawn)
+        }
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::new();
+        let (areas, redrawn) = frame(&mut display, &mut smartstates);
+        assert_eq!(redrawn, 3);
+        assert_eq!(smartstates.get(1).drawn_area(), Some(areas[1]));
+        assert_eq!(smartstates.get_keyed(7).drawn_area(), Some(areas[2]));
+
+        // something covered the second and third label
+        let covered = Rectangle::new(areas[1].center(), Size::new(1, 40));
+        assert_eq!(smartstates.invalidate_area(covered), 2);
+        let (_, redrawn) = frame(&mut display, &mut smartstates);
+        assert_eq!(redrawn, 2);
+        let (_, redrawn) = frame(&mut display, &mut smartstates);
+        assert_eq!(redrawn, 0);
+    }
+
+    #[test]
+    fn keyed_smartstates_survive_conditional_widgets() {
+        use crate::button::Button;
+        use crate::label::Label;
+        use crate::style::medsize_rgb565_style;
+        use crate::ui::{Interaction, Ui};
+        use embedded_graphics::mock_display::MockDisplay;
+        use embedded_graphics::pixelcolor::Rgb565;
+        use embedded_graphics::prelude::*;
+        use embedded_graphics::primitives::Rectangle;
+
+        // an optional label in the first row, and a button in the second row that may be hovered
+        fn frame(
+            display: &mut MockDisplay<Rgb565>,
+            smartstates: &mut SmartstateProvider<0, 4>,
+            error: bool,
+            hover: bool,
+        ) -> Rectangle {
+            let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+            if hover {
+                ui.interact(Interaction::Hover(Point::new(10, 40)));
+            }
+            smartstates.restart_counter();
+            ui.add_horizontal(Button::new("A").smartstate(smartstates.get_keyed(1)));
+            if error {
+                ui.add_horizontal(Label::new("!").smartstate(smartstates.get_keyed(2)));
+            }
+            ui.new_row();
+            ui.add(Button::new("Retry").smartstate(smartstates.get_keyed(3)))
+                .internal
+                .area
+        }
+
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::new();
+        for (error, hover) in [(true, false), (false, true), (true, false)] {
+            frame(&mut display, &mut smartstates, error, hover);
+        }
+        // the button isn't left drawn as hovered, it matches a fresh drawing
+        let mut fresh = MockDisplay::new();
+        fresh.set_allow_overdraw(true);
+        let retry = frame(&mut fresh, &mut SmartstateProvider::new(), true, false);
+        assert!(retry.contains(Point::new(10, 40)));
+        for p in retry.points() {
+            assert_eq!(display.get_pixel(p), fresh.get_pixel(p), "{p:?}");
+        }
+    }
 }
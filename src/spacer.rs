@@ -15,11 +15,8 @@
 //! ```no_run
 //! # use embedded_graphics::pixelcolor::Rgb565;
 //! # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-//! # use kolibri_embedded_gui::style::medsize_rgb565_style;
-//! # use kolibri_embedded_gui::ui::Ui;
+//! # use kolibri_embedded_gui::prelude::*;
 //! # use embedded_graphics::prelude::*;
-//! # use kolibri_embedded_gui::spacer::Spacer;
-//! # use kolibri_embedded_gui::label::Label;
 //! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 //! # let output_settings = OutputSettingsBuilder::new().build();
 //! # let mut window = Window::new("Kolibri Example", &output_settings);
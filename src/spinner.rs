@@ -0,0 +1,247 @@
+//! # Spinner Widget
+//!
+//! A [Spinner] is an activity indicator for operations of unknown duration (e.g. waiting for
+//! the network): an arc rotating around its center.
+//!
+//! This module is only available with the `indicators` feature (on by default).
+//!
+//! The rotation is driven by the frame time (see [Ui::begin_frame]). Without a frame time,
+//! the spinner is a static three-quarter arc. While rotating, it asks for the next frame
+//! with [Ui::request_repaint].
+//!
+//! ## Performance
+//!
+//! The arc rotates in steps of 30°, so it only redraws when it moves to the next step. With a
+//! smartstate, a step only erases the sector the arc left and draws the sector it entered,
+//! instead of the whole arc. With a buffer (see [Ui::set_buffer]), the whole spinner is
+//! drawn into the buffer and flushed instead.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! let start = std::time::Instant::now();
+//!
+//! loop {
+//!     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//!     ui.begin_frame(start.elapsed().as_millis() as u64);
+//!     smartstates.restart_counter();
+//!
+//!     ui.add(Spinner::new().diameter(32).smartstate(smartstates.nxt()));
+//! }
+//! ```
+
+use crate::smartstate::{Container, Smartstate};
+use crate::ui::{GuiError, GuiResult, Response, Ui, Widget};
+use core::cmp::max;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{AngleUnit, Size};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Arc, PrimitiveStyle};
+
+/// Number of positions of the arc per rotation
+const STEPS: u32 = 12;
+/// Degrees between two positions of the arc
+const STEP_DEGREES: u32 = 360 / STEPS;
+/// The arc covers three quarters of the circle
+const SWEEP_DEGREES: u32 = 270;
+
+/// An indeterminate activity indicator, see the [module documentation](self).
+pub struct Spinner<'a> {
+    diameter: Option<u32>,
+    period_ms: u32,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl Default for Spinner<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Spinner<'a> {
+    /// Creates a spinner as high as the style's default widget height, rotating once per
+    /// second.
+    pub fn new() -> Self {
+        Self {
+            diameter: None,
+            period_ms: 1000,
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Sets the diameter of the spinner.
+    pub fn diameter(mut self, diameter: u32) -> Self {
+        self.diameter = Some(diameter);
+        self
+    }
+
+    /// Sets the time of a full rotation (1000ms by default).
+    pub fn period_ms(mut self, period_ms: u32) -> Self {
+        self.period_ms = max(period_ms, 1);
+        self
+    }
+
+    /// Adds a smartstate, so that the spinner only draws when the arc moves.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+impl Widget for Spinner<'_> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let diameter = self.diameter.unwrap_or(ui.style().default_widget_height);
+        let iresponse = ui.allocate_space(Size::new(diameter, diameter))?;
+
+        // the position of the arc: static without a frame time
+        let step = match ui.now() {
+            Some(now) => {
+                ((now % self.period_ms as u64) * STEPS as u64 / self.period_ms as u64) as u32
+            }
+            None => STEPS,
+        };
+        if step < STEPS {
+            ui.request_repaint();
+        }
+        let style_key = ui.style_key();
+        let encode = |step: u32| {
+            let mut state = Smartstate::state((diameter << 8) | step);
+            state.mix(style_key);
+            state
+        };
+
+        let prevstate = self.smartstate.clone_inner();
+        self.smartstate.modify(|st| *st = encode(step));
+        if self.smartstate.eq_option(&prevstate) && !ui.invalidated(iresponse.area) {
+            return Ok(Response::new(iresponse).set_redraw(false));
+        }
+        // the previous step, if the arc was drawn there and only moved a bit since
+        let previous = prevstate
+            .and_then(|prev| (0..STEPS).find(|&step| prev == encode(step)))
+            .filter(|&previous| step < STEPS && (step + STEPS - previous) % STEPS <= STEPS / 4)
+            .filter(|_| !ui.invalidated(iresponse.area));
+
+        // the stroke fits into the area, so the arc's bounding box is the allocated area
+        let stroke = max(ui.style().border_width, 2).min(diameter / 2);
+        let arc = |start: u32, sweep: u32| {
+            Arc::new(
+                iresponse.area.top_left + Point::new(stroke as i32 / 2, stroke as i32 / 2),
+                diameter.saturating_sub(stroke),
+                (start as f32).deg(),
+                (sweep as f32).deg(),
+            )
+        };
+        let fg = PrimitiveStyle::with_stroke(ui.style().primary_color, stroke);
+        let bg = PrimitiveStyle::with_stroke(ui.style().background_color, stroke);
+        let start = (step % STEPS) * STEP_DEGREES;
+
+        ui.start_drawing(&iresponse.area);
+        let buffered = ui.clear_buffer_raw(ui.style().background_color);
+        match previous {
+            Some(previous) if !buffered => {
+                // erase the sector the arc left, and draw the sector it entered
+                let moved = (step + STEPS - previous) % STEPS * STEP_DEGREES;
+                let previous_start = previous * STEP_DEGREES;
+                ui.draw(&arc(previous_start, moved).into_styled(bg))
+                    .and_then(|_| {
+                        // (redraws the pixels on the boundary, which both sectors contain)
+                        ui.draw(&arc(start, 1).into_styled(fg))
+                    })
+                    .and_then(|_| {
+                        ui.draw(&arc(previous_start + SWEEP_DEGREES, moved).into_styled(fg))
+                    })
+                    .map_err(|_| GuiError::DrawError(Some("Couldn't draw Spinner")))?;
+            }
+            _ => {
+                if !buffered && !ui.cleared() {
+                    ui.clear_area(iresponse.area)?;
+                }
+                ui.draw(&arc(start, SWEEP_DEGREES).into_styled(fg))
+                    .map_err(|_| GuiError::DrawError(Some("Couldn't draw Spinner")))?;
+            }
+        }
+        ui.finalize()?;
+
+        Ok(Response::new(iresponse).set_redraw(true))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// Draws a frame at `now`, returning the number of pixels drawn
+    fn frame(
+        display: &mut MockDisplay<Rgb565>,
+        smartstate: &mut Smartstate,
+        now: Option<u64>,
+    ) -> u32 {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        if let Some(now) = now {
+            ui.begin_frame(now);
+        }
+        ui.add(Spinner::new().diameter(40).smartstate(smartstate));
+        ui.perf().pixels_drawn
+    }
+
+    #[test]
+    fn redraws_only_when_the_arc_moves() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+
+        let full = frame(&mut display, &mut smartstate, Some(0));
+        assert!(full > 0);
+        // still the same step
+        assert_eq!(frame(&mut display, &mut smartstate, Some(50)), 0);
+        // the next step only draws the sectors that changed
+        let step = frame(&mut display, &mut smartstate, Some(90));
+        assert!(step > 0 && step < full / 2, "{step} of {full}");
+    }
+
+    #[test]
+    fn delta_drawing_matches_full_drawing() {
+        let mut smartstate = Smartstate::empty();
+        let mut stepped = MockDisplay::new();
+        stepped.set_allow_overdraw(true);
+        for now in [0, 90, 170, 250] {
+            frame(&mut stepped, &mut smartstate, Some(now));
+        }
+
+        let mut full = MockDisplay::new();
+        full.set_allow_overdraw(true);
+        frame(&mut full, &mut Smartstate::empty(), Some(250));
+
+        // compare the colors, as the stepped display also has background pixels
+        let style = medsize_rgb565_style();
+        for p in full.affected_area().points() {
+            let color = |d: &MockDisplay<Rgb565>| d.get_pixel(p).unwrap_or(style.background_color);
+            assert_eq!(color(&stepped), color(&full), "{p:?}");
+        }
+    }
+
+    #[test]
+    fn static_without_frame_time() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+
+        assert!(frame(&mut display, &mut smartstate, None) > 0);
+        assert_eq!(frame(&mut display, &mut smartstate, None), 0);
+    }
+}
@@ -13,17 +13,11 @@
 //! # use embedded_graphics::pixelcolor::Rgb565;
 //! # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
 //! # use embedded_graphics::prelude::*;
-//! # use embedded_graphics::primitives::Rectangle;
-//! # use embedded_iconoir::prelude::*;
-//! # use embedded_iconoir::size12px;
-//! # use kolibri_embedded_gui::ui::*;
-//! # use kolibri_embedded_gui::label::*;
-//! # use kolibri_embedded_gui::smartstate::*;
+//! # use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+//! use kolibri_embedded_gui::prelude::*;
 //! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 //! # let output_settings = OutputSettingsBuilder::new().build();
 //! # let mut window = Window::new("Kolibri Example", &output_settings);
-//! use kolibri_embedded_gui::style::medsize_rgb565_style;
-//! use kolibri_embedded_gui::ui::Ui;
 //!
 //! // Create UI with dark theme
 //! let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
@@ -35,233 +29,421 @@
 //! # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
 //! # use embedded_graphics::prelude::*;
 //! # use embedded_graphics::primitives::Rectangle;
-//! # use embedded_iconoir::prelude::*;
-//! # use embedded_iconoir::size12px;
-//! # use kolibri_embedded_gui::ui::*;
-//! # use kolibri_embedded_gui::label::*;
-//! # use kolibri_embedded_gui::smartstate::*;
+//! use kolibri_embedded_gui::prelude::*;
 //! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 //! # let output_settings = OutputSettingsBuilder::new().build();
 //! # let mut window = Window::new("Kolibri Example", &output_settings);
-//! use kolibri_embedded_gui::style::{medsize_rgb565_style, medsize_light_rgb565_style};
 //!
 //! let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
 //! // Later...
 //! *ui.style_mut() = medsize_light_rgb565_style(); // Switch to light theme
 //! ```
+//!
+//! The predefined themes are also available as constants (e.g. [MEDSIZE_RGB565_STYLE]),
+//! so they can be placed in flash instead of being built at runtime:
+//! ```
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! use kolibri_embedded_gui::prelude::*;
+//! use kolibri_embedded_gui::style::MEDSIZE_RGB565_STYLE;
+//!
+//! static STYLE: Style<Rgb565> = MEDSIZE_RGB565_STYLE;
+//! ```
 
+use crate::text::TextBackend;
 use embedded_graphics::mono_font::{self, MonoFont};
-use embedded_graphics::pixelcolor::{PixelColor, Rgb565};
+use embedded_graphics::pixelcolor::{PixelColor, Rgb565, Rgb888};
 use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
 
 /// Controls spacing between UI elements.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spacing {
     /// Space between adjacent items in the UI
+    #[cfg_attr(feature = "serde", serde(with = "crate::style_de::size"))]
     pub item_spacing: Size,
     /// Internal padding within buttons
+    #[cfg_attr(feature = "serde", serde(with = "crate::style_de::size"))]
     pub button_padding: Size,
     /// Padding around the border of a widget (e.g. a checkbox)
+    #[cfg_attr(feature = "serde", serde(with = "crate::style_de::size"))]
     pub default_padding: Size,
     /// Padding inside window borders
+    #[cfg_attr(feature = "serde", serde(with = "crate::style_de::size"))]
     pub window_border_padding: Size,
+    /// Pixels scrolled per detent of a mouse wheel or an encoder (see
+    /// [Interaction::Scroll](crate::ui::Interaction::Scroll))
+    #[cfg_attr(feature = "serde", serde(default = "default_scroll_step"))]
+    pub scroll_step: u32,
+    /// Distance in pixels a press may wander outside of a button it started on, and still be
+    /// held and released on it (see [Ui::check_held_press](crate::ui::Ui::check_held_press)),
+    /// against the jitter of touch panels
+    #[cfg_attr(feature = "serde", serde(default = "default_press_slop"))]
+    pub press_slop: u32,
+}
+
+/// For themes saved before [Spacing::scroll_step] existed
+#[cfg(feature = "serde")]
+fn default_scroll_step() -> u32 {
+    16
+}
+
+/// For themes saved before [Spacing::press_slop] existed
+#[cfg(feature = "serde")]
+fn default_press_slop() -> u32 {
+    4
+}
+
+impl Spacing {
+    /// Tight spacing for dense pages, e.g. tables of readings.
+    ///
+    /// Use it in a [Style], or for a section with [StylePatch::spacing].
+    pub const fn compact() -> Self {
+        Self {
+            item_spacing: Size::new(4, 2),
+            button_padding: Size::new(3, 2),
+            default_padding: Size::new(1, 1),
+            window_border_padding: Size::new(2, 2),
+            scroll_step: 12,
+            press_slop: 4,
+        }
+    }
+
+    /// Loose spacing for pages with few, large targets, e.g. menus on a touch screen.
+    pub const fn comfortable() -> Self {
+        Self {
+            item_spacing: Size::new(12, 8),
+            button_padding: Size::new(8, 7),
+            default_padding: Size::new(2, 2),
+            window_border_padding: Size::new(5, 5),
+            scroll_step: 24,
+            press_slop: 4,
+        }
+    }
 }
 
 /// Debug-friendly dark theme with visible borders for development.
 ///
 /// This theme uses high-contrast colors and visible borders to make UI layout
 /// and component boundaries clear during development.
+pub const MEDSIZE_RGB565_DEBUG_STYLE: Style<Rgb565> = Style {
+    background_color: Rgb565::BLACK,
+    item_background_color: Fill::Solid(Rgb565::CSS_GRAY),
+    highlight_item_background_color: Rgb565::new(0x1, 0x2, 0x1),
+    border_color: Rgb565::RED,
+    highlight_border_color: Rgb565::WHITE,
+    primary_color: Rgb565::CYAN,
+    secondary_color: Rgb565::YELLOW,
+    icon_color: Rgb565::WHITE,
+    text_color: Rgb565::WHITE,
+    default_widget_height: 16,
+    border_width: 1,
+    highlight_border_width: 1,
+    default_font: mono_font::iso_8859_10::FONT_9X15,
+    text_backend: TextBackend::Mono,
+    spacing: Spacing {
+        item_spacing: Size::new(8, 4),
+        button_padding: Size::new(2, 2),
+        default_padding: Size::new(3, 3),
+        window_border_padding: Size::new(3, 3),
+        scroll_step: 16,
+        press_slop: 4,
+    },
+    corner_radius: 8,
+    pressed_offset: Size::zero(),
+    shadow_color: None,
+    shadow_offset: Size::new(2, 2),
+};
+
+/// Debug-friendly dark theme with visible borders for development.
+///
+/// Returns [MEDSIZE_RGB565_DEBUG_STYLE].
 pub fn medsize_rgb565_debug_style() -> Style<Rgb565> {
-    Style {
-        background_color: Rgb565::BLACK,
-        item_background_color: Rgb565::CSS_GRAY,
-        highlight_item_background_color: Rgb565::new(0x1, 0x2, 0x1),
-        border_color: Rgb565::RED,
-        highlight_border_color: Rgb565::WHITE,
-        primary_color: Rgb565::CYAN,
-        secondary_color: Rgb565::YELLOW,
-        icon_color: Rgb565::WHITE,
-        text_color: Rgb565::WHITE,
-        default_widget_height: 16,
-        border_width: 1,
-        highlight_border_width: 1,
-        default_font: mono_font::iso_8859_10::FONT_9X15,
-        spacing: Spacing {
-            item_spacing: Size::new(8, 4),
-            button_padding: Size::new(2, 2),
-            default_padding: Size::new(3, 3),
-            window_border_padding: Size::new(3, 3),
-        },
-        corner_radius: 8,
-    }
+    MEDSIZE_RGB565_DEBUG_STYLE
+}
+
+/// Dark theme in [Rgb888], the canonical source for converting to other color types.
+///
+/// Author themes once in [Rgb888] and convert them at startup with [Style::convert] or
+/// [Style::map_colors]. Converted to [Rgb565], this is [medsize_rgb565_style].
+///
+/// # Example
+///
+/// ```
+/// # use embedded_graphics::pixelcolor::{BinaryColor, Rgb565};
+/// use kolibri_embedded_gui::prelude::*;
+///
+/// let tft: Style<Rgb565> = medsize_rgb888_base_style().convert();
+/// let oled: Style<BinaryColor> = medsize_rgb888_base_style().convert();
+/// ```
+pub const MEDSIZE_RGB888_BASE_STYLE: Style<Rgb888> = Style {
+    background_color: Rgb888::new(33, 32, 33), // pretty dark gray
+    item_background_color: Fill::Solid(Rgb888::new(16, 16, 16)), // darker gray
+    highlight_item_background_color: Rgb888::new(8, 8, 8),
+    border_color: Rgb888::WHITE,
+    highlight_border_color: Rgb888::WHITE,
+    primary_color: Rgb888::CSS_DARK_CYAN,
+    secondary_color: Rgb888::YELLOW,
+    icon_color: Rgb888::WHITE,
+    text_color: Rgb888::WHITE,
+    default_widget_height: 16,
+    border_width: 0,
+    highlight_border_width: 1,
+    default_font: mono_font::iso_8859_10::FONT_9X15,
+    text_backend: TextBackend::Mono,
+    spacing: Spacing {
+        item_spacing: Size::new(8, 4),
+        button_padding: Size::new(6, 5),
+        default_padding: Size::new(1, 1),
+        window_border_padding: Size::new(3, 3),
+        scroll_step: 16,
+        press_slop: 4,
+    },
+    corner_radius: 8,
+    pressed_offset: Size::zero(),
+    shadow_color: None,
+    shadow_offset: Size::new(2, 2),
+};
+
+/// Dark theme in [Rgb888], the canonical source for converting to other color types.
+///
+/// Returns [MEDSIZE_RGB888_BASE_STYLE].
+pub fn medsize_rgb888_base_style() -> Style<Rgb888> {
+    MEDSIZE_RGB888_BASE_STYLE
 }
 
 /// Dark theme for RGB565 displays.
 ///
 /// Features a dark gray background with cyan accents and white text.
+pub const MEDSIZE_RGB565_STYLE: Style<Rgb565> = Style {
+    background_color: Rgb565::new(0x4, 0x8, 0x4), // pretty dark gray
+    item_background_color: Fill::Solid(Rgb565::new(0x2, 0x4, 0x2)), // darker gray
+    highlight_item_background_color: Rgb565::new(0x1, 0x2, 0x1),
+    border_color: Rgb565::WHITE,
+    highlight_border_color: Rgb565::WHITE,
+    primary_color: Rgb565::CSS_DARK_CYAN,
+    secondary_color: Rgb565::YELLOW,
+    icon_color: Rgb565::WHITE,
+    text_color: Rgb565::WHITE,
+    default_widget_height: 16,
+    border_width: 0,
+    highlight_border_width: 1,
+    default_font: mono_font::iso_8859_10::FONT_9X15,
+    text_backend: TextBackend::Mono,
+    spacing: Spacing {
+        item_spacing: Size::new(8, 4),
+        button_padding: Size::new(6, 5),
+        default_padding: Size::new(1, 1),
+        window_border_padding: Size::new(3, 3),
+        scroll_step: 16,
+        press_slop: 4,
+    },
+    corner_radius: 8,
+    pressed_offset: Size::zero(),
+    shadow_color: None,
+    shadow_offset: Size::new(2, 2),
+};
+
+/// Dark theme for RGB565 displays.
+///
+/// Returns [MEDSIZE_RGB565_STYLE].
 pub fn medsize_rgb565_style() -> Style<Rgb565> {
-    Style {
-        background_color: Rgb565::new(0x4, 0x8, 0x4), // pretty dark gray
-        item_background_color: Rgb565::new(0x2, 0x4, 0x2), // darker gray
-        highlight_item_background_color: Rgb565::new(0x1, 0x2, 0x1),
-        border_color: Rgb565::WHITE,
-        highlight_border_color: Rgb565::WHITE,
-        primary_color: Rgb565::CSS_DARK_CYAN,
-        secondary_color: Rgb565::YELLOW,
-        icon_color: Rgb565::WHITE,
-        text_color: Rgb565::WHITE,
-        default_widget_height: 16,
-        border_width: 0,
-        highlight_border_width: 1,
-        default_font: mono_font::iso_8859_10::FONT_9X15,
-        spacing: Spacing {
-            item_spacing: Size::new(8, 4),
-            button_padding: Size::new(6, 5),
-            default_padding: Size::new(1, 1),
-            window_border_padding: Size::new(3, 3),
-        },
-        corner_radius: 8,
-    }
+    MEDSIZE_RGB565_STYLE
 }
 
 /// Light theme for RGB565 displays.
 ///
 /// Features a white background with orange accents and black text.
+pub const MEDSIZE_LIGHT_RGB565_STYLE: Style<Rgb565> = Style {
+    background_color: Rgb565::CSS_WHITE,
+    item_background_color: Fill::Solid(Rgb565::CSS_NAVAJO_WHITE),
+    highlight_item_background_color: Rgb565::CSS_GAINSBORO,
+    border_color: Rgb565::CSS_WHITE,
+    highlight_border_color: Rgb565::CSS_BLACK,
+    primary_color: Rgb565::CSS_DARK_ORANGE,
+    secondary_color: Rgb565::YELLOW,
+    icon_color: Rgb565::CSS_BLACK,
+    text_color: Rgb565::CSS_BLACK,
+    default_widget_height: 16,
+    border_width: 0,
+    highlight_border_width: 1,
+    default_font: mono_font::iso_8859_10::FONT_9X15,
+    text_backend: TextBackend::Mono,
+    spacing: Spacing {
+        item_spacing: Size::new(8, 4),
+        button_padding: Size::new(6, 5),
+        default_padding: Size::new(1, 1),
+        window_border_padding: Size::new(3, 3),
+        scroll_step: 16,
+        press_slop: 4,
+    },
+    corner_radius: 8,
+    pressed_offset: Size::zero(),
+    shadow_color: None,
+    shadow_offset: Size::new(2, 2),
+};
+
+/// Light theme for RGB565 displays.
+///
+/// Returns [MEDSIZE_LIGHT_RGB565_STYLE].
 pub fn medsize_light_rgb565_style() -> Style<Rgb565> {
-    Style {
-        background_color: Rgb565::CSS_WHITE,
-        item_background_color: Rgb565::CSS_NAVAJO_WHITE,
-        highlight_item_background_color: Rgb565::CSS_GAINSBORO,
-        border_color: Rgb565::CSS_WHITE,
-        highlight_border_color: Rgb565::CSS_BLACK,
-        primary_color: Rgb565::CSS_DARK_ORANGE,
-        secondary_color: Rgb565::YELLOW,
-        icon_color: Rgb565::CSS_BLACK,
-        text_color: Rgb565::CSS_BLACK,
-        default_widget_height: 16,
-        border_width: 0,
-        highlight_border_width: 1,
-        default_font: mono_font::iso_8859_10::FONT_9X15,
-        spacing: Spacing {
-            item_spacing: Size::new(8, 4),
-            button_padding: Size::new(6, 5),
-            default_padding: Size::new(1, 1),
-            window_border_padding: Size::new(3, 3),
-        },
-        corner_radius: 8,
-    }
+    MEDSIZE_LIGHT_RGB565_STYLE
 }
 
 /// Pink theme for RGB565 displays.
 ///
 /// Features a peach background with pink accents and black text.
+pub const MEDSIZE_SAKURA_RGB565_STYLE: Style<Rgb565> = Style {
+    background_color: Rgb565::CSS_PEACH_PUFF,
+    item_background_color: Fill::Solid(Rgb565::CSS_LIGHT_PINK),
+    highlight_item_background_color: Rgb565::CSS_HOT_PINK,
+    border_color: Rgb565::CSS_WHITE,
+    highlight_border_color: Rgb565::CSS_BLACK,
+    primary_color: Rgb565::CSS_DEEP_PINK,
+    secondary_color: Rgb565::YELLOW,
+    icon_color: Rgb565::CSS_BLACK,
+    text_color: Rgb565::CSS_BLACK,
+    default_widget_height: 16,
+    border_width: 0,
+    highlight_border_width: 1,
+    default_font: mono_font::ascii::FONT_9X15,
+    text_backend: TextBackend::Mono,
+    spacing: Spacing {
+        item_spacing: Size::new(8, 4),
+        button_padding: Size::new(6, 5),
+        default_padding: Size::new(1, 1),
+        window_border_padding: Size::new(3, 3),
+        scroll_step: 16,
+        press_slop: 4,
+    },
+    corner_radius: 8,
+    pressed_offset: Size::zero(),
+    shadow_color: None,
+    shadow_offset: Size::new(2, 2),
+};
+
+/// Pink theme for RGB565 displays.
+///
+/// Returns [MEDSIZE_SAKURA_RGB565_STYLE].
 pub fn medsize_sakura_rgb565_style() -> Style<Rgb565> {
-    Style {
-        background_color: Rgb565::CSS_PEACH_PUFF,
-        item_background_color: Rgb565::CSS_LIGHT_PINK,
-        highlight_item_background_color: Rgb565::CSS_HOT_PINK,
-        border_color: Rgb565::CSS_WHITE,
-        highlight_border_color: Rgb565::CSS_BLACK,
-        primary_color: Rgb565::CSS_DEEP_PINK,
-        secondary_color: Rgb565::YELLOW,
-        icon_color: Rgb565::CSS_BLACK,
-        text_color: Rgb565::CSS_BLACK,
-        default_widget_height: 16,
-        border_width: 0,
-        highlight_border_width: 1,
-        default_font: mono_font::ascii::FONT_9X15,
-        spacing: Spacing {
-            item_spacing: Size::new(8, 4),
-            button_padding: Size::new(6, 5),
-            default_padding: Size::new(1, 1),
-            window_border_padding: Size::new(3, 3),
-        },
-        corner_radius: 8,
-    }
+    MEDSIZE_SAKURA_RGB565_STYLE
 }
 
 /// Blue theme for RGB565 displays.
 ///
 /// Features a midnight blue background with violet accents and white text.
+pub const MEDSIZE_BLUE_RGB565_STYLE: Style<Rgb565> = Style {
+    background_color: Rgb565::CSS_MIDNIGHT_BLUE,
+    item_background_color: Fill::Solid(Rgb565::CSS_BLUE),
+    highlight_item_background_color: Rgb565::CSS_BLUE_VIOLET,
+    border_color: Rgb565::CSS_WHITE,
+    highlight_border_color: Rgb565::CSS_WHITE,
+    primary_color: Rgb565::CSS_PALE_VIOLET_RED,
+    secondary_color: Rgb565::YELLOW,
+    icon_color: Rgb565::CSS_WHITE,
+    text_color: Rgb565::CSS_WHITE,
+    default_widget_height: 16,
+    border_width: 0,
+    highlight_border_width: 1,
+    default_font: mono_font::iso_8859_10::FONT_9X15,
+    text_backend: TextBackend::Mono,
+    spacing: Spacing {
+        item_spacing: Size::new(8, 4),
+        button_padding: Size::new(6, 5),
+        default_padding: Size::new(1, 1),
+        window_border_padding: Size::new(3, 3),
+        scroll_step: 16,
+        press_slop: 4,
+    },
+    corner_radius: 8,
+    pressed_offset: Size::zero(),
+    shadow_color: None,
+    shadow_offset: Size::new(2, 2),
+};
+
+/// Blue theme for RGB565 displays.
+///
+/// Returns [MEDSIZE_BLUE_RGB565_STYLE].
 pub fn medsize_blue_rgb565_style() -> Style<Rgb565> {
-    Style {
-        background_color: Rgb565::CSS_MIDNIGHT_BLUE,
-        item_background_color: Rgb565::CSS_BLUE,
-        highlight_item_background_color: Rgb565::CSS_BLUE_VIOLET,
-        border_color: Rgb565::CSS_WHITE,
-        highlight_border_color: Rgb565::CSS_WHITE,
-        primary_color: Rgb565::CSS_PALE_VIOLET_RED,
-        secondary_color: Rgb565::YELLOW,
-        icon_color: Rgb565::CSS_WHITE,
-        text_color: Rgb565::CSS_WHITE,
-        default_widget_height: 16,
-        border_width: 0,
-        highlight_border_width: 1,
-        default_font: mono_font::iso_8859_10::FONT_9X15,
-        spacing: Spacing {
-            item_spacing: Size::new(8, 4),
-            button_padding: Size::new(6, 5),
-            default_padding: Size::new(1, 1),
-            window_border_padding: Size::new(3, 3),
-        },
-        corner_radius: 8,
-    }
+    MEDSIZE_BLUE_RGB565_STYLE
 }
 
 /// Retro CRT monitor theme for RGB565 displays.
 ///
 /// Features a black background with green text and borders, reminiscent of early CRT monitors.
+pub const MEDSIZE_CRT_RGB565_STYLE: Style<Rgb565> = Style {
+    background_color: Rgb565::CSS_BLACK,
+    item_background_color: Fill::Solid(Rgb565::CSS_BLACK),
+    highlight_item_background_color: Rgb565::CSS_BLACK,
+    border_color: Rgb565::CSS_GREEN,
+    highlight_border_color: Rgb565::CSS_GREEN,
+    primary_color: Rgb565::CSS_GREEN,
+    secondary_color: Rgb565::YELLOW,
+    icon_color: Rgb565::CSS_GREEN,
+    text_color: Rgb565::CSS_GREEN,
+    default_widget_height: 16,
+    border_width: 1,
+    highlight_border_width: 3,
+    default_font: mono_font::iso_8859_10::FONT_9X15,
+    text_backend: TextBackend::Mono,
+    spacing: Spacing {
+        item_spacing: Size::new(8, 4),
+        button_padding: Size::new(5, 5),
+        default_padding: Size::new(1, 1),
+        window_border_padding: Size::new(3, 3),
+        scroll_step: 16,
+        press_slop: 4,
+    },
+    corner_radius: 0,
+    pressed_offset: Size::zero(),
+    shadow_color: None,
+    shadow_offset: Size::new(2, 2),
+};
+
+/// Retro CRT monitor theme for RGB565 displays.
+///
+/// Returns [MEDSIZE_CRT_RGB565_STYLE].
 pub fn medsize_crt_rgb565_style() -> Style<Rgb565> {
-    Style {
-        background_color: Rgb565::CSS_BLACK,
-        item_background_color: Rgb565::CSS_BLACK,
-        highlight_item_background_color: Rgb565::CSS_BLACK,
-        border_color: Rgb565::CSS_GREEN,
-        highlight_border_color: Rgb565::CSS_GREEN,
-        primary_color: Rgb565::CSS_GREEN,
-        secondary_color: Rgb565::YELLOW,
-        icon_color: Rgb565::CSS_GREEN,
-        text_color: Rgb565::CSS_GREEN,
-        default_widget_height: 16,
-        border_width: 1,
-        highlight_border_width: 3,
-        default_font: mono_font::iso_8859_10::FONT_9X15,
-        spacing: Spacing {
-            item_spacing: Size::new(8, 4),
-            button_padding: Size::new(5, 5),
-            default_padding: Size::new(1, 1),
-            window_border_padding: Size::new(3, 3),
-        },
-        corner_radius: 0,
-    }
+    MEDSIZE_CRT_RGB565_STYLE
 }
 
 /// Minimalist black and white theme for RGB565 displays.
 ///
 /// Features a white background with black borders and text, suitable for high contrast displays or e-ink screens.
+pub const MEDSIZE_RETRO_RGB565_STYLE: Style<Rgb565> = Style {
+    background_color: Rgb565::CSS_WHITE,
+    item_background_color: Fill::Solid(Rgb565::CSS_WHITE),
+    highlight_item_background_color: Rgb565::CSS_WHITE,
+    border_color: Rgb565::CSS_BLACK,
+    highlight_border_color: Rgb565::CSS_BLACK,
+    primary_color: Rgb565::CSS_BLACK,
+    secondary_color: Rgb565::YELLOW,
+    icon_color: Rgb565::CSS_BLACK,
+    text_color: Rgb565::CSS_BLACK,
+    default_widget_height: 16,
+    border_width: 1,
+    highlight_border_width: 1,
+    default_font: mono_font::ascii::FONT_9X15,
+    text_backend: TextBackend::Mono,
+    spacing: Spacing {
+        item_spacing: Size::new(8, 4),
+        button_padding: Size::new(5, 5),
+        default_padding: Size::new(1, 1),
+        window_border_padding: Size::new(3, 3),
+        scroll_step: 16,
+        press_slop: 4,
+    },
+    corner_radius: 0,
+    pressed_offset: Size::zero(),
+    shadow_color: None,
+    shadow_offset: Size::new(2, 2),
+};
+
+/// Minimalist black and white theme for RGB565 displays.
+///
+/// Returns [MEDSIZE_RETRO_RGB565_STYLE].
 pub fn medsize_retro_rgb565_style() -> Style<Rgb565> {
-    Style {
-        background_color: Rgb565::CSS_WHITE,
-        item_background_color: Rgb565::CSS_WHITE,
-        highlight_item_background_color: Rgb565::CSS_WHITE,
-        border_color: Rgb565::CSS_BLACK,
-        highlight_border_color: Rgb565::CSS_BLACK,
-        primary_color: Rgb565::CSS_BLACK,
-        secondary_color: Rgb565::YELLOW,
-        icon_color: Rgb565::CSS_BLACK,
-        text_color: Rgb565::CSS_BLACK,
-        default_widget_height: 16,
-        border_width: 1,
-        highlight_border_width: 1,
-        default_font: mono_font::ascii::FONT_9X15,
-        spacing: Spacing {
-            item_spacing: Size::new(8, 4),
-            button_padding: Size::new(5, 5),
-            default_padding: Size::new(1, 1),
-            window_border_padding: Size::new(3, 3),
-        },
-        corner_radius: 0,
-    }
+    MEDSIZE_RETRO_RGB565_STYLE
 }
 
 /// Defines the visual appearance of a Kolibri UI.
@@ -276,7 +458,8 @@ pub fn medsize_retro_rgb565_style() -> Style<Rgb565> {
 /// ```rust
 /// use embedded_graphics::pixelcolor::Rgb565;
 /// use embedded_graphics::mono_font;
-/// use kolibri_embedded_gui::style::{Style, Spacing};
+/// use kolibri_embedded_gui::prelude::*;
+/// use kolibri_embedded_gui::text::TextBackend;
 /// use embedded_graphics::prelude::*;
 ///
 /// let custom_style = Style {
@@ -288,8 +471,11 @@ pub fn medsize_retro_rgb565_style() -> Style<Rgb565> {
 ///         button_padding: Size::new(4, 4),
 ///         default_padding: Size::new(2, 2),
 ///         window_border_padding: Size::new(3, 3),
+///         scroll_step: 16,
+///         press_slop: 4,
 ///     },
 ///     default_font: mono_font::ascii::FONT_6X13,
+///     text_backend: TextBackend::Mono,
 ///     border_color: Rgb565::BLACK,
 ///     border_width: 1,
 ///     default_widget_height: 16,
@@ -298,8 +484,11 @@ pub fn medsize_retro_rgb565_style() -> Style<Rgb565> {
 ///     highlight_border_color: Rgb565::WHITE,
 ///     highlight_border_width: 2,
 ///     highlight_item_background_color: Rgb565::BLUE,
-///     item_background_color: Rgb565::BLACK,
+///     item_background_color: Fill::Solid(Rgb565::BLACK),
 ///     corner_radius: 8,
+///     pressed_offset: Size::zero(),
+///     shadow_color: None,
+///     shadow_offset: Size::new(2, 2),
 /// };
 /// ```
 #[derive(Debug, Clone, Copy)]
@@ -320,10 +509,13 @@ pub struct Style<COL: PixelColor> {
     pub border_width: u32,
     /// Default font used for text rendering
     pub default_font: MonoFont<'static>,
+    /// Backend used for text rendering, see [crate::text]. With [TextBackend::Mono],
+    /// `default_font` is used.
+    pub text_backend: TextBackend,
     /// Spacing configuration for UI elements
     pub spacing: Spacing,
-    /// Background color for items like buttons
-    pub item_background_color: COL,
+    /// Background of items like buttons, a [Fill::Solid] color or a [Fill::VGradient]
+    pub item_background_color: Fill<COL>,
     /// Background color for highlighted items
     pub highlight_item_background_color: COL,
     /// Border color for highlighted elements
@@ -334,4 +526,487 @@ pub struct Style<COL: PixelColor> {
     pub text_color: COL,
     /// Corner radius for rounded corners on widgets
     pub corner_radius: u32,
+    /// Offset of the content (icon, text) of pressed buttons, for a tactile look.
+    /// `(0, 0)` draws pressed buttons like all others.
+    pub pressed_offset: Size,
+    /// Color of the shadow drawn behind widget backgrounds (e.g. buttons), `None` for no shadow.
+    /// There is no alpha blending, so a plain dark color works best.
+    pub shadow_color: Option<COL>,
+    /// Offset of the shadow from the widget background.
+    /// Widgets allocate this much extra space, so the shadow doesn't overlap other widgets.
+    pub shadow_offset: Size,
+}
+
+impl<COL: PixelColor> Style<COL> {
+    /// Converts every color of the style with `f`, keeping all other fields.
+    ///
+    /// This allows authoring a theme once (e.g. in [Rgb888]) and using it on displays
+    /// with different color types. See [Style::convert] for the common case of a plain conversion.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use embedded_graphics::pixelcolor::{BinaryColor, Rgb888};
+    /// # use embedded_graphics::prelude::*;
+    /// use kolibri_embedded_gui::prelude::*;
+    ///
+    /// // anything that isn't the background is "on"
+    /// let background = medsize_rgb888_base_style().background_color;
+    /// let oled: Style<BinaryColor> = medsize_rgb888_base_style()
+    ///     .map_colors(|c| if c == background { BinaryColor::Off } else { BinaryColor::On });
+    /// ```
+    pub fn map_colors<C2: PixelColor>(self, mut f: impl FnMut(COL) -> C2) -> Style<C2> {
+        // destructure without `..`, so that new color fields can't be forgotten here
+        let Style {
+            background_color,
+            border_color,
+            primary_color,
+            secondary_color,
+            icon_color,
+            default_widget_height,
+            border_width,
+            default_font,
+            text_backend,
+            spacing,
+            item_background_color,
+            highlight_item_background_color,
+            highlight_border_color,
+            highlight_border_width,
+            text_color,
+            corner_radius,
+            pressed_offset,
+            shadow_color,
+            shadow_offset,
+        } = self;
+        Style {
+            background_color: f(background_color),
+            border_color: f(border_color),
+            primary_color: f(primary_color),
+            secondary_color: f(secondary_color),
+            icon_color: f(icon_color),
+            default_widget_height,
+            border_width,
+            default_font,
+            text_backend,
+            spacing,
+            item_background_color: item_background_color.map(&mut f),
+            highlight_item_background_color: f(highlight_item_background_color),
+            highlight_border_color: f(highlight_border_color),
+            highlight_border_width,
+            text_color: f(text_color),
+            corner_radius,
+            pressed_offset,
+            shadow_color: shadow_color.map(&mut f),
+            shadow_offset,
+        }
+    }
+
+    /// Converts every color of the style to another color type using its [From] implementation.
+    ///
+    /// See [Style::map_colors] for custom conversions.
+    pub fn convert<C2: PixelColor + From<COL>>(self) -> Style<C2> {
+        self.map_colors(C2::from)
+    }
+
+    /// Returns the offset to draw a widget's content at, depending on whether it is `pressed`.
+    ///
+    /// This is [Style::pressed_offset] for pressed widgets, and zero otherwise.
+    /// Only the content is offset, the widget's background stays in place.
+    pub fn pressed_offset_for(&self, pressed: bool) -> Point {
+        if pressed {
+            Point::new(
+                self.pressed_offset.width as i32,
+                self.pressed_offset.height as i32,
+            )
+        } else {
+            Point::zero()
+        }
+    }
+
+    /// Returns a widget's `padding` (e.g. [Spacing::button_padding]), grown where needed so that
+    /// the widest border of the widget across its looks doesn't cover the content.
+    ///
+    /// Widgets are sized with [Style::border_width], but drawn with
+    /// [Style::highlight_border_width] when hovered or pressed. Growing the padding for all looks
+    /// keeps the widget's size the same when it is hovered.
+    pub fn widget_padding(&self, padding: Size) -> Size {
+        let extra = self
+            .highlight_border_width
+            .saturating_sub(self.border_width);
+        Size::new(padding.width.max(extra), padding.height.max(extra))
+    }
+
+    /// Returns the extra space widgets need for their shadow.
+    ///
+    /// This is [Style::shadow_offset] if a [Style::shadow_color] is set, and zero otherwise.
+    pub fn shadow_size(&self) -> Size {
+        match self.shadow_color {
+            Some(_) => self.shadow_offset,
+            None => Size::zero(),
+        }
+    }
+
+    /// Returns the part of the allocated `area` covered by the widget itself, without its shadow.
+    ///
+    /// The shadow takes up the bottom and right edges of the area (see [Style::shadow_size]).
+    pub fn without_shadow(&self, area: Rectangle) -> Rectangle {
+        Rectangle::new(area.top_left, area.size.saturating_sub(self.shadow_size()))
+    }
+
+    /// Resolves the corner radius for a widget of the given size.
+    ///
+    /// A widget-specific `radius` (e.g. set with [Button::with_radius](crate::button::Button::with_radius))
+    /// takes precedence over [Style::corner_radius]. The result is clamped to half of the
+    /// widget's smaller dimension, as larger radii make the corner arcs overlap. This also means
+    /// that a radius of `u32::MAX` results in a pill-shaped widget.
+    pub fn corner_radius_for(&self, radius: Option<u32>, size: Size) -> u32 {
+        clamp_corner_radius(radius.unwrap_or(self.corner_radius), size)
+    }
+}
+
+/// Clamps `radius` to half of the smaller dimension of `size`, see [Style::corner_radius_for].
+pub(crate) fn clamp_corner_radius(radius: u32, size: Size) -> u32 {
+    radius.min(size.width.min(size.height) / 2)
+}
+
+/// How the background of a widget is filled, see [Style::item_background_color].
+///
+/// Only [Button](crate::button::Button) and [IconButton](crate::iconbutton::IconButton) draw
+/// gradients, other widgets fill with the [top color](Fill::color).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fill<COL> {
+    /// A single color
+    Solid(COL),
+    /// A vertical gradient from the top color (first) to the bottom color (second).
+    ///
+    /// The colors of the rows in between are interpolated in [Rgb888] when the widget is drawn,
+    /// in horizontal 1px strips.
+    VGradient(COL, COL),
+}
+
+impl<COL: Copy> Fill<COL> {
+    /// Returns the color of a solid fill, or the top color of a gradient, for widgets drawn in
+    /// a single color.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics::prelude::*;
+    /// use kolibri_embedded_gui::prelude::*;
+    ///
+    /// let mut style = medsize_rgb565_style();
+    /// style.item_background_color = Fill::VGradient(Rgb565::CSS_GRAY, Rgb565::CSS_DIM_GRAY);
+    /// assert_eq!(style.item_background_color.color(), Rgb565::CSS_GRAY);
+    /// ```
+    pub const fn color(&self) -> COL {
+        match *self {
+            Fill::Solid(color) => color,
+            Fill::VGradient(top, _) => top,
+        }
+    }
+
+    /// Returns the color of row `row` (counted from the top) of a widget that is `height`
+    /// rows high. The top row has the top color of a gradient, the bottom row the bottom color.
+    pub fn color_at(&self, row: u32, height: u32) -> COL
+    where
+        COL: Into<Rgb888> + From<Rgb888>,
+    {
+        match *self {
+            Fill::Solid(color) => color,
+            Fill::VGradient(top, bottom) => {
+                let (top, bottom) = (top.into(), bottom.into());
+                let last = height.saturating_sub(1).max(1) as i32;
+                let row = row.min(last as u32) as i32;
+                // rounded to the nearest value
+                let mix = |a: u8, b: u8| {
+                    let delta = (b as i32 - a as i32) * row;
+                    (a as i32 + (delta + delta.signum() * last / 2) / last) as u8
+                };
+                Rgb888::new(
+                    mix(top.r(), bottom.r()),
+                    mix(top.g(), bottom.g()),
+                    mix(top.b(), bottom.b()),
+                )
+                .into()
+            }
+        }
+    }
+
+    /// Converts every color of the fill with `f`, like [Style::map_colors].
+    pub fn map<C2>(self, mut f: impl FnMut(COL) -> C2) -> Fill<C2> {
+        match self {
+            Fill::Solid(color) => Fill::Solid(f(color)),
+            Fill::VGradient(top, bottom) => Fill::VGradient(f(top), f(bottom)),
+        }
+    }
+}
+
+impl<COL> From<COL> for Fill<COL> {
+    fn from(color: COL) -> Self {
+        Fill::Solid(color)
+    }
+}
+
+/// The role of a widget in its screen, which its look follows, see e.g.
+/// [Button::context](crate::button::Button::context).
+///
+/// More contexts may be added, so matches need a wildcard arm.
+///
+/// # Example
+///
+/// ```
+/// use kolibri_embedded_gui::prelude::*;
+///
+/// // e.g. from the app's config
+/// let confirm_is_primary = true;
+/// let context = WidgetContext::from_primary(confirm_is_primary);
+/// assert_eq!(context, WidgetContext::Primary);
+/// assert_eq!(WidgetContext::default(), WidgetContext::Normal);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum WidgetContext {
+    /// An ordinary widget. This is the default.
+    #[default]
+    Normal,
+    /// The main action of a screen (e.g. "Save"), filled with the [Style::primary_color] even
+    /// when it isn't pressed. Hovering and pressing it still change its border.
+    Primary,
+}
+
+impl WidgetContext {
+    /// Returns [WidgetContext::Primary] if `primary` is set, [WidgetContext::Normal] otherwise.
+    pub const fn from_primary(primary: bool) -> Self {
+        if primary {
+            WidgetContext::Primary
+        } else {
+            WidgetContext::Normal
+        }
+    }
+
+    /// Returns whether this is [WidgetContext::Primary].
+    pub const fn is_primary(self) -> bool {
+        matches!(self, WidgetContext::Primary)
+    }
+
+    /// Adjusts the look `rect_style` of a widget, as resolved with
+    /// [resolve_widget_style](crate::ui::resolve_widget_style), to the context.
+    pub fn apply<COL: PixelColor>(self, rect_style: &mut PrimitiveStyle<COL>, style: &Style<COL>) {
+        if self.is_primary() {
+            rect_style.fill_color = Some(style.primary_color);
+        }
+    }
+}
+
+/// Overrides of some [Style] fields for a section of the UI, see [Ui::with_style](crate::ui::Ui::with_style).
+///
+/// Fields that are `None` keep the value of the current style.
+///
+/// # Example
+///
+/// ```
+/// # use embedded_graphics::mono_font::ascii;
+/// use kolibri_embedded_gui::style::StylePatch;
+///
+/// let compact = StylePatch::new()
+///     .font(ascii::FONT_6X10)
+///     .default_widget_height(12);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StylePatch {
+    /// Overrides [Style::default_font]
+    pub default_font: Option<MonoFont<'static>>,
+    /// Overrides [Style::spacing]
+    pub spacing: Option<Spacing>,
+    /// Overrides [Style::default_widget_height]
+    pub default_widget_height: Option<u32>,
+    /// Overrides [Style::border_width]
+    pub border_width: Option<u32>,
+    /// Overrides [Style::corner_radius]
+    pub corner_radius: Option<u32>,
+}
+
+impl StylePatch {
+    /// Creates a patch that doesn't override anything.
+    pub const fn new() -> Self {
+        Self {
+            default_font: None,
+            spacing: None,
+            default_widget_height: None,
+            border_width: None,
+            corner_radius: None,
+        }
+    }
+
+    /// Overrides the default font.
+    pub const fn font(mut self, font: MonoFont<'static>) -> Self {
+        self.default_font = Some(font);
+        self
+    }
+
+    /// Overrides the spacing.
+    pub const fn spacing(mut self, spacing: Spacing) -> Self {
+        self.spacing = Some(spacing);
+        self
+    }
+
+    /// Overrides the default widget height.
+    pub const fn default_widget_height(mut self, height: u32) -> Self {
+        self.default_widget_height = Some(height);
+        self
+    }
+
+    /// Overrides the border width.
+    pub const fn border_width(mut self, width: u32) -> Self {
+        self.border_width = Some(width);
+        self
+    }
+
+    /// Overrides the corner radius.
+    pub const fn corner_radius(mut self, radius: u32) -> Self {
+        self.corner_radius = Some(radius);
+        self
+    }
+
+    /// Applies the overrides to `style`.
+    ///
+    /// ## Returns
+    ///
+    /// The patch that restores the overridden fields of `style` to their previous values.
+    pub fn apply<COL: PixelColor>(&self, style: &mut Style<COL>) -> StylePatch {
+        fn swap<T: Copy>(field: &mut T, value: Option<T>) -> Option<T> {
+            value.map(|value| core::mem::replace(field, value))
+        }
+        StylePatch {
+            default_font: swap(&mut style.default_font, self.default_font),
+            spacing: swap(&mut style.spacing, self.spacing),
+            default_widget_height: swap(
+                &mut style.default_widget_height,
+                self.default_widget_height,
+            ),
+            border_width: swap(&mut style.border_width, self.border_width),
+            corner_radius: swap(&mut style.corner_radius, self.corner_radius),
+        }
+    }
+}
+
+/// Returns the metrics of `font` for hashing it: fonts aren't hashable, but their metrics tell
+/// the usual fonts apart.
+pub(crate) fn font_key(font: &MonoFont<'_>) -> impl core::hash::Hash {
+    (
+        font.character_size,
+        font.character_spacing,
+        font.baseline,
+        font.image.size(),
+    )
+}
+
+impl core::hash::Hash for StylePatch {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.default_font.as_ref().map(font_key).hash(state);
+        self.spacing.hash(state);
+        self.default_widget_height.hash(state);
+        self.border_width.hash(state);
+        self.corner_radius.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // themes must stay usable in const contexts, so that they can live in flash
+    static STYLE: Style<Rgb565> = MEDSIZE_RGB565_STYLE;
+    const CUSTOM_STYLE: Style<Rgb565> = Style {
+        corner_radius: 0,
+        ..MEDSIZE_LIGHT_RGB565_STYLE
+    };
+
+    #[test]
+    fn themes_are_const() {
+        assert_eq!(STYLE.corner_radius, medsize_rgb565_style().corner_radius);
+        assert_eq!(CUSTOM_STYLE.corner_radius, 0);
+    }
+
+    #[test]
+    fn map_colors_converts_every_color() {
+        let mut style = medsize_rgb888_base_style();
+        style.shadow_color = Some(Rgb888::BLACK);
+
+        let mut converted = 0;
+        let mapped = style.map_colors(|_| {
+            converted += 1;
+            Rgb565::RED
+        });
+        assert_eq!(converted, 10);
+        assert_eq!(mapped.shadow_color, Some(Rgb565::RED));
+        assert_eq!(mapped.corner_radius, style.corner_radius);
+    }
+
+    #[test]
+    fn rgb888_base_style_converts_to_rgb565_style() {
+        let colors = |style: Style<Rgb565>| {
+            let mut colors = [Rgb565::BLACK; 9];
+            let mut i = 0;
+            style.map_colors(|c| {
+                colors[i] = c;
+                i += 1;
+                c
+            });
+            colors
+        };
+        assert_eq!(
+            colors(medsize_rgb888_base_style().convert()),
+            colors(medsize_rgb565_style())
+        );
+    }
+
+    #[test]
+    fn gradient_runs_from_top_to_bottom() {
+        let fill = Fill::VGradient(Rgb565::BLACK, Rgb565::WHITE);
+        assert_eq!(fill.color(), Rgb565::BLACK);
+        assert_eq!(fill.color_at(0, 30), Rgb565::BLACK);
+        assert_eq!(fill.color_at(29, 30), Rgb565::WHITE);
+        // interpolated per row in Rgb888
+        let fill = Fill::VGradient(Rgb888::BLACK, Rgb888::new(200, 100, 0));
+        assert_eq!(fill.color_at(1, 5), Rgb888::new(50, 25, 0));
+        assert_eq!(fill.color_at(2, 5), Rgb888::new(100, 50, 0));
+        assert_ne!(fill.color_at(1, 200), fill.color_at(2, 200));
+        // rows below the widget have the bottom color, a single row the top color
+        assert_eq!(fill.color_at(9, 5), Rgb888::new(200, 100, 0));
+        assert_eq!(fill.color_at(0, 1), Rgb888::BLACK);
+        // solid fills are the same in every row
+        assert_eq!(Fill::from(Rgb565::RED).color_at(3, 5), Rgb565::RED);
+    }
+
+    #[test]
+    fn pressed_offset_only_applies_when_pressed() {
+        let mut style = medsize_rgb565_style();
+        assert_eq!(style.pressed_offset_for(true), Point::zero());
+        style.pressed_offset = Size::new(1, 2);
+        assert_eq!(style.pressed_offset_for(true), Point::new(1, 2));
+        assert_eq!(style.pressed_offset_for(false), Point::zero());
+    }
+
+    #[test]
+    fn corner_radius_uses_style_without_override() {
+        let style = medsize_rgb565_style();
+        assert_eq!(style.corner_radius_for(None, Size::new(100, 30)), 8);
+        assert_eq!(style.corner_radius_for(Some(3), Size::new(100, 30)), 3);
+    }
+
+    #[test]
+    fn corner_radius_is_clamped_to_half_the_smaller_dimension() {
+        let style = medsize_rgb565_style();
+        assert_eq!(style.corner_radius_for(None, Size::new(10, 10)), 5);
+        assert_eq!(style.corner_radius_for(Some(8), Size::new(10, 40)), 5);
+        assert_eq!(
+            style.corner_radius_for(Some(u32::MAX), Size::new(100, 21)),
+            10
+        );
+        assert_eq!(style.corner_radius_for(Some(4), Size::zero()), 0);
+    }
 }
@@ -0,0 +1,334 @@
+//! # Checking styles for legibility
+//!
+//! [Style::validate] checks a theme for combinations that make widgets hard to read or use:
+//!
+//! - foreground colors (text and icons) too similar to the background they are drawn on, in
+//!   every look of a widget: on the UI background (labels), normal, hovered and pressed (which
+//!   is also the look of [WidgetContext::Primary](crate::style::WidgetContext::Primary)
+//!   widgets). The colors are compared by their rough brightness (luma), via [Rgb888].
+//! - looks that can't be told apart, e.g. hovering that neither changes the background color
+//!   nor the border
+//! - a [corner radius](Style::corner_radius) that is clamped for widgets of the
+//!   [default height](Style::default_widget_height)
+//!
+//! Check the style in the display's color type: a theme authored in [Rgb888] may have distinct
+//! colors that are the same after [converting](Style::convert) it to
+//! [Rgb565](embedded_graphics::pixelcolor::Rgb565).
+//!
+//! With the `validate-styles` feature, [Style::debug_validate] asserts that a style is valid in
+//! debug builds, e.g. for themes loaded at runtime.
+//!
+//! # Example
+//!
+//! ```
+//! use kolibri_embedded_gui::prelude::*;
+//! use kolibri_embedded_gui::style_check::StyleIssue;
+//!
+//! let mut style = medsize_rgb565_style();
+//! assert!(style.validate().is_ok());
+//!
+//! style.primary_color = style.text_color;
+//! let issues = style.validate().unwrap_err();
+//! assert!(issues.iter().any(|issue| matches!(
+//!     issue,
+//!     StyleIssue::LowContrast { foreground: "text_color", background: "primary_color", .. }
+//! )));
+//! ```
+
+use crate::style::{Fill, Style};
+use core::fmt;
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888, RgbColor};
+
+/// Minimum difference in luma (0 to 255) between a foreground color and its background
+pub const MIN_LUMA_CONTRAST: u8 = 48;
+
+/// Maximum number of issues in [StyleIssues], one per check
+const MAX_ISSUES: usize = 10;
+
+/// A problem of a [Style] found by [Style::validate]
+///
+/// The `&'static str`s are the names of the offending [Style] fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StyleIssue {
+    /// The `foreground` color is drawn on the `background` color, but their luma differs by
+    /// less than [MIN_LUMA_CONTRAST].
+    LowContrast {
+        foreground: &'static str,
+        background: &'static str,
+        /// The difference in luma
+        contrast: u8,
+    },
+    /// Widgets look the same in `state` as in the state before (normal for hovered, hovered
+    /// for pressed): `field` has the same color, and neither the border nor the content changes.
+    IndistinctState {
+        state: &'static str,
+        field: &'static str,
+    },
+    /// The corner radius is larger than half of the default widget height, so it is clamped to
+    /// `max` for widgets of that height, but not for taller ones.
+    CornerRadiusClamped { corner_radius: u32, max: u32 },
+}
+
+impl StyleIssue {
+    /// Returns the name of the [Style] field to change to fix the issue.
+    pub fn field(&self) -> &'static str {
+        match self {
+            StyleIssue::LowContrast { background, .. } => background,
+            StyleIssue::IndistinctState { field, .. } => field,
+            StyleIssue::CornerRadiusClamped { .. } => "corner_radius",
+        }
+    }
+}
+
+impl fmt::Display for StyleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StyleIssue::LowContrast {
+                foreground,
+                background,
+                contrast,
+            } => write!(
+                f,
+                "{foreground} on {background}: contrast {contrast} (min. {MIN_LUMA_CONTRAST})"
+            ),
+            StyleIssue::IndistinctState { state, field } => {
+                write!(f, "{state} looks like the state before ({field})")
+            }
+            StyleIssue::CornerRadiusClamped { corner_radius, max } => {
+                write!(f, "corner_radius {corner_radius} is clamped to {max}")
+            }
+        }
+    }
+}
+
+/// The issues of a [Style] found by [Style::validate], never empty
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleIssues(heapless::Vec<StyleIssue, MAX_ISSUES>);
+
+impl StyleIssues {
+    /// Returns the issues, in the order of the checks.
+    pub fn iter(&self) -> impl Iterator<Item = &StyleIssue> {
+        self.0.iter()
+    }
+
+    /// Returns the number of issues.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `false`, as there is at least one issue.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl fmt::Display for StyleIssues {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, issue) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the rough brightness of `color`, from 0 to 255.
+fn luma(color: Rgb888) -> u8 {
+    ((299 * color.r() as u32 + 587 * color.g() as u32 + 114 * color.b() as u32) / 1000) as u8
+}
+
+impl<COL: PixelColor + Into<Rgb888>> Style<COL> {
+    /// Checks the style for illegible color combinations and indistinguishable looks, see
+    /// the [module documentation](crate::style_check).
+    ///
+    /// ## Returns
+    ///
+    /// `Ok(())` for a valid style, the [StyleIssues] found otherwise.
+    // a few hundred bytes on the stack, but styles are rarely validated
+    #[allow(clippy::result_large_err)]
+    pub fn validate(&self) -> Result<(), StyleIssues> {
+        let mut issues = heapless::Vec::new();
+        let mut report = |issue| {
+            // there are MAX_ISSUES checks
+            issues.push(issue).ok();
+        };
+
+        // the top and bottom colors of each background, the same for solid ones
+        let solid = |color: COL| (color, color);
+        let item_background = match self.item_background_color {
+            Fill::Solid(color) => solid(color),
+            Fill::VGradient(top, bottom) => (top, bottom),
+        };
+        let backgrounds = [
+            ("background_color", solid(self.background_color)),
+            ("item_background_color", item_background),
+            (
+                "highlight_item_background_color",
+                solid(self.highlight_item_background_color),
+            ),
+            ("primary_color", solid(self.primary_color)),
+        ];
+        let foregrounds = [
+            ("text_color", self.text_color),
+            ("icon_color", self.icon_color),
+        ];
+        for (background, (top, bottom)) in backgrounds {
+            for (foreground, color) in foregrounds {
+                if background == "background_color" && foreground == "icon_color" {
+                    // icons are only drawn on widgets
+                    continue;
+                }
+                let fg = luma(color.into());
+                let (top, bottom) = (luma(top.into()), luma(bottom.into()));
+                // a gradient passes every luma between its ends
+                let contrast = match (top.min(bottom)..=top.max(bottom)).contains(&fg) {
+                    true => 0,
+                    false => fg.abs_diff(top).min(fg.abs_diff(bottom)),
+                };
+                if contrast < MIN_LUMA_CONTRAST {
+                    report(StyleIssue::LowContrast {
+                        foreground,
+                        background,
+                        contrast,
+                    });
+                }
+            }
+        }
+
+        // hovering changes the fill or the border
+        let border = |width: u32, color: COL| (width > 0).then_some((width, color));
+        let same_border = border(self.border_width, self.border_color)
+            == border(self.highlight_border_width, self.highlight_border_color);
+        if self.item_background_color == Fill::Solid(self.highlight_item_background_color)
+            && same_border
+        {
+            report(StyleIssue::IndistinctState {
+                state: "hover",
+                field: "highlight_item_background_color",
+            });
+        }
+        // pressing changes the fill or moves the content (the border is the same as hovered)
+        if self.primary_color == self.highlight_item_background_color
+            && self.pressed_offset == embedded_graphics::geometry::Size::zero()
+        {
+            report(StyleIssue::IndistinctState {
+                state: "pressed",
+                field: "primary_color",
+            });
+        }
+
+        let max = self.default_widget_height / 2;
+        if self.corner_radius > max && self.corner_radius != u32::MAX {
+            report(StyleIssue::CornerRadiusClamped {
+                corner_radius: self.corner_radius,
+                max,
+            });
+        }
+
+        match issues.is_empty() {
+            true => Ok(()),
+            false => Err(StyleIssues(issues)),
+        }
+    }
+
+    /// Asserts that the style is valid (see [Style::validate]) in debug builds with the
+    /// `validate-styles` feature, and does nothing otherwise.
+    ///
+    /// # Panics
+    ///
+    /// With the feature, in debug builds, if the style has issues.
+    pub fn debug_validate(&self) {
+        #[cfg(feature = "validate-styles")]
+        if let Err(issues) = self.validate() {
+            debug_assert!(false, "invalid style: {issues}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::{
+        medsize_blue_rgb565_style, medsize_light_rgb565_style, medsize_rgb565_style,
+        medsize_sakura_rgb565_style,
+    };
+    use embedded_graphics::pixelcolor::{Rgb565, WebColors};
+
+    fn fields<COL: PixelColor + Into<Rgb888>>(
+        style: &Style<COL>,
+    ) -> heapless::Vec<&'static str, MAX_ISSUES> {
+        match style.validate() {
+            Ok(()) => heapless::Vec::new(),
+            Err(issues) => issues.iter().map(StyleIssue::field).collect(),
+        }
+    }
+
+    #[test]
+    fn broken_themes_are_reported() {
+        for style in [
+            medsize_rgb565_style(),
+            medsize_light_rgb565_style(),
+            medsize_sakura_rgb565_style(),
+            medsize_blue_rgb565_style(),
+        ] {
+            assert_eq!(style.validate(), Ok(()));
+        }
+
+        // authored in Rgb888 with distinct colors, which are the same in Rgb565, and a dim icon
+        let mut style = medsize_rgb565_style().map_colors(Rgb888::from);
+        style.item_background_color = Rgb888::new(40, 40, 40).into();
+        style.highlight_item_background_color = Rgb888::new(41, 42, 43);
+        style.icon_color = Rgb888::new(70, 70, 70);
+        style.highlight_border_width = style.border_width;
+        style.corner_radius = 20;
+        assert_eq!(
+            fields(&style).as_slice(),
+            &[
+                "item_background_color",
+                "highlight_item_background_color",
+                "primary_color",
+                "corner_radius",
+            ]
+        );
+
+        let style = style.convert::<Rgb565>();
+        assert_eq!(
+            fields(&style).as_slice(),
+            &[
+                "item_background_color",
+                "highlight_item_background_color",
+                "primary_color",
+                "highlight_item_background_color",
+                "corner_radius",
+            ]
+        );
+        let issues = style.validate().unwrap_err();
+        assert!(matches!(
+            issues.iter().next(),
+            Some(StyleIssue::LowContrast {
+                foreground: "icon_color",
+                ..
+            })
+        ));
+        assert!(issues
+            .iter()
+            .any(|issue| matches!(issue, StyleIssue::IndistinctState { state: "hover", .. })));
+    }
+
+    #[test]
+    fn gradients_are_checked_between_their_ends() {
+        let mut style = medsize_rgb565_style();
+        // the black top is far from the white text and icons, the middle isn't
+        style.item_background_color = Fill::VGradient(Rgb565::BLACK, Rgb565::CSS_LIGHT_GRAY);
+        assert_eq!(
+            fields(&style).as_slice(),
+            &["item_background_color", "item_background_color"]
+        );
+
+        style.item_background_color = Fill::VGradient(Rgb565::BLACK, Rgb565::CSS_DIM_GRAY);
+        assert_eq!(style.validate(), Ok(()));
+    }
+}
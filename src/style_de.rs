@@ -0,0 +1,231 @@
+//! # Loading styles with serde
+//!
+//! [Style] can't be (de)serialized directly, as it's generic over the color type and contains a
+//! [MonoFont] (which references the font's glyph data). [StyleDe] is a serializable intermediate
+//! representation of a [Style]:
+//!
+//! - colors are stored as `[u8; 3]` RGB values and converted from/to the display's color type
+//!   through [Rgb888]
+//! - the font is stored by name and resolved through a caller-provided lookup function
+//!   when converting to a [Style] with [StyleDe::into_style]. Only [MonoFont]s are supported,
+//!   loaded styles use [TextBackend::Mono].
+//!
+//! This module is only available with the `serde` feature.
+//!
+//! # Example
+//!
+//! ```
+//! # use embedded_graphics::mono_font::{ascii, MonoFont};
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! use kolibri_embedded_gui::prelude::*;
+//! use kolibri_embedded_gui::style_de::StyleDe;
+//!
+//! fn font_lookup(name: &str) -> Option<&'static MonoFont<'static>> {
+//!     match name {
+//!         "6x10" => Some(&ascii::FONT_6X10),
+//!         "9x15" => Some(&ascii::FONT_9X15),
+//!         _ => None,
+//!     }
+//! }
+//!
+//! // e.g. read from flash or an SD card, deserialized with postcard
+//! let blob = StyleDe::from_style(&medsize_rgb565_style(), "9x15").unwrap();
+//! let style: Style<Rgb565> = blob.into_style(font_lookup).unwrap();
+//! ```
+
+use crate::style::{Fill, Spacing, Style};
+use crate::text::TextBackend;
+use embedded_graphics::mono_font::MonoFont;
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888, RgbColor};
+use embedded_graphics::prelude::Size;
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a font name in a [StyleDe]
+pub const FONT_NAME_LEN: usize = 32;
+
+/// Serializable representation of a [Style], see the [module documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StyleDe {
+    /// [Style::background_color] as RGB
+    pub background_color: [u8; 3],
+    /// [Style::border_color] as RGB
+    pub border_color: [u8; 3],
+    /// [Style::primary_color] as RGB
+    pub primary_color: [u8; 3],
+    /// [Style::secondary_color] as RGB
+    pub secondary_color: [u8; 3],
+    /// [Style::icon_color] as RGB
+    pub icon_color: [u8; 3],
+    /// [Style::default_widget_height] in pixels
+    pub default_widget_height: u32,
+    /// [Style::border_width] in pixels
+    pub border_width: u32,
+    /// Name of the font, resolved by the lookup passed to [StyleDe::into_style]
+    pub font: heapless::String<FONT_NAME_LEN>,
+    /// [Style::spacing]
+    pub spacing: Spacing,
+    /// [Style::item_background_color] with RGB colors, a solid color or a gradient
+    pub item_background_color: Fill<[u8; 3]>,
+    /// [Style::highlight_item_background_color] as RGB
+    pub highlight_item_background_color: [u8; 3],
+    /// [Style::highlight_border_color] as RGB
+    pub highlight_border_color: [u8; 3],
+    /// [Style::highlight_border_width] in pixels
+    pub highlight_border_width: u32,
+    /// [Style::text_color] as RGB
+    pub text_color: [u8; 3],
+    /// [Style::corner_radius] in pixels
+    pub corner_radius: u32,
+    /// [Style::pressed_offset], stored as `[width, height]`
+    #[serde(with = "size")]
+    pub pressed_offset: Size,
+    /// [Style::shadow_color] as RGB, `None` for no shadow
+    pub shadow_color: Option<[u8; 3]>,
+    /// [Style::shadow_offset], stored as `[width, height]`
+    #[serde(with = "size")]
+    pub shadow_offset: Size,
+}
+
+impl StyleDe {
+    /// Creates the serializable representation of `style`, storing `font_name` for its font.
+    ///
+    /// ## Returns
+    ///
+    /// `None` if `font_name` is longer than [FONT_NAME_LEN].
+    pub fn from_style<COL>(style: &Style<COL>, font_name: &str) -> Option<Self>
+    where
+        COL: PixelColor,
+        Rgb888: From<COL>,
+    {
+        let font = heapless::String::try_from(font_name).ok()?;
+        let style = style.map_colors(Rgb888::from);
+        Some(StyleDe {
+            background_color: rgb(style.background_color),
+            border_color: rgb(style.border_color),
+            primary_color: rgb(style.primary_color),
+            secondary_color: rgb(style.secondary_color),
+            icon_color: rgb(style.icon_color),
+            default_widget_height: style.default_widget_height,
+            border_width: style.border_width,
+            font,
+            spacing: style.spacing,
+            item_background_color: style.item_background_color.map(rgb),
+            highlight_item_background_color: rgb(style.highlight_item_background_color),
+            highlight_border_color: rgb(style.highlight_border_color),
+            highlight_border_width: style.highlight_border_width,
+            text_color: rgb(style.text_color),
+            corner_radius: style.corner_radius,
+            pressed_offset: style.pressed_offset,
+            shadow_color: style.shadow_color.map(rgb),
+            shadow_offset: style.shadow_offset,
+        })
+    }
+
+    /// Converts to a [Style], resolving the font name with `font_lookup`.
+    ///
+    /// ## Returns
+    ///
+    /// `None` if `font_lookup` doesn't know the font.
+    pub fn into_style<COL>(
+        self,
+        font_lookup: fn(&str) -> Option<&'static MonoFont<'static>>,
+    ) -> Option<Style<COL>>
+    where
+        COL: PixelColor + From<Rgb888>,
+    {
+        let color = |[r, g, b]: [u8; 3]| COL::from(Rgb888::new(r, g, b));
+        Some(Style {
+            background_color: color(self.background_color),
+            border_color: color(self.border_color),
+            primary_color: color(self.primary_color),
+            secondary_color: color(self.secondary_color),
+            icon_color: color(self.icon_color),
+            default_widget_height: self.default_widget_height,
+            border_width: self.border_width,
+            default_font: *font_lookup(&self.font)?,
+            text_backend: TextBackend::Mono,
+            spacing: self.spacing,
+            item_background_color: self.item_background_color.map(color),
+            highlight_item_background_color: color(self.highlight_item_background_color),
+            highlight_border_color: color(self.highlight_border_color),
+            highlight_border_width: self.highlight_border_width,
+            text_color: color(self.text_color),
+            corner_radius: self.corner_radius,
+            pressed_offset: self.pressed_offset,
+            shadow_color: self.shadow_color.map(color),
+            shadow_offset: self.shadow_offset,
+        })
+    }
+}
+
+fn rgb(color: Rgb888) -> [u8; 3] {
+    [color.r(), color.g(), color.b()]
+}
+
+/// (De)serializes a [Size] as `[width, height]`, as embedded-graphics doesn't implement serde
+pub(crate) mod size {
+    use embedded_graphics::prelude::Size;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(size: &Size, serializer: S) -> Result<S::Ok, S::Error> {
+        [size.width, size.height].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Size, D::Error> {
+        let [width, height] = <[u32; 2]>::deserialize(deserializer)?;
+        Ok(Size::new(width, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::{medsize_rgb565_style, medsize_sakura_rgb565_style};
+    use embedded_graphics::mono_font::{ascii, iso_8859_10};
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    fn font_lookup(name: &str) -> Option<&'static MonoFont<'static>> {
+        match name {
+            "6x10" => Some(&ascii::FONT_6X10),
+            "9x15" => Some(&iso_8859_10::FONT_9X15),
+            _ => None,
+        }
+    }
+
+    fn round_trip(style: Style<Rgb565>, font: &str) -> Style<Rgb565> {
+        let mut buf = [0u8; 256];
+        let bytes =
+            postcard::to_slice(&StyleDe::from_style(&style, font).unwrap(), &mut buf).unwrap();
+        let de: StyleDe = postcard::from_bytes(bytes).unwrap();
+        de.into_style(font_lookup).unwrap()
+    }
+
+    #[test]
+    fn style_round_trips_through_postcard() {
+        let mut style = medsize_sakura_rgb565_style();
+        style.shadow_color = Some(Rgb565::BLACK);
+        style.pressed_offset = Size::new(1, 1);
+        style.item_background_color = Fill::VGradient(Rgb565::WHITE, Rgb565::MAGENTA);
+
+        let loaded = round_trip(style, "9x15");
+
+        // Rgb565 -> Rgb888 -> Rgb565 is lossless
+        assert_eq!(
+            StyleDe::from_style(&loaded, "9x15"),
+            StyleDe::from_style(&style, "9x15")
+        );
+        assert_eq!(loaded.spacing.button_padding, style.spacing.button_padding);
+        assert_eq!(loaded.shadow_color, Some(Rgb565::BLACK));
+        assert_eq!(loaded.item_background_color, style.item_background_color);
+        assert_eq!(
+            loaded.default_font.character_size,
+            style.default_font.character_size
+        );
+    }
+
+    #[test]
+    fn unknown_font_fails() {
+        let de = StyleDe::from_style(&medsize_rgb565_style(), "comic sans").unwrap();
+        assert!(de.into_style::<Rgb565>(font_lookup).is_none());
+    }
+}
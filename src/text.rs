@@ -0,0 +1,709 @@
+//! # Text rendering backends
+//!
+//! Widgets measure and draw their text through the [TextRenderer] trait, so that the font
+//! backend can be switched per theme (see [Style::text_backend](crate::style::Style::text_backend)):
+//!
+//! - [MonoFont]s from embedded-graphics, the default. The style's
+//!   [default_font](crate::style::Style::default_font) is used.
+//! - [u8g2 fonts](https://docs.rs/u8g2-fonts), with larger and proportional fonts. This needs
+//!   the `u8g2-fonts` feature.
+//!
+//! Widgets position text by the size returned by [TextRenderer::measure], so every
+//! implementation guarantees that drawing the text at `top_left` stays within the measured
+//! size.
+//!
+//! Multi-line text is split into lines with [lines], which breaks at `\n` and optionally wraps
+//! at spaces to fit a maximum width. Widgets then measure ([measure_lines]) and draw the lines
+//! individually, e.g. to center each of them.
+//!
+//! Text is placed in its widget with [text_layout], which centers it vertically the same way in
+//! every widget, so that labels and buttons in a row line up.
+//!
+//! Text is only ever cut between characters (and not in front of a combining mark, like the
+//! accent of `"e\u{301}"`), so UTF-8 text like `"21 °C"` or `"Температура"` is safe to
+//! [truncate], [ellipsize] and wrap. Widths are always measured by the font: a [MonoFont] draws
+//! a replacement glyph of the same width for characters it lacks (e.g. Cyrillic in a Latin-1
+//! font), so the measured width stays correct.
+
+use crate::format::Buf;
+use crate::style::Style;
+use crate::ui::HorizontalAlign;
+use core::fmt;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::mono_font::{MonoFont, MonoTextStyle, MonoTextStyleBuilder};
+use embedded_graphics::pixelcolor::{BinaryColor, PixelColor};
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::{Baseline, Text};
+
+/// Measures and draws text, see the [module documentation](self).
+pub trait TextRenderer {
+    /// Returns the size of `text` when drawn with [TextRenderer::draw].
+    fn measure(&self, text: &str) -> Size;
+
+    /// Returns the distance between the tops of two consecutive lines.
+    fn line_height(&self) -> u32 {
+        self.measure(" ").height
+    }
+
+    /// Returns whether the font has a glyph for `c` (instead of drawing a replacement).
+    fn has_glyph(&self, _c: char) -> bool {
+        true
+    }
+
+    /// Draws `text` with the top left corner of its measured box at `top_left`.
+    fn draw<D: DrawTarget>(
+        &self,
+        text: &str,
+        top_left: Point,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<(), D::Error>;
+}
+
+impl TextRenderer for MonoFont<'_> {
+    fn measure(&self, text: &str) -> Size {
+        let style = MonoTextStyleBuilder::<BinaryColor>::new()
+            .font(self)
+            .build();
+        Text::with_baseline(text, Point::zero(), style, Baseline::Top)
+            .bounding_box()
+            .size
+    }
+
+    fn line_height(&self) -> u32 {
+        self.character_size.height
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        // missing characters map to the replacement glyph, like a noncharacter does
+        let mapping = self.glyph_mapping;
+        let replacement = mapping.index('\u{FFFF}');
+        let index = mapping.index(c);
+        if index != replacement {
+            return true;
+        }
+        // unless `c` is the replacement glyph itself (e.g. '?'): then it's part of a range of
+        // the mapping, and its neighbors have the neighboring glyphs
+        let neighbor = |c: Option<char>, index: Option<usize>| {
+            c.zip(index)
+                .is_some_and(|(c, index)| mapping.index(c) == index && index != replacement)
+        };
+        let code = c as u32;
+        neighbor(
+            code.checked_sub(1).and_then(char::from_u32),
+            index.checked_sub(1),
+        ) || neighbor(char::from_u32(code + 1), index.checked_add(1))
+    }
+
+    fn draw<D: DrawTarget>(
+        &self,
+        text: &str,
+        top_left: Point,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<(), D::Error> {
+        Text::with_baseline(
+            text,
+            top_left,
+            MonoTextStyle::new(self, color),
+            Baseline::Top,
+        )
+        .draw(target)
+        .map(|_| ())
+    }
+}
+
+/// Returns the box `text` covers when drawn by `font` at the origin with
+/// [VerticalPosition::Top](u8g2_fonts::types::VerticalPosition::Top): the advance and the line
+/// height, extended by glyphs that reach beyond them, also left of or above the origin.
+#[cfg(feature = "u8g2-fonts")]
+fn u8g2_extent(font: &u8g2_fonts::FontRenderer, text: &str) -> Rectangle {
+    use u8g2_fonts::types::VerticalPosition;
+
+    let line_height = font.get_default_line_height();
+    let Ok(dimensions) = font.get_rendered_dimensions(text, Point::zero(), VerticalPosition::Top)
+    else {
+        // only the text in front of a missing glyph is drawn
+        return match text.find(|c| !font.has_glyph(c)) {
+            Some(0) => Rectangle::new(Point::zero(), Size::new(0, line_height)),
+            Some(end) => u8g2_extent(font, &text[..end]),
+            None => Rectangle::zero(),
+        };
+    };
+    let mut top_left = Point::zero();
+    let mut bottom_right = Point::new(dimensions.advance.x.max(0), line_height as i32);
+    if let Some(bb) = dimensions.bounding_box {
+        top_left = top_left.component_min(bb.top_left);
+        bottom_right = bottom_right.component_max(bb.top_left + bb.size);
+    }
+    let size = bottom_right - top_left;
+    Rectangle::new(top_left, Size::new(size.x as u32, size.y as u32))
+}
+
+/// Draws with [VerticalPosition::Top](u8g2_fonts::types::VerticalPosition::Top), i.e. the font's
+/// ascent at `top_left`, moved right and down by glyphs that would reach left of or above it.
+/// Characters missing from the font aren't drawn (unless the renderer is configured to ignore
+/// them, drawing stops at the first one).
+#[cfg(feature = "u8g2-fonts")]
+impl TextRenderer for u8g2_fonts::FontRenderer {
+    fn measure(&self, text: &str) -> Size {
+        u8g2_extent(self, text).size
+    }
+
+    fn line_height(&self) -> u32 {
+        self.get_default_line_height()
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        use u8g2_fonts::types::VerticalPosition;
+
+        let mut buf = [0; 4];
+        self.get_rendered_dimensions(
+            &*c.encode_utf8(&mut buf),
+            Point::zero(),
+            VerticalPosition::Top,
+        )
+        .is_ok()
+    }
+
+    fn draw<D: DrawTarget>(
+        &self,
+        text: &str,
+        top_left: Point,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<(), D::Error> {
+        use u8g2_fonts::types::{FontColor, VerticalPosition};
+        use u8g2_fonts::Error;
+
+        match self.render(
+            text,
+            top_left - u8g2_extent(self, text).top_left,
+            VerticalPosition::Top,
+            FontColor::Transparent(color),
+            target,
+        ) {
+            Ok(_) => Ok(()),
+            Err(Error::DisplayError(e)) => Err(e),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// The text backend of a [Style]
+#[derive(Clone, Copy, Default)]
+pub enum TextBackend {
+    /// The style's [default_font](Style::default_font)
+    #[default]
+    Mono,
+    /// A u8g2 font. The renderer has to live in a `static`, e.g.
+    /// `static FONT: FontRenderer = FontRenderer::new::<fonts::u8g2_font_helvB12_tf>();`
+    #[cfg(feature = "u8g2-fonts")]
+    U8g2(&'static u8g2_fonts::FontRenderer),
+}
+
+impl fmt::Debug for TextBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextBackend::Mono => f.write_str("Mono"),
+            #[cfg(feature = "u8g2-fonts")]
+            TextBackend::U8g2(_) => f.write_str("U8g2"),
+        }
+    }
+}
+
+/// A font of any backend, as used by widgets
+#[derive(Clone, Copy)]
+pub enum Font<'a> {
+    /// A [MonoFont]
+    Mono(MonoFont<'a>),
+    /// A u8g2 font
+    #[cfg(feature = "u8g2-fonts")]
+    U8g2(&'a u8g2_fonts::FontRenderer),
+}
+
+impl<'a> Font<'a> {
+    /// Returns the font a widget should use: `custom` if set, the [Style]'s font otherwise.
+    pub fn resolve<COL: PixelColor>(custom: Option<MonoFont<'a>>, style: &Style<COL>) -> Self {
+        match (custom, style.text_backend) {
+            (Some(font), _) => Font::Mono(font),
+            (None, TextBackend::Mono) => Font::Mono(style.default_font),
+            #[cfg(feature = "u8g2-fonts")]
+            (None, TextBackend::U8g2(font)) => Font::U8g2(font),
+        }
+    }
+}
+
+impl TextRenderer for Font<'_> {
+    fn measure(&self, text: &str) -> Size {
+        match self {
+            Font::Mono(font) => font.measure(text),
+            #[cfg(feature = "u8g2-fonts")]
+            Font::U8g2(font) => font.measure(text),
+        }
+    }
+
+    fn line_height(&self) -> u32 {
+        match self {
+            Font::Mono(font) => font.line_height(),
+            #[cfg(feature = "u8g2-fonts")]
+            Font::U8g2(font) => font.line_height(),
+        }
+    }
+
+    fn has_glyph(&self, c: char) -> bool {
+        match self {
+            Font::Mono(font) => font.has_glyph(c),
+            #[cfg(feature = "u8g2-fonts")]
+            Font::U8g2(font) => font.has_glyph(c),
+        }
+    }
+
+    fn draw<D: DrawTarget>(
+        &self,
+        text: &str,
+        top_left: Point,
+        color: D::Color,
+        target: &mut D,
+    ) -> Result<(), D::Error> {
+        match self {
+            Font::Mono(font) => font.draw(text, top_left, color, target),
+            #[cfg(feature = "u8g2-fonts")]
+            Font::U8g2(font) => font.draw(text, top_left, color, target),
+        }
+    }
+}
+
+/// Splits `text` into lines: at every `\n` and, if `max_width` is set, at the last space
+/// before a line gets wider than `max_width`.
+///
+/// A word wider than `max_width` on its own is broken between characters. The space a line is
+/// wrapped at isn't part of either line.
+///
+/// # Example
+///
+/// ```
+/// # use embedded_graphics::mono_font::ascii::FONT_6X10;
+/// use kolibri_embedded_gui::text::lines;
+///
+/// // 6px per character: at most 10 characters per line
+/// let mut lines = lines(&FONT_6X10, "Start the motor\nnow", Some(60));
+/// assert_eq!(lines.next(), Some("Start the"));
+/// assert_eq!(lines.next(), Some("motor"));
+/// assert_eq!(lines.next(), Some("now"));
+/// assert_eq!(lines.next(), None);
+/// ```
+pub fn lines<'r, 't, R: TextRenderer>(
+    renderer: &'r R,
+    text: &'t str,
+    max_width: Option<u32>,
+) -> Lines<'r, 't, R> {
+    Lines {
+        renderer,
+        rest: Some(text),
+        max_width,
+    }
+}
+
+/// Returns the size of `text` split into [lines]: the width of the widest line, and the
+/// [line height](TextRenderer::line_height) times the number of lines.
+pub fn measure_lines<R: TextRenderer>(renderer: &R, text: &str, max_width: Option<u32>) -> Size {
+    let (width, count) = lines(renderer, text, max_width).fold((0, 0), |(width, count), line| {
+        (width.max(renderer.measure(line).width), count + 1)
+    });
+    Size::new(width, count * renderer.line_height())
+}
+
+/// Returns the top left corner to draw text of the measured `size` at, so that it is centered
+/// vertically in `area` and aligned horizontally by `align`.
+///
+/// Widgets lay out their text with this, so that the same text lines up on the same pixel rows
+/// in a [Label](crate::label::Label), a [Button](crate::button::Button) or any other widget of
+/// the same height. The measured box is centered, i.e. the font's full line from the top of its
+/// ascent to the bottom of its descent (and [MonoFont] underlines, which stay within it), not
+/// the ink of the particular text, so that text doesn't jump up and down when it changes.
+///
+/// Text larger than `area` starts at its top left corner (and is clipped by the widget).
+///
+/// # Example
+///
+/// ```
+/// # use embedded_graphics::prelude::*;
+/// # use embedded_graphics::primitives::Rectangle;
+/// # use kolibri_embedded_gui::ui::HorizontalAlign;
+/// use kolibri_embedded_gui::text::text_layout;
+///
+/// let area = Rectangle::new(Point::new(10, 10), Size::new(48, 25));
+/// let top_left = text_layout(Size::new(27, 15), area, HorizontalAlign::Center);
+/// assert_eq!(top_left, Point::new(20, 15));
+/// ```
+pub fn text_layout(size: Size, area: Rectangle, align: HorizontalAlign) -> Point {
+    let free = area.size.saturating_sub(size);
+    let x = match align {
+        HorizontalAlign::Left => 0,
+        HorizontalAlign::Center => free.width / 2,
+        HorizontalAlign::Right => free.width,
+    };
+    area.top_left + Point::new(x as i32, (free.height / 2) as i32)
+}
+
+/// Returns the longest beginning of the single-line `text` that is at most `max_width` wide,
+/// e.g. for a label in a fixed-size widget.
+///
+/// # Example
+///
+/// ```
+/// # use embedded_graphics::mono_font::ascii::FONT_6X10;
+/// use kolibri_embedded_gui::text::truncate;
+///
+/// // 6px per character
+/// assert_eq!(truncate(&FONT_6X10, "Temperature", 30), "Tempe");
+/// assert_eq!(truncate(&FONT_6X10, "Temp", 30), "Temp");
+/// ```
+pub fn truncate<'t, R: TextRenderer>(renderer: &R, text: &'t str, max_width: u32) -> &'t str {
+    if renderer.measure(text).width <= max_width {
+        return text;
+    }
+    let end = breaks(text)
+        .take_while(|&i| renderer.measure(&text[..i]).width <= max_width)
+        .last()
+        .unwrap_or(0);
+    &text[..end]
+}
+
+/// Returns the ellipsis [ellipsize] appends: `"…"`, or `"..."` if the font lacks that glyph
+/// (like the ASCII and Latin-1 [MonoFont]s).
+pub fn ellipsis<R: TextRenderer>(renderer: &R) -> &'static str {
+    match renderer.has_glyph('…') {
+        true => "…",
+        false => "...",
+    }
+}
+
+/// Length of the buffers widgets [ellipsize] their text into, in bytes
+pub(crate) const ELLIPSIZED_LEN: usize = 96;
+
+/// Returns `text` if it is at most `max_width` wide. Otherwise, returns as much of its beginning
+/// as fits together with an [ellipsis], followed by the ellipsis, built in `buf`.
+///
+/// The result is measured as a whole, so it is never wider than `max_width` (unless not even
+/// the ellipsis fits, which is then cut off like with [truncate]). If `buf` is too small for
+/// everything that would fit, less of `text` is kept.
+///
+/// # Example
+///
+/// ```
+/// # use embedded_graphics::mono_font::ascii::FONT_6X10;
+/// use kolibri_embedded_gui::format::Buf;
+/// use kolibri_embedded_gui::text::ellipsize;
+///
+/// let mut buf = Buf::<32>::new();
+/// // 6px per character, the ASCII font has no "…"
+/// assert_eq!(ellipsize(&FONT_6X10, "Temperature sensor", 60, &mut buf), "Tempera...");
+/// assert_eq!(ellipsize(&FONT_6X10, "Temp", 60, &mut buf), "Temp");
+/// ```
+pub fn ellipsize<'t, R: TextRenderer, const N: usize>(
+    renderer: &R,
+    text: &'t str,
+    max_width: u32,
+    buf: &'t mut Buf<N>,
+) -> &'t str {
+    if renderer.measure(text).width <= max_width {
+        return text;
+    }
+    let ellipsis = ellipsis(renderer);
+    let candidate = |buf: &mut Buf<N>, end: usize| {
+        buf.clear();
+        // no space in front of the ellipsis
+        buf.push_str(text[..end].trim_end());
+        buf.push_str(ellipsis);
+    };
+
+    let mut fitting = 0;
+    for end in breaks(text) {
+        candidate(buf, end);
+        if buf.truncated() || renderer.measure(buf.as_str()).width > max_width {
+            break;
+        }
+        fitting = end;
+    }
+    candidate(buf, fitting);
+    truncate(renderer, buf.as_str(), max_width)
+}
+
+/// Returns the offsets within `text` where it can be cut, in ascending order: the starts of
+/// all characters except the first one and combining marks.
+fn breaks(text: &str) -> impl Iterator<Item = usize> + '_ {
+    text.char_indices()
+        .skip(1)
+        .filter(|&(_, c)| !is_combining(c))
+        .map(|(i, _)| i)
+}
+
+/// Returns whether `c` is a combining mark, i.e. belongs to the character in front of it
+fn is_combining(c: char) -> bool {
+    matches!(
+        c,
+        '\u{300}'..='\u{36F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+    )
+}
+
+/// Iterator over the lines of a text, see [lines]
+pub struct Lines<'r, 't, R> {
+    renderer: &'r R,
+    rest: Option<&'t str>,
+    max_width: Option<u32>,
+}
+
+impl<'t, R: TextRenderer> Iterator for Lines<'_, 't, R> {
+    type Item = &'t str;
+
+    fn next(&mut self) -> Option<&'t str> {
+        let rest = self.rest?;
+        let (paragraph, after) = match rest.split_once('\n') {
+            Some((paragraph, after)) => (paragraph, Some(after)),
+            None => (rest, None),
+        };
+
+        let fits = |line: &str| {
+            self.max_width
+                .is_none_or(|max| self.renderer.measure(line).width <= max)
+        };
+        // (a single character is a line, even if it doesn't fit)
+        let first = match breaks(paragraph).next() {
+            Some(first) if !fits(paragraph) => first,
+            _ => {
+                self.rest = after;
+                return Some(paragraph);
+            }
+        };
+
+        // wrap at the last space that fits
+        let space = paragraph
+            .match_indices(' ')
+            .map(|(i, _)| i)
+            .filter(|&i| i > 0)
+            .take_while(|&i| fits(&paragraph[..i]))
+            .last();
+        let (line, next) = match space {
+            Some(i) => (&paragraph[..i], &rest[i + 1..]),
+            None => {
+                // a single word too wide for a line: break it, keeping at least one character
+                let i = breaks(paragraph)
+                    .skip(1)
+                    .take_while(|&i| fits(&paragraph[..i]))
+                    .last()
+                    .unwrap_or(first);
+                (&paragraph[..i], &rest[i..])
+            }
+        };
+        self.rest = Some(next);
+        Some(line)
+    }
+}
+
+/// Text drawn with a [TextRenderer], as a [Drawable] (e.g. for [Ui::draw](crate::ui::Ui::draw))
+pub struct RenderedText<'a, R, COL> {
+    renderer: &'a R,
+    text: &'a str,
+    top_left: Point,
+    color: COL,
+}
+
+impl<'a, R: TextRenderer, COL: PixelColor> RenderedText<'a, R, COL> {
+    /// Creates the drawable for `text`, with the top left corner of its measured box at
+    /// `top_left`.
+    pub fn new(renderer: &'a R, text: &'a str, top_left: Point, color: COL) -> Self {
+        Self {
+            renderer,
+            text,
+            top_left,
+            color,
+        }
+    }
+}
+
+impl<R: TextRenderer, COL: PixelColor> Drawable for RenderedText<'_, R, COL> {
+    type Color = COL;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = COL>>(&self, target: &mut D) -> Result<(), D::Error> {
+        self.renderer
+            .draw(self.text, self.top_left, self.color, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::mono_font::ascii;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    /// Draws `text` and checks that all pixels are within the measured box
+    fn assert_drawn_within_measure(font: &impl TextRenderer, text: &str) {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let top_left = Point::new(3, 5);
+        RenderedText::new(font, text, top_left, Rgb565::WHITE)
+            .draw(&mut display)
+            .unwrap();
+
+        let measured = Rectangle::new(top_left, font.measure(text));
+        let drawn = display.affected_area();
+        assert!(!drawn.is_zero_sized());
+        assert_eq!(drawn.intersection(&measured), drawn, "{text:?}");
+    }
+
+    #[test]
+    fn mono_draws_within_measure() {
+        for text in ["Agy", "|", "two\nlines"] {
+            assert_drawn_within_measure(&ascii::FONT_6X10, text);
+            assert_drawn_within_measure(&Font::Mono(ascii::FONT_9X15), text);
+        }
+        assert_eq!(ascii::FONT_6X10.measure("abc"), Size::new(18, 10));
+    }
+
+    fn split(text: &str, max_width: Option<u32>) -> heapless::Vec<&str, 8> {
+        split_with(&ascii::FONT_6X10, text, max_width)
+    }
+
+    fn split_with<'t>(
+        font: &MonoFont,
+        text: &'t str,
+        max_width: Option<u32>,
+    ) -> heapless::Vec<&'t str, 8> {
+        lines(font, text, max_width).collect()
+    }
+
+    #[test]
+    fn splits_lines() {
+        assert_eq!(split("one\ntwo", None).as_slice(), &["one", "two"]);
+        assert_eq!(split("", None).as_slice(), &[""]);
+        assert_eq!(split("trailing\n", None).as_slice(), &["trailing", ""]);
+
+        // wrapping at spaces: 4 characters per line
+        assert_eq!(split("ab cd ef", Some(24)).as_slice(), &["ab", "cd", "ef"]);
+        assert_eq!(split("ab c\nd", Some(24)).as_slice(), &["ab c", "d"]);
+        // breaking words that are too long
+        assert_eq!(
+            split("abcdefghi x", Some(24)).as_slice(),
+            &["abcd", "efgh", "i x"]
+        );
+        assert_eq!(split("äbc", Some(1)).as_slice(), &["ä", "b", "c"]);
+        assert_eq!(split("ab\ncd", Some(1)).as_slice(), &["a", "b", "c", "d"]);
+
+        assert_eq!(
+            measure_lines(&ascii::FONT_6X10, "ab cd\nefg", Some(24)),
+            Size::new(18, 30)
+        );
+    }
+
+    #[test]
+    fn mono_fonts_have_their_replacement_glyph() {
+        use embedded_graphics::mono_font::iso_8859_1;
+
+        for font in [ascii::FONT_6X10, iso_8859_1::FONT_6X10] {
+            // '?' is drawn for missing characters, and is a character of the font as well
+            assert!(font.has_glyph('?'));
+            assert!(font.has_glyph('>') && font.has_glyph('@'));
+            assert!(!font.has_glyph('…') && !font.has_glyph('\u{FFFF}'));
+        }
+        assert!(iso_8859_1::FONT_6X10.has_glyph('é'));
+        assert!(!ascii::FONT_6X10.has_glyph('é'));
+    }
+
+    #[test]
+    fn ellipsized_text_fits_exactly() {
+        let font = ascii::FONT_6X10;
+        assert!(font.has_glyph('A') && !font.has_glyph('…'));
+        assert_eq!(ellipsis(&font), "...");
+
+        let text = "Temperature sensor";
+        let mut buf = Buf::<32>::new();
+        for max_width in 0..=120 {
+            let result = ellipsize(&font, text, max_width, &mut buf);
+            let width = font.measure(result).width;
+            assert!(width <= max_width, "{result:?} is {width}px");
+            if max_width >= 108 {
+                assert_eq!(result, text);
+            } else if max_width >= 18 {
+                // one more character (after the trimmed space) wouldn't fit
+                let kept = result.len() - 3;
+                let next = kept + text[kept..].find(|c| c != ' ').unwrap() + 1;
+                let longer = font.measure(&text[..next]).width + font.measure("...").width;
+                assert!(result.ends_with("...") && longer > max_width, "{result:?}");
+            }
+        }
+        // no space in front of the ellipsis
+        assert_eq!(ellipsize(&font, text, 90, &mut buf), "Temperature...");
+        // a small buffer keeps less of the text
+        assert_eq!(
+            ellipsize(&font, text, 100, &mut Buf::<8>::new()),
+            "Tempe..."
+        );
+    }
+
+    #[test]
+    fn mixed_scripts_are_cut_between_characters() {
+        use embedded_graphics::mono_font::{iso_8859_1, iso_8859_5};
+
+        let texts = [
+            "21 °C",
+            "Température",
+            "Температура 21°C",
+            "e\u{301}te\u{301}",
+        ];
+        let mut buf = Buf::<32>::new();
+        for font in [iso_8859_1::FONT_6X10, iso_8859_5::FONT_6X10] {
+            for text in texts {
+                // 6px per character, missing ones are drawn as a replacement glyph
+                assert_eq!(font.measure(text).width, 6 * text.chars().count() as u32);
+                for max_width in 0..=100 {
+                    let truncated = truncate(&font, text, max_width);
+                    assert!(font.measure(truncated).width <= max_width);
+                    assert!(!text[truncated.len()..].starts_with('\u{301}'));
+
+                    let ellipsized = ellipsize(&font, text, max_width, &mut buf);
+                    assert!(font.measure(ellipsized).width <= max_width);
+
+                    for line in lines(&font, text, Some(max_width)) {
+                        let single = breaks(line).next().is_none();
+                        assert!(font.measure(line).width <= max_width || single);
+                        assert!(!line.starts_with('\u{301}'), "{line:?}");
+                    }
+                }
+            }
+        }
+
+        let font = iso_8859_5::FONT_6X10;
+        assert_eq!(truncate(&font, "Температура", 30), "Темпе");
+        assert_eq!(
+            ellipsize(&font, "Температура 21°C", 60, &mut buf),
+            "Темпера..."
+        );
+        assert_eq!(
+            split_with(&font, "Температура 21°C", Some(66)).as_slice(),
+            &["Температура", "21°C"]
+        );
+        // the accent stays with its letter
+        assert_eq!(truncate(&font, "e\u{301}te\u{301}", 6), "");
+        assert_eq!(truncate(&font, "e\u{301}te\u{301}", 12), "e\u{301}");
+    }
+
+    #[cfg(feature = "u8g2-fonts")]
+    #[test]
+    fn u8g2_draws_within_measure() {
+        use u8g2_fonts::{fonts, FontRenderer};
+
+        let font = FontRenderer::new::<fonts::u8g2_font_helvB08_tf>();
+        for text in ["Agy", "|", "jÄ"] {
+            assert_drawn_within_measure(&font, text);
+            assert_drawn_within_measure(&Font::U8g2(&font), text);
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Multi-line text with proper text layout, using [embedded-text](embedded_text).
+//!
+//! [Label](crate::label::Label) draws a single run of text. For long-form text (help screens,
+//! licenses, descriptions), [TextBox] lays out text inside a fixed-size box:
+//!
+//! - wrapping on word boundaries (and at soft hyphens, `\u{AD}`)
+//! - left, centered, right or justified alignment
+//! - top, middle or bottom vertical alignment
+//! - optionally shrinking the box to the height of the text
+//!
+//! This module is only available with the `embedded-text` feature.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # use kolibri_embedded_gui::text_box::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! ui.add(
+//!     TextBox::new(
+//!         "Kolibri is a GUI framework made to be as lightweight as its namesake.",
+//!         Size::new(200, 100),
+//!     )
+//!     .alignment(HorizontalAlignment::Justified)
+//!     .shrink_to_text(true)
+//!     .smartstate(smartstates.nxt()),
+//! );
+//! ```
+
+use crate::smartstate::{Container, Smartstate};
+use crate::style::font_key;
+use crate::ui::{GuiError, GuiResult, Response, Ui, Widget};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::Size;
+use embedded_graphics::mono_font::{MonoFont, MonoTextStyle};
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_text::style::{HeightMode, TextBoxStyleBuilder, VerticalOverdraw};
+
+pub use embedded_text::alignment::{HorizontalAlignment, VerticalAlignment};
+
+/// A widget that lays out text inside a box, see the [module documentation](self).
+pub struct TextBox<'a> {
+    text: &'a str,
+    size: Size,
+    font: Option<MonoFont<'a>>,
+    alignment: HorizontalAlignment,
+    vertical_alignment: VerticalAlignment,
+    shrink_to_text: bool,
+    paragraph_spacing: u32,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<'a> TextBox<'a> {
+    /// Creates a text box of the given size. Text that doesn't fit is cut off after the last
+    /// full line.
+    pub fn new(text: &'a str, size: Size) -> Self {
+        Self {
+            text,
+            size,
+            font: None,
+            alignment: HorizontalAlignment::Left,
+            vertical_alignment: VerticalAlignment::Top,
+            shrink_to_text: false,
+            paragraph_spacing: 0,
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Sets a custom font. The style's default font is used otherwise.
+    pub fn with_font(mut self, font: MonoFont<'a>) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Sets the horizontal alignment of the lines. The default is [HorizontalAlignment::Left].
+    pub fn alignment(mut self, alignment: HorizontalAlignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// Sets the vertical alignment of the text within the box. The default is
+    /// [VerticalAlignment::Top].
+    pub fn vertical_alignment(mut self, alignment: VerticalAlignment) -> Self {
+        self.vertical_alignment = alignment;
+        self
+    }
+
+    /// If enabled, only allocates the height the text needs (but at most the box's height).
+    pub fn shrink_to_text(mut self, shrink: bool) -> Self {
+        self.shrink_to_text = shrink;
+        self
+    }
+
+    /// Sets the additional space between paragraphs (i.e. after `\n`), in pixels.
+    pub fn paragraph_spacing(mut self, spacing: u32) -> Self {
+        self.paragraph_spacing = spacing;
+        self
+    }
+
+    /// Adds a smartstate, so that the text box is only redrawn if its text, size or layout
+    /// options change.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+impl Widget for TextBox<'_> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let font = self.font.unwrap_or(ui.style().default_font);
+        let character_style = MonoTextStyle::new(&font, ui.style().text_color);
+        let textbox_style = TextBoxStyleBuilder::new()
+            .alignment(self.alignment)
+            .vertical_alignment(self.vertical_alignment)
+            .paragraph_spacing(self.paragraph_spacing)
+            .height_mode(HeightMode::Exact(VerticalOverdraw::FullRowsOnly))
+            .build();
+
+        // allocate space
+        let height = if self.shrink_to_text {
+            textbox_style
+                .measure_text_height(&character_style, self.text, self.size.width)
+                .min(self.size.height)
+        } else {
+            self.size.height
+        };
+        let iresponse = ui.allocate_space(Size::new(self.size.width, height))?;
+
+        // check smartstate
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(&(
+            self.text,
+            iresponse.area.size.width,
+            iresponse.area.size.height,
+            self.alignment as u8,
+            self.vertical_alignment as u8,
+            self.paragraph_spacing,
+            self.font.as_ref().map(font_key),
+        ));
+        state.mix(ui.style_key());
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(iresponse.area);
+        if redraw {
+            trace!(
+                "TextBox smartstate: {} -> {}",
+                self.smartstate.clone_inner(),
+                state
+            );
+        }
+        self.smartstate.modify(|st| *st = state);
+
+        // draw
+        if redraw {
+            ui.start_drawing(&iresponse.area);
+            if !ui.cleared() {
+                ui.clear_area(iresponse.area)?;
+            }
+
+            let text_box = embedded_text::TextBox::with_textbox_style(
+                self.text,
+                iresponse.area,
+                character_style,
+                textbox_style,
+            );
+            ui.draw(&text_box)
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw TextBox")))?;
+
+            ui.finalize()?;
+        }
+
+        Ok(Response::new(iresponse).set_redraw(redraw))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smartstate::SmartstateProvider;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::mono_font::ascii;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    fn add(display: &mut MockDisplay<Rgb565>, text_box: TextBox) -> Response {
+        let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+        ui.add(text_box.with_font(ascii::FONT_6X10))
+    }
+
+    #[test]
+    fn shrinks_to_wrapped_text() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+
+        // 9 characters per line: "hello" / "world foo"
+        let size = Size::new(54, 40);
+        let response = add(
+            &mut display,
+            TextBox::new("hello world foo", size).shrink_to_text(true),
+        );
+        assert_eq!(response.internal.area.size, Size::new(54, 20));
+
+        let response = add(&mut display, TextBox::new("hello world foo", size));
+        assert_eq!(response.internal.area.size, size);
+    }
+
+    #[test]
+    fn redraws_when_text_changes() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<1>::new();
+        let size = Size::new(54, 40);
+
+        for (text, redraw) in [("one", true), ("one", false), ("two", true)] {
+            smartstates.restart_counter();
+            let text_box = TextBox::new(text, size).smartstate(smartstates.nxt());
+            assert_eq!(add(&mut display, text_box).redrawn(), redraw);
+        }
+    }
+
+    #[test]
+    fn redraws_when_the_font_changes() {
+        let mut display = MockDisplay::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+        let size = Size::new(54, 40);
+
+        for (font, redraw) in [
+            (ascii::FONT_6X10, true),
+            (ascii::FONT_6X10, false),
+            (ascii::FONT_5X8, true),
+        ] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            let text_box = TextBox::new("text", size)
+                .with_font(font)
+                .smartstate(&mut smartstate);
+            let response = ui.add(text_box);
+            assert_eq!(response.error(), None);
+            assert_eq!(response.redrawn(), redraw);
+        }
+    }
+}
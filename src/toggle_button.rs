@@ -2,6 +2,8 @@
 //!
 //! A customizable toggle button widget that provides a clickable on/off control.
 //!
+//! This module is only available with the `controls` feature (on by default).
+//!
 //! The toggle button provides a traditional button-style control that maintains its state,
 //! featuring different visual styles for active and inactive states. It supports text labels
 //! and integrates with the framework's theming system for consistent appearance.
@@ -10,15 +12,15 @@
 //! with the framework's [Smartstate] system for efficient rendering.
 //!
 use crate::smartstate::{Container, Smartstate};
-use crate::ui::{GuiError, GuiResult, Interaction, Response, Ui, Widget};
+use crate::text::{Font, RenderedText, TextRenderer};
+use crate::ui::{
+    resolve_widget_style, rounded_rect_clamped, GuiError, GuiResult, Interaction, Response, Ui,
+    Widget, WIDGET_STYLE_BUCKETS,
+};
 use core::cmp::max;
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::{Point, Size};
-use embedded_graphics::mono_font::MonoTextStyle;
 use embedded_graphics::pixelcolor::PixelColor;
-use embedded_graphics::prelude::*;
-use embedded_graphics::primitives::{PrimitiveStyleBuilder, Rectangle, RoundedRectangle};
-use embedded_graphics::text::{Baseline, Text};
 
 /// A button widget that can be toggled on and off.
 ///
@@ -31,19 +33,13 @@ use embedded_graphics::text::{Baseline, Text};
 /// ```no_run
 /// # use embedded_graphics::pixelcolor::Rgb565;
 /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-/// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-/// # use kolibri_embedded_gui::ui::Ui;
+/// # use kolibri_embedded_gui::prelude::*;
 /// # use embedded_graphics::prelude::*;
 /// # use embedded_graphics::primitives::Rectangle;
-/// # use embedded_iconoir::prelude::*;
-/// # use kolibri_embedded_gui::ui::*;
-/// # use kolibri_embedded_gui::label::*;
-/// # use kolibri_embedded_gui::smartstate::*;
 /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 /// # let output_settings = OutputSettingsBuilder::new().build();
 /// # let mut window = Window::new("Kolibri Example", &output_settings);
 /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-/// # use kolibri_embedded_gui::toggle_button::ToggleButton;
 /// let mut state = false;
 ///
 /// loop {
@@ -120,6 +116,8 @@ impl<'a> ToggleButton<'a> {
     /// Sets a custom corner radius for the toggle button.
     ///
     /// If not specified, the button will use the corner radius from the UI style.
+    /// Radii larger than half of the button's smaller dimension are clamped, so
+    /// `u32::MAX` gives a pill-shaped button.
     ///
     /// # Arguments
     /// * `radius` - The corner radius in pixels
@@ -138,43 +136,38 @@ impl Widget for ToggleButton<'_> {
         ui: &mut Ui<DRAW, COL>,
     ) -> GuiResult<Response> {
         // Prepare text
-        let font = ui.style().default_font;
-        let mut text = Text::new(
-            self.label,
-            Point::zero(),
-            MonoTextStyle::new(&font, ui.style().text_color),
-        );
+        let font = Font::resolve(None, ui.style());
 
         // Determine size
-        let text_bounds = text.bounding_box();
-        let padding = ui.style().spacing.button_padding;
+        let text_size = font.measure(self.label);
+        let padding = ui.style().widget_padding(ui.style().spacing.button_padding);
         let border = ui.style().border_width;
         let height = ui.style().default_widget_height;
 
         let size = Size::new(
-            text_bounds.size.width + 2 * padding.width + 2 * border,
-            max(
-                text_bounds.size.height + 2 * padding.height + 2 * border,
-                height,
-            ),
+            text_size.width + 2 * padding.width + 2 * border,
+            max(text_size.height + 2 * padding.height + 2 * border, height),
         );
 
         // Allocate space
-        let iresponse = ui.allocate_space(size)?;
+        let iresponse = ui.allocate_space(size + ui.style().shadow_size())?;
+        let body = ui.style().without_shadow(iresponse.area);
 
         // Position text
-        text.translate_mut(
-            iresponse.area.top_left
+        let text = RenderedText::new(
+            &font,
+            self.label,
+            body.top_left
                 + Point::new(
                     (padding.width + border) as i32,
                     (padding.height + border) as i32,
                 ),
+            ui.style().text_color,
         );
-        text.text_style.baseline = Baseline::Top;
 
         // Handle interaction
         let mut changed = false;
-        if let Interaction::Release(_) = iresponse.interaction {
+        if iresponse.interaction.is_release() {
             *self.active = !*self.active;
             changed = true;
         }
@@ -183,68 +176,31 @@ impl Widget for ToggleButton<'_> {
         let prevstate = self.smartstate.clone_inner();
 
         // Determine widget style
-        let style = match (*self.active, iresponse.interaction) {
-            (true, Interaction::Click(_) | Interaction::Drag(_) | Interaction::Release(_)) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(1));
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-                    .fill_color(ui.style().primary_color)
-                    .build()
-            }
-            (true, Interaction::Hover(_)) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(2));
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-                    .fill_color(ui.style().primary_color)
-                    .build()
-            }
-            (true, _) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(3));
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().border_color)
-                    .stroke_width(ui.style().border_width)
-                    .fill_color(ui.style().primary_color)
-                    .build()
-            }
-            (false, Interaction::Click(_) | Interaction::Drag(_) | Interaction::Release(_)) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(4));
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-                    .fill_color(ui.style().primary_color)
-                    .build()
-            }
-            (false, Interaction::Hover(_)) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(5));
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().highlight_border_color)
-                    .stroke_width(ui.style().highlight_border_width)
-                    .fill_color(ui.style().highlight_item_background_color)
-                    .build()
-            }
-            (false, _) => {
-                self.smartstate.modify(|st| *st = Smartstate::state(6));
-                PrimitiveStyleBuilder::new()
-                    .stroke_color(ui.style().border_color)
-                    .stroke_width(ui.style().border_width)
-                    .fill_color(ui.style().item_background_color)
-                    .build()
-            }
-        };
+        let (mut style, bucket) = resolve_widget_style(ui.style(), iresponse.interaction);
+        if *self.active {
+            // active buttons are always filled with the primary color
+            style.fill_color = Some(ui.style().primary_color);
+        }
+        let active = *self.active as u32;
+        self.smartstate.modify(|st| {
+            *st = Smartstate::state(active * WIDGET_STYLE_BUCKETS + bucket as u32);
+            st.mix(ui.style_key());
+        });
 
-        let redraw = !self.smartstate.eq_option(&prevstate) || changed;
+        let redraw =
+            !self.smartstate.eq_option(&prevstate) || changed || ui.invalidated(iresponse.area);
 
         if redraw {
+            trace!(
+                "ToggleButton smartstate: {} -> {}",
+                prevstate,
+                self.smartstate.clone_inner()
+            );
             ui.start_drawing(&iresponse.area);
 
-            let corner_radius = self.corner_radius.unwrap_or(ui.style().corner_radius);
-            let rounded_rect = RoundedRectangle::with_equal_corners(
-                Rectangle::new(iresponse.area.top_left, iresponse.area.size),
-                Size::new(corner_radius, corner_radius),
-            );
-            ui.draw(&rounded_rect.into_styled(style))
+            let corner_radius = ui.style().corner_radius_for(self.corner_radius, body.size);
+            let rounded_rect = rounded_rect_clamped(body, corner_radius);
+            ui.draw_background(rounded_rect, style)
                 .map_err(|_| GuiError::DrawError(Some("Couldn't draw ToggleButton")))?;
             ui.draw(&text)
                 .map_err(|_| GuiError::DrawError(Some("Couldn't draw ToggleButton label")))?;
@@ -252,7 +208,7 @@ impl Widget for ToggleButton<'_> {
             ui.finalize()?;
         }
 
-        let click = matches!(iresponse.interaction, Interaction::Release(_));
+        let click = iresponse.interaction.is_release();
         let down = matches!(
             iresponse.interaction,
             Interaction::Click(_) | Interaction::Drag(_)
@@ -263,4 +219,31 @@ impl Widget for ToggleButton<'_> {
             .set_down(down)
             .set_changed(changed))
     }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    #[test]
+    fn padding_changes_the_size() {
+        let size = |padding| {
+            let mut display = MockDisplay::<Rgb565>::new();
+            let mut style = medsize_rgb565_style();
+            style.spacing.button_padding = padding;
+            let mut active = false;
+            let mut ui = Ui::new_fullscreen(&mut display, style);
+            ui.measure_widget(&mut ToggleButton::new("+", &mut active))
+        };
+        let (small, large) = (size(Size::new(2, 2)), size(Size::new(12, 24)));
+        assert_eq!(large.width, small.width + 20);
+        assert!(large.height > small.height);
+    }
 }
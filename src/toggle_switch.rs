@@ -2,6 +2,8 @@
 //!
 //! A customizable toggle switch widget that provides a simple on/off control.
 //!
+//! This module is only available with the `controls` feature (on by default).
+//!
 //! The toggle switch provides a slider-style control similar to those found in mobile applications,
 //! with a background track and sliding knob that moves between on/off positions.
 //! The widget supports customizable dimensions, colors based on theme, and hover/interaction states.
@@ -9,15 +11,14 @@
 //! This widget is part of the Kolibri embedded GUI framework's core widget set and integrates
 //! with the framework's [Smartstate] system for efficient rendering.
 
+use crate::anim::{Interpolate, Transition, ONE};
 use crate::smartstate::{Container, Smartstate};
-use crate::ui::{GuiError, GuiResult, Interaction, Response, Ui, Widget};
+use crate::ui::{rounded_rect_clamped, GuiError, GuiResult, Interaction, Response, Ui, Widget};
 use core::cmp::max;
 use embedded_graphics::draw_target::DrawTarget;
 use embedded_graphics::geometry::{Point, Size};
 use embedded_graphics::prelude::*;
-use embedded_graphics::primitives::{
-    Circle, CornerRadii, PrimitiveStyleBuilder, Rectangle, RoundedRectangle,
-};
+use embedded_graphics::primitives::{Circle, PrimitiveStyleBuilder, Rectangle};
 
 /// A toggle switch widget that provides an animated on/off control with a sliding knob.
 ///
@@ -36,19 +37,13 @@ use embedded_graphics::primitives::{
 /// ```no_run
 /// # use embedded_graphics::pixelcolor::Rgb565;
 /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-/// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-/// # use kolibri_embedded_gui::ui::Ui;
+/// # use kolibri_embedded_gui::prelude::*;
 /// # use embedded_graphics::prelude::*;
 /// # use embedded_graphics::primitives::Rectangle;
-/// # use embedded_iconoir::prelude::*;
-/// # use kolibri_embedded_gui::ui::*;
-/// # use kolibri_embedded_gui::label::*;
-/// # use kolibri_embedded_gui::smartstate::*;
 /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
 /// # let output_settings = OutputSettingsBuilder::new().build();
 /// # let mut window = Window::new("Kolibri Example", &output_settings);
 /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-/// # use kolibri_embedded_gui::toggle_switch::ToggleSwitch;
 /// let mut state = false;
 ///
 /// // Create a basic toggle switch
@@ -64,6 +59,7 @@ pub struct ToggleSwitch<'a> {
     smartstate: Container<'a, Smartstate>,
     width: u32,
     height: u32,
+    animate_ms: u32,
 }
 
 impl<'a> ToggleSwitch<'a> {
@@ -76,6 +72,7 @@ impl<'a> ToggleSwitch<'a> {
             smartstate: Container::empty(),
             width: 50,
             height: 25,
+            animate_ms: 0,
         }
     }
 
@@ -99,19 +96,13 @@ impl<'a> ToggleSwitch<'a> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # use kolibri_embedded_gui::toggle_switch::ToggleSwitch;
     /// let mut state = false;
     /// ui.add(ToggleSwitch::new(&mut state).width(60));
     /// ```
@@ -129,19 +120,13 @@ impl<'a> ToggleSwitch<'a> {
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # use kolibri_embedded_gui::smartstate::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # use kolibri_embedded_gui::toggle_switch::ToggleSwitch;
     /// let mut state = false;
     ///
     /// loop {
@@ -153,6 +138,18 @@ impl<'a> ToggleSwitch<'a> {
         self.height = max(height, 15); // Enforce a minimum height
         self
     }
+
+    /// Slides the knob to its new position over `duration_ms` when the switch is clicked. `0`
+    /// (the default) doesn't animate.
+    ///
+    /// Only the look is animated, the value changes right away. The track changes its color
+    /// when the knob passes the middle. The animation needs a
+    /// [smartstate](ToggleSwitch::smartstate) and a frame time ([Ui::dt]), otherwise the knob
+    /// jumps.
+    pub fn animate(mut self, duration_ms: u32) -> Self {
+        self.animate_ms = duration_ms;
+        self
+    }
 }
 
 impl Widget for ToggleSwitch<'_> {
@@ -172,22 +169,34 @@ impl Widget for ToggleSwitch<'_> {
 
         // Handle interaction
         let mut changed = false;
-        if matches!(iresponse.interaction, Interaction::Release(_)) {
+        if iresponse.interaction.is_release() {
             *self.active = !*self.active;
             changed = true;
         }
 
+        let prevstate = self.smartstate.clone_inner();
+        let transition = Transition::update(prevstate, changed, self.animate_ms, ui.dt());
+        // knob position, from the left (0) to the right (ONE)
+        let position = match (transition.running(), *self.active) {
+            (false, true) => ONE,
+            (false, false) => 0,
+            (true, true) => transition.progress(),
+            (true, false) => ONE - transition.progress(),
+        };
+
         // Colors for active and inactive states
-        let switch_color = if *self.active {
+        let switch_color = if position * 2 >= ONE {
             ui.style().primary_color
         } else {
-            ui.style().item_background_color
+            ui.style().item_background_color.color()
         };
 
         let knob_color = match iresponse.interaction {
-            Interaction::Click(_) | Interaction::Drag(_) => ui.style().primary_color,
+            Interaction::Click(_) | Interaction::Drag(_) | Interaction::ActivatedByFocus(_) => {
+                ui.style().primary_color
+            }
             Interaction::Hover(_) => ui.style().highlight_item_background_color,
-            _ => ui.style().item_background_color,
+            _ => ui.style().item_background_color.color(),
         };
 
         // Determine border color based on interaction
@@ -196,36 +205,50 @@ impl Widget for ToggleSwitch<'_> {
             _ => ui.style().border_color,
         };
 
-        // Inside the draw method, replace the current smartstate handling with:
-
-        let prevstate = self.smartstate.clone_inner();
-
         // Determine state based on both toggle state and interaction
         let state = match (iresponse.interaction, *self.active) {
-            (Interaction::Click(_) | Interaction::Drag(_), true) => 1,
-            (Interaction::Click(_) | Interaction::Drag(_), false) => 2,
+            (
+                Interaction::Click(_) | Interaction::Drag(_) | Interaction::ActivatedByFocus(_),
+                true,
+            ) => 1,
+            (
+                Interaction::Click(_) | Interaction::Drag(_) | Interaction::ActivatedByFocus(_),
+                false,
+            ) => 2,
             (Interaction::Hover(_), true) => 3,
             (Interaction::Hover(_), false) => 4,
             (_, true) => 5,
             (_, false) => 6,
         };
 
-        self.smartstate.modify(|st| *st = Smartstate::state(state));
+        if transition.running() {
+            self.smartstate.modify(|st| *st = transition.smartstate());
+            ui.request_repaint();
+        } else {
+            self.smartstate.modify(|st| *st = Smartstate::state(state));
+            self.smartstate.modify(|st| st.mix(ui.style_key()));
+        }
 
         // Determine if redraw is needed based on state change or active state change
-        let redraw = !self.smartstate.eq_option(&prevstate) || changed;
+        let redraw =
+            !self.smartstate.eq_option(&prevstate) || changed || ui.invalidated(iresponse.area);
 
         if redraw {
+            trace!(
+                "ToggleSwitch smartstate: {} -> {}",
+                prevstate,
+                self.smartstate.clone_inner()
+            );
             ui.start_drawing(&iresponse.area);
 
             // Define the switch background (rounded rectangle)
-            let switch_rect = RoundedRectangle::new(
+            let switch_rect = rounded_rect_clamped(
                 Rectangle::new(
                     iresponse.area.top_left
                         + Point::new(padding.width as i32, padding.height as i32),
                     Size::new(self.width, self.height),
                 ),
-                CornerRadii::new(Size::new(self.height / 2, self.height / 2)),
+                self.height / 2,
             );
 
             let switch_style = PrimitiveStyleBuilder::new()
@@ -239,18 +262,14 @@ impl Widget for ToggleSwitch<'_> {
 
             // Calculate knob position
             let knob_radius = (self.height / 2) - ui.style().border_width;
-            let knob_x = if *self.active {
-                // Positioned on the right
-                iresponse.area.top_left.x + padding.width as i32 + self.width as i32
-                    - knob_radius as i32
-                    - ui.style().border_width as i32
-            } else {
-                // Positioned on the left
-                iresponse.area.top_left.x
-                    + padding.width as i32
-                    + knob_radius as i32
-                    + ui.style().border_width as i32
-            };
+            let right = iresponse.area.top_left.x + padding.width as i32 + self.width as i32
+                - knob_radius as i32
+                - ui.style().border_width as i32;
+            let left = iresponse.area.top_left.x
+                + padding.width as i32
+                + knob_radius as i32
+                + ui.style().border_width as i32;
+            let knob_x = i32::interpolate(left, right, position);
 
             let knob_center = Point::new(
                 knob_x,
@@ -271,7 +290,7 @@ impl Widget for ToggleSwitch<'_> {
             ui.finalize()?;
         }
 
-        let click = matches!(iresponse.interaction, Interaction::Release(_));
+        let click = iresponse.interaction.is_release();
         let down = matches!(
             iresponse.interaction,
             Interaction::Click(_) | Interaction::Drag(_)
@@ -282,4 +301,8 @@ impl Widget for ToggleSwitch<'_> {
             .set_down(down)
             .set_changed(changed))
     }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
 }
@@ -0,0 +1,122 @@
+//! # Integer Trigonometry
+//!
+//! Sine and cosine of whole degrees from a table, as fixed-point numbers scaled by [ONE], for
+//! widgets drawing rotated things (like a [Compass](crate::compass::Compass)) without floating
+//! point math, which is slow on MCUs without an FPU.
+//!
+//! Angles are in degrees, and [polar] measures them clockwise from "up", like a compass
+//! heading, so that 90° points to the right.
+//!
+//! For drawing within a circle (e.g. a dial), [circle_span] returns the pixels of a filled
+//! circle row by row, to draw or clip scanlines to.
+//!
+//! ```
+//! use embedded_graphics::geometry::Point;
+//! use kolibri_embedded_gui::trig::{polar, sin, ONE};
+//!
+//! assert_eq!(sin(30), ONE / 2);
+//! // 10 pixels east of the center
+//! assert_eq!(polar(Point::new(20, 20), 10, 90), Point::new(30, 20));
+//! ```
+
+use embedded_graphics::geometry::Point;
+
+/// The fixed-point value of 1.0 returned by [sin] and [cos]
+pub const ONE: i32 = 1024;
+
+/// `sin(d) * ONE` for each degree `d` of the first quadrant
+const QUARTER: [i16; 91] = [
+    0, 18, 36, 54, 71, 89, 107, 125, 143, 160, //
+    178, 195, 213, 230, 248, 265, 282, 299, 316, 333, //
+    350, 367, 384, 400, 416, 433, 449, 465, 481, 496, //
+    512, 527, 543, 558, 573, 587, 602, 616, 630, 644, //
+    658, 672, 685, 698, 711, 724, 737, 749, 761, 773, //
+    784, 796, 807, 818, 828, 839, 849, 859, 868, 878, //
+    887, 896, 904, 912, 920, 928, 935, 943, 949, 956, //
+    962, 968, 974, 979, 984, 989, 994, 998, 1002, 1005, //
+    1008, 1011, 1014, 1016, 1018, 1020, 1022, 1023, 1023, 1024, //
+    1024,
+];
+
+/// Returns the sine of `degrees` (any whole number of degrees), scaled by [ONE].
+pub fn sin(degrees: i32) -> i32 {
+    let degrees = degrees.rem_euclid(360);
+    match degrees {
+        0..=90 => QUARTER[degrees as usize] as i32,
+        91..=180 => QUARTER[(180 - degrees) as usize] as i32,
+        181..=270 => -(QUARTER[(degrees - 180) as usize] as i32),
+        _ => -(QUARTER[(360 - degrees) as usize] as i32),
+    }
+}
+
+/// Returns the cosine of `degrees` (any whole number of degrees), scaled by [ONE].
+pub fn cos(degrees: i32) -> i32 {
+    sin(degrees + 90)
+}
+
+/// Returns the point `radius` pixels from `center` in the direction of `degrees`, clockwise
+/// from up.
+pub fn polar(center: Point, radius: i32, degrees: i32) -> Point {
+    // rounded to the nearest pixel
+    let scale = |value: i32| (radius * value + value.signum() * ONE / 2) / ONE;
+    center + Point::new(scale(sin(degrees)), -scale(cos(degrees)))
+}
+
+/// Returns the first and last column of row `y` of a filled circle around `center`, or `None`
+/// if the row is outside of it.
+///
+/// The circle is `2 * radius + 1` pixels wide, and includes the pixels whose center is less
+/// than half a pixel outside of `radius`, for a round outline.
+pub fn circle_span(center: Point, radius: i32, y: i32) -> Option<(i32, i32)> {
+    let dy = y - center.y;
+    let squared = radius * radius + radius - dy * dy;
+    (radius >= 0 && squared >= 0).then(|| {
+        let half = isqrt(squared as u32) as i32;
+        (center.x - half, center.x + half)
+    })
+}
+
+/// Returns the integer square root of `value`, rounded down
+pub(crate) fn isqrt(value: u32) -> u32 {
+    // Newton's method, from above
+    let mut root = value;
+    let mut next = root / 2 + root % 2;
+    while next < root {
+        root = next;
+        next = (root + value / root) / 2;
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quadrants_mirror_the_table() {
+        assert_eq!([0, 90, 180, 270].map(sin), [0, ONE, 0, -ONE]);
+        assert_eq!([0, 90, 180, 270].map(cos), [ONE, 0, -ONE, 0]);
+        assert_eq!(sin(-30), -ONE / 2);
+        assert_eq!(sin(210), sin(570));
+        assert_eq!(cos(60), ONE / 2);
+        let center = Point::new(5, 5);
+        assert_eq!(polar(center, 4, 0), Point::new(5, 1));
+        assert_eq!(polar(center, 4, 180), Point::new(5, 9));
+        assert_eq!(polar(center, 4, 270), Point::new(1, 5));
+    }
+
+    #[test]
+    fn circle_spans_are_symmetric_and_round() {
+        assert_eq!(
+            [0, 1, 2, 15, 16, 17, 1 << 20].map(isqrt),
+            [0, 1, 1, 3, 4, 4, 1 << 10]
+        );
+        let center = Point::new(10, 10);
+        assert_eq!(circle_span(center, 3, 10), Some((7, 13)));
+        assert_eq!(circle_span(center, 3, 8), Some((8, 12)));
+        assert_eq!(circle_span(center, 3, 7), Some((9, 11)));
+        assert_eq!(circle_span(center, 3, 13), Some((9, 11)));
+        assert_eq!(circle_span(center, 3, 14), None);
+        assert_eq!(circle_span(center, 0, 10), Some((10, 10)));
+    }
+}
@@ -1,22 +1,51 @@
 use crate::framebuf::WidgetFramebuf;
-use crate::style::Style;
+use crate::img::Sprite;
+use crate::smartstate::{Container, Smartstate};
+use crate::style::{clamp_corner_radius, Fill, Style, StylePatch};
+use crate::widget_builder::WidgetBuilder;
 use core::cell::UnsafeCell;
 use core::cmp::{max, min};
 use core::fmt::Debug;
+use core::hash::{BuildHasher, Hash};
 use core::ops::{Add, AddAssign, Sub};
-use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::draw_target::{Clipped, DrawTarget};
 use embedded_graphics::geometry::Dimensions;
-use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::pixelcolor::{PixelColor, Rgb888};
 use embedded_graphics::prelude::*;
 use embedded_graphics::primitives::{
-    PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, StyledDrawable,
+    PrimitiveStyle, PrimitiveStyleBuilder, Rectangle, RoundedRectangle, StyledDrawable,
 };
 use embedded_graphics::{Drawable, Pixel};
+use foldhash::fast::FixedState;
 
+/// Errors that can occur while laying out or drawing the [Ui].
+///
+/// The layout errors carry the sizes involved, so overflowing layouts can be debugged on-target
+/// (e.g. by printing the error via its [Display](core::fmt::Display) implementation).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum GuiError {
-    /// The widget is too large to fit in the bounds with the current constraints
-    NoSpaceLeft,
+    /// The widget is too wide to fit into the remaining width of the row
+    NoHorizontalSpace {
+        /// Width the widget needs, in pixels
+        needed: u32,
+        /// Width that was available, in pixels
+        available: u32,
+    },
+    /// The widget is too tall to fit into the remaining height of the [Ui]
+    NoVerticalSpace {
+        /// Height the widget (or its row) needs, in pixels
+        needed: u32,
+        /// Height that was available, in pixels
+        available: u32,
+    },
+    /// The requested bounds (e.g. of a sub-[Ui]) don't fit into the available bounds
+    BoundsTooSmall {
+        /// Size that was requested
+        needed: Size,
+        /// Size that was available
+        available: Size,
+    },
     /// The Drawable returned an error while drawing
     // TODO: (maybe) add better error handling here
     // The rationale for the 'static str is that generics are annoying to implement,
@@ -26,9 +55,11 @@ pub enum GuiError {
     // If you have a better idea, a PR is much appreciated.
     // (maybe a Box<dyn Error> with alloc feature gate? Or a 'String' (heapless / alloc) and format!()?)
     DrawError(Option<&'static str>),
-
-    /// The requested operation would cause the bounds to be different from the expected size
-    BoundsError,
+    /// The widget wasn't drawn, as the [Ui] is only measuring widget sizes (see [Ui::equal_width_row])
+    Measuring,
+    /// The overlay wasn't drawn, as there are already [MAX_OVERLAYS] overlays this frame (see
+    /// [Ui::draw_overlay])
+    TooManyOverlays,
 }
 
 impl GuiError {
@@ -37,24 +68,87 @@ impl GuiError {
     }
 }
 
+impl core::fmt::Display for GuiError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            GuiError::NoHorizontalSpace { needed, available } => write!(
+                f,
+                "no horizontal space left: needed {needed}px, {available}px available"
+            ),
+            GuiError::NoVerticalSpace { needed, available } => write!(
+                f,
+                "no vertical space left: needed {needed}px, {available}px available"
+            ),
+            GuiError::BoundsTooSmall { needed, available } => write!(
+                f,
+                "bounds too small: needed {}x{}, {}x{} available",
+                needed.width, needed.height, available.width, available.height
+            ),
+            GuiError::DrawError(Some(msg)) => write!(f, "draw error: {msg}"),
+            GuiError::DrawError(None) => write!(f, "draw error"),
+            GuiError::Measuring => write!(f, "not drawn, only measuring"),
+            GuiError::TooManyOverlays => write!(f, "too many overlays"),
+        }
+    }
+}
+
 pub type GuiResult<T> = Result<T, GuiError>;
 
+/// Paints the background of the given area instead of filling it with the background color
+/// (see [Ui::set_background_painter]).
+///
+/// The draw target is clipped to the area, so the painter may draw beyond it (e.g. a whole
+/// image or gradient) and only the requested part ends up on the screen.
+pub type BackgroundPainter<DRAW> =
+    fn(&mut Clipped<'_, DRAW>, Rectangle) -> Result<(), <DRAW as DrawTarget>::Error>;
+
+/// Moves the pixels of an area of the draw target by an offset, e.g. with the display's
+/// hardware scrolling or a `copy_within` of a framebuffer (see [Ui::set_area_mover]).
+///
+/// Returns whether the pixels were moved. Pixels moved out of the area may be discarded, and
+/// the pixels the move uncovers may be left as they are.
+pub type AreaMover<DRAW> = fn(&mut DRAW, Rectangle, Point) -> bool;
+
+/// Vertical hardware scrolling of a display, e.g. with the scroll registers of an ST7789 or
+/// ILI9341, for the app's display wrapper to implement (see [Ui::set_scroll_assist]).
+pub trait ScrollAssist {
+    /// Scrolls the pixels of the rows of `area`, which spans the whole width of the display,
+    /// down by `lines` (up if negative).
+    ///
+    /// Returns whether they were scrolled. Rows scrolled out of the area may be discarded, and
+    /// the rows the scroll uncovers may be left as they are.
+    fn hw_scroll(&mut self, area: Rectangle, lines: i32) -> bool;
+}
+
 pub struct InternalResponse {
     pub area: Rectangle,
     pub interaction: Interaction,
+    /// What produced the interaction, if known (see [Response::source])
+    pub source: Option<InteractionSource>,
 }
 
 impl InternalResponse {
     pub fn new(area: Rectangle, interaction: Interaction) -> Self {
-        Self { area, interaction }
+        Self {
+            area,
+            interaction,
+            source: None,
+        }
     }
 
     pub fn empty() -> Self {
         Self {
             area: Rectangle::new(Point::zero(), Size::zero()),
             interaction: Interaction::None,
+            source: None,
         }
     }
+
+    /// Sets what produced the interaction, see [Response::source].
+    pub fn with_source(mut self, source: InteractionSource) -> Self {
+        self.source = Some(source);
+        self
+    }
 }
 
 /// Response for UI interaction / space allocation and such
@@ -80,8 +174,15 @@ pub struct Response {
     /// Always `false` for something like a [`Button`](crate::button::Button).
     pub changed: bool,
 
+    /// Whether the input was confirmed (e.g. [KeyEvent::Enter] in a text-accepting widget)
+    pub confirmed: bool,
+
     /// Whether the widget had an error while drawing
     pub error: Option<GuiError>,
+
+    /// Where the pointer was in the last frame, if the widget remembers it (see
+    /// [PointerState]). The edge events like [Response::pressed_started] need it.
+    pub previous_pointer: Option<PointerState>,
 }
 
 // builder pattern
@@ -92,8 +193,10 @@ impl Response {
             click: false,
             redraw: true,
             changed: false,
+            confirmed: false,
             down: false,
             error: None,
+            previous_pointer: None,
         }
     }
 
@@ -116,6 +219,11 @@ impl Response {
         self
     }
 
+    pub fn set_confirmed(mut self, confirmed: bool) -> Self {
+        self.confirmed = confirmed;
+        self
+    }
+
     pub fn set_error(mut self, error: GuiError) -> Self {
         self.error = Some(error);
         self
@@ -126,6 +234,11 @@ impl Response {
         self
     }
 
+    pub fn set_previous_pointer(mut self, previous: Option<PointerState>) -> Self {
+        self.previous_pointer = previous;
+        self
+    }
+
     /// Check whether the widget was clicked (as in successfully interacted with)
     pub fn clicked(&self) -> bool {
         self.click
@@ -148,11 +261,143 @@ impl Response {
         self.changed
     }
 
+    /// Check whether the input was confirmed (e.g. enter was pressed in a text-accepting widget)
+    pub fn confirmed(&self) -> bool {
+        self.confirmed
+    }
+
     /// Check whether the widget had an error while drawing
     /// (e.g. the underlying draw target returned an error), no space was left, ...
     pub fn error(&self) -> Option<GuiError> {
         self.error
     }
+
+    /// Returns the area the widget was drawn in.
+    ///
+    /// Use it for decorations drawn with [Ui::draw] after the widget, e.g. a line connecting
+    /// two widgets. It is empty if the widget had no space left (see [Response::error]).
+    pub fn area(&self) -> Rectangle {
+        self.internal.area
+    }
+
+    /// Returns the interaction with the widget in this frame.
+    pub fn interaction(&self) -> Interaction {
+        self.internal.interaction
+    }
+
+    /// Returns the point where the widget is interacted with (pressed, hovered, ...),
+    /// `None` if it isn't.
+    pub fn interaction_point(&self) -> Option<Point> {
+        self.internal.interaction.get_point()
+    }
+
+    /// Returns what produced the interaction with the widget, e.g. to tell a tap on a touch
+    /// panel from a press of a hardware button activating the focused widget.
+    ///
+    /// ## Returns
+    ///
+    /// `None` if the widget isn't interacted with (or the widget doesn't report the source).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::ui::InteractionSource;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let response = ui.add(Button::new("OK"));
+    /// if response.clicked() && response.source() == Some(InteractionSource::Synthetic) {
+    ///     println!("confirmed with the encoder");
+    /// }
+    /// ```
+    pub fn source(&self) -> Option<InteractionSource> {
+        self.internal
+            .source
+            .filter(|_| self.internal.interaction != Interaction::None)
+    }
+
+    /// Returns whether the widget started being pressed this frame, e.g. to play a click sound
+    /// or a haptic pulse once when the finger lands on a button.
+    ///
+    /// Like the other edge events ([Response::hover_entered], [Response::hover_exited]), this
+    /// compares with the last frame, which the widget has to remember in its smartstate. It is
+    /// `false` for widgets without a smartstate, or that don't support edge events.
+    pub fn pressed_started(&self) -> bool {
+        let now = PointerState::of(self.internal.interaction);
+        now == PointerState::Pressed
+            && self
+                .previous_pointer
+                .is_some_and(|previous| previous != PointerState::Pressed)
+    }
+
+    /// Returns whether the pointer got onto the widget this frame, hovering or pressing it
+    /// (see [Response::pressed_started]).
+    pub fn hover_entered(&self) -> bool {
+        let now = PointerState::of(self.internal.interaction);
+        now != PointerState::Outside && self.previous_pointer == Some(PointerState::Outside)
+    }
+
+    /// Returns whether the pointer left the widget this frame (see
+    /// [Response::pressed_started]). Releasing the widget counts as leaving it, like a finger
+    /// lifted off a touch panel.
+    pub fn hover_exited(&self) -> bool {
+        let now = PointerState::of(self.internal.interaction);
+        now == PointerState::Outside
+            && self
+                .previous_pointer
+                .is_some_and(|previous| previous != PointerState::Outside)
+    }
+
+    /// Returns the information of the response as a [ResponseCtx], which can be destructured.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::ui::ResponseCtx;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let ResponseCtx { area, clicked, .. } = ui.add(Button::new("Tab")).ctx();
+    /// if clicked {
+    ///     println!("the tab at {:?} was clicked", area.top_left);
+    /// }
+    /// ```
+    pub fn ctx(&self) -> ResponseCtx {
+        ResponseCtx {
+            area: self.internal.area,
+            interaction: self.internal.interaction,
+            point: self.interaction_point(),
+            clicked: self.click,
+            down: self.down,
+            changed: self.changed,
+            confirmed: self.confirmed,
+        }
+    }
+}
+
+/// The information of a [Response] as plain fields, see [Response::ctx]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResponseCtx {
+    /// The area the widget was drawn in
+    pub area: Rectangle,
+    /// The interaction with the widget
+    pub interaction: Interaction,
+    /// The point of the interaction, if any
+    pub point: Option<Point>,
+    /// Whether the widget was clicked
+    pub clicked: bool,
+    /// Whether the widget is held down
+    pub down: bool,
+    /// Whether the underlying data changed
+    pub changed: bool,
+    /// Whether the input was confirmed
+    pub confirmed: bool,
 }
 
 pub trait Widget {
@@ -160,6 +405,57 @@ pub trait Widget {
         &mut self,
         ui: &mut Ui<DRAW, COL>,
     ) -> GuiResult<Response>;
+
+    /// Returns the widget's smartstate, if it has one. After the widget is drawn, the [Ui]
+    /// records its area in it (see [Drawn Areas](crate::smartstate::Smartstate#drawn-areas)).
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        None
+    }
+}
+
+/// Adding a borrowed widget draws it, e.g. for adding the widgets of a slice one by one.
+impl<W: Widget + ?Sized> Widget for &mut W {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        (**self).draw(ui)
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        (**self).smartstate_mut()
+    }
+}
+
+/// A widget for [Ui]s of one color type `COL`, e.g. as it has colors of its own (like
+/// [RichLabel](crate::rich_label::RichLabel)).
+///
+/// Every [Widget] works with all color types, and is a [TypedWidget] for each of them. The
+/// methods adding widgets to a [Ui] (like [Ui::add]) take either.
+pub trait TypedWidget<COL: PixelColor> {
+    /// Draws the widget, like [Widget::draw].
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response>;
+
+    /// Returns the widget's smartstate, like [Widget::smartstate_mut].
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        None
+    }
+}
+
+impl<W: Widget, COL: PixelColor> TypedWidget<COL> for W {
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        self.draw(ui)
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        Widget::smartstate_mut(self)
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -185,6 +481,33 @@ impl Default for Align {
     }
 }
 
+/// What the [Ui] does with a widget that doesn't fit into the remaining width of the current row.
+///
+/// Set with [Ui::set_overflow_policy].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Adding the widget fails with [GuiError::NoHorizontalSpace].
+    Error,
+    /// The widget is moved to a new row (like after [Ui::new_row]). Widgets that are
+    /// wider than the entire [Ui] still fail with [GuiError::NoHorizontalSpace].
+    #[default]
+    Wrap,
+    /// The widget is placed anyway, and its drawing is clipped to the [Ui]'s bounds.
+    Clip,
+}
+
+/// A widget width relative to the current row, e.g. for
+/// [Button::width_fraction](crate::button::Button::width_fraction).
+///
+/// Resolved into pixels with [Ui::resolve_width] when the widget is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeWidth {
+    /// A share of the row in per mille, e.g. `500` for half of it (see [Ui::resolve_width])
+    PerMille(u16),
+    /// Everything that is left of the row
+    Fill,
+}
+
 #[derive(Clone, Debug)]
 /// Struct for managing placing of widgets in the [Ui]
 ///
@@ -206,86 +529,102 @@ struct Placer {
     row_height: u32,
     /// Bounds of the placer
     bounds: Size,
-    /// Whether to wrap to the next row if the widget doesn't fit
-    wrap: bool,
-    #[allow(unused)] // TODO: use in the future
+    /// What to do if the widget doesn't fit into the current row
+    overflow: Overflow,
+    /// Alignment of widgets; the vertical part aligns widgets within the row height
     align: Align,
+    /// Height every row starts with, overriding the style's default widget height
+    fixed_row_height: Option<u32>,
+    /// Width the [RelativeWidth::PerMille] widths of the current row are shares of
+    fraction_base: Option<u32>,
 }
 
 impl Placer {
-    /// Create a new placer with the given bounds, wrapping and alignment
-    pub fn new(bounds: Size, wrap: bool, align: Align) -> Self {
+    /// Create a new placer with the given bounds, overflow policy and alignment
+    pub fn new(bounds: Size, overflow: Overflow, align: Align) -> Self {
         Placer {
             row: 0,
             col: 0,
             pos: Point::zero(),
             row_height: 0,
             bounds,
-            wrap,
+            overflow,
             align,
+            fixed_row_height: None,
+            fraction_base: None,
         }
     }
 
-    /// **STUB / NOT YET USEFUL**: Set the wrap setting of the placer
-    #[allow(unused)] // TODO: use in the future
-    pub fn set_wrap(&mut self, wrap: bool) {
-        self.wrap = wrap;
-    }
-
-    /// **STUB / NOT YET USEFUL**: Set the alignment of the placer
-    #[allow(unused)] // TODO: use in the future
+    /// Set the alignment of the placer
+    #[allow(unused)]
     pub fn set_align(&mut self, align: Align) {
         self.align = align;
     }
 
-    /// Allocate the next widget with the given `size`, explicitly disabling wrapping for this operation
+    /// Allocate the next widget with the given `size`, failing if it doesn't fit regardless of the overflow policy
     ///
     /// ## Returns
     ///
     /// Returns the allocated rectangle, or an error if the widget doesn't fit
     fn next_no_wrap(&mut self, size: Size) -> GuiResult<Rectangle> {
-        let wrap = self.wrap;
-        self.wrap = false;
+        let overflow = self.overflow;
+        self.overflow = Overflow::Error;
         let res = self.next(size);
-        self.wrap = wrap;
+        self.overflow = overflow;
         res
     }
 
-    /// Allocate the next widget with the given `size`.
+    /// Allocate the next widget with the given `size` in the current row.
+    ///
+    /// Wrapping to the next row is up to the caller (see [Ui::allocate_space]), as a new row needs
+    /// the [Ui]'s style. With [Overflow::Clip], widgets overflowing the bounds are placed anyway,
+    /// as long as they start inside of the bounds.
     ///
     /// ## Returns
     ///
     /// Returns the allocated rectangle, or an error if the widget doesn't fit
     fn next(&mut self, size: Size) -> GuiResult<Rectangle> {
+        if self.overflow == Overflow::Clip {
+            if self.pos.x as u32 >= self.bounds.width || self.pos.y as u32 >= self.bounds.height {
+                return Err(self.no_space_error(size));
+            }
+            self.row_height = max(self.row_height, size.height);
+            let item_pos = self.pos;
+            self.pos = Point::new(self.pos.x + size.width as i32, self.pos.y);
+            self.col += 1;
+            return Ok(self.row_aligned(item_pos, size));
+        }
+
         // check that it's in bounds (size < bounds)
-        if !self.check_bounds(size) {
-            return Err(GuiError::NoSpaceLeft);
+        if size.width > self.bounds.width {
+            return Err(GuiError::NoHorizontalSpace {
+                needed: size.width,
+                available: self.bounds.width,
+            });
+        }
+        if size.height > self.bounds.height {
+            return Err(GuiError::NoVerticalSpace {
+                needed: size.height,
+                available: self.bounds.height,
+            });
         }
 
         // set bounds (temporary) TODO: do this PROPERLY!
-        if let Align(HorizontalAlign::Center, _) = self.align {
+        if let Align(HorizontalAlign::Center | HorizontalAlign::Right, _) = self.align {
             if self.pos.x as u32 + size.width > self.bounds.width {
-                return Err(GuiError::NoSpaceLeft);
+                return Err(self.no_space_error(size));
             }
-            // Calculate the right x-coordinate to center the widget between self.pos.x and self.bounds.width
-            // (self.bounds.width + self.pos.x as u32 - size.width) / 2
-            self.pos.x = ((self.bounds.width + self.pos.x as u32 - size.width) / 2) as i32;
+            self.pos.x = match self.align.0 {
+                // Calculate the right x-coordinate to center the widget between self.pos.x and self.bounds.width
+                // (self.bounds.width + self.pos.x as u32 - size.width) / 2
+                HorizontalAlign::Center => (self.bounds.width + self.pos.x as u32 - size.width) / 2,
+                _ => self.bounds.width - size.width,
+            } as i32;
         };
         let right = size.width + self.pos.x as u32;
-        let mut bottom = max(self.row_height, size.height) + self.pos.y as u32;
+        let bottom = max(self.row_height, size.height) + self.pos.y as u32;
         if !self.check_bounds(Size::new(right, bottom)) {
-            if self.wrap {
-                bottom = self.pos.y as u32 + max(self.row_height, size.height);
-                // check that wrap fits
-                if !self.check_bounds(Size::new(0, bottom)) {
-                    return Err(GuiError::NoSpaceLeft);
-                }
-
-                // perform wrap
-                self.new_row(size.height); // TODO: better / proper wrap impl
-            } else {
-                return Err(GuiError::NoSpaceLeft);
-            }
+            return Err(self.no_space_error(size));
         }
 
         // set new col height (expand if necessary)
@@ -294,11 +633,23 @@ impl Placer {
         // set new position
         let item_pos = self.pos;
         self.pos = Point::new(right as i32, self.pos.y);
+        self.col += 1;
 
-        Ok(Rectangle::new(
-            item_pos,
-            Size::new(size.width, self.row_height),
-        ))
+        Ok(self.row_aligned(item_pos, size))
+    }
+
+    /// Returns the area of a widget of the given `size` placed at `pos`, aligned vertically within
+    /// the current row height.
+    ///
+    /// With [VerticalAlign::Top], the area spans the full row height (as widgets have always done).
+    /// Otherwise, the area has the widget's own height and is offset within the row.
+    fn row_aligned(&self, pos: Point, size: Size) -> Rectangle {
+        let free = self.row_height.saturating_sub(size.height);
+        match self.align.1 {
+            VerticalAlign::Top => Rectangle::new(pos, Size::new(size.width, self.row_height)),
+            VerticalAlign::Center => Rectangle::new(pos + Point::new(0, (free / 2) as i32), size),
+            VerticalAlign::Bottom => Rectangle::new(pos + Point::new(0, free as i32), size),
+        }
     }
 
     #[allow(unused)]
@@ -311,17 +662,39 @@ impl Placer {
     /// The remaining space hearby is a rectangle from the current plcer X and Y position to the bottom right corner of the bounds.
     fn space_available(&self) -> Size {
         Size::new(
-            self.bounds.width - self.pos.x as u32,
-            self.bounds.height - self.pos.y as u32,
+            self.bounds.width.saturating_sub(self.pos.x as u32),
+            self.bounds.height.saturating_sub(self.pos.y as u32),
         )
     }
 
+    /// Returns the error describing why a widget of the given `size` doesn't fit at the current position.
+    ///
+    /// Horizontal overflow takes precedence, as it is the more common (and more fixable) case.
+    fn no_space_error(&self, size: Size) -> GuiError {
+        let available = Size::new(
+            self.bounds.width.saturating_sub(self.pos.x as u32),
+            self.bounds.height.saturating_sub(self.pos.y as u32),
+        );
+        if size.width > available.width {
+            GuiError::NoHorizontalSpace {
+                needed: size.width,
+                available: available.width,
+            }
+        } else {
+            GuiError::NoVerticalSpace {
+                needed: max(self.row_height, size.height),
+                available: available.height,
+            }
+        }
+    }
+
     /// Advances to the next row, setting the initial row height to the provided `height` parameter.
     fn new_row(&mut self, height: u32) {
         self.row += 1;
         self.col = 0;
         self.pos = Point::new(0, self.pos.y + self.row_height as i32);
         self.row_height = height;
+        self.fraction_base = None;
     }
 
     /// Returns the current row height.
@@ -348,6 +721,19 @@ struct Painter<'a, COL: PixelColor, DRAW: DrawTarget<Color = COL>> {
     target: &'a mut DRAW,
     buffer_raw: Option<UnsafeCell<&'a mut [COL]>>,
     framebuf: Option<WidgetFramebuf<'a, COL>>,
+    /// Area outside of which drawing is discarded, if any
+    clip: Option<Rectangle>,
+    /// Area the clip is limited to, the clip of the parent painter (see [Painter::with_subpainter])
+    limit: Option<Rectangle>,
+    /// Number of pixels sent to the target (see [PerfStats::pixels_drawn])
+    pixels_drawn: u32,
+    /// Number of items drawn (see [PerfStats::draw_calls])
+    draw_calls: u32,
+    /// Rotation of everything drawn (see [Ui::set_rotation])
+    rotation: Rotation,
+    /// Areas of the overlays drawn this frame, which nothing else draws over (see
+    /// [Ui::draw_overlay])
+    masks: heapless::Vec<Rectangle, MAX_OVERLAYS>,
 }
 
 impl<'a, COL: PixelColor, DRAW: DrawTarget<Color = COL>> Painter<'a, COL, DRAW> {
@@ -357,6 +743,12 @@ impl<'a, COL: PixelColor, DRAW: DrawTarget<Color = COL>> Painter<'a, COL, DRAW>
             target,
             buffer_raw: None,
             framebuf: None,
+            clip: None,
+            limit: None,
+            pixels_drawn: 0,
+            draw_calls: 0,
+            rotation: Rotation::Deg0,
+            masks: heapless::Vec::new(),
         }
     }
 
@@ -375,11 +767,16 @@ impl<'a, COL: PixelColor, DRAW: DrawTarget<Color = COL>> Painter<'a, COL, DRAW>
     ///
     /// If a framebuffer is provided and of sufficient size, it gets used for subsequent drawing operations.
     /// If not, the drawing operations are performed directly on the [DrawTarget].
+    /// The framebuffer only covers the visible part of the area, within the clip.
     fn start_drawing(&mut self, area: &Rectangle) {
         if self.framebuf.is_some() {
             panic!("Framebuffer is already in use!");
         }
 
+        let area = self.visible(*area);
+        if area.is_zero_sized() {
+            return;
+        }
         if let Some(buf) = &mut self.buffer_raw {
             let buf = WidgetFramebuf::try_new(unsafe { *buf.get() }, area.size, area.top_left);
             if let Some(framebuf) = buf {
@@ -388,6 +785,14 @@ impl<'a, COL: PixelColor, DRAW: DrawTarget<Color = COL>> Painter<'a, COL, DRAW>
         }
     }
 
+    /// Returns the part of `area` within the clip
+    fn visible(&self, area: Rectangle) -> Rectangle {
+        match self.clip {
+            Some(clip) => area.intersection(&clip),
+            None => area,
+        }
+    }
+
     /// Clear the buffer, if it's available.
     ///
     /// ## Returns
@@ -411,8 +816,14 @@ impl<'a, COL: PixelColor, DRAW: DrawTarget<Color = COL>> Painter<'a, COL, DRAW>
     /// If not, the drawing operations are performed directly on the [DrawTarget].
     fn finalize(&mut self) -> GuiResult<()> {
         if let Some(buf) = &mut self.framebuf {
-            buf.draw(self.target)
-                .map_err(|_| GuiError::draw_error("Failed to draw framebuf"))?;
+            let mut target =
+                CountingTarget::new(self.target, &mut self.pixels_drawn, self.rotation)
+                    .masked(&self.masks);
+            match self.clip {
+                Some(clip) => buf.draw(&mut target.clipped(&clip)),
+                None => buf.draw(&mut target),
+            }
+            .map_err(|_| GuiError::draw_error("Failed to draw framebuf"))?;
             self.framebuf = None;
         }
         Ok(())
@@ -427,12 +838,19 @@ impl<'a, COL: PixelColor, DRAW: DrawTarget<Color = COL>> Painter<'a, COL, DRAW>
     ///
     /// Returns a `GuiResult` indicating whether the drawing was successful.
     fn draw(&mut self, item: &impl Drawable<Color = COL>) -> GuiResult<()> {
+        self.draw_calls += 1;
         if let Some(buffer) = &mut self.framebuf {
             item.draw(buffer)
                 .ok() /* cannot fail */;
         } else {
-            item.draw(self.target)
-                .map_err(|_| GuiError::draw_error("Failed to draw item"))?;
+            let mut target =
+                CountingTarget::new(self.target, &mut self.pixels_drawn, self.rotation)
+                    .masked(&self.masks);
+            match self.clip {
+                Some(clip) => item.draw(&mut target.clipped(&clip)),
+                None => item.draw(&mut target),
+            }
+            .map_err(|_| GuiError::draw_error("Failed to draw item"))?;
         }
         Ok(())
     }
@@ -463,6 +881,10 @@ impl<'a, COL: PixelColor, DRAW: DrawTarget<Color = COL>> Painter<'a, COL, DRAW>
         if let Some(buf) = &mut self.buffer_raw {
             subpainter.set_buffer(unsafe { *buf.get() });
         }
+        subpainter.clip = self.clip;
+        subpainter.limit = self.clip;
+        subpainter.rotation = self.rotation;
+        subpainter.masks = self.masks.clone();
         (f)(subpainter)?;
         Ok(())
     }
@@ -473,7 +895,7 @@ impl<COL: PixelColor, DRAW: DrawTarget<Color = COL, Error = ERR>, ERR> Dimension
     for Painter<'_, COL, DRAW>
 {
     fn bounding_box(&self) -> Rectangle {
-        self.target.bounding_box()
+        self.rotation.rotated_frame(self.target.bounding_box())
     }
 }
 
@@ -489,1549 +911,7568 @@ impl<COL: PixelColor, DRAW: DrawTarget<Color = COL, Error = ERR>, ERR> DrawTarge
     where
         I: IntoIterator<Item = Pixel<Self::Color>>,
     {
-        self.target.draw_iter(pixels)
+        CountingTarget::new(self.target, &mut self.pixels_drawn, self.rotation)
+            .masked(&self.masks)
+            .draw_iter(pixels)
     }
 }
 
-/// Interaction with the UI
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
-pub enum Interaction {
-    /// A click event (mouse, touch, etc. down)
-    Click(Point),
-    /// A drag event (mouse, touch, etc. move while clicked)
-    Drag(Point),
-    /// A release event (mouse, touch, etc. up)
-    Release(Point),
-    /// A hover event (mouse, touch, etc. move while not clicked).
-    /// Generally not applicable to touch screens.
-    Hover(Point),
-    /// No interaction
-    #[default]
-    None,
+/// What a [CustomTarget] draws to: a [Painter], without its draw target's type
+pub(crate) trait PixelSink<COL: PixelColor> {
+    fn draw_pixels(&mut self, pixels: &mut dyn Iterator<Item = Pixel<COL>>) -> GuiResult<()>;
+    fn fill_solid(&mut self, area: &Rectangle, color: COL) -> GuiResult<()>;
 }
 
-impl Interaction {
-    /// Gets the point associated with the current interaction, if any.
-    ///
-    /// This method returns the point associated with the current interaction, such as the click, drag, release, or hover point. If the interaction is [Interaction::None], this method returns [None`.
-    fn get_point(&self) -> Option<Point> {
-        match self {
-            Interaction::Click(p) => Some(*p),
-            Interaction::Drag(p) => Some(*p),
-            Interaction::Release(p) => Some(*p),
-            Interaction::Hover(p) => Some(*p),
-            Interaction::None => None,
+impl<COL: PixelColor, DRAW: DrawTarget<Color = COL>> PixelSink<COL> for Painter<'_, COL, DRAW> {
+    fn draw_pixels(&mut self, pixels: &mut dyn Iterator<Item = Pixel<COL>>) -> GuiResult<()> {
+        self.draw_calls += 1;
+        if let Some(buffer) = &mut self.framebuf {
+            buffer.draw_iter(pixels).ok() /* cannot fail */;
+            return Ok(());
+        }
+        let mut target = CountingTarget::new(self.target, &mut self.pixels_drawn, self.rotation)
+            .masked(&self.masks);
+        match self.clip {
+            Some(clip) => target.clipped(&clip).draw_iter(pixels),
+            None => target.draw_iter(pixels),
+        }
+        .map_err(|_| GuiError::draw_error("Failed to draw custom content"))
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: COL) -> GuiResult<()> {
+        self.draw_calls += 1;
+        if let Some(buffer) = &mut self.framebuf {
+            buffer.fill_solid(area, color).ok() /* cannot fail */;
+            return Ok(());
+        }
+        let mut target = CountingTarget::new(self.target, &mut self.pixels_drawn, self.rotation)
+            .masked(&self.masks);
+        match self.clip {
+            Some(clip) => target.clipped(&clip).fill_solid(area, color),
+            None => target.fill_solid(area, color),
         }
+        .map_err(|_| GuiError::draw_error("Failed to draw custom content"))
     }
 }
 
-/// The main UI struct, responsible for managing the layout and rendering of the user interface.
-///
-/// The [Ui] struct is the core of the Kolibri GUI framework. It manages the following:
-/// - A [Painter] for rendering widgets to a [DrawTarget]
-/// - A [Placer] for managing widget layout and positioning
-/// - A [Style] for configuring the appearance of widgets
-/// - Interaction state tracking (clicks, drags, hovers, etc.)
-/// - Debug settings (e.g., debug color for visualizing widget bounds)
-///
-/// # Example
-///
-/// ```no_run
-/// use embedded_graphics::geometry::Size;
-/// use embedded_graphics::pixelcolor::Rgb565;
-/// use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-/// use kolibri_embedded_gui::style::medsize_rgb565_style;
-/// use kolibri_embedded_gui::ui::Ui;
-///
-/// let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-/// let output_settings = OutputSettingsBuilder::new().build();
-/// let mut window = Window::new("Kolibri Example", &output_settings);
-///
-/// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-/// ui.clear_background().unwrap();
-/// // ... add widgets etc.
-/// ```
+/// The [DrawTarget] of custom content drawn with [Ui::custom], or by a widget built with
+/// [Ui::widget] (see [DrawContext::target](crate::widget_builder::DrawContext::target))
 ///
-pub struct Ui<'a, DRAW, COL>
-where
-    DRAW: DrawTarget<Color = COL>,
-    COL: PixelColor,
-{
-    bounds: Rectangle,
-    painter: Painter<'a, COL, DRAW>,
-    style: Style<COL>,
-    placer: Placer,
-    interact: Interaction,
-    /// Whether the UI was background-cleared this frame
-    cleared: bool,
-    debug_color: Option<COL>,
+/// For [Ui::custom], its origin is the top left corner of the allocated area; for built
+/// widgets, it is the drawable's origin. Everything outside of the area is discarded. Drawing
+/// goes through the [Ui], so it uses the [Ui]'s buffer (see [Ui::set_buffer]), clip, rotation
+/// and overlays, and is counted in [Ui::perf].
+pub struct CustomTarget<'a, COL: PixelColor> {
+    sink: &'a mut dyn PixelSink<COL>,
+    area: Rectangle,
+    /// Where the target's origin is, in the drawable's coordinates
+    origin: Point,
 }
 
-// -- Getter methods for [Ui] --
-impl<DRAW, COL> Ui<'_, DRAW, COL>
+impl<'a, COL: PixelColor> CustomTarget<'a, COL> {
+    /// A target drawing to `area` of `sink`, with its origin at `origin`
+    pub(crate) fn new(sink: &'a mut dyn PixelSink<COL>, area: Rectangle, origin: Point) -> Self {
+        Self { sink, area, origin }
+    }
+
+    /// Reborrows the target with its origin moved to `origin`
+    pub(crate) fn with_origin(&mut self, origin: Point) -> CustomTarget<'_, COL> {
+        CustomTarget::new(&mut *self.sink, self.area, origin)
+    }
+}
+
+impl<COL: PixelColor> Dimensions for CustomTarget<'_, COL> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(self.area.top_left - self.origin, self.area.size)
+    }
+}
+
+impl<COL: PixelColor> DrawTarget for CustomTarget<'_, COL> {
+    type Color = COL;
+    type Error = GuiError;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (area, origin) = (self.area, self.origin);
+        let mut pixels = pixels
+            .into_iter()
+            .map(|Pixel(point, color)| Pixel(point + origin, color))
+            .filter(|Pixel(point, _)| area.contains(*point));
+        self.sink.draw_pixels(&mut pixels)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let area = area.translate(self.origin).intersection(&self.area);
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        self.sink.fill_solid(&area, color)
+    }
+}
+
+/// Custom content of a [Ui], see [Ui::custom]
+struct Custom<'s, F> {
+    size: Size,
+    draw: Option<F>,
+    smartstate: Container<'s, Smartstate>,
+    version: u32,
+}
+
+impl<COL, F> TypedWidget<COL> for Custom<'_, F>
 where
-    DRAW: DrawTarget<Color = COL>,
     COL: PixelColor,
+    F: FnOnce(&mut CustomTarget<'_, COL>, Rectangle) -> GuiResult<()>,
 {
-    /// Returns the width of the [Ui]'s placer.
-    ///
-    /// Note that this is not the entire screen width.
-    ///
-    /// ## Returns
-    ///
-    /// The width of the placer as a `u32`.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let width = ui.get_width();
-    /// println!("Placer width: {}", width);
-    /// ```
-    pub fn get_width(&self) -> u32 {
-        self.placer.bounds.width
+    fn draw_typed<DRAW: DrawTarget<Color = COL>>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let draw = &mut self.draw;
+        ui.widget(self.size)
+            .draw_if_changed(self.smartstate.get_mut(), self.version, |ctx| {
+                let area = ctx.area();
+                match draw.take() {
+                    Some(draw) => draw(&mut ctx.target().with_origin(area.top_left), area),
+                    None => Ok(()),
+                }
+            })
     }
 
-    /// Returns the width of the screen.
-    ///
-    /// This includes the UI's window border padding.
-    ///
-    /// ## Returns
-    ///
-    /// The screen width as a `u32`.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::geometry::Size;
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// let screen_width = ui.get_screen_width();
-    /// println!("Screen width: {}", screen_width);
-    /// ```
-    pub fn get_screen_width(&self) -> u32 {
-        self.bounds.size.width + self.style.spacing.window_border_padding.width * 2
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
     }
 }
 
-// -- Construction and widget addition methods --
-impl<'a, COL, DRAW> Ui<'a, DRAW, COL>
-where
-    DRAW: DrawTarget<Color = COL>,
-    COL: PixelColor,
-{
-    /// Creates a new [Ui] instance with the given drawable, bounds and style.
-    ///
-    /// The provided bounds are adjusted by the style's window border padding.
-    ///
-    /// ## Returns
-    ///
-    /// A new instance of [Ui].
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::geometry::Size;
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// use embedded_graphics::geometry::Dimensions;
-    /// let bounds = display.bounding_box();
-    /// let ui = Ui::new(&mut display, bounds, medsize_rgb565_style());
-    /// ```
-    pub fn new(drawable: &'a mut DRAW, bounds: Rectangle, style: Style<COL>) -> Self {
-        // set bounds to internal bounds (apply padding)
-        let bounds = Rectangle::new(
-            bounds.top_left.add(Point::new(
-                style.spacing.window_border_padding.height as i32,
-                style.spacing.window_border_padding.width as i32,
-            )),
-            bounds
-                .size
-                .saturating_sub(style.spacing.window_border_padding * 2),
-        );
-
-        // set up placer
-        let placer = Placer::new(
-            bounds.size,
-            true,
-            Align(HorizontalAlign::Left, VerticalAlign::Top),
-        );
+/// [DrawTarget] adapter counting the pixels drawn to the wrapped target, and rotating them
+/// (see [Ui::set_rotation])
+struct CountingTarget<'a, DRAW> {
+    target: &'a mut DRAW,
+    pixels: &'a mut u32,
+    rotation: Rotation,
+    /// Areas where nothing is drawn (see [Ui::draw_overlay])
+    masks: &'a [Rectangle],
+}
 
+impl<'a, DRAW> CountingTarget<'a, DRAW> {
+    fn new(target: &'a mut DRAW, pixels: &'a mut u32, rotation: Rotation) -> Self {
         Self {
-            bounds,
-            painter: Painter::new(drawable),
-            style,
-            placer,
-            interact: Interaction::None,
-            cleared: false,
-            debug_color: None,
+            target,
+            pixels,
+            rotation,
+            masks: &[],
         }
     }
 
-    /// Creates a new fullscreen [Ui] instance using the entire bounding box of the drawable.
-    ///
-    /// This is equivalent to calling [Ui::new] with the drawable's bounding box.
-    ///
-    /// ## Returns
-    ///
-    /// A new fullscreen instance of [Ui].
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// let ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// ```
-    pub fn new_fullscreen(drawable: &'a mut DRAW, style: Style<COL>) -> Self {
-        let bounds = drawable.bounding_box();
-        Ui::new(drawable, bounds, style)
+    /// Discards everything drawn within `masks`.
+    fn masked(mut self, masks: &'a [Rectangle]) -> Self {
+        self.masks = masks;
+        self
     }
 
-    /// Sets the current interaction for the [Ui].
-    ///
-    /// This interaction is used to update the state of widgets.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// ui.interact(Interaction::Click(Point::new(10, 10)));
-    /// ```
-    pub fn interact(&mut self, interaction: Interaction) {
-        self.interact = interaction;
+    /// Returns whether a part of `area` is masked
+    fn is_masked(&self, area: &Rectangle) -> bool {
+        self.masks
+            .iter()
+            .any(|mask| !mask.intersection(area).is_zero_sized())
     }
 
-    /// Adds a widget to the [Ui] and, if requested, clears the remaining horizontal space in the current row.
-    ///
-    /// After adding the widget, a new row is started.
-    ///
-    /// ## Returns
-    ///
-    /// A [Response] indicating the result of adding the widget.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let response = ui.add_and_clear_col_remainder(widget, true);
-    /// ```
-    pub fn add_and_clear_col_remainder(&mut self, widget: impl Widget, clear: bool) -> Response {
-        let resp = self.add_raw(widget).unwrap_or_else(Response::from_error);
-        if clear {
-            self.clear_row_to_end().ok();
+    fn count(&mut self, area: &Rectangle) {
+        *self.pixels = self
+            .pixels
+            .saturating_add(area.size.width * area.size.height);
+    }
+}
+
+impl<DRAW: Dimensions> Dimensions for CountingTarget<'_, DRAW> {
+    fn bounding_box(&self) -> Rectangle {
+        self.rotation.rotated_frame(self.target.bounding_box())
+    }
+}
+
+impl<DRAW: DrawTarget> DrawTarget for CountingTarget<'_, DRAW> {
+    type Color = DRAW::Color;
+    type Error = DRAW::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let count = &mut *self.pixels;
+        let masks = self.masks;
+        let (rotation, frame) = (self.rotation, self.target.bounding_box());
+        self.target.draw_iter(
+            pixels
+                .into_iter()
+                .filter(|Pixel(point, _)| !masks.iter().any(|mask| mask.contains(*point)))
+                .map(|Pixel(point, color)| {
+                    *count = count.saturating_add(1);
+                    Pixel(rotation.unrotate_point(point, frame), color)
+                }),
+        )
+    }
+
+    // forward the rest, so that the target's optimized implementations are used
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        if self.rotation != Rotation::Deg0 || self.is_masked(area) {
+            // the rows of the area are columns on the target, or parts are left out
+            let pixels = area.points().zip(colors).map(|(p, c)| Pixel(p, c));
+            return self.draw_iter(pixels);
         }
-        self.new_row();
-        resp
+        self.count(area);
+        self.target.fill_contiguous(area, colors)
     }
 
-    /// Adds a widget to the [Ui] and then starts a new row.
-    ///
-    /// The widget is drawn and its response is returned.
-    ///
-    /// ## Returns
-    ///
-    /// A [Response] indicating the result of adding the widget.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let response = ui.add(widget);
-    /// ```
-    pub fn add(&mut self, widget: impl Widget) -> Response {
-        let resp = self.add_raw(widget).unwrap_or_else(Response::from_error);
-        self.new_row();
-        resp
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        let (rotation, frame) = (self.rotation, self.target.bounding_box());
+        let (target, pixels) = (&mut *self.target, &mut *self.pixels);
+        for_each_unmasked(*area, self.masks, &mut |area| {
+            *pixels = pixels.saturating_add(area.size.width * area.size.height);
+            target.fill_solid(&rotation.unrotate_rect(area, frame), color)
+        })
     }
 
-    /// Adds a widget centered horizontally in the current row of the [Ui].
-    ///
-    /// After drawing the widget, the row alignment is reset.
-    ///
-    /// ## Returns
-    ///
-    /// A [Response] indicating the result of adding the widget.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let response = ui.add_centered(widget);
-    /// ```
-    pub fn add_centered(&mut self, widget: impl Widget) -> Response {
-        let align = self.placer.align;
-        self.placer.align = Align(HorizontalAlign::Center, align.1);
-        let resp = self.add_raw(widget).unwrap_or_else(Response::from_error);
-        self.placer.align = align;
-        self.new_row();
-        resp
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        if !self.masks.is_empty() {
+            return self.fill_solid(&self.bounding_box(), color);
+        }
+        let area = self.target.bounding_box();
+        self.count(&area);
+        self.target.clear(color)
     }
+}
 
-    /// Adds a widget to the current row of the [Ui] without starting a new row.
-    ///
-    /// Space is allocated for the next widget after this one.
-    ///
-    /// ## Returns
-    ///
-    /// A [Response] indicating the result of adding the widget.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let response = ui.add_horizontal(widget);
-    /// ```
-    pub fn add_horizontal(&mut self, widget: impl Widget) -> Response {
-        let resp = self.add_raw(widget).unwrap_or_else(Response::from_error);
-        // Allocate space between widgets; ignore space errors.
-        self.allocate_space_no_wrap(self.style().spacing.item_spacing)
-            .ok();
-        resp
+/// What the on-screen debug overlay shows, see [Ui::set_debug_overlay]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DebugOverlay {
+    /// No overlay
+    #[default]
+    Off,
+    /// Outline the allocated area of every widget
+    Bounds,
+    /// Outline widgets that redrew this frame
+    Redraws,
+    /// Both [DebugOverlay::Bounds] and [DebugOverlay::Redraws]
+    Both,
+}
+
+/// Performance counters of a [Ui], see [Ui::perf]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PerfStats {
+    /// Number of widgets added (with [Ui::add] and friends)
+    pub widgets_added: u32,
+    /// Number of widgets that actually redrew themselves
+    pub widgets_redrawn: u32,
+    /// Number of pixels sent to the draw target (clipped pixels may be included)
+    pub pixels_drawn: u32,
+    /// Bounding box of all redrawn areas (including a cleared background), `None` if nothing
+    /// was redrawn
+    pub dirty_area: Option<Rectangle>,
+    /// Number of items drawn with [Ui::draw] (and [Ui::draw_raw])
+    pub draw_calls: u32,
+}
+
+/// What changed in a frame, returned by [Ui::finish_frame]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameInfo {
+    /// Bounding box of everything redrawn this frame (including a cleared background), in the
+    /// drawable's coordinates. `None` if nothing changed, so there's nothing to flush.
+    pub dirty_area: Option<Rectangle>,
+    /// The performance counters of the frame
+    pub perf: PerfStats,
+    /// Whether a widget is animating, so the next frame should follow right away
+    /// (see [Ui::needs_repaint])
+    pub needs_repaint: bool,
+    /// The area whose background the application has to restore next frame
+    /// (see [Ui::stale_area])
+    pub stale_area: Option<Rectangle>,
+    /// The area cleared over widgets added before, which redraw next frame
+    /// (see [Ui::erased_area])
+    pub erased_area: Option<Rectangle>,
+}
+
+impl PerfStats {
+    /// Adds the counters of `other` (e.g. of a sub-[Ui]) to these counters.
+    fn merge(&mut self, other: PerfStats) {
+        self.widgets_added += other.widgets_added;
+        self.widgets_redrawn += other.widgets_redrawn;
+        self.pixels_drawn = self.pixels_drawn.saturating_add(other.pixels_drawn);
+        if let Some(area) = other.dirty_area {
+            self.add_dirty_area(area);
+        }
+        self.draw_calls += other.draw_calls;
     }
 
-    /// Draws a widget directly to the [Ui] without changing the layout.
-    ///
-    /// If a debug color is set, the widget's bounding area is drawn with that color.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] wrapping a [Response] for the drawn widget.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// match ui.add_raw(widget) {
-    ///     Ok(response) => { /* widget drawn successfully */ },
-    ///     Err(e) => { /* handle error */ },
-    /// }
-    /// ```
-    pub fn add_raw(&mut self, mut widget: impl Widget) -> GuiResult<Response> {
-        let res = widget.draw(self);
-        if let (Ok(res), Some(debug_color)) = (&res, self.debug_color) {
-            res.internal
-                .area
-                .draw_styled(
-                    &PrimitiveStyleBuilder::new()
-                        .stroke_color(debug_color)
-                        .stroke_width(1)
-                        .build(),
-                    &mut self.painter,
-                )
-                .ok();
+    /// Extends the dirty area to include `area`.
+    fn add_dirty_area(&mut self, area: Rectangle) {
+        self.dirty_area = union(self.dirty_area, area);
+    }
+}
+
+/// Returns the bounding box of `a` and `b`, ignoring a zero-sized `b`.
+fn union(a: Option<Rectangle>, b: Rectangle) -> Option<Rectangle> {
+    match (a, b.bottom_right()) {
+        (_, None) => a,
+        (None, Some(_)) => Some(b),
+        (Some(a), Some(bottom_right)) => Some(Rectangle::with_corners(
+            a.top_left.component_min(b.top_left),
+            a.bottom_right()
+                .unwrap_or(a.top_left)
+                .component_max(bottom_right),
+        )),
+    }
+}
+
+/// Maximum number of style patches pushed with [Ui::push_style]
+pub const STYLE_STACK_DEPTH: usize = 4;
+
+/// Returns the [Ui::style_key] of `patch` applied within the patches identified by `outer`.
+fn nested_style_key(outer: u32, patch: &StylePatch) -> u32 {
+    let key = FixedState::with_seed(0x6a09e667f3bcc908).hash_one((outer, patch)) as u32;
+    // `0` means no patch
+    key.max(1)
+}
+
+/// Returns the parts of `a` that aren't covered by `b`, as up to four rectangles.
+fn difference(a: Rectangle, b: Rectangle) -> impl Iterator<Item = Rectangle> {
+    let rect = |x0: i32, y0: i32, x1: i32, y1: i32| {
+        (x1 > x0 && y1 > y0).then(|| {
+            Rectangle::new(
+                Point::new(x0, y0),
+                Size::new((x1 - x0) as u32, (y1 - y0) as u32),
+            )
+        })
+    };
+    let (ax0, ay0) = (a.top_left.x, a.top_left.y);
+    let (ax1, ay1) = (ax0 + a.size.width as i32, ay0 + a.size.height as i32);
+    let i = a.intersection(&b);
+    let parts = if i.is_zero_sized() {
+        [Some(a), None, None, None]
+    } else {
+        let (ix0, iy0) = (i.top_left.x, i.top_left.y);
+        let (ix1, iy1) = (ix0 + i.size.width as i32, iy0 + i.size.height as i32);
+        [
+            // above, below, left and right of the intersection
+            rect(ax0, ay0, ax1, iy0),
+            rect(ax0, iy1, ax1, ay1),
+            rect(ax0, iy0, ix0, iy1),
+            rect(ix1, iy0, ax1, iy1),
+        ]
+    };
+    parts.into_iter().flatten()
+}
+
+/// Calls `f` for the parts of `area` outside of all `masks`
+fn for_each_unmasked<E>(
+    area: Rectangle,
+    masks: &[Rectangle],
+    f: &mut impl FnMut(Rectangle) -> Result<(), E>,
+) -> Result<(), E> {
+    match masks.split_first() {
+        None => f(area),
+        Some((mask, rest)) => {
+            difference(area, *mask).try_for_each(|part| for_each_unmasked(part, rest, f))
         }
-        res
     }
+}
 
-    /// Returns an immutable reference to the current style of the [Ui].
-    ///
-    /// ## Returns
-    ///
-    /// A reference to the [Style].
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let current_style = ui.style();
-    /// ```
+/// The most overlays per frame, see [Ui::draw_overlay]
+pub const MAX_OVERLAYS: usize = 4;
+
+/// The areas of the overlays of a frame, kept between frames to repaint where an overlay
+/// disappeared (see [Ui::begin_overlays])
+///
+/// See [retained state](crate::smartstate::Smartstate#retained-state).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Overlays {
+    /// The overlays of the last frame
+    areas: heapless::Vec<Rectangle, MAX_OVERLAYS>,
+    /// The areas of the last frame's overlays that are gone, to be repainted
+    gone: heapless::Vec<Rectangle, MAX_OVERLAYS>,
+}
+
+impl Overlays {
+    /// Creates [Overlays] for a [Ui] that hasn't been drawn yet.
+    pub const fn new() -> Self {
+        Self {
+            areas: heapless::Vec::new(),
+            gone: heapless::Vec::new(),
+        }
+    }
+
+    /// Returns the areas of the overlays of the last frame.
+    pub fn areas(&self) -> &[Rectangle] {
+        &self.areas
+    }
+}
+
+/// The tallest content of a scroll area, in pixels (see [Ui::scroll_area])
+const MAX_CONTENT_HEIGHT: u32 = 1 << 15;
+
+/// The scroll position of a [Ui::scroll_area], kept between frames
+///
+/// One per scroll area, see [retained state](crate::smartstate::Smartstate#retained-state).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrollState {
+    /// How far the content is scrolled up, in pixels
+    offset: u32,
+    /// The height of the content in the last frame
+    content_height: u32,
+    /// The offset the content was drawn at in the last frame
+    drawn: Option<u32>,
+    /// Whether the last pointer interaction was within the scroll area, `None` before the first
+    pointer_inside: Option<bool>,
+}
+
+impl ScrollState {
+    /// Creates the state of a scroll area scrolled to the top.
+    pub const fn new() -> Self {
+        Self {
+            offset: 0,
+            content_height: 0,
+            drawn: None,
+            pointer_inside: None,
+        }
+    }
+
+    /// Returns how far the content is scrolled up, in pixels.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Scrolls the content up by `offset` pixels, e.g. to `0` to go back to the top. It is
+    /// limited to the content's height in the next frame.
+    pub fn set_offset(&mut self, offset: u32) {
+        self.offset = offset;
+    }
+
+    /// Returns the height of the content in the last frame, in pixels.
+    pub fn content_height(&self) -> u32 {
+        self.content_height
+    }
+}
+
+/// Tracks the frame time between frames, to provide both [Ui::now] and [Ui::dt]
+///
+/// See [retained state](crate::smartstate::Smartstate#retained-state).
+///
+/// # Example
+///
+/// ```no_run
+/// # use embedded_graphics::pixelcolor::Rgb565;
+/// # use embedded_graphics_simulator::SimulatorDisplay;
+/// # use kolibri_embedded_gui::prelude::*;
+/// # use kolibri_embedded_gui::ui::FrameClock;
+/// # use embedded_graphics::prelude::*;
+/// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+/// let start = std::time::Instant::now();
+/// let mut clock = FrameClock::new();
+///
+/// loop {
+///     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+///     clock.begin_frame(&mut ui, start.elapsed().as_millis() as u64);
+///     // [...] widgets can use ui.now() and ui.dt()
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FrameClock {
+    last_ms: Option<u64>,
+}
+
+impl FrameClock {
+    /// Creates a clock that hasn't seen a frame yet.
+    pub const fn new() -> Self {
+        Self { last_ms: None }
+    }
+
+    /// Starts a frame at `now_ms`: calls [Ui::begin_frame] and [Ui::tick] with the time since
+    /// the last frame (`0` for the first frame).
+    pub fn begin_frame<DRAW, COL>(&mut self, ui: &mut Ui<DRAW, COL>, now_ms: u64)
+    where
+        DRAW: DrawTarget<Color = COL>,
+        COL: PixelColor,
+    {
+        let delta = self.last_ms.map_or(0, |last| now_ms.saturating_sub(last));
+        self.last_ms = Some(now_ms);
+        ui.begin_frame(now_ms);
+        ui.tick(delta.try_into().unwrap_or(u32::MAX));
+    }
+}
+
+/// The data of a [Ui] kept between frames, for building every frame with the same state
+///
+/// A [Ui] borrows the draw target, so it is created for every frame (with [Ui::new] or
+/// [Ui::new_fullscreen]) and can't remember anything from one frame to the next. A [UiState]
+/// lives outside of the UI loop instead, and brackets the widgets of every frame:
+/// [UiState::begin_frame] creates the [Ui] for the frame with the retained state and the
+/// frame's input, and [UiState::end_frame] keeps what changed and finishes the frame. It
+/// retains
+/// - the [Style] and bounds of the [Ui],
+/// - the frame time, for [Ui::now] and [Ui::dt] (like a [FrameClock]),
+/// - the pointer, for [Ui::pointer] and the press tracking of [Ui::press_origin] (a new [Ui]
+///   only knows where a press started in the frame of the [Interaction::Click]),
+/// - the focus (see [Ui::set_focus]) and the [PointerKind],
+/// - the [DebugOverlay], so that turning it off redraws the UI without the outlines.
+///
+/// This is the recommended way to build frames. Creating a [Ui] per frame without it still
+/// works, widgets just can't rely on the retained data then.
+///
+/// # Example
+///
+/// ```no_run
+/// # use embedded_graphics::pixelcolor::Rgb565;
+/// # use embedded_graphics_simulator::SimulatorDisplay;
+/// # use kolibri_embedded_gui::prelude::*;
+/// # use kolibri_embedded_gui::ui::UiState;
+/// # use embedded_graphics::prelude::*;
+/// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+/// # let mut smartstates = SmartstateProvider::<20>::new();
+/// # let start = std::time::Instant::now();
+/// # fn read_touch() -> Interaction { Interaction::None }
+/// let mut state = UiState::new(medsize_rgb565_style());
+///
+/// loop {
+///     let now = start.elapsed().as_millis() as u64;
+///     let frame = state.frame(&mut display, read_touch(), now, |ui| {
+///         smartstates.restart_counter();
+///         if ui.add(Button::new("OK").smartstate(smartstates.nxt())).clicked() {
+///             // ...
+///         }
+///     });
+///     if let Some(area) = frame.dirty_area {
+///         // flush the area
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UiState<COL: PixelColor> {
+    style: Style<COL>,
+    /// Bounds of the [Ui], the whole draw target if `None`
+    bounds: Option<Rectangle>,
+    clock: FrameClock,
+    pointer: PointerTrack,
+    focus: Option<u32>,
+    pointer_kind: PointerKind,
+    debug_overlay: DebugOverlay,
+    /// Erased widgets to redraw in the next frame (see [Ui::erased_area])
+    erased: Option<Rectangle>,
+}
+
+impl<COL: PixelColor> UiState<COL> {
+    /// Creates the state of a fullscreen [Ui] with the given style, before the first frame.
+    pub fn new(style: Style<COL>) -> Self {
+        Self {
+            style,
+            bounds: None,
+            clock: FrameClock::new(),
+            pointer: PointerTrack::default(),
+            focus: None,
+            pointer_kind: PointerKind::Mouse,
+            debug_overlay: DebugOverlay::Off,
+            erased: None,
+        }
+    }
+
+    /// Limits the [Ui] to `bounds` instead of the whole draw target, like [Ui::new].
+    pub fn with_bounds(mut self, bounds: Rectangle) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// Returns the style of the [Ui].
     pub fn style(&self) -> &Style<COL> {
         &self.style
     }
 
-    /// Returns a mutable reference to the current style of the [Ui].
-    ///
-    /// ## Returns
-    ///
-    /// A mutable reference to the [Style].
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let style = ui.style_mut();
-    /// style.background_color = Rgb565::BLACK;
-    /// ```
+    /// Returns the style of the [Ui] for modification, e.g. to switch the theme. The widgets
+    /// are not redrawn by this, see [Ui::style_key] for restyling parts of the UI.
     pub fn style_mut(&mut self) -> &mut Style<COL> {
         &mut self.style
     }
 
-    /// Advances the layout to a new row in the [Ui].
-    ///
-    /// This method uses the default spacing and widget height from the current style.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.new_row();
-    /// ```
-    pub fn new_row(&mut self) {
-        self.new_row_raw(self.style().spacing.item_spacing.height);
-        self.new_row_raw(self.style().default_widget_height);
+    /// Sets the focused widget of the next frames, see [Ui::set_focus].
+    pub fn set_focus(&mut self, index: Option<u32>) {
+        self.focus = index;
     }
 
-    /// Advances the layout to a new row in the [Ui] with the specified height.
-    ///
-    /// ## Parameters
-    ///
-    /// - `height`: The height for the new row.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.new_row_raw(20);
-    /// ```
-    pub fn new_row_raw(&mut self, height: u32) {
-        self.placer.new_row(height);
+    /// Returns the index of the focused widget, as of the end of the last frame.
+    pub fn focus(&self) -> Option<u32> {
+        self.focus
     }
 
-    /// Expands the current row height of the [Ui] to at least the given height.
-    ///
-    /// If the current row height is less than `height`, it is increased.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.expand_row_height(30);
-    /// ```
-    pub fn expand_row_height(&mut self, height: u32) {
-        self.placer.expand_row_height(height);
+    /// Sets the kind of pointing device of the next frames, see [Ui::set_pointer_kind].
+    pub fn set_pointer_kind(&mut self, kind: PointerKind) {
+        self.pointer_kind = kind;
     }
 
-    /// Draws a [Drawable] item directly using the [Ui]'s underlying draw target.
-    ///
-    /// ## Returns
-    ///
-    /// The output produced by drawing the item, or an error if drawing fails.
-    ///
-    /// # Example
+    /// Begins a frame at `now_ms` (see [Ui::begin_frame]) with the input `interaction`,
+    /// returning the [Ui] to add the frame's widgets to.
     ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// # use embedded_graphics::primitives::PrimitiveStyle;
-    /// # let rectangle_shape = Rectangle::new(Point::new(50, 0), Size::new(200, 150));
-    /// # // Make it drawable with a filled red style
-    /// # let rectangle = rectangle_shape.into_styled(PrimitiveStyle::with_fill(Rgb565::RED));
-    /// let result = ui.draw_raw(&rectangle);
-    /// ```
-    pub fn draw_raw<OUT>(
+    /// Finish the frame with [UiState::end_frame], or use [UiState::frame] for both.
+    pub fn begin_frame<'a, DRAW>(
         &mut self,
-        to_draw: &impl Drawable<Color = COL, Output = OUT>,
-    ) -> Result<OUT, DRAW::Error> {
-        to_draw.draw(self.painter.target)
+        drawable: &'a mut DRAW,
+        interaction: Interaction,
+        now_ms: u64,
+    ) -> Ui<'a, DRAW, COL>
+    where
+        DRAW: DrawTarget<Color = COL>,
+    {
+        let bounds = self.bounds.unwrap_or_else(|| drawable.bounding_box());
+        let mut ui = Ui::new(drawable, bounds, self.style);
+        self.clock.begin_frame(&mut ui, now_ms);
+        ui.pointer = self.pointer;
+        ui.set_focus(self.focus);
+        ui.set_pointer_kind(self.pointer_kind);
+        ui.debug_overlay = self.debug_overlay;
+        if let Some(area) = self.erased {
+            ui.redraw_erased(area);
+        }
+        ui.interact(interaction);
+        ui
     }
 
-    /// Returns the remaining available space for widget placement in the [Ui].
+    /// Ends the frame of `ui`, which was returned by [UiState::begin_frame], keeping its pointer,
+    /// focus and debug overlay for the next frame, which also redraws the widgets erased in this
+    /// frame (see [Ui::erased_area]).
     ///
     /// ## Returns
     ///
-    /// A [Size] representing the available width and height.
+    /// The [FrameInfo] of the frame (see [Ui::finish_frame]).
+    pub fn end_frame<DRAW>(&mut self, ui: Ui<'_, DRAW, COL>) -> FrameInfo
+    where
+        DRAW: DrawTarget<Color = COL>,
+    {
+        self.pointer = ui.pointer;
+        self.focus = ui.focus;
+        self.debug_overlay = ui.debug_overlay;
+        let frame = ui.finish_frame();
+        self.erased = frame.erased_area;
+        frame
+    }
+
+    /// Builds a frame at `now_ms` with the input `interaction`, adding the widgets in `f`,
+    /// between [UiState::begin_frame] and [UiState::end_frame].
     ///
-    /// # Example
+    /// ## Returns
     ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let space = ui.space_available();
-    /// println!("Available space: {:?}", space);
-    /// ```
-    pub fn space_available(&self) -> Size {
-        self.placer.space_available()
+    /// The [FrameInfo] of the frame (see [Ui::finish_frame]).
+    pub fn frame<DRAW, F>(
+        &mut self,
+        drawable: &mut DRAW,
+        interaction: Interaction,
+        now_ms: u64,
+        f: F,
+    ) -> FrameInfo
+    where
+        DRAW: DrawTarget<Color = COL>,
+        F: FnOnce(&mut Ui<'_, DRAW, COL>),
+    {
+        let mut ui = self.begin_frame(drawable, interaction, now_ms);
+        f(&mut ui);
+        self.end_frame(ui)
     }
+}
 
-    /// Checks if the current interaction occurs within the specified area.
-    ///
-    /// ## Returns
-    ///
-    /// The [Interaction] if the interaction's point is within the area, otherwise [Interaction::None].
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// # let some_rectangle = Rectangle::new(Point::new(50, 0), Size::new(200, 150));
-    /// let interaction = ui.check_interact(some_rectangle);
-    /// ```
-    pub fn check_interact(&self, area: Rectangle) -> Interaction {
-        if self
-            .interact
-            .get_point()
-            .map(|pt| area.contains(pt))
-            .unwrap_or(false)
-        {
-            self.interact
-        } else {
-            Interaction::None
+/// Where the pointer is, and where and when the current press started, in interaction
+/// coordinates (see [Ui::press_origin])
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct PointerTrack {
+    /// Last known pointer position
+    position: Option<Point>,
+    /// The current press, or the press released this frame
+    press: Option<Press>,
+    /// Both touches of a pinch in this frame (see [Ui::interact_second])
+    touches: Option<[Point; 2]>,
+    /// Both touches of the pinch in the last frame
+    touches_before: Option<[Point; 2]>,
+    /// Whether the press pinched, so that it is ignored until it is released
+    pinched: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Press {
+    origin: Point,
+    /// Frame time at the start of the press, if known
+    since: Option<u64>,
+    released: bool,
+}
+
+impl PointerTrack {
+    /// Follows the pointer with the interaction of a frame at `now`
+    fn observe(&mut self, interaction: Interaction, now: Option<u64>) {
+        if self.press.is_some_and(|press| press.released) {
+            self.press = None;
+            self.pinched = false;
+        }
+        self.touches_before = self.touches.take();
+        let start = |origin: Point| Press {
+            origin,
+            since: now,
+            released: false,
+        };
+        match interaction {
+            Interaction::Click(p) => {
+                self.press = Some(start(p));
+                self.pinched = false;
+            }
+            // without a click (e.g. it was missed), the press starts here
+            Interaction::Drag(p) => {
+                self.press.get_or_insert(start(p));
+            }
+            Interaction::Release(p) => {
+                self.press.get_or_insert(start(p)).released = true;
+            }
+            Interaction::Hover(_) | Interaction::None => {
+                self.press = None;
+                self.pinched = false;
+            }
+            Interaction::ActivatedByFocus(_) | Interaction::Scroll(_) => return,
+        }
+        if let Some(p) = interaction.get_point() {
+            self.position = Some(p);
         }
     }
+}
 
-    /// Allocates an exact space in the [Ui] for a widget of the desired size.
-    ///
-    /// This method currently wraps [Ui::allocate_space] without extra logic.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] containing an [InternalResponse] with the allocated area and interaction.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let allocation = ui.allocate_exact_size(Size::new(50, 30));
-    /// ```
-    pub fn allocate_exact_size(&mut self, desired_size: Size) -> GuiResult<InternalResponse> {
-        self.allocate_space(desired_size)
+/// The area used by the widgets of a frame, kept between frames for [Ui::clear_unused]
+///
+/// See [retained state](crate::smartstate::Smartstate#retained-state).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UsedArea {
+    area: Option<Rectangle>,
+}
+
+impl UsedArea {
+    /// Creates a [UsedArea] for a [Ui] that hasn't been drawn yet.
+    pub const fn new() -> Self {
+        Self { area: None }
     }
 
-    /// Allocates space in the [Ui] for a widget of the desired size, with wrapping if needed.
-    ///
-    /// The allocated area is adjusted by the [Ui]'s bounds.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] containing an [InternalResponse] with the allocated rectangle and interaction.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let allocation = ui.allocate_space(Size::new(100, 40));
-    /// ```
-    pub fn allocate_space(&mut self, desired_size: Size) -> GuiResult<InternalResponse> {
-        let rect = self.placer.next(desired_size).map(|mut rect| {
-            rect.top_left.add_assign(self.bounds.top_left);
-            rect
-        })?;
-        let inter = self.check_interact(rect);
+    /// Returns the area used by the widgets of the last frame, `None` if no widget was added.
+    pub fn area(&self) -> Option<Rectangle> {
+        self.area
+    }
+}
 
-        Ok(InternalResponse {
-            area: rect,
-            interaction: inter,
-        })
+/// Whether a widget added with [Ui::add_or_placeholder] was shown in the last frame, and where
+///
+/// One per conditional widget, see [retained state](crate::smartstate::Smartstate#retained-state).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PlaceholderState {
+    last: Option<(bool, Rectangle)>,
+}
+
+impl PlaceholderState {
+    /// Creates a [PlaceholderState] for a widget that hasn't been added yet.
+    pub const fn new() -> Self {
+        Self { last: None }
     }
 
-    /// Allocates space in the [Ui] for a widget of the desired size without wrapping.
-    ///
-    /// The allocated area is adjusted by the [Ui]'s bounds.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] containing an [InternalResponse] with the allocated rectangle and interaction.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let allocation = ui.allocate_space_no_wrap(Size::new(80, 25));
-    /// ```
-    pub fn allocate_space_no_wrap(&mut self, desired_size: Size) -> GuiResult<InternalResponse> {
-        let area = self.placer.next_no_wrap(desired_size).map(|mut rect| {
-            rect.top_left.add_assign(self.bounds.top_left);
-            rect
-        })?;
+    /// Returns whether the widget was shown in the last frame, `None` before the first one.
+    pub fn shown(&self) -> Option<bool> {
+        self.last.map(|(shown, _)| shown)
+    }
+}
+
+/// Interaction with the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Interaction {
+    /// A click event (mouse, touch, etc. down)
+    Click(Point),
+    /// A drag event (mouse, touch, etc. move while clicked)
+    Drag(Point),
+    /// A release event (mouse, touch, etc. up)
+    Release(Point),
+    /// A hover event (mouse, touch, etc. move while not clicked).
+    /// Generally not applicable to touch screens.
+    Hover(Point),
+    /// The focused widget was activated (e.g. with an encoder push), see [Ui::activate_focus].
+    /// Widgets treat this like a [Interaction::Release]. The point is the center of the widget.
+    ActivatedByFocus(Point),
+    /// A scroll by a number of detents of a mouse wheel or an encoder, positive scrolling down
+    /// (see [Ui::scroll_area]). It has no point, so widgets other than scroll areas ignore it.
+    Scroll(i32),
+    /// No interaction
+    #[default]
+    None,
+}
+
+/// Where the pointer is relative to a widget, for the edge events of its [Response]
+/// (see [Response::pressed_started]).
+///
+/// Widgets supporting edge events remember the state in their smartstate: it is hashed
+/// together with the widget's look ([PointerState::smartstate]), and recovered in the next
+/// frame by trying the looks the widget may have had ([PointerState::previous]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PointerState {
+    /// The pointer isn't on the widget, or was just released
+    #[default]
+    Outside,
+    /// The pointer hovers over the widget
+    Over,
+    /// The widget is pressed (or activated by focus)
+    Pressed,
+}
 
-        let inter = self.check_interact(area);
+impl PointerState {
+    /// All pointer states
+    pub const ALL: [PointerState; 3] = [
+        PointerState::Outside,
+        PointerState::Over,
+        PointerState::Pressed,
+    ];
 
-        Ok(InternalResponse {
-            area,
-            interaction: inter,
-        })
+    /// Returns the pointer state of a widget with the given interaction.
+    pub fn of(interaction: Interaction) -> Self {
+        match interaction {
+            Interaction::None | Interaction::Release(_) | Interaction::Scroll(_) => {
+                PointerState::Outside
+            }
+            Interaction::Hover(_) => PointerState::Over,
+            Interaction::Click(_) | Interaction::Drag(_) | Interaction::ActivatedByFocus(_) => {
+                PointerState::Pressed
+            }
+        }
     }
 
-    /// Returns the current row height used in the [Ui]'s layout.
+    /// Returns the smartstate of a widget whose look hashes like `look` (e.g. its style bucket
+    /// and label), remembering the pointer state.
+    pub fn smartstate(self, look: &impl Hash, style_key: u32) -> Smartstate {
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(&(look, self));
+        state.mix(style_key);
+        state
+    }
+
+    /// Returns whether `previous` is the smartstate of the look `look`, whatever the pointer
+    /// state was. The widget doesn't need to redraw then.
+    pub fn same_look(previous: Option<Smartstate>, look: &impl Hash, style_key: u32) -> bool {
+        PointerState::ALL
+            .iter()
+            .any(|pointer| previous == Some(pointer.smartstate(look, style_key)))
+    }
+
+    /// Recovers the pointer state from the widget's smartstate of the last frame, trying the
+    /// look of every style bucket (see [resolve_widget_style]) with `look`.
     ///
     /// ## Returns
     ///
-    /// The current row height as a `u32`.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let row_height = ui.get_row_height();
-    /// println!("Row height: {}", row_height);
-    /// ```
-    pub fn get_row_height(&self) -> u32 {
-        self.placer.row_height()
+    /// The pointer state, [PointerState::Outside] for a new (empty) smartstate, and `None`
+    /// without a smartstate, or if the widget's look changed otherwise.
+    pub fn previous<L: Hash>(
+        previous: Option<Smartstate>,
+        look: impl Fn(u8) -> L,
+        style_key: u32,
+    ) -> Option<PointerState> {
+        let previous = previous?;
+        if previous.is_empty() {
+            return Some(PointerState::Outside);
+        }
+        (0..WIDGET_STYLE_BUCKETS as u8).find_map(|bucket| {
+            let look = look(bucket);
+            PointerState::ALL
+                .into_iter()
+                .find(|pointer| previous == pointer.smartstate(&look, style_key))
+        })
     }
 }
 
-// -- Clearing methods --
-impl<COL, DRAW> Ui<'_, DRAW, COL>
-where
-    DRAW: DrawTarget<Color = COL>,
-    COL: PixelColor,
-{
-    /// Returns whether the [Ui]'s background was cleared this frame.
-    ///
-    /// ## Returns
-    ///
-    /// `true` if the background was cleared, `false` otherwise.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// if ui.cleared() {
-    ///     println!("Background cleared");
-    /// }
-    /// ```
-    pub fn cleared(&self) -> bool {
-        self.cleared
+/// An interaction to give feedback for (e.g. a click sound or a haptic pulse), see
+/// [Ui::set_feedback_handler]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeedbackEvent {
+    /// A widget started being pressed
+    Press,
+    /// A widget was clicked, i.e. released (or activated by focus)
+    Release,
+    /// A widget held down fired again, e.g. a button with
+    /// [Button::repeat](crate::button::Button::repeat)
+    LongPress,
+    /// A widget changed its value (e.g. a checkbox or a slider)
+    ValueChanged,
+}
+
+impl FeedbackEvent {
+    /// Returns the events of a widget's [Response], in the order they happened.
+    fn of(response: &Response) -> impl Iterator<Item = FeedbackEvent> {
+        // widgets without edge events are pressed when the pointer goes down on them
+        let press = response.pressed_started()
+            || matches!(response.internal.interaction, Interaction::Click(_));
+        let long_press = response.click && response.down && !press;
+        let release = response.click && !response.down;
+        [
+            (press, FeedbackEvent::Press),
+            (long_press, FeedbackEvent::LongPress),
+            (release, FeedbackEvent::Release),
+            (response.changed, FeedbackEvent::ValueChanged),
+        ]
+        .into_iter()
+        .filter_map(|(happened, event)| happened.then_some(event))
     }
+}
 
-    /// Clears the specified area in the [Ui] using the background color.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] indicating success or error.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.clear_area(Rectangle::new(Point::new(0,0), Size::new(100, 50))).unwrap();
-    /// ```
-    pub fn clear_area(&mut self, area: Rectangle) -> GuiResult<()> {
-        self.draw(&area.into_styled(PrimitiveStyle::with_fill(self.style.background_color)))
-            .map_err(|_| GuiError::DrawError(Some("Couldn't clear area")))
+/// The kind of pointing device providing the [Interaction]s, see [Ui::set_pointer_kind]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PointerKind {
+    /// A mouse (or the simulator), which hovers over widgets without pressing
+    #[default]
+    Mouse,
+    /// A touch panel, which only "hovers" while pressed
+    Touch,
+}
+
+/// What produced an [Interaction], see [Response::source]
+///
+/// The [Ui] gets it with [Ui::interact_from]. [Ui::interact] takes it from the
+/// [PointerKind] instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InteractionSource {
+    /// A touch panel
+    Touch,
+    /// A mouse (or the simulator)
+    Mouse,
+    /// A hardware button, e.g. a key mapped to a point on the screen
+    Button,
+    /// The [Ui] itself, e.g. for the focused widget (see [Ui::activate_focus])
+    Synthetic,
+}
+
+impl From<PointerKind> for InteractionSource {
+    fn from(kind: PointerKind) -> Self {
+        match kind {
+            PointerKind::Mouse => InteractionSource::Mouse,
+            PointerKind::Touch => InteractionSource::Touch,
+        }
     }
+}
 
-    /// Clears the current row in the [Ui] with the background color.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] indicating whether the row was successfully cleared.
+/// Clockwise rotation of the [Ui] on the drawable, see [Ui::set_rotation]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Rotation {
+    /// Not rotated
+    #[default]
+    Deg0,
+    /// Rotated by 90°: the top of the [Ui] is at the right edge of the drawable
+    Deg90,
+    /// Rotated by 180° (upside down)
+    Deg180,
+    /// Rotated by 270°: the top of the [Ui] is at the left edge of the drawable
+    Deg270,
+}
+
+impl Rotation {
+    /// Returns whether width and height are swapped
+    pub fn is_transposed(self) -> bool {
+        matches!(self, Rotation::Deg90 | Rotation::Deg270)
+    }
+
+    /// Returns the rotated coordinate space of `frame`, the bounding box of a drawable.
+    pub fn rotated_frame(self, frame: Rectangle) -> Rectangle {
+        let size = frame.size;
+        match self.is_transposed() {
+            true => Rectangle::new(frame.top_left, Size::new(size.height, size.width)),
+            false => frame,
+        }
+    }
+
+    /// Maps `point` from the rotated coordinates of `frame` (the bounding box of the drawable)
+    /// to the drawable's own coordinates.
+    pub fn unrotate_point(self, point: Point, frame: Rectangle) -> Point {
+        let p = point - frame.top_left;
+        let (w, h) = (frame.size.width as i32, frame.size.height as i32);
+        let p = match self {
+            Rotation::Deg0 => p,
+            Rotation::Deg90 => Point::new(w - 1 - p.y, p.x),
+            Rotation::Deg180 => Point::new(w - 1 - p.x, h - 1 - p.y),
+            Rotation::Deg270 => Point::new(p.y, h - 1 - p.x),
+        };
+        p + frame.top_left
+    }
+
+    /// Maps `point` from the drawable's coordinates to the rotated ones, the inverse of
+    /// [Rotation::unrotate_point]. Use this for touch points.
+    pub fn rotate_point(self, point: Point, frame: Rectangle) -> Point {
+        let p = point - frame.top_left;
+        let (w, h) = (frame.size.width as i32, frame.size.height as i32);
+        let p = match self {
+            Rotation::Deg0 => p,
+            Rotation::Deg90 => Point::new(p.y, w - 1 - p.x),
+            Rotation::Deg180 => Point::new(w - 1 - p.x, h - 1 - p.y),
+            Rotation::Deg270 => Point::new(h - 1 - p.y, p.x),
+        };
+        p + frame.top_left
+    }
+
+    /// Maps `rect` from the rotated coordinates of `frame` to the drawable's own coordinates.
+    pub fn unrotate_rect(self, rect: Rectangle, frame: Rectangle) -> Rectangle {
+        self.map_rect(rect, |p| self.unrotate_point(p, frame))
+    }
+
+    /// Maps `rect` from the drawable's coordinates of `frame` to the rotated ones.
+    pub fn rotate_rect(self, rect: Rectangle, frame: Rectangle) -> Rectangle {
+        self.map_rect(rect, |p| self.rotate_point(p, frame))
+    }
+
+    /// Maps the corners of `rect` with `map`
+    fn map_rect(self, rect: Rectangle, map: impl Fn(Point) -> Point) -> Rectangle {
+        let size = match self.is_transposed() {
+            true => Size::new(rect.size.height, rect.size.width),
+            false => rect.size,
+        };
+        match rect.bottom_right() {
+            Some(bottom_right) => Rectangle::with_corners(map(rect.top_left), map(bottom_right)),
+            None => Rectangle::new(map(rect.top_left), size),
+        }
+    }
+}
+
+/// A key press from a hardware keyboard or keypad, see [Ui::push_key]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyEvent {
+    /// A character was typed
+    Char(char),
+    /// Delete the character before the cursor
+    Backspace,
+    /// Confirm the input
+    Enter,
+    /// Left arrow
+    Left,
+    /// Right arrow
+    Right,
+    /// Up arrow
+    Up,
+    /// Down arrow
+    Down,
+}
+
+/// Maximum number of [KeyEvent]s queued per frame
+pub const KEY_QUEUE_LEN: usize = 8;
+
+impl Interaction {
+    /// Gets the point associated with the current interaction, if any.
+    ///
+    /// This method returns the point associated with the current interaction, such as the click, drag, release, or hover point. If the interaction is [Interaction::None], this method returns [None`.
+    pub fn get_point(&self) -> Option<Point> {
+        match self {
+            Interaction::Click(p) => Some(*p),
+            Interaction::Drag(p) => Some(*p),
+            Interaction::Release(p) => Some(*p),
+            Interaction::Hover(p) => Some(*p),
+            Interaction::ActivatedByFocus(p) => Some(*p),
+            Interaction::Scroll(_) | Interaction::None => None,
+        }
+    }
+
+    /// Returns whether the interaction completes a press, i.e. is an [Interaction::Release]
+    /// or an [Interaction::ActivatedByFocus]. Widgets report a click for this.
+    pub fn is_release(&self) -> bool {
+        matches!(
+            self,
+            Interaction::Release(_) | Interaction::ActivatedByFocus(_)
+        )
+    }
+
+    /// Returns whether the widget is pressed, i.e. the interaction is neither a hover nor
+    /// [Interaction::None]. Widgets draw their pressed look for this, so that an activation by
+    /// focus is visible for a frame.
+    pub fn is_pressed(&self) -> bool {
+        !matches!(
+            self,
+            Interaction::None | Interaction::Hover(_) | Interaction::Scroll(_)
+        )
+    }
+
+    /// Returns the same interaction, with its point moved by `offset`.
+    pub fn translated(self, offset: Point) -> Interaction {
+        self.mapped(|p| p + offset)
+    }
+
+    /// Returns the same interaction, with its point mapped by `map`.
+    fn mapped(self, map: impl FnOnce(Point) -> Point) -> Interaction {
+        match self {
+            Interaction::Click(p) => Interaction::Click(map(p)),
+            Interaction::Drag(p) => Interaction::Drag(map(p)),
+            Interaction::Release(p) => Interaction::Release(map(p)),
+            Interaction::Hover(p) => Interaction::Hover(map(p)),
+            Interaction::ActivatedByFocus(p) => Interaction::ActivatedByFocus(map(p)),
+            Interaction::Scroll(detents) => Interaction::Scroll(detents),
+            Interaction::None => Interaction::None,
+        }
+    }
+}
+
+/// A gesture of several pointers, recognized by the [Ui] (see [Ui::interact_second])
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// Two touches moving apart (zooming in) or together (zooming out)
+    Pinch {
+        /// The point between the touches
+        center: Point,
+        /// How much the distance between the touches grew since the last frame, e.g. `1.1`
+        /// for 10 % farther apart. It is `1.0` in the first frame of the pinch.
+        scale_delta: f32,
+    },
+}
+
+/// Creates a [RoundedRectangle] with equal corners from `rect`, clamping the corner radius
+/// to half of the rectangle's smaller dimension like [Style::corner_radius_for].
+///
+/// Widgets should use this instead of constructing the [RoundedRectangle] directly,
+/// as radii that don't fit the rectangle render with overlapping corner arcs.
+///
+/// # Example
+///
+/// ```
+/// # use embedded_graphics::prelude::*;
+/// # use embedded_graphics::primitives::Rectangle;
+/// use kolibri_embedded_gui::ui::rounded_rect_clamped;
+///
+/// let rect = rounded_rect_clamped(Rectangle::new(Point::zero(), Size::new(10, 10)), 8);
+/// assert_eq!(rect.corners.top_left, Size::new(5, 5));
+/// ```
+pub fn rounded_rect_clamped(rect: Rectangle, radius: u32) -> RoundedRectangle {
+    let radius = clamp_corner_radius(radius, rect.size);
+    RoundedRectangle::with_equal_corners(rect, Size::new(radius, radius))
+}
+
+/// A shape filled with a [Fill], gradients in horizontal 1px strips.
+///
+/// The strips are clipped to the shape, so a gradient follows rounded corners.
+struct FilledShape<COL> {
+    shape: RoundedRectangle,
+    fill: Fill<COL>,
+}
+
+impl<COL: PixelColor + Into<Rgb888> + From<Rgb888>> Drawable for FilledShape<COL> {
+    type Color = COL;
+    type Output = ();
+
+    fn draw<D: DrawTarget<Color = COL>>(&self, target: &mut D) -> Result<(), D::Error> {
+        match self.fill {
+            Fill::Solid(color) => self
+                .shape
+                .into_styled(PrimitiveStyle::with_fill(color))
+                .draw(target),
+            Fill::VGradient(top, _) => {
+                // fill the shape with any color, the rows get their color on the way to the target
+                let mut target = RowColors {
+                    target,
+                    fill: &self.fill,
+                    area: self.shape.bounding_box(),
+                };
+                self.shape
+                    .into_styled(PrimitiveStyle::with_fill(top))
+                    .draw(&mut target)
+            }
+        }
+    }
+}
+
+/// Draw target replacing the color of each pixel with the [Fill]'s color for its row in `area`
+struct RowColors<'a, D, COL> {
+    target: &'a mut D,
+    fill: &'a Fill<COL>,
+    area: Rectangle,
+}
+
+impl<COL: PixelColor + Into<Rgb888> + From<Rgb888>, D> RowColors<'_, D, COL> {
+    /// Returns the color of the pixels in row `y`
+    fn color(&self, y: i32) -> COL {
+        let row = (y - self.area.top_left.y).max(0) as u32;
+        self.fill.color_at(row, self.area.size.height)
+    }
+}
+
+impl<D: DrawTarget<Color = COL>, COL: PixelColor> Dimensions for RowColors<'_, D, COL> {
+    fn bounding_box(&self) -> Rectangle {
+        self.target.bounding_box()
+    }
+}
+
+impl<D, COL> DrawTarget for RowColors<'_, D, COL>
+where
+    D: DrawTarget<Color = COL>,
+    COL: PixelColor + Into<Rgb888> + From<Rgb888>,
+{
+    type Color = COL;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let RowColors { target, fill, area } = self;
+        let color = |y: i32| fill.color_at((y - area.top_left.y).max(0) as u32, area.size.height);
+        target.draw_iter(
+            pixels
+                .into_iter()
+                .map(|Pixel(point, _)| Pixel(point, color(point.y))),
+        )
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, _color: COL) -> Result<(), Self::Error> {
+        for y in area.rows() {
+            let strip = Rectangle::new(
+                Point::new(area.top_left.x, y),
+                Size::new(area.size.width, 1),
+            );
+            self.target.fill_solid(&strip, self.color(y))?;
+        }
+        Ok(())
+    }
+}
+
+/// Number of smartstate buckets returned by [resolve_widget_style]
+pub const WIDGET_STYLE_BUCKETS: u32 = 3;
+
+/// Resolves the background and border style of an interactive widget (e.g. a button) for an
+/// [Interaction], so that all widgets react the same way.
+///
+/// - Normal: the border color and width, filled with the item background color
+/// - Hovered (or focused): the highlight border, filled with the highlight item background color
+/// - Pressed (see [Interaction::is_pressed]): the highlight border, filled with the primary color
+///
+/// ## Returns
+///
+/// The style, and its smartstate bucket: `0` for normal, `1` for hovered and `2` for pressed
+/// (less than [WIDGET_STYLE_BUCKETS]). Widgets put the bucket into their smartstate, so that
+/// they redraw when the look changes.
+///
+/// # Example
+///
+/// ```
+/// # use embedded_graphics::prelude::*;
+/// use kolibri_embedded_gui::prelude::*;
+/// use kolibri_embedded_gui::ui::resolve_widget_style;
+///
+/// let style = medsize_rgb565_style();
+/// let click = Interaction::Click(Point::zero());
+/// let (rect_style, bucket) = resolve_widget_style(&style, click);
+/// assert_eq!(rect_style.fill_color, Some(style.primary_color));
+/// assert_eq!(bucket, 2);
+/// ```
+pub fn resolve_widget_style<COL: PixelColor>(
+    style: &Style<COL>,
+    interaction: Interaction,
+) -> (PrimitiveStyle<COL>, u8) {
+    match interaction {
+        Interaction::None => (
+            PrimitiveStyleBuilder::new()
+                .stroke_color(style.border_color)
+                .stroke_width(style.border_width)
+                .fill_color(style.item_background_color.color())
+                .build(),
+            0,
+        ),
+        Interaction::Hover(_) => (
+            PrimitiveStyleBuilder::new()
+                .stroke_color(style.highlight_border_color)
+                .stroke_width(style.highlight_border_width)
+                .fill_color(style.highlight_item_background_color)
+                .build(),
+            1,
+        ),
+        _ => (
+            PrimitiveStyleBuilder::new()
+                .stroke_color(style.highlight_border_color)
+                .stroke_width(style.highlight_border_width)
+                .fill_color(style.primary_color)
+                .build(),
+            2,
+        ),
+    }
+}
+
+/// The main UI struct, responsible for managing the layout and rendering of the user interface.
+///
+/// The [Ui] struct is the core of the Kolibri GUI framework. It manages the following:
+/// - A [Painter] for rendering widgets to a [DrawTarget]
+/// - A [Placer] for managing widget layout and positioning
+/// - A [Style] for configuring the appearance of widgets
+/// - Interaction state tracking (clicks, drags, hovers, etc.)
+/// - Debug settings (e.g., debug color for visualizing widget bounds)
+///
+/// # Example
+///
+/// ```no_run
+/// use embedded_graphics::geometry::Size;
+/// use embedded_graphics::pixelcolor::Rgb565;
+/// use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+/// use kolibri_embedded_gui::prelude::*;
+///
+/// let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+/// let output_settings = OutputSettingsBuilder::new().build();
+/// let mut window = Window::new("Kolibri Example", &output_settings);
+///
+/// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+/// ui.clear_background().unwrap();
+/// // ... add widgets etc.
+/// ```
+///
+/// As the [Ui] borrows the draw target, it is created for every frame. To keep data between
+/// frames, create the [Ui]s with a [UiState] instead (recommended).
+pub struct Ui<'a, DRAW, COL>
+where
+    DRAW: DrawTarget<Color = COL>,
+    COL: PixelColor,
+{
+    bounds: Rectangle,
+    painter: Painter<'a, COL, DRAW>,
+    style: Style<COL>,
+    placer: Placer,
+    interact: Interaction,
+    /// What produced the interaction, `None` for the [PointerKind] (see [Ui::interact_from])
+    source: Option<InteractionSource>,
+    /// Position of the drawable's origin in interaction coordinates (see [Ui::set_origin])
+    origin: Point,
+    /// Whether the UI was background-cleared this frame
+    cleared: bool,
+    debug_color: Option<COL>,
+    /// What the on-screen debug overlay shows (see [Ui::set_debug_overlay])
+    debug_overlay: DebugOverlay,
+    /// Timestamp of the current frame in milliseconds, if provided
+    now: Option<u64>,
+    /// Time since the last frame in milliseconds, if provided
+    dt: Option<u32>,
+    /// Hash of the positions where widgets were wrapped to a new row this frame
+    wrap_state: u32,
+    /// Minimum width of allocated widgets (see [Ui::equal_width_row])
+    min_width: Option<u32>,
+    /// Widget sizes measured instead of allocated (see [Ui::equal_width_row])
+    measure: Option<RowMeasure>,
+    /// Whether [Ui::add] places widgets next to each other (see [Ui::horizontal])
+    horizontal: bool,
+    /// Whether the next allocation is invalidated (see [Ui::invalidate_next_allocation])
+    invalidate_next: bool,
+    /// Where the widget being added was drawn in the last frame, its first allocation is
+    /// invalidated if it moved (see [Ui::set_overflow_policy])
+    drawn_before: Option<Rectangle>,
+    /// How far the pixels of the last frame's widgets were moved this frame, e.g. by a scroll
+    /// area, so that they aren't redrawn for moving along
+    drawn_shift: Point,
+    /// Performance counters of the current frame; pixels and draw calls are counted by the painter
+    perf: PerfStats,
+    /// Key presses not yet consumed by a widget (see [Ui::push_key])
+    keys: heapless::Deque<KeyEvent, KEY_QUEUE_LEN>,
+    /// Bounding box of all widget allocations this frame (see [Ui::used_area])
+    used_area: Option<Rectangle>,
+    /// Bounding box of the areas cleared outside of widgets this frame (see [Ui::invalidated])
+    invalidated_area: Option<Rectangle>,
+    /// Bounding box of the areas cleared this frame over widgets added before, which redraw
+    /// next frame (see [Ui::erased_area])
+    erased_area: Option<Rectangle>,
+    /// Bounding box of the areas erased in the last frame, whose widgets redraw (see
+    /// [Ui::redraw_erased])
+    erased_before: Option<Rectangle>,
+    /// Number of widgets currently being drawn (widgets may add other widgets)
+    widget_depth: u32,
+    /// Patches restoring the style of [Ui::push_style] calls, innermost last
+    style_stack: heapless::Vec<(StylePatch, u32), STYLE_STACK_DEPTH>,
+    /// Identifies the active style patches, `0` without any (see [Ui::style_key])
+    style_key: u32,
+    /// Index of the focused widget allocation (see [Ui::set_focus])
+    focus: Option<u32>,
+    /// Whether the focused widget is activated this frame (see [Ui::activate_focus])
+    focus_activated: bool,
+    /// Number of widget allocations this frame, including sub-[Ui]s
+    allocations: u32,
+    /// Whether hover interactions are shown (see [Ui::set_pointer_kind])
+    pointer_kind: PointerKind,
+    /// Whether a widget asked to be drawn again right away (see [Ui::request_repaint])
+    repaint: bool,
+    /// Bounding box of the areas transparent widgets couldn't redraw (see [Ui::stale_area])
+    stale_area: Option<Rectangle>,
+    /// Paints cleared areas instead of the background color (see [Ui::set_background_painter])
+    background_painter: Option<BackgroundPainter<DRAW>>,
+    /// Moves pixels on the draw target (see [Ui::set_area_mover])
+    area_mover: Option<AreaMover<DRAW>>,
+    /// Scrolls rows of the draw target (see [Ui::set_scroll_assist])
+    scroll_assist: Option<fn(&mut DRAW, Rectangle, i32) -> bool>,
+    /// Called for the interactions of widgets (see [Ui::set_feedback_handler])
+    feedback_handler: Option<fn(FeedbackEvent)>,
+    /// Outer bounds of the top-level [Ui], which overlays are limited to
+    screen: Rectangle,
+    /// Areas of the last frame's overlays, where widgets get no interactions (see
+    /// [Ui::begin_overlays])
+    covered: heapless::Vec<Rectangle, MAX_OVERLAYS>,
+    /// Whether an overlay is being drawn (see [Ui::draw_overlay])
+    in_overlay: bool,
+    /// Dirty area of the enclosing [Ui]s this frame, before this sub-[Ui] was created
+    outer_dirty: Option<Rectangle>,
+    /// Bounding box of the enclosing [Ui]s' widget allocations this frame, before this
+    /// sub-[Ui] was created
+    outer_used: Option<Rectangle>,
+    /// The pointer and its press, retained by a [UiState] (see [Ui::press_origin])
+    pointer: PointerTrack,
+}
+
+/// Returns `bounds` without the window border `padding` on each side
+fn inner_bounds(bounds: Rectangle, padding: Size) -> Rectangle {
+    Rectangle::new(
+        bounds
+            .top_left
+            .add(Point::new(padding.width as i32, padding.height as i32)),
+        bounds.size.saturating_sub(padding * 2),
+    )
+}
+
+/// Sizes of the widgets measured by [Ui::equal_width_row], [Ui::horizontal] and [Ui::vertical]
+#[derive(Clone, Copy, Debug, Default)]
+struct RowMeasure {
+    /// Width of the widest widget
+    max_width: u32,
+    /// Number of widgets
+    count: u32,
+    /// Sum of the widths of the widgets
+    total_width: u32,
+    /// Height of the tallest widget, at least the height rows start with
+    max_height: u32,
+    /// Sum of the heights of the widgets, each at least the height rows start with
+    total_height: u32,
+}
+
+impl RowMeasure {
+    /// Returns the size of the measured widgets next to each other (`horizontal`) or below each
+    /// other, separated by `spacing`
+    fn size(&self, horizontal: bool, spacing: Size) -> Size {
+        let gaps = self.count.saturating_sub(1);
+        match horizontal {
+            true => Size::new(self.total_width + gaps * spacing.width, self.max_height),
+            false => Size::new(self.max_width, self.total_height + gaps * spacing.height),
+        }
+    }
+}
+
+// -- Getter methods for [Ui] --
+impl<DRAW, COL> Ui<'_, DRAW, COL>
+where
+    DRAW: DrawTarget<Color = COL>,
+    COL: PixelColor,
+{
+    /// The size of a [Ui] in bytes, without the draw target it borrows, e.g. to budget the
+    /// stack of the task drawing the UI. It doesn't depend on the type of the draw target, but on
+    /// the color type (through the [Style]).
     ///
     /// # Example
     ///
-    /// ```no_run
+    /// ```
+    /// # use embedded_graphics::mock_display::MockDisplay;
     /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.clear_row().unwrap();
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// println!("{} bytes", Ui::<MockDisplay<Rgb565>, Rgb565>::STATIC_SIZE);
     /// ```
-    pub fn clear_row(&mut self) -> GuiResult<()> {
-        let row_height = self.placer.row_height();
-        let row_rect = Rectangle::new(
-            Point::new(0, self.placer.pos.y),
-            Size::new(self.placer.bounds.width, row_height),
-        );
-        self.clear_area(row_rect)
-    }
+    pub const STATIC_SIZE: usize = core::mem::size_of::<Self>();
 
-    /// Clears the current row from the current widget position to the end of the row.
+    /// Returns the width of the [Ui]'s placer.
     ///
-    /// This is useful for removing any rendering remains of partially drawn widgets.
+    /// Note that this is not the entire screen width.
     ///
     /// ## Returns
     ///
-    /// A [GuiResult] indicating success or error.
+    /// The width of the placer as a `u32`.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
     /// # let mut widget = Label::new("Hi");
-    /// ui.clear_row_to_end().unwrap();
+    /// let width = ui.get_width();
+    /// println!("Placer width: {}", width);
     /// ```
-    pub fn clear_row_to_end(&mut self) -> GuiResult<()> {
-        let col_height = self.placer.row_height;
-        let col_rect = Rectangle::new(
-            // clear right to widget bounds
-            Point::new(
-                self.placer.pos.x + self.style.spacing.window_border_padding.width as i32,
-                self.placer.pos.y,
-            ),
-            Size::new(
-                (self.placer.bounds.width as i32 - self.placer.pos.x).max(0) as u32,
-                col_height,
-            ),
-        );
-        self.clear_area(col_rect)
+    pub fn get_width(&self) -> u32 {
+        self.placer.bounds.width
     }
 
-    /// Clears the [Ui] from the current placement position down to the bottom of the screen.
+    /// Returns the width of the screen.
     ///
-    /// **Warning:** This clears the entire screen area from the current row downwards.
+    /// This includes the UI's window border padding.
     ///
     /// ## Returns
     ///
-    /// A [GuiResult] indicating success or error.
+    /// The screen width as a `u32`.
     ///
     /// # Example
     ///
     /// ```no_run
+    /// # use embedded_graphics::geometry::Size;
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.clear_to_bottom().unwrap();
+    /// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let screen_width = ui.get_screen_width();
+    /// println!("Screen width: {}", screen_width);
     /// ```
-    pub fn clear_to_bottom(&mut self) -> GuiResult<()> {
-        self.clear_area(Rectangle::new(
-            Point::new(0, self.placer.pos.y),
-            Size::new(
-                self.placer.bounds.width,
-                self.placer.bounds.height - self.placer.pos.y as u32,
-            ),
-        ))
+    pub fn get_screen_width(&self) -> u32 {
+        self.bounds.size.width + self.style.spacing.window_border_padding.width * 2
     }
+}
 
-    /// Clears the entire background of the [Ui] with the background color defined in the style.
+// -- Construction and widget addition methods --
+impl<'a, COL, DRAW> Ui<'a, DRAW, COL>
+where
+    DRAW: DrawTarget<Color = COL>,
+    COL: PixelColor,
+{
+    /// Creates a new [Ui] instance with the given drawable, bounds and style.
     ///
-    /// This method updates the [Ui]'s cleared flag.
+    /// The provided bounds are adjusted by the style's window border padding. The [Ui] stays
+    /// within them (widgets, [clearing](Ui::clear_background) and interactions), so the rest of
+    /// the drawable can be used by the application, e.g. for a video feed. Everything drawn
+    /// with [Ui::draw] is clipped to the bounds, even widgets drawing outside of their area.
     ///
     /// ## Returns
     ///
-    /// A [GuiResult] indicating success or error.
+    /// A new instance of [Ui].
     ///
     /// # Example
     ///
     /// ```no_run
+    /// # use embedded_graphics::geometry::Size;
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.clear_background().unwrap();
-    /// ```
-    pub fn clear_background(&mut self) -> GuiResult<()> {
-        self.cleared = true;
+    /// use embedded_graphics::geometry::Dimensions;
+    /// let bounds = display.bounding_box();
+    /// let ui = Ui::new(&mut display, bounds, medsize_rgb565_style());
+    /// ```
+    pub fn new(drawable: &'a mut DRAW, bounds: Rectangle, style: Style<COL>) -> Self {
+        // set bounds to internal bounds (apply padding)
+        let bounds = inner_bounds(bounds, style.spacing.window_border_padding);
 
-        // clear background
-        let real_bg = Rectangle::new(
-            self.bounds.top_left.sub(Point::new(
-                self.style.spacing.window_border_padding.width as i32,
-                self.style.spacing.window_border_padding.height as i32,
-            )),
-            self.bounds
-                .size
-                .saturating_add(self.style.spacing.window_border_padding * 2),
+        // set up placer
+        let placer = Placer::new(
+            bounds.size,
+            Overflow::default(),
+            Align(HorizontalAlign::Left, VerticalAlign::Center),
         );
 
-        real_bg
-            .draw_styled(
-                &PrimitiveStyleBuilder::new()
-                    .fill_color(self.style.background_color)
-                    .build(),
-                self.painter.target,
-            )
-            .map_err(|_| GuiError::DrawError(Some("Couldn't clear GUI Background")))
+        let mut ui = Self {
+            bounds,
+            painter: Painter::new(drawable),
+            style,
+            placer,
+            interact: Interaction::None,
+            source: None,
+            origin: Point::zero(),
+            cleared: false,
+            debug_color: None,
+            debug_overlay: DebugOverlay::Off,
+            now: None,
+            dt: None,
+            wrap_state: 0,
+            min_width: None,
+            measure: None,
+            horizontal: false,
+            invalidate_next: false,
+            drawn_before: None,
+            drawn_shift: Point::zero(),
+            perf: PerfStats::default(),
+            keys: heapless::Deque::new(),
+            used_area: None,
+            invalidated_area: None,
+            erased_area: None,
+            erased_before: None,
+            widget_depth: 0,
+            style_stack: heapless::Vec::new(),
+            style_key: 0,
+            focus: None,
+            focus_activated: false,
+            allocations: 0,
+            pointer_kind: PointerKind::Mouse,
+            repaint: false,
+            stale_area: None,
+            background_painter: None,
+            area_mover: None,
+            scroll_assist: None,
+            feedback_handler: None,
+            screen: Rectangle::zero(),
+            covered: heapless::Vec::new(),
+            in_overlay: false,
+            outer_dirty: None,
+            outer_used: None,
+            pointer: PointerTrack::default(),
+        };
+        ui.screen = ui.outer_bounds();
+        ui.update_clip();
+        ui
     }
-}
 
-// -- Drawing methods --
-impl<'a, COL, DRAW> Ui<'a, DRAW, COL>
-where
-    DRAW: DrawTarget<Color = COL>,
-    COL: PixelColor,
-{
-    /// Sets the internal drawing buffer for the [Ui].
+    /// Creates a new fullscreen [Ui] instance using the entire bounding box of the drawable.
     ///
-    /// This buffer is used for optimized drawing and is optional.
+    /// This is equivalent to calling [Ui::new] with the drawable's bounding box.
+    ///
+    /// ## Returns
+    ///
+    /// A new fullscreen instance of [Ui].
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let mut buffer = [Rgb565::BLACK; 10000];
-    /// ui.set_buffer(&mut buffer);
+    /// let ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
     /// ```
-    pub fn set_buffer(&mut self, buffer: &'a mut [COL]) {
-        self.painter.set_buffer(buffer);
+    pub fn new_fullscreen(drawable: &'a mut DRAW, style: Style<COL>) -> Self {
+        let bounds = drawable.bounding_box();
+        Ui::new(drawable, bounds, style)
     }
 
-    /// Begins the drawing process for a specified area in the [Ui].
-    ///
-    /// This initializes the drawing buffer (if set) and clears it with the background color.
-    ///
-    /// ## Panics
+    /// Sets the current interaction for the [Ui].
     ///
-    /// Panics if the underlying [Painter] is already using its framebuffer.
+    /// This interaction is used to update the state of widgets.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let draw_area = Rectangle::new(Point::new(30, 20), Size::new(100, 150));
-    /// ui.start_drawing(&draw_area);
+    /// ui.interact(Interaction::Click(Point::new(10, 10)));
     /// ```
-    pub fn start_drawing(&mut self, area: &Rectangle) {
-        self.painter.start_drawing(area);
-        self.painter.clear_buffer(self.style.background_color);
+    pub fn interact(&mut self, interaction: Interaction) {
+        self.interact = interaction;
+        self.source = None;
+        self.pointer.observe(interaction, self.now);
+        if self.pointer.pinched {
+            // the touch left after a pinch neither drags nor clicks
+            self.interact = Interaction::None;
+        }
     }
 
-    /// Clears the current drawing buffer with the specified color.
+    /// Sets the current interaction for the [Ui] like [Ui::interact], together with what
+    /// produced it. Widgets report it in [Response::source].
+    ///
+    /// Without a source, it is the [PointerKind] (see [Ui::set_pointer_kind]).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::ui::InteractionSource;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// // a hardware key mapped to the on-screen "OK" button
+    /// let release = Interaction::Release(Point::new(20, 200));
+    /// ui.interact_from(release, InteractionSource::Button);
+    /// ```
+    pub fn interact_from(&mut self, interaction: Interaction, source: InteractionSource) {
+        self.interact(interaction);
+        self.source = Some(source);
+    }
+
+    /// Returns what produced the current interaction, see [Ui::interact_from].
+    pub fn interaction_source(&self) -> InteractionSource {
+        self.source.unwrap_or(self.pointer_kind.into())
+    }
+
+    /// Sets the position of a second touch of a multi-touch panel in this frame, `None`
+    /// without one. Call it after [Ui::interact], which gets the first touch as usual.
+    ///
+    /// While both touches are down, they form a [Gesture::Pinch] (see [Ui::check_gesture]),
+    /// and the widgets get no interaction: neither the pinch nor the touch left after it (until
+    /// it is released) clicks or drags them. Frames without a second touch are unaffected.
+    ///
+    /// The scale of a pinch is relative to the last frame, so it needs a [UiState] keeping the
+    /// touches between frames. Without one, every pinch is in its first frame.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::{Gesture, UiState};
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let (touch, second_touch, now_ms) = (Interaction::None, None, 0);
+    /// let mut state = UiState::new(medsize_rgb565_style());
+    ///
+    /// loop {
+    ///     let mut ui = state.begin_frame(&mut display, touch, now_ms);
+    ///     ui.interact_second(second_touch);
+    ///     if let Some(Gesture::Pinch { scale_delta, .. }) = ui.gesture() {
+    ///         println!("zoomed by {scale_delta}");
+    ///     }
+    ///     state.end_frame(ui);
+    /// }
+    /// ```
+    pub fn interact_second(&mut self, second: Option<Point>) {
+        let pressed = self.pointer.press.is_some_and(|press| !press.released);
+        let first = self.pointer.position.filter(|_| pressed);
+        self.pointer.touches = first.zip(second).map(|(first, second)| [first, second]);
+        if self.pointer.touches.is_some() {
+            self.pointer.pinched = true;
+            self.interact = Interaction::None;
+        }
+    }
+
+    /// Returns the gesture of this frame (see [Ui::interact_second]), in interaction
+    /// coordinates.
     ///
     /// ## Returns
     ///
-    /// `true` if the buffer was successfully cleared, or `false` if no buffer is present.
+    /// `Some(gesture)` while two touches are down, `None` otherwise.
+    pub fn gesture(&self) -> Option<Gesture> {
+        let [first, second] = self.pointer.touches?;
+        let distance = |[first, second]: [Point; 2]| {
+            let d = first - second;
+            crate::trig::isqrt((d.x * d.x + d.y * d.y) as u32)
+        };
+        let scale_delta = match self.pointer.touches_before.map(distance) {
+            Some(before) if before > 0 => distance([first, second]) as f32 / before as f32,
+            _ => 1.0,
+        };
+        Some(Gesture::Pinch {
+            center: (first + second) / 2,
+            scale_delta,
+        })
+    }
+
+    /// Returns the gesture of this frame if both of its touches are within `area` (in the
+    /// drawable's coordinates, like widget areas) and not within an overlay, with the center
+    /// in the drawable's coordinates, too.
+    ///
+    /// ## Returns
+    ///
+    /// `Some(gesture)` while two touches are down within `area`, `None` otherwise.
+    pub fn check_gesture(&self, area: Rectangle) -> Option<Gesture> {
+        let (rotation, frame) = (self.painter.rotation, self.painter.target.bounding_box());
+        let map = |p: Point| rotation.rotate_point(p - self.origin, frame);
+        let within =
+            |p: Point| area.contains(map(p)) && (self.in_overlay || !self.is_covered(map(p)));
+        if !self.pointer.touches?.into_iter().all(within) {
+            return None;
+        }
+        let Gesture::Pinch {
+            center,
+            scale_delta,
+        } = self.gesture()?;
+        Some(Gesture::Pinch {
+            center: map(center),
+            scale_delta,
+        })
+    }
+
+    /// Returns the last known pointer position, in interaction coordinates. With a [UiState],
+    /// it is kept while the interactions have no point (e.g. [Interaction::None] after a touch
+    /// was lifted).
+    pub fn pointer(&self) -> Option<Point> {
+        self.pointer.position
+    }
+
+    /// Returns where the current press (a click followed by drags) started, in interaction
+    /// coordinates, including in the frame of its release.
+    ///
+    /// Only a [UiState] keeps the press between frames. A [Ui] created without one only knows
+    /// the origin in the frame of the [Interaction::Click], and assumes the press started at
+    /// the current point otherwise.
+    pub fn press_origin(&self) -> Option<Point> {
+        self.pointer.press.map(|press| press.origin)
+    }
+
+    /// Returns how long the current press lasts so far in milliseconds, based on the frame
+    /// times of [Ui::begin_frame] (see [Ui::press_origin]).
+    pub fn press_duration(&self) -> Option<u64> {
+        let since = self.pointer.press?.since?;
+        Some(self.now?.saturating_sub(since))
+    }
+
+    /// Returns the drag or release of a press that started within `area` (see
+    /// [Ui::press_started_in]) and wandered at most
+    /// [Spacing::press_slop](crate::style::Spacing::press_slop) pixels outside of it, e.g. by
+    /// the jitter of a touch panel, otherwise [Interaction::None].
+    ///
+    /// Buttons use this when [Ui::check_interact] finds no interaction, so they stay pressed
+    /// (and are clicked on release) while the pointer jitters around their edge. As the press
+    /// origin is only known across frames with a [UiState], this has no effect without one.
+    pub fn check_held_press(&self, area: Rectangle) -> Interaction {
+        if !self.press_started_in(area) {
+            return Interaction::None;
+        }
+        let slop = self.style.spacing.press_slop.min(i32::MAX as u32) as i32;
+        match self.check_interact(area.offset(slop)) {
+            interaction @ (Interaction::Drag(_) | Interaction::Release(_)) => interaction,
+            _ => Interaction::None,
+        }
+    }
+
+    /// Returns whether the current press started within `area` (in the drawable's
+    /// coordinates, like widget areas), see [Ui::press_origin].
+    pub fn press_started_in(&self, area: Rectangle) -> bool {
+        self.pointer.press.is_some_and(|press| {
+            let (rotation, frame) = (self.painter.rotation, self.painter.target.bounding_box());
+            area.contains(rotation.rotate_point(press.origin - self.origin, frame))
+        })
+    }
+
+    /// Sets the kind of pointing device providing the interactions ([PointerKind::Mouse] by
+    /// default).
+    ///
+    /// With [PointerKind::Touch], [Interaction::Hover]s are ignored: a touch panel only reports
+    /// them while pressed, which would flash the hover look before the pressed one. This also
+    /// saves the redraws of the hover look. Widgets focused with [Ui::set_focus] still look
+    /// hovered.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
+    /// use kolibri_embedded_gui::ui::PointerKind;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let cleared = ui.clear_buffer_raw(Rgb565::BLACK);
-    /// println!("Buffer cleared: {}", cleared);
+    /// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.set_pointer_kind(PointerKind::Touch);
     /// ```
-    pub fn clear_buffer_raw(&mut self, color: COL) -> bool {
-        self.painter.clear_buffer(color)
+    pub fn set_pointer_kind(&mut self, kind: PointerKind) {
+        self.pointer_kind = kind;
     }
 
-    /// Finalizes the drawing process for the [Ui].
+    /// Returns the kind of pointing device, see [Ui::set_pointer_kind].
+    pub fn pointer_kind(&self) -> PointerKind {
+        self.pointer_kind
+    }
+
+    /// Focuses the `index`-th widget added to the [Ui] this frame (counting every allocation,
+    /// including sub-[Ui]s), or nothing with `None`.
     ///
-    /// This flushes any buffered drawing to the underlying draw target.
+    /// The focused widget looks hovered. With [Ui::activate_focus], it gets an
+    /// [Interaction::ActivatedByFocus] instead, which widgets handle like a touch release: it
+    /// reports `clicked()` / `changed()` and draws the widget pressed for that frame.
+    /// A pointer interaction within the widget takes precedence.
+    ///
+    /// The focus only lasts for the frame, so keep the index outside of the UI loop (e.g. moved by
+    /// an encoder), and set it at the start of every frame, before adding widgets.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "checkbox")] {
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::KeyEvent;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let next_key = || -> Option<KeyEvent> { None };
+    /// // with the `checkbox` feature
+    /// let mut focus = 0;
+    /// let mut widgets = 1;
+    /// let mut checked = [false; 3];
+    ///
+    /// loop {
+    ///     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ///     match next_key() {
+    ///         Some(KeyEvent::Down) => focus = (focus + 1) % widgets,
+    ///         Some(KeyEvent::Up) => focus = (focus + widgets - 1) % widgets,
+    ///         Some(KeyEvent::Enter) => ui.activate_focus(),
+    ///         _ => {}
+    ///     }
+    ///     ui.set_focus(Some(focus));
+    ///
+    ///     for checked in checked.iter_mut() {
+    ///         ui.add(Checkbox::new(checked));
+    ///     }
+    ///     widgets = ui.allocations();
+    /// }
+    /// # }
+    /// ```
+    pub fn set_focus(&mut self, index: Option<u32>) {
+        self.focus = index;
+    }
+
+    /// Returns the index of the focused widget, see [Ui::set_focus].
+    pub fn focus(&self) -> Option<u32> {
+        self.focus
+    }
+
+    /// Activates the focused widget (see [Ui::set_focus]) this frame, e.g. when an encoder is
+    /// pushed.
+    pub fn activate_focus(&mut self) {
+        self.focus_activated = true;
+    }
+
+    /// Returns the number of widgets added to the [Ui] so far this frame (including sub-[Ui]s),
+    /// e.g. to wrap the focus around.
+    pub fn allocations(&self) -> u32 {
+        self.allocations
+    }
+
+    /// Queues a key press (e.g. from a USB HID keyboard or a matrix keypad) for the widgets of
+    /// this frame.
+    ///
+    /// Like [Ui::interact], call this before adding widgets. Widgets that accept text input
+    /// take the keys they handle with [Ui::consume_keys]; the first such widget added this
+    /// frame gets them. Keys that no widget consumed are still available from
+    /// [Ui::pending_keys] after adding the widgets, and are discarded with the [Ui].
+    ///
+    /// At most [KEY_QUEUE_LEN] keys are queued per frame.
     ///
     /// ## Returns
     ///
-    /// A [GuiResult] indicating success or error.
+    /// The key as an error if the queue is full.
     ///
     /// # Example
     ///
     /// ```no_run
+    /// # #[cfg(feature = "text-input")] {
     /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::KeyEvent;
+    /// # use kolibri_embedded_gui::helpers::keyboard::{draw_keyboard, Layout};
     /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.finalize().unwrap();
+    /// # let mut smartstates = SmartstateProvider::<60>::new();
+    /// # let (mut shift, mut open) = (false, true);
+    /// # let mut text = heapless::String::<32>::new();
+    /// # fn poll_keypad() -> Option<KeyEvent> { None }
+    /// // with the `text-input` feature
+    /// while let Some(key) = poll_keypad() {
+    ///     ui.push_key(key).ok();
+    /// }
+    ///
+    /// let layout = Layout::qwerty_with_special();
+    /// let response = draw_keyboard(
+    ///     &mut ui,
+    ///     &layout,
+    ///     Some(&mut smartstates),
+    ///     true,
+    ///     false,
+    ///     &mut shift,
+    ///     &mut open,
+    ///     &mut text,
+    /// );
+    /// if response.confirmed() {
+    ///     // enter was pressed
+    /// }
+    ///
+    /// // keys the keyboard didn't use (e.g. arrows) are left to the app
+    /// for key in ui.pending_keys() {
+    ///     // ...
+    /// }
+    /// # }
     /// ```
-    pub fn finalize(&mut self) -> GuiResult<()> {
-        self.painter.finalize()
+    pub fn push_key(&mut self, key: KeyEvent) -> Result<(), KeyEvent> {
+        self.keys.push_back(key)
     }
 
-    /// Draws a [Drawable] item onto the [Ui].
+    /// Passes the queued key presses (see [Ui::push_key]) to `f`, in order, and removes those
+    /// for which `f` returns `true` (i.e. that it handled).
+    ///
+    /// This is for widgets accepting keyboard input.
+    pub fn consume_keys(&mut self, mut f: impl FnMut(KeyEvent) -> bool) {
+        for _ in 0..self.keys.len() {
+            if let Some(key) = self.keys.pop_front() {
+                if !f(key) {
+                    // keep the order of the remaining keys
+                    self.keys.push_back(key).ok();
+                }
+            }
+        }
+    }
+
+    /// Returns the queued key presses that no widget consumed (so far) this frame.
+    pub fn pending_keys(&self) -> impl Iterator<Item = KeyEvent> + '_ {
+        self.keys.iter().copied()
+    }
+
+    /// Discards the queued key presses.
+    pub fn clear_keys(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Sets where the drawable's origin is in the coordinates of the interactions passed to
+    /// [Ui::interact].
     ///
-    /// If a buffer is active, the item is drawn to the buffer; otherwise, it is drawn directly.
+    /// Widgets are laid out and drawn in the drawable's coordinates, while touch and mouse
+    /// input usually comes in screen coordinates. If the drawable is only a part of the screen
+    /// (e.g. a [Cropped](embedded_graphics::draw_target::Cropped) region of the display, or a
+    /// [Translated](embedded_graphics::draw_target::Translated) display), set the origin to
+    /// the screen position of the drawable's (0, 0), so that interactions hit the widgets
+    /// where they're visible. The default is (0, 0), i.e. both coordinate systems are the same.
     ///
-    /// ## Returns
+    /// # Example
     ///
-    /// A [GuiResult] indicating whether the drawing operation was successful.
+    /// Rendering a [Ui] into a region of a larger scene:
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let touch = Interaction::None;
+    /// // the rest of the screen is drawn by the app
+    /// let region = Rectangle::new(Point::new(100, 50), Size::new(200, 150));
+    /// let mut target = display.cropped(&region);
+    ///
+    /// let mut ui = Ui::new_fullscreen(&mut target, medsize_rgb565_style());
+    /// ui.set_origin(region.top_left);
+    /// // in screen coordinates, like the touch controller reports them
+    /// ui.interact(touch);
+    /// if ui.add(Button::new("OK")).clicked() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn set_origin(&mut self, origin: Point) {
+        self.origin = origin;
+    }
+
+    /// Returns where the drawable's origin is in interaction coordinates (see [Ui::set_origin]).
+    pub fn origin(&self) -> Point {
+        self.origin
+    }
+
+    /// Rotates the [Ui] clockwise on the drawable, e.g. for a panel mounted upside down or in
+    /// portrait orientation.
+    ///
+    /// Everything drawn is rotated, and the interactions are rotated back, so they stay in the
+    /// coordinates the touch controller reports (see also [Ui::set_origin]). The [Ui] keeps
+    /// covering the same part of the drawable, but its bounds are now in the rotated
+    /// coordinates: a fullscreen [Ui] on a 320x240 display is 240x320 with [Rotation::Deg90].
+    ///
+    /// Call this right after creating the [Ui], before adding widgets, as it restarts the
+    /// layout. A [background painter](Ui::set_background_painter) still draws unrotated.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::ui::Rotation;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let touch = Interaction::None;
+    /// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.set_rotation(Rotation::Deg90);
+    /// // as the touch controller reports it, unrotated
+    /// ui.interact(touch);
+    /// if ui.add(Button::new("OK")).clicked() {
+    ///     // ...
+    /// }
+    /// ```
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        let frame = self.painter.target.bounding_box();
+        let outer = self
+            .painter
+            .rotation
+            .unrotate_rect(self.outer_bounds(), frame);
+        self.painter.rotation = rotation;
+        let outer = rotation.rotate_rect(outer, frame);
+        self.bounds = inner_bounds(outer, self.style.spacing.window_border_padding);
+
+        let mut placer = Placer::new(self.bounds.size, self.placer.overflow, self.placer.align);
+        placer.fixed_row_height = self.placer.fixed_row_height;
+        self.placer = placer;
+        self.update_clip();
+    }
+
+    /// Returns the rotation of the [Ui], see [Ui::set_rotation].
+    pub fn rotation(&self) -> Rotation {
+        self.painter.rotation
+    }
+
+    /// Sets the timestamp of the current frame, in milliseconds.
+    ///
+    /// Time-dependent widget features (like [Button::repeat](crate::button::Button::repeat))
+    /// use this timestamp. The clock source is up to you, it only has to be monotonic.
+    /// If this is never called, these features fall back to their time-independent behavior
+    /// (e.g. a held button only fires once).
+    ///
+    /// As a [Ui] only lives for a frame, it doesn't know the previous timestamp. For the time
+    /// since the last frame ([Ui::dt]), use a [FrameClock] instead, or call [Ui::tick].
+    ///
+    /// # Clock Sources
+    ///
+    /// With embassy, use `embassy_time::Instant::now().as_millis()`. On bare metal, a
+    /// millisecond counter incremented by the SysTick interrupt works just as well, e.g.
+    /// `static MILLIS: AtomicU32` read with `MILLIS.load(Ordering::Relaxed) as u64` (a `u32`
+    /// counter wraps after 49 days, so extend it to `u64` if the device runs longer).
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
     /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
     /// # let output_settings = OutputSettingsBuilder::new().build();
     /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// # use embedded_graphics::primitives::PrimitiveStyle;
-    /// # let rectangle_shape = Rectangle::new(Point::new(50, 0), Size::new(200, 150));
-    /// # // Make it drawable with a filled red style
-    /// # let rectangle = rectangle_shape.into_styled(PrimitiveStyle::with_fill(Rgb565::RED));
-    /// ui.draw(&rectangle).unwrap();
+    /// let start = std::time::Instant::now();
+    /// // [...]
+    /// ui.begin_frame(start.elapsed().as_millis() as u64);
     /// ```
-    pub fn draw(&mut self, item: &impl Drawable<Color = COL>) -> GuiResult<()> {
-        self.painter.draw(item)
+    pub fn begin_frame(&mut self, now_ms: u64) {
+        self.now = Some(now_ms);
+        self.reset_perf();
     }
-}
 
-// -- Sub-[Ui] methods --
-impl<COL, DRAW> Ui<'_, DRAW, COL>
-where
-    DRAW: DrawTarget<Color = COL>,
-    COL: PixelColor,
-{
-    /// Creates a sub-[Ui] with the given bounds without performing extra bounds checks.
+    /// Returns the performance counters of the current frame.
     ///
-    /// This sub-[Ui] is useful for drawing to a specific area with its own layout and style.
+    /// The counters start at zero for a new [Ui], and are reset by [Ui::begin_frame] and
+    /// [Ui::reset_perf]. Counters of sub-[Ui]s are included once they're done.
     ///
     /// ## Returns
     ///
-    /// A [GuiResult] indicating success or error.
+    /// The [PerfStats] of the current frame.
     ///
     /// # Example
     ///
     /// ```no_run
     /// # use embedded_graphics::pixelcolor::Rgb565;
     /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
+    /// # use kolibri_embedded_gui::prelude::*;
     /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
     /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
     /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// let sub_bounds = Rectangle::new(Point::new(50, 0), Size::new(200, 150));
-    /// ui.unchecked_sub_ui(sub_bounds, |sub_ui| {
-    ///     sub_ui.add(widget);
-    ///     Ok(())
-    /// }).unwrap();
+    /// # let mut smartstates = SmartstateProvider::<20>::new();
+    /// ui.add(Button::new("Hello").smartstate(smartstates.nxt()));
+    ///
+    /// let perf = ui.perf();
+    /// println!(
+    ///     "{} widgets, {} redrawn, {} px",
+    ///     perf.widgets_added, perf.widgets_redrawn, perf.pixels_drawn
+    /// );
     /// ```
-    pub fn unchecked_sub_ui<F>(&mut self, bounds: Rectangle, f: F) -> GuiResult<()>
-    where
-        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
+    pub fn perf(&self) -> PerfStats {
+        PerfStats {
+            pixels_drawn: self
+                .perf
+                .pixels_drawn
+                .saturating_add(self.painter.pixels_drawn),
+            draw_calls: self.perf.draw_calls + self.painter.draw_calls,
+            ..self.perf
+        }
+    }
+
+    /// Ends the frame, returning what changed.
+    ///
+    /// Widgets only draw into the [DrawTarget] the [Ui] was created with. On displays that need
+    /// an explicit flush (e.g. a frame buffer in RAM that is sent to the panel via DMA), this
+    /// keeps the expensive bus transfer out of the widget code: build the frame, finish it, and
+    /// flush only [FrameInfo::dirty_area] afterwards, e.g. asynchronously. As the [Ui] is
+    /// consumed, the borrow of the draw target ends here.
+    ///
+    /// [DebugOverlay] outlines aren't part of the dirty area, flush the whole screen while using
+    /// them.
+    ///
+    /// ## Returns
+    ///
+    /// The [FrameInfo] of the frame.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut framebuffer = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut smartstates = SmartstateProvider::<20>::new();
+    /// # async fn flush(framebuffer: &SimulatorDisplay<Rgb565>, area: Rectangle) {}
+    /// # async {
+    /// let mut ui = Ui::new_fullscreen(&mut framebuffer, medsize_rgb565_style());
+    /// ui.add(Button::new("OK").smartstate(smartstates.nxt()));
+    /// let frame = ui.finish_frame();
+    ///
+    /// if let Some(area) = frame.dirty_area {
+    ///     // e.g. an async DMA transfer of the area, letting other tasks run meanwhile
+    ///     flush(&framebuffer, area).await;
+    /// }
+    /// # };
+    /// ```
+    pub fn finish_frame(self) -> FrameInfo {
+        let perf = self.perf();
+        FrameInfo {
+            dirty_area: perf.dirty_area,
+            perf,
+            needs_repaint: self.repaint,
+            stale_area: self.stale_area,
+            erased_area: self.erased_area,
+        }
+    }
+
+    /// Resets the performance counters (see [Ui::perf]).
+    pub fn reset_perf(&mut self) {
+        self.perf = PerfStats::default();
+        self.painter.pixels_drawn = 0;
+        self.painter.draw_calls = 0;
+    }
+
+    /// Returns the timestamp of the current frame in milliseconds, as set by [Ui::begin_frame].
+    ///
+    /// ## Returns
+    ///
+    /// `Some(timestamp)` if [Ui::begin_frame] was called, `None` otherwise.
+    pub fn now(&self) -> Option<u64> {
+        self.now
+    }
+
+    /// Sets the time since the last frame, in milliseconds.
+    ///
+    /// [FrameClock::begin_frame] calls this. Call it yourself if your main loop already knows
+    /// the frame time (e.g. a fixed-rate loop).
+    pub fn tick(&mut self, delta_ms: u32) {
+        self.dt = Some(delta_ms);
+    }
+
+    /// Returns the time since the last frame in milliseconds, as set by [Ui::tick] (or a
+    /// [FrameClock]).
+    ///
+    /// Transitions advance by it: [Animated](crate::anim::Animated) values, the check mark of a
+    /// [Checkbox](crate::checkbox::Checkbox) and the knob of a
+    /// [ToggleSwitch](crate::toggle_switch::ToggleSwitch). Looping animations (the
+    /// [Spinner](crate::spinner::Spinner) and the [Skeleton](crate::skeleton::Skeleton)
+    /// shimmer) and timers ([Button::repeat](crate::button::Button::repeat), the long press of a
+    /// [ReorderableList](crate::reorderable_list::ReorderableList)) use [Ui::now] instead, so
+    /// that their phase doesn't drift when frames take longer.
+    ///
+    /// ## Returns
+    ///
+    /// `Some(delta)` if the frame time is known, `None` otherwise.
+    pub fn dt(&self) -> Option<u32> {
+        self.dt
+    }
+
+    /// Asks for the next frame to be built right away, even without new input.
+    ///
+    /// Animated widgets (e.g. the [Spinner](crate::spinner::Spinner)) call this while drawing.
+    /// Custom widgets should call it while they animate, e.g. while
+    /// [Animated::animating](crate::anim::Animated::animating) is `true`.
+    pub fn request_repaint(&mut self) {
+        self.repaint = true;
+    }
+
+    /// Returns whether a widget asked for the next frame to follow right away
+    /// (see [Ui::request_repaint]), also available as [FrameInfo::needs_repaint].
+    ///
+    /// Otherwise, nothing changes until the next input, so the application can sleep
+    /// (e.g. on `WFI` until the touch interrupt).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "indicators")] {
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # fn millis() -> u64 { 0 }
+    /// # fn wait_for_touch_interrupt() {}
+    /// // with the `indicators` feature
+    /// loop {
+    ///     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ///     ui.begin_frame(millis());
+    ///     ui.add(Spinner::new());
+    ///
+    ///     if !ui.needs_repaint() {
+    ///         wait_for_touch_interrupt();
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn needs_repaint(&self) -> bool {
+        self.repaint
+    }
+
+    /// Returns whether anything was drawn so far in this frame: a widget redrew itself
+    /// (its smartstate changed), or something else was drawn (e.g. a cleared background).
+    ///
+    /// If not, the display doesn't need to be flushed. Together with [Ui::needs_repaint],
+    /// this allows event-driven rendering: build a frame for new input or when a widget
+    /// animates, and only flush it if it had changes.
+    pub fn frame_had_changes(&self) -> bool {
+        let perf = self.perf();
+        perf.widgets_redrawn > 0 || perf.dirty_area.is_some()
+    }
+
+    /// Sets what happens with widgets that don't fit into the remaining width of the current row.
+    ///
+    /// The default is [Overflow::Wrap]. With [Overflow::Clip], all drawing of this [Ui]
+    /// is clipped to its bounds. Sub-[Ui]s inherit the policy.
+    ///
+    /// # Smartstates
+    ///
+    /// With [Overflow::Wrap], the position of a widget depends on the size of the widgets before it.
+    /// If a wrap point moves (e.g. because a label's text got longer), the widgets after it move
+    /// without their smartstates changing. As smartstates record where their widget was drawn
+    /// (see [Drawn Areas](crate::smartstate::Smartstate#drawn-areas)), a widget allocated
+    /// elsewhere than in the last frame redraws at its new position anyway.
+    ///
+    /// The places the widgets moved away from aren't cleared, though: where no other widget is
+    /// drawn now, e.g. at the end of the row a widget wrapped from, its old pixels stay.
+    /// [Ui::wrap_state] changes whenever the wrap points change, so it can be used to detect this
+    /// and clear the background.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # use kolibri_embedded_gui::ui::Overflow;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut smartstates = kolibri_embedded_gui::smartstate::SmartstateProvider::<20>::new();
+    /// let mut layout = Smartstate::empty();
+    /// loop {
+    ///     // [...]
+    ///     ui.set_overflow_policy(Overflow::Wrap);
+    ///     for i in 0..10 {
+    ///         ui.add_horizontal(Label::new("Item").smartstate(smartstates.nxt()));
+    ///     }
+    ///
+    ///     // wrap points moved: clear the leftovers, and redraw everything next frame
+    ///     if !layout.is_state(ui.wrap_state()) {
+    ///         layout.set_state(ui.wrap_state());
+    ///         ui.clear_background().ok();
+    ///         smartstates.force_redraw_all();
+    ///     }
+    /// }
+    /// ```
+    pub fn set_overflow_policy(&mut self, overflow: Overflow) {
+        self.placer.overflow = overflow;
+        self.update_clip();
+    }
+
+    /// Sets how widgets are aligned vertically within a row when they have different heights.
+    ///
+    /// The default is [VerticalAlign::Center]. With [VerticalAlign::Top], every widget is given the
+    /// full height of the row (so e.g. buttons stretch to the tallest widget before them).
+    /// Sub-[Ui]s inherit the alignment.
+    ///
+    /// # Caveat
+    ///
+    /// In a row of the [Ui], widgets are aligned to the row height *at the time they are added*, so
+    /// widgets added before a taller widget in the same row are not moved afterwards. Add such a
+    /// row as a [Ui::horizontal] group, which measures its widgets before drawing them, add the
+    /// tallest widget first, or expand the row height up front with [Ui::expand_row_height].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[cfg(feature = "icon-widgets")] {
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::ui::VerticalAlign;
+    /// # use embedded_iconoir::size32px;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// // with the `icon-widgets` feature
+    /// ui.set_row_alignment(VerticalAlign::Center);
+    /// ui.add_horizontal(IconButton::new(size32px::actions::Check));
+    /// // the label is vertically centered next to the (taller) icon button
+    /// ui.add(Label::new("Done"));
+    /// # }
+    /// ```
+    pub fn set_row_alignment(&mut self, align: VerticalAlign) {
+        self.placer.align.1 = align;
+    }
+
+    /// Returns the current vertical row alignment (see [Ui::set_row_alignment]).
+    pub fn row_alignment(&self) -> VerticalAlign {
+        self.placer.align.1
+    }
+
+    /// Sets how widgets are aligned horizontally within the rest of the row.
+    ///
+    /// The default is [HorizontalAlign::Left]. With [HorizontalAlign::Center] or
+    /// [HorizontalAlign::Right], each widget is moved to the center or the right end of the
+    /// rest of the row (like [Ui::add_centered] does for a single widget), so they are meant
+    /// for one widget per row, e.g. a [Ui::horizontal] group.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::HorizontalAlign;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// // dialog buttons in the bottom right corner
+    /// ui.set_horizontal_alignment(HorizontalAlign::Right);
+    /// ui.horizontal(|ui| {
+    ///     ui.add(Button::new("Cancel"));
+    ///     ui.add(Button::new("OK"));
+    /// });
+    /// ui.set_horizontal_alignment(HorizontalAlign::Left);
+    /// ```
+    pub fn set_horizontal_alignment(&mut self, align: HorizontalAlign) {
+        self.placer.align.0 = align;
+    }
+
+    /// Returns the current horizontal alignment (see [Ui::set_horizontal_alignment]).
+    pub fn horizontal_alignment(&self) -> HorizontalAlign {
+        self.placer.align.0
+    }
+
+    /// Returns the current [Overflow] policy (see [Ui::set_overflow_policy]).
+    pub fn overflow_policy(&self) -> Overflow {
+        self.placer.overflow
+    }
+
+    /// Returns a value identifying where widgets were wrapped to a new row so far this frame.
+    ///
+    /// The value is the same for identical wrap points, and `0` if nothing was wrapped. Compare it
+    /// between frames to detect wrap points moving (see [Ui::set_overflow_policy]).
+    pub fn wrap_state(&self) -> u32 {
+        self.wrap_state
+    }
+
+    /// Clips the painter to the [Ui]'s area, or to its bounds without the window border padding
+    /// if the overflow policy requires it. A sub-[Ui] is clipped to its parent, too.
+    fn update_clip(&mut self) {
+        let clip = match self.placer.overflow {
+            Overflow::Clip => self.bounds,
+            _ => self.outer_bounds(),
+        };
+        self.painter.clip = Some(match self.painter.limit {
+            Some(limit) => clip.intersection(&limit),
+            None => clip,
+        });
+    }
+
+    /// Adds a widget to the [Ui] and, if requested, clears the remaining horizontal space in the current row.
+    ///
+    /// After adding the widget, a new row is started.
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] indicating the result of adding the widget.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let response = ui.add_and_clear_col_remainder(widget, true);
+    /// ```
+    pub fn add_and_clear_col_remainder(
+        &mut self,
+        widget: impl TypedWidget<COL>,
+        clear: bool,
+    ) -> Response {
+        let mut resp = self.add_raw(widget).unwrap_or_else(Response::from_error);
+        if clear {
+            if let (Err(e), None) = (self.clear_row_to_end(), resp.error) {
+                resp = resp.set_error(e);
+            }
+        }
+        self.new_row();
+        resp
+    }
+
+    /// Adds a widget to the [Ui] and then starts a new row.
+    ///
+    /// The widget is drawn and its response is returned. In a [Ui::horizontal] group, the
+    /// widget is followed by the item spacing instead of a new row.
+    ///
+    /// This is the convenience variant of [Ui::try_add]: it never panics, and errors
+    /// (e.g. the widget not fitting, or the display failing) are only reported through
+    /// [Response::error]. Use [Ui::try_add] if you want to handle them with `?`,
+    /// or [Ui::add_or_skip] for optional widgets that may not fit.
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] indicating the result of adding the widget.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let response = ui.add(widget);
+    /// if let Some(err) = response.error() {
+    ///     println!("couldn't add widget: {err}");
+    /// }
+    /// ```
+    pub fn add(&mut self, widget: impl TypedWidget<COL>) -> Response {
+        self.try_add(widget).unwrap_or_else(Response::from_error)
+    }
+
+    /// Adds a widget to the [Ui] and then starts a new row, returning any error to the caller.
+    ///
+    /// A new row is started even if the widget couldn't be added, so the layout of the
+    /// following widgets is the same as with [Ui::add].
+    ///
+    /// ## Returns
+    ///
+    /// The widget's [Response], or the [GuiError] that occurred while placing or drawing it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// fn draw_menu<DRAW: DrawTarget<Color = Rgb565>>(ui: &mut Ui<DRAW, Rgb565>) -> GuiResult<()> {
+    ///     ui.try_add(Label::new("Menu"))?;
+    ///     if ui.try_add(Label::new("Settings"))?.clicked() {
+    ///         // [...]
+    ///     }
+    ///     Ok(())
+    /// }
+    ///
+    /// draw_menu(&mut ui).unwrap();
+    /// ```
+    pub fn try_add(&mut self, widget: impl TypedWidget<COL>) -> GuiResult<Response> {
+        let resp = self.add_raw(widget);
+        self.end_item();
+        resp
+    }
+
+    /// Moves on after an item added with [Ui::add]: to a new row, or past the item spacing in a
+    /// [Ui::horizontal] group
+    fn end_item(&mut self) {
+        if self.horizontal {
+            // ignore space errors, like Ui::add_horizontal
+            self.allocate_space_no_wrap(self.style().spacing.item_spacing)
+                .ok();
+        } else {
+            self.new_row();
+        }
+    }
+
+    /// Reserves space like a widget of `size` added with [Ui::add], without drawing anything.
+    ///
+    /// This keeps the position of the following widgets when a widget is hidden. To also
+    /// erase the hidden widget, use [Ui::add_or_placeholder].
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] for the reserved area.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let warning = false;
+    /// if warning {
+    ///     ui.add(Label::new("Low battery"));
+    /// } else {
+    ///     ui.placeholder(Size::new(0, 15));
+    /// }
+    /// // always in the same place
+    /// ui.add(Label::new("Ready"));
+    /// ```
+    pub fn placeholder(&mut self, size: Size) -> Response {
+        let resp = self
+            .allocate_space(size)
+            .map(Response::new)
+            .unwrap_or_else(Response::from_error);
+        self.end_item();
+        resp
+    }
+
+    /// Adds `widget` like [Ui::add] if `show` is set, and otherwise reserves its size with a
+    /// [placeholder](Ui::placeholder), so that the following widgets stay in place.
+    ///
+    /// When the widget was shown in the last frame (according to `state`), the placeholder
+    /// erases it where its smartstate recorded it was drawn (see
+    /// [Drawn Areas](crate::smartstate::Smartstate#drawn-areas)), like [Ui::clear_area]. When it is shown again, it is redrawn, even with an unchanged smartstate.
+    /// The widget is created in both cases, so it consumes its smartstate (e.g.
+    /// [SmartstateProvider::nxt](crate::smartstate::SmartstateProvider::nxt)) either way and
+    /// the following widgets keep theirs.
+    ///
+    /// ## Returns
+    ///
+    /// The widget's [Response], or the placeholder's if the widget is hidden.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::PlaceholderState;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut smartstates = SmartstateProvider::<4>::new();
+    /// # let running = true;
+    /// let mut stop_slot = PlaceholderState::new();
+    /// loop {
+    ///     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ///     smartstates.restart_counter();
+    ///     // only while running
+    ///     let stop = Button::new("Stop").smartstate(smartstates.nxt());
+    ///     ui.add_or_placeholder(running, stop, &mut stop_slot);
+    ///     ui.add(Button::new("Settings").smartstate(smartstates.nxt()));
+    /// }
+    /// ```
+    pub fn add_or_placeholder(
+        &mut self,
+        show: bool,
+        mut widget: impl TypedWidget<COL>,
+        state: &mut PlaceholderState,
+    ) -> Response {
+        let last = state.last;
+        if show {
+            if let Some((false, area)) = last {
+                // the placeholder left the area empty: the widget has to be redrawn
+                self.invalidate(area);
+            }
+            let resp = self.add(widget);
+            if resp.error.is_none() {
+                state.last = Some((true, resp.internal.area));
+            }
+            return resp;
+        }
+
+        let size = self.measure_widget(&mut widget);
+        if let (Some((true, shown)), false) = (last, self.cleared) {
+            // erase the widget where it was drawn, even if it moved since
+            let drawn = (widget.smartstate_mut())
+                .and_then(|state| state.drawn_area())
+                .map_or(shown, |drawn| drawn.translate(self.drawn_shift));
+            if let Err(e) = self.clear_area(drawn) {
+                return Response::from_error(e);
+            }
+        }
+        let resp = self.placeholder(size);
+        if resp.error.is_none() {
+            state.last = Some((false, resp.internal.area));
+        }
+        resp
+    }
+
+    /// Adds a widget to the [Ui] if it fits at the current position, and then starts a new row.
+    ///
+    /// Unlike [Ui::add], the widget doesn't wrap to the next row, and a widget that doesn't
+    /// fit is skipped silently instead of reporting an error. This is useful for optional
+    /// trailing items (e.g. a status text after a row of buttons) on small screens.
+    /// Other errors, like draw errors, are still reported through [Response::error].
+    ///
+    /// ## Returns
+    ///
+    /// The widget's [Response], or an empty [Response] if the widget was skipped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.add_horizontal(Button::new("Start"));
+    /// ui.add_horizontal(Button::new("Stop"));
+    /// // only shown if there's enough space left in the row
+    /// ui.add_or_skip(Label::new("Motor idle"));
+    /// ```
+    pub fn add_or_skip(&mut self, widget: impl TypedWidget<COL>) -> Response {
+        let overflow = self.placer.overflow;
+        self.placer.overflow = Overflow::Error;
+        let resp = match self.add_raw(widget) {
+            Ok(resp) => resp,
+            Err(GuiError::NoHorizontalSpace { .. } | GuiError::NoVerticalSpace { .. }) => {
+                Response::new(InternalResponse::empty())
+            }
+            Err(e) => Response::from_error(e),
+        };
+        self.placer.overflow = overflow;
+        self.new_row();
+        resp
+    }
+
+    /// Adds a widget centered horizontally in the current row of the [Ui].
+    ///
+    /// After drawing the widget, the row alignment is reset.
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] indicating the result of adding the widget.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let response = ui.add_centered(widget);
+    /// ```
+    pub fn add_centered(&mut self, widget: impl TypedWidget<COL>) -> Response {
+        let align = self.placer.align;
+        self.placer.align = Align(HorizontalAlign::Center, align.1);
+        let resp = self.add_raw(widget).unwrap_or_else(Response::from_error);
+        self.placer.align = align;
+        self.new_row();
+        resp
+    }
+
+    /// Adds a widget to the current row of the [Ui] without starting a new row.
+    ///
+    /// Space is allocated for the next widget after this one.
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] indicating the result of adding the widget.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let response = ui.add_horizontal(widget);
+    /// ```
+    pub fn add_horizontal(&mut self, widget: impl TypedWidget<COL>) -> Response {
+        let resp = self.add_raw(widget).unwrap_or_else(Response::from_error);
+        // Allocate space between widgets; ignore space errors.
+        self.allocate_space_no_wrap(self.style().spacing.item_spacing)
+            .ok();
+        resp
+    }
+
+    /// Draws a widget directly to the [Ui] without changing the layout.
+    ///
+    /// If a debug color is set, the widget's bounding area is drawn with that color.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] wrapping a [Response] for the drawn widget.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// match ui.add_raw(widget) {
+    ///     Ok(response) => { /* widget drawn successfully */ },
+    ///     Err(e) => { /* handle error */ },
+    /// }
+    /// ```
+    pub fn add_raw(&mut self, mut widget: impl TypedWidget<COL>) -> GuiResult<Response> {
+        self.perf.widgets_added += 1;
+        let redraws = self.perf.widgets_redrawn;
+        if self.measure.is_none() {
+            self.drawn_before = (widget.smartstate_mut())
+                .and_then(|state| state.drawn_area())
+                .map(|drawn| drawn.translate(self.drawn_shift));
+        }
+        self.widget_depth += 1;
+        let res = widget.draw_typed(self);
+        self.drawn_before = None;
+        self.widget_depth -= 1;
+        let res = res?;
+        if self.measure.is_none() {
+            if let Some(smartstate) = widget.smartstate_mut() {
+                smartstate.set_drawn_area(&res.internal.area);
+            }
+        }
+        let redrawn = self.perf.widgets_redrawn != redraws;
+        if let Some(handler) = self.feedback_handler {
+            FeedbackEvent::of(&res).for_each(handler);
+        }
+
+        let bounds_color = match self.debug_overlay {
+            DebugOverlay::Bounds | DebugOverlay::Both => Some(
+                self.debug_color
+                    .unwrap_or(self.style.highlight_border_color),
+            ),
+            _ => self.debug_color,
+        };
+        let redraw_color = match self.debug_overlay {
+            DebugOverlay::Redraws | DebugOverlay::Both if redrawn => {
+                Some(self.style.secondary_color)
+            }
+            _ => None,
+        };
+        // the redraw outline goes on top, so it is visible in both modes
+        for color in [bounds_color, redraw_color].into_iter().flatten() {
+            res.internal
+                .area
+                .draw_styled(
+                    &PrimitiveStyleBuilder::new()
+                        .stroke_color(color)
+                        .stroke_width(1)
+                        .build(),
+                    &mut self.painter,
+                )
+                .map_err(|_| GuiError::DrawError(Some("Couldn't draw widget bounds")))?;
+        }
+        Ok(res)
+    }
+
+    /// Returns an immutable reference to the current style of the [Ui].
+    ///
+    /// ## Returns
+    ///
+    /// A reference to the [Style].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let current_style = ui.style();
+    /// ```
+    pub fn style(&self) -> &Style<COL> {
+        &self.style
+    }
+
+    /// Returns a mutable reference to the current style of the [Ui].
+    ///
+    /// ## Returns
+    ///
+    /// A mutable reference to the [Style].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let style = ui.style_mut();
+    /// style.background_color = Rgb565::BLACK;
+    /// ```
+    pub fn style_mut(&mut self) -> &mut Style<COL> {
+        &mut self.style
+    }
+
+    /// Applies `patch` over the current style until the matching [Ui::pop_style].
+    ///
+    /// Patches compose: fields a nested patch doesn't override keep the value of the outer ones.
+    /// Prefer [Ui::with_style], which can't forget the pop. At most [STYLE_STACK_DEPTH] patches
+    /// can be pushed (per [Ui], sub-[Ui]s have their own stack).
+    ///
+    /// ## Returns
+    ///
+    /// `false` if the stack is full, in which case the style is unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::mono_font::ascii;
+    /// use kolibri_embedded_gui::style::StylePatch;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.push_style(StylePatch::new().font(ascii::FONT_6X10));
+    /// ui.add(Label::new("small print"));
+    /// ui.pop_style();
+    /// ```
+    pub fn push_style(&mut self, patch: StylePatch) -> bool {
+        if self.style_stack.is_full() {
+            return false;
+        }
+        let restore = patch.apply(&mut self.style);
+        self.style_stack.push((restore, self.style_key)).ok();
+        self.style_key = nested_style_key(self.style_key, &patch);
+        true
+    }
+
+    /// Restores the style from before the last [Ui::push_style].
+    ///
+    /// ## Returns
+    ///
+    /// `false` if no patch was pushed.
+    pub fn pop_style(&mut self) -> bool {
+        let Some((restore, key)) = self.style_stack.pop() else {
+            return false;
+        };
+        restore.apply(&mut self.style);
+        self.style_key = key;
+        true
+    }
+
+    /// Calls `f` with `patch` applied over the current style, e.g. for a section that uses a
+    /// smaller font and tighter spacing. Patches compose, like with [Ui::push_style].
+    ///
+    /// Widgets include the active patches in their smartstates (see [Ui::style_key]), so
+    /// a widget is redrawn when it is drawn with a different patch than in the last frame.
+    ///
+    /// ## Returns
+    ///
+    /// What `f` returned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::mono_font::ascii;
+    /// use kolibri_embedded_gui::style::StylePatch;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let compact = StylePatch::new()
+    ///     .font(ascii::FONT_6X10)
+    ///     .default_widget_height(12);
+    /// let reset = ui.with_style(compact, |ui| {
+    ///     ui.add_horizontal(Button::new("Reset")).clicked()
+    /// });
+    /// ```
+    pub fn with_style<R>(&mut self, patch: StylePatch, f: impl FnOnce(&mut Self) -> R) -> R {
+        let restore = patch.apply(&mut self.style);
+        let key = self.style_key;
+        self.style_key = nested_style_key(key, &patch);
+        let res = f(self);
+        restore.apply(&mut self.style);
+        self.style_key = key;
+        res
+    }
+
+    /// Returns a key identifying the style patches active at the moment (see [Ui::with_style]),
+    /// `0` without any.
+    ///
+    /// Widgets [mix](crate::smartstate::Smartstate::mix) it into their smartstate, so that they
+    /// are redrawn when the patch changes. Custom widgets should do the same.
+    pub fn style_key(&self) -> u32 {
+        self.style_key
+    }
+
+    /// Sets the space between items, which [Ui::new_row], [Ui::add_horizontal] and the
+    /// following allocations use, until it is changed again. Padding inside widgets stays
+    /// with the style.
+    ///
+    /// Changing the spacing moves the widgets after it. As their smartstates record where they
+    /// were drawn (see [Drawn Areas](crate::smartstate::Smartstate#drawn-areas)), the moved
+    /// widgets redraw at their new position, and the others don't. For a section,
+    /// [Ui::with_style] with a spacing patch restores the spacing afterwards.
+    ///
+    /// ## Returns
+    ///
+    /// The previous item spacing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// // a dense data page
+    /// ui.set_item_spacing(Size::new(4, 1));
+    /// for reading in ["12.1 V", "0.4 A", "23 °C"] {
+    ///     ui.add(Label::new(reading));
+    /// }
+    /// ```
+    pub fn set_item_spacing(&mut self, spacing: Size) -> Size {
+        core::mem::replace(&mut self.style.spacing.item_spacing, spacing)
+    }
+
+    /// Advances the layout to a new row in the [Ui].
+    ///
+    /// This method uses the default spacing from the current style. The tracked row height is reset:
+    /// the new row starts with the height set by [Ui::set_row_height], or the style's default widget
+    /// height otherwise, and grows with the widgets added to it. So the first widget of a row sees
+    /// this starting height in [Ui::get_row_height], never the height of the previous row.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.new_row();
+    /// ```
+    pub fn new_row(&mut self) {
+        self.new_row_raw(self.style().spacing.item_spacing.height);
+        let height = self
+            .placer
+            .fixed_row_height
+            .unwrap_or(self.style().default_widget_height);
+        self.new_row_raw(height);
+    }
+
+    /// Advances the layout to a new row in the [Ui] with the specified height.
+    ///
+    /// ## Parameters
+    ///
+    /// - `height`: The height for the new row.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.new_row_raw(20);
+    /// ```
+    pub fn new_row_raw(&mut self, height: u32) {
+        self.placer.new_row(height);
+    }
+
+    /// Expands the current row height of the [Ui] to at least the given height.
+    ///
+    /// If the current row height is less than `height`, it is increased.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.expand_row_height(30);
+    /// ```
+    pub fn expand_row_height(&mut self, height: u32) {
+        self.placer.expand_row_height(height);
+    }
+
+    /// Sets the height every row of the [Ui] starts with, e.g. to get uniform rows regardless of content.
+    ///
+    /// The current row is expanded to `height` right away, and rows started by [Ui::new_row]
+    /// start with `height` instead of the style's default widget height. Widgets taller than
+    /// `height` still expand their row. Sub-[Ui]s inherit the row height.
+    ///
+    /// Use [Ui::reset_row_height] to go back to the default.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.set_row_height(40);
+    /// ui.add(Label::new("40px row"));
+    /// ui.add(Button::new("40px row as well"));
+    /// ui.reset_row_height();
+    /// ```
+    pub fn set_row_height(&mut self, height: u32) {
+        self.placer.fixed_row_height = Some(height);
+        self.placer.expand_row_height(height);
+    }
+
+    /// Resets the row height set by [Ui::set_row_height].
+    ///
+    /// Rows started afterwards start with the style's default widget height again. The current row
+    /// keeps its height.
+    pub fn reset_row_height(&mut self) {
+        self.placer.fixed_row_height = None;
+    }
+
+    /// Draws a [Drawable] item directly using the [Ui]'s underlying draw target.
+    ///
+    /// ## Returns
+    ///
+    /// The output produced by drawing the item, or an error if drawing fails.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// # use embedded_graphics::primitives::PrimitiveStyle;
+    /// # let rectangle_shape = Rectangle::new(Point::new(50, 0), Size::new(200, 150));
+    /// # // Make it drawable with a filled red style
+    /// # let rectangle = rectangle_shape.into_styled(PrimitiveStyle::with_fill(Rgb565::RED));
+    /// let result = ui.draw_raw(&rectangle);
+    /// ```
+    pub fn draw_raw<OUT>(
+        &mut self,
+        to_draw: &impl Drawable<Color = COL, Output = OUT>,
+    ) -> Result<OUT, DRAW::Error> {
+        self.painter.draw_calls += 1;
+        to_draw.draw(&mut CountingTarget::new(
+            self.painter.target,
+            &mut self.painter.pixels_drawn,
+            self.painter.rotation,
+        ))
+    }
+
+    /// Adds custom content of `size`, drawn by `draw` with embedded-graphics, e.g. a waveform
+    /// or a one-off gauge, without implementing [Widget].
+    ///
+    /// The space is allocated like for any widget, and cleared to the background before `draw`
+    /// is called with a [CustomTarget] for the area (with its origin at the area's top left
+    /// corner, clipped to it) and the area itself, in the drawable's coordinates. The area is
+    /// part of the frame's dirty area. `draw` is called every frame; use [Ui::custom_cached] to
+    /// skip unchanged content.
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] with the area and the interaction within it (e.g. to check for
+    /// [clicked](Response::clicked)), or an error if it doesn't fit or `draw` failed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::{Polyline, PrimitiveStyle};
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let samples = [Point::new(0, 20), Point::new(40, 5), Point::new(80, 35), Point::new(119, 20)];
+    /// let color = ui.style().primary_color;
+    /// ui.custom(Size::new(120, 40), |target, _area| {
+    ///     Polyline::new(&samples)
+    ///         .into_styled(PrimitiveStyle::with_stroke(color, 2))
+    ///         .draw(target)
+    /// })?;
+    /// # Ok::<(), GuiError>(())
+    /// ```
+    pub fn custom<F>(&mut self, size: Size, draw: F) -> GuiResult<Response>
+    where
+        F: FnOnce(&mut CustomTarget<'_, COL>, Rectangle) -> GuiResult<()>,
+    {
+        self.try_add(Custom {
+            size,
+            draw: Some(draw),
+            smartstate: Container::empty(),
+            version: 0,
+        })
+    }
+
+    /// Adds custom content like [Ui::custom], which is only drawn if `version` changed since the
+    /// last frame (as tracked by `smartstate`), or its area was invalidated.
+    ///
+    /// Change the version whenever the content changes, e.g. count up when new samples arrive,
+    /// or hash the drawn data.
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] like [Ui::custom], which [Response::redraw] tells whether `draw` was called.
+    pub fn custom_cached<F>(
+        &mut self,
+        size: Size,
+        smartstate: &mut Smartstate,
+        version: u32,
+        draw: F,
+    ) -> GuiResult<Response>
+    where
+        F: FnOnce(&mut CustomTarget<'_, COL>, Rectangle) -> GuiResult<()>,
+    {
+        self.try_add(Custom {
+            size,
+            draw: Some(draw),
+            smartstate: Container::new(smartstate),
+            version,
+        })
+    }
+
+    /// Starts a widget of `size`, drawn by a closure, for implementing [Widget]s with little
+    /// code (see [WidgetBuilder] and the [widget_prelude](crate::widget_prelude) guide).
+    ///
+    /// Unlike [Ui::custom], this doesn't add a widget, but is what a widget does while it is
+    /// added: call it from [Widget::draw].
+    ///
+    /// ## Returns
+    ///
+    /// A [WidgetBuilder] for the widget, which allocates the space once it is drawn.
+    pub fn widget(&mut self, size: Size) -> WidgetBuilder<'_, 'a, DRAW, COL> {
+        WidgetBuilder::new(self, size)
+    }
+
+    /// Returns a [CustomTarget] for `area` (with the drawable's origin) and the [Style], for
+    /// drawing a widget with a [DrawContext](crate::widget_builder::DrawContext)
+    pub(crate) fn custom_target(
+        &mut self,
+        area: Rectangle,
+    ) -> (CustomTarget<'_, COL>, &Style<COL>) {
+        (
+            CustomTarget::new(&mut self.painter, area, Point::zero()),
+            &self.style,
+        )
+    }
+
+    /// Returns the remaining available space for widget placement in the [Ui].
+    ///
+    /// ## Returns
+    ///
+    /// A [Size] representing the available width and height.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let space = ui.space_available();
+    /// println!("Available space: {:?}", space);
+    /// ```
+    pub fn space_available(&self) -> Size {
+        self.placer.space_available()
+    }
+
+    /// Returns the width left in the current row of the [Ui].
+    ///
+    /// A widget at most this wide fits into the current row without wrapping.
+    ///
+    /// ## Returns
+    ///
+    /// The remaining width in pixels, `0` if the row is full.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// // right-align a 40px wide widget
+    /// let gap = ui.remaining_width().saturating_sub(40);
+    /// ui.advance_cursor(Size::new(gap, 0)).ok();
+    /// ```
+    pub fn remaining_width(&self) -> u32 {
+        self.placer.space_available().width
+    }
+
+    /// Resolves a [RelativeWidth] into pixels, for widgets sized relative to the row.
+    ///
+    /// [RelativeWidth::PerMille] widths are shares of the width that was left in the row when
+    /// the first of them was resolved, so that e.g. two widgets of 50% added one after the
+    /// other share that width. Each share includes the item spacing after the widget (as
+    /// added by [Ui::add_horizontal]), so that shares adding up to 100% fit into the row,
+    /// spacing included. [RelativeWidth::Fill] is the [remaining width](Ui::remaining_width),
+    /// e.g. for the last widget of a row.
+    ///
+    /// ## Returns
+    ///
+    /// The width in pixels.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::RelativeWidth;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// // a custom widget taking a third of the row
+    /// let width = ui.resolve_width(RelativeWidth::PerMille(333));
+    /// let response = ui.allocate_space(Size::new(width, 20));
+    /// ```
+    pub fn resolve_width(&mut self, width: RelativeWidth) -> u32 {
+        match width {
+            RelativeWidth::PerMille(per_mille) => {
+                let available = self.remaining_width();
+                let base = *self.placer.fraction_base.get_or_insert(available);
+                let spacing = self.style.spacing.item_spacing.width;
+                let share = (base + spacing) as u64 * per_mille.min(1000) as u64 / 1000;
+                (share as u32).saturating_sub(spacing)
+            }
+            RelativeWidth::Fill => self.remaining_width(),
+        }
+    }
+
+    /// Returns the height left in the [Ui], from the top of the current row to the bottom.
+    ///
+    /// A widget at most this high fits into the current row.
+    ///
+    /// ## Returns
+    ///
+    /// The remaining height in pixels, `0` if the [Ui] is full.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// // anchor a 20px high row at the bottom
+    /// let gap = ui.remaining_height().saturating_sub(20);
+    /// ui.new_row_raw(gap);
+    /// ```
+    pub fn remaining_height(&self) -> u32 {
+        self.placer.space_available().height
+    }
+
+    /// Returns the position where the next widget would be placed, in screen coordinates.
+    ///
+    /// This is the top left corner of the next allocation, unless it is wrapped to a new row.
+    ///
+    /// ## Returns
+    ///
+    /// The cursor position as a [Point].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let cursor = ui.cursor();
+    /// println!("Next widget at: {:?}", cursor);
+    /// ```
+    pub fn cursor(&self) -> Point {
+        self.bounds.top_left + self.placer.pos
+    }
+
+    /// Returns the area widgets are placed in, in screen coordinates.
+    ///
+    /// Unlike the screen, this excludes the window border padding.
+    ///
+    /// ## Returns
+    ///
+    /// The bounds of the [Ui] as a [Rectangle].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let bounds = ui.bounds();
+    /// println!("Widgets go into: {:?}", bounds);
+    /// ```
+    pub fn bounds(&self) -> Rectangle {
+        self.bounds
+    }
+
+    /// Consumes space in the current row without drawing anything.
+    ///
+    /// The space is allocated like a widget of the given `size` (see [Ui::allocate_space]),
+    /// so the row height expands as needed and the [Overflow] policy applies.
+    ///
+    /// ## Returns
+    ///
+    /// `Ok(())` if the space was allocated, or the layout error otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.advance_cursor(Size::new(20, 0)).ok();
+    /// ```
+    pub fn advance_cursor(&mut self, size: Size) -> GuiResult<()> {
+        self.allocate_space(size).map(|_| ())
+    }
+
+    /// Checks if the current interaction occurs within the specified area.
+    ///
+    /// ## Returns
+    ///
+    /// The [Interaction] if the interaction's point is within the area, otherwise [Interaction::None].
+    /// The returned interaction is in the drawable's coordinates, see [Ui::set_origin].
+    /// Hovers are [Interaction::None] for [PointerKind::Touch], and interactions within an
+    /// overlay are [Interaction::None] outside of it (see [Ui::draw_overlay]).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// # let some_rectangle = Rectangle::new(Point::new(50, 0), Size::new(200, 150));
+    /// let interaction = ui.check_interact(some_rectangle);
+    /// ```
+    pub fn check_interact(&self, area: Rectangle) -> Interaction {
+        self.interact_within(area, self.in_overlay)
+    }
+
+    /// Checks for an interaction within `area` like [Ui::check_interact], but also within the
+    /// overlays, e.g. for a widget to check the area of its overlay before drawing it (see
+    /// [Ui::draw_overlay]).
+    ///
+    /// ## Returns
+    ///
+    /// The [Interaction] if the interaction's point is within the area, otherwise [Interaction::None].
+    pub fn check_overlay_interact(&self, area: Rectangle) -> Interaction {
+        self.interact_within(area, true)
+    }
+
+    /// Returns the interaction if it is within `area` and, unless `overlay` is set, not within
+    /// an overlay
+    fn interact_within(&self, area: Rectangle, overlay: bool) -> Interaction {
+        // interactions are in screen coordinates, the area in the drawable's (rotated) ones
+        let interact = match (self.interact, self.pointer_kind) {
+            (Interaction::Hover(_), PointerKind::Touch) => return Interaction::None,
+            (interact, _) => {
+                let (rotation, frame) = (self.painter.rotation, self.painter.target.bounding_box());
+                interact
+                    .translated(-self.origin)
+                    .mapped(|p| rotation.rotate_point(p, frame))
+            }
+        };
+        if interact
+            .get_point()
+            .map(|pt| area.contains(pt) && (overlay || !self.is_covered(pt)))
+            .unwrap_or(false)
+        {
+            interact
+        } else {
+            Interaction::None
+        }
+    }
+
+    /// Returns the interaction of a new widget allocation: the pointer interaction if it is
+    /// within `area`, or the focus interaction if the allocation is focused.
+    fn interact_for_allocation(&mut self, area: Rectangle) -> InternalResponse {
+        let index = self.allocations;
+        self.allocations += 1;
+        match self.check_interact(area) {
+            Interaction::None if self.focus == Some(index) => {
+                let interaction = if self.focus_activated {
+                    Interaction::ActivatedByFocus(area.center())
+                } else {
+                    // focused widgets look hovered
+                    Interaction::Hover(area.center())
+                };
+                InternalResponse::new(area, interaction).with_source(InteractionSource::Synthetic)
+            }
+            interaction => {
+                InternalResponse::new(area, interaction).with_source(self.interaction_source())
+            }
+        }
+    }
+
+    /// Allocates an exact space in the [Ui] for a widget of the desired size.
+    ///
+    /// This method currently wraps [Ui::allocate_space] without extra logic.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] containing an [InternalResponse] with the allocated area and interaction.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let allocation = ui.allocate_exact_size(Size::new(50, 30));
+    /// ```
+    pub fn allocate_exact_size(&mut self, desired_size: Size) -> GuiResult<InternalResponse> {
+        self.allocate_space(desired_size)
+    }
+
+    /// Allocates space in the [Ui] for a widget of the desired size.
+    ///
+    /// If the widget doesn't fit into the current row, the [Overflow] policy decides whether
+    /// it is wrapped to a new row, clipped, or an error is returned (see [Ui::set_overflow_policy]).
+    /// The allocated area is adjusted by the [Ui]'s bounds.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] containing an [InternalResponse] with the allocated rectangle and interaction.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let allocation = ui.allocate_space(Size::new(100, 40));
+    /// ```
+    pub fn allocate_space(&mut self, desired_size: Size) -> GuiResult<InternalResponse> {
+        if let Some(measure) = self.measure.as_mut() {
+            let row_height = self
+                .placer
+                .fixed_row_height
+                .unwrap_or(self.style.default_widget_height);
+            let height = max(desired_size.height, row_height);
+            measure.max_width = max(measure.max_width, desired_size.width);
+            measure.count += 1;
+            measure.total_width += desired_size.width;
+            measure.max_height = max(measure.max_height, height);
+            measure.total_height += height;
+            return Err(GuiError::Measuring);
+        }
+        let desired_size = match self.min_width {
+            Some(min_width) => Size::new(max(desired_size.width, min_width), desired_size.height),
+            None => desired_size,
+        };
+        let rect = match self.placer.next(desired_size) {
+            Err(GuiError::NoHorizontalSpace { .. })
+                if self.placer.overflow == Overflow::Wrap
+                    && self.placer.pos.x > 0
+                    && desired_size.width <= self.placer.bounds.width =>
+            {
+                // record where the wrap happened, so that moving wrap points can be detected
+                self.wrap_state = self
+                    .wrap_state
+                    .wrapping_mul(31)
+                    .wrapping_add((self.placer.row << 16) | self.placer.pos.x as u32);
+                self.new_row();
+                self.placer.next(desired_size)
+            }
+            res => res,
+        }
+        .map(|mut rect| {
+            rect.top_left.add_assign(self.bounds.top_left);
+            rect
+        });
+        trace!(
+            "allocate_space: widget {} of row {}: {}",
+            self.placer.col,
+            self.placer.row,
+            rect
+        );
+        let rect = rect?;
+        self.used_area = union(self.used_area, rect);
+        // widgets that moved or were erased in the last frame redraw
+        let erased_before = self.erased_before;
+        let stale = self.drawn_before.take().is_some_and(|drawn| {
+            Smartstate::recorded_area(&drawn) != Smartstate::recorded_area(&rect)
+                || erased_before.is_some_and(|erased| !erased.intersection(&drawn).is_zero_sized())
+        });
+        if core::mem::take(&mut self.invalidate_next) || stale {
+            self.invalidate(rect);
+        }
+        Ok(self.interact_for_allocation(rect))
+    }
+
+    /// Allocates space in the [Ui] for a widget of the desired size without wrapping.
+    ///
+    /// The allocated area is adjusted by the [Ui]'s bounds. While measuring (see
+    /// [Ui::equal_width_row]), nothing is allocated and a [GuiError::Measuring] error is returned.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] containing an [InternalResponse] with the allocated rectangle and interaction.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let allocation = ui.allocate_space_no_wrap(Size::new(80, 25));
+    /// ```
+    pub fn allocate_space_no_wrap(&mut self, desired_size: Size) -> GuiResult<InternalResponse> {
+        if self.measure.is_some() {
+            return Err(GuiError::Measuring);
+        }
+        let area = self.placer.next_no_wrap(desired_size).map(|mut rect| {
+            rect.top_left.add_assign(self.bounds.top_left);
+            rect
+        })?;
+        self.used_area = union(self.used_area, area);
+
+        Ok(self.interact_for_allocation(area))
+    }
+
+    /// Returns the current row height used in the [Ui]'s layout.
+    ///
+    /// ## Returns
+    ///
+    /// The current row height as a `u32`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let row_height = ui.get_row_height();
+    /// println!("Row height: {}", row_height);
+    /// ```
+    pub fn get_row_height(&self) -> u32 {
+        self.placer.row_height()
+    }
+}
+
+// -- Clearing methods --
+impl<COL, DRAW> Ui<'_, DRAW, COL>
+where
+    DRAW: DrawTarget<Color = COL>,
+    COL: PixelColor,
+{
+    /// Returns whether the [Ui]'s background was cleared this frame.
+    ///
+    /// ## Returns
+    ///
+    /// `true` if the background was cleared, `false` otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// if ui.cleared() {
+    ///     println!("Background cleared");
+    /// }
+    /// ```
+    pub fn cleared(&self) -> bool {
+        self.cleared
+    }
+
+    /// Returns the bounding box of all areas allocated to widgets so far this frame, `None` if
+    /// no widget was added yet.
+    pub fn used_area(&self) -> Option<Rectangle> {
+        self.used_area
+    }
+
+    /// Erases what the widgets of the last frame left outside of this frame's [used area](Ui::used_area),
+    /// e.g. the rows of a list that got shorter, and remembers this frame's used area in `previous`.
+    ///
+    /// Call this after adding all widgets. If the background was cleared this frame
+    /// (see [Ui::clear_background]), there are no leftovers and nothing is drawn.
+    ///
+    /// The used areas are bounding boxes, so leftovers within this frame's bounding box (e.g. of
+    /// a row that got narrower in the middle of a list) aren't erased.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::UsedArea;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let items = ["a", "b"];
+    /// let mut used = UsedArea::new();
+    ///
+    /// loop {
+    ///     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ///     // the number of items changes between frames
+    ///     for item in items.iter() {
+    ///         ui.add(Label::new(item));
+    ///     }
+    ///     ui.clear_unused(&mut used).unwrap();
+    /// }
+    /// ```
+    pub fn clear_unused(&mut self, previous: &mut UsedArea) -> GuiResult<()> {
+        let used = self.used_area;
+        let leftover = core::mem::replace(&mut previous.area, used);
+        let Some(leftover) = leftover.filter(|_| !self.cleared) else {
+            return Ok(());
+        };
+        let current = used.unwrap_or(Rectangle::zero());
+        for area in difference(leftover, current) {
+            self.clear_area(area)?;
+        }
+        Ok(())
+    }
+
+    /// Clears the specified area in the [Ui] using the background color, or the background
+    /// painter if one is set (see [Ui::set_background_painter]).
+    ///
+    /// The area is clipped to the [Ui] (including its window border padding), and added to the
+    /// dirty area of the frame (see [Ui::finish_frame]).
+    ///
+    /// Widgets added later this frame whose area intersects a cleared area redraw, even if their
+    /// smartstate didn't change (see [Ui::invalidated]). Widgets added *before* the area was
+    /// cleared are erased: the area is added to [Ui::erased_area] and a repaint is requested
+    /// (see [Ui::needs_repaint]), so that they redraw next frame. Clearing before adding the
+    /// widgets in the area saves that frame.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.clear_area(Rectangle::new(Point::new(0,0), Size::new(100, 50))).unwrap();
+    /// ```
+    pub fn clear_area(&mut self, area: Rectangle) -> GuiResult<()> {
+        let area = area.intersection(&self.outer_bounds());
+        if self.widget_depth == 0 {
+            let erased = self
+                .allocated_area()
+                .map(|allocated| allocated.intersection(&area))
+                .filter(|erased| !erased.is_zero_sized());
+            if let Some(erased) = erased {
+                self.erased_area = union(self.erased_area, erased);
+                self.request_repaint();
+            }
+        }
+        self.clear_own_area(area)
+    }
+
+    /// Clears `area` like [Ui::clear_area], but for an area the [Ui] draws over on purpose
+    /// (e.g. an overlay, or the viewport of a scroll area), so that the widgets added before
+    /// aren't redrawn next frame.
+    fn clear_own_area(&mut self, area: Rectangle) -> GuiResult<()> {
+        let area = area.intersection(&self.outer_bounds());
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        self.perf.add_dirty_area(area);
+        // widgets clearing their own area don't invalidate it
+        if self.widget_depth == 0 {
+            self.invalidated_area = union(self.invalidated_area, area);
+        }
+        if self.background_painter.is_some() {
+            let area = self.painter.visible(area);
+            return self.paint_background(area);
+        }
+        self.draw(&area.into_styled(PrimitiveStyle::with_fill(self.style.background_color)))
+            .map_err(|_| GuiError::DrawError(Some("Couldn't clear area")))
+    }
+
+    /// Sets a function painting the background (e.g. an image or a gradient) wherever the [Ui]
+    /// clears an area, instead of filling it with the background color: in
+    /// [Ui::clear_background], [Ui::clear_area] and the methods using it (like
+    /// [Ui::clear_unused]), and behind widgets clearing their own area.
+    ///
+    /// The painter is called with the area to paint, and a draw target clipped to exactly that
+    /// area. It is called for every cleared area separately, so it should draw the part of the
+    /// background that belongs there, not start it at the area's corner.
+    ///
+    /// Transparent widgets (e.g. with
+    /// [Label::transparent_background](crate::label::Label::transparent_background)) use the
+    /// painter to restore the background behind them before they redraw, so there's no need to
+    /// track the [Ui::stale_area].
+    ///
+    /// While a background painter is set, widgets are drawn directly to the draw target instead
+    /// of the buffer (see [Ui::set_buffer]), which could only be cleared with a flat color.
+    ///
+    /// Sub-[Ui]s inherit the painter.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::draw_target::Clipped;
+    /// # use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// // dark blue on the left half of the screen, black on the right
+    /// fn halves(
+    ///     target: &mut Clipped<'_, SimulatorDisplay<Rgb565>>,
+    ///     _area: Rectangle,
+    /// ) -> Result<(), core::convert::Infallible> {
+    ///     target.clear(Rgb565::BLACK)?;
+    ///     Rectangle::new(Point::zero(), Size::new(160, 240))
+    ///         .into_styled(PrimitiveStyle::with_fill(Rgb565::new(0, 0, 12)))
+    ///         .draw(target)
+    /// }
+    ///
+    /// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.set_background_painter(halves);
+    /// ui.clear_background().unwrap();
+    /// ```
+    pub fn set_background_painter(&mut self, painter: BackgroundPainter<DRAW>) {
+        self.background_painter = Some(painter);
+    }
+
+    /// Sets a function moving pixels on the draw target, so that widgets showing a moving view
+    /// (like a [PanCanvas](crate::pan_canvas::PanCanvas)) can move what they drew and only draw
+    /// what the move uncovered, instead of everything (see [Ui::move_area]).
+    ///
+    /// Sub-[Ui]s inherit the mover.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// // reads the pixels back, which is slow, but shows the idea
+    /// fn move_pixels(display: &mut SimulatorDisplay<Rgb565>, area: Rectangle, by: Point) -> bool {
+    ///     let moved: Vec<_> = (area.points())
+    ///         .filter(|point| area.contains(*point + by))
+    ///         .map(|point| Pixel(point + by, display.get_pixel(point)))
+    ///         .collect();
+    ///     display.draw_iter(moved).is_ok()
+    /// }
+    ///
+    /// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.set_area_mover(move_pixels);
+    /// ```
+    pub fn set_area_mover(&mut self, mover: AreaMover<DRAW>) {
+        self.area_mover = Some(mover);
+    }
+
+    /// Lets [Ui::move_area] scroll the display's rows with its hardware scrolling (see
+    /// [ScrollAssist]), e.g. so that a [scroll area](Ui::scroll_area) only draws the rows a
+    /// scroll uncovers.
+    ///
+    /// Whole rows of the display are scrolled, so this is only used for vertical moves of
+    /// areas with nothing but the [Ui]'s window border padding beside them, i.e. spanning the
+    /// whole width of a fullscreen [Ui]. Other moves use the [area mover](Ui::set_area_mover),
+    /// if any. Sub-[Ui]s inherit the scroll assist.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::ScrollAssist;
+    /// # struct St7789;
+    /// # impl Dimensions for St7789 { fn bounding_box(&self) -> Rectangle { Rectangle::zero() } }
+    /// # impl DrawTarget for St7789 {
+    /// #     type Color = Rgb565;
+    /// #     type Error = core::convert::Infallible;
+    /// #     fn draw_iter<I: IntoIterator<Item = Pixel<Rgb565>>>(&mut self, _: I) -> Result<(), Self::Error> { Ok(()) }
+    /// # }
+    /// # let mut display = St7789;
+    /// impl ScrollAssist for St7789 {
+    ///     fn hw_scroll(&mut self, area: Rectangle, lines: i32) -> bool {
+    ///         // set the vertical scroll area to the rows of `area` and shift its start
+    ///         // address by `lines`, then map later draws to the scrolled rows
+    ///         true
+    ///     }
+    /// }
+    ///
+    /// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.set_scroll_assist();
+    /// ```
+    pub fn set_scroll_assist(&mut self)
+    where
+        DRAW: ScrollAssist,
+    {
+        self.scroll_assist = Some(DRAW::hw_scroll);
+    }
+
+    /// Moves the pixels of `area` by `by` with the [scroll assist](Ui::set_scroll_assist) or
+    /// the [area mover](Ui::set_area_mover), for a widget drawing a moving view within its
+    /// area.
+    ///
+    /// Afterwards, the part of `area` the pixels didn't move to has to be drawn again. The area
+    /// is part of the frame's dirty area.
+    ///
+    /// ## Returns
+    ///
+    /// Whether the pixels were moved: not without a scroll assist or area mover suitable for
+    /// the move, or if the pixels of the drawable aren't the [Ui]'s (as it is
+    /// [rotated](Ui::set_rotation)), or if the area is clipped or partly covered by an overlay.
+    pub fn move_area(&mut self, area: Rectangle, by: Point) -> bool {
+        let covers = |area: Rectangle| {
+            (self.covered.iter())
+                .chain(&self.painter.masks)
+                .any(|covered| !covered.intersection(&area).is_zero_sized())
+        };
+        if self.painter.rotation != Rotation::Deg0 || self.painter.visible(area) != area {
+            return false;
+        }
+
+        // scroll whole rows in hardware, or move the area's pixels
+        let rows = self
+            .full_width_rows(area)
+            .filter(|rows| by.x == 0 && !covers(*rows));
+        let moved = match (rows, self.scroll_assist, self.area_mover) {
+            (Some(rows), Some(assist), _) if assist(self.painter.target, rows, by.y) => Some(rows),
+            (_, _, Some(mover)) if !covers(area) => {
+                mover(self.painter.target, area, by).then_some(area)
+            }
+            _ => None,
+        };
+        if let Some(moved) = moved {
+            self.perf.add_dirty_area(moved);
+        }
+        moved.is_some()
+    }
+
+    /// Returns the rows of `area` across the whole draw target, if only the window border
+    /// padding of a [Ui] as wide as the draw target is beside the area
+    fn full_width_rows(&self, area: Rectangle) -> Option<Rectangle> {
+        let target = self.painter.target.bounding_box();
+        let inner = inner_bounds(self.screen, self.style.spacing.window_border_padding);
+        let spans = |outer: Rectangle, inner: Rectangle| {
+            outer.top_left.x <= inner.top_left.x
+                && outer.top_left.x + outer.size.width as i32
+                    >= inner.top_left.x + inner.size.width as i32
+        };
+        (spans(self.screen, target) && spans(area, inner)).then(|| {
+            Rectangle::new(
+                Point::new(target.top_left.x, area.top_left.y),
+                Size::new(target.size.width, area.size.height),
+            )
+        })
+    }
+
+    /// Sets a function called for the interactions of all widgets added from now on, e.g. to
+    /// play a click sound or pulse a vibration motor, instead of checking every [Response].
+    ///
+    /// The handler is called while the widgets are added, once per [FeedbackEvent]:
+    /// - [FeedbackEvent::Press] when a widget starts being pressed (see
+    ///   [Response::pressed_started])
+    /// - [FeedbackEvent::Release] when a widget is clicked
+    /// - [FeedbackEvent::LongPress] when a widget held down fires again
+    /// - [FeedbackEvent::ValueChanged] when a widget changes its value
+    ///
+    /// It's a plain function, so it can't capture anything: keep what it needs (e.g. the
+    /// buzzer) in a `static`. Sub-[Ui]s inherit the handler.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// use kolibri_embedded_gui::ui::FeedbackEvent;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    ///
+    /// fn beep(event: FeedbackEvent) {
+    ///     if event == FeedbackEvent::Press {
+    ///         // start the buzzer
+    ///     }
+    /// }
+    ///
+    /// let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.set_feedback_handler(beep);
+    /// ui.add(Button::new("Beep"));
+    /// ```
+    pub fn set_feedback_handler(&mut self, handler: fn(FeedbackEvent)) {
+        self.feedback_handler = Some(handler);
+    }
+
+    /// Paints the background into `area` with the background painter, if one is set.
+    fn paint_background(&mut self, area: Rectangle) -> GuiResult<()> {
+        let Some(paint) = self.background_painter else {
+            return Ok(());
+        };
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        self.painter.draw_calls += 1;
+        let painter = &mut self.painter;
+        let (rotation, frame) = (painter.rotation, painter.target.bounding_box());
+        for_each_unmasked(area, &painter.masks, &mut |area| {
+            painter.pixels_drawn += area.size.width * area.size.height;
+            // the painter draws unrotated
+            let area = rotation.unrotate_rect(area, frame);
+            paint(&mut painter.target.clipped(&area), area)
+        })
+        .map_err(|_| GuiError::DrawError(Some("Couldn't paint background")))
+    }
+
+    /// Clears everything in the [Ui] from the row `y` (in the drawable's coordinates) down,
+    /// like [Ui::clear_area].
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let show_details = false;
+    /// let header = ui.add(Label::new("Status"));
+    /// if !show_details {
+    ///     // the details were hidden: erase them
+    ///     let below = header.internal.area.bottom_right().unwrap().y + 1;
+    ///     ui.clear_below(below).unwrap();
+    /// }
+    /// ```
+    pub fn clear_below(&mut self, y: i32) -> GuiResult<()> {
+        let outer = self.outer_bounds();
+        let bottom = outer.top_left.y + outer.size.height as i32;
+        self.clear_area(Rectangle::new(
+            Point::new(outer.top_left.x, y),
+            Size::new(outer.size.width, (bottom - y).max(0) as u32),
+        ))
+    }
+
+    /// Marks `area` as redrawn outside of the widgets, e.g. after the application drew a
+    /// background image into it. Like after [Ui::clear_area], widgets added later this frame
+    /// in the area redraw (see [Ui::invalidated]).
+    pub fn invalidate(&mut self, area: Rectangle) {
+        let area = area.intersection(&self.outer_bounds());
+        if area.is_zero_sized() {
+            return;
+        }
+        self.perf.add_dirty_area(area);
+        self.invalidated_area = union(self.invalidated_area, area);
+    }
+
+    /// Called by widgets drawn without a background (e.g. with
+    /// [Label::transparent_background](crate::label::Label::transparent_background)) that
+    /// need to redraw `area`.
+    ///
+    /// Such widgets can't erase their previous content. Unless the area is blank (the [Ui]
+    /// was cleared, or the area [invalidated](Ui::invalidated)), the area is added to the
+    /// [Ui::stale_area] and a repaint is requested. The widget then skips drawing and keeps its
+    /// previous smartstate, so that it redraws once the application restored the background.
+    ///
+    /// With a [background painter](Ui::set_background_painter), the background is restored
+    /// right away instead, and the widget can always draw.
+    ///
+    /// ## Returns
+    ///
+    /// Whether the widget can draw now.
+    pub fn redraw_transparent(&mut self, area: Rectangle) -> bool {
+        if self.cleared || self.invalidated(area) {
+            return true;
+        }
+        if self.background_painter.is_some() {
+            // restore the background right away
+            let area = area.intersection(&self.outer_bounds());
+            let area = self.painter.visible(area);
+            self.perf.add_dirty_area(area);
+            return self.paint_background(area).is_ok();
+        }
+        self.stale_area = union(self.stale_area, area);
+        self.repaint = true;
+        false
+    }
+
+    /// Returns the bounding box of the transparent widgets that couldn't redraw this frame
+    /// (see [Ui::redraw_transparent]), `None` if there are none.
+    ///
+    /// At the start of the next frame, redraw the background (e.g. an image) in this area
+    /// and [invalidate](Ui::invalidate) it, then add the widgets as usual.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::format::Buf;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut smartstates = SmartstateProvider::<20>::new();
+    /// # let mut buf = Buf::<16>::new();
+    /// # let background = |area: Rectangle| area.into_styled(PrimitiveStyle::with_fill(Rgb565::BLUE));
+    /// # let temperature = 21;
+    /// // everything needs the background in the first frame
+    /// let mut stale = Some(display.bounding_box());
+    /// loop {
+    ///     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ///     smartstates.restart_counter();
+    ///     if let Some(area) = stale {
+    ///         ui.draw(&background(area)).ok();
+    ///         ui.invalidate(area);
+    ///     }
+    ///
+    ///     ui.add(
+    ///         Label::new_fmt(&mut buf, format_args!("{temperature} °C"))
+    ///             .transparent_background()
+    ///             .smartstate(smartstates.nxt()),
+    ///     );
+    ///     stale = ui.stale_area();
+    /// }
+    /// ```
+    pub fn stale_area(&self) -> Option<Rectangle> {
+        self.stale_area
+    }
+
+    /// Returns the size `widget` would allocate, without drawing it, e.g. to allocate the space
+    /// of a wrapped widget that isn't drawn.
+    pub fn measure_widget(&mut self, widget: &mut impl TypedWidget<COL>) -> Size {
+        let outer = self.measure.replace(RowMeasure::default());
+        let _ = widget.draw_typed(self);
+        let measure = core::mem::replace(&mut self.measure, outer).unwrap_or_default();
+        Size::new(measure.total_width, measure.max_height)
+    }
+
+    /// Invalidates the area of the next widget allocation (see [Ui::invalidate]), so that the
+    /// next widget redraws even with an unchanged smartstate, e.g. after something else was
+    /// drawn in its place.
+    pub fn invalidate_next_allocation(&mut self) {
+        self.invalidate_next = true;
+    }
+
+    /// Returns whether (a part of) `area` was cleared this frame with [Ui::clear_area] (or
+    /// one of the methods using it), outside of a widget.
+    ///
+    /// Widgets with a smartstate redraw if their area was invalidated, as the clear erased
+    /// them.
+    pub fn invalidated(&self, area: Rectangle) -> bool {
+        self.invalidated_area
+            .is_some_and(|invalidated| !invalidated.intersection(&area).is_zero_sized())
+    }
+
+    /// Returns the bounding box of the areas cleared this frame with [Ui::clear_area] (or one
+    /// of the methods using it) over widgets added before, `None` if no widget was erased.
+    ///
+    /// The erased widgets are redrawn next frame, when the area is passed to
+    /// [Ui::redraw_erased] ([UiState] does this), or to
+    /// [SmartstateProvider::invalidate_area](crate::smartstate::SmartstateProvider::invalidate_area).
+    /// It is also returned as [FrameInfo::erased_area].
+    pub fn erased_area(&self) -> Option<Rectangle> {
+        self.erased_area
+    }
+
+    /// Redraws the widgets added from now on that were drawn within `area` in the last frame
+    /// (see [Drawn Areas](crate::smartstate::Smartstate#drawn-areas)), even if their
+    /// smartstates didn't change.
+    ///
+    /// Call it at the beginning of a frame with the [erased area](Ui::erased_area) of the
+    /// last frame, so that widgets cleared after they were added are drawn again.
+    pub fn redraw_erased(&mut self, area: Rectangle) {
+        self.erased_before = union(self.erased_before, area);
+    }
+
+    /// Returns the area redrawn this frame so far, including the enclosing [Ui]s
+    fn dirty_area(&self) -> Option<Rectangle> {
+        match self.outer_dirty {
+            Some(outer) => union(self.perf.dirty_area, outer),
+            None => self.perf.dirty_area,
+        }
+    }
+
+    /// Returns the bounding box of the widgets allocated this frame so far, including the
+    /// enclosing [Ui]s
+    fn allocated_area(&self) -> Option<Rectangle> {
+        match self.outer_used {
+            Some(outer) => union(self.used_area, outer),
+            None => self.used_area,
+        }
+    }
+
+    /// Returns whether interactions at `point` belong to an overlay, and not to the widgets
+    /// below it (see [Ui::draw_overlay])
+    fn is_covered(&self, point: Point) -> bool {
+        (self.covered.iter())
+            .chain(&self.painter.masks)
+            .any(|area| area.contains(point))
+    }
+
+    /// Returns the area overlays are limited to: the [Ui] this [Ui] is a part of, including its
+    /// window border padding.
+    pub fn overlay_bounds(&self) -> Rectangle {
+        self.screen
+    }
+
+    /// Draws an overlay into `area`, e.g. the list of a
+    /// [Dropdown](crate::dropdown::Dropdown), on top of the widgets around it.
+    ///
+    /// `f` draws the overlay's content like a sub-[Ui] with the bounds `area` (see
+    /// [Ui::unchecked_sub_ui]), limited to the [overlay bounds](Ui::overlay_bounds) rather than
+    /// to this [Ui]. It is called right away, and the area is reserved for the rest of the frame:
+    /// widgets added afterwards don't draw into it, as if the overlay was drawn after them, and
+    /// get no interactions within it. Widgets added before (and, from the next frame on, all
+    /// widgets) get no interactions within the area either.
+    ///
+    /// When the overlay is new, or something was drawn over its area this frame, the area is
+    /// cleared and the widgets of the overlay redraw (see [Ui::invalidated]). When it
+    /// disappears, the widgets below it are repainted by [Ui::begin_overlays] in the next frame.
+    ///
+    /// Overlays shouldn't overlap each other; the one drawn first stays on top.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error, [GuiError::TooManyOverlays] if there are
+    /// [MAX_OVERLAYS] overlays already.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::Overlays;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let show_hint = true;
+    /// let mut overlays = Overlays::new();
+    ///
+    /// loop {
+    ///     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ///     ui.begin_overlays(&mut overlays).unwrap();
+    ///
+    ///     let response = ui.add(Label::new("Battery: 12%"));
+    ///     if show_hint {
+    ///         let below = response.area().top_left + Point::new(20, 20);
+    ///         let area = Rectangle::new(below, Size::new(160, 30));
+    ///         ui.draw_overlay(area, |ui| {
+    ///             ui.add(Label::new("Charge soon!"));
+    ///             Ok(())
+    ///         })
+    ///         .ok();
+    ///     }
+    ///     // not drawn over the hint
+    ///     ui.add(Label::new("Temperature: 21°C"));
+    ///
+    ///     ui.end_overlays(&mut overlays);
+    /// }
+    /// ```
+    pub fn draw_overlay<F>(&mut self, area: Rectangle, f: F) -> GuiResult<()>
+    where
+        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
+    {
+        if self.measure.is_some() {
+            return Ok(());
+        }
+        if self.painter.masks.is_full() {
+            return Err(GuiError::TooManyOverlays);
+        }
+        let area = area.intersection(&self.screen);
+        if area.is_zero_sized() {
+            return Ok(());
+        }
+        let repaint = !self.covered.contains(&area)
+            || self
+                .dirty_area()
+                .is_some_and(|dirty| !dirty.intersection(&area).is_zero_sized());
+
+        // draw beyond this Ui, but only within the area
+        let clip = self.painter.clip.replace(area);
+        let in_overlay = core::mem::replace(&mut self.in_overlay, true);
+        let bounds = inner_bounds(area, self.style.spacing.window_border_padding);
+        let placer = self.child_placer(bounds.size);
+        let res = self.child_ui(bounds, placer, false, None, |ui| {
+            if repaint {
+                ui.clear_own_area(area)?;
+                ui.invalidate(area);
+            }
+            f(ui)
+        });
+        self.painter.clip = clip;
+        self.in_overlay = in_overlay;
+        self.painter.masks.push(area).ok();
+        res.map(|_| ())
+    }
+
+    /// Starts the overlays of a frame (see [Ui::draw_overlay]): repaints the areas of the
+    /// overlays that disappeared in the last frame, and keeps the interactions within the last
+    /// frame's overlays from the widgets below them.
+    ///
+    /// Call this at the beginning of the frame, after clearing the background (if at all), and
+    /// [Ui::end_overlays] after adding all widgets.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    pub fn begin_overlays(&mut self, overlays: &mut Overlays) -> GuiResult<()> {
+        self.covered = overlays.areas.clone();
+        for area in core::mem::take(&mut overlays.gone) {
+            if !self.cleared {
+                // the widgets below redraw, as the area is invalidated
+                self.clear_area(area)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ends the overlays of a frame (see [Ui::begin_overlays]), and remembers them in
+    /// `overlays`.
+    ///
+    /// If an overlay of the last frame is gone, a repaint is requested (see
+    /// [Ui::needs_repaint]) to erase it.
+    pub fn end_overlays(&mut self, overlays: &mut Overlays) {
+        let current = self.painter.masks.clone();
+        for area in &overlays.areas {
+            if !current.contains(area) && !overlays.gone.contains(area) {
+                overlays.gone.push(*area).ok();
+            }
+        }
+        if !overlays.gone.is_empty() {
+            self.request_repaint();
+        }
+        overlays.areas = current;
+    }
+
+    /// Returns the area of the [Ui] including its window border padding.
+    fn outer_bounds(&self) -> Rectangle {
+        Rectangle::new(
+            self.bounds.top_left.sub(Point::new(
+                self.style.spacing.window_border_padding.width as i32,
+                self.style.spacing.window_border_padding.height as i32,
+            )),
+            self.bounds
+                .size
+                .saturating_add(self.style.spacing.window_border_padding * 2),
+        )
+    }
+
+    /// Clears the current row in the [Ui] with the background color.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating whether the row was successfully cleared.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.clear_row().unwrap();
+    /// ```
+    pub fn clear_row(&mut self) -> GuiResult<()> {
+        let row_height = self.placer.row_height();
+        let row_rect = Rectangle::new(
+            Point::new(0, self.placer.pos.y),
+            Size::new(self.placer.bounds.width, row_height),
+        );
+        self.clear_area(row_rect)
+    }
+
+    /// Clears the current row from the current widget position to the end of the row.
+    ///
+    /// This is useful for removing any rendering remains of partially drawn widgets.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.clear_row_to_end().unwrap();
+    /// ```
+    pub fn clear_row_to_end(&mut self) -> GuiResult<()> {
+        let col_height = self.placer.row_height;
+        let col_rect = Rectangle::new(
+            // clear right to widget bounds
+            Point::new(
+                self.placer.pos.x + self.style.spacing.window_border_padding.width as i32,
+                self.placer.pos.y,
+            ),
+            Size::new(
+                (self.placer.bounds.width as i32 - self.placer.pos.x).max(0) as u32,
+                col_height,
+            ),
+        );
+        self.clear_area(col_rect)
+    }
+
+    /// Clears the [Ui] from the current placement position down to the bottom of the screen.
+    ///
+    /// **Warning:** This clears the entire screen area from the current row downwards.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.clear_to_bottom().unwrap();
+    /// ```
+    pub fn clear_to_bottom(&mut self) -> GuiResult<()> {
+        self.clear_area(Rectangle::new(
+            Point::new(0, self.placer.pos.y),
+            Size::new(
+                self.placer.bounds.width,
+                self.placer.bounds.height - self.placer.pos.y as u32,
+            ),
+        ))
+    }
+
+    /// Clears the entire background of the [Ui] with the background color defined in the style.
+    ///
+    /// This method updates the [Ui]'s cleared flag.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.clear_background().unwrap();
+    /// ```
+    pub fn clear_background(&mut self) -> GuiResult<()> {
+        self.cleared = true;
+
+        // clear background (of a sub-Ui only within its parent)
+        let real_bg = match self.painter.limit {
+            Some(limit) => self.outer_bounds().intersection(&limit),
+            None => self.outer_bounds(),
+        };
+
+        self.perf.add_dirty_area(real_bg);
+        if self.background_painter.is_some() {
+            return self.paint_background(real_bg);
+        }
+        real_bg
+            .draw_styled(
+                &PrimitiveStyleBuilder::new()
+                    .fill_color(self.style.background_color)
+                    .build(),
+                &mut CountingTarget::new(
+                    self.painter.target,
+                    &mut self.painter.pixels_drawn,
+                    self.painter.rotation,
+                )
+                .masked(&self.painter.masks),
+            )
+            .map_err(|_| GuiError::DrawError(Some("Couldn't clear GUI Background")))
+    }
+
+    /// Clears the background and invalidates the whole [Ui], so that all widgets added afterwards
+    /// redraw, regardless of their smartstates
+    fn redraw_all(&mut self) -> GuiResult<()> {
+        self.clear_background()?;
+        self.invalidate(self.outer_bounds());
+        Ok(())
+    }
+}
+
+// -- Drawing methods --
+impl<'a, COL, DRAW> Ui<'a, DRAW, COL>
+where
+    DRAW: DrawTarget<Color = COL>,
+    COL: PixelColor,
+{
+    /// Renders a widget into `buf` instead of the display, as a [Sprite] of `size` pixels.
+    ///
+    /// The widget is drawn at the top left of a separate [Ui] with this [Ui]'s current style
+    /// (without the window border padding), on a cleared background. That [Ui] has no
+    /// interaction and leaves this one alone, so the widget doesn't take this frame's clicks.
+    /// Pass it without a smartstate, or with one of its own: it is always drawn.
+    ///
+    /// Rendering a widget that appears many times (like a gauge) once and drawing the sprite
+    /// with the [Img](crate::img::Img) widget is a lot faster than drawing it from primitives.
+    ///
+    /// ## Returns
+    ///
+    /// The sprite, borrowing `buf`, or [GuiError::BoundsTooSmall] if `buf` is smaller than
+    /// `size`, or the error adding the widget returned (e.g. if it doesn't fit into `size`).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let mut buf = [Rgb565::BLACK; 60 * 20];
+    /// let sprite = ui.render_offscreen(Label::new("Sprite"), &mut buf, Size::new(60, 20))?;
+    /// ui.add(Img::new(&sprite));
+    /// ui.add(Img::new(&sprite));
+    /// # Ok::<(), kolibri_embedded_gui::ui::GuiError>(())
+    /// ```
+    pub fn render_offscreen<'b>(
+        &self,
+        widget: impl TypedWidget<COL>,
+        buf: &'b mut [COL],
+        size: Size,
+    ) -> GuiResult<Sprite<'b, COL>> {
+        if buf.len() < size.width as usize * size.height as usize {
+            return Err(GuiError::BoundsTooSmall {
+                needed: size,
+                available: Size::new(size.width, buf.len() as u32 / size.width.max(1)),
+            });
+        }
+
+        let mut style = self.style;
+        style.spacing.window_border_padding = Size::zero();
+        let mut framebuf = WidgetFramebuf::new(buf, size, Point::zero());
+        let mut ui = Ui::new(&mut framebuf, Rectangle::new(Point::zero(), size), style);
+        ui.clear_background()?;
+        ui.invalidate(ui.outer_bounds());
+        ui.try_add(widget)?;
+        drop(ui);
+
+        Ok(Sprite::new(framebuf.into_buf(), size))
+    }
+
+    /// Sets the internal drawing buffer for the [Ui].
+    ///
+    /// This buffer is used for optimized drawing and is optional.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let mut buffer = [Rgb565::BLACK; 10000];
+    /// ui.set_buffer(&mut buffer);
+    /// ```
+    pub fn set_buffer(&mut self, buffer: &'a mut [COL]) {
+        self.painter.set_buffer(buffer);
+    }
+
+    /// Begins the drawing process for a specified area in the [Ui].
+    ///
+    /// This initializes the drawing buffer (if set) and clears it with the background color.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the underlying [Painter] is already using its framebuffer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let draw_area = Rectangle::new(Point::new(30, 20), Size::new(100, 150));
+    /// ui.start_drawing(&draw_area);
+    /// ```
+    pub fn start_drawing(&mut self, area: &Rectangle) {
+        trace!(
+            "redraw {} (~{} px)",
+            area,
+            area.size.width * area.size.height
+        );
+        self.perf.widgets_redrawn += 1;
+        self.perf.add_dirty_area(self.painter.visible(*area));
+        // the buffer could only be cleared with the background color
+        if self.background_painter.is_none() {
+            self.painter.start_drawing(area);
+        }
+        self.painter.clear_buffer(self.style.background_color);
+    }
+
+    /// Clears the current drawing buffer with the specified color.
+    ///
+    /// ## Returns
+    ///
+    /// `true` if the buffer was successfully cleared, or `false` if no buffer is present.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let cleared = ui.clear_buffer_raw(Rgb565::BLACK);
+    /// println!("Buffer cleared: {}", cleared);
+    /// ```
+    pub fn clear_buffer_raw(&mut self, color: COL) -> bool {
+        self.painter.clear_buffer(color)
+    }
+
+    /// Finalizes the drawing process for the [Ui].
+    ///
+    /// This flushes any buffered drawing to the underlying draw target.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.finalize().unwrap();
+    /// ```
+    pub fn finalize(&mut self) -> GuiResult<()> {
+        self.painter.finalize()
+    }
+
+    /// Draws a [Drawable] item onto the [Ui].
+    ///
+    /// If a buffer is active, the item is drawn to the buffer; otherwise, it is drawn directly.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating whether the drawing operation was successful.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// # use embedded_graphics::primitives::PrimitiveStyle;
+    /// # let rectangle_shape = Rectangle::new(Point::new(50, 0), Size::new(200, 150));
+    /// # // Make it drawable with a filled red style
+    /// # let rectangle = rectangle_shape.into_styled(PrimitiveStyle::with_fill(Rgb565::RED));
+    /// ui.draw(&rectangle).unwrap();
+    /// ```
+    pub fn draw(&mut self, item: &impl Drawable<Color = COL>) -> GuiResult<()> {
+        self.painter.draw(item)
+    }
+
+    /// Draws a widget background `rect` with the given `style`, with the [Style]'s shadow behind it.
+    ///
+    /// `rect` should be the widget body, i.e. the allocated area without the shadow
+    /// (see [Style::without_shadow]), so that the shadow stays inside of the allocated area and is
+    /// cleaned up with it. If the style has no shadow color, only `rect` is drawn.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating whether the drawing operation was successful.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+    /// # use kolibri_embedded_gui::ui::rounded_rect_clamped;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.style_mut().shadow_color = Some(Rgb565::BLACK);
+    /// let size = Size::new(60, 20) + ui.style().shadow_size();
+    /// let area = ui.allocate_space(size).unwrap().area;
+    /// let body = ui.style().without_shadow(area);
+    /// let fill = PrimitiveStyle::with_fill(ui.style().item_background_color.color());
+    /// ui.draw_background(rounded_rect_clamped(body, 4), fill).unwrap();
+    /// ```
+    pub fn draw_background(
+        &mut self,
+        rect: RoundedRectangle,
+        style: PrimitiveStyle<COL>,
+    ) -> GuiResult<()> {
+        self.draw_shadow(rect)?;
+        self.painter.draw(&rect.into_styled(style))
+    }
+
+    /// Like [Ui::draw_background], but fills `rect` with `fill` (e.g. a gradient, see
+    /// [Style::item_background_color]) instead of the fill color of `style`. Only the stroke of
+    /// `style` is drawn, on top of the fill.
+    ///
+    /// Gradients are clipped to the rounded corners of `rect`.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating whether the drawing operation was successful.
+    pub fn draw_background_fill(
+        &mut self,
+        rect: RoundedRectangle,
+        fill: Fill<COL>,
+        style: PrimitiveStyle<COL>,
+    ) -> GuiResult<()>
+    where
+        COL: Into<Rgb888> + From<Rgb888>,
+    {
+        self.draw_shadow(rect)?;
+        self.painter.draw(&FilledShape { shape: rect, fill })?;
+        let mut stroke = style;
+        stroke.fill_color = None;
+        if stroke.stroke_width > 0 && stroke.stroke_color.is_some() {
+            self.painter.draw(&rect.into_styled(stroke))?;
+        }
+        Ok(())
+    }
+
+    /// Draws the [Style]'s shadow behind the widget background `rect`, if it has one.
+    fn draw_shadow(&mut self, rect: RoundedRectangle) -> GuiResult<()> {
+        if let Some(shadow_color) = self.style.shadow_color {
+            let offset = self.style.shadow_offset;
+            let shadow = rect.translate(Point::new(offset.width as i32, offset.height as i32));
+            self.painter
+                .draw(&shadow.into_styled(PrimitiveStyle::with_fill(shadow_color)))?;
+        }
+        Ok(())
+    }
+}
+
+// -- Sub-[Ui] methods --
+impl<COL, DRAW> Ui<'_, DRAW, COL>
+where
+    DRAW: DrawTarget<Color = COL>,
+    COL: PixelColor,
+{
+    /// Creates a sub-[Ui] with the given bounds without performing extra bounds checks.
+    ///
+    /// This sub-[Ui] is useful for drawing to a specific area with its own layout and style.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// let sub_bounds = Rectangle::new(Point::new(50, 0), Size::new(200, 150));
+    /// ui.unchecked_sub_ui(sub_bounds, |sub_ui| {
+    ///     sub_ui.add(widget);
+    ///     Ok(())
+    /// }).unwrap();
+    /// ```
+    pub fn unchecked_sub_ui<F>(&mut self, bounds: Rectangle, f: F) -> GuiResult<()>
+    where
+        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
+    {
+        let bounds = inner_bounds(bounds, self.style.spacing.window_border_padding);
+        let placer = self.child_placer(bounds.size);
+        self.child_ui(bounds, placer, false, None, f)?;
+        Ok(())
+    }
+
+    /// Returns the placer of a sub-[Ui] of the given `size`, with the overflow policy and the row
+    /// settings of this [Ui]
+    fn child_placer(&self, size: Size) -> Placer {
+        let mut placer = Placer::new(
+            size,
+            self.placer.overflow,
+            Align(HorizontalAlign::Left, self.placer.align.1),
+        );
+        placer.fixed_row_height = self.placer.fixed_row_height;
+        placer
+    }
+
+    /// Runs `f` in a sub-[Ui] within `bounds` (without padding), laid out by `placer`.
+    ///
+    /// ## Returns
+    ///
+    /// The widget sizes measured by the sub-[Ui], if it was created in measuring mode (with
+    /// `measure` set).
+    fn child_ui<F>(
+        &mut self,
+        bounds: Rectangle,
+        placer: Placer,
+        horizontal: bool,
+        measure: Option<RowMeasure>,
+        f: F,
+    ) -> GuiResult<Option<RowMeasure>>
+    where
+        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
+    {
+        let mut measured = None;
+        let mut masks = self.painter.masks.clone();
+        let outer_dirty = self.dirty_area();
+        let outer_used = self.allocated_area();
+        self.painter.with_subpainter(|painter| {
+            let mut sub_ui = Ui {
+                painter,
+                bounds,
+                style: self.style,
+                interact: self.interact,
+                source: self.source,
+                origin: self.origin,
+                placer,
+                cleared: false,
+                debug_color: self.debug_color,
+                debug_overlay: self.debug_overlay,
+                now: self.now,
+                dt: self.dt,
+                wrap_state: 0,
+                min_width: None,
+                measure,
+                horizontal,
+                invalidate_next: false,
+                drawn_before: None,
+                drawn_shift: self.drawn_shift,
+                perf: PerfStats::default(),
+                keys: core::mem::take(&mut self.keys),
+                used_area: None,
+                invalidated_area: self.invalidated_area,
+                erased_area: self.erased_area,
+                erased_before: self.erased_before,
+                widget_depth: self.widget_depth,
+                style_stack: heapless::Vec::new(),
+                style_key: self.style_key,
+                focus: self.focus,
+                focus_activated: self.focus_activated,
+                allocations: self.allocations,
+                pointer_kind: self.pointer_kind,
+                repaint: false,
+                stale_area: self.stale_area,
+                background_painter: self.background_painter,
+                area_mover: self.area_mover,
+                scroll_assist: self.scroll_assist,
+                feedback_handler: self.feedback_handler,
+                screen: self.screen,
+                covered: self.covered.clone(),
+                in_overlay: self.in_overlay,
+                outer_dirty,
+                outer_used,
+                pointer: self.pointer,
+            };
+            sub_ui.update_clip();
+            let res = (f)(&mut sub_ui);
+            self.perf.merge(sub_ui.perf());
+            self.keys = sub_ui.keys;
+            if let Some(area) = sub_ui.used_area {
+                self.used_area = union(self.used_area, area);
+            }
+            self.invalidated_area = sub_ui.invalidated_area;
+            self.erased_area = sub_ui.erased_area;
+            self.allocations = sub_ui.allocations;
+            self.repaint |= sub_ui.repaint;
+            self.stale_area = sub_ui.stale_area;
+            measured = sub_ui.measure;
+            masks = sub_ui.painter.masks;
+            res
+        })?;
+        self.painter.masks = masks;
+
+        Ok(measured)
+    }
+
+    /// Creates a sub-[Ui] that shares the same bounds as the parent [Ui].
+    ///
+    /// Changes to the sub-[Ui]'s layout are reflected in the parent.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.sub_ui(|sub_ui| {
+    ///     sub_ui.add(widget);
+    ///     Ok(())
+    /// }).unwrap();
+    /// ```
+    pub fn sub_ui<F>(&mut self, f: F) -> GuiResult<()>
+    where
+        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
+    {
+        let mut masks = self.painter.masks.clone();
+        let outer_dirty = self.dirty_area();
+        self.painter.with_subpainter(|painter| {
+            let mut sub_ui = Ui {
+                painter,
+                bounds: self.bounds,
+                style: self.style,
+                interact: self.interact,
+                source: self.source,
+                origin: self.origin,
+                placer: self.placer.clone(),
+                cleared: false,
+                debug_color: self.debug_color,
+                debug_overlay: self.debug_overlay,
+                now: self.now,
+                dt: self.dt,
+                wrap_state: self.wrap_state,
+                min_width: self.min_width,
+                measure: self.measure,
+                horizontal: self.horizontal,
+                invalidate_next: self.invalidate_next,
+                drawn_before: self.drawn_before,
+                drawn_shift: self.drawn_shift,
+                perf: PerfStats::default(),
+                keys: core::mem::take(&mut self.keys),
+                used_area: self.used_area,
+                invalidated_area: self.invalidated_area,
+                erased_area: self.erased_area,
+                erased_before: self.erased_before,
+                widget_depth: self.widget_depth,
+                style_stack: heapless::Vec::new(),
+                style_key: self.style_key,
+                focus: self.focus,
+                focus_activated: self.focus_activated,
+                allocations: self.allocations,
+                pointer_kind: self.pointer_kind,
+                repaint: false,
+                stale_area: self.stale_area,
+                background_painter: self.background_painter,
+                area_mover: self.area_mover,
+                scroll_assist: self.scroll_assist,
+                feedback_handler: self.feedback_handler,
+                screen: self.screen,
+                covered: self.covered.clone(),
+                in_overlay: self.in_overlay,
+                outer_dirty,
+                outer_used: self.outer_used,
+                pointer: self.pointer,
+            };
+            let res = (f)(&mut sub_ui);
+            self.perf.merge(sub_ui.perf());
+            self.keys = sub_ui.keys;
+            self.used_area = sub_ui.used_area;
+            self.invalidated_area = sub_ui.invalidated_area;
+            self.erased_area = sub_ui.erased_area;
+            self.allocations = sub_ui.allocations;
+            self.repaint |= sub_ui.repaint;
+            self.stale_area = sub_ui.stale_area;
+            self.placer = sub_ui.placer;
+            self.wrap_state = sub_ui.wrap_state;
+            self.measure = sub_ui.measure;
+            self.invalidate_next = sub_ui.invalidate_next;
+            self.drawn_before = sub_ui.drawn_before;
+            masks = sub_ui.painter.masks;
+            res
+        })?;
+        self.painter.masks = masks;
+
+        Ok(())
+    }
+
+    /// Adds a group of widgets laid out left to right, as one widget of the [Ui].
+    ///
+    /// In the closure, [Ui::add] places the widgets next to each other (separated by the item
+    /// spacing) instead of starting new rows. The group is as large as its widgets and takes
+    /// part in the [Ui]'s layout like a single widget: it is wrapped, aligned (see
+    /// [Ui::set_horizontal_alignment]) and followed by a new row like one added with
+    /// [Ui::add] (or by the item spacing in an enclosing horizontal group).
+    ///
+    /// Groups can be nested, e.g. a [Ui::vertical] group within a horizontal one.
+    ///
+    /// The closure is called twice: first to measure the widgets, then to draw them, like with
+    /// [Ui::equal_width_row]. So, unlike in a row of the [Ui], the widgets of a horizontal group
+    /// are aligned (see [Ui::set_row_alignment]) to the tallest one, wherever it is.
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] for the area of the whole group.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let mut volume = 5;
+    /// ui.horizontal(|ui| {
+    ///     ui.add(Label::new("Volume"));
+    ///     // two buttons, stacked on the right of the label
+    ///     ui.vertical(|ui| {
+    ///         if ui.add(Button::new("+")).clicked() {
+    ///             volume += 1;
+    ///         }
+    ///         if ui.add(Button::new("-")).clicked() {
+    ///             volume -= 1;
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn horizontal<F>(&mut self, f: F) -> Response
+    where
+        F: FnMut(&mut Ui<DRAW, COL>),
+    {
+        self.group(true, f)
+    }
+
+    /// Adds a group of widgets laid out top to bottom, as one widget of the [Ui].
+    ///
+    /// In the closure, [Ui::add] starts a new row after every widget. Otherwise, this works like
+    /// [Ui::horizontal]: the group is as large as its widgets, and is placed like a single
+    /// widget, e.g. next to others in a horizontal group.
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] for the area of the whole group.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// // two columns of labels
+    /// ui.add_horizontal(Label::new("Speed"));
+    /// ui.vertical(|ui| {
+    ///     ui.add(Label::new("12 km/h"));
+    ///     ui.add(Label::new("3.3 m/s"));
+    /// });
+    /// ```
+    pub fn vertical<F>(&mut self, f: F) -> Response
+    where
+        F: FnMut(&mut Ui<DRAW, COL>),
+    {
+        self.group(false, f)
+    }
+
+    /// Adds a scroll area `height` pixels high (and as wide as the rest of the row), with the
+    /// widgets added in the closure laid out top to bottom in it, scrolled up by the offset in
+    /// `state`.
+    ///
+    /// Only the part of the content within the area is drawn, and only widgets within it get
+    /// pointer interactions. When the offset changes, the drawn content is moved with
+    /// [Ui::move_area] (e.g. with the display's [hardware scrolling](Ui::set_scroll_assist)),
+    /// and only the rows the move uncovers are cleared and redrawn. If it can't be moved, the
+    /// whole area is cleared and the content is redrawn.
+    ///
+    /// The area scrolls with [Interaction::Scroll], by [Spacing::scroll_step](crate::style::Spacing::scroll_step)
+    /// pixels per detent, if
+    /// - the last pointer interaction (e.g. a hover with a mouse) was within the area,
+    /// - or the focused widget is in the area (see [Ui::set_focus]),
+    /// - or, without a pointer or focus, it's the first scroll area.
+    ///
+    /// The scroll is consumed by the area, so the scroll areas after it don't scroll, too. The
+    /// new offset is drawn in the next frame, and a repaint is requested for it (see
+    /// [Ui::needs_repaint]). The content isn't scrolled by dragging it.
+    ///
+    /// ## Returns
+    ///
+    /// A [Response] for the area, [changed](Response::changed) if it was scrolled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::SimulatorDisplay;
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use kolibri_embedded_gui::ui::ScrollState;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let wheel_detents = 0;
+    /// let mut scroll = ScrollState::new();
+    ///
+    /// loop {
+    ///     let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    ///     if wheel_detents != 0 {
+    ///         ui.interact(Interaction::Scroll(wheel_detents));
+    ///     }
+    ///     ui.add(Label::new("Log"));
+    ///     ui.scroll_area(120, &mut scroll, |ui| {
+    ///         for line in ["boot", "wifi up", "sync done"] {
+    ///             ui.add(Label::new(line));
+    ///         }
+    ///     });
+    /// }
+    /// ```
+    pub fn scroll_area<F>(&mut self, height: u32, state: &mut ScrollState, f: F) -> Response
+    where
+        F: FnOnce(&mut Ui<DRAW, COL>),
+    {
+        let resp = self
+            .scroll_area_raw(height, state, f)
+            .unwrap_or_else(Response::from_error);
+        self.end_item();
+        resp
+    }
+
+    fn scroll_area_raw<F>(
+        &mut self,
+        height: u32,
+        state: &mut ScrollState,
+        f: F,
+    ) -> GuiResult<Response>
+    where
+        F: FnOnce(&mut Ui<DRAW, COL>),
+    {
+        let row_height = self
+            .placer
+            .fixed_row_height
+            .unwrap_or(self.style.default_widget_height);
+        let iresponse = self.allocate_space(Size::new(self.remaining_width(), height))?;
+        let viewport = iresponse.area;
+
+        // remember where the pointer is, as scrolls have no point
+        let pointed = self.check_interact(viewport);
+        if pointed != Interaction::None {
+            state.pointer_inside = Some(true);
+        } else if !matches!(self.interact, Interaction::ActivatedByFocus(_))
+            && self.interact.get_point().is_some()
+        {
+            state.pointer_inside = Some(false);
+        }
+
+        // a moved content is moved along if possible, otherwise it is drawn anew
+        let max_offset = state.content_height.saturating_sub(viewport.size.height);
+        state.offset = state.offset.min(max_offset);
+        if state.drawn != Some(state.offset) {
+            let height = viewport.size.height as i32;
+            let by = state.drawn.map(|drawn| drawn as i32 - state.offset as i32);
+            let exposed = match by {
+                Some(by) if by.abs() < height && self.move_area(viewport, Point::new(0, by)) => {
+                    // the rows the content moved away from
+                    let top = if by > 0 { 0 } else { height + by };
+                    Rectangle::new(
+                        viewport.top_left + Point::new(0, top),
+                        Size::new(viewport.size.width, by.unsigned_abs()),
+                    )
+                }
+                _ => viewport,
+            };
+            if !self.cleared {
+                self.clear_own_area(exposed)?;
+            }
+            self.invalidate(exposed);
+        }
+        // the content moved along, so it isn't redrawn for moving (see Ui::set_overflow_policy)
+        let shift = Point::new(
+            0,
+            state
+                .drawn
+                .map_or(0, |drawn| drawn as i32 - state.offset as i32),
+        );
+        state.drawn = Some(state.offset);
+
+        // only the part of the content within the area is drawn and interacted with
+        let content = Rectangle::new(
+            viewport.top_left - Point::new(0, state.offset as i32),
+            Size::new(viewport.size.width, MAX_CONTENT_HEIGHT),
+        );
+        let mut placer = self.child_placer(content.size);
+        placer.row_height = row_height;
+        let (clip, interact, used_area) = (self.painter.clip, self.interact, self.used_area);
+        let drawn_shift = self.drawn_shift;
+        self.drawn_shift += shift;
+        let first_allocation = self.allocations;
+        self.painter.clip = Some(clip.map_or(viewport, |clip| clip.intersection(&viewport)));
+        // the child maps the raw interaction itself, it is only dropped outside of the viewport
+        if pointed == Interaction::None {
+            self.interact = Interaction::None;
+        }
+        let mut bottom = None;
+        let res = self.child_ui(content, placer, false, None, |ui| {
+            f(ui);
+            bottom = ui.used_area.and_then(|area| area.bottom_right());
+            Ok(())
+        });
+        (self.painter.clip, self.interact, self.used_area) = (clip, interact, used_area);
+        self.drawn_shift = drawn_shift;
+        res?;
+        state.content_height =
+            bottom.map_or(0, |bottom| (bottom.y + 1 - content.top_left.y) as u32);
+
+        // scroll with a mouse wheel or an encoder
+        let mut scrolled = false;
+        if let Interaction::Scroll(detents) = self.interact {
+            let focused = self
+                .focus
+                .map(|focus| (first_allocation..self.allocations).contains(&focus));
+            let target = focused == Some(true)
+                || state.pointer_inside == Some(true)
+                || (focused.is_none() && state.pointer_inside.is_none());
+            if target {
+                self.interact = Interaction::None;
+                let max_offset = state.content_height.saturating_sub(viewport.size.height);
+                let step = i64::from(detents) * i64::from(self.style.spacing.scroll_step);
+                let offset = (i64::from(state.offset) + step).clamp(0, i64::from(max_offset));
+                scrolled = offset as u32 != state.offset;
+                state.offset = offset as u32;
+                if scrolled {
+                    self.request_repaint();
+                }
+            }
+        }
+
+        Ok(Response::new(iresponse).set_changed(scrolled))
+    }
+
+    fn group<F>(&mut self, horizontal: bool, mut f: F) -> Response
+    where
+        F: FnMut(&mut Ui<DRAW, COL>),
+    {
+        let resp = self
+            .group_raw(horizontal, &mut f)
+            .map(Response::new)
+            .unwrap_or_else(Response::from_error);
+        self.end_item();
+        resp
+    }
+
+    fn group_raw<F>(&mut self, horizontal: bool, f: &mut F) -> GuiResult<InternalResponse>
+    where
+        F: FnMut(&mut Ui<DRAW, COL>),
+    {
+        let row_height = self
+            .placer
+            .fixed_row_height
+            .unwrap_or(self.style.default_widget_height);
+        let mut run = |ui: &mut Self, bounds: Rectangle, row_height, measure| {
+            let mut placer = ui.child_placer(bounds.size);
+            placer.row_height = row_height;
+            ui.child_ui(bounds, placer, horizontal, measure, |ui| {
+                f(ui);
+                Ok(())
+            })
+        };
+
+        // measuring pass in the rest of the row: nothing is allocated or drawn
+        let available = Rectangle::new(
+            self.bounds.top_left + self.placer.pos,
+            self.placer.space_available(),
+        );
+        let (allocations, used_area) = (self.allocations, self.used_area);
+        let measure =
+            run(self, available, row_height, Some(RowMeasure::default()))?.unwrap_or_default();
+        (self.allocations, self.used_area) = (allocations, used_area);
+
+        // drawing pass in the group's area
+        let size = measure.size(horizontal, self.style.spacing.item_spacing);
+        let iresponse = self.allocate_space(size)?;
+        // a horizontal group is one row, aligned to its tallest widget from the start
+        let row_height = match horizontal {
+            true => max(row_height, measure.max_height),
+            false => row_height,
+        };
+        run(self, iresponse.area, row_height, None)?;
+        Ok(iresponse)
+    }
+
+    /// Adds a row of widgets that all share the width of the widest one (e.g. "OK" / "Cancel" / "Apply").
+    ///
+    /// The closure is called twice: first to measure the widgets, then to draw them.
+    /// While measuring, nothing is drawn or allocated and every widget returns a
+    /// [GuiError::Measuring] error (so e.g. `clicked()` is always `false`). While drawing, every
+    /// widget is allocated at least the width of the widest one. Widgets center their content in
+    /// the wider space.
+    ///
+    /// Anything else the closure does happens twice per frame, so it should only act on the
+    /// responses of the widgets, like `ok` below, and not e.g. count frames or log.
+    ///
+    /// Use [Ui::add_horizontal] in the closure so that the widgets are separated by the item spacing.
+    /// A new row is started afterwards.
+    ///
+    /// ## Returns
+    ///
+    /// The shared width of the widgets.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// let mut ok = false;
+    /// ui.equal_width_row(|ui| {
+    ///     ok |= ui.add_horizontal(Button::new("OK")).clicked();
+    ///     ui.add_horizontal(Button::new("Cancel"));
+    ///     ui.add_horizontal(Button::new("Apply"));
+    /// });
+    /// ```
+    pub fn equal_width_row<F>(&mut self, f: F) -> u32
+    where
+        F: FnMut(&mut Ui<DRAW, COL>),
     {
-        let bounds = Rectangle::new(
-            bounds.top_left.add(Point::new(
-                self.style.spacing.window_border_padding.height as i32,
-                self.style.spacing.window_border_padding.width as i32,
-            )),
-            bounds
-                .size
-                .saturating_sub(self.style.spacing.window_border_padding * 2),
+        self.equal_width_row_impl(false, f)
+    }
+
+    /// Like [Ui::equal_width_row], but the widgets are widened to fill the remaining width of the row.
+    ///
+    /// The widgets are never made narrower than the widest one, so if they don't fit,
+    /// the [Overflow] policy applies as usual.
+    ///
+    /// ## Returns
+    ///
+    /// The shared width of the widgets.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// ui.justified_row(|ui| {
+    ///     ui.add_horizontal(Button::new("Back"));
+    ///     ui.add_horizontal(Button::new("Next"));
+    /// });
+    /// ```
+    pub fn justified_row<F>(&mut self, f: F) -> u32
+    where
+        F: FnMut(&mut Ui<DRAW, COL>),
+    {
+        self.equal_width_row_impl(true, f)
+    }
+
+    fn equal_width_row_impl<F>(&mut self, justify: bool, mut f: F) -> u32
+    where
+        F: FnMut(&mut Ui<DRAW, COL>),
+    {
+        // measuring pass: nothing is allocated or drawn
+        let placer = self.placer.clone();
+        let wrap_state = self.wrap_state;
+        self.measure = Some(RowMeasure::default());
+        f(self);
+        let measure = self.measure.take().unwrap_or_default();
+        self.placer = placer;
+        self.wrap_state = wrap_state;
+
+        let mut width = measure.max_width;
+        if justify && measure.count > 0 {
+            let spacing = self.style.spacing.item_spacing.width * (measure.count - 1);
+            let fill = self.remaining_width().saturating_sub(spacing) / measure.count;
+            width = max(width, fill);
+        }
+
+        // drawing pass
+        let min_width = self.min_width.replace(width);
+        f(self);
+        self.min_width = min_width;
+        self.new_row();
+        width
+    }
+
+    /// Creates a right-side panel sub-[Ui] with the specified width.
+    ///
+    /// If `allow_smaller` is false, an error is returned if there is insufficient space.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.right_panel_ui(100, false, |sub_ui| {
+    ///     sub_ui.add(widget);
+    ///     Ok(())
+    /// }).unwrap();
+    /// ```
+    pub fn right_panel_ui<F>(&mut self, width: u32, allow_smaller: bool, f: F) -> GuiResult<()>
+    where
+        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
+    {
+        let bounds = self.placer.bounds;
+        let y = self.placer.pos.y as u32;
+        let max_width = bounds.width - self.placer.pos.x as u32;
+        let max_height = bounds.height - y;
+
+        if width > max_width && !allow_smaller {
+            return Err(GuiError::BoundsTooSmall {
+                needed: Size::new(width, max_height),
+                available: Size::new(max_width, max_height),
+            });
+        }
+
+        self.placer.bounds.width -= min(width, max_width);
+
+        let area = Rectangle::new(
+            Point::new((bounds.width - min(width, max_width)) as i32, y as i32),
+            Size::new(
+                bounds.width - (bounds.width - min(width, max_width)),
+                max_height,
+            ),
+        );
+
+        self.unchecked_sub_ui(area, f)
+    }
+
+    /// Creates a centered sub-[Ui] panel with the specified width and height.
+    ///
+    /// The panel is centered within the current bounds. An error is returned if the dimensions exceed the available space.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.central_centered_panel_ui(200, 150, |sub_ui| {
+    ///     sub_ui.add(widget);
+    ///     Ok(())
+    /// }).unwrap();
+    /// ```
+    pub fn central_centered_panel_ui<F>(&mut self, width: u32, height: u32, f: F) -> GuiResult<()>
+    where
+        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
+    {
+        let bounds = self.placer.bounds;
+        let max_width = bounds.width;
+        let max_height = bounds.height;
+
+        if width > max_width || height > max_height {
+            return Err(GuiError::BoundsTooSmall {
+                needed: Size::new(width, height),
+                available: bounds,
+            });
+        }
+
+        self.placer.bounds.width -= min(width, max_width);
+        self.placer.bounds.height -= min(height, max_height);
+
+        let area = Rectangle::new(
+            Point::new(
+                ((bounds.width - width) / 2) as i32,
+                ((bounds.height - height) / 2) as i32,
+            ),
+            Size::new(width, height),
+        );
+
+        self.unchecked_sub_ui(area, f)
+    }
+}
+
+// -- Debug drawing methods --
+impl<COL, DRAW> Ui<'_, DRAW, COL>
+where
+    DRAW: DrawTarget<Color = COL>,
+    COL: PixelColor,
+{
+    /// Draws a debug outline around the [Ui]'s bounds using the specified color.
+    ///
+    /// ## Returns
+    ///
+    /// A [GuiResult] indicating success or error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.draw_bounds_debug(Rgb565::RED).unwrap();
+    /// ```
+    pub fn draw_bounds_debug(&mut self, color: COL) -> GuiResult<()> {
+        let bounds = self.bounds;
+        bounds
+            .draw_styled(
+                &PrimitiveStyleBuilder::new()
+                    .stroke_color(color)
+                    .stroke_width(1)
+                    .build(),
+                &mut self.painter,
+            )
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw bounds")))
+    }
+
+    /// Enables debug drawing of widget bounds in the [Ui] using the specified color.
+    ///
+    /// Once set, all added widgets will have their bounds outlined in this color for debugging.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use embedded_graphics::primitives::Rectangle;
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let output_settings = OutputSettingsBuilder::new().build();
+    /// # let mut window = Window::new("Kolibri Example", &output_settings);
+    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+    /// # let mut widget = Label::new("Hi");
+    /// ui.draw_widget_bounds_debug(Rgb565::GREEN);
+    /// ```
+    pub fn draw_widget_bounds_debug(&mut self, color: COL) {
+        self.debug_color = Some(color);
+    }
+
+    /// Sets what the on-screen debug overlay shows for widgets added afterwards.
+    ///
+    /// The overlay is drawn after each widget, on top of it:
+    /// - [DebugOverlay::Bounds] outlines the allocated area of every widget, in the color set with
+    ///   [Ui::draw_widget_bounds_debug] or the style's `highlight_border_color`.
+    /// - [DebugOverlay::Redraws] outlines widgets that actually redrew this frame in the style's
+    ///   `secondary_color`. The outline stays until the widget redraws again.
+    /// - [DebugOverlay::Both] does both, so redrawn widgets flash in the redraw color for one frame.
+    ///
+    /// The overlay isn't a widget redraw, so it's not part of [PerfStats::widgets_redrawn] and
+    /// [PerfStats::dirty_area] (its pixels are counted in [PerfStats::pixels_drawn], though).
+    ///
+    /// Turning the overlay off removes the outlines on screen: the background is cleared, and all
+    /// widgets added afterwards redraw (see [Ui::invalidated]). As a [Ui] only lives for a frame,
+    /// it only knows that the overlay was on in the last frame if it was created by a [UiState],
+    /// which retains the overlay. Without one, clear the background and invalidate all
+    /// smartstates yourself in the frame after turning the overlay off.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use embedded_graphics::pixelcolor::Rgb565;
+    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
+    /// # use kolibri_embedded_gui::prelude::*;
+    /// # use embedded_graphics::prelude::*;
+    /// # use kolibri_embedded_gui::ui::{DebugOverlay, UiState};
+    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+    /// # let mut smartstates = SmartstateProvider::<20>::new();
+    /// # let debug_key_pressed = || false;
+    /// let mut state = UiState::new(medsize_rgb565_style());
+    /// let mut overlay = DebugOverlay::Both;
+    /// loop {
+    ///     if debug_key_pressed() {
+    ///         overlay = DebugOverlay::Off;
+    ///     }
+    ///     state.frame(&mut display, Interaction::None, 0, |ui| {
+    ///         // turning it off redraws everything without the outlines
+    ///         ui.set_debug_overlay(overlay);
+    ///         smartstates.restart_counter();
+    ///         ui.add(Button::new("OK").smartstate(smartstates.nxt()));
+    ///     });
+    /// #   break;
+    /// }
+    /// ```
+    pub fn set_debug_overlay(&mut self, overlay: DebugOverlay) {
+        if overlay == DebugOverlay::Off && self.debug_overlay != DebugOverlay::Off {
+            // without the background, drawing the widgets fails anyway
+            self.redraw_all().ok();
+        }
+        self.debug_overlay = overlay;
+    }
+
+    /// Returns what the debug overlay shows (see [Ui::set_debug_overlay]).
+    pub fn debug_overlay(&self) -> DebugOverlay {
+        self.debug_overlay
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::button::Button;
+    #[cfg(feature = "controls")]
+    use crate::slider::Slider;
+    use crate::smartstate::SmartstateProvider;
+    use crate::spacer::Spacer;
+    use crate::style::{medsize_rgb565_style, Spacing};
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::{BinaryColor, Rgb565};
+
+    #[test]
+    fn layout_errors_carry_sizes() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        // 64x64 display, minus 3px window border padding on each side
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+
+        assert_eq!(
+            ui.add_raw(Spacer::new(Size::new(100, 10))).err(),
+            Some(GuiError::NoHorizontalSpace {
+                needed: 100,
+                available: 58
+            })
+        );
+        assert_eq!(
+            ui.add_raw(Spacer::new(Size::new(10, 100))).err(),
+            Some(GuiError::NoVerticalSpace {
+                needed: 100,
+                available: 58
+            })
+        );
+
+        ui.add_raw(Spacer::new(Size::new(40, 10))).unwrap();
+        assert_eq!(
+            ui.allocate_space_no_wrap(Size::new(20, 10)).err(),
+            Some(GuiError::NoHorizontalSpace {
+                needed: 20,
+                available: 18
+            })
+        );
+    }
+
+    /// Draw target that fails once a given number of pixels has been drawn,
+    /// like a display on a flaky bus.
+    struct FailingDisplay {
+        pixels_left: usize,
+    }
+
+    impl OriginDimensions for FailingDisplay {
+        fn size(&self) -> Size {
+            Size::new(320, 240)
+        }
+    }
+
+    impl DrawTarget for FailingDisplay {
+        type Color = Rgb565;
+        type Error = ();
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            for _ in pixels {
+                if self.pixels_left == 0 {
+                    return Err(());
+                }
+                self.pixels_left -= 1;
+            }
+            Ok(())
+        }
+    }
+
+    fn working_display() -> FailingDisplay {
+        FailingDisplay {
+            pixels_left: usize::MAX,
+        }
+    }
+
+    // 45 characters of a 9px wide font: 405px wide, more than the 314px wide Ui
+    const WIDE_TEXT: &str = "This label is too wide for a 320px wide Ui...";
+
+    #[test]
+    fn try_add_returns_layout_errors() {
+        use crate::label::Label;
+
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        assert_eq!(
+            ui.try_add(Label::new(WIDE_TEXT)).err(),
+            Some(GuiError::NoHorizontalSpace {
+                needed: 405,
+                available: 314
+            })
+        );
+        assert!(ui.try_add(Label::new("fits")).is_ok());
+    }
+
+    #[test]
+    fn add_reports_layout_errors_in_response() {
+        use crate::label::Label;
+
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let response = ui.add(Label::new(WIDE_TEXT));
+        assert_eq!(
+            response.error(),
+            Some(GuiError::NoHorizontalSpace {
+                needed: 405,
+                available: 314
+            })
+        );
+        assert_eq!(ui.add(Label::new("fits")).error(), None);
+    }
+
+    #[test]
+    fn add_or_skip_skips_widgets_that_dont_fit() {
+        use crate::label::Label;
+
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let response = ui.add_or_skip(Label::new(WIDE_TEXT));
+        assert_eq!(response.error(), None);
+        assert_eq!(response.internal.area.size, Size::zero());
+
+        // doesn't wrap a trailing widget into the next row either
+        ui.add_horizontal(Label::new("A row that is almost full...."));
+        let response = ui.add_or_skip(Label::new("trailing"));
+        assert_eq!(response.error(), None);
+        assert_eq!(response.internal.area.size, Size::zero());
+
+        assert!(ui.add_or_skip(Label::new("fits")).internal.area.size.width > 0);
+    }
+
+    #[cfg(all(feature = "controls", feature = "icon-widgets"))]
+    #[test]
+    fn draw_target_errors_surface_from_add() {
+        use crate::button::Button;
+        use crate::iconbutton::IconButton;
+        use crate::slider::Slider;
+        use embedded_iconoir::size24px;
+
+        // fails while drawing the background, after the first 100 pixels
+        let mut display = FailingDisplay { pixels_left: 100 };
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        assert!(matches!(
+            ui.add(Button::new("Button")).error(),
+            Some(GuiError::DrawError(_))
+        ));
+        assert!(matches!(
+            ui.add(IconButton::new(size24px::actions::AddCircle).label("Icon"))
+                .error(),
+            Some(GuiError::DrawError(_))
+        ));
+        let mut value = 5;
+        assert!(matches!(
+            ui.add(Slider::new(&mut value, 0..=10)).error(),
+            Some(GuiError::DrawError(_))
+        ));
+
+        // a working display doesn't produce errors
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        assert_eq!(ui.add(Button::new("Button")).error(), None);
+    }
+
+    #[test]
+    fn draw_target_errors_surface_through_framebuffer() {
+        use crate::button::Button;
+
+        let mut buffer = [Rgb565::BLACK; 100 * 100];
+        let mut display = FailingDisplay { pixels_left: 0 };
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_buffer(&mut buffer);
+        assert!(matches!(
+            ui.add(Button::new("Button")).error(),
+            Some(GuiError::DrawError(_))
+        ));
+    }
+
+    #[test]
+    fn overflow_wrap_starts_new_row() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        assert_eq!(ui.overflow_policy(), Overflow::Wrap);
+        ui.set_row_alignment(VerticalAlign::Top);
+
+        let first = ui.add_horizontal(Spacer::new(Size::new(40, 10)));
+        assert_eq!(ui.wrap_state(), 0);
+        let wrapped = ui.add_horizontal(Spacer::new(Size::new(30, 10)));
+        assert_eq!(wrapped.error(), None);
+        assert_eq!(wrapped.internal.area.top_left.x, 3);
+        // like after new_row(): the first row (10px high) is followed by item spacing (4px)
+        assert_eq!(
+            wrapped.internal.area.top_left.y,
+            first.internal.area.top_left.y + 10 + 4
+        );
+        assert_ne!(ui.wrap_state(), 0);
+
+        // widgets wider than the whole Ui still fail
+        assert!(matches!(
+            ui.add_horizontal(Spacer::new(Size::new(59, 10))).error(),
+            Some(GuiError::NoHorizontalSpace { .. })
+        ));
+    }
+
+    #[test]
+    fn widgets_moved_by_a_wrap_point_redraw() {
+        use crate::label::Label;
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<4>::new();
+
+        // returns the button's area and whether it was redrawn
+        let mut frame = |display: &mut MockDisplay<Rgb565>, text| {
+            let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            ui.add_horizontal(Label::new(text).smartstate(smartstates.nxt()));
+            let redraws = ui.perf().widgets_redrawn;
+            let area = ui
+                .add_horizontal(Button::new("B").smartstate(smartstates.nxt()))
+                .area();
+            (area, ui.perf().widgets_redrawn > redraws)
+        };
+
+        let (area, redrawn) = frame(&mut display, "A");
+        assert!(redrawn);
+        assert_eq!(frame(&mut display, "A"), (area, false));
+
+        // the label gets longer, so the button wraps to the next row, and back
+        let (wrapped, redrawn) = frame(&mut display, "AAAA");
+        assert!(wrapped.top_left.y > area.top_left.y);
+        assert!(redrawn);
+        assert_eq!(frame(&mut display, "AAAA"), (wrapped, false));
+        assert_eq!(frame(&mut display, "A"), (area, true));
+
+        // moving along with the content of a scroll area isn't a move; returns whether the
+        // label at the top was redrawn
+        let mut scroll = ScrollState::new();
+        let mut frame = |display: &mut MockDisplay<Rgb565>, offset| {
+            let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+            ui.set_area_mover(|display, area, by| {
+                let before = display.clone();
+                for point in area.points().filter(|point| area.contains(*point - by)) {
+                    display.set_pixel(point, before.get_pixel(point - by));
+                }
+                true
+            });
+            smartstates.restart_counter();
+            scroll.set_offset(offset);
+            let mut redrawn = false;
+            ui.scroll_area(40, &mut scroll, |ui| {
+                ui.add(Label::new("A").smartstate(smartstates.nxt()));
+                redrawn = ui.perf().widgets_redrawn > 0;
+                for _ in 0..3 {
+                    ui.add(Button::new("B").smartstate(smartstates.nxt()));
+                }
+            });
+            redrawn
+        };
+        assert!(frame(&mut display, 0));
+        assert!(!frame(&mut display, 0));
+        assert!(!frame(&mut display, 4));
+    }
+
+    #[test]
+    fn rows_start_with_default_height() {
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_row_alignment(VerticalAlign::Top);
+
+        // first row: 10px and 30px widgets
+        let a = ui
+            .add_horizontal(Spacer::new(Size::new(5, 10)))
+            .internal
+            .area;
+        ui.add(Spacer::new(Size::new(5, 30)));
+        assert_eq!(a.top_left.y, 3);
+        // second row: starts at the default widget height, not the previous row's 30px
+        assert_eq!(ui.get_row_height(), 16);
+        let b = ui.add(Spacer::new(Size::new(5, 10))).internal.area;
+        assert_eq!(
+            b,
+            Rectangle::new(Point::new(3, 3 + 30 + 4), Size::new(5, 16))
+        );
+        // third row
+        let c = ui.add(Spacer::new(Size::new(5, 20))).internal.area;
+        assert_eq!(c.top_left.y, b.top_left.y + 16 + 4);
+    }
+
+    #[test]
+    fn set_row_height_gives_uniform_rows() {
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_row_height(40);
+
+        let a = ui
+            .add_horizontal(Spacer::new(Size::new(5, 10)))
+            .internal
+            .area;
+        ui.add(Spacer::new(Size::new(5, 30)));
+        let b = ui.add(Spacer::new(Size::new(5, 20))).internal.area;
+        let c = ui.add(Spacer::new(Size::new(5, 16))).internal.area;
+
+        // centered in 40px rows, 4px item spacing between the rows
+        assert_eq!(a.top_left.y, 3 + 15);
+        assert_eq!(b.top_left.y, 3 + 44 + 10);
+        assert_eq!(c.top_left.y, 3 + 88 + 12);
+
+        // taller widgets still expand their row
+        ui.add(Spacer::new(Size::new(5, 50)));
+        let d = ui.add(Spacer::new(Size::new(5, 40))).internal.area;
+        assert_eq!(d.top_left.y, 3 + 132 + 54);
+
+        // back to the default height for the rows after the current one
+        ui.reset_row_height();
+        assert_eq!(ui.get_row_height(), 40);
+        ui.new_row();
+        assert_eq!(ui.get_row_height(), 16);
+    }
+
+    #[test]
+    fn remaining_space_matches_allocation() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        assert_eq!(
+            ui.bounds(),
+            Rectangle::new(Point::new(3, 3), Size::new(58, 58))
+        );
+        assert_eq!(ui.cursor(), Point::new(3, 3));
+
+        ui.advance_cursor(Size::new(20, 10)).unwrap();
+        assert_eq!(ui.cursor(), Point::new(23, 3));
+        assert_eq!(ui.remaining_width(), 38);
+        assert_eq!(ui.remaining_height(), 58);
+
+        // exactly the remaining width fits, one more pixel doesn't
+        ui.set_overflow_policy(Overflow::Error);
+        assert!(ui.allocate_space(Size::new(39, 10)).is_err());
+        let area = ui.allocate_space(Size::new(38, 10)).unwrap().area;
+        assert_eq!(area.top_left, Point::new(23, 3));
+        assert_eq!(ui.remaining_width(), 0);
+
+        ui.new_row();
+        assert_eq!(ui.cursor(), Point::new(3, 3 + 10 + 4));
+        assert_eq!(ui.remaining_height(), 58 - 14);
+    }
+
+    #[test]
+    fn equal_width_row_uses_widest_widget() {
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let mut areas = [Rectangle::zero(); 3];
+        let mut errors = [None; 3];
+
+        // "Cancel" is 6 chars * 9px + 2 * 6px padding wide
+        let width = ui.equal_width_row(|ui| {
+            for (i, label) in ["OK", "Cancel", "Apply"].into_iter().enumerate() {
+                let response = ui.add_horizontal(Button::new(label));
+                areas[i] = response.internal.area;
+                errors[i] = response.error();
+            }
+        });
+
+        assert_eq!(width, 66);
+        assert_eq!(errors, [None; 3]);
+        for (i, area) in areas.iter().enumerate() {
+            assert_eq!(area.top_left, Point::new(3 + i as i32 * (66 + 8), 3));
+            assert_eq!(area.size.width, 66);
+        }
+        // only the drawing pass allocates: the buttons and the spacing after each of them
+        assert_eq!(ui.allocations(), 6);
+        // the row is finished
+        assert_eq!(ui.cursor().x, 3);
+    }
+
+    #[test]
+    fn justified_row_fills_width() {
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let mut measured = None;
+
+        let width = ui.justified_row(|ui| {
+            let response = ui.add_horizontal(Button::new("A"));
+            if measured.is_none() {
+                measured = response.error();
+            }
+            ui.add_horizontal(Button::new("B"));
+        });
+
+        assert_eq!(measured, Some(GuiError::Measuring));
+        // 314px wide, minus 8px spacing between the two buttons
+        assert_eq!(width, 153);
+    }
+
+    #[cfg(feature = "controls")]
+    #[test]
+    fn width_fractions_fit_the_row_with_spacing() {
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+
+        // 314px wide, with 8px spacing after every button
+        for (per_mille, count, width) in [(500, 2, 153), (333, 3, 99), (1000, 1, 314)] {
+            let top = ui.cursor().y;
+            for i in 0..count {
+                let response = ui.add_horizontal(Button::new("A").width_fraction(per_mille));
+                assert_eq!(response.error(), None);
+                let area = response.internal.area;
+                assert_eq!(area.size.width, width, "{per_mille}");
+                assert_eq!(area.top_left, Point::new(3 + i * (width as i32 + 8), top));
+            }
+            ui.new_row();
+        }
+
+        // the last button takes what's left
+        ui.add_horizontal(Button::new("A").width_fraction(250));
+        let response = ui.add_horizontal(Button::new("B").fill_width());
+        assert_eq!(response.internal.area.size.width, 314 - 72 - 8);
+        // a slider of half the row
+        ui.new_row();
+        let mut value = 0;
+        let response = ui.add(Slider::new(&mut value, 0..=10).width_fraction(500));
+        assert_eq!(response.internal.area.size.width, 153);
+    }
+
+    #[test]
+    fn groups_are_placed_like_one_widget() {
+        use crate::label::Label;
+
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let mut areas = [Rectangle::zero(); 4];
+
+        // a button next to two stacked labels, next to another button
+        let group = ui.horizontal(|ui| {
+            areas[0] = ui.add(Button::new("A")).area();
+            ui.vertical(|ui| {
+                areas[1] = ui.add(Label::new("B")).area();
+                areas[2] = ui.add(Label::new("C")).area();
+            });
+            areas[3] = ui.add(Button::new("D")).area();
+        });
+        let [a, b, c, d] = areas;
+        assert_eq!(group.error(), None);
+        assert_eq!(a.top_left.x, 3);
+        // left to right, separated by the 8px item spacing
+        assert_eq!(b.top_left.x, a.top_left.x + a.size.width as i32 + 8);
+        assert_eq!(d.top_left.x, b.top_left.x + b.size.width as i32 + 8);
+        // top to bottom
+        assert_eq!(c.top_left.x, b.top_left.x);
+        assert!(c.top_left.y > b.bottom_right().unwrap().y);
+        // the group covers all of them, and the next widget is below it
+        let area = group.area();
+        assert_eq!(area.top_left, Point::new(3, 3));
+        // the buttons are centered next to the taller labels
+        assert_eq!(a.center().y, area.center().y);
+        assert_eq!(d.center().y, a.center().y);
+        assert_eq!(area.bottom_right().unwrap().x, d.bottom_right().unwrap().x);
+        assert!(area.bottom_right().unwrap().y >= c.bottom_right().unwrap().y);
+        let next = ui.add(Label::new("E")).area();
+        assert!(next.top_left.y > area.bottom_right().unwrap().y);
+
+        // right-aligned as a whole
+        ui.set_horizontal_alignment(HorizontalAlign::Right);
+        let mut ok = Rectangle::zero();
+        let group = ui.horizontal(|ui| {
+            ui.add(Button::new("Cancel"));
+            ok = ui.add(Button::new("OK")).area();
+        });
+        assert_eq!(group.area().bottom_right().unwrap().x, 316);
+        assert_eq!(ok.bottom_right(), group.area().bottom_right());
+    }
+
+    #[test]
+    fn placeholders_keep_the_layout_and_erase_hidden_widgets() {
+        use crate::label::Label;
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<2>::new();
+        let mut slot = PlaceholderState::new();
+        let background = medsize_rgb565_style().background_color;
+
+        let mut label_area = None;
+        for (show, redrawn) in [(true, 2), (false, 0), (false, 0), (true, 1)] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            let button = Button::new("A").smartstate(smartstates.nxt());
+            let area = ui.add_or_placeholder(show, button, &mut slot).area();
+            let label = ui.add(Label::new("B").smartstate(smartstates.nxt())).area();
+            assert_eq!(*label_area.get_or_insert(label), label);
+            assert_eq!(ui.perf().widgets_redrawn, redrawn, "{show}");
+            assert_eq!(slot.shown(), Some(show));
+
+            let erased = area
+                .points()
+                .all(|p| display.get_pixel(p) == Some(background));
+            assert_eq!(erased, !show);
+        }
+        assert!(Ui::new_fullscreen(&mut display, medsize_rgb565_style())
+            .placeholder(Size::new(10, 10))
+            .error()
+            .is_none());
+    }
+
+    #[test]
+    fn widgets_erased_by_clearing_redraw_next_frame() {
+        use crate::label::Label;
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<2>::new();
+        let mut state = UiState::new(medsize_rgb565_style());
+
+        let mut first = Rectangle::zero();
+        for (clear, redrawn, erased) in [(false, 2, false), (true, 0, true), (false, 1, false)] {
+            let frame = state.frame(&mut display, Interaction::None, 0, |ui| {
+                smartstates.restart_counter();
+                first = ui.add(Label::new("A").smartstate(smartstates.nxt())).area();
+                ui.add(Label::new("B").smartstate(smartstates.nxt()));
+                if clear {
+                    ui.clear_area(first).unwrap();
+                }
+            });
+            assert_eq!(frame.perf.widgets_redrawn, redrawn, "{clear}");
+            assert_eq!(frame.erased_area, erased.then_some(first));
+            assert_eq!(frame.needs_repaint, erased);
+        }
+
+        // clearing before adding the widgets doesn't erase them
+        let frame = state.frame(&mut display, Interaction::None, 0, |ui| {
+            smartstates.restart_counter();
+            ui.clear_area(first).unwrap();
+            ui.add(Label::new("A").smartstate(smartstates.nxt()));
+        });
+        assert_eq!(frame.perf.widgets_redrawn, 1);
+        assert_eq!(frame.erased_area, None);
+
+        // without a UiState, the smartstates are invalidated by the erased area
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        smartstates.restart_counter();
+        ui.add(Label::new("A").smartstate(smartstates.nxt()));
+        ui.sub_ui(|ui| ui.clear_area(first)).unwrap();
+        let erased = ui.finish_frame().erased_area.unwrap();
+        assert_eq!(smartstates.invalidate_area(erased), 1);
+    }
+
+    #[test]
+    fn overlays_stay_on_top_until_they_are_gone() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut overlays = Overlays::new();
+        let background = medsize_rgb565_style().background_color;
+        let area = Rectangle::new(Point::new(10, 10), Size::new(30, 30));
+        let everything = Rectangle::new(Point::zero(), Size::new(64, 64));
+
+        for first in [true, false] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            ui.interact(Interaction::Release(Point::new(20, 20)));
+            ui.begin_overlays(&mut overlays).unwrap();
+            let before = ui.check_interact(everything);
+            let mut inside = Interaction::None;
+            ui.draw_overlay(area, |ui| {
+                inside = ui.check_interact(area);
+                ui.draw(&area.into_styled(PrimitiveStyle::with_fill(Rgb565::RED)))
+            })
+            .unwrap();
+            // like a widget added after the overlay
+            let after = ui.check_interact(everything);
+            ui.clear_area(everything).unwrap();
+            ui.end_overlays(&mut overlays);
+
+            assert!(inside.is_release());
+            assert_eq!(after, Interaction::None);
+            assert_eq!(overlays.areas(), &[area]);
+            // from the second frame on, widgets before the overlay are covered, too
+            assert_eq!(before.is_release(), first);
+        }
+        assert_eq!(display.get_pixel(Point::new(20, 20)), Some(Rgb565::RED));
+        assert_eq!(display.get_pixel(Point::new(5, 5)), Some(background));
+
+        // the overlay is gone: the next frame repaints its area
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.begin_overlays(&mut overlays).unwrap();
+        ui.end_overlays(&mut overlays);
+        assert!(ui.needs_repaint());
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.begin_overlays(&mut overlays).unwrap();
+        assert!(ui.invalidated(area));
+        ui.end_overlays(&mut overlays);
+        assert_eq!(display.get_pixel(Point::new(20, 20)), Some(background));
+        assert!(overlays.areas().is_empty());
+    }
+
+    #[test]
+    fn scroll_areas_consume_scrolls() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut scroll = ScrollState::new();
+
+        // returns the scroll area's and the first button's responses, and if a repaint is needed
+        let frame = |display: &mut MockDisplay<Rgb565>, scroll: &mut ScrollState, interaction| {
+            let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+            ui.interact(interaction);
+            let mut first = None;
+            let area = ui.scroll_area(30, scroll, |ui| {
+                for label in ["A", "B", "C"] {
+                    let response = ui.add(Button::new(label));
+                    first.get_or_insert(response);
+                }
+            });
+            (area, first.unwrap(), ui.needs_repaint())
+        };
+
+        let (area, button, _) = frame(&mut display, &mut scroll, Interaction::None);
+        assert_eq!(
+            area.area(),
+            Rectangle::new(Point::new(3, 3), Size::new(58, 30))
+        );
+        assert_eq!(button.area().top_left, Point::new(3, 3));
+        assert!(scroll.content_height() > 30);
+
+        // without a pointer, the scroll goes to the only scroll area, not to its buttons
+        let (area, button, repaint) = frame(&mut display, &mut scroll, Interaction::Scroll(1));
+        assert!(area.changed() && repaint);
+        assert!(!button.clicked() && !button.down());
+        assert_eq!(scroll.offset(), 16);
+        let (_, button, _) = frame(&mut display, &mut scroll, Interaction::None);
+        assert_eq!(button.area().top_left, Point::new(3, -13));
+
+        // the offset stops at the end of the content
+        frame(&mut display, &mut scroll, Interaction::Scroll(100));
+        assert_eq!(scroll.offset(), scroll.content_height() - 30);
+
+        // a pointer elsewhere keeps the area from scrolling
+        frame(
+            &mut display,
+            &mut scroll,
+            Interaction::Hover(Point::new(30, 50)),
+        );
+        let (area, _, _) = frame(&mut display, &mut scroll, Interaction::Scroll(-1));
+        assert!(!area.changed());
+        frame(
+            &mut display,
+            &mut scroll,
+            Interaction::Hover(Point::new(30, 10)),
+        );
+        let (area, _, _) = frame(&mut display, &mut scroll, Interaction::Scroll(-1));
+        assert!(area.changed());
+
+        // other widgets ignore scrolls
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.interact(Interaction::Scroll(1));
+        let button = ui.add(Button::new("A"));
+        assert!(!button.clicked() && !button.down());
+    }
+
+    #[test]
+    fn scroll_areas_map_interactions_once() {
+        let frame = Rectangle::new(Point::zero(), Size::new(64, 64));
+        for (origin, rotation) in [
+            (Point::new(100, 50), Rotation::Deg0),
+            (Point::zero(), Rotation::Deg90),
+            (Point::new(100, 50), Rotation::Deg270),
+        ] {
+            let mut display = MockDisplay::<Rgb565>::new();
+            display.set_allow_overdraw(true);
+            let mut scroll = ScrollState::new();
+            // returns the second button's area and if it was clicked
+            let mut click = |point: Option<Point>| {
+                let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+                ui.set_origin(origin);
+                ui.set_rotation(rotation);
+                if let Some(point) = point {
+                    ui.interact(Interaction::Release(point));
+                }
+                let mut button = None;
+                ui.scroll_area(58, &mut scroll, |ui| {
+                    ui.add(Button::new("A"));
+                    button = Some(ui.add(Button::new("B")));
+                });
+                let button = button.unwrap();
+                (button.area(), button.clicked())
+            };
+
+            let (area, _) = click(None);
+            // the touch point where the button's center is visible
+            let center = rotation.unrotate_point(area.center(), frame) + origin;
+            assert_eq!(click(Some(center)), (area, true), "{rotation:?}");
+            let beside = rotation.unrotate_point(area.center() + Point::new(0, 20), frame) + origin;
+            assert!(!click(Some(beside)).1, "{rotation:?}");
+        }
+    }
+
+    /// A display scrolling whole rows, like with hardware scrolling
+    struct ScrollingDisplay {
+        display: MockDisplay<Rgb565>,
+        scrolls: u32,
+    }
+
+    impl Dimensions for ScrollingDisplay {
+        fn bounding_box(&self) -> Rectangle {
+            self.display.bounding_box()
+        }
+    }
+
+    impl DrawTarget for ScrollingDisplay {
+        type Color = Rgb565;
+        type Error = core::convert::Infallible;
+
+        fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+        {
+            self.display.draw_iter(pixels)
+        }
+    }
+
+    impl ScrollAssist for ScrollingDisplay {
+        fn hw_scroll(&mut self, area: Rectangle, lines: i32) -> bool {
+            assert_eq!(area.size.width, 64);
+            let before = self.display.clone();
+            let by = Point::new(0, lines);
+            for point in area.points().filter(|point| area.contains(*point - by)) {
+                self.display.set_pixel(point, before.get_pixel(point - by));
+            }
+            self.scrolls += 1;
+            true
+        }
+    }
+
+    #[test]
+    fn scroll_areas_scroll_in_hardware_and_draw_the_uncovered_rows() {
+        let mut display = ScrollingDisplay {
+            display: MockDisplay::new(),
+            scrolls: 0,
+        };
+        display.display.set_allow_overdraw(true);
+        let mut scroll = ScrollState::new();
+        let mut smartstates = SmartstateProvider::<5>::new();
+
+        // returns the number of pixels drawn
+        let mut frame = |display: &mut ScrollingDisplay, scroll: &mut ScrollState, assist| {
+            let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+            if assist {
+                ui.set_scroll_assist();
+            }
+            ui.interact(match scroll.offset() {
+                0 => Interaction::Scroll(1),
+                _ => Interaction::None,
+            });
+            smartstates.restart_counter();
+            ui.scroll_area(40, scroll, |ui| {
+                for label in ["A", "B", "C", "D", "E"] {
+                    ui.add(Button::new(label).smartstate(smartstates.nxt()));
+                }
+            });
+            ui.perf().pixels_drawn
+        };
+
+        // drawn, scrolled down, then drawn scrolled
+        frame(&mut display, &mut scroll, true);
+        assert_eq!(scroll.offset(), 16);
+        let scrolled = frame(&mut display, &mut scroll, true);
+        assert_eq!(display.scrolls, 1);
+
+        // it looks like redrawing everything
+        let mut expected = ScrollingDisplay {
+            display: MockDisplay::new(),
+            scrolls: 0,
+        };
+        expected.display.set_allow_overdraw(true);
+        let mut scroll = ScrollState::new();
+        frame(&mut expected, &mut scroll, false);
+        let redrawn = frame(&mut expected, &mut scroll, false);
+        assert_eq!(display.display, expected.display);
+        assert!(
+            scrolled < redrawn / 2,
+            "{scrolled} of {redrawn} pixels drawn"
+        );
+    }
+
+    #[test]
+    fn ui_state_retains_the_press_between_frames() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut state = UiState::new(medsize_rgb565_style());
+        let origin = Point::new(10, 10);
+        let button = Rectangle::new(Point::new(5, 5), Size::new(10, 10));
+
+        let frames = [
+            (Interaction::Click(origin), 0, Some(0)),
+            (Interaction::Drag(Point::new(30, 12)), 40, Some(40)),
+            (Interaction::Release(Point::new(31, 12)), 100, Some(100)),
+        ];
+        for (interaction, now, duration) in frames {
+            let mut ui = state.begin_frame(&mut display, interaction, now);
+            assert_eq!(ui.press_origin(), Some(origin));
+            assert_eq!(ui.press_duration(), duration);
+            assert!(ui.press_started_in(button));
+            ui.add(Button::new("A"));
+            ui.set_focus(Some(1));
+            state.end_frame(ui);
+        }
+
+        // the press is over, the pointer and the focus stay
+        state.frame(&mut display, Interaction::None, 120, |ui| {
+            assert_eq!(ui.press_origin(), None);
+            assert_eq!(ui.pointer(), Some(Point::new(31, 12)));
+            assert_eq!(ui.focus(), Some(1));
+        });
+
+        // a new Ui only knows the current point
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.interact(Interaction::Drag(Point::new(30, 12)));
+        assert_eq!(ui.press_origin(), Some(Point::new(30, 12)));
+        assert!(!ui.press_started_in(button));
+    }
+
+    #[test]
+    fn a_second_touch_pinches_instead_of_dragging() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut state = UiState::new(medsize_rgb565_style());
+        let first = Point::new(20, 20);
+        let left = Rectangle::new(Point::zero(), Size::new(32, 64));
+
+        // (first touch, second touch, interaction widgets get, pinch center and scale)
+        let frames = [
+            (
+                Interaction::Click(first),
+                None,
+                Interaction::Click(first),
+                None,
+            ),
+            (
+                Interaction::Drag(first),
+                Some(Point::new(26, 28)),
+                Interaction::None,
+                Some((Point::new(23, 24), 1.0)),
+            ),
+            (
+                Interaction::Drag(first),
+                Some(Point::new(32, 36)),
+                Interaction::None,
+                Some((Point::new(26, 28), 2.0)),
+            ),
+            // the touch left doesn't drag, nor click when released
+            (Interaction::Drag(first), None, Interaction::None, None),
+            (Interaction::Release(first), None, Interaction::None, None),
+            (
+                Interaction::Click(first),
+                None,
+                Interaction::Click(first),
+                None,
+            ),
+        ];
+        for (interaction, second, interacted, pinch) in frames {
+            let mut ui = state.begin_frame(&mut display, interaction, 0);
+            ui.interact_second(second);
+            assert_eq!(ui.check_interact(ui.overlay_bounds()), interacted);
+            let pinch = pinch.map(|(center, scale_delta)| Gesture::Pinch {
+                center,
+                scale_delta,
+            });
+            assert_eq!(ui.gesture(), pinch);
+            // only the first pinch frame is within the left half
+            let within = ui.check_gesture(left);
+            assert_eq!(within, pinch.filter(|_| second.unwrap().x < 32));
+            state.end_frame(ui);
+        }
+    }
+
+    #[test]
+    fn custom_content_is_translated_clipped_and_cached() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+        let background = medsize_rgb565_style().background_color;
+
+        // returns the area, whether it was drawn, and the number of redrawn widgets
+        let mut frame = |display: &mut MockDisplay<Rgb565>, version| {
+            let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+            let mut drawn = false;
+            let response = ui
+                .custom_cached(Size::new(10, 10), &mut smartstate, version, |target, _| {
+                    drawn = true;
+                    assert_eq!(target.bounding_box().size, Size::new(10, 10));
+                    // the line continues beyond the area
+                    embedded_graphics::primitives::Line::new(Point::zero(), Point::new(20, 0))
+                        .into_styled(PrimitiveStyle::with_stroke(Rgb565::RED, 1))
+                        .draw(target)
+                })
+                .unwrap();
+            (response.area(), drawn, ui.perf().widgets_redrawn)
+        };
+
+        let (area, drawn, redrawn) = frame(&mut display, 1);
+        assert_eq!(area, Rectangle::new(Point::new(3, 3), Size::new(10, 10)));
+        assert!(drawn);
+        assert_eq!(redrawn, 1);
+        assert_eq!(display.get_pixel(Point::new(3, 3)), Some(Rgb565::RED));
+        assert_eq!(display.get_pixel(Point::new(12, 3)), Some(Rgb565::RED));
+        assert_eq!(display.get_pixel(Point::new(13, 3)), None);
+        assert_eq!(display.get_pixel(Point::new(3, 4)), Some(background));
+
+        // unchanged content is skipped, a new version is drawn
+        assert_eq!(frame(&mut display, 1), (area, false, 0));
+        assert_eq!(frame(&mut display, 2), (area, true, 1));
+    }
+
+    #[test]
+    fn static_frame_has_no_redraws() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<4>::new();
+
+        let mut frame = |display: &mut MockDisplay<Rgb565>, t| {
+            let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+            ui.begin_frame(t);
+            smartstates.restart_counter();
+            ui.add(Button::new("A").smartstate(smartstates.nxt()));
+            ui.add(Button::new("B").smartstate(smartstates.nxt()));
+            ui.perf()
+        };
+
+        let first = frame(&mut display, 0);
+        assert_eq!(first.widgets_added, 2);
+        assert_eq!(first.widgets_redrawn, 2);
+        assert!(first.pixels_drawn > 0);
+        assert!(first.draw_calls >= 4);
+        assert!(first.dirty_area.is_some());
+
+        let second = frame(&mut display, 16);
+        assert_eq!(
+            second,
+            PerfStats {
+                widgets_added: 2,
+                ..PerfStats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn perf_counts_pixels_and_dirty_area() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let a = Rectangle::new(Point::new(0, 0), Size::new(4, 4));
+        let b = Rectangle::new(Point::new(10, 20), Size::new(2, 2));
+
+        ui.start_drawing(&a);
+        ui.draw(&a.into_styled(PrimitiveStyle::with_fill(Rgb565::RED)))
+            .unwrap();
+        ui.finalize().unwrap();
+        ui.start_drawing(&b);
+        ui.finalize().unwrap();
+
+        let perf = ui.perf();
+        assert_eq!(perf.pixels_drawn, 16);
+        assert_eq!(perf.draw_calls, 1);
+        assert_eq!(perf.widgets_redrawn, 2);
+        assert_eq!(
+            perf.dirty_area,
+            Some(Rectangle::new(Point::zero(), Size::new(12, 22)))
+        );
+
+        ui.reset_perf();
+        assert_eq!(ui.perf(), PerfStats::default());
+    }
+
+    #[test]
+    fn finish_frame_reports_dirty_area() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<4>::new();
+
+        let mut frame = |display: &mut MockDisplay<Rgb565>, clear: bool| {
+            let mut ui = Ui::new_fullscreen(display, medsize_rgb565_style());
+            if clear {
+                ui.clear_background().unwrap();
+            }
+            smartstates.restart_counter();
+            let area = ui
+                .add(Button::new("A").smartstate(smartstates.nxt()))
+                .internal
+                .area;
+            (area, ui.finish_frame())
+        };
+
+        let (area, info) = frame(&mut display, false);
+        assert_eq!(info.dirty_area, Some(area));
+        assert_eq!(info.perf.widgets_redrawn, 1);
+
+        // nothing changed, nothing to flush
+        assert_eq!(frame(&mut display, false).1.dirty_area, None);
+
+        // a cleared background is flushed as a whole
+        let (_, info) = frame(&mut display, true);
+        assert_eq!(
+            info.dirty_area,
+            Some(Rectangle::new(Point::zero(), Size::new(64, 64)))
+        );
+    }
+
+    #[test]
+    fn debug_overlay_outlines_redrawn_widgets() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<4>::new();
+        let style = medsize_rgb565_style();
+
+        let mut frame = |display: &mut MockDisplay<Rgb565>| {
+            let mut ui = Ui::new_fullscreen(display, style);
+            ui.set_debug_overlay(DebugOverlay::Both);
+            smartstates.restart_counter();
+            let area = ui
+                .add(Button::new("A").smartstate(smartstates.nxt()))
+                .internal
+                .area;
+            (area.top_left, ui.perf())
+        };
+
+        // first frame: redrawn, outlined in the redraw color
+        let (corner, perf) = frame(&mut display);
+        assert_eq!(display.get_pixel(corner), Some(style.secondary_color));
+        assert_eq!(perf.widgets_redrawn, 1);
+
+        // second frame: not redrawn, just the bounds outline
+        let (corner, perf) = frame(&mut display);
+        assert_eq!(
+            display.get_pixel(corner),
+            Some(style.highlight_border_color)
+        );
+        assert_eq!(perf.widgets_redrawn, 0);
+        assert_eq!(perf.dirty_area, None);
+    }
+
+    #[test]
+    fn turning_the_debug_overlay_off_redraws_everything() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<4>::new();
+        let style = medsize_rgb565_style();
+        let mut state = UiState::new(style);
+
+        // the overlay is only set in the first frame, the state keeps it on
+        let mut frame = |display: &mut MockDisplay<Rgb565>, overlay: Option<DebugOverlay>| {
+            let mut corner = Point::zero();
+            let info = state.frame(display, Interaction::None, 0, |ui| {
+                if let Some(overlay) = overlay {
+                    ui.set_debug_overlay(overlay);
+                }
+                smartstates.restart_counter();
+                corner = ui
+                    .add(Button::new("A").smartstate(smartstates.nxt()))
+                    .area()
+                    .top_left;
+            });
+            (corner, info)
+        };
+
+        frame(&mut display, Some(DebugOverlay::Bounds));
+        let (corner, info) = frame(&mut display, None);
+        assert_eq!(info.perf.widgets_redrawn, 0);
+        assert_eq!(
+            display.get_pixel(corner),
+            Some(style.highlight_border_color)
+        );
+
+        // the outline is cleared, and the button is redrawn
+        let (corner, info) = frame(&mut display, Some(DebugOverlay::Off));
+        assert_eq!(info.perf.widgets_redrawn, 1);
+        assert_eq!(display.get_pixel(corner), Some(style.background_color));
+        assert_eq!(info.dirty_area, Some(display.bounding_box()));
+        let (_, info) = frame(&mut display, None);
+        assert_eq!(info.perf.widgets_redrawn, 0);
+        assert_eq!(info.dirty_area, None);
+    }
+
+    #[test]
+    fn row_alignment_offsets_shorter_widgets() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        assert!(matches!(ui.row_alignment(), VerticalAlign::Center));
+
+        let tall = ui.add_horizontal(Spacer::new(Size::new(5, 30)));
+        let top = tall.internal.area.top_left.y;
+
+        let centered = ui.add_horizontal(Spacer::new(Size::new(5, 10)));
+        assert_eq!(
+            centered.internal.area,
+            Rectangle::new(Point::new(16, top + 10), Size::new(5, 10))
+        );
+
+        ui.set_row_alignment(VerticalAlign::Bottom);
+        let bottom = ui.add_horizontal(Spacer::new(Size::new(5, 10)));
+        assert_eq!(bottom.internal.area.top_left.y, top + 20);
+        assert_eq!(bottom.internal.area.size.height, 10);
+
+        // top alignment stretches the widget to the full row height
+        ui.set_row_alignment(VerticalAlign::Top);
+        let stretched = ui.add_horizontal(Spacer::new(Size::new(5, 10)));
+        assert_eq!(
+            stretched.internal.area,
+            Rectangle::new(Point::new(42, top), Size::new(5, 30))
+        );
+    }
+
+    #[test]
+    fn horizontal_groups_align_shorter_widgets_before_taller_ones() {
+        use crate::label::Label;
+
+        let mut display = working_display();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let mut areas = [Rectangle::zero(); 3];
+        ui.horizontal(|ui| {
+            areas[0] = ui.add(Label::new("Short")).area();
+            areas[1] = ui.add(Button::new("Tall")).area();
+            areas[2] = ui.add(Spacer::new(Size::new(5, 41))).area();
+        });
+        let [label, button, spacer] = areas;
+        assert!(label.size.height < button.size.height);
+        assert!(button.size.height < spacer.size.height);
+        assert_eq!(label.center().y, spacer.center().y);
+        assert_eq!(button.center().y, spacer.center().y);
+
+        // stretched to the tallest widget with top alignment
+        ui.set_row_alignment(VerticalAlign::Top);
+        ui.horizontal(|ui| {
+            areas[0] = ui.add(Label::new("Short")).area();
+            areas[1] = ui.add(Button::new("Tall")).area();
+        });
+        assert_eq!(areas[0].size.height, areas[1].size.height);
+    }
+
+    #[test]
+    fn overflow_error_doesnt_wrap() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_overflow_policy(Overflow::Error);
+
+        ui.add_horizontal(Spacer::new(Size::new(40, 10)));
+        assert_eq!(
+            ui.add_horizontal(Spacer::new(Size::new(30, 10))).error(),
+            Some(GuiError::NoHorizontalSpace {
+                needed: 30,
+                available: 10
+            })
+        );
+        assert_eq!(ui.wrap_state(), 0);
+    }
+
+    #[test]
+    fn overflow_clip_clips_drawing_to_bounds() {
+        use crate::label::Label;
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_overflow_policy(Overflow::Clip);
+
+        let response = ui.add(Label::new("Much too wide for this"));
+        assert_eq!(response.error(), None);
+        assert!(response.internal.area.size.width > 58);
+
+        // the Ui's bounds end at x = 60 (64px display, 3px padding)
+        assert!(display.affected_area().bottom_right().unwrap().x <= 60);
+        assert!(display.affected_area().size.width > 0);
+    }
+
+    #[test]
+    fn origin_offsets_interactions() {
+        // the display stands in for a region at (100, 50) of a larger screen
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let origin = Point::new(100, 50);
+
+        let mut release_at = |origin: Option<Point>, point: Point| {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            if let Some(origin) = origin {
+                ui.set_origin(origin);
+            }
+            ui.interact(Interaction::Release(point));
+            let response = ui.add(Button::new("OK"));
+            (response.internal.area, response.clicked())
+        };
+
+        let (area, _) = release_at(None, Point::zero());
+        // where the button is visible on the screen
+        let visible = area.center() + origin;
+
+        assert_eq!(release_at(Some(origin), visible), (area, true));
+        assert!(!release_at(None, visible).1);
+        assert!(!release_at(Some(origin), area.center()).1);
+    }
+
+    #[test]
+    fn explicit_bounds_confine_the_ui() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut style = medsize_rgb565_style();
+        style.spacing.window_border_padding = Size::new(4, 2);
+        // the right half of the display
+        let bounds = Rectangle::new(Point::new(32, 0), Size::new(32, 64));
+
+        let mut ui = Ui::new(&mut display, bounds, style);
+        ui.clear_background().unwrap();
+        ui.interact(Interaction::Release(Point::new(10, 10)));
+        let response = ui.add(Button::new("+"));
+        assert_eq!(response.internal.area.top_left, Point::new(36, 2));
+        assert!(!response.clicked());
+        assert_eq!(display.affected_area(), bounds);
+
+        let mut ui = Ui::new(&mut display, bounds, style);
+        ui.interact(Interaction::Release(Point::new(40, 10)));
+        assert!(ui.add(Button::new("+")).clicked());
+    }
+
+    #[test]
+    fn rotation_maps_drawing_and_interactions() {
+        // the top half of the display, in portrait orientation
+        let bounds = Rectangle::new(Point::zero(), Size::new(64, 32));
+        let frame = |display: &mut MockDisplay<Rgb565>, buffer: Option<&mut [Rgb565]>, point| {
+            let mut ui = Ui::new(display, bounds, medsize_rgb565_style());
+            ui.set_rotation(Rotation::Deg90);
+            if let Some(buffer) = buffer {
+                ui.set_buffer(buffer);
+            }
+            ui.interact(Interaction::Release(point));
+            assert_eq!(ui.get_width(), 32 - 6);
+            let response = ui.add(Button::new("+"));
+            (response.internal.area, response.clicked())
+        };
+
+        let new_display = || {
+            let mut display = MockDisplay::new();
+            display.set_allow_overdraw(true);
+            display
+        };
+
+        let mut display = new_display();
+        let (area, clicked) = frame(&mut display, None, Point::zero());
+        assert!(!clicked);
+        assert_eq!(area, Rectangle::new(Point::new(3, 3), Size::new(21, 25)));
+        // the button's top is at the right edge
+        assert_eq!(
+            display.affected_area(),
+            Rectangle::new(Point::new(36, 3), Size::new(25, 21))
+        );
+
+        // drawing through the framebuffer rotates the same way (and fills the corners)
+        let mut buffered = new_display();
+        let mut buffer = [Rgb565::BLACK; 21 * 25];
+        frame(&mut buffered, Some(&mut buffer), Point::zero());
+        assert_eq!(buffered.affected_area(), display.affected_area());
+        for point in display.affected_area().points() {
+            if let Some(color) = display.get_pixel(point) {
+                assert_eq!(buffered.get_pixel(point), Some(color), "{point:?}");
+            }
+        }
+
+        // the touch point where the button's center is visible
+        let center = Rotation::Deg90.unrotate_point(area.center(), display.bounding_box());
+        assert!(frame(&mut new_display(), None, center).1);
+        assert!(!frame(&mut new_display(), None, area.center()).1);
+    }
+
+    #[test]
+    fn gui_error_display() {
+        extern crate std;
+        use std::string::ToString;
+
+        let err = GuiError::NoHorizontalSpace {
+            needed: 400,
+            available: 314,
+        };
+        assert_eq!(
+            err.to_string(),
+            "no horizontal space left: needed 400px, 314px available"
+        );
+        assert_eq!(
+            GuiError::draw_error("Couldn't draw Button").to_string(),
+            "draw error: Couldn't draw Button"
         );
+    }
+
+    #[test]
+    fn rounded_rect_clamped_limits_radius() {
+        let rect = Rectangle::new(Point::zero(), Size::new(40, 12));
+        assert_eq!(
+            rounded_rect_clamped(rect, 3).corners.top_left,
+            Size::new(3, 3)
+        );
+        assert_eq!(
+            rounded_rect_clamped(rect, 8).corners.top_left,
+            Size::new(6, 6)
+        );
+        assert_eq!(
+            rounded_rect_clamped(rect, u32::MAX).corners.bottom_right,
+            Size::new(6, 6)
+        );
+    }
+
+    #[test]
+    fn rounded_rect_clamped_small_button_has_no_artifacts() {
+        // a 10x10 button with radius 8 must render like a fully rounded 10x10 button
+        let rect = Rectangle::new(Point::new(1, 1), Size::new(10, 10));
+        let style = PrimitiveStyleBuilder::new()
+            .fill_color(BinaryColor::On)
+            .build();
+
+        let mut display = MockDisplay::new();
+        rounded_rect_clamped(rect, 8)
+            .into_styled(style)
+            .draw(&mut display)
+            .unwrap();
 
-        let placer = Placer::new(
-            bounds.size,
-            true,
-            Align(HorizontalAlign::Left, VerticalAlign::Top),
-        );
+        let mut expected = MockDisplay::new();
+        RoundedRectangle::with_equal_corners(rect, Size::new(5, 5))
+            .into_styled(style)
+            .draw(&mut expected)
+            .unwrap();
 
-        self.painter.with_subpainter(|painter| {
-            let mut sub_ui = Ui {
-                painter,
-                bounds,
-                style: self.style,
-                interact: self.interact,
-                placer,
-                cleared: false,
-                debug_color: self.debug_color,
-            };
-            (f)(&mut sub_ui)
-        })?;
+        display.assert_eq(&expected);
+        // the shape must be symmetric
+        for y in 0..12 {
+            for x in 0..12 {
+                let p = display.get_pixel(Point::new(x, y));
+                assert_eq!(p, display.get_pixel(Point::new(11 - x, y)), "({x}, {y})");
+                assert_eq!(p, display.get_pixel(Point::new(x, 11 - y)), "({x}, {y})");
+            }
+        }
+    }
 
-        Ok(())
+    #[test]
+    fn keys_are_consumed_in_order() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        for key in [KeyEvent::Char('a'), KeyEvent::Left, KeyEvent::Char('b')] {
+            ui.push_key(key).unwrap();
+        }
+
+        // consumed in a sub-ui, by a text-accepting "widget"
+        let mut typed = heapless::String::<4>::new();
+        ui.sub_ui(|ui| {
+            ui.consume_keys(|key| match key {
+                KeyEvent::Char(c) => typed.push(c).is_ok(),
+                _ => false,
+            });
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(typed, "ab");
+        assert!(ui.pending_keys().eq([KeyEvent::Left]));
+
+        ui.clear_keys();
+        assert_eq!(ui.pending_keys().count(), 0);
+        for _ in 0..KEY_QUEUE_LEN {
+            ui.push_key(KeyEvent::Enter).unwrap();
+        }
+        assert_eq!(ui.push_key(KeyEvent::Up), Err(KeyEvent::Up));
     }
 
-    /// Creates a sub-[Ui] that shares the same bounds as the parent [Ui].
-    ///
-    /// Changes to the sub-[Ui]'s layout are reflected in the parent.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] indicating success or error.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.sub_ui(|sub_ui| {
-    ///     sub_ui.add(widget);
-    ///     Ok(())
-    /// }).unwrap();
-    /// ```
-    pub fn sub_ui<F>(&mut self, f: F) -> GuiResult<()>
-    where
-        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
-    {
-        self.painter.with_subpainter(|painter| {
-            let mut sub_ui = Ui {
-                painter,
-                bounds: self.bounds,
-                style: self.style,
-                interact: self.interact,
-                placer: self.placer.clone(),
-                cleared: false,
-                debug_color: self.debug_color,
-            };
-            let res = (f)(&mut sub_ui);
-            self.placer = sub_ui.placer;
-            res
-        })?;
+    /// Adds a list of `rows` labels, returning the area of each row
+    fn list(ui: &mut Ui<MockDisplay<Rgb565>, Rgb565>, rows: usize) -> heapless::Vec<Rectangle, 5> {
+        use crate::label::Label;
+        use embedded_graphics::mono_font::ascii;
 
-        Ok(())
+        (0..rows)
+            .map(|_| {
+                ui.add(Label::new("#").with_font(ascii::FONT_4X6))
+                    .internal
+                    .area
+            })
+            .collect()
     }
 
-    /// Creates a right-side panel sub-[Ui] with the specified width.
-    ///
-    /// If `allow_smaller` is false, an error is returned if there is insufficient space.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] indicating success or error.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.right_panel_ui(100, false, |sub_ui| {
-    ///     sub_ui.add(widget);
-    ///     Ok(())
-    /// }).unwrap();
-    /// ```
-    pub fn right_panel_ui<F>(&mut self, width: u32, allow_smaller: bool, f: F) -> GuiResult<()>
-    where
-        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
-    {
-        let bounds = self.placer.bounds;
-        let y = self.placer.pos.y as u32;
-        let max_width = bounds.width - self.placer.pos.x as u32;
-        let max_height = bounds.height - y;
+    #[test]
+    fn clear_unused_erases_removed_rows() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut style = medsize_rgb565_style();
+        style.default_widget_height = 6;
+        let mut used = UsedArea::new();
 
-        if width > max_width && !allow_smaller {
-            return Err(GuiError::BoundsError);
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        ui.clear_background().unwrap();
+        let five = list(&mut ui, 5);
+        ui.clear_unused(&mut used).unwrap();
+        assert_eq!(used.area(), union(Some(five[0]), five[4]));
+
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        list(&mut ui, 3);
+        ui.clear_unused(&mut used).unwrap();
+        for row in &five[3..] {
+            assert!(row
+                .points()
+                .all(|p| display.get_pixel(p) == Some(style.background_color)));
         }
 
-        self.placer.bounds.width -= min(width, max_width);
+        // growing again: nothing to erase
+        let mut ui = Ui::new_fullscreen(&mut display, style);
+        list(&mut ui, 5);
+        let draw_calls = ui.perf().draw_calls;
+        ui.clear_unused(&mut used).unwrap();
+        assert_eq!(ui.perf().draw_calls, draw_calls);
+    }
 
-        let area = Rectangle::new(
-            Point::new((bounds.width - min(width, max_width)) as i32, y as i32),
-            Size::new(
-                bounds.width - (bounds.width - min(width, max_width)),
-                max_height,
-            ),
-        );
+    #[test]
+    fn clear_unused_skips_cleared_frames() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut used = UsedArea::new();
 
-        self.unchecked_sub_ui(area, f)
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.add(Spacer::new(Size::new(20, 40)));
+        ui.clear_unused(&mut used).unwrap();
+
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.clear_background().unwrap();
+        ui.add(Spacer::new(Size::new(20, 10)));
+        let draw_calls = ui.perf().draw_calls;
+        ui.clear_unused(&mut used).unwrap();
+        assert_eq!(ui.perf().draw_calls, draw_calls);
+        assert_eq!(used.area().unwrap().size, Size::new(20, 10));
     }
 
-    /// Creates a centered sub-[Ui] panel with the specified width and height.
-    ///
-    /// The panel is centered within the current bounds. An error is returned if the dimensions exceed the available space.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] indicating success or error.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.central_centered_panel_ui(200, 150, |sub_ui| {
-    ///     sub_ui.add(widget);
-    ///     Ok(())
-    /// }).unwrap();
-    /// ```
-    pub fn central_centered_panel_ui<F>(&mut self, width: u32, height: u32, f: F) -> GuiResult<()>
-    where
-        F: FnOnce(&mut Ui<DRAW, COL>) -> GuiResult<()>,
-    {
-        let bounds = self.placer.bounds;
-        let max_width = bounds.width;
-        let max_height = bounds.height;
+    #[test]
+    fn clear_area_clips_and_marks_dirty() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.clear_area(Rectangle::new(Point::new(50, -10), Size::new(100, 20)))
+            .unwrap();
+        let cleared = Rectangle::new(Point::new(50, 0), Size::new(14, 10));
+        assert_eq!(ui.perf().dirty_area, Some(cleared));
+
+        // nothing left below the Ui
+        ui.clear_below(64).unwrap();
+        assert_eq!(ui.perf().dirty_area, Some(cleared));
+        assert_eq!(display.affected_area(), cleared);
+    }
 
-        if width > max_width {
-            return Err(GuiError::BoundsError);
+    #[test]
+    fn background_painter_is_clipped_to_the_cleared_area() {
+        // fills everything it gets, not only the area
+        fn paint(
+            target: &mut Clipped<'_, MockDisplay<Rgb565>>,
+            _area: Rectangle,
+        ) -> Result<(), core::convert::Infallible> {
+            target.clear(Rgb565::RED)
         }
 
-        if height > max_height {
-            return Err(GuiError::BoundsError);
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_background_painter(paint);
+        let area = Rectangle::new(Point::new(10, 20), Size::new(30, 5));
+        ui.clear_area(area).unwrap();
+        assert_eq!(ui.perf().dirty_area, Some(area));
+        assert_eq!(display.affected_area(), area);
+        assert_eq!(display.get_pixel(Point::new(10, 20)), Some(Rgb565::RED));
+    }
+
+    #[test]
+    fn clear_below_redraws_later_widgets() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<2>::new();
+
+        let mut redraws = heapless::Vec::<u32, 3>::new();
+        for clear in [false, false, true] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            let top = ui.add(Button::new("A").smartstate(smartstates.nxt()));
+            if clear {
+                ui.clear_below(top.internal.area.bottom_right().unwrap().y + 1)
+                    .unwrap();
+            }
+            ui.add(Button::new("B").smartstate(smartstates.nxt()));
+            redraws.push(ui.perf().widgets_redrawn).unwrap();
         }
+        // only the button below the cleared area redraws
+        assert_eq!(redraws.as_slice(), &[2, 0, 1]);
+    }
 
-        self.placer.bounds.width -= min(width, max_width);
-        self.placer.bounds.height -= min(height, max_height);
+    #[test]
+    fn frame_clock_provides_delta() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        let mut clock = FrameClock::new();
 
-        let area = Rectangle::new(
-            Point::new(
-                ((bounds.width - width) / 2) as i32,
-                ((bounds.height - height) / 2) as i32,
-            ),
-            Size::new(width, height),
+        let mut deltas = heapless::Vec::<Option<u32>, 4>::new();
+        for now in [100, 116, 150] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            clock.begin_frame(&mut ui, now);
+            assert_eq!(ui.now(), Some(now));
+            deltas.push(ui.dt()).unwrap();
+        }
+        assert_eq!(deltas.as_slice(), &[Some(0), Some(16), Some(34)]);
+
+        // without a clock, there's no frame time
+        let ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        assert_eq!((ui.now(), ui.dt()), (None, None));
+    }
+
+    #[test]
+    fn style_patches_compose_and_repaint() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<1>::new();
+        let base = medsize_rgb565_style();
+        let small = StylePatch::new().font(embedded_graphics::mono_font::ascii::FONT_6X10);
+
+        let mut redrawn = heapless::Vec::<bool, 4>::new();
+        for patched in [false, false, true, true] {
+            let mut ui = Ui::new_fullscreen(&mut display, base);
+            smartstates.restart_counter();
+            let patch = if patched { small } else { StylePatch::new() };
+            ui.with_style(patch, |ui| {
+                ui.add(Button::new("Hi").smartstate(smartstates.nxt()));
+            });
+            redrawn.push(ui.perf().widgets_redrawn == 1).unwrap();
+        }
+        assert_eq!(redrawn.as_slice(), &[true, false, true, false]);
+
+        // nested patches compose, and popping restores the outer style
+        let mut ui = Ui::new_fullscreen(&mut display, base);
+        assert!(ui.push_style(small));
+        let outer_key = ui.style_key();
+        assert!(ui.push_style(StylePatch::new().default_widget_height(7)));
+        assert_eq!(ui.style().default_font.character_size, Size::new(6, 10));
+        assert_eq!(ui.style().default_widget_height, 7);
+        assert_ne!(ui.style_key(), outer_key);
+        assert!(ui.pop_style());
+        assert_eq!(ui.style_key(), outer_key);
+        assert_eq!(ui.style().default_widget_height, base.default_widget_height);
+        assert!(ui.pop_style());
+        assert!(!ui.pop_style());
+        assert_eq!(ui.style_key(), 0);
+        assert_eq!(
+            ui.style().default_font.character_size,
+            base.default_font.character_size
         );
+    }
 
-        self.unchecked_sub_ui(area, f)
+    #[test]
+    fn item_spacing_is_live_and_repaints_moved_widgets() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<2>::new();
+
+        let mut redrawn = heapless::Vec::<u32, 4>::new();
+        for spacing in [
+            Spacing::compact(),
+            Spacing::compact(),
+            Spacing::comfortable(),
+        ] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            ui.set_item_spacing(spacing.item_spacing);
+            let first = ui.add(Button::new("A").smartstate(smartstates.nxt()));
+            let second = ui.add(Button::new("B").smartstate(smartstates.nxt()));
+            let gap =
+                second.internal.area.top_left.y - first.internal.area.bottom_right().unwrap().y;
+            assert_eq!(gap as u32, spacing.item_spacing.height + 1);
+            redrawn.push(ui.perf().widgets_redrawn).unwrap();
+        }
+        assert_eq!(redrawn.as_slice(), &[2, 0, 1]);
+
+        // setting the same spacing again changes nothing
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        let spacing = ui.style().spacing.item_spacing;
+        assert_eq!(ui.set_item_spacing(spacing), spacing);
+        assert_eq!(ui.style_key(), 0);
     }
-}
 
-// -- Debug drawing methods --
-impl<COL, DRAW> Ui<'_, DRAW, COL>
-where
-    DRAW: DrawTarget<Color = COL>,
-    COL: PixelColor,
-{
-    /// Draws a debug outline around the [Ui]'s bounds using the specified color.
-    ///
-    /// ## Returns
-    ///
-    /// A [GuiResult] indicating success or error.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.draw_bounds_debug(Rgb565::RED).unwrap();
-    /// ```
-    pub fn draw_bounds_debug(&mut self, color: COL) -> GuiResult<()> {
-        let bounds = self.bounds;
-        bounds
-            .draw_styled(
-                &PrimitiveStyleBuilder::new()
-                    .stroke_color(color)
-                    .stroke_width(1)
-                    .build(),
-                &mut self.painter,
-            )
-            .map_err(|_| GuiError::DrawError(Some("Couldn't draw bounds")))
+    #[cfg(feature = "checkbox")]
+    #[test]
+    fn feedback_handler_gets_the_events() {
+        use crate::checkbox::Checkbox;
+        use std::sync::Mutex;
+
+        static EVENTS: Mutex<heapless::Vec<FeedbackEvent, 8>> = Mutex::new(heapless::Vec::new());
+        fn record(event: FeedbackEvent) {
+            EVENTS.lock().unwrap().push(event).unwrap();
+        }
+
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<2>::new();
+        let mut checked = false;
+        let button = Point::new(10, 10);
+        for interaction in [
+            Interaction::Hover(button),
+            Interaction::Click(button),
+            Interaction::Drag(button),
+            Interaction::Release(button),
+            // the checkbox is in the second row
+            Interaction::Release(Point::new(5, 35)),
+        ] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            ui.set_feedback_handler(record);
+            ui.interact(interaction);
+            smartstates.restart_counter();
+            ui.add(Button::new("Beep").smartstate(smartstates.nxt()));
+            ui.add(Checkbox::new(&mut checked).smartstate(smartstates.nxt()));
+        }
+        assert!(checked);
+        assert_eq!(
+            EVENTS.lock().unwrap().as_slice(),
+            &[
+                FeedbackEvent::Press,
+                FeedbackEvent::Release,
+                FeedbackEvent::ValueChanged
+            ]
+        );
     }
 
-    /// Enables debug drawing of widget bounds in the [Ui] using the specified color.
-    ///
-    /// Once set, all added widgets will have their bounds outlined in this color for debugging.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_graphics::pixelcolor::Rgb565;
-    /// # use embedded_graphics_simulator::{SimulatorDisplay, OutputSettingsBuilder, Window};
-    /// # use kolibri_embedded_gui::style::medsize_rgb565_style;
-    /// # use kolibri_embedded_gui::ui::Ui;
-    /// # use embedded_graphics::prelude::*;
-    /// # use embedded_graphics::primitives::Rectangle;
-    /// # use embedded_iconoir::prelude::*;
-    /// # use kolibri_embedded_gui::ui::*;
-    /// # use kolibri_embedded_gui::label::*;
-    /// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
-    /// # let output_settings = OutputSettingsBuilder::new().build();
-    /// # let mut window = Window::new("Kolibri Example", &output_settings);
-    /// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
-    /// # let mut widget = Label::new("Hi");
-    /// ui.draw_widget_bounds_debug(Rgb565::GREEN);
-    /// ```
-    pub fn draw_widget_bounds_debug(&mut self, color: COL) {
-        self.debug_color = Some(color);
+    #[test]
+    fn response_exposes_area_and_point() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.interact(Interaction::Release(Point::new(8, 8)));
+
+        let response = ui.add(Button::new("A"));
+        assert_eq!(response.area(), response.internal.area);
+        assert_eq!(response.interaction_point(), Some(Point::new(8, 8)));
+        let ResponseCtx {
+            area,
+            point,
+            clicked,
+            ..
+        } = response.ctx();
+        assert!(clicked && area.contains(point.unwrap()));
+
+        // not interacted with
+        let response = ui.add(Button::new("B"));
+        assert_eq!(response.interaction_point(), None);
+        assert!(response.area().top_left.y > area.bottom_right().unwrap().y);
+    }
+
+    #[cfg(feature = "indicators")]
+    #[test]
+    fn animated_widgets_request_repaints() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.sub_ui(|ui| {
+            ui.add(crate::spinner::Spinner::new());
+            // static without a frame time
+            assert!(!ui.needs_repaint());
+            ui.begin_frame(0);
+            ui.add(crate::spinner::Spinner::new());
+            Ok(())
+        })
+        .unwrap();
+        assert!(ui.finish_frame().needs_repaint);
+    }
+
+    #[test]
+    fn frames_without_smartstate_changes_have_no_changes() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<1>::new();
+
+        let mut changes = heapless::Vec::<bool, 3>::new();
+        for hover in [None, None, Some(Point::new(8, 8))] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            if let Some(point) = hover {
+                ui.interact(Interaction::Hover(point));
+            }
+            smartstates.restart_counter();
+            ui.add(Button::new("A").smartstate(smartstates.nxt()));
+            changes.push(ui.frame_had_changes()).unwrap();
+        }
+        assert_eq!(changes.as_slice(), &[true, false, true]);
+    }
+
+    #[test]
+    fn touch_ignores_hover() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<1>::new();
+
+        let mut redrawn = heapless::Vec::<u32, 4>::new();
+        for kind in [PointerKind::Touch, PointerKind::Touch, PointerKind::Mouse] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            ui.set_pointer_kind(kind);
+            ui.interact(Interaction::Hover(Point::new(8, 8)));
+            smartstates.restart_counter();
+            let response = ui.add(Button::new("A").smartstate(smartstates.nxt()));
+            assert_eq!(
+                response.internal.interaction == Interaction::None,
+                kind == PointerKind::Touch
+            );
+            redrawn.push(ui.perf().widgets_redrawn).unwrap();
+        }
+        // the hover look is only drawn for the mouse
+        assert_eq!(redrawn.as_slice(), &[1, 0, 1]);
+    }
+
+    #[test]
+    fn responses_report_the_source_of_the_interaction() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let release = Interaction::Release(Point::new(8, 8));
+
+        // the pointer kind, unless the source is given
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.set_pointer_kind(PointerKind::Touch);
+        ui.interact(release);
+        let response = ui.add(Button::new("A"));
+        assert!(response.clicked());
+        assert_eq!(response.source(), Some(InteractionSource::Touch));
+        // widgets without an interaction have no source
+        assert_eq!(ui.add(Button::new("B")).source(), None);
+
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.interact_from(release, InteractionSource::Button);
+        let response = ui.add(Button::new("A"));
+        assert!(response.clicked());
+        assert_eq!(response.source(), Some(InteractionSource::Button));
+
+        // the focused widget is activated by the Ui
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+        ui.interact(release);
+        ui.set_focus(Some(1));
+        ui.activate_focus();
+        let pointed = ui.add(Button::new("A"));
+        let focused = ui.add(Button::new("B"));
+        assert!(pointed.clicked() && focused.clicked());
+        assert_eq!(pointed.source(), Some(InteractionSource::Mouse));
+        assert_eq!(focused.source(), Some(InteractionSource::Synthetic));
     }
 }
@@ -0,0 +1,383 @@
+//! # Value Label
+//!
+//! A [ValueLabel] displays a number, e.g. a sensor readout. It formats the value itself (in
+//! fixed point, without float formatting or libm) and tracks the displayed value in its
+//! smartstate, so a readout updated at 50Hz only redraws when the displayed digits change.
+//!
+//! This module is only available with the `indicators` feature (on by default).
+//!
+//! With [ValueLabel::width_chars], the label has a fixed width and the value is right-aligned
+//! within it, so columns of numbers don't shift when the number of digits changes.
+//!
+//! Raw integer readings in a smaller unit (e.g. an ADC value in millivolts) are shown with
+//! [Scaled], which inserts the decimal point with integer math only.
+//!
+//! For durations, byte sizes or SI prefixes, format the value with the helpers in
+//! [format](crate::format) and show it with [Label::new_fmt](crate::label::Label::new_fmt).
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstates = SmartstateProvider::<20>::new();
+//! let temperature: f32 = 21.537;
+//! // "   21.5 °C"
+//! ui.add(
+//!     ValueLabel::new(&temperature, 1)
+//!         .unit(" °C")
+//!         .width_chars(10)
+//!         .smartstate(smartstates.nxt()),
+//! );
+//! ```
+
+use crate::format::{write_fixed, Buf};
+use crate::smartstate::{Container, Smartstate};
+use crate::text::{Font, RenderedText, TextRenderer};
+use crate::ui::{GuiError, GuiResult, Response, Ui, Widget};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::mono_font::MonoFont;
+use embedded_graphics::pixelcolor::PixelColor;
+
+/// The maximum number of decimal places of a [ValueLabel]
+pub const MAX_PRECISION: u8 = 9;
+
+/// A number that a [ValueLabel] can display
+pub trait LabelValue {
+    /// Returns the value scaled by `10^precision` and rounded to the nearest integer
+    /// (saturating), or `None` if the value isn't finite.
+    fn to_fixed(&self, precision: u8) -> Option<i64>;
+}
+
+macro_rules! impl_label_value_int {
+    ($($t:ty),*) => {
+        $(
+            impl LabelValue for $t {
+                fn to_fixed(&self, precision: u8) -> Option<i64> {
+                    let value = i64::try_from(*self).unwrap_or(i64::MAX);
+                    Some(value.saturating_mul(10i64.pow(precision as u32)))
+                }
+            }
+        )*
+    };
+}
+
+impl_label_value_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+macro_rules! impl_label_value_float {
+    ($($t:ty),*) => {
+        $(
+            impl LabelValue for $t {
+                fn to_fixed(&self, precision: u8) -> Option<i64> {
+                    if !self.is_finite() {
+                        return None;
+                    }
+                    let scaled = *self * (10i64.pow(precision as u32) as $t);
+                    // round half away from zero; `as` saturates
+                    Some(if scaled < 0.0 {
+                        (scaled - 0.5) as i64
+                    } else {
+                        (scaled + 0.5) as i64
+                    })
+                }
+            }
+        )*
+    };
+}
+
+impl_label_value_float!(f32, f64);
+
+/// An integer reading in a smaller unit, e.g. `Scaled::new(3275, 3)` for 3275 mV displayed
+/// in volts.
+///
+/// Converting it for a [ValueLabel] with fewer decimal places than the reading rounds half away
+/// from zero, using integer math only: with two decimal places, `3275` shows `3.28` and `-3275`
+/// shows `-3.28`, while `3274` shows `3.27`.
+///
+/// ```no_run
+/// # use embedded_graphics::pixelcolor::Rgb565;
+/// # use embedded_graphics_simulator::SimulatorDisplay;
+/// use kolibri_embedded_gui::prelude::*;
+/// # use embedded_graphics::prelude::*;
+/// use kolibri_embedded_gui::value_label::Scaled;
+/// # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+/// # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+/// # let mut smartstates = SmartstateProvider::<20>::new();
+/// let millivolts: i32 = 3274;
+/// // "3.27 V"
+/// ui.add(
+///     ValueLabel::new(&Scaled::new(millivolts, 3), 2)
+///         .unit(" V")
+///         .smartstate(smartstates.nxt()),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Scaled {
+    raw: i32,
+    decimals: u8,
+}
+
+impl Scaled {
+    /// Creates the value `raw / 10^decimals`.
+    pub const fn new(raw: i32, decimals: u8) -> Self {
+        Self { raw, decimals }
+    }
+}
+
+impl LabelValue for Scaled {
+    fn to_fixed(&self, precision: u8) -> Option<i64> {
+        let raw = self.raw as i64;
+        if precision >= self.decimals {
+            let factor = 10i64.checked_pow((precision - self.decimals) as u32);
+            return Some(factor.map_or(i64::MAX, |factor| raw.saturating_mul(factor)));
+        }
+        let Some(divisor) = 10i64.checked_pow((self.decimals - precision) as u32) else {
+            // far below the displayed precision
+            return Some(0);
+        };
+        // round half away from zero
+        let (quotient, remainder) = (raw / divisor, raw % divisor);
+        Some(if 2 * remainder.abs() >= divisor {
+            quotient + raw.signum()
+        } else {
+            quotient
+        })
+    }
+}
+
+/// A label displaying a number, see the [module documentation](self).
+pub struct ValueLabel<'a, V: LabelValue> {
+    value: &'a V,
+    precision: u8,
+    unit: &'a str,
+    width_chars: Option<u8>,
+    force_sign: bool,
+    font: Option<MonoFont<'a>>,
+    smartstate: Container<'a, Smartstate>,
+}
+
+impl<'a, V: LabelValue> ValueLabel<'a, V> {
+    /// Creates a label for `value`, with `precision` decimal places (at most [MAX_PRECISION]).
+    ///
+    /// Non-finite floats are displayed as `--`.
+    pub fn new(value: &'a V, precision: u8) -> Self {
+        Self {
+            value,
+            precision: precision.min(MAX_PRECISION),
+            unit: "",
+            width_chars: None,
+            force_sign: false,
+            font: None,
+            smartstate: Container::empty(),
+        }
+    }
+
+    /// Sets a unit that is appended to the value as is (include a leading space if you want one).
+    pub fn unit(mut self, unit: &'a str) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Gives the label the width of `chars` characters (of the widest digit), with the value
+    /// (and unit) right-aligned within it. Values that don't fit make the label wider.
+    pub fn width_chars(mut self, chars: u8) -> Self {
+        self.width_chars = Some(chars);
+        self
+    }
+
+    /// Shows a `+` in front of positive values, e.g. for offsets. Zero has no sign.
+    pub fn force_sign(mut self, force_sign: bool) -> Self {
+        self.force_sign = force_sign;
+        self
+    }
+
+    /// Sets a custom font. The style's default font is used otherwise.
+    pub fn with_font(mut self, font: MonoFont<'a>) -> Self {
+        self.font = Some(font);
+        self
+    }
+
+    /// Adds a smartstate, so that the label only redraws when the displayed value changes.
+    pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+        self.smartstate.set(smartstate);
+        self
+    }
+}
+
+impl<V: LabelValue> Widget for ValueLabel<'_, V> {
+    fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+        &mut self,
+        ui: &mut Ui<DRAW, COL>,
+    ) -> GuiResult<Response> {
+        let fixed = self.value.to_fixed(self.precision);
+
+        // format
+        let mut text = Buf::<40>::new();
+        format_value(&mut text, fixed, self.precision, self.force_sign);
+        text.push_str(self.unit);
+
+        // get size
+        let font = Font::resolve(self.font, ui.style());
+        let text_size = font.measure(text.as_str());
+        let min_width = self
+            .width_chars
+            .map(|chars| font.measure("0").width * chars as u32)
+            .unwrap_or(0);
+        let size = Size::new(text_size.width.max(min_width), text_size.height);
+
+        // allocate space
+        let iresponse = ui.allocate_space(size)?;
+
+        // check smartstate: the displayed value rather than the text
+        let mut state = Smartstate::empty();
+        state.set_state_hashed(&(
+            fixed,
+            self.precision,
+            self.force_sign,
+            self.unit,
+            iresponse.area.size,
+        ));
+        state.mix(ui.style_key());
+        let redraw = !self.smartstate.eq_option(&Some(state)) || ui.invalidated(iresponse.area);
+        if redraw {
+            trace!(
+                "ValueLabel smartstate: {} -> {}",
+                self.smartstate.clone_inner(),
+                state
+            );
+        }
+        self.smartstate.modify(|st| *st = state);
+
+        // draw
+        if redraw {
+            // right-align, center vertically
+            let area = iresponse.area;
+            let position = area.top_left
+                + Point::new(
+                    (area.size.width - text_size.width) as i32,
+                    (area.size.height - text_size.height) as i32 / 2,
+                );
+
+            ui.start_drawing(&area);
+            if !ui.cleared() {
+                ui.clear_area(area)?;
+            }
+            ui.draw(&RenderedText::new(
+                &font,
+                text.as_str(),
+                position,
+                ui.style().text_color,
+            ))
+            .map_err(|_| GuiError::DrawError(Some("Couldn't draw ValueLabel")))?;
+            ui.finalize()?;
+        }
+
+        Ok(Response::new(iresponse).set_redraw(redraw))
+    }
+
+    fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+        self.smartstate.get_mut()
+    }
+}
+
+/// Formats the fixed-point value of a [ValueLabel], `--` if there is none
+fn format_value<const N: usize>(buf: &mut Buf<N>, fixed: Option<i64>, precision: u8, sign: bool) {
+    match fixed {
+        Some(fixed) => {
+            if sign && fixed > 0 {
+                buf.push_str("+");
+            }
+            write_fixed(buf, fixed, precision);
+        }
+        None => buf.push_str("--"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smartstate::SmartstateProvider;
+    use crate::style::medsize_rgb565_style;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::mono_font::ascii;
+    use embedded_graphics::pixelcolor::Rgb565;
+
+    fn fixed(value: impl LabelValue, precision: u8) -> Buf<40> {
+        let mut buf = Buf::new();
+        format_value(&mut buf, value.to_fixed(precision), precision, false);
+        buf
+    }
+
+    #[test]
+    fn formats_fixed_point() {
+        assert_eq!(fixed(21.537f32, 1).as_str(), "21.5");
+        assert_eq!(fixed(21.55f64, 1).as_str(), "21.6");
+        assert_eq!(fixed(-0.04f32, 1).as_str(), "0.0");
+        assert_eq!(fixed(-3.25f64, 2).as_str(), "-3.25");
+        assert_eq!(fixed(0.5f32, 0).as_str(), "1");
+        assert_eq!(fixed(42u8, 0).as_str(), "42");
+        assert_eq!(fixed(-7i32, 3).as_str(), "-7.000");
+        assert_eq!(fixed(f32::NAN, 2).as_str(), "--");
+    }
+
+    #[test]
+    fn scaled_rounds_half_away_from_zero() {
+        let volts = |mv: i32, precision: u8| fixed(Scaled::new(mv, 3), precision);
+        assert_eq!(volts(3274, 2).as_str(), "3.27");
+        assert_eq!(volts(3275, 2).as_str(), "3.28");
+        assert_eq!(volts(-3274, 2).as_str(), "-3.27");
+        assert_eq!(volts(-3275, 2).as_str(), "-3.28");
+        assert_eq!(volts(-4, 2).as_str(), "0.00");
+        assert_eq!(volts(-5, 2).as_str(), "-0.01");
+        assert_eq!(volts(500, 0).as_str(), "1");
+        assert_eq!(volts(-500, 0).as_str(), "-1");
+        // more decimals than the reading
+        assert_eq!(volts(-1250, 4).as_str(), "-1.2500");
+        assert_eq!(volts(i32::MIN, 1).as_str(), "-2147483.6");
+
+        let mut buf = Buf::<40>::new();
+        format_value(&mut buf, Scaled::new(5, 1).to_fixed(1), 1, true);
+        assert_eq!(buf.as_str(), "+0.5");
+        buf.clear();
+        format_value(&mut buf, Scaled::new(-4, 2).to_fixed(1), 1, true);
+        assert_eq!(buf.as_str(), "0.0");
+    }
+
+    #[test]
+    fn redraws_only_when_displayed_value_changes() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstates = SmartstateProvider::<1>::new();
+
+        let mut redraws = heapless::Vec::<bool, 4>::new();
+        for value in [20.01f32, 20.04, 20.06, 20.06] {
+            let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+            smartstates.restart_counter();
+            let response = ui.add(ValueLabel::new(&value, 1).smartstate(smartstates.nxt()));
+            redraws.push(response.redrawn()).unwrap();
+        }
+        assert_eq!(redraws.as_slice(), &[true, false, true, false]);
+    }
+
+    #[test]
+    fn fixed_width_right_aligns() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+
+        for value in [5, 12345] {
+            let response = ui.add(
+                ValueLabel::new(&value, 0)
+                    .unit("V")
+                    .width_chars(6)
+                    .with_font(ascii::FONT_6X10),
+            );
+            assert_eq!(response.internal.area.size, Size::new(36, 10));
+        }
+    }
+}
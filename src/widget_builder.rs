@@ -0,0 +1,311 @@
+//! # Widget Builder
+//!
+//! A [WidgetBuilder] does what (almost) every widget does when it is drawn: allocate its space,
+//! resolve its look from the interaction, compare its [Smartstate], and draw between
+//! [Ui::start_drawing] and [Ui::finalize] if something changed. What remains for a widget is
+//! the drawing itself, in a closure getting a [DrawContext].
+//!
+//! Start one with [Ui::widget] in [Widget::draw](crate::ui::Widget::draw). See the
+//! [widget_prelude](crate::widget_prelude) for a complete widget.
+//!
+//! ```no_run
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use embedded_graphics_simulator::SimulatorDisplay;
+//! # use kolibri_embedded_gui::prelude::*;
+//! # use embedded_graphics::prelude::*;
+//! # use embedded_graphics::primitives::Circle;
+//! # let mut display = SimulatorDisplay::<Rgb565>::new(Size::new(320, 240));
+//! # let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! # let mut smartstate = Smartstate::empty();
+//! let response = ui
+//!     .widget(Size::new(20, 20))
+//!     .padding(Size::new(2, 2))
+//!     .interactive(true)
+//!     .draw_if_changed(&mut smartstate, 0, |ctx| {
+//!         let content = ctx.content();
+//!         let style = ctx.widget_style();
+//!         ctx.draw(&Circle::new(content.top_left, content.size.width).into_styled(style))
+//!     })?;
+//! # Ok::<(), GuiError>(())
+//! ```
+
+use crate::smartstate::Smartstate;
+use crate::style::Style;
+use crate::ui::{resolve_widget_style, CustomTarget, GuiResult, Interaction, Response, Ui};
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::pixelcolor::PixelColor;
+use embedded_graphics::prelude::{Point, Size};
+use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+use embedded_graphics::Drawable;
+
+/// Draws a widget with a closure, see the [module documentation](self) and [Ui::widget].
+pub struct WidgetBuilder<'u, 'a, DRAW, COL>
+where
+    DRAW: DrawTarget<Color = COL>,
+    COL: PixelColor,
+{
+    ui: &'u mut Ui<'a, DRAW, COL>,
+    size: Size,
+    padding: Size,
+    interactive: bool,
+}
+
+impl<'u, 'a, DRAW, COL> WidgetBuilder<'u, 'a, DRAW, COL>
+where
+    DRAW: DrawTarget<Color = COL>,
+    COL: PixelColor,
+{
+    /// A builder for a widget with content of `size`, see [Ui::widget].
+    pub(crate) fn new(ui: &'u mut Ui<'a, DRAW, COL>, size: Size) -> Self {
+        Self {
+            ui,
+            size,
+            padding: Size::zero(),
+            interactive: false,
+        }
+    }
+
+    /// Adds `padding` on each side of the content, which is part of the widget's area, but not
+    /// of its [content](DrawContext::content).
+    pub fn padding(mut self, padding: Size) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Sets whether the widget reacts to the pointer (off by default).
+    ///
+    /// An interactive widget gets the [interaction](DrawContext::interaction) and the matching
+    /// [widget_style](DrawContext::widget_style), is redrawn when its look changes (normal,
+    /// hovered or pressed), and its [Response] reports whether it was
+    /// [clicked](Response::clicked) or is [down](Response::down). Other widgets are always
+    /// drawn with the normal look, and their [Response] only has the interaction within their
+    /// area.
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Allocates the space and draws the widget with `draw` if it changed: if `state_id` (a
+    /// value describing what the widget shows, e.g. a hash of its data) or its look differ from
+    /// `smartstate`, or its area was invalidated. Without a smartstate, it is drawn every frame.
+    ///
+    /// The area is cleared to the background before `draw` is called, unless it already was
+    /// this frame.
+    ///
+    /// ## Returns
+    ///
+    /// The widget's [Response], which [redraw](Response::redraw) tells whether `draw` was
+    /// called, or the error of allocating the space or of `draw`.
+    pub fn draw_if_changed<'s, F>(
+        self,
+        smartstate: impl Into<Option<&'s mut Smartstate>>,
+        state_id: u32,
+        draw: F,
+    ) -> GuiResult<Response>
+    where
+        F: FnOnce(&mut DrawContext<'_, COL>) -> GuiResult<()>,
+    {
+        let Self {
+            ui,
+            size,
+            padding,
+            interactive,
+        } = self;
+        let smartstate = smartstate.into();
+        let iresponse = ui.allocate_space(size + padding * 2)?;
+        let area = iresponse.area;
+        let interaction = match interactive {
+            true => iresponse.interaction,
+            false => Interaction::None,
+        };
+
+        let mut state = Smartstate::state(state_id);
+        state.mix(ui.style_key());
+        if interactive {
+            state.mix(resolve_widget_style(ui.style(), interaction).1 as u32);
+        }
+        let redraw = smartstate.as_deref() != Some(&state) || ui.invalidated(area);
+        if redraw {
+            ui.start_drawing(&area);
+            if !ui.cleared() {
+                ui.clear_area(area)?;
+            }
+            let (target, style) = ui.custom_target(area);
+            draw(&mut DrawContext {
+                target,
+                style,
+                area,
+                content: Rectangle::new(area.top_left + padding, size),
+                interaction,
+            })?;
+            ui.finalize()?;
+            if let Some(smartstate) = smartstate {
+                *smartstate = state;
+            }
+        }
+
+        let response = Response::new(iresponse).set_redraw(redraw);
+        Ok(match interactive {
+            true => response
+                .set_clicked(interaction.is_release())
+                .set_down(matches!(
+                    interaction,
+                    Interaction::Click(_) | Interaction::Drag(_)
+                )),
+            false => response,
+        })
+    }
+
+    /// Allocates the space and draws the widget with `draw` every frame, like
+    /// [draw_if_changed](Self::draw_if_changed) without a smartstate.
+    ///
+    /// ## Returns
+    ///
+    /// The widget's [Response], or the error of allocating the space or of `draw`.
+    pub fn draw<F>(self, draw: F) -> GuiResult<Response>
+    where
+        F: FnOnce(&mut DrawContext<'_, COL>) -> GuiResult<()>,
+    {
+        self.draw_if_changed(None, 0, draw)
+    }
+}
+
+/// What the closure drawing a [WidgetBuilder]'s widget gets: where to draw, how, and a target
+/// to draw to.
+///
+/// Coordinates are the drawable's, like in [Ui::draw]; drawing outside of the widget's
+/// [area](Self::area) is discarded.
+pub struct DrawContext<'c, COL: PixelColor> {
+    target: CustomTarget<'c, COL>,
+    style: &'c Style<COL>,
+    area: Rectangle,
+    content: Rectangle,
+    interaction: Interaction,
+}
+
+impl<'c, COL: PixelColor> DrawContext<'c, COL> {
+    /// Returns the widget's area, including its padding.
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    /// Returns the widget's content area, i.e. its area without the padding.
+    pub fn content(&self) -> Rectangle {
+        self.content
+    }
+
+    /// Returns the interaction within the widget, or [Interaction::None] if it isn't
+    /// [interactive](WidgetBuilder::interactive).
+    pub fn interaction(&self) -> Interaction {
+        self.interaction
+    }
+
+    /// Returns the [Ui]'s style.
+    pub fn style(&self) -> &Style<COL> {
+        self.style
+    }
+
+    /// Returns the style of the widget's body for its interaction, like the built-in widgets
+    /// use (see [resolve_widget_style]).
+    pub fn widget_style(&self) -> PrimitiveStyle<COL> {
+        resolve_widget_style(self.style, self.interaction).0
+    }
+
+    /// Returns the target to draw to, e.g. for drawing with other crates.
+    pub fn target(&mut self) -> &mut CustomTarget<'c, COL> {
+        &mut self.target
+    }
+
+    /// Draws `item`.
+    ///
+    /// ## Returns
+    ///
+    /// The item's output, or an error if drawing failed.
+    pub fn draw<D: Drawable<Color = COL>>(&mut self, item: &D) -> GuiResult<D::Output> {
+        item.draw(&mut self.target)
+    }
+
+    /// Returns the point of the interaction, if there is one.
+    pub fn pointer(&self) -> Option<Point> {
+        self.interaction.get_point()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::medsize_rgb565_style;
+    use crate::ui::Widget;
+    use embedded_graphics::mock_display::MockDisplay;
+    use embedded_graphics::pixelcolor::Rgb565;
+    use embedded_graphics::prelude::Primitive;
+
+    struct Square<'a> {
+        smartstate: &'a mut Smartstate,
+        value: u32,
+        content: &'a mut Option<Rectangle>,
+    }
+
+    impl Widget for Square<'_> {
+        fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+            &mut self,
+            ui: &mut Ui<DRAW, COL>,
+        ) -> GuiResult<Response> {
+            let content = &mut *self.content;
+            ui.widget(Size::new(10, 10))
+                .padding(Size::new(2, 3))
+                .interactive(true)
+                .draw_if_changed(&mut *self.smartstate, self.value, |ctx| {
+                    *content = Some(ctx.content());
+                    let style = ctx.widget_style();
+                    ctx.draw(&ctx.content().into_styled(style))
+                })
+        }
+    }
+
+    #[test]
+    fn built_widgets_redraw_when_their_state_or_look_changes() {
+        let mut display = MockDisplay::<Rgb565>::new();
+        display.set_allow_overdraw(true);
+        let mut smartstate = Smartstate::empty();
+        let style = medsize_rgb565_style();
+        let mut frame = |value, interaction| {
+            let mut ui = Ui::new_fullscreen(&mut display, style);
+            ui.interact(interaction);
+            let mut content = None;
+            let response = ui.add(Square {
+                smartstate: &mut smartstate,
+                value,
+                content: &mut content,
+            });
+            (response, content)
+        };
+
+        let (response, content) = frame(1, Interaction::None);
+        assert!(response.redraw);
+        assert_eq!(response.area().size.width, 14);
+        let content = content.unwrap();
+        assert_eq!(
+            content.top_left,
+            response.area().top_left + Point::new(2, 3)
+        );
+        assert_eq!(content.size, Size::new(10, 10));
+
+        // unchanged
+        let (response, drawn) = frame(1, Interaction::None);
+        assert!(!response.redraw && drawn.is_none());
+        // the state id changed
+        assert!(frame(2, Interaction::None).0.redraw);
+        // hovering changes the look, hovering elsewhere in it doesn't
+        assert!(frame(2, Interaction::Hover(content.top_left)).0.redraw);
+        assert!(!frame(2, Interaction::Hover(content.center())).0.redraw);
+        let (response, _) = frame(2, Interaction::Click(content.center()));
+        assert!(response.redraw && response.down() && !response.clicked());
+        let (response, _) = frame(2, Interaction::Release(content.center()));
+        assert!(response.clicked());
+        assert_eq!(
+            display.get_pixel(content.center()),
+            Some(style.primary_color)
+        );
+    }
+}
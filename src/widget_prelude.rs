@@ -0,0 +1,110 @@
+//! # Writing Widgets
+//!
+//! Everything needed to write a widget in another crate, imported with
+//! `use kolibri_embedded_gui::widget_prelude::*;`.
+//!
+//! A widget is a struct implementing [Widget]. It is created every frame with what it shows
+//! (and usually a [Smartstate], which remembers what was drawn), and added to the [Ui] with
+//! e.g. [Ui::add], which calls [Widget::draw]. Most widgets do the same things there:
+//!
+//! 1. allocate their space with [Ui::allocate_space], getting the area and the interaction
+//!    within it,
+//! 2. resolve their look from the interaction with [resolve_widget_style],
+//! 3. compare a [Smartstate] built from what they show and their look with the previous one,
+//!    to skip drawing if nothing changed (or the area was [invalidated](Ui::invalidated)),
+//! 4. draw between [Ui::start_drawing] and [Ui::finalize], clearing the area first, and
+//! 5. report clicks in their [Response].
+//!
+//! A [WidgetBuilder] (see [Ui::widget]) does all of this but the drawing, which is done in a
+//! closure with a [DrawContext]. Widgets with unusual needs (e.g. transparency or several
+//! areas) can use the building blocks directly, as the built-in widgets do.
+//!
+//! ## Example: a Progress Dot
+//!
+//! A dot filling up with the progress, which can be clicked:
+//!
+//! ```
+//! use embedded_graphics::primitives::Circle;
+//! use kolibri_embedded_gui::widget_prelude::*;
+//!
+//! /// A dot filling up with the progress, in percent
+//! pub struct ProgressDot<'a> {
+//!     percent: u8,
+//!     smartstate: Container<'a, Smartstate>,
+//! }
+//!
+//! impl<'a> ProgressDot<'a> {
+//!     pub fn new(percent: u8) -> Self {
+//!         Self {
+//!             percent: percent.min(100),
+//!             smartstate: Container::empty(),
+//!         }
+//!     }
+//!
+//!     pub fn smartstate(mut self, smartstate: &'a mut Smartstate) -> Self {
+//!         self.smartstate.set(smartstate);
+//!         self
+//!     }
+//! }
+//!
+//! impl Widget for ProgressDot<'_> {
+//!     fn draw<DRAW: DrawTarget<Color = COL>, COL: PixelColor>(
+//!         &mut self,
+//!         ui: &mut Ui<DRAW, COL>,
+//!     ) -> GuiResult<Response> {
+//!         let diameter = ui.style().default_widget_height - 4;
+//!         let percent = self.percent as u32;
+//!         ui.widget(Size::new_equal(diameter))
+//!             .padding(Size::new_equal(2))
+//!             .interactive(true)
+//!             // the percentage is all the dot shows, the builder adds the look
+//!             .draw_if_changed(self.smartstate.get_mut(), percent, |ctx| {
+//!                 let content = ctx.content();
+//!                 let outline = Circle::new(content.top_left, diameter);
+//!                 ctx.draw(&outline.into_styled(ctx.widget_style()))?;
+//!                 let filled = Circle::with_center(content.center(), diameter * percent / 100);
+//!                 ctx.draw(&filled.into_styled(PrimitiveStyle::with_fill(ctx.style().text_color)))
+//!             })
+//!     }
+//!
+//!     fn smartstate_mut(&mut self) -> Option<&mut Smartstate> {
+//!         self.smartstate.get_mut()
+//!     }
+//! }
+//!
+//! // it is used like any other widget
+//! # use embedded_graphics::mock_display::MockDisplay;
+//! # use embedded_graphics::pixelcolor::Rgb565;
+//! # use kolibri_embedded_gui::style::medsize_rgb565_style;
+//! # let mut display = MockDisplay::<Rgb565>::new();
+//! # display.set_allow_overdraw(true);
+//! let mut smartstate = Smartstate::empty();
+//! let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! let response = ui.add(ProgressDot::new(40).smartstate(&mut smartstate));
+//! assert!(response.redraw);
+//!
+//! // the next frame, it isn't drawn again until the progress changes
+//! let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! assert!(!ui.add(ProgressDot::new(40).smartstate(&mut smartstate)).redraw);
+//! assert!(ui.add(ProgressDot::new(50)).redraw);
+//!
+//! // or it is clicked
+//! let mut ui = Ui::new_fullscreen(&mut display, medsize_rgb565_style());
+//! ui.interact(Interaction::Click(response.area().center()));
+//! assert!(ui.add(ProgressDot::new(40).smartstate(&mut smartstate)).down());
+//! ```
+
+pub use crate::smartstate::{Container, Smartstate};
+pub use crate::style::Style;
+pub use crate::ui::{
+    resolve_widget_style, rounded_rect_clamped, CustomTarget, Gesture, GuiError, GuiResult,
+    Interaction, InternalResponse, PointerState, Response, TypedWidget, Ui, Widget,
+};
+pub use crate::widget_builder::{DrawContext, WidgetBuilder};
+pub use embedded_graphics::draw_target::DrawTarget;
+pub use embedded_graphics::geometry::{Dimensions, Point, Size};
+pub use embedded_graphics::pixelcolor::PixelColor;
+pub use embedded_graphics::primitives::{
+    Primitive, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle,
+};
+pub use embedded_graphics::Drawable;
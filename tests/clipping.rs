@@ -0,0 +1,77 @@
+//! Tests that a [Ui] in a part of the display doesn't draw outside of it.
+
+use embedded_graphics::mono_font::ascii;
+use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics_simulator::SimulatorDisplay;
+use kolibri_embedded_gui::button::Button;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::style::medsize_rgb565_style;
+use kolibri_embedded_gui::ui::{Overflow, Ui};
+
+/// The color of the display outside of the [Ui], e.g. a video feed
+const OUTSIDE: Rgb565 = Rgb565::MAGENTA;
+
+/// Renders an oversized label and a button partially outside of a sub-[Ui] into `bounds`
+fn render(bounds: Rectangle, buffered: bool) -> SimulatorDisplay<Rgb565> {
+    let mut display = SimulatorDisplay::new(Size::new(200, 200));
+    display.clear(OUTSIDE).unwrap();
+    let mut buffer = [Rgb565::BLACK; 100 * 40];
+
+    let mut ui = Ui::new(&mut display, bounds, medsize_rgb565_style());
+    if buffered {
+        ui.set_buffer(&mut buffer);
+    }
+    ui.set_overflow_policy(Overflow::Clip);
+    ui.clear_background().unwrap();
+    ui.add(Label::new("A label far too long for the area").with_font(ascii::FONT_10X20));
+
+    // a sub-Ui reaching past the bottom right corner
+    let sub_bounds = Rectangle::new(bounds.top_left + Point::new(60, 60), Size::new(80, 80));
+    ui.unchecked_sub_ui(sub_bounds, |ui| {
+        ui.add(Button::new("Overflowing"));
+        Ok(())
+    })
+    .unwrap();
+    display
+}
+
+#[test]
+fn nothing_is_drawn_outside_of_the_bounds() {
+    let area = Rectangle::new(Point::new(50, 50), Size::new(100, 100));
+    // where widgets are clipped to, without the window border padding
+    let inner = Rectangle::new(Point::new(53, 53), Size::new(94, 94));
+    // the same Ui, with room to the right and below
+    let unclipped = Rectangle::new(area.top_left, Size::new(150, 150));
+
+    for buffered in [false, true] {
+        let display = render(area, buffered);
+        let reference = render(unclipped, buffered);
+
+        for point in display.bounding_box().points() {
+            let pixel = display.get_pixel(point);
+            if inner.contains(point) {
+                // cut off, not moved
+                assert_eq!(
+                    pixel,
+                    reference.get_pixel(point),
+                    "{point:?} (buffered: {buffered})"
+                );
+            } else if !area.contains(point) {
+                assert_eq!(pixel, OUTSIDE, "{point:?} (buffered: {buffered})");
+            }
+        }
+        // the label reaches the right edge, and so does the button
+        let background = medsize_rgb565_style().background_color;
+        let drawn = |r: Rectangle| r.points().any(|p| display.get_pixel(p) != background);
+        assert!(drawn(Rectangle::new(
+            Point::new(137, 53),
+            Size::new(10, 20)
+        )));
+        assert!(drawn(Rectangle::new(
+            Point::new(137, 113),
+            Size::new(10, 25)
+        )));
+    }
+}
@@ -0,0 +1,112 @@
+//! Golden image tests for the [IconButton] states, see [kolibri_test].
+
+mod kolibri_test;
+
+use embedded_graphics::prelude::*;
+use kolibri_embedded_gui::icon::{RawIcon, RawIconData};
+use kolibri_embedded_gui::iconbutton::{IconButton, LabelPosition};
+use kolibri_embedded_gui::style::{
+    medsize_light_rgb565_style, medsize_rgb565_style, WidgetContext,
+};
+use kolibri_embedded_gui::ui::Interaction;
+use kolibri_test::{assert_snapshot, render};
+
+/// Inside the button, which is placed at the top left of the Ui
+const INSIDE: Point = Point::new(10, 10);
+
+/// A 24x24 arrow pointing down onto a bar. The icon is part of the test, so the golden images
+/// don't change with the icon sets.
+struct Arrow;
+
+impl RawIconData for Arrow {
+    const DATA: &'static [u8] = &arrow();
+    const WIDTH: u32 = 24;
+}
+
+const fn arrow() -> [u8; 3 * 24] {
+    let mut data = [0; 3 * 24];
+    let mut y = 0;
+    while y < 24 {
+        let mut x = 0;
+        while x < 24 {
+            let set = match y {
+                // shaft
+                2..=11 => x >= 10 && x < 14,
+                // head, narrowing by one pixel per side and row
+                12..=18 => x + 12 >= 4 + y && x < 32 - y,
+                // bar
+                20..=21 => x >= 3 && x < 21,
+                _ => false,
+            };
+            if set {
+                data[y * 3 + x / 8] |= 0x80 >> (x % 8);
+            }
+            x += 1;
+        }
+        y += 1;
+    }
+    data
+}
+
+type Button<'a> = IconButton<'a, RawIcon<Arrow>>;
+
+/// Renders the button returned by `button` in the dark and light theme, with `interaction`.
+fn snapshot(name: &str, interaction: Interaction, button: impl Fn() -> Button<'static>) {
+    for (theme, style) in [
+        ("dark", medsize_rgb565_style()),
+        ("light", medsize_light_rgb565_style()),
+    ] {
+        let display = render(Size::new(96, 64), style, |ui| {
+            ui.interact(interaction);
+            ui.add(button());
+        });
+        assert_snapshot(&format!("icon_button_{name}_{theme}"), &display, 0);
+    }
+}
+
+/// Snapshots the button returned by `button` without a label, and with the label below and to
+/// the right of the icon.
+fn snapshot_labels(name: &str, interaction: Interaction, button: impl Fn() -> Button<'static>) {
+    snapshot(name, interaction, &button);
+    for (position, suffix) in [
+        (LabelPosition::Below, "label_below"),
+        (LabelPosition::Right, "label_right"),
+    ] {
+        snapshot(&format!("{name}_{suffix}"), interaction, || {
+            button().label("Get").label_position(position)
+        });
+    }
+}
+
+#[test]
+fn icon_button_normal() {
+    snapshot_labels("normal", Interaction::None, Button::new_from_type);
+}
+
+#[test]
+fn icon_button_hover() {
+    snapshot_labels("hover", Interaction::Hover(INSIDE), Button::new_from_type);
+}
+
+#[test]
+fn icon_button_pressed() {
+    snapshot_labels("pressed", Interaction::Drag(INSIDE), Button::new_from_type);
+}
+
+#[test]
+fn icon_button_disabled() {
+    // looks the same when hovered
+    for interaction in [Interaction::None, Interaction::Hover(INSIDE)] {
+        snapshot_labels("disabled", interaction, || {
+            Button::new_from_type().enabled(false)
+        });
+    }
+}
+
+#[test]
+fn icon_button_primary() {
+    let primary = || Button::new_from_type().context(WidgetContext::Primary);
+    snapshot_labels("primary", Interaction::None, primary);
+    snapshot_labels("primary_hover", Interaction::Hover(INSIDE), primary);
+    snapshot_labels("primary_pressed", Interaction::Drag(INSIDE), primary);
+}
@@ -0,0 +1,185 @@
+//! # Snapshot testing helpers
+//!
+//! Renders Ui code to a [SimulatorDisplay] and compares the result against a checked-in golden
+//! image in `tests/snapshots/`, to catch visual regressions when refactoring widget drawing.
+//!
+//! Golden images are binary PPM (`P6`) files, so they can be opened with most image viewers
+//! and diffed without any extra dependencies.
+//!
+//! - With `KOLIBRI_BLESS=1` set, golden images are written (or overwritten) with the current
+//!   output, and the check passes. Review and commit them.
+//! - Otherwise, a missing golden image fails the test, so a snapshot can't pass without one.
+//! - On a mismatch, the actual image and a diff image (differing pixels in red) are dumped to
+//!   `target/tmp/snapshots/` and the test fails.
+//!
+//! This only runs on the host, as part of `cargo test`.
+
+#![allow(dead_code)]
+
+use embedded_graphics::pixelcolor::{Rgb565, Rgb888, RgbColor};
+use embedded_graphics::prelude::*;
+use embedded_graphics_simulator::SimulatorDisplay;
+use kolibri_embedded_gui::style::Style;
+use kolibri_embedded_gui::ui::Ui;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An RGB image, as stored in the golden files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub size: Size,
+    pub pixels: Vec<Rgb888>,
+}
+
+impl Image {
+    /// Copies the pixels of `display`.
+    pub fn from_display(display: &SimulatorDisplay<Rgb565>) -> Self {
+        let size = display.size();
+        let pixels = (0..size.height as i32)
+            .flat_map(|y| (0..size.width as i32).map(move |x| Point::new(x, y)))
+            .map(|p| display.get_pixel(p).into())
+            .collect();
+        Image { size, pixels }
+    }
+
+    /// Encodes the image as a binary PPM file.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.size.width, self.size.height).into_bytes();
+        for px in &self.pixels {
+            out.extend_from_slice(&[px.r(), px.g(), px.b()]);
+        }
+        out
+    }
+
+    /// Decodes a binary PPM file as written by [Image::to_ppm].
+    ///
+    /// ## Returns
+    ///
+    /// `None` if the file isn't a PPM file with a max value of 255.
+    pub fn from_ppm(data: &[u8]) -> Option<Self> {
+        // header: magic, width, height, max value, each followed by one whitespace byte
+        let mut fields = Vec::new();
+        let mut start = 0;
+        for (i, b) in data.iter().enumerate() {
+            if b.is_ascii_whitespace() {
+                fields.push(core::str::from_utf8(&data[start..i]).ok()?);
+                start = i + 1;
+                if fields.len() == 4 {
+                    break;
+                }
+            }
+        }
+        let [magic, width, height, max] = fields[..] else {
+            return None;
+        };
+        if magic != "P6" || max != "255" {
+            return None;
+        }
+        let size = Size::new(width.parse().ok()?, height.parse().ok()?);
+        let body = &data[start..];
+        if body.len() != (size.width * size.height * 3) as usize {
+            return None;
+        }
+        let pixels = body
+            .chunks_exact(3)
+            .map(|c| Rgb888::new(c[0], c[1], c[2]))
+            .collect();
+        Some(Image { size, pixels })
+    }
+
+    /// Returns the number of pixels that differ from `other`, and an image highlighting them.
+    ///
+    /// Images of different sizes differ in every pixel.
+    pub fn diff(&self, other: &Image) -> (usize, Image) {
+        if self.size != other.size {
+            return (self.pixels.len().max(other.pixels.len()), self.clone());
+        }
+        let mut count = 0;
+        let pixels = self
+            .pixels
+            .iter()
+            .zip(&other.pixels)
+            .map(|(a, b)| {
+                if a == b {
+                    // dimmed, so the differences stand out
+                    Rgb888::new(a.r() / 4, a.g() / 4, a.b() / 4)
+                } else {
+                    count += 1;
+                    Rgb888::RED
+                }
+            })
+            .collect();
+        (
+            count,
+            Image {
+                size: self.size,
+                pixels,
+            },
+        )
+    }
+}
+
+/// Renders `f` on a freshly cleared [SimulatorDisplay] of the given size.
+pub fn render<F>(size: Size, style: Style<Rgb565>, f: F) -> SimulatorDisplay<Rgb565>
+where
+    F: FnOnce(&mut Ui<SimulatorDisplay<Rgb565>, Rgb565>),
+{
+    let mut display = SimulatorDisplay::new(size);
+    let mut ui = Ui::new_fullscreen(&mut display, style);
+    ui.clear_background().unwrap();
+    f(&mut ui);
+    display
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.ppm"))
+}
+
+fn dump_dir() -> PathBuf {
+    Path::new(env!("CARGO_TARGET_TMPDIR")).join("snapshots")
+}
+
+/// Compares `display` against the golden image `name`, allowing up to `max_diff_pixels`
+/// differing pixels.
+///
+/// See the [module documentation](self) for how golden images are created and updated.
+///
+/// # Panics
+///
+/// If the images differ in more than `max_diff_pixels` pixels, or the golden image is missing
+/// or invalid.
+pub fn assert_snapshot(name: &str, display: &SimulatorDisplay<Rgb565>, max_diff_pixels: usize) {
+    let actual = Image::from_display(display);
+    let golden = golden_path(name);
+
+    if std::env::var_os("KOLIBRI_BLESS").is_some() {
+        fs::create_dir_all(golden.parent().unwrap()).unwrap();
+        fs::write(&golden, actual.to_ppm()).unwrap();
+        return;
+    }
+    if !golden.exists() {
+        panic!(
+            "golden image {} is missing, run the test with KOLIBRI_BLESS=1 to create it",
+            golden.display()
+        );
+    }
+
+    let expected = Image::from_ppm(&fs::read(&golden).unwrap())
+        .unwrap_or_else(|| panic!("{} isn't a valid golden image", golden.display()));
+    let (count, diff) = actual.diff(&expected);
+    if count > max_diff_pixels {
+        let dir = dump_dir();
+        fs::create_dir_all(&dir).unwrap();
+        let actual_path = dir.join(format!("{name}.actual.ppm"));
+        let diff_path = dir.join(format!("{name}.diff.ppm"));
+        fs::write(&actual_path, actual.to_ppm()).unwrap();
+        fs::write(&diff_path, diff.to_ppm()).unwrap();
+        panic!(
+            "snapshot '{name}' differs in {count} pixels (max {max_diff_pixels}), see {} and {}",
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
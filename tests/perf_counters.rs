@@ -0,0 +1,30 @@
+//! Checks the performance counters of the benchmark scenes, so that redraw regressions (like an
+//! unchanged frame drawing pixels) fail `cargo test`, not only show up in `cargo bench`.
+
+#[path = "../benches/scenes/mod.rs"]
+mod scenes;
+
+use scenes::{Framebuffer, LabelScene, LABELS};
+
+#[test]
+fn only_changed_labels_are_drawn() {
+    let mut display = Framebuffer::new();
+    let mut scene = LabelScene::new();
+
+    let first = scene.frame(&mut display, true);
+    assert_eq!(first.widgets_added, LABELS as u32);
+    assert_eq!(first.widgets_redrawn, LABELS as u32);
+    assert!(first.pixels_drawn > 0);
+
+    let unchanged = scene.frame(&mut display, false);
+    assert_eq!(unchanged.widgets_added, LABELS as u32);
+    assert_eq!(unchanged.widgets_redrawn, 0);
+    assert_eq!(unchanged.pixels_drawn, 0);
+    assert_eq!(unchanged.dirty_area, None);
+
+    scene.change_tenth();
+    let changed = scene.frame(&mut display, false);
+    assert_eq!(changed.widgets_redrawn, LABELS as u32 / 10);
+    assert!(changed.pixels_drawn > 0);
+    assert!(changed.pixels_drawn < first.pixels_drawn / 5);
+}
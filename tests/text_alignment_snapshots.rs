@@ -0,0 +1,59 @@
+//! Golden image tests for the vertical alignment of text in a row of different widgets, see
+//! [kolibri_test].
+
+mod kolibri_test;
+
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics_simulator::SimulatorDisplay;
+use kolibri_embedded_gui::button::Button;
+use kolibri_embedded_gui::label::Label;
+use kolibri_embedded_gui::style::{medsize_light_rgb565_style, medsize_rgb565_style, Style};
+use kolibri_test::{assert_snapshot, render};
+
+/// Returns the pixels of `color` in `area`, with their x relative to the leftmost one
+fn glyphs(display: &SimulatorDisplay<Rgb565>, area: Rectangle, color: Rgb565) -> Vec<Point> {
+    let pixels: Vec<Point> = area
+        .points()
+        .filter(|&p| display.get_pixel(p) == color)
+        .collect();
+    let left = pixels.iter().map(|p| p.x).min().unwrap_or(0);
+    let mut pixels: Vec<Point> = pixels
+        .into_iter()
+        .map(|p| Point::new(p.x - left, p.y))
+        .collect();
+    pixels.sort_by_key(|p| (p.y, p.x));
+    pixels
+}
+
+fn snapshot(name: &str, style: Style<Rgb565>) {
+    let mut areas = (Rectangle::zero(), Rectangle::zero());
+    let display = render(Size::new(128, 40), style, |ui| {
+        let button = ui.add_horizontal(Button::new("Save")).internal.area;
+        let label = ui.add_horizontal(Label::new("Save")).internal.area;
+        areas = (button, label);
+    });
+    assert_snapshot(&format!("text_alignment_{name}"), &display, 0);
+
+    // the same text is drawn on the same pixel rows (inside the button's border)
+    let (button, label) = areas;
+    let inside = style
+        .without_shadow(button)
+        .offset(-(style.border_width as i32 + 1));
+    let in_button = glyphs(&display, inside, style.text_color);
+    assert!(!in_button.is_empty());
+    assert_eq!(in_button, glyphs(&display, label, style.text_color));
+}
+
+#[test]
+fn label_and_button_text_line_up() {
+    snapshot("dark", medsize_rgb565_style());
+    snapshot("light", medsize_light_rgb565_style());
+
+    // the label centers its text like the button, ignoring the button's shadow
+    let mut style = medsize_rgb565_style();
+    style.shadow_color = Some(Rgb565::new(4, 8, 4));
+    style.shadow_offset = Size::new(3, 3);
+    snapshot("shadow", style);
+}